@@ -0,0 +1,58 @@
+//! Compares how long a large dictionary file takes to load with and without its cache (see `bananagrams_core::dictionary_cache`), on
+//! synthetic random words, run with `cargo bench --bench dictionary_cache`
+use std::{collections::HashSet, fs, path::Path, time::{Duration, Instant}};
+use rand::prelude::*;
+use bananagrams_core::{seeded_rng, DEFAULT_MIN_WORD_LENGTH};
+use bananagrams_core::dictionary_cache::{cache_path, load_preprocessed_dictionary};
+
+/// Number of synthetic words in the dictionary (about as many as in a full Scrabble dictionary)
+const WORDS: usize = 170_000;
+/// Number of times each way is timed (the fastest time is reported)
+const ITERATIONS: usize = 5;
+
+/// Times loading the dictionary, keeping the fastest of `ITERATIONS` runs
+/// # Arguments
+/// * `path` - Path to the dictionary
+/// * `before` - Run before each load (and not timed)
+/// # Returns
+/// * `Duration` - The fastest time
+fn time(path: &Path, before: impl Fn()) -> Duration {
+    (0..ITERATIONS).map(|_| {
+        before();
+        let start = Instant::now();
+        let dictionary = load_preprocessed_dictionary(path, false, DEFAULT_MIN_WORD_LENGTH).expect("Failed to load the dictionary!");
+        let elapsed = start.elapsed();
+        assert_eq!(dictionary.words.len(), WORDS);
+        elapsed
+    }).min().unwrap_or_default()
+}
+
+fn main() {
+    let mut rng = seeded_rng(Some(0));
+    // Repeated words are removed when the dictionary is loaded, so only distinct ones are kept
+    let mut words: HashSet<String> = HashSet::new();
+    while words.len() < WORDS {
+        let length = rng.gen_range(DEFAULT_MIN_WORD_LENGTH..=15);
+        words.insert((0..length).map(|_| rng.gen_range(b'a'..=b'z') as char).collect());
+    }
+    let dictionary: String = words.into_iter().map(|word| word + "\n").collect();
+    let path = std::env::temp_dir().join(format!("bananagrams_dictionary_cache_bench_{}.txt", std::process::id()));
+    let cache = cache_path(&path);
+    fs::write(&path, dictionary).expect("Failed to write the dictionary!");
+    let uncached = time(&path, || { let _ = fs::remove_file(&cache); });
+    let cached = time(&path, || ());
+    // A corrupt cache is regenerated, so loading it takes about as long as not having one
+    let corrupt = time(&path, || {
+        let mut bytes = fs::read(&cache).expect("Failed to read the cache!");
+        let middle = bytes.len() / 2;
+        bytes[middle] ^= 0xff;
+        fs::write(&cache, bytes).expect("Failed to write the cache!");
+    });
+    println!("{:<10} {:>12}", "load", "time (ms)");
+    for (name, elapsed) in [("uncached", uncached), ("cached", cached), ("corrupt", corrupt)] {
+        println!("{:<10} {:>12.1}", name, elapsed.as_secs_f64() * 1e3);
+    }
+    println!("cache file {:.1} MB, loads {:.2}x as fast", fs::metadata(&cache).map(|metadata| metadata.len()).unwrap_or_default() as f64 / 1e6, uncached.as_secs_f64() / cached.as_secs_f64());
+    let _ = fs::remove_file(&cache);
+    let _ = fs::remove_file(&path);
+}
@@ -0,0 +1,50 @@
+//! Compares finding the words that contain a letter by scanning the dictionary and by looking them up in a `DictionaryIndex`, on synthetic
+//! random words, run with `cargo bench --bench dictionary_index`
+use std::{collections::HashSet, hint::black_box, time::{Duration, Instant}};
+use rand::prelude::*;
+use bananagrams_core::{seeded_rng, DictionaryIndex, Word, DEFAULT_MIN_WORD_LENGTH, ENGLISH_ALPHABET_SIZE};
+
+/// Number of synthetic words in the dictionary
+const WORDS: usize = 50_000;
+/// Number of times each way is timed (the fastest time is reported)
+const ITERATIONS: usize = 20;
+
+/// Times a way of finding the words containing each letter of the alphabet in turn, keeping the fastest of `ITERATIONS` runs
+/// # Arguments
+/// * `find` - Finds the indices of the words containing a letter, then picks one of them as `generate_board` does
+/// # Returns
+/// * `Duration` - The fastest time
+fn time(mut find: impl FnMut(u8) -> Option<usize>) -> Duration {
+    (0..ITERATIONS).map(|_| {
+        let start = Instant::now();
+        for letter in 0..ENGLISH_ALPHABET_SIZE as u8 {
+            black_box(find(letter));
+        }
+        start.elapsed()
+    }).min().unwrap_or_default()
+}
+
+fn main() {
+    let mut rng = seeded_rng(Some(0));
+    let mut words: HashSet<Word> = HashSet::new();
+    while words.len() < WORDS {
+        let length = rng.gen_range(DEFAULT_MIN_WORD_LENGTH..=15);
+        words.insert((0..length).map(|_| rng.gen_range(0..ENGLISH_ALPHABET_SIZE as u8)).collect());
+    }
+    let dictionary: Vec<Word> = words.into_iter().collect();
+    let start = Instant::now();
+    let index = DictionaryIndex::new(&dictionary);
+    let build = start.elapsed();
+    let mut pick_rng = seeded_rng(Some(1));
+    let scan = time(|letter| {
+        let candidates: Vec<usize> = dictionary.iter().enumerate().filter(|(_, word)| word.contains(&letter)).map(|(i, _)| i).collect();
+        candidates.choose(&mut pick_rng).copied()
+    });
+    let lookup = time(|letter| index.words_containing(letter as usize).choose(&mut pick_rng).copied());
+    // Times are for all 26 letters, except for building the index, which is done once
+    println!("{:<10} {:>12}", "lookup", "time (us)");
+    for (name, elapsed) in [("scan", scan), ("index", lookup), ("build", build)] {
+        println!("{:<10} {:>12.1}", name, elapsed.as_secs_f64() * 1e6);
+    }
+    println!("{} words, looking up every letter {:.0}x as fast with the index as by scanning", WORDS, scan.as_secs_f64() / lookup.as_secs_f64());
+}
@@ -0,0 +1,75 @@
+//! Compares how fast JSON Lines and MessagePack board files (see `bananagrams_core::msgpack`) are parsed, on synthetic records shaped like
+//! those written by `training_data`, run with `cargo bench --bench msgpack`
+use std::time::{Duration, Instant};
+use rand::prelude::*;
+use bananagrams_core::{msgpack, seeded_rng};
+
+/// Number of synthetic records parsed
+const RECORDS: usize = 100_000;
+/// Number of times each format is parsed (the fastest time is reported)
+const ITERATIONS: usize = 5;
+
+/// Makes a record with the fields `training_data` writes for a solved board, with random values
+/// # Arguments
+/// * `rng` - Random number generator
+/// # Returns
+/// * `serde_json::Value` - The record
+fn synthetic_record(rng: &mut StdRng) -> serde_json::Value {
+    let hand: Vec<u8> = (0..26).map(|_| rng.gen_range(0..4)).collect();
+    let (width, height) = (rng.gen_range(5..15), rng.gen_range(5..15));
+    let board: Vec<String> = (0..height).map(|_| (0..width).map(|_| if rng.gen_bool(0.4) { rng.gen_range(b'A'..=b'Z') as char } else { ' ' }).collect()).collect();
+    serde_json::json!({
+        "hand": hand,
+        "blanks": 0,
+        "board": board,
+        "words_checked": rng.gen_range(0..1_000_000u64),
+        "depth": rng.gen_range(0..30),
+        "failed_first_words": rng.gen_range(0..10),
+        "elapsed_ms": rng.gen_range(0..5_000),
+        "thread": rng.gen_range(0..16),
+        "seed": rng.gen::<u64>(),
+        "partial": false,
+        "unused": ""
+    })
+}
+
+/// Converts a JSON value to the MessagePack value with the same contents
+fn to_msgpack(value: &serde_json::Value) -> msgpack::Value {
+    match value {
+        serde_json::Value::Null => msgpack::Value::Nil,
+        serde_json::Value::Bool(value) => msgpack::Value::Bool(*value),
+        serde_json::Value::Number(number) => number.as_u64().map(msgpack::Value::UInt).unwrap_or_else(|| msgpack::Value::Float(number.as_f64().unwrap_or_default())),
+        serde_json::Value::String(value) => msgpack::Value::String(value.clone()),
+        serde_json::Value::Array(values) => msgpack::Value::Array(values.iter().map(to_msgpack).collect()),
+        serde_json::Value::Object(entries) => msgpack::Value::Map(entries.iter().map(|(key, value)| (msgpack::Value::String(key.clone()), to_msgpack(value))).collect())
+    }
+}
+
+/// Times parsing a file, keeping the fastest of `ITERATIONS` runs
+/// # Arguments
+/// * `parse` - Parses the file, returning the number of records
+/// # Returns
+/// * `Duration` - The fastest time
+fn time(parse: impl Fn() -> usize) -> Duration {
+    (0..ITERATIONS).map(|_| {
+        let start = Instant::now();
+        assert_eq!(parse(), RECORDS);
+        start.elapsed()
+    }).min().unwrap_or_default()
+}
+
+fn main() {
+    let mut rng = seeded_rng(Some(0));
+    let records: Vec<serde_json::Value> = (0..RECORDS).map(|_| synthetic_record(&mut rng)).collect();
+    let jsonl: Vec<u8> = records.iter().flat_map(|record| format!("{}\n", record).into_bytes()).collect();
+    let msgpack_file: Vec<u8> = records.iter().flat_map(|record| msgpack::encode_record(&to_msgpack(record))).collect();
+    // Both keep every record, as reading a file does
+    let jsonl_time = time(|| jsonl.split(|&byte| byte == b'\n').filter(|line| !line.is_empty()).map(|line| serde_json::from_slice::<serde_json::Value>(line).expect("Invalid JSON!")).collect::<Vec<_>>().len());
+    let msgpack_time = time(|| msgpack::read_records(&msgpack_file).expect("Invalid MessagePack!").len());
+    println!("{:<8} {:>10} {:>12} {:>14} {:>10}", "format", "size (MB)", "time (ms)", "records/s", "MB/s");
+    for (name, size, elapsed) in [("jsonl", jsonl.len(), jsonl_time), ("msgpack", msgpack_file.len(), msgpack_time)] {
+        let seconds = elapsed.as_secs_f64();
+        println!("{:<8} {:>10.1} {:>12.1} {:>14.0} {:>10.1}", name, size as f64 / 1e6, seconds * 1e3, RECORDS as f64 / seconds, size as f64 / 1e6 / seconds);
+    }
+    println!("msgpack parses {:.2}x as fast", jsonl_time.as_secs_f64() / msgpack_time.as_secs_f64());
+}
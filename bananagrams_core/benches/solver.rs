@@ -0,0 +1,16 @@
+//! Benchmarks of the solver on a fixed suite of hands (see `bananagrams_core::bench`), run with `cargo bench --features bench --bench solver`
+use std::process;
+use bananagrams_core::bench::{format_table, run_benchmarks};
+
+/// Number of times each benchmark is run
+const ITERATIONS: usize = 10;
+
+fn main() {
+    match run_benchmarks(ITERATIONS) {
+        Ok(results) => print!("{}", format_table(&results)),
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    }
+}
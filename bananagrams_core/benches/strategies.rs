@@ -0,0 +1,68 @@
+//! Compares the solve rate and words checked of each `FirstWordStrategy` on the same seeded hands, and of each `SearchHeuristic` on the
+//! same seeded large hands, solved with the benchmark dictionary (see `bananagrams_core::bench`), run with
+//! `cargo bench --features bench --bench strategies`
+use std::time::{Duration, Instant};
+use bananagrams_core::{bench::BENCH_DICTIONARY, generate_hand, parse_dictionary, play_bananagrams, seeded_rng, FirstWordStrategy, HandSizeDistribution, Letters, SearchHeuristic, SolveOptions, TileSet, Word};
+
+/// Number of hands each strategy solves
+const HANDS: usize = 100;
+/// Number of large hands each heuristic solves
+const LARGE_HANDS: usize = 50;
+/// Words each hand may check before it's given up on (lower than `MAXIMUM_WORDS_CHECKED`, so that the hands that are hard for a strategy
+/// show up in its solve rate rather than taking minutes)
+const MAX_WORDS_CHECKED: usize = 100_000;
+
+/// How a strategy did over every hand
+struct Outcome {
+    /// Number of hands solved
+    solved: usize,
+    /// Words checked over every hand, solved or not
+    words_checked: usize,
+    /// Time taken over every hand
+    elapsed: Duration
+}
+
+/// Solves every hand with a strategy and heuristic
+/// # Arguments
+/// * `hands` - The hands
+/// * `dictionary` - The words to solve with
+/// * `first_word` - How to order the first words tried
+/// * `heuristic` - How to order the words tried after the first
+/// # Returns
+/// * `Outcome` - How the strategy and heuristic did
+fn solve_all(hands: &[Letters], dictionary: &Vec<Word>, first_word: FirstWordStrategy, heuristic: SearchHeuristic) -> Outcome {
+    let mut outcome = Outcome { solved: 0, words_checked: 0, elapsed: Duration::ZERO };
+    let start = Instant::now();
+    for &letters in hands {
+        match play_bananagrams(letters, 0, dictionary, &SolveOptions { max_words_checked: MAX_WORDS_CHECKED, first_word, heuristic, ..SolveOptions::default() }) {
+            Ok(solution) => {
+                outcome.solved += 1;
+                outcome.words_checked += solution.metrics.words_checked;
+            },
+            Err((_, words_checked, _)) => outcome.words_checked += words_checked
+        }
+    }
+    outcome.elapsed = start.elapsed();
+    outcome
+}
+
+fn main() {
+    let dictionary = parse_dictionary(BENCH_DICTIONARY, false).expect("Failed to parse the dictionary!");
+    let mut rng = seeded_rng(Some(0));
+    let tiles = TileSet::standard();
+    // From the smallest hands up to those of a two-player game, with smaller ones more likely, as `training_data` generates by default
+    let hands: Vec<Letters> = (0..HANDS).map(|_| generate_hand(&mut rng, &tiles, HandSizeDistribution::LogScaled(11, 40), 0).0).collect();
+    println!("{:<16} {:>8} {:>22} {:>10}", "first word", "solved", "mean words checked", "time (s)");
+    for first_word in [FirstWordStrategy::Longest, FirstWordStrategy::Random(0), FirstWordStrategy::MostVowels, FirstWordStrategy::RarestLetters] {
+        let outcome = solve_all(&hands, &dictionary, first_word, SearchHeuristic::Dictionary);
+        println!("{:<16} {:>7.1}% {:>22.0} {:>10.2}", first_word.name(), 100.0 * outcome.solved as f64 / HANDS as f64, outcome.words_checked as f64 / HANDS as f64, outcome.elapsed.as_secs_f64());
+    }
+    // Hard-to-place letters are most often left until last in large hands, which is where the heuristic is meant to help
+    let large_hands: Vec<Letters> = (0..LARGE_HANDS).map(|_| generate_hand(&mut rng, &tiles, HandSizeDistribution::Uniform(30, 50), 0).0).collect();
+    println!();
+    println!("{:<16} {:>8} {:>22} {:>10}", "heuristic", "solved", "mean words checked", "time (s)");
+    for heuristic in [SearchHeuristic::Dictionary, SearchHeuristic::RareFirst] {
+        let outcome = solve_all(&large_hands, &dictionary, FirstWordStrategy::Longest, heuristic);
+        println!("{:<16} {:>7.1}% {:>22.0} {:>10.2}", format!("{:?}", heuristic), 100.0 * outcome.solved as f64 / LARGE_HANDS as f64, outcome.words_checked as f64 / LARGE_HANDS as f64, outcome.elapsed.as_secs_f64());
+    }
+}
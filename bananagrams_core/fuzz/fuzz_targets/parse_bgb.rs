@@ -0,0 +1,26 @@
+//! Feeds arbitrary bytes to every reader of `.bgb` and `.bgb2` files, which must report invalid files as errors rather than panicking
+//! (the corpus starts from real generated files: boards of both `.bgb` versions, labeled negatives, and `.bgb2`)
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use bananagrams_core::{ENGLISH_ALPHABET_SIZE, board_to_bytes, complete_bgb_records, index_bgb2, parse_bgb, parse_bgb_metrics, parse_labeled_bgb, read_bgb2, read_boards};
+
+fuzz_target!(|bytes: &[u8]| {
+    // Every error must say what's wrong
+    let check = |result: Result<(), String>| if let Err(message) = result {
+        assert!(!message.is_empty(), "Empty error message");
+    };
+    check(parse_bgb::<ENGLISH_ALPHABET_SIZE>(bytes).map(|boards| {
+        // Any board that parses must be writable again
+        for (letters, board, _, _, _, _) in boards.iter() {
+            board_to_bytes(letters, board).expect("Parsed board can't be written!");
+        }
+    }));
+    check(parse_bgb_metrics::<ENGLISH_ALPHABET_SIZE>(bytes).map(|_| ()));
+    check(parse_labeled_bgb(bytes).map(|_| ()));
+    check(complete_bgb_records(bytes).map(|ends| {
+        assert!(ends.windows(2).all(|pair| pair[0] < pair[1]) && ends.last().is_none_or(|&end| end <= bytes.len()), "Invalid record ends {:?}", ends);
+    }));
+    check(read_boards(bytes).map(|_| ()));
+    check(read_bgb2(bytes).map(|_| ()));
+    check(index_bgb2(bytes).map(|_| ()));
+});
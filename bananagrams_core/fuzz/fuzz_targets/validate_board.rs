@@ -0,0 +1,49 @@
+//! Feeds arbitrary small grids to `validate_board` and `extract_words`, which must report what's wrong with the board rather than
+//! panicking, even for letters outside A-Z (as in a board decoded from a generator's output)
+//!
+//! The first byte gives the side of the grid (1 plus the byte modulo `MAX_SIDE`), and each following byte a cell, row by row: `EMPTY_VALUE`
+//! for an empty cell, otherwise a letter with `BGB_BLANK_FLAG` set if it was played with a blank (missing cells are empty)
+#![no_main]
+use std::sync::OnceLock;
+use hashbrown::HashSet;
+use libfuzzer_sys::fuzz_target;
+use bananagrams_core::{Board, BGB_BLANK_FLAG, DEFAULT_MIN_WORD_LENGTH, EMPTY_VALUE, Word, board_rows, extract_words, parse_dictionary, validate_board};
+
+/// Largest side of a grid
+const MAX_SIDE: usize = 16;
+
+/// Gets the words the boards are checked against
+fn valid_words() -> &'static HashSet<Word> {
+    static VALID_WORDS: OnceLock<HashSet<Word>> = OnceLock::new();
+    VALID_WORDS.get_or_init(|| parse_dictionary(include_str!("../../../new_short_dictionary.txt"), false).expect("Invalid dictionary!").into_iter().collect())
+}
+
+fuzz_target!(|bytes: &[u8]| {
+    let (&side, cells) = match bytes.split_first() {
+        Some(split) => split,
+        None => return
+    };
+    let side = 1 + side as usize % MAX_SIDE;
+    let mut board = Board::new(side);
+    for (index, &cell) in cells.iter().take(side * side).enumerate() {
+        if cell != EMPTY_VALUE {
+            let (row, col) = (index / side, index % side);
+            board.set_val(row, col, cell & !BGB_BLANK_FLAG);
+            if cell & BGB_BLANK_FLAG != 0 {
+                board.set_blank(row, col);
+            }
+        }
+    }
+    if let Err(errors) = validate_board(&board, valid_words(), DEFAULT_MIN_WORD_LENGTH) {
+        assert!(!errors.is_empty(), "Invalid board without errors");
+        for error in errors.iter() {
+            assert!(!error.to_string().is_empty(), "Empty error message");
+        }
+    }
+    if let Some((min_col, max_col, min_row, max_row)) = board.bounds() {
+        for (word, row, col, _) in extract_words(&board, min_col, max_col, min_row, max_row) {
+            assert!(word.len() >= 2 && row < side && col < side, "Invalid word {} at row {}, column {}", word, row, col);
+        }
+    }
+    board_rows(&board);
+});
@@ -0,0 +1,205 @@
+//! A fixed suite of benchmarks of the solver, run by `cargo bench --features bench` and by the `bench` subcommand of `training_data`, so
+//! that changes to its performance can be measured (behind the `bench` feature, so that other crates don't build in its dictionary)
+use std::{hint::black_box, time::{Duration, Instant}};
+use hashbrown::HashSet;
+use rand::{seq::SliceRandom, Rng};
+use crate::{Board, Letters, SolveOptions, TileSet, Word, BOARD_SIZE, DEFAULT_MIN_WORD_LENGTH, ENGLISH_ALPHABET_SIZE,
+    EMPTY_VALUE, check_filter_after_play_later, is_board_valid_horizontal, letter_masks, makeable_words, parse_dictionary, play_bananagrams,
+    seeded_rng};
+
+/// Dictionary the benchmarks solve with: every third word of `new_short_dictionary.txt`, kept as its own file so that changes to that
+/// dictionary don't change the benchmarks
+pub const BENCH_DICTIONARY: &str = include_str!("../benches/dictionary.txt");
+/// Maximum number of words the solver checks for each hand (fewer than `MAXIMUM_WORDS_CHECKED`, so that the unsolvable hand gives up
+/// quickly)
+pub const BENCH_MAX_WORDS_CHECKED: usize = 50_000;
+
+/// A hand the benchmarks solve
+pub struct BenchHand {
+    /// Name of the benchmark
+    pub name: &'static str,
+    /// The letters of the hand
+    pub letters: &'static str,
+    /// Whether the solver finds a solution within `BENCH_MAX_WORDS_CHECKED` words (checked before timing, so that a change that breaks the
+    /// solver isn't mistaken for one that speeds it up)
+    pub solvable: bool
+}
+
+/// The hands the benchmarks solve, from easy to hard
+pub const BENCH_HANDS: [BenchHand; 4] = [
+    BenchHand { name: "easy (11 tiles)", letters: "AEINRSTLODG", solvable: true },
+    BenchHand { name: "medium (21 tiles)", letters: "AEEIIORSTNLDGHMPCUABY", solvable: true },
+    BenchHand { name: "hard (62 tiles)", letters: "EEEEEEEEAAAAAIIIIIOOOOUURRRRRSSSSSTTTTTNNNNNLLLDDDGGHMPCBYWFKX", solvable: true },
+    BenchHand { name: "hard, rare letters (59 tiles)", letters: "EEEEEEAAAAAIIIIOOOOUURRRSSSTTTTNNNLLLDDDGGHMMPCCSBYWFKVXJQZ", solvable: false }
+];
+
+/// Number of rows/columns of the smaller board the medium hand is also solved on (a third of `BOARD_SIZE`, still far more than a hand of
+/// its size spreads across)
+const SMALL_BOARD_SIZE: usize = 48;
+
+/// Number of words in the synthetic dictionary the letter mask pre-filter is timed with (about the size of a full English word list, eight
+/// times the size of `BENCH_DICTIONARY`)
+const SYNTHETIC_DICTIONARY_SIZE: usize = 50_000;
+
+/// Timing of one benchmark
+pub struct BenchResult {
+    /// Name of the benchmark
+    pub name: String,
+    /// Number of times it was run
+    pub iterations: usize,
+    /// Mean time taken per run
+    pub mean: Duration,
+    /// Shortest time taken by a run
+    pub min: Duration,
+    /// Longest time taken by a run
+    pub max: Duration
+}
+
+/// Times a benchmark
+/// # Arguments
+/// * `name` - Name of the benchmark
+/// * `iterations` - Number of times to run it
+/// * `run` - Runs the benchmark once
+/// # Returns
+/// * `BenchResult` - How long it took
+fn time(name: String, iterations: usize, mut run: impl FnMut()) -> BenchResult {
+    let mut times: Vec<Duration> = Vec::with_capacity(iterations);
+    for _ in 0..iterations {
+        let start = Instant::now();
+        run();
+        times.push(start.elapsed());
+    }
+    BenchResult {
+        name,
+        iterations,
+        mean: times.iter().sum::<Duration>() / iterations.max(1) as u32,
+        min: times.iter().min().copied().unwrap_or_default(),
+        max: times.iter().max().copied().unwrap_or_default()
+    }
+}
+
+/// Converts a hand of the suite to the number of each letter
+/// # Arguments
+/// * `letters` - The letters of the hand (A-Z)
+/// # Returns
+/// * `Letters` - The number of each letter
+fn hand_letters(letters: &str) -> Letters {
+    let mut counts: Letters = [0; ENGLISH_ALPHABET_SIZE];
+    for letter in letters.bytes() {
+        counts[(letter - b'A') as usize] += 1;
+    }
+    counts
+}
+
+/// Solves a hand with the benchmarks' options
+/// # Arguments
+/// * `letters` - Number of each letter in the hand
+/// * `dictionary` - The words to solve with
+/// * `board_size` - Number of rows/columns in the board
+/// # Returns
+/// * `Option` with the board and the minimum column, maximum column, minimum row, and maximum row of its letters, or `None` if the hand
+///   wasn't solved
+fn solve(letters: Letters, dictionary: &Vec<Word>, board_size: usize) -> Option<(Board, usize, usize, usize, usize)> {
+    play_bananagrams(letters, 0, dictionary, &SolveOptions { max_words_checked: BENCH_MAX_WORDS_CHECKED, board_size, ..SolveOptions::default() })
+        .ok().map(|solution| (solution.board, solution.min_col, solution.max_col, solution.min_row, solution.max_row))
+}
+
+/// Makes a dictionary of random words, with letters as common as they are among the tiles of the standard set, so that it's as large as
+/// needed and the same on every run
+/// # Arguments
+/// * `size` - Number of words
+/// # Returns
+/// * `Vec<Word>` - The words, from 2 to 12 letters long
+fn synthetic_dictionary(size: usize) -> Vec<Word> {
+    let mut rng = seeded_rng(Some(0));
+    let tiles = TileSet::standard().tiles;
+    (0..size).map(|_| {
+        let length = rng.gen_range(2..=12);
+        (0..length).map(|_| *tiles.choose(&mut rng).expect("The standard tile set is empty!")).collect()
+    }).collect()
+}
+
+/// Runs every benchmark: solving each of `BENCH_HANDS` end to end (and the medium hand on a smaller board as well), then the work the
+/// solver does most often in isolation (building the set of words a hand can make with and without letter masks, copying a board,
+/// validating each row of a solved board, and filtering the dictionary by the letters left in a hand)
+/// # Arguments
+/// * `iterations` - Number of times to run each benchmark
+/// # Returns
+/// * `Result` with the timing of each benchmark, or an error message if a hand wasn't solved (or was) when expected
+pub fn run_benchmarks(iterations: usize) -> Result<Vec<BenchResult>, String> {
+    let dictionary = parse_dictionary(BENCH_DICTIONARY, false).map_err(|e| e.to_string())?;
+    let mut results: Vec<BenchResult> = Vec::new();
+    for hand in BENCH_HANDS.iter() {
+        let letters = hand_letters(hand.letters);
+        if solve(letters, &dictionary, BOARD_SIZE).is_some() != hand.solvable {
+            return Err(format!("Expected the {} hand {} to be {}", hand.name, hand.letters, if hand.solvable { "solvable" } else { "unsolvable" }));
+        }
+        results.push(time(format!("play_bananagrams: {}", hand.name), iterations, || {
+            black_box(solve(letters, &dictionary, BOARD_SIZE));
+        }));
+    }
+    // The solver's time is mostly spent around the tiles, so a smaller board should only save the cost of clearing and copying the rest
+    let medium = hand_letters(BENCH_HANDS[1].letters);
+    for board_size in [SMALL_BOARD_SIZE, BOARD_SIZE] {
+        if solve(medium, &dictionary, board_size).is_none() {
+            return Err(format!("Expected the {} hand {} to be solvable on a {}x{} board", BENCH_HANDS[1].name, BENCH_HANDS[1].letters, board_size, board_size));
+        }
+        results.push(time(format!("play_bananagrams: {}, {}x{} board", BENCH_HANDS[1].name, board_size, board_size), iterations, || {
+            black_box(solve(medium, &dictionary, board_size));
+        }));
+    }
+    // The solver builds this set once per hand (it was once rebuilt for every first word tried, which doubled the time to give up on the
+    // rare letters hand)
+    let medium_words = makeable_words(&dictionary, None, &medium, 0, DEFAULT_MIN_WORD_LENGTH, None);
+    results.push(time(format!("valid word set: {} words of the medium hand", medium_words.len()), iterations, || {
+        black_box(medium_words.iter().cloned().collect::<HashSet<Word>>());
+    }));
+    // The letter masks rule out most words of a large dictionary with a single AND, leaving only the rest to have their letters counted
+    let synthetic = synthetic_dictionary(SYNTHETIC_DICTIONARY_SIZE);
+    let synthetic_masks = letter_masks(&synthetic);
+    for (masks, name) in [(None, "without"), (Some(synthetic_masks.as_slice()), "with")] {
+        results.push(time(format!("makeable_words: {} words, medium hand, {} letter masks", SYNTHETIC_DICTIONARY_SIZE, name), iterations, || {
+            black_box(makeable_words(&synthetic, masks, &medium, 0, DEFAULT_MIN_WORD_LENGTH, None));
+        }));
+    }
+    // The medium hand's board is representative of those checked while solving
+    let (board, min_col, max_col, min_row, max_row) = solve(medium, &dictionary, BOARD_SIZE).expect("Medium hand not solved!");
+    // Copying a whole board is bound by its size in memory (a byte per cell)
+    results.push(time(format!("Board::clone: {}x{} board", board.size(), board.size()), iterations, || {
+        black_box(board.clone());
+    }));
+    let valid_words: HashSet<Word> = dictionary.iter().cloned().collect();
+    results.push(time("is_board_valid_horizontal: every row of a board".to_string(), iterations, || {
+        for row in min_row..max_row+1 {
+            black_box(is_board_valid_horizontal(&board, min_col, max_col, min_row, max_row, row, min_col, max_col, &valid_words, DEFAULT_MIN_WORD_LENGTH));
+        }
+    }));
+    let mut board_letters: Letters = [0; ENGLISH_ALPHABET_SIZE];
+    for row in min_row..max_row+1 {
+        for col in min_col..max_col+1 {
+            let val = board.get_val(row, col);
+            if val != EMPTY_VALUE {
+                board_letters[val as usize] += 1;
+            }
+        }
+    }
+    let hand = hand_letters(BENCH_HANDS[2].letters);
+    results.push(time("check_filter_after_play_later: the whole dictionary".to_string(), iterations, || {
+        black_box(dictionary.iter().filter(|word| check_filter_after_play_later(hand, board_letters, 0, word)).count());
+    }));
+    Ok(results)
+}
+
+/// Formats the timing of the benchmarks as a table
+/// # Arguments
+/// * `results` - The timing of each benchmark (see `run_benchmarks`)
+/// # Returns
+/// * `String` - The table, with a row for each benchmark giving its mean, shortest, and longest times
+pub fn format_table(results: &[BenchResult]) -> String {
+    let width = results.iter().map(|result| result.name.len()).max().unwrap_or(0).max("Benchmark".len());
+    let mut table = format!("{:<width$} {:>10} {:>12} {:>12} {:>12}\n", "Benchmark", "Iterations", "Mean", "Min", "Max", width = width);
+    for result in results {
+        table.push_str(&format!("{:<width$} {:>10} {:>12.3?} {:>12.3?} {:>12.3?}\n", result.name, result.iterations, result.mean, result.min, result.max, width = width));
+    }
+    table
+}
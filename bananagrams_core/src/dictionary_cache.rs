@@ -0,0 +1,202 @@
+//! A cache of a preprocessed dictionary file, kept in a binary file next to it so that short-lived processes (such as Python workers)
+//! don't convert, sort, and index a large dictionary every time they start
+use std::{fs, path::{Path, PathBuf}};
+use log::debug;
+use crate::{DictionaryError, DictionaryIndex, LetterMask, Word, MAX_WORD_LENGTH, letter_masks, parse_weighted_dictionary, remove_short_words};
+
+/// Bytes a cache file starts with
+const CACHE_MAGIC: &[u8; 4] = b"BGDC";
+/// Version of the cache file format, raised whenever it changes so that older caches are regenerated
+pub const CACHE_FORMAT_VERSION: u8 = 1;
+/// Extension added to the name of a dictionary file to name its cache
+pub const CACHE_EXTENSION: &str = "bgdcache";
+
+/// A dictionary along with everything the solver and board generator compute from it up front
+pub struct PreprocessedDictionary {
+    /// The words in numeric form, sorted longest first, without those shorter than the minimum word length
+    pub words: Vec<Word>,
+    /// The frequency of each word, if the dictionary gave any
+    pub frequencies: Option<Vec<f64>>,
+    /// The letters in each word (see `letter_masks`)
+    pub masks: Vec<LetterMask>,
+    /// Lookup from each letter to the words containing it
+    pub index: DictionaryIndex
+}
+
+/// Gets the path of the cache of a dictionary file
+/// # Arguments
+/// * `path` - Path to the dictionary
+/// # Returns
+/// * `PathBuf` - The path with `.bgdcache` added to it (so `words.txt` is cached in `words.txt.bgdcache`)
+pub fn cache_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".");
+    name.push(CACHE_EXTENSION);
+    PathBuf::from(name)
+}
+
+/// Loads a dictionary file and preprocesses it, reading the result from its cache if that was made from the same file (by the same
+/// version of this library, with the same options), and otherwise writing the cache for next time; a cache that's stale, corrupt, or
+/// can't be written is silently ignored
+/// # Arguments
+/// * `path` - Path to the dictionary, with one word per line, each optionally followed by its frequency
+/// * `strict` - Whether single-letter words are an error rather than a warning (the warning is only given when the cache is made)
+/// * `min_word_length` - Minimum length of a word to keep
+/// # Returns
+/// * `Result` with the preprocessed dictionary, or a `DictionaryError` if the file can't be read or is invalid (see
+///   `parse_weighted_dictionary`)
+pub fn load_preprocessed_dictionary(path: &Path, strict: bool, min_word_length: usize) -> Result<PreprocessedDictionary, DictionaryError> {
+    let bytes = fs::read(path).map_err(|e| DictionaryError::Read { path: path.to_path_buf(), message: e.to_string() })?;
+    let key = cache_key(&bytes, strict, min_word_length);
+    let cache = cache_path(path);
+    match fs::read(&cache).map_err(|e| e.to_string()).and_then(|cached| decode(&cached, &key)) {
+        Ok(dictionary) => return Ok(dictionary),
+        Err(e) => debug!("Not using the dictionary cache {}: {}", cache.display(), e)
+    }
+    let text = String::from_utf8(bytes).map_err(|e| DictionaryError::Read { path: path.to_path_buf(), message: e.to_string() })?;
+    let (mut words, mut frequencies) = parse_weighted_dictionary(&text, strict)?;
+    remove_short_words(&mut words, frequencies.as_mut(), min_word_length);
+    let dictionary = PreprocessedDictionary { masks: letter_masks(&words), index: DictionaryIndex::new(&words), words, frequencies };
+    // Written to another file first and then moved over the cache, so that a process starting at the same time never reads half of it
+    let temporary = cache.with_extension(format!("{}.{}", CACHE_EXTENSION, std::process::id()));
+    if let Err(e) = fs::write(&temporary, encode(&dictionary, &key)).and_then(|()| fs::rename(&temporary, &cache)) {
+        debug!("Failed to write the dictionary cache {}: {}", cache.display(), e);
+        let _ = fs::remove_file(&temporary);
+    }
+    Ok(dictionary)
+}
+
+/// Makes the bytes identifying what a cache was made from, which it must start with to be used
+/// # Arguments
+/// * `bytes` - Contents of the dictionary file
+/// * `strict` - Whether single-letter words are an error
+/// * `min_word_length` - Minimum length of a word kept
+/// # Returns
+/// * `Vec<u8>` - The magic bytes, the format version, the FNV-1a hash of the file, the version of this library, and the options
+fn cache_key(bytes: &[u8], strict: bool, min_word_length: usize) -> Vec<u8> {
+    let version = env!("CARGO_PKG_VERSION").as_bytes();
+    let mut key = CACHE_MAGIC.to_vec();
+    key.push(CACHE_FORMAT_VERSION);
+    key.extend(fnv1a(bytes).to_le_bytes());
+    key.push(version.len() as u8);
+    key.extend(version);
+    key.push(u8::from(strict));
+    key.extend((min_word_length as u64).to_le_bytes());
+    key
+}
+
+/// Hashes bytes with 64-bit FNV-1a (as `dictionary_hash` does words), which gives the same result on every platform and Rust version
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3))
+}
+
+/// Encodes a preprocessed dictionary as a cache file
+/// # Arguments
+/// * `dictionary` - The dictionary
+/// * `key` - What the cache was made from (see `cache_key`)
+/// # Returns
+/// * `Vec<u8>` - The `key`, then the number of words and each word (as its length and its letters), whether there are frequencies and
+///   each one, each word's letter mask, the number of letters indexed and the words containing each one, and lastly the FNV-1a hash of
+///   everything before it (all numbers little-endian)
+fn encode(dictionary: &PreprocessedDictionary, key: &[u8]) -> Vec<u8> {
+    let mut out = key.to_vec();
+    out.extend((dictionary.words.len() as u32).to_le_bytes());
+    for word in dictionary.words.iter() {
+        out.push(word.len() as u8);
+        out.extend(word.iter());
+    }
+    match dictionary.frequencies.as_ref() {
+        Some(frequencies) => {
+            out.push(1);
+            frequencies.iter().for_each(|frequency| out.extend(frequency.to_le_bytes()));
+        },
+        None => out.push(0)
+    }
+    dictionary.masks.iter().for_each(|mask| out.extend(mask.to_le_bytes()));
+    out.extend((dictionary.index.containing.len() as u32).to_le_bytes());
+    for words in dictionary.index.containing.iter() {
+        out.extend((words.len() as u32).to_le_bytes());
+        words.iter().for_each(|&word| out.extend((word as u32).to_le_bytes()));
+    }
+    out.extend(fnv1a(&out).to_le_bytes());
+    out
+}
+
+/// Reads the fields of a cache file in order
+struct Reader<'a> {
+    /// The bytes not yet read
+    bytes: &'a [u8]
+}
+impl<'a> Reader<'a> {
+    /// Reads the next bytes
+    /// # Arguments
+    /// * `length` - Number of bytes to read
+    /// # Returns
+    /// * `Result` with the bytes, or an error message if the file ends first
+    fn take(&mut self, length: usize) -> Result<&'a [u8], String> {
+        if length > self.bytes.len() {
+            return Err("The file is cut off".to_string());
+        }
+        let (taken, rest) = self.bytes.split_at(length);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    /// Reads a little-endian number of up to 8 bytes
+    fn number(&mut self, size: usize) -> Result<u64, String> {
+        Ok(self.take(size)?.iter().rev().fold(0, |number, &byte| number << 8 | byte as u64))
+    }
+}
+
+/// Decodes a cache file
+/// # Arguments
+/// * `bytes` - Contents of the file
+/// * `key` - What the cache must have been made from (see `cache_key`)
+/// # Returns
+/// * `Result` with the preprocessed dictionary, or an error message if the cache is for something else or is corrupt
+fn decode(bytes: &[u8], key: &[u8]) -> Result<PreprocessedDictionary, String> {
+    if !bytes.starts_with(key) {
+        return Err("It was made from another dictionary, version, or options".to_string());
+    }
+    let (contents, checksum) = bytes.split_at(bytes.len().checked_sub(8).ok_or("The file is cut off")?);
+    if contents.len() < key.len() || fnv1a(contents).to_le_bytes() != checksum {
+        return Err("The file is corrupt".to_string());
+    }
+    let mut reader = Reader { bytes: &contents[key.len()..] };
+    let num_words = reader.number(4)? as usize;
+    // Every word takes at least a byte, so a count larger than the file is corrupt (and isn't allocated)
+    if num_words > reader.bytes.len() {
+        return Err("The file is corrupt".to_string());
+    }
+    let mut words: Vec<Word> = Vec::with_capacity(num_words);
+    for _ in 0..num_words {
+        let length = reader.take(1)?[0] as usize;
+        if length > MAX_WORD_LENGTH {
+            return Err("The file is corrupt".to_string());
+        }
+        words.push(Word::from_slice(reader.take(length)?));
+    }
+    let frequencies = match reader.take(1)?[0] {
+        0 => None,
+        _ => Some((0..num_words).map(|_| reader.number(8).map(f64::from_bits)).collect::<Result<Vec<f64>, String>>()?)
+    };
+    let masks = (0..num_words).map(|_| reader.number(8)).collect::<Result<Vec<LetterMask>, String>>()?;
+    let num_letters = reader.number(4)? as usize;
+    if num_letters > reader.bytes.len() {
+        return Err("The file is corrupt".to_string());
+    }
+    let mut containing: Vec<Vec<usize>> = Vec::with_capacity(num_letters);
+    for _ in 0..num_letters {
+        let count = reader.number(4)? as usize;
+        let indices = reader.take(count.checked_mul(4).ok_or("The file is corrupt")?)?;
+        let indices: Vec<usize> = indices.chunks_exact(4).map(|index| u32::from_le_bytes([index[0], index[1], index[2], index[3]]) as usize).collect();
+        if indices.iter().any(|&index| index >= num_words) {
+            return Err("The file is corrupt".to_string());
+        }
+        containing.push(indices);
+    }
+    if !reader.bytes.is_empty() {
+        return Err("The file is corrupt".to_string());
+    }
+    Ok(PreprocessedDictionary { words, frequencies, masks, index: DictionaryIndex { containing } })
+}
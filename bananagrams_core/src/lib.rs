@@ -0,0 +1,6134 @@
+//! Board representation, word validation, and the Bananagrams solver shared by the `training_data` binary and the
+//! `board_generator` Python module
+use std::{borrow::Cow, cmp, f32::consts::E, fmt, hash::{BuildHasher, Hash, Hasher}, path::PathBuf, str::FromStr, time::Instant};
+#[cfg(feature = "native")]
+use std::{fs, path::Path};
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(feature = "parallel")]
+use std::sync::{Mutex, atomic::AtomicUsize};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use hashbrown::{HashMap, HashSet};     // For faster default hash (ahash)
+use hashbrown::hash_map::DefaultHashBuilder;
+use log::{trace, warn};
+use smallvec::SmallVec;
+use rand::prelude::*;
+use rand::distributions::Standard;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[cfg(any(test, feature = "bench"))]
+pub mod bench;
+#[cfg(feature = "native")]
+pub mod dictionary_cache;
+pub mod msgpack;
+pub mod render;
+
+/// A numeric representation of a word, with up to `MAX_WORD_LENGTH` letters stored inline so that words can be hashed, compared, and
+/// cloned without going through the heap (longer runs of letters, which are never words, spill onto it)
+pub type Word = SmallVec<[u8; MAX_WORD_LENGTH]>;
+/// Number of letters in the English alphabet, the default size of `Letters`
+pub const ENGLISH_ALPHABET_SIZE: usize = 26;
+/// Represents a hand of letters, as the number of each of the `N` letters of the alphabet (see `Alphabet`)
+pub type Letters<const N: usize = ENGLISH_ALPHABET_SIZE> = [u8; N];
+/// A hand of letters and the board made from it, with the board's minimum column, maximum column, minimum row, and maximum row with letters
+pub type HandBoard<const N: usize = ENGLISH_ALPHABET_SIZE> = (Letters<N>, Board, usize, usize, usize, usize);
+
+/// The maximum length of any word in the dictionary
+pub const MAX_WORD_LENGTH: usize = 17;
+/// Default minimum length of a word, below which words are removed from the dictionary and runs of letters on a board are invalid (a lone
+/// letter is never a word)
+pub const DEFAULT_MIN_WORD_LENGTH: usize = 2;
+/// Default temperature of word frequencies, at which words are sampled in proportion to their frequency (see `choose_by_frequency`)
+pub const DEFAULT_FREQUENCY_TEMPERATURE: f64 = 1.0;
+/// Value of an empty cell on the board (as large as possible so that every other value can be a letter)
+pub const EMPTY_VALUE: u8 = u8::MAX;
+/// Default number of rows/columns in the board
+pub const BOARD_SIZE: usize = 144;
+/// Number of letters present on the board that can be used in a word (higher will result in fewer words being filtered out)
+const FILTER_LETTERS_ON_BOARD: u8 = 2;
+/// Default maximum number of words to check before the solver stops trying a given word
+pub const MAXIMUM_WORDS_CHECKED: usize = 500_000;
+/// Number of words the solver checks between looking at the clock for whether it has run out of time
+const DEADLINE_CHECK_INTERVAL: usize = 1024;
+/// Maximum number of failed states the solver remembers while solving a hand when memoizing (see `FailureMemo`)
+const MEMO_CAPACITY: usize = 1 << 21;
+/// Maximum number of times `play_bananagrams_multi` solves a hand for each distinct board asked for (since reshuffled dictionaries often
+/// lead back to a board already found)
+pub const MULTI_SOLUTION_ATTEMPTS: usize = 10;
+/// Minimum size of hand of letters to generate
+pub const MINIMUM_HAND_SIZE: f32 = 11.0;
+/// Base to use when generating the logarithmically scaled hand size (see `HandSizeDistribution::LogScaled`)
+const BASE: f32 = E;
+/// The vowels of the English alphabet (A, E, I, O, and U), as indices
+const VOWELS: [u8; 5] = [0, 4, 8, 14, 20];
+/// All the letters present in standard Bananagrams as ASCII values (see `TileSet::standard`)
+pub const TO_CHOOSE_FROM: [usize; 144] = [65, 65, 65, 65, 65, 65, 65, 65, 65, 65, 65, 65, 65, 66, 66, 66, 67, 67, 67, 68, 68, 68, 68, 68,
+                                          68, 69, 69, 69, 69, 69, 69, 69, 69, 69, 69, 69, 69, 69, 69, 69, 69, 69, 69, 70, 70, 70, 71, 71,
+                                          71, 71, 72, 72, 72, 73, 73, 73, 73, 73, 73, 73, 73, 73, 73, 73, 73, 74, 74, 75, 75, 76, 76, 76,
+                                          76, 76, 77, 77, 77, 78, 78, 78, 78, 78, 78, 78, 78, 79, 79, 79, 79, 79, 79, 79, 79, 79, 79, 79,
+                                          80, 80, 80, 81, 81, 82, 82, 82, 82, 82, 82, 82, 82, 82, 83, 83, 83, 83, 83, 83, 84, 84, 84, 84,
+                                          84, 84, 84, 84, 84, 85, 85, 85, 85, 85, 85, 86, 86, 86, 87, 87, 87, 88, 88, 89, 89, 89, 90, 90];
+
+/// A thin wrapper for handling the board
+#[derive(Clone)]
+pub struct Board {
+    /// The underlying vector of the board (as in optimization level 0 the array overflows the stack)
+    arr: Vec<u8>,
+    /// Number of rows/columns in the board
+    size: usize,
+    /// The (row, column) of every tile that was placed with a blank (which holds the letter the blank stands for)
+    blanks: Vec<(usize, usize)>,
+    /// Minimum occupied column index (`usize::MAX` while the board is empty)
+    min_col: usize,
+    /// Maximum occupied column index
+    max_col: usize,
+    /// Minimum occupied row index (`usize::MAX` while the board is empty)
+    min_row: usize,
+    /// Maximum occupied row index
+    max_row: usize
+}
+impl Board {
+    /// Creates a new board of dimensions `size`x`size` filled with the `EMPTY_VALUE`
+    /// # Arguments
+    /// * `size` - Number of rows/columns in the board (see `BOARD_SIZE` for the default)
+    pub fn new(size: usize) -> Board {
+        Board { arr: vec![EMPTY_VALUE; size*size], size, blanks: Vec::new(), min_col: usize::MAX, max_col: 0, min_row: usize::MAX, max_row: 0 }
+    }
+
+    /// Gets the number of rows/columns in the board
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Gets a value from the board at the given index
+    /// # Arguments
+    /// * `row` - Row index of the value to get
+    /// * `col` - Column index of the value to get
+    /// # Returns
+    /// `u8` - The value in the board at `(row, col)` (panics if either `row` or `col` are not less than the board's `size`)
+    pub fn get_val(&self, row: usize, col: usize) -> u8 {
+        self.arr[self.checked_index(row, col)]
+    }
+
+    /// Sets a value in the board at the given index
+    /// # Arguments
+    /// * `row` - Row index of the value to set
+    /// * `col` - Column index of the value to set
+    /// * `val` - Value to set at `(row, col)` in the board (panics if either `row` or `col` are not less than the board's `size`); setting
+    ///   `EMPTY_VALUE` also unmarks the tile if it was placed with a blank
+    pub fn set_val(&mut self, row: usize, col: usize, val: u8) {
+        self.checked_index(row, col);
+        self.place(row, col, val);
+        if val == EMPTY_VALUE {
+            self.clear_blanks(&[(row, col)]);
+        }
+    }
+
+    /// Gets a value from the board at the given index for the solver, without checking the index unless built with the `checked-board`
+    /// feature or debug assertions
+    /// # Arguments
+    /// * `row` - Row index of the value to get (must be less than the board's `size`)
+    /// * `col` - Column index of the value to get (must be less than the board's `size`)
+    /// # Returns
+    /// `u8` - The value in the board at `(row, col)` (if either `row` or `col` are greater than the board's `size` this will be undefined behavior,
+    /// or a panic when checked)
+    fn cell(&self, row: usize, col: usize) -> u8 {
+        #[cfg(any(feature = "checked-board", debug_assertions))]
+        return self.arr[self.checked_index(row, col)];
+        #[cfg(not(any(feature = "checked-board", debug_assertions)))]
+        return unsafe { *self.arr.get_unchecked(row*self.size + col) };
+    }
+
+    /// Sets a value in the board at the given index for the solver, without checking the index unless built with the `checked-board` feature
+    /// or debug assertions
+    /// # Arguments
+    /// * `row` - Row index of the value to set (must be less than the board's `size`)
+    /// * `col` - Column index of the value to set (must be less than the board's `size`)
+    /// * `val` - Value to set at `(row, col)` in the board (if either `row` or `col` are greater than the board's `size` this will be undefined behavior,
+    ///   or a panic when checked)
+    fn place(&mut self, row: usize, col: usize, val: u8) {
+        let v = self.cell_mut(row, col);
+        let previous = *v;
+        *v = val;
+        if val != EMPTY_VALUE {
+            self.min_col = cmp::min(self.min_col, col);
+            self.max_col = cmp::max(self.max_col, col);
+            self.min_row = cmp::min(self.min_row, row);
+            self.max_row = cmp::max(self.max_row, row);
+        }
+        else if previous != EMPTY_VALUE && (col == self.min_col || col == self.max_col || row == self.min_row || row == self.max_row) {
+            // The removed tile may have been the only one in its row or column, so the bounds can only be found again by looking
+            self.recompute_bounds();
+        }
+    }
+
+    /// Gets the occupied region of the board
+    /// # Returns
+    /// `Option<(usize, usize, usize, usize)>` - The (minimum column, maximum column, minimum row, maximum row) of the tiles on the board, or
+    /// `None` if it's empty
+    pub fn bounds(&self) -> Option<(usize, usize, usize, usize)> {
+        if self.min_row > self.max_row {
+            None
+        }
+        else {
+            Some((self.min_col, self.max_col, self.min_row, self.max_row))
+        }
+    }
+
+    /// Finds the occupied region of the board from scratch, after a tile on its edge is removed
+    fn recompute_bounds(&mut self) {
+        let (old_min_col, old_max_col, old_min_row, old_max_row) = (self.min_col, self.max_col, self.min_row, self.max_row);
+        (self.min_col, self.max_col, self.min_row, self.max_row) = (usize::MAX, 0, usize::MAX, 0);
+        // Removing a tile can only shrink the region, so only the old one needs searching
+        for row in old_min_row..old_max_row+1 {
+            for col in old_min_col..old_max_col+1 {
+                if self.cell(row, col) != EMPTY_VALUE {
+                    self.min_col = cmp::min(self.min_col, col);
+                    self.max_col = cmp::max(self.max_col, col);
+                    self.min_row = cmp::min(self.min_row, row);
+                    self.max_row = cmp::max(self.max_row, row);
+                }
+            }
+        }
+    }
+
+    /// Removes tiles from the board, restoring the occupied region to what it was before they were placed rather than searching for it
+    /// again (so `bounds` must be exactly the region of the board without them)
+    /// # Arguments
+    /// * `cells` - The (row, column) of each tile to remove (any placed with a blank are unmarked)
+    /// * `bounds` - The (minimum column, maximum column, minimum row, maximum row) of the board without the tiles
+    fn remove_tiles(&mut self, cells: &[(usize, usize)], bounds: (usize, usize, usize, usize)) {
+        for &(row, col) in cells.iter() {
+            *self.cell_mut(row, col) = EMPTY_VALUE;
+        }
+        (self.min_col, self.max_col, self.min_row, self.max_row) = bounds;
+        self.clear_blanks(cells);
+    }
+
+    /// Gets a cell of the board to modify, without checking the index unless built with the `checked-board` feature or debug assertions
+    /// # Arguments
+    /// * `row` - Row index of the cell (must be less than the board's `size`)
+    /// * `col` - Column index of the cell (must be less than the board's `size`)
+    /// # Returns
+    /// `&mut u8` - The value in the board at `(row, col)`
+    fn cell_mut(&mut self, row: usize, col: usize) -> &mut u8 {
+        #[cfg(any(feature = "checked-board", debug_assertions))]
+        return { let index = self.checked_index(row, col); &mut self.arr[index] };
+        #[cfg(not(any(feature = "checked-board", debug_assertions)))]
+        return unsafe { self.arr.get_unchecked_mut(row*self.size + col) };
+    }
+
+    /// Gets the index into the underlying vector of a cell of the board, panicking if the cell is off the board (even if the index would
+    /// be within `arr`, where it would silently wrap onto another row)
+    /// # Arguments
+    /// * `row` - Row index of the cell
+    /// * `col` - Column index of the cell
+    /// # Returns
+    /// `usize` - The index of `(row, col)` in `arr`
+    fn checked_index(&self, row: usize, col: usize) -> usize {
+        assert!(row < self.size && col < self.size, "Board index (row {}, col {}) is out of bounds for a {}x{} board", row, col, self.size, self.size);
+        row*self.size + col
+    }
+
+    /// Gets whether the tile at the given index was placed with a blank
+    /// # Arguments
+    /// * `row` - Row index of the tile
+    /// * `col` - Column index of the tile
+    /// # Returns
+    /// `bool` - Whether the tile at `(row, col)` is a blank standing for the letter there
+    pub fn is_blank(&self, row: usize, col: usize) -> bool {
+        self.blanks.contains(&(row, col))
+    }
+
+    /// Marks the tile at the given index as placed with a blank (the letter it stands for is set with `set_val` as for any other tile)
+    /// # Arguments
+    /// * `row` - Row index of the tile
+    /// * `col` - Column index of the tile
+    pub fn set_blank(&mut self, row: usize, col: usize) {
+        if !self.is_blank(row, col) {
+            self.blanks.push((row, col));
+        }
+    }
+
+    /// Gets every tile that was placed with a blank
+    /// # Returns
+    /// `&[(usize, usize)]` - The (row, column) of each blank, in the order they were placed
+    pub fn blanks(&self) -> &[(usize, usize)] {
+        &self.blanks
+    }
+
+    /// Unmarks any of the given tiles that were placed with a blank
+    /// # Arguments
+    /// * `cells` - The (row, column) of each tile
+    fn clear_blanks(&mut self, cells: &[(usize, usize)]) {
+        if !self.blanks.is_empty() {
+            self.blanks.retain(|cell| !cells.contains(cell));
+        }
+    }
+}
+
+impl Board {
+    /// Gets whether two boards have exactly the same tiles in exactly the same positions (unlike `==`, which ignores where on the board the
+    /// tiles are), and are the same size
+    /// # Arguments
+    /// * `other` - Board to compare with
+    /// # Returns
+    /// `bool` - Whether the boards are identical
+    pub fn identical(&self, other: &Board) -> bool {
+        self.size == other.size && self.arr == other.arr && self.blanks.len() == other.blanks.len() && self.blanks.iter().all(|&(row, col)| other.is_blank(row, col))
+    }
+
+    /// Gets the value of each cell in the occupied region of the board, row by row
+    /// # Returns
+    /// `(usize, usize, impl Iterator<Item = (u8, bool)>)` - The width and height of the region, and the value of each cell in it along with
+    /// whether it holds a blank
+    fn occupied_cells(&self) -> (usize, usize, impl Iterator<Item = (u8, bool)> + '_) {
+        let (min_col, max_col, min_row, max_row) = self.bounds().unwrap_or((1, 0, 1, 0));
+        let cells = (min_row..max_row+1).flat_map(move |row| (min_col..max_col+1).map(move |col| (self.get_val(row, col), self.is_blank(row, col))));
+        (max_col+1-min_col, max_row+1-min_row, cells)
+    }
+}
+impl fmt::Display for Board {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", board_to_string(self))
+    }
+}
+impl fmt::Debug for Board {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Board ({}x{}):\n{}", self.size, self.size, board_to_string(self))
+    }
+}
+// Boards are equal if they have the same tiles (including which are blanks) in the same arrangement, wherever they are on the board, so
+// that a translated board (or one of another size) compares equal; see `Board::identical` for the strict comparison
+impl PartialEq for Board {
+    fn eq(&self, other: &Board) -> bool {
+        let (width, height, cells) = self.occupied_cells();
+        let (other_width, other_height, other_cells) = other.occupied_cells();
+        width == other_width && height == other_height && cells.eq(other_cells)
+    }
+}
+impl Eq for Board {}
+impl Hash for Board {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let (width, height, cells) = self.occupied_cells();
+        width.hash(state);
+        height.hash(state);
+        for cell in cells {
+            cell.hash(state);
+        }
+    }
+}
+impl FromStr for Board {
+    type Err = BoardParseError;
+
+    /// Parses a board in the form written by `board_to_string` (or `Display`), with one line per row; a space or a period is an empty cell,
+    /// an uppercase letter a tile, and a lowercase letter a tile placed with a blank. The board is made just large enough to hold the text,
+    /// with its first character at row 0 and column 0.
+    fn from_str(text: &str) -> Result<Board, BoardParseError> {
+        let lines: Vec<&str> = text.lines().collect();
+        let width = lines.first().map_or(0, |line| line.chars().count());
+        let mut board = Board::new(cmp::max(width, lines.len()));
+        for (row, line) in lines.iter().enumerate() {
+            // Only the last line can be shorter, since `board_to_string` trims the spaces at the end of the board
+            let length = line.chars().count();
+            if length > width || (length < width && row+1 < lines.len()) {
+                return Err(BoardParseError::RaggedLine { line: row+1, length, expected: width });
+            }
+            for (col, character) in line.chars().enumerate() {
+                match character {
+                    ' ' | '.' => (),
+                    'A'..='Z' => board.set_val(row, col, character as u8 - 65),
+                    'a'..='z' => {
+                        board.set_val(row, col, character as u8 - 97);
+                        board.set_blank(row, col);
+                    },
+                    _ => return Err(BoardParseError::InvalidCharacter { line: row+1, column: col+1, character })
+                }
+            }
+        }
+        Ok(board)
+    }
+}
+
+/// A problem with the text of a board that keeps it from being parsed (see `Board::from_str`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BoardParseError {
+    /// A line is a different length than the first
+    RaggedLine {
+        /// Line of the row, counting from 1
+        line: usize,
+        /// Number of characters in the line
+        length: usize,
+        /// Number of characters in the first line
+        expected: usize
+    },
+    /// A character is neither a letter nor an empty cell
+    InvalidCharacter {
+        /// Line of the row, counting from 1
+        line: usize,
+        /// Column of the character, counting from 1
+        column: usize,
+        /// The character as given
+        character: char
+    }
+}
+impl fmt::Display for BoardParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BoardParseError::RaggedLine { line, length, expected } => write!(f, "Line {} has {} characters, but the board is {} wide", line, length, expected),
+            BoardParseError::InvalidCharacter { line, column, character } => write!(f, "Character {:?} on line {}, column {} isn't a letter, space, or period", character, line, column)
+        }
+    }
+}
+impl std::error::Error for BoardParseError {}
+
+/// The form a `Board` is serialized in: the occupied region as rows of text (see `board_rows`), along with where that region is on the
+/// board, so that the board can be rebuilt exactly without writing out every empty cell
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+struct SerializedBoard {
+    /// Number of rows/columns in the board
+    size: usize,
+    /// Row index of the first row of `rows`
+    row: usize,
+    /// Column index of the first column of `rows`
+    col: usize,
+    /// Each row of the occupied region, with spaces for empty cells and lowercase letters for tiles placed with a blank
+    rows: Vec<String>
+}
+#[cfg(feature = "serde")]
+impl Serialize for Board {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let (min_col, _, min_row, _) = self.bounds().unwrap_or((0, 0, 0, 0));
+        SerializedBoard { size: self.size, row: min_row, col: min_col, rows: board_rows(self) }.serialize(serializer)
+    }
+}
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Board {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Board, D::Error> {
+        let serialized = SerializedBoard::deserialize(deserializer)?;
+        let region: Board = serialized.rows.join("\n").parse().map_err(serde::de::Error::custom)?;
+        let (width, height) = (serialized.rows.first().map_or(0, |row| row.chars().count()), serialized.rows.len());
+        if serialized.row + height > serialized.size || serialized.col + width > serialized.size {
+            return Err(serde::de::Error::custom(format!("Rows starting at row {}, column {} don't fit on a {}x{} board", serialized.row, serialized.col, serialized.size, serialized.size)));
+        }
+        let mut board = Board::new(serialized.size);
+        for row in 0..height {
+            for col in 0..width {
+                let val = region.get_val(row, col);
+                if val != EMPTY_VALUE {
+                    board.set_val(serialized.row + row, serialized.col + col, val);
+                    if region.is_blank(row, col) {
+                        board.set_blank(serialized.row + row, serialized.col + col);
+                    }
+                }
+            }
+        }
+        Ok(board)
+    }
+}
+
+/// Converts a `board` to a `String`
+/// # Arguments
+/// * `board` - Board to display
+/// # Returns
+/// * `String` - The occupied part of `board` in string form (with all numbers converted to letters, in lowercase for tiles placed with a
+///   blank), or an empty string if `board` is empty
+pub fn board_to_string(board: &Board) -> String {
+    board_rows(board).join("\n").trim_end().to_owned()
+}
+
+/// Converts each row of the occupied part of a `board` to a `String`
+/// # Arguments
+/// * `board` - Board to convert
+/// # Returns
+/// * `Vec<String>` - Each row of the occupied part of `board`, all the same length (with spaces for empty cells, and lowercase letters for
+///   tiles placed with a blank), or no rows if `board` is empty
+pub fn board_rows(board: &Board) -> Vec<String> {
+    let (min_col, max_col, min_row, max_row) = match board.bounds() {
+        Some(bounds) => bounds,
+        None => return Vec::new()
+    };
+    (min_row..max_row+1).map(|row| (min_col..max_col+1).map(|col| match board.get_val(row, col) {
+        EMPTY_VALUE => ' ',
+        val if board.is_blank(row, col) => letter_to_char(val).to_ascii_lowercase(),
+        val => letter_to_char(val)
+    }).collect()).collect()
+}
+
+/// ANSI escape code coloring a vowel in `board_to_pretty_string`
+const ANSI_VOWEL: &str = "\x1b[1;33m";
+/// ANSI escape code coloring a consonant in `board_to_pretty_string`
+const ANSI_CONSONANT: &str = "\x1b[1;36m";
+/// ANSI escape code coloring a highlighted tile in `board_to_pretty_string`
+const ANSI_HIGHLIGHT: &str = "\x1b[1;97;41m";
+/// ANSI escape code resetting the color
+const ANSI_RESET: &str = "\x1b[0m";
+
+/// Options for `board_to_pretty_string`
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PrettyOptions<'a> {
+    /// Whether to label each row and column with its index, counting from the top-left corner of the part of the board shown
+    pub coordinates: bool,
+    /// Whether to color the letters with ANSI escape codes, vowels and consonants differently (for printing to a terminal)
+    pub color: bool,
+    /// The (row, column) of each tile to highlight when colored, such as those of invalid words (see `validate_board`)
+    pub highlight: &'a [(usize, usize)]
+}
+
+/// Converts part of a `board` to a `String` laid out as a grid, with box-drawing lines between the cells, for reading large boards in a
+/// terminal
+/// # Arguments
+/// * `board` - Board to display
+/// * `min_col` - Minimum column index of the part of the `board` to display
+/// * `max_col` - Maximum column index of the part of the `board` to display
+/// * `min_row` - Minimum row index of the part of the `board` to display
+/// * `max_row` - Maximum row index of the part of the `board` to display
+/// * `options` - Whether to show coordinates and colors, and which tiles to highlight
+/// # Returns
+/// * `String` - The grid, with a line per row of cells and per line between them (and tiles placed with a blank in lowercase); with
+///   `coordinates`, the column indices are above and the row indices to the left
+pub fn board_to_pretty_string(board: &Board, min_col: usize, max_col: usize, min_row: usize, max_row: usize, options: &PrettyOptions) -> String {
+    let label_width = if options.coordinates { (max_row - min_row).to_string().len() + 1 } else { 0 };
+    let columns = max_col + 1 - min_col;
+    let border = |left: char, middle: char, right: char| {
+        format!("{:label_width$}{}{}{}\n", "", left, vec!["───"; columns].join(&middle.to_string()), right, label_width = label_width)
+    };
+    let mut text = String::new();
+    if options.coordinates {
+        let labels: Vec<String> = (0..columns).map(|col| format!("{:^3}", col)).collect();
+        text.push_str(format!("{:label_width$} {}", "", labels.join(" "), label_width = label_width).trim_end());
+        text.push('\n');
+    }
+    text.push_str(&border('┌', '┬', '┐'));
+    for row in min_row..max_row+1 {
+        if options.coordinates {
+            text.push_str(&format!("{:<label_width$}", row - min_row, label_width = label_width));
+        }
+        text.push('│');
+        for col in min_col..max_col+1 {
+            let letter = match board.get_val(row, col) {
+                EMPTY_VALUE => ' ',
+                val if board.is_blank(row, col) => letter_to_char(val).to_ascii_lowercase(),
+                val => letter_to_char(val)
+            };
+            let color = if options.highlight.contains(&(row, col)) { ANSI_HIGHLIGHT } else if "AEIOU".contains(letter.to_ascii_uppercase()) { ANSI_VOWEL } else { ANSI_CONSONANT };
+            if options.color && letter != ' ' {
+                text.push_str(&format!(" {}{}{} │", color, letter, ANSI_RESET));
+            }
+            else {
+                text.push_str(&format!(" {} │", letter));
+            }
+        }
+        text.push('\n');
+        if row < max_row {
+            text.push_str(&border('├', '┼', '┤'));
+        }
+    }
+    text.push_str(border('└', '┴', '┘').trim_end());
+    text
+}
+
+/// The version of the `.bgb` format written by `board_to_bytes`, stored as the first byte of each file
+pub const BGB_FORMAT_VERSION: u8 = 1;
+/// The version of the `.bgb` format in which each board is followed by how hard it was to solve (see `metrics_to_bytes`), stored as the
+/// first byte of each file in place of `BGB_FORMAT_VERSION`
+pub const BGB_METRICS_FORMAT_VERSION: u8 = 2;
+/// Number of bytes following each board in a version `BGB_METRICS_FORMAT_VERSION` `.bgb` file
+pub const BGB_METRICS_SIZE: usize = 16;
+/// Byte marking the end of each board in the `.bgb` format
+const BGB_BOARD_END: u8 = 255;
+/// Largest board that can be saved in the `.bgb` format, since positions are stored as bytes (and 255 marks the end of a board)
+pub const BGB_MAX_BOARD_SIZE: usize = 254;
+/// Bit set in the letter value of a tile placed with a blank in the `.bgb` and `.bgb2` formats (so blanks can only be saved for alphabets
+/// of at most 128 letters)
+pub const BGB_BLANK_FLAG: u8 = 0x80;
+
+/// Converts the `board` to a bytes representation for saving
+/// # Arguments
+/// * `letters` - Hand of letters the `board` was made from
+/// * `board` - Board to save
+/// # Returns
+/// * `Result` with a vector starting with the `N` letter counts of `letters` (26 for English), followed by each non-empty cell on the `board` represented by
+///   the \[row index, column index, letter value\], with all letters in succession (tiles placed with a blank have `BGB_BLANK_FLAG` set in
+///   their letter value, and aren't in the letter counts). At the end will always be 255 (to serve as the demarcation between boards when
+///   saving). A file of these should start with `BGB_FORMAT_VERSION`. If the `board` is larger than `BGB_MAX_BOARD_SIZE`, or has blanks
+///   and an alphabet of more than 128 letters, an error message is returned instead.
+pub fn board_to_bytes<const N: usize>(letters: &Letters<N>, board: &Board) -> Result<Vec<u8>, String> {
+    if board.size() > BGB_MAX_BOARD_SIZE {
+        return Err(format!("Boards larger than {}x{} can't be saved (this board is {}x{})", BGB_MAX_BOARD_SIZE, BGB_MAX_BOARD_SIZE, board.size(), board.size()));
+    }
+    if N > BGB_BLANK_FLAG as usize && !board.blanks().is_empty() {
+        return Err(format!("Boards with blanks can't be saved for alphabets of more than {} letters (this one has {})", BGB_BLANK_FLAG, N));
+    }
+    // (An empty board has no cells to write, so its minimums are made one more than its maximums)
+    let (min_col, max_col, min_row, max_row) = board.bounds().unwrap_or((1, 0, 1, 0));
+    let mut board_bytes: Vec<u8> = Vec::with_capacity(N + 1 + 3*(max_row+1-min_row)*(max_col+1-min_col));
+    board_bytes.extend(letters.iter());
+    for row in min_row..max_row+1 {
+        for col in min_col..max_col+1 {
+            if board.get_val(row, col) != EMPTY_VALUE {
+                board_bytes.push(row as u8);
+                board_bytes.push(col as u8);
+                board_bytes.push(if board.is_blank(row, col) { board.get_val(row, col) | BGB_BLANK_FLAG } else { board.get_val(row, col) });
+            }
+        }
+    }
+    board_bytes.push(BGB_BOARD_END);
+    Ok(board_bytes)
+}
+
+/// Number of channels of a board in one-hot form (see `board_to_onehot`): one for empty cells, then one for each English letter
+pub const ONEHOT_CHANNELS: usize = ENGLISH_ALPHABET_SIZE + 1;
+
+/// Converts part of a `board` to one-hot form, centered in a grid of the given size (such as for training on boards as images)
+/// # Arguments
+/// * `board` - Board to convert
+/// * `min_col` - Minimum column index of the part to convert
+/// * `max_col` - Maximum column index of the part to convert
+/// * `min_row` - Minimum row index of the part to convert
+/// * `max_row` - Maximum row index of the part to convert
+/// * `height` - Number of rows in the grid
+/// * `width` - Number of columns in the grid
+/// # Returns
+/// * `Option<Vec<u8>>` - The grid as a (`ONEHOT_CHANNELS`, `height`, `width`) array in row-major order, where channel 0 is 1 in each empty
+///   cell (including the padding) and channel k is 1 in each cell with the k-th letter (whether or not it was played with a blank); the part
+///   is placed `(height - rows) / 2` rows down and `(width - columns) / 2` columns across, so any odd padding is at the bottom and right.
+///   `None` if the part doesn't fit in the grid, or has a letter outside the English alphabet.
+pub fn board_to_onehot(board: &Board, min_col: usize, max_col: usize, min_row: usize, max_row: usize, height: usize, width: usize) -> Option<Vec<u8>> {
+    let (rows, cols) = (max_row + 1 - min_row, max_col + 1 - min_col);
+    if rows > height || cols > width {
+        return None;
+    }
+    let (top, left) = ((height - rows) / 2, (width - cols) / 2);
+    let mut grid = vec![0u8; ONEHOT_CHANNELS * height * width];
+    // Every cell starts out empty, and is moved to its letter's channel if it has one
+    grid[..height * width].fill(1);
+    for row in min_row..max_row+1 {
+        for col in min_col..max_col+1 {
+            let val = board.get_val(row, col);
+            if val != EMPTY_VALUE {
+                if val as usize >= ENGLISH_ALPHABET_SIZE {
+                    return None;
+                }
+                let cell = (top + row - min_row) * width + left + col - min_col;
+                grid[cell] = 0;
+                grid[(val as usize + 1) * height * width + cell] = 1;
+            }
+        }
+    }
+    Some(grid)
+}
+
+/// Converts how hard a board was to solve to the bytes following it in a version `BGB_METRICS_FORMAT_VERSION` `.bgb` file
+/// # Arguments
+/// * `metrics` - How hard the board was to solve
+/// * `elapsed_ms` - Time taken to solve it, in milliseconds
+/// # Returns
+/// * `[u8; BGB_METRICS_SIZE]` - The number of words checked, `elapsed_ms`, the depth, and the number of failed first words, each as a
+///   little-endian `u32` (at most `u32::MAX`)
+pub fn metrics_to_bytes(metrics: &SolveMetrics, elapsed_ms: u128) -> [u8; BGB_METRICS_SIZE] {
+    let mut bytes = [0u8; BGB_METRICS_SIZE];
+    let values = [metrics.words_checked as u128, elapsed_ms, metrics.depth as u128, metrics.failed_first_words as u128];
+    for (chunk, value) in bytes.chunks_exact_mut(4).zip(values) {
+        chunk.copy_from_slice(&u32::try_from(value).unwrap_or(u32::MAX).to_le_bytes());
+    }
+    bytes
+}
+
+/// Parses the contents of a `.bgb` file back into hands and boards
+/// # Arguments
+/// * `bytes` - Contents of the file, starting with the format version byte
+/// # Returns
+/// * `Result` with each hand of letters, the board made from it (just large enough to hold its letters), and the board's minimum column,
+///   maximum column, minimum row, and maximum row with letters (for a board with no letters the minimums are one more than the maximums),
+///   or an error message giving the byte offset if `bytes` is not a valid version `BGB_FORMAT_VERSION` or `BGB_METRICS_FORMAT_VERSION` file
+///   (the file doesn't record its alphabet, so one written with an alphabet of `N` letters must be read with the same `N`)
+pub fn parse_bgb<const N: usize>(bytes: &[u8]) -> Result<Vec<HandBoard<N>>, String> {
+    parse_bgb_records(bytes, false)
+}
+
+/// Parses how hard each board of a `.bgb` file was to solve
+/// # Arguments
+/// * `bytes` - Contents of the file, starting with the format version byte
+/// # Returns
+/// * `Result` with how hard each board was to solve and the time taken to solve it in milliseconds (see `metrics_to_bytes`), or `None` for
+///   a version `BGB_FORMAT_VERSION` file (which doesn't record them), or an error message if `bytes` is not a valid file
+pub fn parse_bgb_metrics<const N: usize>(bytes: &[u8]) -> Result<Option<Vec<(SolveMetrics, u64)>>, String> {
+    if check_bgb_version(bytes)? == 0 {
+        return Ok(None);
+    }
+    let mut metrics: Vec<(SolveMetrics, u64)> = Vec::new();
+    let mut offset = 1;
+    while offset < bytes.len() {
+        let (_, next_offset) = parse_bgb_record::<N>(bytes, offset, BGB_METRICS_SIZE)?;
+        let values: Vec<usize> = bytes[next_offset-BGB_METRICS_SIZE..next_offset].chunks_exact(4)
+            .map(|chunk| u32::from_le_bytes(chunk.try_into().expect("Chunk isn't 4 bytes!")) as usize).collect();
+        metrics.push((SolveMetrics { words_checked: values[0], depth: values[2], failed_first_words: values[3] }, values[1] as u64));
+        offset = next_offset;
+    }
+    Ok(Some(metrics))
+}
+
+/// Finds where each complete board in the contents of a `.bgb` file ends, stopping at the first board that is cut off or invalid (such
+/// as one left half-written by an interrupted run)
+/// # Arguments
+/// * `bytes` - Contents of the file, starting with the format version byte
+/// # Returns
+/// * `Result` with the offset just past each complete board, or an error message if the file doesn't start with a supported version byte
+pub fn complete_bgb_records(bytes: &[u8]) -> Result<Vec<usize>, String> {
+    let metrics_size = check_bgb_version(bytes)?;
+    let mut ends: Vec<usize> = Vec::new();
+    let mut offset = 1;
+    while let Ok((_, next_offset)) = parse_bgb_record::<ENGLISH_ALPHABET_SIZE>(bytes, offset, metrics_size) {
+        ends.push(next_offset);
+        offset = next_offset;
+    }
+    Ok(ends)
+}
+
+/// Parses the contents of a `.bgb` file back into hands and boards, optionally ignoring an incomplete board at the end
+/// # Arguments
+/// * `bytes` - Contents of the file, starting with the format version byte
+/// * `allow_truncated` - Whether to stop at the first invalid board rather than returning an error (for files that were cut off)
+/// # Returns
+/// * `Result` as returned by `parse_bgb`
+fn parse_bgb_records<const N: usize>(bytes: &[u8], allow_truncated: bool) -> Result<Vec<HandBoard<N>>, String> {
+    let metrics_size = check_bgb_version(bytes)?;
+    let mut boards: Vec<HandBoard<N>> = Vec::new();
+    let mut offset = 1;
+    while offset < bytes.len() {
+        match parse_bgb_record(bytes, offset, metrics_size) {
+            Ok((board, next_offset)) => {
+                boards.push(board);
+                offset = next_offset;
+            },
+            Err(_) if allow_truncated => break,
+            Err(e) => return Err(e)
+        }
+    }
+    Ok(boards)
+}
+
+/// Parses the contents of a labeled `.bgb` file, in which each board is preceded by a label byte (like the corrupted boards written
+/// alongside the training data, labeled by their `Corruption`)
+/// # Arguments
+/// * `bytes` - Contents of the file, starting with the format version byte
+/// # Returns
+/// * `Result` with each label together with the hand and board that follow it (as returned by `parse_bgb`), or an error message giving the
+///   byte offset if `bytes` is not a valid file
+pub fn parse_labeled_bgb(bytes: &[u8]) -> Result<Vec<(u8, HandBoard)>, String> {
+    let metrics_size = check_bgb_version(bytes)?;
+    let mut boards: Vec<(u8, HandBoard)> = Vec::new();
+    let mut offset = 1;
+    while offset < bytes.len() {
+        let label = bytes[offset];
+        let (board, next_offset) = parse_bgb_record(bytes, offset+1, metrics_size)?;
+        boards.push((label, board));
+        offset = next_offset;
+    }
+    Ok(boards)
+}
+
+/// Checks that the contents of a `.bgb` file start with a supported format version
+/// # Arguments
+/// * `bytes` - Contents of the file
+/// # Returns
+/// * `Result` with the number of bytes following each board (0 for `BGB_FORMAT_VERSION`, or `BGB_METRICS_SIZE` for
+///   `BGB_METRICS_FORMAT_VERSION`), otherwise an error message
+fn check_bgb_version(bytes: &[u8]) -> Result<usize, String> {
+    match *bytes.first().ok_or("File is empty")? {
+        BGB_FORMAT_VERSION => Ok(0),
+        BGB_METRICS_FORMAT_VERSION => Ok(BGB_METRICS_SIZE),
+        version => Err(format!("Unsupported format version {} at byte 0 (expected {} or {})", version, BGB_FORMAT_VERSION, BGB_METRICS_FORMAT_VERSION))
+    }
+}
+
+/// Parses a single board (as written by `board_to_bytes`) from the contents of a `.bgb` file
+/// # Arguments
+/// * `bytes` - Contents of the file
+/// * `offset` - Byte offset at which the board starts
+/// * `metrics_size` - Number of bytes following the board's end marker (see `check_bgb_version`), which are skipped
+/// # Returns
+/// * `Result` with the board (as returned by `parse_bgb`) and the offset of the byte following it (and the bytes after it), or an error
+///   message giving the byte offset if the board is invalid
+fn parse_bgb_record<const N: usize>(bytes: &[u8], mut offset: usize, metrics_size: usize) -> Result<(HandBoard<N>, usize), String> {
+    if bytes.len() - offset < N {
+        return Err(format!("Hand of letters starting at byte {} is truncated", offset));
+    }
+    let mut letters: Letters<N> = [0u8; N];
+    for (count, &byte) in letters.iter_mut().zip(&bytes[offset..offset+N]) {
+        *count = byte;
+    }
+    offset += N;
+    let mut cells: Vec<(usize, usize, u8, bool)> = Vec::new();
+    let (mut min_col, mut max_col, mut min_row, mut max_row) = (BGB_MAX_BOARD_SIZE, 0, BGB_MAX_BOARD_SIZE, 0);
+    loop {
+        match bytes[offset..] {
+            [BGB_BOARD_END, ..] => {
+                offset += 1;
+                if bytes.len() - offset < metrics_size {
+                    return Err(format!("Metrics of the board starting at byte {} are truncated", offset));
+                }
+                offset += metrics_size;
+                break;
+            },
+            [row, col, val, ..] => {
+                let (row, col) = (row as usize, col as usize);
+                if row >= BGB_MAX_BOARD_SIZE || col >= BGB_MAX_BOARD_SIZE {
+                    return Err(format!("Position ({}, {}) at byte {} is outside the largest {}x{} board", row, col, offset, BGB_MAX_BOARD_SIZE, BGB_MAX_BOARD_SIZE));
+                }
+                // Larger alphabets can't have blanks, since their letter values may have the flag bit set
+                let is_blank = N <= BGB_BLANK_FLAG as usize && val & BGB_BLANK_FLAG != 0;
+                if (val & if is_blank { !BGB_BLANK_FLAG } else { u8::MAX }) as usize >= N {
+                    return Err(format!("Letter value {} at byte {} is not between 0 and {}", val, offset+2, N-1));
+                }
+                cells.push((row, col, val & if is_blank { !BGB_BLANK_FLAG } else { u8::MAX }, is_blank));
+                min_col = cmp::min(min_col, col);
+                max_col = cmp::max(max_col, col);
+                min_row = cmp::min(min_row, row);
+                max_row = cmp::max(max_row, row);
+                offset += 3;
+            },
+            _ => return Err(format!("Board ending at byte {} is missing its end marker", bytes.len()))
+        }
+    }
+    if cells.is_empty() {
+        (min_col, max_col, min_row, max_row) = (1, 0, 1, 0);
+    }
+    let mut board = Board::new(cmp::max(max_col, max_row)+1);
+    for (row, col, val, is_blank) in cells {
+        board.set_val(row, col, val);
+        if is_blank {
+            board.set_blank(row, col);
+        }
+    }
+    Ok(((letters, board, min_col, max_col, min_row, max_row), offset))
+}
+
+/// Bytes at the start of every `.bgb2` file
+pub const BGB2_MAGIC: [u8; 4] = *b"BGB2";
+/// The version of the `.bgb2` format written by `write_bgb2`, stored after `BGB2_MAGIC`
+pub const BGB2_FORMAT_VERSION: u8 = 2;
+/// Largest number of rows or columns a board saved in the `.bgb2` format can span, since they are stored as bytes
+pub const BGB2_MAX_DIMENSION: usize = 255;
+
+/// Converts the occupied part of a `board` to a record of the `.bgb2` format
+/// # Arguments
+/// * `letters` - Hand of letters the `board` was made from
+/// * `board` - Board to save
+/// * `min_col` - Minimum column with letters
+/// * `max_col` - Maximum column with letters
+/// * `min_row` - Minimum row with letters
+/// * `max_row` - Maximum row with letters
+/// # Returns
+/// * `Result` with a vector of the number of bytes that follow (as a little-endian `u16`), the width and height of the bounding box, the
+///   26 letter counts of `letters`, and then every cell of the bounding box in row-major order (0 for empty, otherwise 1 ('A') through
+///   26 ('Z'), with `BGB_BLANK_FLAG` set for tiles placed with a blank), or an error message if the bounding box spans more than
+///   `BGB2_MAX_DIMENSION` rows or columns
+pub fn board_to_bgb2(letters: &Letters, board: &Board, min_col: usize, max_col: usize, min_row: usize, max_row: usize) -> Result<Vec<u8>, String> {
+    let (height, width) = (max_row+1-min_row, max_col+1-min_col);
+    if height > BGB2_MAX_DIMENSION || width > BGB2_MAX_DIMENSION {
+        return Err(format!("Boards spanning more than {} rows or columns can't be saved (this board is {}x{})", BGB2_MAX_DIMENSION, height, width));
+    }
+    let length = 2 + 26 + width*height;
+    let mut record: Vec<u8> = Vec::with_capacity(2 + length);
+    record.extend_from_slice(&(length as u16).to_le_bytes());
+    record.push(width as u8);
+    record.push(height as u8);
+    record.extend(letters.iter());
+    for row in min_row..max_row+1 {
+        for col in min_col..max_col+1 {
+            let val = board.get_val(row, col);
+            record.push(match val {
+                EMPTY_VALUE => 0,
+                _ if board.is_blank(row, col) => (val + 1) | BGB_BLANK_FLAG,
+                _ => val + 1
+            });
+        }
+    }
+    Ok(record)
+}
+
+/// Converts boards to the contents of a `.bgb2` file
+/// # Arguments
+/// * `boards` - Each hand of letters with the board made from it and the board's minimum column, maximum column, minimum row, and maximum
+///   row with letters (as returned by `parse_bgb`)
+/// # Returns
+/// * `Result` with `BGB2_MAGIC`, `BGB2_FORMAT_VERSION`, and then each board as converted by `board_to_bgb2`, or an error message if a board
+///   is too large to save
+pub fn write_bgb2(boards: &[HandBoard]) -> Result<Vec<u8>, String> {
+    let mut bytes: Vec<u8> = BGB2_MAGIC.to_vec();
+    bytes.push(BGB2_FORMAT_VERSION);
+    for (letters, board, min_col, max_col, min_row, max_row) in boards.iter() {
+        bytes.extend(board_to_bgb2(letters, board, *min_col, *max_col, *min_row, *max_row)?);
+    }
+    Ok(bytes)
+}
+
+/// Finds where each record of a `.bgb2` file starts, without parsing the boards
+/// # Arguments
+/// * `bytes` - Contents of the file, starting with `BGB2_MAGIC`
+/// # Returns
+/// * `Result` with the byte offset of every record (for use with `read_bgb2_record`), or an error message giving the byte offset if `bytes` is
+///   not a valid version `BGB2_FORMAT_VERSION` file
+pub fn index_bgb2(bytes: &[u8]) -> Result<Vec<usize>, String> {
+    index_bgb2_records(bytes, false)
+}
+
+/// Finds where each record of a `.bgb2` file starts, optionally ignoring an incomplete record at the end
+/// # Arguments
+/// * `bytes` - Contents of the file, starting with `BGB2_MAGIC`
+/// * `allow_truncated` - Whether to leave out a record cut off by the end of `bytes` rather than returning an error
+/// # Returns
+/// * `Result` as returned by `index_bgb2`
+fn index_bgb2_records(bytes: &[u8], allow_truncated: bool) -> Result<Vec<usize>, String> {
+    if !bytes.starts_with(&BGB2_MAGIC) {
+        return Err("File doesn't start with the .bgb2 magic bytes".to_owned());
+    }
+    match bytes.get(BGB2_MAGIC.len()) {
+        Some(&BGB2_FORMAT_VERSION) => {},
+        Some(version) => return Err(format!("Unsupported format version {} at byte {} (expected {})", version, BGB2_MAGIC.len(), BGB2_FORMAT_VERSION)),
+        None => return Err("File is missing its format version".to_owned())
+    }
+    let mut offsets = Vec::new();
+    let mut offset = BGB2_MAGIC.len() + 1;
+    while offset < bytes.len() {
+        if bytes.len() - offset < 2 {
+            if allow_truncated {
+                break;
+            }
+            return Err(format!("Length of the record at byte {} is truncated", offset));
+        }
+        let length = u16::from_le_bytes([bytes[offset], bytes[offset+1]]) as usize;
+        if bytes.len() - offset - 2 < length {
+            if allow_truncated {
+                break;
+            }
+            return Err(format!("Record at byte {} is truncated (expected {} bytes but only {} remain)", offset, length, bytes.len() - offset - 2));
+        }
+        offsets.push(offset);
+        offset += 2 + length;
+    }
+    Ok(offsets)
+}
+
+/// Parses a single record of a `.bgb2` file
+/// # Arguments
+/// * `bytes` - Contents of the file
+/// * `offset` - Byte offset at which the record starts (as returned by `index_bgb2`)
+/// # Returns
+/// * `Result` with the hand of letters, the board made from it (just large enough to hold its bounding box, which starts at (0, 0)), and the
+///   board's minimum column, maximum column, minimum row, and maximum row (for a board with no letters the minimums are one more than the
+///   maximums), or an error message giving the byte offset if the record is invalid
+pub fn read_bgb2_record(bytes: &[u8], offset: usize) -> Result<HandBoard, String> {
+    let header = bytes.get(offset..offset+4).ok_or_else(|| format!("Record header at byte {} is truncated", offset))?;
+    let length = u16::from_le_bytes([header[0], header[1]]) as usize;
+    let (width, height) = (header[2] as usize, header[3] as usize);
+    if length != 2 + 26 + width*height {
+        return Err(format!("Record at byte {} has length {} but a {}x{} board needs {}", offset, length, height, width, 2 + 26 + width*height));
+    }
+    let record = bytes.get(offset+4..offset+2+length).ok_or_else(|| format!("Record at byte {} is truncated", offset))?;
+    let mut letters: Letters = [0u8; 26];
+    letters.copy_from_slice(&record[..26]);
+    let mut board = Board::new(cmp::max(width, height));
+    for (i, &val) in record[26..].iter().enumerate() {
+        match val {
+            0 => {},
+            1..=26 => board.set_val(i / width, i % width, val - 1),
+            0x81..=0x9A => {
+                board.set_val(i / width, i % width, (val & !BGB_BLANK_FLAG) - 1);
+                board.set_blank(i / width, i % width);
+            },
+            _ => return Err(format!("Letter value {} at byte {} is not between 0 and 26 (or a blank from {} to {})", val, offset + 4 + 26 + i, 0x81, 0x9A))
+        }
+    }
+    if width == 0 || height == 0 {
+        return Ok((letters, board, 1, 0, 1, 0));
+    }
+    Ok((letters, board, 0, width-1, 0, height-1))
+}
+
+/// Parses the contents of a `.bgb2` file back into hands and boards
+/// # Arguments
+/// * `bytes` - Contents of the file, starting with `BGB2_MAGIC`
+/// # Returns
+/// * `Result` with each record as returned by `read_bgb2_record`, or an error message giving the byte offset if `bytes` is not a valid file
+pub fn read_bgb2(bytes: &[u8]) -> Result<Vec<HandBoard>, String> {
+    index_bgb2(bytes)?.into_iter().map(|offset| read_bgb2_record(bytes, offset)).collect()
+}
+
+/// Bytes at the start of every gzip stream
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+/// Bytes at the start of every zstd frame
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// Reads everything a decoder can decompress, stopping where the compressed data ends early so that a file cut off partway through (such
+/// as by an interrupted run) still gives back everything before that point
+/// # Arguments
+/// * `decoder` - Decoder reading the compressed file
+/// # Returns
+/// * `Result` with a tuple of (the decompressed data, whether the compressed data was cut off), or the decoder's error if the compressed
+///   data is corrupt
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+fn read_until_cut_off(mut decoder: impl std::io::Read) -> Result<(Vec<u8>, bool), std::io::Error> {
+    let mut decompressed = Vec::new();
+    let mut buffer = [0u8; 8192];
+    loop {
+        match decoder.read(&mut buffer) {
+            Ok(0) => return Ok((decompressed, false)),
+            Ok(n) => decompressed.extend_from_slice(&buffer[..n]),
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok((decompressed, true)),
+            Err(e) => return Err(e)
+        }
+    }
+}
+
+/// Decompresses the contents of a file if they are gzip- or zstd-compressed (as detected from their first bytes)
+/// # Arguments
+/// * `bytes` - Contents of the file
+/// # Returns
+/// * `Result` with the decompressed contents (or `bytes` itself if it isn't compressed) and whether they were cut off partway through, or
+///   an error message if `bytes` is compressed with a codec this was built without (see the `gzip` and `zstd` features) or is corrupt
+pub fn decompress(bytes: &[u8]) -> Result<(Cow<'_, [u8]>, bool), String> {
+    if bytes.starts_with(&GZIP_MAGIC) {
+        #[cfg(feature = "gzip")]
+        return read_until_cut_off(flate2::read::MultiGzDecoder::new(bytes)).map(|(decompressed, truncated)| (Cow::Owned(decompressed), truncated))
+            .map_err(|e| format!("Gzip-compressed file is corrupt: {}", e));
+        #[cfg(not(feature = "gzip"))]
+        return Err("File is gzip-compressed, but gzip support wasn't enabled".to_owned());
+    }
+    if bytes.starts_with(&ZSTD_MAGIC) {
+        #[cfg(feature = "zstd")]
+        return zstd::stream::read::Decoder::new(bytes).and_then(read_until_cut_off).map(|(decompressed, truncated)| (Cow::Owned(decompressed), truncated))
+            .map_err(|e| format!("Zstd-compressed file is corrupt: {}", e));
+        #[cfg(not(feature = "zstd"))]
+        return Err("File is zstd-compressed, but zstd support wasn't enabled".to_owned());
+    }
+    Ok((Cow::Borrowed(bytes), false))
+}
+
+/// Reads the boards from the contents of a `.bgb` or `.bgb2` file, which may be compressed (see `decompress`); the format is detected from
+/// the first bytes, and if a compressed file was cut off (such as by an interrupted run) the incomplete board at its end is left out
+/// # Arguments
+/// * `bytes` - Contents of the file
+/// # Returns
+/// * `Result` with each board as returned by `parse_bgb` or `read_bgb2`, or an error message if `bytes` isn't a valid file
+pub fn read_boards(bytes: &[u8]) -> Result<Vec<HandBoard>, String> {
+    let (bytes, truncated) = decompress(bytes)?;
+    if truncated && bytes.is_empty() {
+        return Ok(Vec::new());
+    }
+    if bytes.starts_with(&BGB2_MAGIC) {
+        index_bgb2_records(&bytes, truncated)?.into_iter().map(|offset| read_bgb2_record(&bytes, offset)).collect()
+    }
+    else {
+        parse_bgb_records(&bytes, truncated)
+    }
+}
+
+/// Converts a word into a numeric vector representation
+/// # Arguments
+/// * `word` - String word to convert
+/// # Returns
+/// `Word` - numeric representation of `word`, with each letter converted from 65 ('A') to 90 ('Z')
+/// # See also
+/// `convert_array_to_word`, and `Alphabet::convert_word` for alphabets other than English
+pub fn convert_word_to_array(word: &str) -> Word {
+    word.chars().filter(|c| c.is_ascii_uppercase()).map(|c| c as u8 - 65).collect()
+}
+
+/// Converts a numeric vector representation of a word back into a string
+/// # Arguments
+/// * `word` - Numeric representation of the word
+/// # Returns
+/// `String` - `word` in string form
+/// # See also
+/// `convert_word_to_array`
+pub fn convert_array_to_word(word: &Word) -> String {
+    word.iter().map(|&letter| letter_to_char(letter)).collect()
+}
+
+/// Converts the numeric representation of a letter back into a character
+/// # Arguments
+/// * `letter` - The letter, from 0 ('A') to 25 ('Z')
+/// # Returns
+/// `char` - `letter` as an uppercase character, or `?` if it isn't one of A-Z (such as on a board read from a corrupted file), so that
+/// untrusted boards can always be printed
+pub fn letter_to_char(letter: u8) -> char {
+    if (letter as usize) < ENGLISH_ALPHABET_SIZE { (letter + 65) as char } else { '?' }
+}
+
+/// The symbols of the tiles of an alphabet, each of which is numbered by its position (so that words and hands can be represented
+/// numerically as with English, where 'A' is 0 and 'Z' is 25)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Alphabet {
+    /// The symbol of each letter, in uppercase (such as `"Ñ"` or `"LL"` for Spanish)
+    symbols: Vec<String>
+}
+impl Alphabet {
+    /// Creates the English alphabet of 'A' to 'Z'
+    /// # Returns
+    /// * `Alphabet` - The English alphabet
+    pub fn english() -> Alphabet {
+        Alphabet { symbols: (b'A'..=b'Z').map(|c| (c as char).to_string()).collect() }
+    }
+
+    /// Creates an alphabet from the symbols of its letters
+    /// # Arguments
+    /// * `symbols` - The symbol of each letter in order, which may have several characters (like the `"LL"` tile of Spanish) and are
+    ///   converted to uppercase
+    /// # Returns
+    /// * `Result` with the alphabet, or an error message if there are no symbols, more than 255 of them (since `EMPTY_VALUE` marks empty
+    ///   cells), or any is empty, contains whitespace, or is repeated
+    pub fn new(symbols: Vec<String>) -> Result<Alphabet, String> {
+        if symbols.is_empty() {
+            return Err("The alphabet contains no letters".to_owned());
+        }
+        if symbols.len() > EMPTY_VALUE as usize {
+            return Err(format!("The alphabet has {} letters (at most {} are allowed)", symbols.len(), EMPTY_VALUE));
+        }
+        let symbols: Vec<String> = symbols.iter().map(|symbol| symbol.to_uppercase()).collect();
+        for (i, symbol) in symbols.iter().enumerate() {
+            if symbol.is_empty() || symbol.chars().any(char::is_whitespace) {
+                return Err(format!("Invalid letter {:?} in the alphabet (letters can't be empty or contain whitespace)", symbol));
+            }
+            if symbols[..i].contains(symbol) {
+                return Err(format!("The letter {} is in the alphabet more than once", symbol));
+            }
+        }
+        Ok(Alphabet { symbols })
+    }
+
+    /// Parses the text of an alphabet file
+    /// # Arguments
+    /// * `text` - The symbol of each letter in order, one per line (blank lines are ignored)
+    /// # Returns
+    /// * `Result` with the alphabet, or an error message if it is invalid (see `Alphabet::new`)
+    pub fn parse(text: &str) -> Result<Alphabet, String> {
+        Alphabet::new(text.lines().map(str::trim).filter(|line| !line.is_empty()).map(str::to_owned).collect())
+    }
+
+    /// Loads an alphabet from a file
+    /// # Arguments
+    /// * `path` - Path to the alphabet file (see `Alphabet::parse`)
+    /// # Returns
+    /// * `Result` with the alphabet, or an error message if the file can't be read or is invalid
+    #[cfg(feature = "native")]
+    pub fn load(path: &Path) -> Result<Alphabet, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("Failed to read alphabet {}: {}", path.display(), e))?;
+        Alphabet::parse(&text).map_err(|e| format!("Invalid alphabet {}: {}", path.display(), e))
+    }
+
+    /// Gets the number of letters in the alphabet
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    /// Gets whether the alphabet has no letters (which an `Alphabet` never does)
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    /// Gets the symbol of a letter
+    /// # Arguments
+    /// * `letter` - The letter's number, from 0 to one less than the size of the alphabet
+    /// # Returns
+    /// * `&str` - The letter's symbol
+    pub fn symbol(&self, letter: u8) -> &str {
+        &self.symbols[letter as usize]
+    }
+
+    /// Finds the letter with a symbol
+    /// # Arguments
+    /// * `symbol` - The symbol, in any case
+    /// # Returns
+    /// * `Option<u8>` - The letter's number, or `None` if no letter has the `symbol`
+    pub fn index_of(&self, symbol: &str) -> Option<u8> {
+        let symbol = symbol.to_uppercase();
+        self.symbols.iter().position(|other| *other == symbol).map(|index| index as u8)
+    }
+
+    /// Converts a word into a numeric vector representation, matching the longest symbol at each point (so that `"LLAMA"` starts with
+    /// the `"LL"` tile if the alphabet has one)
+    /// # Arguments
+    /// * `word` - String word to convert, in any case
+    /// # Returns
+    /// * `Option<Word>` - Numeric representation of `word`, or `None` if part of it isn't any letter of the alphabet
+    /// # See also
+    /// `Alphabet::convert_array`, and `convert_word_to_array` for the English alphabet
+    pub fn convert_word(&self, word: &str) -> Option<Word> {
+        self.try_convert_word(word).ok()
+    }
+
+    /// Converts a word into a numeric vector representation as `Alphabet::convert_word` does, saying where it went wrong if it can't
+    /// # Arguments
+    /// * `word` - String word to convert, in any case
+    /// # Returns
+    /// * `Result` with the numeric representation of `word`, or the (uppercased) character at which no letter of the alphabet matched
+    fn try_convert_word(&self, word: &str) -> Result<Word, char> {
+        let word = word.to_uppercase();
+        let mut rest = word.as_str();
+        let mut converted = Word::new();
+        while let Some(next) = rest.chars().next() {
+            let (index, symbol) = self.symbols.iter().enumerate().filter(|(_, symbol)| rest.starts_with(symbol.as_str())).max_by_key(|(_, symbol)| symbol.len()).ok_or(next)?;
+            converted.push(index as u8);
+            rest = &rest[symbol.len()..];
+        }
+        Ok(converted)
+    }
+
+    /// Converts a numeric vector representation of a word back into a string
+    /// # Arguments
+    /// * `word` - Numeric representation of the word
+    /// # Returns
+    /// * `String` - `word` in string form, with the symbol of each letter in turn
+    /// # See also
+    /// `Alphabet::convert_word`
+    pub fn convert_array(&self, word: &Word) -> String {
+        word.iter().map(|&letter| self.symbol(letter)).collect()
+    }
+
+    /// Converts the text of a dictionary into words of the alphabet
+    /// # Arguments
+    /// * `text` - Dictionary with one word per line
+    /// * `strict` - Whether single-letter words are an error rather than a warning
+    /// # Returns
+    /// * `Result` as returned by `Alphabet::parse_dictionary_lines`
+    pub fn parse_dictionary(&self, text: &str, strict: bool) -> Result<Vec<Word>, DictionaryError> {
+        self.parse_dictionary_lines(text.lines(), strict)
+    }
+
+    /// Converts the lines of a dictionary into words of the alphabet, ignoring any frequencies given (see
+    /// `Alphabet::parse_weighted_dictionary_lines`)
+    /// # Arguments
+    /// * `lines` - The lines of the dictionary, one word each
+    /// * `strict` - Whether single-letter words are an error rather than a warning
+    /// # Returns
+    /// * `Result` with the words in numeric form sorted longest first (words of the same length staying in the order they were given), or
+    ///   the first `DictionaryError` found
+    pub fn parse_dictionary_lines<'a>(&self, lines: impl IntoIterator<Item = &'a str>, strict: bool) -> Result<Vec<Word>, DictionaryError> {
+        self.parse_weighted_dictionary_lines(lines, strict).map(|(dictionary, _)| dictionary)
+    }
+
+    /// Converts the lines of a dictionary into words of the alphabet, trimming and uppercasing each, skipping blank lines and duplicates,
+    /// and logging a warning for any single-letter words
+    /// # Arguments
+    /// * `lines` - The lines of the dictionary, one word each, optionally followed by whitespace and the frequency of the word (any
+    ///   non-negative number, such as a count from a corpus; words without one get a frequency of 1)
+    /// * `strict` - Whether single-letter words are an error rather than a warning
+    /// # Returns
+    /// * `Result` with a tuple of (the words in numeric form sorted longest first (words of the same length staying in the order they
+    ///   were given), the frequency of each word, or `None` if no line gave one), or the first `DictionaryError` found
+    pub fn parse_weighted_dictionary_lines<'a>(&self, lines: impl IntoIterator<Item = &'a str>, strict: bool) -> Result<(Vec<Word>, Option<Vec<f64>>), DictionaryError> {
+        let mut entries: Vec<(Word, f64)> = Vec::new();
+        let mut weighted = false;
+        let mut seen: HashSet<Word> = HashSet::new();
+        let mut single_letters: Vec<(usize, String)> = Vec::new();
+        for (line_num, line) in lines.into_iter().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (text, frequency) = match line.split_once(char::is_whitespace) {
+                Some((text, frequency)) => {
+                    let frequency = frequency.trim();
+                    let parsed = frequency.parse::<f64>().ok().filter(|f| f.is_finite() && *f >= 0.0)
+                        .ok_or_else(|| DictionaryError::InvalidFrequency { line: line_num+1, word: text.to_owned(), frequency: frequency.to_owned() })?;
+                    weighted = true;
+                    (text, parsed)
+                },
+                None => (line, 1.0)
+            };
+            let word = self.try_convert_word(text).map_err(|character| DictionaryError::InvalidCharacter { line: line_num+1, word: text.to_owned(), character })?;
+            if word.len() > MAX_WORD_LENGTH {
+                return Err(DictionaryError::TooLong { line: line_num+1, word: text.to_owned() });
+            }
+            if word.len() == 1 {
+                if strict {
+                    return Err(DictionaryError::SingleLetter { line: line_num+1, word: text.to_owned() });
+                }
+                single_letters.push((line_num+1, text.to_owned()));
+            }
+            if seen.insert(word.clone()) {
+                entries.push((word, frequency));
+            }
+        }
+        if let Some((line, word)) = single_letters.first() {
+            warn!("The dictionary has {} single-letter words (the first is {:?} on line {})", single_letters.len(), word, line);
+        }
+        entries.sort_by_key(|(w, _)| cmp::Reverse(w.len()));
+        let (dictionary, frequencies): (Vec<Word>, Vec<f64>) = entries.into_iter().unzip();
+        Ok((dictionary, weighted.then_some(frequencies)))
+    }
+
+    /// Converts a string of letters into a hand
+    /// # Arguments
+    /// * `letters` - The letters of the hand in any order, matched as by `Alphabet::convert_word`
+    /// # Returns
+    /// * `Result` with the number of each letter in the hand, or an error message if part of `letters` isn't a letter of the alphabet, the
+    ///   alphabet has more than `N` letters, or there are more than 255 of a letter
+    pub fn hand<const N: usize>(&self, letters: &str) -> Result<Letters<N>, String> {
+        if self.len() > N {
+            return Err(format!("An alphabet of {} letters doesn't fit in hands of {}", self.len(), N));
+        }
+        let word = self.convert_word(letters).ok_or_else(|| format!("Hand {:?} isn't made of letters of the alphabet", letters))?;
+        let mut hand = [0u8; N];
+        for letter in word {
+            hand[letter as usize] = hand[letter as usize].checked_add(1).ok_or_else(|| format!("Too many of the letter {} in hand (at most 255 are allowed)", self.symbol(letter)))?;
+        }
+        Ok(hand)
+    }
+
+    /// Converts a hand of letters into a string
+    /// # Arguments
+    /// * `letters` - Array of the number of each letter in the hand
+    /// # Returns
+    /// * `String` - The symbol of every letter in the hand in order of the alphabet (like `"AAEINRST"`)
+    pub fn letters_to_string<const N: usize>(&self, letters: &Letters<N>) -> String {
+        letters.iter().enumerate().flat_map(|(i, &count)| std::iter::repeat_n(self.symbol(i as u8), count as usize)).collect()
+    }
+}
+
+/// A problem with a dictionary that keeps it from being loaded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DictionaryError {
+    /// The dictionary file couldn't be read
+    Read {
+        /// Path to the file
+        path: PathBuf,
+        /// Why it couldn't be read
+        message: String
+    },
+    /// A word has a character that isn't a letter of the alphabet (such as an apostrophe)
+    InvalidCharacter {
+        /// Line of the word, counting from 1
+        line: usize,
+        /// The word as given
+        word: String,
+        /// The first character that isn't a letter
+        character: char
+    },
+    /// A word is longer than `MAX_WORD_LENGTH`
+    TooLong {
+        /// Line of the word, counting from 1
+        line: usize,
+        /// The word as given
+        word: String
+    },
+    /// A word is a single letter (only an error when loading strictly, otherwise a warning)
+    SingleLetter {
+        /// Line of the word, counting from 1
+        line: usize,
+        /// The word as given
+        word: String
+    },
+    /// The frequency after a word isn't a non-negative number
+    InvalidFrequency {
+        /// Line of the word, counting from 1
+        line: usize,
+        /// The word as given
+        word: String,
+        /// The frequency as given
+        frequency: String
+    }
+}
+impl fmt::Display for DictionaryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DictionaryError::Read { path, message } => write!(f, "Failed to read dictionary {}: {}", path.display(), message),
+            DictionaryError::InvalidCharacter { line, word, character } => write!(f, "Word {:?} on line {} has the character {:?}, which isn't a letter of the alphabet", word, line, character),
+            DictionaryError::TooLong { line, word } => write!(f, "Word {:?} on line {} is longer than the maximum of {} letters", word, line, MAX_WORD_LENGTH),
+            DictionaryError::SingleLetter { line, word } => write!(f, "Word {:?} on line {} is a single letter", word, line),
+            DictionaryError::InvalidFrequency { line, word, frequency } => write!(f, "Word {:?} on line {} has the frequency {:?}, which isn't a non-negative number", word, line, frequency)
+        }
+    }
+}
+impl std::error::Error for DictionaryError {}
+
+/// Converts the text of an English dictionary into words
+/// # Arguments
+/// * `text` - Dictionary with one word per line
+/// * `strict` - Whether single-letter words are an error rather than a warning
+/// # Returns
+/// * `Result` as returned by `Alphabet::parse_dictionary_lines` (so words are trimmed and uppercased, and may only have the letters A-Z)
+pub fn parse_dictionary(text: &str, strict: bool) -> Result<Vec<Word>, DictionaryError> {
+    Alphabet::english().parse_dictionary(text, strict)
+}
+
+/// Loads an English dictionary from a file
+/// # Arguments
+/// * `path` - Path to the dictionary, with one word per line
+/// * `strict` - Whether single-letter words are an error rather than a warning
+/// # Returns
+/// * `Result` with the words in numeric form sorted longest first, or a `DictionaryError` if the file can't be read or is invalid (see
+///   `parse_dictionary`)
+#[cfg(feature = "native")]
+pub fn load_dictionary(path: &Path, strict: bool) -> Result<Vec<Word>, DictionaryError> {
+    load_weighted_dictionary(path, strict).map(|(dictionary, _)| dictionary)
+}
+
+/// Converts the text of an English dictionary into words and their frequencies
+/// # Arguments
+/// * `text` - Dictionary with one word per line, each optionally followed by its frequency
+/// * `strict` - Whether single-letter words are an error rather than a warning
+/// # Returns
+/// * `Result` as returned by `Alphabet::parse_weighted_dictionary_lines`
+pub fn parse_weighted_dictionary(text: &str, strict: bool) -> Result<(Vec<Word>, Option<Vec<f64>>), DictionaryError> {
+    Alphabet::english().parse_weighted_dictionary_lines(text.lines(), strict)
+}
+
+/// Loads an English dictionary and the frequencies of its words from a file
+/// # Arguments
+/// * `path` - Path to the dictionary, with one word per line, each optionally followed by its frequency
+/// * `strict` - Whether single-letter words are an error rather than a warning
+/// # Returns
+/// * `Result` with a tuple of (the words in numeric form sorted longest first, the frequency of each word if any were given), or a
+///   `DictionaryError` if the file can't be read or is invalid (see `parse_weighted_dictionary`)
+#[cfg(feature = "native")]
+pub fn load_weighted_dictionary(path: &Path, strict: bool) -> Result<(Vec<Word>, Option<Vec<f64>>), DictionaryError> {
+    let text = fs::read_to_string(path).map_err(|e| DictionaryError::Read { path: path.to_path_buf(), message: e.to_string() })?;
+    parse_weighted_dictionary(&text, strict)
+}
+
+/// Removes the words shorter than a minimum length from a dictionary, along with their frequencies
+/// # Arguments
+/// * `dictionary` - The words of the dictionary, changed in-place
+/// * `frequencies` - The frequency of each word of the `dictionary`, if any, changed in-place to stay parallel to it
+/// * `min_word_length` - Minimum length of a word to keep
+pub fn remove_short_words(dictionary: &mut Vec<Word>, frequencies: Option<&mut Vec<f64>>, min_word_length: usize) {
+    retain_words(dictionary, frequencies, |word| word.len() >= min_word_length);
+}
+
+/// Removes the words of a dictionary that don't satisfy a condition, along with their frequencies
+/// # Arguments
+/// * `dictionary` - The words of the dictionary, changed in-place
+/// * `frequencies` - The frequency of each word of the `dictionary`, if any, changed in-place to stay parallel to it
+/// * `keep` - Whether to keep a word
+pub fn retain_words(dictionary: &mut Vec<Word>, frequencies: Option<&mut Vec<f64>>, keep: impl Fn(&Word) -> bool) {
+    if let Some(frequencies) = frequencies {
+        let mut kept = dictionary.iter().map(&keep);
+        frequencies.retain(|_| kept.next().unwrap_or(false));
+    }
+    dictionary.retain(|word| keep(word));
+}
+
+/// Weights a word for sampling by its frequency
+/// # Arguments
+/// * `frequency` - Frequency of the word
+/// * `max_frequency` - Highest frequency of the words being sampled from
+/// * `temperature` - Temperature of the frequencies (see `choose_by_frequency`); must be positive
+/// # Returns
+/// * `f64` - The weight of the word, from 0 to 1 (every word is weighted 1 if all of their frequencies are 0)
+fn frequency_weight(frequency: f64, max_frequency: f64, temperature: f64) -> f64 {
+    if max_frequency <= 0.0 {
+        return 1.0;
+    }
+    // Relative to the highest frequency so that a low temperature can't overflow
+    (frequency / max_frequency).powf(1.0 / temperature)
+}
+
+/// Chooses a random word from a dictionary, weighted by the frequencies of its words
+/// # Arguments
+/// * `candidates` - Indices of the words in the dictionary to choose from
+/// * `frequencies` - The frequency of each word in the dictionary
+/// * `temperature` - How strongly to weight by frequency: each word is weighted by its frequency to the power of `1/temperature`, so 1 samples
+///   in proportion to frequency, higher temperatures approach uniform sampling, and 0 always chooses the most frequent word
+/// * `rng` - Random number generator (not used when `temperature` is 0)
+/// # Returns
+/// * `Option<usize>` - The index of the chosen word (the first of the most frequent if `temperature` is 0), or `None` if there are no
+///   `candidates`
+pub fn choose_by_frequency(candidates: impl IntoIterator<Item = usize>, frequencies: &[f64], temperature: f64, rng: &mut impl Rng) -> Option<usize> {
+    let candidates: Vec<usize> = candidates.into_iter().collect();
+    let max_frequency = candidates.iter().map(|&i| frequencies[i]).fold(0.0, f64::max);
+    if temperature == 0.0 {
+        return candidates.iter().copied().reduce(|best, i| if frequencies[i] > frequencies[best] { i } else { best });
+    }
+    candidates.choose_weighted(rng, |&i| frequency_weight(frequencies[i], max_frequency, temperature)).ok().copied()
+}
+
+/// Orders a dictionary for the solver to try more frequent words first, keeping the longest words first (which the solver relies on to find
+/// solutions quickly) and ordering the words of each length by sampling without replacement, weighted by frequency
+/// # Arguments
+/// * `dictionary` - The words of the dictionary
+/// * `frequencies` - The frequency of each word of the `dictionary`
+/// * `temperature` - How strongly to weight by frequency (see `choose_by_frequency`); at 0 the words of each length are in descending
+///   order of frequency
+/// * `rng` - Random number generator (not used when `temperature` is 0)
+/// # Returns
+/// * `Vec<Word>` - The words of the `dictionary` in their new order, to pass to `play_bananagrams`
+pub fn order_by_frequency(dictionary: &[Word], frequencies: &[f64], temperature: f64, rng: &mut impl Rng) -> Vec<Word> {
+    let max_frequency = frequencies.iter().copied().fold(0.0, f64::max);
+    // Sorting by u^(1/weight) for uniform u samples without replacement in proportion to weight (Efraimidis and Spirakis), here taking logs
+    let keys: Vec<f64> = if temperature == 0.0 {
+        frequencies.to_vec()
+    }
+    else {
+        frequencies.iter().map(|&f| rng.gen::<f64>().ln() / frequency_weight(f, max_frequency, temperature)).collect()
+    };
+    let mut order: Vec<usize> = (0..dictionary.len()).collect();
+    order.sort_by(|&a, &b| dictionary[b].len().cmp(&dictionary[a].len()).then(keys[b].total_cmp(&keys[a])));
+    order.into_iter().map(|i| dictionary[i].clone()).collect()
+}
+
+/// Shuffles a dictionary for the solver, keeping the longest words first (which the solver relies on to find solutions quickly) but putting
+/// the words of each length in a random order
+/// # Arguments
+/// * `dictionary` - The words of the dictionary
+/// * `rng` - Random number generator
+/// # Returns
+/// * `Vec<Word>` - The words of the `dictionary` in their new order, to pass to `play_bananagrams`
+pub fn shuffle_dictionary(dictionary: &[Word], rng: &mut impl Rng) -> Vec<Word> {
+    let mut shuffled = dictionary.to_vec();
+    shuffled.shuffle(rng);
+    // Stable, so the shuffled order is kept within each length
+    shuffled.sort_by_key(|word| cmp::Reverse(word.len()));
+    shuffled
+}
+
+/// Hashes a dictionary with 64-bit FNV-1a, which (unlike the standard library's hasher) gives the same result on every platform and
+/// Rust version, so that it can be recorded alongside generated data to identify the dictionary used
+/// # Arguments
+/// * `dictionary` - The words of the dictionary, in order
+/// # Returns
+/// * `u64` - The hash
+pub fn dictionary_hash(dictionary: &[Word]) -> u64 {
+    const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const FNV_PRIME: u64 = 0x100000001b3;
+    let mut hash = FNV_OFFSET_BASIS;
+    for word in dictionary.iter() {
+        // Each word ends with a byte that isn't a letter so that, for example, ["AB", "C"] and ["A", "BC"] hash differently
+        for byte in word.iter().copied().chain([u8::MAX]) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(FNV_PRIME);
+        }
+    }
+    hash
+}
+
+/// Converts a hand of letters into a string
+/// # Arguments
+/// * `letters` - Length-26 array of the number of each letter in the hand
+/// # Returns
+/// * `String` - Every letter in the hand in alphabetical order (like `"AAEINRST"`)
+pub fn letters_to_string(letters: &Letters) -> String {
+    letters.iter().enumerate().flat_map(|(i, count)| std::iter::repeat_n((i as u8 + 65) as char, *count as usize)).collect()
+}
+
+/// Checks whether a `word` can be made using the given `letters`
+/// # Arguments
+/// * `word` - The vector form of the word to check
+/// * `letters` - Array of the number of each letter in the hand
+/// * `blanks` - Number of blanks in the hand, each of which can stand for any letter missing from `letters`
+/// # Returns
+/// * `bool` - Whether `word` can be made using `letters` and `blanks` (never if it has a letter outside the alphabet of `letters`, which the
+///   solver relies on to only ever look up letters within it)
+pub fn is_makeable<const N: usize>(word: &Word, letters: &Letters<N>, mut blanks: u8) -> bool {
+    let mut available_letters = *letters;
+    for letter in word.iter() {
+        match available_letters.get_mut(*letter as usize) {
+            Some(count) if *count > 0 => *count -= 1,
+            Some(_) if blanks > 0 => blanks -= 1,
+            _ => return false
+        }
+    }
+    true
+}
+
+/// Set of letters as a bitmask, with bit `i % 64` set for each letter `i` in the set (so with an alphabet of more than 64 letters some
+/// letters share a bit, which can only let a word through to the exact check in `is_makeable`, never rule one out wrongly)
+pub type LetterMask = u64;
+
+/// Gets the set of letters used in a word
+/// # Arguments
+/// * `word` - The vector form of the word
+/// # Returns
+/// * `LetterMask` - The letters in `word`
+pub fn word_mask(word: &Word) -> LetterMask {
+    word.iter().fold(0, |mask, letter| mask | 1 << (letter % 64))
+}
+
+/// Gets the set of letters used in each word of a dictionary, to pass to the solver when solving many hands with the same dictionary
+/// (computing a word's mask costs about as much as checking it with `is_makeable`, so it only pays off once reused)
+/// # Arguments
+/// * `dictionary` - The words of the dictionary (see `convert_word_to_array`)
+/// # Returns
+/// * `Vec<LetterMask>` - The letters in each word of `dictionary`, in the same order
+pub fn letter_masks(dictionary: &[Word]) -> Vec<LetterMask> {
+    dictionary.iter().map(word_mask).collect()
+}
+
+/// Gets the words of a dictionary that can be made from a hand
+/// # Arguments
+/// * `dictionary` - The words of the dictionary
+/// * `dictionary_masks` - The letters in each word of `dictionary` (see `letter_masks`), if known, to rule out most words with a single AND
+///   before counting their letters
+/// * `letters` - Array of the number of each letter in the hand
+/// * `blanks` - Number of blanks in the hand
+/// * `min_word_length` - Minimum length of a word to keep
+/// * `target_tiles` - Number of tiles to finish the board with, if any (longer words are left out, since every letter of a word is a tile)
+/// # Returns
+/// * `Vec<Word>` - The words of `dictionary` that can be made (see `is_makeable`), in the same order
+fn makeable_words<const N: usize>(dictionary: &[Word], dictionary_masks: Option<&[LetterMask]>, letters: &Letters<N>, blanks: u8, min_word_length: usize, target_tiles: Option<usize>) -> Vec<Word> {
+    let keep = |word: &Word| word.len() >= min_word_length && target_tiles.is_none_or(|target| word.len() <= target) && is_makeable(word, letters, blanks);
+    match dictionary_masks {
+        Some(masks) => {
+            let hand_mask = letters.iter().enumerate().filter(|(_, count)| **count > 0).fold(0, |mask, (letter, _)| mask | 1 << (letter % 64));
+            // Each letter of a word that isn't in the hand at all needs a blank of its own
+            dictionary.iter().zip(masks.iter()).filter(|(word, mask)| (*mask & !hand_mask).count_ones() <= blanks as u32 && keep(word)).map(|(word, _)| word.clone()).collect()
+        },
+        None => dictionary.iter().filter(|word| keep(word)).cloned().collect()
+    }
+}
+
+/// Lookup from each letter to the words in a dictionary containing it, so that words overlapping a given letter can be found
+/// without scanning the whole dictionary
+pub struct DictionaryIndex {
+    /// For each letter of the alphabet, the indices (in ascending order) of the words containing it
+    containing: Vec<Vec<usize>>
+}
+impl DictionaryIndex {
+    /// Creates a new `DictionaryIndex`
+    /// # Arguments
+    /// * `dictionary` - The words of the dictionary (see `convert_word_to_array`)
+    pub fn new(dictionary: &[Word]) -> DictionaryIndex {
+        // Large enough for the English alphabet, or for any larger alphabet the dictionary's words come from
+        let alphabet_size = dictionary.iter().flatten().map(|&letter| letter as usize + 1).max().unwrap_or(0).max(ENGLISH_ALPHABET_SIZE);
+        let mut containing: Vec<Vec<usize>> = vec![Vec::new(); alphabet_size];
+        for (word_idx, word) in dictionary.iter().enumerate() {
+            for letter in word.iter() {
+                // Words with a repeated letter are only listed once for it
+                if containing[*letter as usize].last() != Some(&word_idx) {
+                    containing[*letter as usize].push(word_idx);
+                }
+            }
+        }
+        DictionaryIndex { containing }
+    }
+
+    /// Gets the words containing a letter
+    /// # Arguments
+    /// * `letter` - The letter, such as from 0 ('A') to 25 ('Z') in English
+    /// # Returns
+    /// * `&[usize]` - The indices (in ascending order) of the words in the dictionary containing `letter`
+    pub fn words_containing(&self, letter: usize) -> &[usize] {
+        &self.containing[letter]
+    }
+}
+
+/// Checks whether a horizontal or vertical run of letters is allowed on a board
+/// # Arguments
+/// * `run` - The letters of the run
+/// * `min_word_length` - Minimum length of a word (see `DEFAULT_MIN_WORD_LENGTH`)
+/// * `valid_words` - HashSet of all valid words as `Word`s
+/// # Returns
+/// `bool` - Whether `run` is a single letter (which isn't a word) or a valid word of at least `min_word_length` letters
+#[inline]
+fn is_valid_run(run: &Word, min_word_length: usize, valid_words: &HashSet<Word>) -> bool {
+    run.len() <= 1 || (run.len() >= min_word_length && valid_words.contains(run))
+}
+
+/// Checks that a `board` is valid after a word is played horizontally, given the specified list of `valid_word`s
+/// Note that this does not check if all words are contiguous; this condition must be enforced elsewhere.
+/// Panics if any of the given indices are off the board.
+/// # Arguments
+/// * `board` - `Board` being checked
+/// * `min_col` - Minimum x (column) index of the subsection of the `board` to be checked
+/// * `max_col` - Maximum x (column) index of the subsection of the `board` to be checked
+/// * `min_row` - Minimum y (row) index of the subsection of the `board` to be checked
+/// * `max_row` - Maximum y (row) index of the subsection of the `board` to be checked
+/// * `row` - Row of the word played
+/// * `start_col` - Starting column of the word played
+/// * `end_col` - Ending column of the word played
+/// * `valid_words` - HashSet of all valid words as `Word`s
+/// * `min_word_length` - Minimum length of a word, so that shorter runs of letters (such as an accidental crossing) are invalid even if they
+///   are in `valid_words` (see `DEFAULT_MIN_WORD_LENGTH`)
+/// # Returns
+/// `bool` - whether the given `board` is made only of valid words
+#[allow(clippy::too_many_arguments, reason = "the placement and the bounds of the board are separate, as the solver tracks them")]
+pub fn is_board_valid_horizontal(board: &Board, min_col: usize, max_col: usize, min_row: usize, max_row: usize, row: usize, start_col: usize, end_col: usize, valid_words: &HashSet<Word>, min_word_length: usize) -> bool {
+    assert!([max_col, max_row, row, start_col, end_col].iter().all(|&index| index < board.size()), "The area to check runs off a {}x{} board", board.size(), board.size());
+    is_board_valid_horizontal_reusing(board, min_col, max_col, min_row, max_row, row, start_col, end_col, valid_words, min_word_length, &mut Word::new())
+}
+
+/// Checks a `board` like `is_board_valid_horizontal`, but collecting the letters of each run in a buffer that's reused from check to check
+/// rather than allocated each time
+/// # Arguments
+/// * `board` - `Board` being checked
+/// * `min_col` - Minimum x (column) index of the subsection of the `board` to be checked
+/// * `max_col` - Maximum x (column) index of the subsection of the `board` to be checked
+/// * `min_row` - Minimum y (row) index of the subsection of the `board` to be checked
+/// * `max_row` - Maximum y (row) index of the subsection of the `board` to be checked
+/// * `row` - Row of the word played
+/// * `start_col` - Starting column of the word played
+/// * `end_col` - Ending column of the word played
+/// * `valid_words` - HashSet of all valid words as `Word`s
+/// * `min_word_length` - Minimum length of a word, so that shorter runs of letters (such as an accidental crossing) are invalid even if they
+///   are in `valid_words` (see `DEFAULT_MIN_WORD_LENGTH`)
+/// * `current_letters` - Buffer for the letters of each run (its contents are overwritten)
+/// # Returns
+/// `bool` - whether the given `board` is made only of valid words
+#[allow(clippy::too_many_arguments, reason = "the placement and the bounds of the board are separate, as the solver tracks them")]
+fn is_board_valid_horizontal_reusing(board: &Board, min_col: usize, max_col: usize, min_row: usize, max_row: usize, row: usize, start_col: usize, end_col: usize, valid_words: &HashSet<Word>, min_word_length: usize, current_letters: &mut Word) -> bool {
+    current_letters.clear();
+    // Find the furtherest left column that the new play is connected to
+    let mut minimum_col = start_col;
+    while minimum_col > min_col {
+        if board.cell(row, minimum_col) == EMPTY_VALUE {
+            minimum_col += 1;
+            break;
+        }
+        minimum_col -= 1;
+    }
+    minimum_col = cmp::max(minimum_col, min_col);
+    // Check across the row where the word was played
+    for col_idx in minimum_col..max_col+1 {
+        // If we're not at an empty square, add it to the current word we're looking at
+        if board.cell(row, col_idx) != EMPTY_VALUE {
+            current_letters.push(board.cell(row, col_idx));
+        }
+        else {
+            // Turns out that checking with a set is faster than using a trie, at least for smaller hands
+            if !is_valid_run(current_letters, min_word_length, valid_words) {
+                return false;
+            }
+            current_letters.clear();
+            if col_idx > end_col {
+                break;
+            }
+        }
+    }
+    if !is_valid_run(current_letters, min_word_length, valid_words) {
+        return false;
+    }
+    // Check down each column where a letter was played
+    for col_idx in start_col..end_col+1 {
+        current_letters.clear();
+        // Find the furtherest up row that the word is connected to
+        let mut minimum_row = row;
+        while minimum_row > min_row {
+            if board.cell(minimum_row, col_idx) == EMPTY_VALUE {
+                minimum_row += 1;
+                break;
+            }
+            minimum_row -= 1;
+        }
+        minimum_row = cmp::max(minimum_row, min_row);
+        for row_idx in minimum_row..max_row+1 {
+            if board.cell(row_idx, col_idx) != EMPTY_VALUE {
+                current_letters.push(board.cell(row_idx, col_idx));
+            }
+            else {
+                if !is_valid_run(current_letters, min_word_length, valid_words) {
+                    return false;
+                }
+                current_letters.clear();
+                if row_idx > row {
+                    break;
+                }
+            }
+        }
+        if !is_valid_run(current_letters, min_word_length, valid_words) {
+            return false;
+        }
+    }
+    true
+}
+
+/// Checks that a `board` is valid after a word is played vertically, given the specified list of `valid_word`s
+/// Note that this does not check if all words are contiguous; this condition must be enforced elsewhere.
+/// Panics if any of the given indices are off the board.
+/// # Arguments
+/// * `board` - `Board` being checked
+/// * `min_col` - Minimum x (column) index of the subsection of the `board` to be checked
+/// * `max_col` - Maximum x (column) index of the subsection of the `board` to be checked
+/// * `min_row` - Minimum y (row) index of the subsection of the `board` to be checked
+/// * `max_row` - Maximum y (row) index of the subsection of the `board` to be checked
+/// * `start_row` - Starting row of the word played
+/// * `end_row` - Ending row of the word played
+/// * `col` - Column of the word played
+/// * `valid_words` - HashSet of all valid words as `Word`s
+/// * `min_word_length` - Minimum length of a word, so that shorter runs of letters (such as an accidental crossing) are invalid even if they
+///   are in `valid_words` (see `DEFAULT_MIN_WORD_LENGTH`)
+/// # Returns
+/// `bool` - whether the given `board` is made only of valid words
+#[allow(clippy::too_many_arguments, reason = "the placement and the bounds of the board are separate, as the solver tracks them")]
+pub fn is_board_valid_vertical(board: &Board, min_col: usize, max_col: usize, min_row: usize, max_row: usize, start_row: usize, end_row: usize, col: usize, valid_words: &HashSet<Word>, min_word_length: usize) -> bool {
+    assert!([max_col, max_row, start_row, end_row, col].iter().all(|&index| index < board.size()), "The area to check runs off a {}x{} board", board.size(), board.size());
+    is_board_valid_vertical_reusing(board, min_col, max_col, min_row, max_row, start_row, end_row, col, valid_words, min_word_length, &mut Word::new())
+}
+
+/// Checks a `board` like `is_board_valid_vertical`, but collecting the letters of each run in a buffer that's reused from check to check
+/// rather than allocated each time
+/// # Arguments
+/// * `board` - `Board` being checked
+/// * `min_col` - Minimum x (column) index of the subsection of the `board` to be checked
+/// * `max_col` - Maximum x (column) index of the subsection of the `board` to be checked
+/// * `min_row` - Minimum y (row) index of the subsection of the `board` to be checked
+/// * `max_row` - Maximum y (row) index of the subsection of the `board` to be checked
+/// * `start_row` - Starting row of the word played
+/// * `end_row` - Ending row of the word played
+/// * `col` - Column of the word played
+/// * `valid_words` - HashSet of all valid words as `Word`s
+/// * `min_word_length` - Minimum length of a word, so that shorter runs of letters (such as an accidental crossing) are invalid even if they
+///   are in `valid_words` (see `DEFAULT_MIN_WORD_LENGTH`)
+/// * `current_letters` - Buffer for the letters of each run (its contents are overwritten)
+/// # Returns
+/// `bool` - whether the given `board` is made only of valid words
+#[allow(clippy::too_many_arguments, reason = "the placement and the bounds of the board are separate, as the solver tracks them")]
+fn is_board_valid_vertical_reusing(board: &Board, min_col: usize, max_col: usize, min_row: usize, max_row: usize, start_row: usize, end_row: usize, col: usize, valid_words: &HashSet<Word>, min_word_length: usize, current_letters: &mut Word) -> bool {
+    current_letters.clear();
+    // Find the furtherest up row that the new play is connected to
+    let mut minimum_row = start_row;
+    while minimum_row > min_row {
+        if board.cell(minimum_row, col) == EMPTY_VALUE {
+            minimum_row += 1;
+            break;
+        }
+        minimum_row -= 1;
+    }
+    minimum_row = cmp::max(minimum_row, min_row);
+    // Check down the column where the word was played
+    for row_idx in minimum_row..max_row+1 {
+        // If it's not an empty value, add it to the current word
+        if board.cell(row_idx, col) != EMPTY_VALUE {
+            current_letters.push(board.cell(row_idx, col));
+        }
+        else {
+            // Otherwise, check if we have more than one letter - if so, check if the word is valid
+            if !is_valid_run(current_letters, min_word_length, valid_words) {
+                return false;
+            }
+            current_letters.clear();
+            // If we're past the end of the played word, no need to check farther
+            if row_idx > end_row {
+                break;
+            }
+        }
+    }
+    // In case we don't hit the `else` in the previous loop
+    if !is_valid_run(current_letters, min_word_length, valid_words) {
+        return false;
+    }
+    // Check across each row where a letter was played
+    for row_idx in start_row..end_row+1 {
+        current_letters.clear();
+        // Find the furtherest left column that the word is connected to
+        let mut minimum_col = col;
+        while minimum_col > min_col {
+            if board.cell(row_idx, minimum_col) == EMPTY_VALUE {
+                minimum_col += 1;
+                break;
+            }
+            minimum_col -= 1;
+        }
+        minimum_col = cmp::max(minimum_col, min_col);
+        for col_idx in minimum_col..max_col+1 {
+            if board.cell(row_idx, col_idx) != EMPTY_VALUE {
+                current_letters.push(board.cell(row_idx, col_idx));
+            }
+            else {
+                if !is_valid_run(current_letters, min_word_length, valid_words) {
+                    return false;
+                }
+                current_letters.clear();
+                if col_idx > col {
+                    break;
+                }
+            }
+        }
+        if !is_valid_run(current_letters, min_word_length, valid_words) {
+            return false;
+        }
+    }
+    true
+}
+
+/// Enumeration of the 8 symmetries of a board (its rotations and reflections)
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Symmetry {
+    /// The board unchanged
+    Identity,
+    /// The board rotated 90 degrees clockwise
+    Rotate90,
+    /// The board rotated 180 degrees
+    Rotate180,
+    /// The board rotated 270 degrees clockwise
+    Rotate270,
+    /// The board flipped left-to-right
+    FlipHorizontal,
+    /// The board flipped top-to-bottom
+    FlipVertical,
+    /// The board reflected across its main diagonal (rows become columns)
+    Transpose,
+    /// The board reflected across its other diagonal
+    AntiTranspose
+}
+impl Symmetry {
+    /// Every symmetry, starting with `Identity`
+    pub const ALL: [Symmetry; 8] = [Symmetry::Identity, Symmetry::Rotate90, Symmetry::Rotate180, Symmetry::Rotate270,
+                                    Symmetry::FlipHorizontal, Symmetry::FlipVertical, Symmetry::Transpose, Symmetry::AntiTranspose];
+
+    /// Gets whether the symmetry swaps rows and columns
+    fn swaps_axes(self) -> bool {
+        matches!(self, Symmetry::Rotate90 | Symmetry::Rotate270 | Symmetry::Transpose | Symmetry::AntiTranspose)
+    }
+
+    /// Maps a position within a `height`x`width` grid to its position after the symmetry is applied
+    /// # Arguments
+    /// * `row` - Row index within the grid
+    /// * `col` - Column index within the grid
+    /// * `height` - Number of rows in the grid
+    /// * `width` - Number of columns in the grid
+    /// # Returns
+    /// * `(usize, usize)` - The (row, column) of the position in the transformed grid
+    fn apply(self, row: usize, col: usize, height: usize, width: usize) -> (usize, usize) {
+        match self {
+            Symmetry::Identity => (row, col),
+            Symmetry::Rotate90 => (col, height-1-row),
+            Symmetry::Rotate180 => (height-1-row, width-1-col),
+            Symmetry::Rotate270 => (width-1-col, row),
+            Symmetry::FlipHorizontal => (row, width-1-col),
+            Symmetry::FlipVertical => (height-1-row, col),
+            Symmetry::Transpose => (col, row),
+            Symmetry::AntiTranspose => (width-1-col, height-1-row)
+        }
+    }
+}
+
+/// Applies a symmetry to the occupied part of a `board`
+/// # Arguments
+/// * `board` - `Board` to transform
+/// * `min_col` - Minimum occupied column index
+/// * `max_col` - Maximum occupied column index
+/// * `min_row` - Minimum occupied row index
+/// * `max_row` - Maximum occupied row index
+/// * `symmetry` - The `Symmetry` to apply
+/// # Returns
+/// * `(Board, usize, usize, usize, usize)` - Tuple of (the transformed board cropped to its bounding box with its top-left corner at (0, 0),
+///   minimum column, maximum column, minimum row, maximum row)
+pub fn transform_board(board: &Board, min_col: usize, max_col: usize, min_row: usize, max_row: usize, symmetry: Symmetry) -> (Board, usize, usize, usize, usize) {
+    let (height, width) = (max_row+1-min_row, max_col+1-min_col);
+    if height == 0 || width == 0 {
+        return (Board::new(0), 1, 0, 1, 0);
+    }
+    let mut transformed = Board::new(cmp::max(height, width));
+    for row in 0..height {
+        for col in 0..width {
+            let (new_row, new_col) = symmetry.apply(row, col, height, width);
+            transformed.set_val(new_row, new_col, board.get_val(min_row+row, min_col+col));
+            if board.is_blank(min_row+row, min_col+col) {
+                transformed.set_blank(new_row, new_col);
+            }
+        }
+    }
+    let (new_height, new_width) = if symmetry.swaps_axes() { (width, height) } else { (height, width) };
+    (transformed, 0, new_width-1, 0, new_height-1)
+}
+
+/// Serializes the occupied part of a `board`, so that boards with the same letters (and blanks) in the same places serialize identically
+/// # Arguments
+/// * `board` - `Board` to serialize
+/// * `min_col` - Minimum occupied column index
+/// * `max_col` - Maximum occupied column index
+/// * `min_row` - Minimum occupied row index
+/// * `max_row` - Maximum occupied row index
+/// # Returns
+/// * `Vec<u8>` - The height and width (each as two big-endian bytes) followed by every cell of the bounding box in row-major order, each
+///   as its value then 1 if it's a blank or 0 if not
+fn serialize_cropped(board: &Board, min_col: usize, max_col: usize, min_row: usize, max_row: usize) -> Vec<u8> {
+    let (height, width) = (max_row+1-min_row, max_col+1-min_col);
+    let mut bytes = Vec::with_capacity(4 + 2*height*width);
+    bytes.extend_from_slice(&(height as u16).to_be_bytes());
+    bytes.extend_from_slice(&(width as u16).to_be_bytes());
+    for row in min_row..max_row+1 {
+        for col in min_col..max_col+1 {
+            bytes.push(board.get_val(row, col));
+            bytes.push(board.is_blank(row, col) as u8);
+        }
+    }
+    bytes
+}
+
+/// Applies every symmetry to the occupied part of a `board`, skipping any that give the same board as an earlier one
+/// # Arguments
+/// * `board` - `Board` to transform
+/// * `min_col` - Minimum occupied column index
+/// * `max_col` - Maximum occupied column index
+/// * `min_row` - Minimum occupied row index
+/// * `max_row` - Maximum occupied row index
+/// # Returns
+/// * `Vec<(Board, usize, usize, usize, usize)>` - The distinct transformed boards in the order of `Symmetry::ALL` (so the first is the board itself),
+///   each as returned by `transform_board`
+pub fn distinct_symmetries(board: &Board, min_col: usize, max_col: usize, min_row: usize, max_row: usize) -> Vec<(Board, usize, usize, usize, usize)> {
+    let mut seen: Vec<Vec<u8>> = Vec::with_capacity(Symmetry::ALL.len());
+    let mut boards = Vec::with_capacity(Symmetry::ALL.len());
+    for symmetry in Symmetry::ALL {
+        let transformed = transform_board(board, min_col, max_col, min_row, max_row, symmetry);
+        let serialized = serialize_cropped(&transformed.0, transformed.1, transformed.2, transformed.3, transformed.4);
+        if !seen.contains(&serialized) {
+            seen.push(serialized);
+            boards.push(transformed);
+        }
+    }
+    boards
+}
+
+/// Gets a form of a board that is the same for all of its rotations and reflections (but differs between boards with blanks in different
+/// places)
+/// # Arguments
+/// * `board` - `Board` to canonicalize
+/// * `min_col` - Minimum occupied column index
+/// * `max_col` - Maximum occupied column index
+/// * `min_row` - Minimum occupied row index
+/// * `max_row` - Maximum occupied row index
+/// # Returns
+/// * `Vec<u8>` - The lexicographically smallest serialization of the board's 8 symmetries
+pub fn canonical_form(board: &Board, min_col: usize, max_col: usize, min_row: usize, max_row: usize) -> Vec<u8> {
+    Symmetry::ALL.iter().map(|symmetry| {
+        let (transformed, t_min_col, t_max_col, t_min_row, t_max_row) = transform_board(board, min_col, max_col, min_row, max_row, *symmetry);
+        serialize_cropped(&transformed, t_min_col, t_max_col, t_min_row, t_max_row)
+    }).min().unwrap()
+}
+
+/// Enumeration of the ways `corrupt_board` can make a board invalid
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Corruption {
+    /// Two tiles with different letters swapped places
+    SwapLetters,
+    /// A tile replaced with a different letter
+    ReplaceLetter,
+    /// A word moved one cell up, down, left, or right (breaking its crossings)
+    ShiftWord,
+    /// A tile removed (preferring one that disconnects the board)
+    DeleteTile
+}
+impl Corruption {
+    /// Every kind of corruption, in the order of their labels
+    pub const ALL: [Corruption; 4] = [Corruption::SwapLetters, Corruption::ReplaceLetter, Corruption::ShiftWord, Corruption::DeleteTile];
+
+    /// Gets the label byte identifying the corruption in a labeled `.bgb` file
+    pub fn label(self) -> u8 {
+        self as u8
+    }
+
+    /// Gets the corruption identified by a label byte
+    /// # Arguments
+    /// * `label` - Label byte from a labeled `.bgb` file
+    /// # Returns
+    /// * `Option<Corruption>` - The corruption, or `None` if `label` isn't the label of one
+    pub fn from_label(label: u8) -> Option<Corruption> {
+        Corruption::ALL.get(label as usize).copied()
+    }
+}
+impl fmt::Display for Corruption {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Corruption::SwapLetters => write!(f, "SwapLetters"),
+            Corruption::ReplaceLetter => write!(f, "ReplaceLetter"),
+            Corruption::ShiftWord => write!(f, "ShiftWord"),
+            Corruption::DeleteTile => write!(f, "DeleteTile")
+        }
+    }
+}
+
+/// Finds the bounding box of the letters on a `board`
+/// # Arguments
+/// * `board` - `Board` to search
+/// # Returns
+/// * `(usize, usize, usize, usize)` - Tuple of (minimum column, maximum column, minimum row, maximum row) with letters; for a board with no
+///   letters the minimums are one more than the maximums
+fn occupied_bounds(board: &Board) -> (usize, usize, usize, usize) {
+    board.bounds().unwrap_or((1, 0, 1, 0))
+}
+
+/// Makes a randomly perturbed copy of a board, as a realistic example of an invalid board
+/// # Arguments
+/// * `board` - `Board` to corrupt
+/// * `rng` - Random number generator
+/// * `severity` - Number of perturbations to make (at least one is always made)
+/// # Returns
+/// * `Option`
+///     * `None` - If the randomly chosen kind of corruption can't be applied to this board (e.g. swapping letters on a board with only one
+///       distinct letter, replacing a letter in an alphabet of one letter, or shifting a word off the edge of the board)
+///     * `Some` - A tuple of (the corrupted board, minimum column, maximum column, minimum row, maximum row, the `Corruption` applied); note that
+///       the result isn't guaranteed to be invalid, so it should be checked with `validate_board`; replaced letters are from the first `N`
+///       of the alphabet, which must include every letter on `board`
+pub fn corrupt_board<const N: usize>(board: &Board, rng: &mut impl Rng, severity: usize) -> Option<(Board, usize, usize, usize, usize, Corruption)> {
+    let corruption = *Corruption::ALL.choose(rng)?;
+    let mut corrupted = board.clone();
+    let occupied = |board: &Board| -> Vec<(usize, usize)> {
+        let (min_col, max_col, min_row, max_row) = occupied_bounds(board);
+        (min_row..max_row+1).flat_map(|row| (min_col..max_col+1).map(move |col| (row, col))).filter(|(row, col)| board.get_val(*row, *col) != EMPTY_VALUE).collect()
+    };
+    for _ in 0..severity.max(1) {
+        let tiles = occupied(&corrupted);
+        match corruption {
+            Corruption::SwapLetters => {
+                let (row_a, col_a) = *tiles.choose(rng)?;
+                let letter_a = corrupted.get_val(row_a, col_a);
+                let (row_b, col_b) = *tiles.iter().filter(|(row, col)| corrupted.get_val(*row, *col) != letter_a).collect::<Vec<_>>().choose(rng)?;
+                corrupted.set_val(row_a, col_a, corrupted.get_val(*row_b, *col_b));
+                corrupted.set_val(*row_b, *col_b, letter_a);
+            },
+            Corruption::ReplaceLetter => {
+                if N < 2 {
+                    return None;
+                }
+                let (row, col) = *tiles.choose(rng)?;
+                let letter = corrupted.get_val(row, col) as usize;
+                corrupted.set_val(row, col, ((letter + rng.gen_range(1..N)) % N) as u8);
+            },
+            Corruption::ShiftWord => {
+                let (min_col, max_col, min_row, max_row) = occupied_bounds(&corrupted);
+                let word = word_runs(&corrupted, min_col, max_col, min_row, max_row).choose(rng)?.clone();
+                let (row_shift, col_shift): (isize, isize) = *[(-1, 0), (1, 0), (0, -1), (0, 1)].choose(rng)?;
+                let cells: Vec<(usize, usize)> = (0..word.word.len()).map(|i| match word.direction {
+                    Direction::Horizontal => (word.row, word.col+i),
+                    Direction::Vertical => (word.row+i, word.col)
+                }).collect();
+                let shifted: Vec<(usize, usize)> = cells.iter().filter_map(|(row, col)| {
+                    let (row, col) = (row.checked_add_signed(row_shift)?, col.checked_add_signed(col_shift)?);
+                    if row < corrupted.size() && col < corrupted.size() { Some((row, col)) } else { None }
+                }).collect();
+                if shifted.len() != cells.len() {
+                    return None;
+                }
+                let blanks: Vec<bool> = cells.iter().map(|&(row, col)| corrupted.is_blank(row, col)).collect();
+                for (row, col) in cells.iter() {
+                    corrupted.set_val(*row, *col, EMPTY_VALUE);
+                }
+                for (((row, col), letter), &is_blank) in shifted.iter().zip(word.word.iter()).zip(blanks.iter()) {
+                    corrupted.set_val(*row, *col, *letter);
+                    if is_blank {
+                        corrupted.set_blank(*row, *col);
+                    }
+                }
+            },
+            Corruption::DeleteTile => {
+                // Try the tiles in a random order, taking the first whose removal splits the board (or the first tried if none do)
+                let mut order = tiles.clone();
+                order.shuffle(rng);
+                let (&(first_row, first_col), _) = order.split_first()?;
+                let components = count_components(&corrupted);
+                let mut disconnected = false;
+                for (row, col) in order {
+                    let (letter, is_blank) = (corrupted.get_val(row, col), corrupted.is_blank(row, col));
+                    corrupted.set_val(row, col, EMPTY_VALUE);
+                    if count_components(&corrupted) > components {
+                        disconnected = true;
+                        break;
+                    }
+                    corrupted.set_val(row, col, letter);
+                    if is_blank {
+                        corrupted.set_blank(row, col);
+                    }
+                }
+                if !disconnected {
+                    corrupted.set_val(first_row, first_col, EMPTY_VALUE);
+                }
+            }
+        }
+    }
+    let (min_col, max_col, min_row, max_row) = occupied_bounds(&corrupted);
+    Some((corrupted, min_col, max_col, min_row, max_row, corruption))
+}
+
+/// A single change made to a board by `repair_board` (the inverse of a `Corruption`)
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Edit {
+    /// The tile at (`row`, `col`) was replaced with `letter`
+    ReplaceLetter {
+        /// Row index of the tile
+        row: usize,
+        /// Column index of the tile
+        col: usize,
+        /// The new letter
+        letter: u8
+    },
+    /// The tile at (`row`, `col`) was removed
+    DeleteTile {
+        /// Row index of the tile
+        row: usize,
+        /// Column index of the tile
+        col: usize
+    },
+    /// The word of `length` letters starting at (`row`, `col`) was moved one cell (by `row_shift` rows and `col_shift` columns, one of which is 0)
+    ShiftWord {
+        /// Row index of the word's first letter before it was moved
+        row: usize,
+        /// Column index of the word's first letter before it was moved
+        col: usize,
+        /// Number of letters in the word
+        length: usize,
+        /// Direction the word is played in
+        direction: Direction,
+        /// Number of rows the word was moved down (-1 for up)
+        row_shift: isize,
+        /// Number of columns the word was moved right (-1 for left)
+        col_shift: isize
+    }
+}
+impl Edit {
+    /// Makes the edit on a board
+    /// # Arguments
+    /// * `board` - `Board` to change in-place
+    fn apply(self, board: &mut Board) {
+        match self {
+            Edit::ReplaceLetter { row, col, letter } => board.set_val(row, col, letter),
+            Edit::DeleteTile { row, col } => board.set_val(row, col, EMPTY_VALUE),
+            Edit::ShiftWord { row, col, length, direction, row_shift, col_shift } => {
+                let cells = run_cells(row, col, length, direction);
+                let letters: Vec<(u8, bool)> = cells.iter().map(|&(row, col)| (board.get_val(row, col), board.is_blank(row, col))).collect();
+                for &(row, col) in cells.iter() {
+                    board.set_val(row, col, EMPTY_VALUE);
+                }
+                for (&(row, col), &(letter, is_blank)) in cells.iter().zip(letters.iter()) {
+                    // The edit was only proposed if every shifted cell is on the board
+                    let (row, col) = (row.wrapping_add_signed(row_shift), col.wrapping_add_signed(col_shift));
+                    board.set_val(row, col, letter);
+                    if is_blank {
+                        board.set_blank(row, col);
+                    }
+                }
+            }
+        }
+    }
+}
+impl fmt::Display for Edit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Edit::ReplaceLetter { row, col, letter } => write!(f, "ReplaceLetter at row {}, column {} with {}", row, col, letter_to_char(*letter)),
+            Edit::DeleteTile { row, col } => write!(f, "DeleteTile at row {}, column {}", row, col),
+            Edit::ShiftWord { row, col, length, direction, row_shift, col_shift } => write!(f, "ShiftWord of {} letters {} at row {}, column {} by ({}, {})", length, direction, row, col, row_shift, col_shift)
+        }
+    }
+}
+
+/// Gets the cells of a run of letters
+/// # Arguments
+/// * `row` - Row index of the run's first letter
+/// * `col` - Column index of the run's first letter
+/// * `length` - Number of letters in the run
+/// * `direction` - Direction of the run
+/// # Returns
+/// * `Vec<(usize, usize)>` - The (row, column) of each letter of the run, in order
+fn run_cells(row: usize, col: usize, length: usize, direction: Direction) -> Vec<(usize, usize)> {
+    (0..length).map(|i| match direction {
+        Direction::Horizontal => (row, col+i),
+        Direction::Vertical => (row+i, col)
+    }).collect()
+}
+
+/// Proposes the edits that might help fix a board: replacing a letter of an invalid word with one that makes it valid, deleting a tile of
+/// an invalid word or of a group of letters apart from the largest, and moving an invalid word (or a word apart from the largest group)
+/// one cell into empty space
+/// # Arguments
+/// * `board` - The invalid `Board`
+/// * `valid_words` - HashSet of all valid words as `Word`s
+/// * `min_word_length` - Minimum length of a word
+/// # Returns
+/// * `Vec<Edit>` - The edits to try, with no repeats
+fn repair_edits(board: &Board, valid_words: &HashSet<Word>, min_word_length: usize) -> Vec<Edit> {
+    let (min_col, max_col, min_row, max_row) = occupied_bounds(board);
+    let runs = word_runs(board, min_col, max_col, min_row, max_row);
+    let is_valid = |word: &Word| word.len() >= min_word_length && valid_words.contains(word);
+    let mut groups = connected_groups(board);
+    // Every group but the largest is stray
+    let largest = (0..groups.len()).max_by_key(|&i| groups[i].len());
+    let stray: HashSet<(usize, usize)> = groups.drain(..).enumerate().filter(|(i, _)| Some(*i) != largest).flat_map(|(_, group)| group).collect();
+    let mut edits: Vec<Edit> = Vec::new();
+    let propose = |edit: Edit, edits: &mut Vec<Edit>| {
+        if !edits.contains(&edit) {
+            edits.push(edit);
+        }
+    };
+    for run in runs.iter() {
+        let cells = run_cells(run.row, run.col, run.word.len(), run.direction);
+        let invalid = !is_valid(&run.word);
+        if invalid {
+            for (i, &(row, col)) in cells.iter().enumerate() {
+                // Only letters that would make this word valid are worth trying
+                let mut word = run.word.clone();
+                for letter in 0..ENGLISH_ALPHABET_SIZE as u8 {
+                    word[i] = letter;
+                    if letter != run.word[i] && is_valid(&word) {
+                        propose(Edit::ReplaceLetter { row, col, letter }, &mut edits);
+                    }
+                }
+                propose(Edit::DeleteTile { row, col }, &mut edits);
+            }
+        }
+        if invalid || cells.iter().any(|cell| stray.contains(cell)) {
+            for (row_shift, col_shift) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+                let fits = cells.iter().all(|&(row, col)| match (row.checked_add_signed(row_shift), col.checked_add_signed(col_shift)) {
+                    (Some(row), Some(col)) => row < board.size() && col < board.size() && (board.get_val(row, col) == EMPTY_VALUE || cells.contains(&(row, col))),
+                    _ => false
+                });
+                if fits {
+                    propose(Edit::ShiftWord { row: run.row, col: run.col, length: run.word.len(), direction: run.direction, row_shift, col_shift }, &mut edits);
+                }
+            }
+        }
+    }
+    let mut stray: Vec<(usize, usize)> = stray.into_iter().collect();
+    stray.sort_unstable();
+    for (row, col) in stray {
+        propose(Edit::DeleteTile { row, col }, &mut edits);
+    }
+    edits
+}
+
+/// Tries to make a nearly legal board legal with as few small edits as possible (replacing a tile's letter, deleting a tile, or moving a
+/// word one cell), searching breadth-first with `validate_board` deciding when the board is fixed; only edits that might help are tried
+/// (see `repair_edits`), but the number of boards tried still grows quickly with `max_edits`
+/// # Arguments
+/// * `board` - `Board` to repair
+/// * `valid_words` - HashSet of all valid words as `Word`s
+/// * `min_word_length` - Minimum length of a word, below which runs of letters are invalid (see `DEFAULT_MIN_WORD_LENGTH`)
+/// * `max_edits` - Maximum number of edits to make
+/// # Returns
+/// * `Option<(Board, Vec<Edit>)>` - The repaired board and the edits made to the `board`, in order (none if it was already legal), or `None`
+///   if no legal board is within `max_edits` edits
+pub fn repair_board(board: &Board, valid_words: &HashSet<Word>, min_word_length: usize, max_edits: usize) -> Option<(Board, Vec<Edit>)> {
+    if validate_board(board, valid_words, min_word_length).is_ok() {
+        return Some((board.clone(), Vec::new()));
+    }
+    // Only the edits leading to each board are kept (and the boards already reached only by their hash), since boards can be large
+    let hasher = DefaultHashBuilder::default();
+    let mut seen: HashSet<u64> = HashSet::from_iter([hasher.hash_one(board)]);
+    let mut frontier: Vec<Vec<Edit>> = vec![Vec::new()];
+    for _ in 0..max_edits {
+        let mut next = Vec::new();
+        for edits in frontier {
+            let mut current = board.clone();
+            for edit in edits.iter() {
+                edit.apply(&mut current);
+            }
+            for edit in repair_edits(&current, valid_words, min_word_length) {
+                let mut edited = current.clone();
+                edit.apply(&mut edited);
+                // (A board already reached elsewhere on the board is no closer to being legal)
+                if !seen.insert(hasher.hash_one(&edited)) {
+                    continue;
+                }
+                let mut edits = edits.clone();
+                edits.push(edit);
+                if validate_board(&edited, valid_words, min_word_length).is_ok() {
+                    return Some((edited, edits));
+                }
+                next.push(edits);
+            }
+        }
+        frontier = next;
+    }
+    None
+}
+
+/// Enumeration of how many letters have been used
+#[derive(Copy, Clone)]
+enum LetterUsage {
+    /// There are still unused letters
+    Remaining,
+    /// More letters have been used than are available
+    Overused,
+    /// All letters have been used
+    Finished,
+    /// Letters remain, but the board has reached its target number of tiles (see `play_bananagrams`)
+    TargetReached
+}
+impl fmt::Display for LetterUsage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+       match self {
+            LetterUsage::Remaining => write!(f, "Remaining"),
+            LetterUsage::Overused => write!(f, "Overused"),
+            LetterUsage::Finished => write!(f, "Finished"),
+            LetterUsage::TargetReached => write!(f, "TargetReached")
+       }
+    }
+}
+impl fmt::Debug for LetterUsage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+             LetterUsage::Remaining => write!(f, "Remaining"),
+             LetterUsage::Overused => write!(f, "Overused"),
+             LetterUsage::Finished => write!(f, "Finished"),
+             LetterUsage::TargetReached => write!(f, "TargetReached")
+        }
+     }
+}
+
+/// The result of trying to play a word (see `play_word`): whether it could be validly played, the indices played in, the remaining letters,
+/// how many letters were used, and the remaining blanks
+type PlayOutcome<const N: usize> = (bool, Vec<(usize, usize)>, Letters<N>, LetterUsage, u8);
+
+/// Enumeration of the direction a word is played
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Direction {
+    /// The word was played horizontally
+    Horizontal,
+    /// The word was played vertically
+    Vertical
+}
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+       match self {
+            Direction::Horizontal => write!(f, "Horizontal"),
+            Direction::Vertical => write!(f, "Vertical")
+       }
+    }
+}
+impl Distribution<Direction> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Direction {
+        if rng.gen() {
+            Direction::Horizontal
+        }
+        else {
+            Direction::Vertical
+        }
+    }
+}
+impl Direction {
+    /// Gets the opposite direction of the current (i.e. vertical -> horizontal or vice versa)
+    pub fn opposite(self) -> Direction {
+        match self {
+            Direction::Horizontal => Direction::Vertical,
+            Direction::Vertical => Direction::Horizontal
+        }
+    }
+}
+
+/// A word played on the board as part of a solution (serialized with the `word` as a string, like `{"word":"CAT","row":72,"col":70,
+/// "direction":"Horizontal"}`)
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PlayedWord {
+    /// The word that was played (including any letters it shares with words already on the board)
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_word", deserialize_with = "deserialize_word"))]
+    pub word: Word,
+    /// Row index of the word's first letter
+    pub row: usize,
+    /// Column index of the word's first letter
+    pub col: usize,
+    /// Direction the word was played in
+    pub direction: Direction
+}
+impl fmt::Display for PlayedWord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {} at row {}, column {}", convert_array_to_word(&self.word), self.direction, self.row, self.col)
+    }
+}
+
+/// Serializes a `word` as a string of the letters A-Z (see `convert_array_to_word`)
+#[cfg(feature = "serde")]
+fn serialize_word<S: Serializer>(word: &Word, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&convert_array_to_word(word))
+}
+
+/// Deserializes a word from a string of the letters A-Z, which must be no longer than `MAX_WORD_LENGTH`
+#[cfg(feature = "serde")]
+fn deserialize_word<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Word, D::Error> {
+    let word = String::deserialize(deserializer)?;
+    if let Some(character) = word.chars().find(|c| !c.is_ascii_uppercase()) {
+        return Err(serde::de::Error::custom(format!("Word {:?} has the character {:?}, which isn't a letter A-Z", word, character)));
+    }
+    if word.len() > MAX_WORD_LENGTH {
+        return Err(serde::de::Error::custom(format!("Word {:?} is longer than the maximum of {} letters", word, MAX_WORD_LENGTH)));
+    }
+    Ok(convert_word_to_array(&word))
+}
+
+/// A reason a board is not a legal Bananagrams board
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BoardError {
+    /// There are no letters on the board
+    Empty,
+    /// The letters on the board form more than one connected group
+    Disconnected {
+        /// Number of separate groups of letters
+        components: usize
+    },
+    /// A horizontal or vertical run of two or more letters is not a valid word (or is shorter than the minimum word length)
+    InvalidWord(PlayedWord)
+}
+impl fmt::Display for BoardError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BoardError::Empty => write!(f, "The board is empty"),
+            BoardError::Disconnected { components } => write!(f, "The letters form {} separate groups", components),
+            BoardError::InvalidWord(word) => write!(f, "Invalid word {}", word)
+        }
+    }
+}
+
+/// Counts the connected groups of letters on a `board`
+/// # Arguments
+/// * `board` - `Board` to check
+/// # Returns
+/// * `usize` - Number of groups of letters that are connected horizontally or vertically (0 if the board is empty)
+fn count_components(board: &Board) -> usize {
+    connected_groups(board).len()
+}
+
+/// Finds the connected groups of letters on a `board`
+/// # Arguments
+/// * `board` - `Board` to search
+/// # Returns
+/// * `Vec<Vec<(usize, usize)>>` - The (row, column) of each tile in each group of letters that are connected horizontally or vertically, with
+///   the groups in row-major order of their first tile
+pub fn connected_groups(board: &Board) -> Vec<Vec<(usize, usize)>> {
+    let size = board.size();
+    let mut seen = vec![false; size*size];
+    let mut groups = Vec::new();
+    let mut stack: Vec<(usize, usize)> = Vec::new();
+    for row in 0..size {
+        for col in 0..size {
+            if seen[row*size + col] || board.get_val(row, col) == EMPTY_VALUE {
+                continue;
+            }
+            // Flood-fill the group containing this letter
+            let mut group = Vec::new();
+            seen[row*size + col] = true;
+            stack.push((row, col));
+            while let Some((r, c)) = stack.pop() {
+                group.push((r, c));
+                let neighbors = [(r.wrapping_sub(1), c), (r+1, c), (r, c.wrapping_sub(1)), (r, c+1)];
+                for (nr, nc) in neighbors {
+                    if nr < size && nc < size && !seen[nr*size + nc] && board.get_val(nr, nc) != EMPTY_VALUE {
+                        seen[nr*size + nc] = true;
+                        stack.push((nr, nc));
+                    }
+                }
+            }
+            groups.push(group);
+        }
+    }
+    groups
+}
+
+/// Finds every maximal horizontal and vertical run of two or more letters within part of a `board`
+/// # Arguments
+/// * `board` - `Board` to search
+/// * `min_col` - Minimum column index of the subsection of the `board` to search
+/// * `max_col` - Maximum column index of the subsection of the `board` to search
+/// * `min_row` - Minimum row index of the subsection of the `board` to search
+/// * `max_row` - Maximum row index of the subsection of the `board` to search
+/// # Returns
+/// * `Vec<PlayedWord>` - Each run with its first letter's position, the horizontal runs (row by row) followed by the vertical runs (column by column)
+fn word_runs(board: &Board, min_col: usize, max_col: usize, min_row: usize, max_row: usize) -> Vec<PlayedWord> {
+    let mut runs = Vec::new();
+    for direction in [Direction::Horizontal, Direction::Vertical] {
+        let (lines, positions) = match direction {
+            Direction::Horizontal => (min_row..max_row+1, min_col..max_col+1),
+            Direction::Vertical => (min_col..max_col+1, min_row..max_row+1)
+        };
+        for line in lines {
+            let mut current_letters = Word::new();
+            // One past the end so that a run reaching the edge is still recorded
+            for i in positions.start..positions.end+1 {
+                let (row, col) = match direction {
+                    Direction::Horizontal => (line, i),
+                    Direction::Vertical => (i, line)
+                };
+                if i < positions.end && board.get_val(row, col) != EMPTY_VALUE {
+                    current_letters.push(board.get_val(row, col));
+                    continue;
+                }
+                if current_letters.len() > 1 {
+                    let start = i - current_letters.len();
+                    let (row, col) = match direction {
+                        Direction::Horizontal => (line, start),
+                        Direction::Vertical => (start, line)
+                    };
+                    runs.push(PlayedWord { word: current_letters.clone(), row, col, direction });
+                }
+                current_letters.clear();
+            }
+        }
+    }
+    runs
+}
+
+/// Extracts the words on part of a `board`
+/// # Arguments
+/// * `board` - `Board` to extract the words from
+/// * `min_col` - Minimum occupied column index
+/// * `max_col` - Maximum occupied column index
+/// * `min_row` - Minimum occupied row index
+/// * `max_row` - Maximum occupied row index
+/// # Returns
+/// * `Vec<(String, usize, usize, Direction)>` - Each word (every maximal run of two or more letters) with the row and column of its first letter
+///   and its direction; the horizontal words (row by row) come before the vertical ones (column by column)
+pub fn extract_words(board: &Board, min_col: usize, max_col: usize, min_row: usize, max_row: usize) -> Vec<(String, usize, usize, Direction)> {
+    word_runs(board, min_col, max_col, min_row, max_row).into_iter().map(|run| (convert_array_to_word(&run.word), run.row, run.col, run.direction)).collect()
+}
+
+/// Finds the banned words on part of a `board`; only whole words count, so a banned word that is just part of a longer word isn't found
+/// # Arguments
+/// * `board` - `Board` to search
+/// * `min_col` - Minimum occupied column index
+/// * `max_col` - Maximum occupied column index
+/// * `min_row` - Minimum occupied row index
+/// * `max_row` - Maximum occupied row index
+/// * `banned_words` - HashSet of the banned words as `Word`s (see `load_dictionary`, which uppercases them)
+/// # Returns
+/// * `Vec<PlayedWord>` - Each word on the board that is in `banned_words`, in the order of `extract_words`
+pub fn find_banned_words(board: &Board, min_col: usize, max_col: usize, min_row: usize, max_row: usize, banned_words: &HashSet<Word>) -> Vec<PlayedWord> {
+    if banned_words.is_empty() {
+        return Vec::new();
+    }
+    word_runs(board, min_col, max_col, min_row, max_row).into_iter().filter(|run| banned_words.contains(&run.word)).collect()
+}
+
+/// Checks that an arbitrary `board` is a legal Bananagrams board: all of its letters are connected, and every horizontal
+/// and vertical run of two or more letters is in `valid_words`
+/// # Arguments
+/// * `board` - `Board` to check
+/// * `valid_words` - HashSet of all valid words as `Word`s
+/// * `min_word_length` - Minimum length of a word, below which runs of letters are invalid (see `DEFAULT_MIN_WORD_LENGTH`)
+/// # Returns
+/// * `Result` - Empty if the board is legal, otherwise every `BoardError` found (with invalid words in row-major order of their first letter,
+///   horizontal before vertical)
+pub fn validate_board(board: &Board, valid_words: &HashSet<Word>, min_word_length: usize) -> Result<(), Vec<BoardError>> {
+    let components = count_components(board);
+    if components == 0 {
+        return Err(vec![BoardError::Empty]);
+    }
+    let mut errors = Vec::new();
+    if components > 1 {
+        errors.push(BoardError::Disconnected { components });
+    }
+    let size = board.size();
+    let mut invalid_words: Vec<PlayedWord> = word_runs(board, 0, size-1, 0, size-1).into_iter().filter(|run| !is_valid_run(&run.word, min_word_length, valid_words)).collect();
+    invalid_words.sort_by_key(|word| (word.row, word.col, word.direction == Direction::Vertical));
+    errors.extend(invalid_words.into_iter().map(BoardError::InvalidWord));
+    if errors.is_empty() {
+        Ok(())
+    }
+    else {
+        Err(errors)
+    }
+}
+
+/// Finds how many letters have been used once a word is played
+/// # Arguments
+/// * `remaining_letters` - The number of each letter left in the hand
+/// * `remaining_blanks` - The number of blanks left in the hand
+/// * `letters_on_board` - The number of each letter on the board
+/// * `target_tiles` - Number of tiles to stop at, if any
+/// # Returns
+/// * `LetterUsage` - `Overused` if the board has more than `target_tiles` tiles, `Finished` if the hand is empty, `TargetReached` if the
+///   board has exactly `target_tiles` tiles, and otherwise `Remaining`
+fn letter_usage<const N: usize>(remaining_letters: &Letters<N>, remaining_blanks: u8, letters_on_board: &Letters<N>, target_tiles: Option<usize>) -> LetterUsage {
+    let tiles_on_board = target_tiles.map(|_| letters_on_board.iter().map(|&count| count as usize).sum::<usize>());
+    if let (Some(target_tiles), Some(tiles_on_board)) = (target_tiles, tiles_on_board) {
+        if tiles_on_board > target_tiles {
+            return LetterUsage::Overused;
+        }
+    }
+    if remaining_letters.iter().all(|count| *count == 0) && remaining_blanks == 0 {
+        LetterUsage::Finished
+    }
+    else if tiles_on_board.is_some() && tiles_on_board == target_tiles {
+        LetterUsage::TargetReached
+    }
+    else {
+        LetterUsage::Remaining
+    }
+}
+
+/// Gets the count of a letter in a hand (or on a board), without checking that the letter is in the alphabet unless built with the
+/// `checked-board` feature or debug assertions
+/// # Arguments
+/// * `letters` - Array of the number of each letter
+/// * `letter` - The letter (must be less than `N`; otherwise this is undefined behavior, or a panic when checked)
+/// # Returns
+/// * `&mut u8` - The number of `letter` in `letters`
+#[inline(always)]
+fn letter_count_mut<const N: usize>(letters: &mut Letters<N>, letter: u8) -> &mut u8 {
+    #[cfg(any(feature = "checked-board", debug_assertions))]
+    return letters.get_mut(letter as usize).unwrap_or_else(|| panic!("Letter {} is outside the alphabet of {} letters", letter, N));
+    #[cfg(not(any(feature = "checked-board", debug_assertions)))]
+    return unsafe { letters.get_unchecked_mut(letter as usize) };
+}
+
+/// Plays a word on the board
+/// # Arguments
+/// * `word` - The word to be played
+/// * `row_idx` - The starting row at which to play the word
+/// * `col_idx` - The starting column at which to play the word
+/// * `board` - The current board (is modified in-place)
+/// * `direction` - The `Direction` in which to play the word
+/// * `letters` - The number of each letter currently in the hand
+/// * `blanks` - The number of blanks currently in the hand (only used for letters missing from `letters`)
+/// * `letters_on_board` - The number of each letter on the board (is modified in-place)
+/// * `target_tiles` - Number of tiles to stop at, if any (a word that would put more tiles than this on the board can't be played)
+/// * `played_indices` - Empty vector to record the indices played in (given so that it can be reused from play to play)
+/// # Returns
+/// *`Result` with:*
+/// * `bool` - Whether the word could be validly played
+/// * `Vec<(usize, usize)>` - Vector of the indices played in `board`
+/// * `Letters`- The remaining letters
+/// * `LetterUsage` - How many letters were used
+/// * `u8` - The remaining blanks
+/// 
+/// *or empty `Err` if out-of-bounds*
+#[allow(clippy::too_many_arguments, reason = "the solver's state is passed piece by piece so that each play can borrow it separately")]
+fn play_word<const N: usize>(word: &Word, row_idx: usize, col_idx: usize, board: &mut Board, direction: Direction, letters: &Letters<N>, blanks: u8, letters_on_board: &mut Letters<N>, target_tiles: Option<usize>, mut played_indices: Vec<(usize, usize)>) -> Result<PlayOutcome<N>, ()> {
+    match direction {
+        Direction::Horizontal => {
+            if row_idx >= board.size() || col_idx + word.len() >= board.size() {
+                return Err(());
+            }
+            let mut remaining_letters = *letters;
+            let mut remaining_blanks = blanks;
+            // Check if the word will start or end at a letter
+            let mut valid_loc = (col_idx != 0 && board.cell(row_idx, col_idx-1) != EMPTY_VALUE) || (board.size()-col_idx <= word.len() && board.cell(row_idx, col_idx+word.len()) != EMPTY_VALUE);
+            // Check if the word will border any letters on the top or bottom
+            valid_loc |= (col_idx..col_idx+word.len()).any(|c_idx| (row_idx < board.size()-1 && board.cell(row_idx+1, c_idx) != EMPTY_VALUE) || (row_idx > 0 && board.cell(row_idx-1, c_idx) != EMPTY_VALUE));
+            if !valid_loc {
+                Ok((false, played_indices, remaining_letters, LetterUsage::Remaining, remaining_blanks))
+            }
+            else {
+                debug_assert!(row_idx < board.size() && col_idx + word.len() <= board.size(), "Horizontal play of {} letters at (row {}, col {}) runs off a {}x{} board", word.len(), row_idx, col_idx, board.size(), board.size());
+                debug_assert!(word.iter().all(|&letter| (letter as usize) < N), "Word {:?} has a letter outside the alphabet of {} letters", word, N);
+                let mut entirely_overlaps = true;
+                for i in 0..word.len() {
+                    if board.cell(row_idx, col_idx+i) == EMPTY_VALUE {
+                        board.place(row_idx, col_idx+i, word[i]);
+                        letters_on_board[word[i] as usize] += 1;
+                        played_indices.push((row_idx, col_idx+i));
+                        entirely_overlaps = false;
+                        let elem = letter_count_mut(&mut remaining_letters, word[i]);
+                        if *elem > 0 {
+                            *elem -= 1;
+                        }
+                        else if remaining_blanks > 0 {
+                            remaining_blanks -= 1;
+                            board.set_blank(row_idx, col_idx+i);
+                        }
+                        else {
+                            return Ok((false, played_indices, remaining_letters, LetterUsage::Overused, remaining_blanks));
+                        }
+                    }
+                    else if board.cell(row_idx, col_idx+i) != word[i] {
+                        return Ok((false, played_indices, remaining_letters, LetterUsage::Remaining, remaining_blanks));
+                    }
+                }
+                let usage = letter_usage(&remaining_letters, remaining_blanks, letters_on_board, target_tiles);
+                Ok((!entirely_overlaps && !matches!(usage, LetterUsage::Overused), played_indices, remaining_letters, usage, remaining_blanks))
+            }
+        },
+        Direction::Vertical => {
+            if col_idx >= board.size() || row_idx + word.len() >= board.size() {
+                return Err(());
+            }
+            let mut remaining_letters = *letters;
+            let mut remaining_blanks = blanks;
+            // Check if the word will start or end at a letter
+            let mut valid_loc = (row_idx != 0 && board.cell(row_idx-1, col_idx) != EMPTY_VALUE) || (board.size()-row_idx <= word.len() && board.cell(row_idx+word.len(), col_idx) != EMPTY_VALUE);
+            // Check if the word will border any letters on the right or left
+            valid_loc |= (row_idx..row_idx+word.len()).any(|r_idx| (col_idx < board.size()-1 && board.cell(r_idx, col_idx+1) != EMPTY_VALUE) || (col_idx > 0 && board.cell(r_idx, col_idx-1) != EMPTY_VALUE));
+            if !valid_loc {
+                Ok((false, played_indices, remaining_letters, LetterUsage::Remaining, remaining_blanks))
+            }
+            else {
+                debug_assert!(col_idx < board.size() && row_idx + word.len() <= board.size(), "Vertical play of {} letters at (row {}, col {}) runs off a {}x{} board", word.len(), row_idx, col_idx, board.size(), board.size());
+                debug_assert!(word.iter().all(|&letter| (letter as usize) < N), "Word {:?} has a letter outside the alphabet of {} letters", word, N);
+                let mut entirely_overlaps = true;
+                for i in 0..word.len() {
+                    if board.cell(row_idx+i, col_idx) == EMPTY_VALUE {
+                        board.place(row_idx+i, col_idx, word[i]);
+                        letters_on_board[word[i] as usize] += 1;
+                        played_indices.push((row_idx+i, col_idx));
+                        entirely_overlaps = false;
+                        let elem = letter_count_mut(&mut remaining_letters, word[i]);
+                        if *elem > 0 {
+                            *elem -= 1;
+                        }
+                        else if remaining_blanks > 0 {
+                            remaining_blanks -= 1;
+                            board.set_blank(row_idx+i, col_idx);
+                        }
+                        else {
+                            return Ok((false, played_indices, remaining_letters, LetterUsage::Overused, remaining_blanks));
+                        }
+                    }
+                    else if board.cell(row_idx+i, col_idx) != word[i] {
+                        return Ok((false, played_indices, remaining_letters, LetterUsage::Remaining, remaining_blanks));
+                    }
+                }
+                let usage = letter_usage(&remaining_letters, remaining_blanks, letters_on_board, target_tiles);
+                Ok((!entirely_overlaps && !matches!(usage, LetterUsage::Overused), played_indices, remaining_letters, usage, remaining_blanks))
+            }
+        }
+    }
+}
+
+/// Checks which words can be played after the first
+/// # Arguments
+/// * `letters` - Array of the letters remaining in the hand after the first word
+/// * `blanks` - Number of blanks remaining in the hand after the first word
+/// * `word_being_checked` - Word that is being checked if playable
+/// * `played_on_board` - Set of the letters played on the board
+/// # Returns
+/// * `bool` - Whether the `word_being_checked` is playable using the remaining `letters` and `blanks` plus at most one of the `played_on_board`
+///   letters
+fn check_filter_after_play<const N: usize>(mut letters: Letters<N>, mut blanks: u8, word_being_checked: &Word, played_on_board: &HashSet<&u8>) -> bool {
+    let mut num_from_board = 0u8;
+    for letter in word_being_checked.iter() {
+        let num_in_hand = letter_count_mut(&mut letters, *letter);
+        if *num_in_hand == 0 {
+            // A word played against only the first word can cross it at most once, and a blank can stand for any other missing letter
+            if num_from_board == 0 && played_on_board.contains(letter) {
+                num_from_board += 1;
+            }
+            else if blanks > 0 {
+                blanks -= 1;
+            }
+            else {
+                return false;
+            }
+        }
+        else {
+            *num_in_hand -= 1;
+        }
+    }
+    true
+}
+
+/// Removes words that can't be played with `current_letters` plus a set number of `board_letters`
+/// # Arguments
+/// * `current_letters` - Letters currently available in the hand
+/// * `board_letters` - Letters played on the board
+/// * `blanks` - Blanks currently available in the hand
+/// * `word_being_checked` - Word to check if it contains the appropriate number of letters
+/// # Returns
+/// * `bool` - Whether `word_being_checked` should pass the filter
+fn check_filter_after_play_later<const N: usize>(mut current_letters: Letters<N>, mut board_letters: Letters<N>, mut blanks: u8, word_being_checked: &Word) -> bool {
+    let mut num_from_board = 0u8;
+    for letter in word_being_checked.iter() {
+        let num_in_hand = letter_count_mut(&mut current_letters, *letter);
+        if *num_in_hand == 0 {
+            let num_on_board = letter_count_mut(&mut board_letters, *letter);
+            if num_from_board < FILTER_LETTERS_ON_BOARD && *num_on_board > 0 {
+                *num_on_board -= 1;
+                num_from_board += 1;
+            }
+            else if blanks > 0 {
+                blanks -= 1;
+            }
+            else {
+                return false;
+            }
+        }
+        else {
+            *num_in_hand -= 1;
+        }
+    }
+    true
+}
+
+/// Undoes a play on the `board`
+/// # Arguments
+/// * `board` - `Board` being undone (is modified in-place)
+/// * `played_indices` - Vector of the indices in `board` that need to be reset (any played with a blank are unmarked, returning the blank to
+///   the hand)
+/// * `letters_on_board` - Array of the number of each letter on the board (is modified in place)
+/// * `bounds` - The (minimum column, maximum column, minimum row, maximum row) of the tiles on the `board` before the play
+fn undo_play<const N: usize>(board: &mut Board, played_indices: &[(usize, usize)], letters_on_board: &mut Letters<N>, bounds: (usize, usize, usize, usize)) {
+    for index in played_indices.iter() {
+        letters_on_board[board.cell(index.0, index.1) as usize] -= 1;
+    }
+    board.remove_tiles(played_indices, bounds);
+}
+
+/// Finds the positions at which a word could be played so that it touches at least one letter already on the board
+/// (the only positions `play_word` will accept), rather than trying every position around the board
+/// # Arguments
+/// * `occupied` - The (row, column) of every letter on the board
+/// * `word_len` - Length of the word to play
+/// * `direction` - The `Direction` in which to play the word
+/// * `min_col` - Minimum occupied column index
+/// * `max_col` - Maximum occupied column index
+/// * `min_row` - Minimum occupied row index
+/// * `max_row` - Maximum occupied row index
+/// * `positions` - Vector to put the (row, column) positions at which to start the word in (replacing its contents), in the same order they
+///   would be reached by scanning the area around the board (row by row for horizontal words, column by column for vertical words)
+#[allow(clippy::too_many_arguments, reason = "the placement and the bounds of the board are separate, as the solver tracks them")]
+fn anchored_positions(occupied: &[(usize, usize)], word_len: usize, direction: Direction, min_col: usize, max_col: usize, min_row: usize, max_row: usize, positions: &mut Vec<(usize, usize)>) {
+    positions.clear();
+    match direction {
+        Direction::Horizontal => {
+            let (row_lo, row_hi) = (min_row.saturating_sub(1), max_row+1);
+            let (col_lo, col_hi) = (min_col.saturating_sub(word_len), max_col+1);
+            for &(row, col) in occupied.iter() {
+                // Letters directly above or below the word
+                for r in [row.checked_sub(1), Some(row+1)].into_iter().flatten().filter(|r| (row_lo..=row_hi).contains(r)) {
+                    for c in cmp::max(col_lo, (col+1).saturating_sub(word_len))..=cmp::min(col_hi, col) {
+                        positions.push((r, c));
+                    }
+                }
+                // A letter directly before the word
+                if (row_lo..=row_hi).contains(&row) && col < col_hi {
+                    positions.push((row, col+1));
+                }
+            }
+            positions.sort_unstable();
+        },
+        Direction::Vertical => {
+            let (col_lo, col_hi) = (min_col.saturating_sub(1), max_col+1);
+            let (row_lo, row_hi) = (min_row.saturating_sub(word_len), max_row+1);
+            for &(row, col) in occupied.iter() {
+                // Letters directly left or right of the word
+                for c in [col.checked_sub(1), Some(col+1)].into_iter().flatten().filter(|c| (col_lo..=col_hi).contains(c)) {
+                    for r in cmp::max(row_lo, (row+1).saturating_sub(word_len))..=cmp::min(row_hi, row) {
+                        positions.push((r, c));
+                    }
+                }
+                // A letter directly above the word
+                if (col_lo..=col_hi).contains(&col) && row < row_hi {
+                    positions.push((row+1, col));
+                }
+            }
+            positions.sort_unstable_by_key(|&(r, c)| (c, r));
+        }
+    }
+    positions.dedup();
+}
+
+/// Checks whether a deadline has passed
+/// # Arguments
+/// * `deadline` - The deadline, if any
+/// # Returns
+/// * `bool` - Whether there is a deadline and it has passed
+fn past_deadline(deadline: Option<Instant>) -> bool {
+    deadline.is_some_and(|deadline| Instant::now() >= deadline)
+}
+
+/// The best board found for a hand that couldn't be solved, with the letters left over (see `play_bananagrams`)
+#[derive(Clone)]
+pub struct PartialSolution<const N: usize = ENGLISH_ALPHABET_SIZE> {
+    /// The board, on which every word is valid
+    pub board: Board,
+    /// Minimum occupied column index
+    pub min_col: usize,
+    /// Maximum occupied column index
+    pub max_col: usize,
+    /// Minimum occupied row index
+    pub min_row: usize,
+    /// Maximum occupied row index
+    pub max_row: usize,
+    /// The words played to make the board, in order
+    pub plays: Vec<PlayedWord>,
+    /// The number of each letter of the hand left unplayed
+    pub unused_letters: Letters<N>,
+    /// The number of blanks left unplayed
+    pub unused_blanks: u8
+}
+
+/// How hard the solver found it to solve a hand, returned with each solution
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SolveMetrics {
+    /// Number of words checked to find the solution (see `MAXIMUM_WORDS_CHECKED`)
+    pub words_checked: usize,
+    /// Depth of the search the solution was found at, which is the number of words played to make it
+    pub depth: usize,
+    /// Number of first words tried before the one the solution starts with (each of which was given up on, unless solving in parallel)
+    pub failed_first_words: usize
+}
+
+/// A solved hand (see `play_bananagrams`)
+#[derive(Debug, Clone)]
+pub struct Solution {
+    /// The board, on which every word is valid
+    pub board: Board,
+    /// Minimum occupied column index
+    pub min_col: usize,
+    /// Maximum occupied column index
+    pub max_col: usize,
+    /// Minimum occupied row index
+    pub min_row: usize,
+    /// Maximum occupied row index
+    pub max_row: usize,
+    /// The words played to make the board, in order
+    pub plays: Vec<PlayedWord>,
+    /// How hard the solution was to find
+    pub metrics: SolveMetrics
+}
+impl Solution {
+    /// Creates a `Solution` from a board found by the search
+    /// # Arguments
+    /// * `found` - The board, its bounds, and the words played to make it
+    /// * `words_checked` - Number of words checked to find it
+    /// * `failed_first_words` - Number of first words tried before the one it starts with
+    fn new(found: Found, words_checked: usize, failed_first_words: usize) -> Solution {
+        let (board, min_col, max_col, min_row, max_row, plays) = found;
+        let metrics = SolveMetrics { words_checked, depth: plays.len(), failed_first_words };
+        Solution { board, min_col, max_col, min_row, max_row, plays, metrics }
+    }
+}
+/// Why a hand wasn't solved: the reason, the number of words checked before giving up, and the best partial board if one was asked for
+/// (see `play_bananagrams`)
+pub type Unsolved<const N: usize = ENGLISH_ALPHABET_SIZE> = (SolveFailure, usize, Option<Box<PartialSolution<N>>>);
+/// A board found by the search, with its bounds and the words played to make it (a `Solution` before its metrics are added)
+type Found = (Board, usize, usize, usize, usize, Vec<PlayedWord>);
+
+/// Finds how hard each letter is to play
+/// # Arguments
+/// * `words` - The words that can be played
+/// # Returns
+/// * `[f64; N]` - The reciprocal of one more than the number of `words` each letter appears in
+fn letter_rarity<const N: usize>(words: &[Word]) -> [f64; N] {
+    let mut containing = [0usize; N];
+    for word in words.iter() {
+        let letters: HashSet<&u8> = word.iter().collect();
+        for &letter in letters {
+            containing[letter as usize] += 1;
+        }
+    }
+    containing.map(|count| 1.0 / (count + 1) as f64)
+}
+
+/// Keeps track of the best partial board seen while solving a hand
+struct PartialTracker<const N: usize> {
+    /// How hard each letter is to play, as the reciprocal of one more than the number of words it appears in
+    rarity: [f64; N],
+    /// The best partial board so far, with its number of unused tiles and the total rarity of its unused letters
+    best: Option<(usize, f64, PartialSolution<N>)>
+}
+impl<const N: usize> PartialTracker<N> {
+    /// Creates a new `PartialTracker`
+    /// # Arguments
+    /// * `words` - The words that can be played, from which the rarity of each letter is found
+    fn new(words: &[Word]) -> PartialTracker<N> {
+        PartialTracker { rarity: letter_rarity(words), best: None }
+    }
+
+    /// Records a board if it has more tiles than the best so far, or as many tiles but fewer rare letters left over (the board is only copied
+    /// if so)
+    /// # Arguments
+    /// * `board` - The board
+    /// * `min_col` - Minimum occupied column index
+    /// * `max_col` - Maximum occupied column index
+    /// * `min_row` - Minimum occupied row index
+    /// * `max_row` - Maximum occupied row index
+    /// * `plays` - The words played to make the board
+    /// * `unused_letters` - The number of each letter left in the hand
+    /// * `unused_blanks` - The number of blanks left in the hand (which count as tiles left over, but not as rare letters)
+    #[allow(clippy::too_many_arguments, reason = "the partial board is passed piece by piece, as the solver tracks it")]
+    fn record(&mut self, board: &Board, min_col: usize, max_col: usize, min_row: usize, max_row: usize, plays: &[PlayedWord], unused_letters: &Letters<N>, unused_blanks: u8) {
+        let unused_tiles = unused_letters.iter().map(|&count| count as usize).sum::<usize>() + unused_blanks as usize;
+        let unused_rarity: f64 = unused_letters.iter().zip(self.rarity.iter()).map(|(&count, rarity)| count as f64 * rarity).sum();
+        if let Some((best_tiles, best_rarity, _)) = &self.best {
+            if (unused_tiles, unused_rarity) >= (*best_tiles, *best_rarity) {
+                return;
+            }
+        }
+        let solution = PartialSolution { board: board.clone(), min_col, max_col, min_row, max_row, plays: plays.to_vec(), unused_letters: *unused_letters, unused_blanks };
+        self.best = Some((unused_tiles, unused_rarity, solution));
+    }
+}
+
+/// Remembers the states the solver failed to finish a board from (the tiles on the board, the letters left in the hand, and the words left to
+/// try), so that a state reached again by playing the same words in another order isn't searched again
+///
+/// Only 64-bit hashes of the states are kept, so a state can (very rarely) be skipped for colliding with one that failed. To bound memory, the
+/// hashes are kept in two generations: once the current one is half of `MEMO_CAPACITY`, it becomes the previous one (dropping the one
+/// before), and hashes found in the previous generation are moved back into the current one.
+struct FailureMemo {
+    /// Hasher of the states
+    hasher: DefaultHashBuilder,
+    /// Hashes of failed states remembered or found again since the last change of generation
+    current: HashSet<u64>,
+    /// Hashes of failed states from the generation before
+    previous: HashSet<u64>
+}
+impl FailureMemo {
+    /// Creates a new, empty `FailureMemo`
+    fn new() -> FailureMemo {
+        FailureMemo { hasher: DefaultHashBuilder::default(), current: HashSet::new(), previous: HashSet::new() }
+    }
+
+    /// Hashes a state of the solver
+    /// # Arguments
+    /// * `board` - The board
+    /// * `min_col` - Minimum occupied column index in `board`
+    /// * `max_col` - Maximum occupied column index in `board`
+    /// * `min_row` - Minimum occupied row index in `board`
+    /// * `max_row` - Maximum occupied row index in `board`
+    /// * `letters` - Array of the number of each letter left in the hand
+    /// * `blanks` - Number of blanks left in the hand
+    /// * `words` - The words left to try (which stay at the same addresses while a hand is solved, so only those are hashed)
+    /// # Returns
+    /// * `u64` - The hash
+    #[allow(clippy::too_many_arguments, reason = "the solver's state is passed piece by piece, as the solver tracks it")]
+    fn key<const N: usize>(&self, board: &Board, min_col: usize, max_col: usize, min_row: usize, max_row: usize, letters: &Letters<N>, blanks: u8, words: &[&Word]) -> u64 {
+        let mut hasher = self.hasher.build_hasher();
+        (min_col, max_col, min_row, max_row, letters, blanks).hash(&mut hasher);
+        for row in min_row..max_row+1 {
+            for col in min_col..max_col+1 {
+                board.get_val(row, col).hash(&mut hasher);
+            }
+        }
+        for &word in words.iter() {
+            (word as *const Word as usize).hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+
+    /// Checks whether a state is known to fail
+    /// # Arguments
+    /// * `key` - Hash of the state (see `key`)
+    fn contains(&mut self, key: u64) -> bool {
+        if self.current.contains(&key) {
+            true
+        }
+        else if self.previous.remove(&key) {
+            self.insert(key);
+            true
+        }
+        else {
+            false
+        }
+    }
+
+    /// Remembers that a state failed
+    /// # Arguments
+    /// * `key` - Hash of the state (see `key`)
+    fn insert(&mut self, key: u64) {
+        if self.current.len() >= MEMO_CAPACITY/2 {
+            self.previous = std::mem::take(&mut self.current);
+        }
+        self.current.insert(key);
+    }
+}
+
+/// The order in which the solver tries the two directions at each depth of its search, alternating horizontal-vertical-horizontal as a
+/// heuristic to solve faster (see `SearchFrame::new`)
+const HORIZONTAL_FIRST: [Direction; 2] = [Direction::Horizontal, Direction::Vertical];
+/// See `HORIZONTAL_FIRST`
+const VERTICAL_FIRST: [Direction; 2] = [Direction::Vertical, Direction::Horizontal];
+/// See `HORIZONTAL_FIRST`; there's no point in playing horizontally at the first depth after the first word, since that would have to form a
+/// vertical word that was already tried and failed
+const VERTICAL_ONLY: [Direction; 1] = [Direction::Vertical];
+
+/// Buffers reused throughout the search of one hand, so that trying a play and moving to the next depth don't allocate
+struct Scratch<'a> {
+    /// Buffer for the letters of each run checked after a play (see `is_board_valid_horizontal_reusing`)
+    run: Word,
+    /// Emptied vectors of the words that could be played, left by the depths already searched, to refill at the depths reached next
+    candidates: Vec<Vec<&'a Word>>,
+    /// Emptied vectors of the indices filled by plays that were undone, to record the next plays in (see `play_word`)
+    played: Vec<Vec<(usize, usize)>>
+}
+impl<'a> Scratch<'a> {
+    /// Creates a new, empty `Scratch`
+    fn new() -> Scratch<'a> {
+        Scratch { run: Word::new(), candidates: Vec::new(), played: Vec::new() }
+    }
+
+    /// Takes an empty vector for the words that could be played at a depth
+    /// # Arguments
+    /// * `capacity` - Number of words the vector should have room for if a new one has to be allocated
+    /// # Returns
+    /// * `Vec<&Word>` - An empty vector, reused if one was given back
+    fn take_candidates(&mut self, capacity: usize) -> Vec<&'a Word> {
+        self.candidates.pop().unwrap_or_else(|| Vec::with_capacity(capacity))
+    }
+
+    /// Gives back a vector of words once its depth has been searched, to be reused
+    /// # Arguments
+    /// * `candidates` - The vector (it's cleared, keeping its allocation)
+    fn give_candidates(&mut self, mut candidates: Vec<&'a Word>) {
+        candidates.clear();
+        self.candidates.push(candidates);
+    }
+
+    /// Takes an empty vector to record the indices filled by a play in
+    /// # Returns
+    /// * `Vec<(usize, usize)>` - An empty vector, reused if one was given back
+    fn take_played(&mut self) -> Vec<(usize, usize)> {
+        self.played.pop().unwrap_or_else(|| Vec::with_capacity(MAX_WORD_LENGTH))
+    }
+
+    /// Gives back a vector of the indices filled by a play once it's been undone, to be reused
+    /// # Arguments
+    /// * `played` - The vector (it's cleared, keeping its allocation)
+    fn give_played(&mut self, mut played: Vec<(usize, usize)>) {
+        played.clear();
+        self.played.push(played);
+    }
+}
+
+/// The state of the solver's search for one hand, carried from each board it searches from to the next (and from each first word tried to
+/// the next)
+struct Search<'a, const N: usize> {
+    /// HashSet of every word that could end up on the board
+    valid_words_set: &'a HashSet<Word>,
+    /// Minimum length of a word on the board
+    min_word_length: usize,
+    /// Number of tiles at which the board is finished even if letters remain, if any
+    target_tiles: Option<usize>,
+    /// Maximum number of words to check before giving up
+    max_words_checked: usize,
+    /// Flag that, once set, makes the solver give up as soon as possible, if any
+    stop: Option<&'a AtomicBool>,
+    /// Flag set once the search is no longer needed because a solution was found elsewhere, if it's searching in parallel
+    superseded: Option<&'a AtomicBool>,
+    /// Time after which the solver gives up, if any
+    deadline: Option<Instant>,
+    /// The number of words checked in total
+    words_checked: usize,
+    /// Deepest depth reached so far while solving this hand
+    deepest_depth: usize,
+    /// Tracker of the best partial board, if one is wanted when the hand can't be solved
+    partial: Option<PartialTracker<N>>,
+    /// States already found to fail, if they're being remembered
+    memo: Option<FailureMemo>,
+    /// Buffers to reuse rather than allocate while searching
+    scratch: Scratch<'a>
+}
+impl<'a, const N: usize> Search<'a, N> {
+    /// Creates a new `Search` that hasn't checked any words, without a partial board tracker
+    /// # Arguments
+    /// * `valid_words_set` - HashSet of every word that could end up on the board
+    /// * `options` - The options of the solver (of which only the limits on the search, `min_word_length`, `target_tiles`, and `memoize`
+    ///   are used)
+    fn new(valid_words_set: &'a HashSet<Word>, options: &SolveOptions<'a>) -> Search<'a, N> {
+        Search {
+            valid_words_set,
+            min_word_length: options.min_word_length,
+            target_tiles: options.target_tiles,
+            max_words_checked: options.max_words_checked,
+            stop: options.stop,
+            superseded: None,
+            deadline: options.deadline,
+            words_checked: 0,
+            deepest_depth: 0,
+            partial: None,
+            memo: if options.memoize { Some(FailureMemo::new()) } else { None },
+            scratch: Scratch::new()
+        }
+    }
+
+    /// Checks whether the search has been stopped
+    /// # Returns
+    /// * `bool` - Whether `stop` or `superseded` is set
+    fn stopped(&self) -> bool {
+        [self.stop, self.superseded].iter().flatten().any(|flag| flag.load(Ordering::Relaxed))
+    }
+}
+
+/// One depth of the solver's search: a board reached by playing some words, and how far through the plays that could follow it the solver is
+struct SearchFrame<'a, const N: usize> {
+    /// The (minimum column, maximum column, minimum row, maximum row) of the tiles on the board, to restore when a play from it is undone
+    bounds: (usize, usize, usize, usize),
+    /// The words that could still be played (see `check_filter_after_play_later`)
+    words: Vec<&'a Word>,
+    /// Array of the number of each letter left in the hand
+    letters: Letters<N>,
+    /// Number of blanks left in the hand
+    blanks: u8,
+    /// Every letter on the board, from which the positions worth trying to play at are found
+    occupied: Vec<(usize, usize)>,
+    /// The directions to try the words in, in order
+    directions: &'static [Direction],
+    /// Index into `directions` of the direction being tried
+    direction: usize,
+    /// Index into `words` of the word being tried, or `None` before the first word of the direction
+    word: Option<usize>,
+    /// The positions the word being tried could be played at (see `anchored_positions`)
+    positions: Vec<(usize, usize)>,
+    /// Index into `positions` of the next position to try
+    position: usize,
+    /// The cells filled by the play the next depth is searching from, if there is one (to undo once it fails)
+    played: Option<Vec<(usize, usize)>>,
+    /// Hash of this state, if states found to fail are being remembered (see `FailureMemo`)
+    key: Option<u64>
+}
+impl<'a, const N: usize> SearchFrame<'a, N> {
+    /// Creates a new `SearchFrame` at the start of its search
+    /// # Arguments
+    /// * `board` - The board reached
+    /// * `words` - The words that could still be played
+    /// * `letters` - Array of the number of each letter left in the hand
+    /// * `blanks` - Number of blanks left in the hand
+    /// * `depth` - Number of words played since the first word
+    /// * `key` - Hash of the state, if states found to fail are being remembered
+    fn new(board: &Board, words: Vec<&'a Word>, letters: Letters<N>, blanks: u8, depth: usize, key: Option<u64>) -> SearchFrame<'a, N> {
+        let bounds = board.bounds().expect("Searching from an empty board!");
+        let (min_col, max_col, min_row, max_row) = bounds;
+        let occupied: Vec<(usize, usize)> = (min_row..max_row+1)
+            .flat_map(|row| (min_col..max_col+1).map(move |col| (row, col)))
+            .filter(|(row, col)| board.get_val(*row, *col) != EMPTY_VALUE)
+            .collect();
+        let directions: &'static [Direction] = match depth {
+            0 => &VERTICAL_ONLY,
+            depth if depth % 2 == 1 => &HORIZONTAL_FIRST,
+            _ => &VERTICAL_FIRST
+        };
+        SearchFrame { bounds, words, letters, blanks, occupied, directions, direction: 0, word: None, positions: Vec::new(), position: 0, played: None, key }
+    }
+}
+
+/// Solves Bananagrams from a board with the first word played, by depth-first search over the words that can be played next
+///
+/// The search keeps its own stack of `SearchFrame`s rather than recursing, so that a hand needing many words can't overflow the call stack.
+/// # Arguments
+/// * `board` - The `Board` to modify in-place
+/// * `valid_words_vec` - Vector of vectors, each representing a word (see `convert_word_to_array`)
+/// * `letters` - Array of the number of each letter in the hand
+/// * `blanks` - Number of blanks in the hand
+/// * `plays` - The words played so far, in order (words are added as they're played and removed when undone)
+/// * `letters_on_board` - Array of the number of each letter currently present on the `board`
+/// * `search` - The state of the search, with the words that could end up on the board and when to give up
+/// # Returns
+/// *`Result` with:*
+/// * `bool` - Whether the board was solved (in which case `board` and `plays` are left as the solution)
+/// 
+/// *or empty `Err` on if out-of-bounds, past the maximum number of words to check or the deadline, or stopped*
+fn play_further<'a, const N: usize>(board: &mut Board, valid_words_vec: Vec<&'a Word>, letters: Letters<N>, blanks: u8, plays: &mut Vec<PlayedWord>, letters_on_board: &mut Letters<N>, search: &mut Search<'a, N>) -> Result<bool, ()> {
+    let mut stack: Vec<SearchFrame<N>> = Vec::new();
+    // The first board searched from, and after that each board reached by a valid play that leaves letters in the hand
+    let mut next = Some((valid_words_vec, letters, blanks));
+    let result = loop {
+        if let Some((words, letters, blanks)) = next.take() {
+            let depth = stack.len();
+            let key = search.memo.as_ref().map(|memo| {
+                let (min_col, max_col, min_row, max_row) = board.bounds().expect("Searching from an empty board!");
+                memo.key(board, min_col, max_col, min_row, max_row, &letters, blanks, &words)
+            });
+            if key.is_some_and(|key| search.memo.as_mut().is_some_and(|memo| memo.contains(key))) {
+                // Already known to fail, so undo the play that reached it straight away
+                search.scratch.give_candidates(words);
+                match stack.last_mut() {
+                    Some(parent) => {
+                        plays.pop();
+                        let played = parent.played.take().expect("Play to undo is missing!");
+                        undo_play(board, &played, letters_on_board, parent.bounds);
+                        search.scratch.give_played(played);
+                        continue;
+                    },
+                    None => break Ok(false)
+                }
+            }
+            if search.words_checked > search.max_words_checked || search.stopped() {
+                if search.words_checked > search.max_words_checked {
+                    trace!("Word budget of {} exhausted at depth {} (deepest depth reached {})", search.max_words_checked, depth, search.deepest_depth);
+                }
+                break Err(());
+            }
+            if depth > search.deepest_depth {
+                search.deepest_depth = depth;
+                trace!("Reached depth {} after checking {} words", depth, search.words_checked);
+            }
+            stack.push(SearchFrame::new(board, words, letters, blanks, depth, key));
+        }
+        let frame = stack.last_mut().expect("Search stack is empty!");
+        // Move on to the next position to try, or to the next word (or direction) once the word has been tried at every position
+        if frame.position >= frame.positions.len() {
+            let word = frame.word.map_or(0, |word| word+1);
+            if word < frame.words.len() {
+                frame.word = Some(word);
+            }
+            else if frame.direction + 1 < frame.directions.len() {
+                frame.direction += 1;
+                frame.word = if frame.words.is_empty() { None } else { Some(0) };
+            }
+            else {
+                frame.word = None;
+            }
+            match frame.word {
+                Some(word) => {
+                    search.words_checked += 1;
+                    if search.words_checked.is_multiple_of(DEADLINE_CHECK_INTERVAL) && past_deadline(search.deadline) {
+                        break Err(());
+                    }
+                    // Try every position where the word would touch a letter already on the board
+                    let (min_col, max_col, min_row, max_row) = frame.bounds;
+                    anchored_positions(&frame.occupied, frame.words[word].len(), frame.directions[frame.direction], min_col, max_col, min_row, max_row, &mut frame.positions);
+                    frame.position = 0;
+                },
+                None => {
+                    // Every play from this board failed, so go back to the board before it
+                    let frame = stack.pop().expect("Search stack is empty!");
+                    if let (Some(key), Some(memo)) = (frame.key, search.memo.as_mut()) {
+                        memo.insert(key);
+                    }
+                    search.scratch.give_candidates(frame.words);
+                    match stack.last_mut() {
+                        Some(parent) => {
+                            // Undo the previous play (cloning the board before each play so we don't have to undo is *way* slower)
+                            plays.pop();
+                            let played = parent.played.take().expect("Play to undo is missing!");
+                            undo_play(board, &played, letters_on_board, parent.bounds);
+                            search.scratch.give_played(played);
+                        },
+                        None => break Ok(false)
+                    }
+                }
+            }
+            continue;
+        }
+        let word = frame.words[frame.word.expect("No word being tried!")];
+        let direction = frame.directions[frame.direction];
+        let (row_idx, col_idx) = frame.positions[frame.position];
+        frame.position += 1;
+        // `play_word` gives an `Err` if the index is out of bounds
+        let res = match play_word(word, row_idx, col_idx, board, direction, &frame.letters, frame.blanks, letters_on_board, search.target_tiles, search.scratch.take_played()) {
+            Ok(res) => res,
+            Err(()) => break Err(())
+        };
+        if !res.0 {
+            // If trying to play the board was invalid, undo the play
+            undo_play(board, &res.1, letters_on_board, frame.bounds);
+            search.scratch.give_played(res.1);
+            continue;
+        }
+        // If the word was played successfully (i.e. it's not a complete overlap and it borders at least one existing tile), then check the validity of the new words it forms
+        let (new_min_col, new_max_col, new_min_row, new_max_row) = board.bounds().expect("Board is empty after a play!");
+        let valid = match direction {
+            Direction::Horizontal => is_board_valid_horizontal_reusing(board, new_min_col, new_max_col, new_min_row, new_max_row, row_idx, col_idx, col_idx+word.len()-1, search.valid_words_set, search.min_word_length, &mut search.scratch.run),
+            Direction::Vertical => is_board_valid_vertical_reusing(board, new_min_col, new_max_col, new_min_row, new_max_row, row_idx, row_idx+word.len()-1, col_idx, search.valid_words_set, search.min_word_length, &mut search.scratch.run)
+        };
+        if !valid {
+            // If the play formed some invalid words, undo the previous play
+            undo_play(board, &res.1, letters_on_board, frame.bounds);
+            search.scratch.give_played(res.1);
+            continue;
+        }
+        plays.push(PlayedWord { word: word.clone(), row: row_idx, col: col_idx, direction });
+        if let Some(partial) = search.partial.as_mut() {
+            partial.record(board, new_min_col, new_max_col, new_min_row, new_max_row, plays, &res.2, res.4);
+        }
+        match res.3 {
+            // If we've used all the letters or reached the target number of tiles, we're done!
+            LetterUsage::Finished | LetterUsage::TargetReached => {
+                break Ok(true);
+            },
+            LetterUsage::Remaining => {
+                // Otherwise search on from the new board, with the words that can still be played
+                let mut new_valid_words_vec = search.scratch.take_candidates(frame.words.len()/2);
+                for &other_word in frame.words.iter() {
+                    if check_filter_after_play_later(frame.letters, *letters_on_board, frame.blanks, other_word) {
+                        new_valid_words_vec.push(other_word);
+                    }
+                }
+                frame.played = Some(res.1);
+                next = Some((new_valid_words_vec, res.2, res.4));
+            },
+            LetterUsage::Overused => unreachable!()
+        }
+    };
+    // Keep the vectors of the depths left unfinished for the next search
+    for frame in stack {
+        search.scratch.give_candidates(frame.words);
+    }
+    result
+}
+
+/// Why the solver gave up on a hand
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolveFailure {
+    /// No word in the dictionary can be made from the hand
+    NoWords,
+    /// Every way of playing the hand was tried without finding a solution
+    Unsolvable,
+    /// The solver checked the maximum number of words it was allowed to
+    WordBudget,
+    /// The solver ran out of time
+    Timeout,
+    /// The solver was stopped
+    Stopped,
+    /// The solution found has a banned word on it (see `find_banned_words`)
+    BannedWord,
+    /// The board to complete isn't a legal board (see `complete_board`)
+    InvalidBoard
+}
+impl SolveFailure {
+    /// Every reason, in the order of their labels
+    pub const ALL: [SolveFailure; 7] = [SolveFailure::NoWords, SolveFailure::Unsolvable, SolveFailure::WordBudget, SolveFailure::Timeout, SolveFailure::Stopped, SolveFailure::BannedWord, SolveFailure::InvalidBoard];
+
+    /// Gets the name identifying the reason in files
+    /// # Returns
+    /// * `&'static str` - The name, in snake case
+    pub fn name(self) -> &'static str {
+        match self {
+            SolveFailure::NoWords => "no_words",
+            SolveFailure::Unsolvable => "unsolvable",
+            SolveFailure::WordBudget => "word_budget",
+            SolveFailure::Timeout => "timeout",
+            SolveFailure::Stopped => "stopped",
+            SolveFailure::BannedWord => "banned_word",
+            SolveFailure::InvalidBoard => "invalid_board"
+        }
+    }
+
+    /// Gets the reason with the given name
+    /// # Arguments
+    /// * `name` - Name of the reason (see `name`)
+    /// # Returns
+    /// * `Option<SolveFailure>` - The reason, or `None` if there isn't one with that name
+    pub fn from_name(name: &str) -> Option<SolveFailure> {
+        SolveFailure::ALL.into_iter().find(|failure| failure.name() == name)
+    }
+
+    /// Gets the label byte identifying the reason in a labeled `.bgb` file (such as the partial boards written with `--allow-partial`)
+    pub fn label(self) -> u8 {
+        self as u8
+    }
+
+    /// Gets the reason identified by a label byte
+    /// # Arguments
+    /// * `label` - Label byte from a labeled `.bgb` file
+    /// # Returns
+    /// * `Option<SolveFailure>` - The reason, or `None` if `label` isn't the label of one
+    pub fn from_label(label: u8) -> Option<SolveFailure> {
+        SolveFailure::ALL.get(label as usize).copied()
+    }
+}
+impl fmt::Display for SolveFailure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SolveFailure::NoWords => write!(f, "No words can be made from the hand"),
+            SolveFailure::Unsolvable => write!(f, "No solution exists"),
+            SolveFailure::WordBudget => write!(f, "Checked the maximum number of words"),
+            SolveFailure::Timeout => write!(f, "Ran out of time"),
+            SolveFailure::Stopped => write!(f, "Stopped"),
+            SolveFailure::BannedWord => write!(f, "The solution has a banned word"),
+            SolveFailure::InvalidBoard => write!(f, "The board to complete isn't legal")
+        }
+    }
+}
+
+/// How the solver orders the words it tries first (playing across the middle of the board), which decides the character of the board found
+/// much more than the words played after it; whether a hand can be solved doesn't depend on it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FirstWordStrategy {
+    /// In the order of the dictionary (usually longest first)
+    Longest,
+    /// In a random order, shuffled with the given seed
+    Random(u64),
+    /// Those with the most vowels (A, E, I, O, and U) first, then in the order of the dictionary
+    MostVowels,
+    /// Those whose letters appear in the fewest of the words makeable from the hand first (so that letters like Q, Z, X, and J are used up
+    /// early), then in the order of the dictionary
+    RarestLetters
+}
+impl FirstWordStrategy {
+    /// The name of every strategy (see `name`)
+    pub const NAMES: [&'static str; 4] = ["longest", "random", "most_vowels", "rarest_letters"];
+
+    /// Gets the name identifying the strategy (in options and summaries)
+    /// # Returns
+    /// * `&'static str` - The name, in snake case
+    pub fn name(self) -> &'static str {
+        match self {
+            FirstWordStrategy::Longest => "longest",
+            FirstWordStrategy::Random(_) => "random",
+            FirstWordStrategy::MostVowels => "most_vowels",
+            FirstWordStrategy::RarestLetters => "rarest_letters"
+        }
+    }
+
+    /// Gets the strategy with the given name
+    /// # Arguments
+    /// * `name` - Name of the strategy (see `name`)
+    /// * `seed` - Seed to shuffle with if the strategy is `Random`
+    /// # Returns
+    /// * `Option<FirstWordStrategy>` - The strategy, or `None` if there isn't one with that name
+    pub fn from_name(name: &str, seed: u64) -> Option<FirstWordStrategy> {
+        match name {
+            "longest" => Some(FirstWordStrategy::Longest),
+            "random" => Some(FirstWordStrategy::Random(seed)),
+            "most_vowels" => Some(FirstWordStrategy::MostVowels),
+            "rarest_letters" => Some(FirstWordStrategy::RarestLetters),
+            _ => None
+        }
+    }
+
+    /// Orders the words to try first
+    /// # Arguments
+    /// * `words` - The words makeable from the hand, in the order of the dictionary
+    /// # Returns
+    /// * `Vec<&Word>` - The `words` in the order to try them in
+    fn order<const N: usize>(self, words: &[Word]) -> Vec<&Word> {
+        let mut ordered: Vec<&Word> = words.iter().collect();
+        match self {
+            FirstWordStrategy::Longest => {},
+            FirstWordStrategy::Random(seed) => ordered.shuffle(&mut StdRng::seed_from_u64(seed)),
+            FirstWordStrategy::MostVowels => {
+                ordered.sort_by_key(|word| cmp::Reverse(word.iter().filter(|letter| VOWELS.contains(letter)).count()));
+            },
+            FirstWordStrategy::RarestLetters => {
+                let rarity: [f64; N] = letter_rarity(words);
+                let total_rarity = |word: &Word| word.iter().map(|&letter| rarity[letter as usize]).sum::<f64>();
+                ordered.sort_by(|a, b| total_rarity(b).total_cmp(&total_rarity(a)));
+            }
+        }
+        ordered
+    }
+}
+
+/// How the solver orders the words it tries after the first (at every depth of its search); whether a hand can be solved doesn't depend on it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchHeuristic {
+    /// In the order of the dictionary (usually longest first)
+    Dictionary,
+    /// Those using the most letters that are scarce in the hand first (scoring each word by the sum over its letters of one over the number
+    /// of that letter in the hand), then in the order of the dictionary, so that hard-to-place letters aren't left until last
+    RareFirst
+}
+impl SearchHeuristic {
+    /// Orders the words to try after the first
+    /// # Arguments
+    /// * `words` - The words makeable from the hand, in the order of the dictionary
+    /// * `available_letters` - Number of each letter in the hand
+    fn order<const N: usize>(self, words: &mut [Word], available_letters: &Letters<N>) {
+        match self {
+            SearchHeuristic::Dictionary => {},
+            SearchHeuristic::RareFirst => {
+                // Letters only playable with blanks count as if the hand had one
+                let inverse_counts: [f64; N] = available_letters.map(|count| 1.0 / count.max(1) as f64);
+                let score = |word: &Word| word.iter().map(|&letter| inverse_counts[letter as usize]).sum::<f64>();
+                words.sort_by(|a, b| score(b).total_cmp(&score(a)));
+            }
+        }
+    }
+}
+
+/// Options for `play_bananagrams` and the other functions that solve hands (each says which it uses); the `Default` is the solver's usual
+/// settings, so that only those that differ need to be set
+#[derive(Clone, Copy, Debug)]
+pub struct SolveOptions<'a> {
+    /// The letters in each word of the dictionary (see `letter_masks`), if already computed, to filter the dictionary by faster
+    pub dictionary_masks: Option<&'a [LetterMask]>,
+    /// Maximum number of words to check before giving up (see `MAXIMUM_WORDS_CHECKED`)
+    pub max_words_checked: usize,
+    /// Flag that, once set, makes the solver give up as soon as possible (such as when the user presses Ctrl-C), if any
+    pub stop: Option<&'a AtomicBool>,
+    /// Time after which to give up, if any (only checked every `DEADLINE_CHECK_INTERVAL` words, so it may be slightly overrun)
+    pub deadline: Option<Instant>,
+    /// Number of rows/columns in the board to solve on (see `BOARD_SIZE`)
+    pub board_size: usize,
+    /// Minimum length of a word on the board (shorter words in the dictionary are never played, and neither are crossings shorter than
+    /// this formed; see `DEFAULT_MIN_WORD_LENGTH`)
+    pub min_word_length: usize,
+    /// Number of tiles to finish the board with, if any: the board is solved once exactly this many tiles are on it even if letters remain
+    /// in the hand (no play may go past it), or once the hand is used up if it has fewer tiles
+    pub target_tiles: Option<usize>,
+    /// How to order the words tried first
+    pub first_word: FirstWordStrategy,
+    /// Word to always play first, if any, rather than trying words in the order of `first_word`; the solver fails with
+    /// `SolveFailure::NoWords` if it isn't in the dictionary or can't be made from the hand
+    pub anchor_word: Option<&'a Word>,
+    /// How to order the words tried after the first
+    pub heuristic: SearchHeuristic,
+    /// Whether to remember the states (board and hand) from which no solution was found so that they aren't searched again if reached by
+    /// playing the same words in another order, which saves time on large hands but costs time hashing states on small ones
+    pub memoize: bool,
+    /// Whether to keep track of the best board found (the one with the most tiles, then the fewest letters left over that appear in few
+    /// words) to return if the hand can't be solved
+    pub allow_partial: bool
+}
+impl Default for SolveOptions<'_> {
+    fn default() -> Self {
+        SolveOptions {
+            dictionary_masks: None,
+            max_words_checked: MAXIMUM_WORDS_CHECKED,
+            stop: None,
+            deadline: None,
+            board_size: BOARD_SIZE,
+            min_word_length: DEFAULT_MIN_WORD_LENGTH,
+            target_tiles: None,
+            first_word: FirstWordStrategy::Longest,
+            anchor_word: None,
+            heuristic: SearchHeuristic::Dictionary,
+            memoize: false,
+            allow_partial: false
+        }
+    }
+}
+
+/// Plays a new bananagrams board using the given letters and dictionary
+/// # Arguments
+/// * `available_letters` - Array of the number of each letter to play with
+/// * `blanks` - Number of blanks to play with, each of which can stand for any letter (tiles played with one are marked on the board, see
+///   `Board::is_blank`)
+/// * `dictionary` - Vector of vectors representing valid words (words with letters outside the alphabet of `available_letters` are ignored),
+///   tried in order (so usually sorted longest first, see `order_by_frequency`)
+/// * `options` - How to solve the hand, and when to give up
+/// # Returns
+/// * `Result`
+///     * `Ok` - If successful, the `Solution`
+///     * `Err` - A tuple of (why no solution was found, the number of words checked before giving up, the best partial board if
+///       `allow_partial` and the solver ran out of words to try, its word budget, or time)
+pub fn play_bananagrams<const N: usize>(available_letters: Letters<N>, blanks: u8, dictionary: &Vec<Word>, options: &SolveOptions) -> Result<Solution, Unsolved<N>> {
+    // Get a vector of all valid words (leaving out any too long to fit within the target, since every letter of a word is a tile on the board)
+    let mut valid_words_vec: Vec<Word> = makeable_words(dictionary, options.dictionary_masks, &available_letters, blanks, options.min_word_length, options.target_tiles);
+    if valid_words_vec.is_empty() {
+        return Err((SolveFailure::NoWords, 0, None));
+    }
+    // The first words are ordered before the heuristic is applied, since the heuristic only orders the later words (which keep the order
+    // of `valid_words_vec` as they're filtered at each depth)
+    let first_words: Vec<Word> = match first_words_to_try::<N>(options.first_word, options.anchor_word, &valid_words_vec) {
+        Some(first_words) => first_words,
+        None => return Err((SolveFailure::NoWords, 0, None))
+    };
+    let partial: Option<PartialTracker<N>> = if options.allow_partial { Some(PartialTracker::new(&valid_words_vec)) } else { None };
+    // Only words makeable from the hand can ever appear on the board, so the set is built once from those
+    let valid_words_set: HashSet<Word> = HashSet::from_iter(valid_words_vec.iter().cloned());
+    options.heuristic.order(&mut valid_words_vec, &available_letters);
+    let mut search = Search::new(&valid_words_set, options);
+    search.partial = partial;
+    // Loop through each word and play it on a new board
+    for (failed_first_words, word) in first_words.iter().enumerate() {
+        if search.stopped() {
+            return Err((SolveFailure::Stopped, search.words_checked, None));
+        }
+        if past_deadline(search.deadline) {
+            return Err((SolveFailure::Timeout, search.words_checked, best_partial(search.partial)));
+        }
+        // Every later first word would give up straight away too
+        if search.words_checked > search.max_words_checked {
+            return Err((SolveFailure::WordBudget, search.words_checked, best_partial(search.partial)));
+        }
+        search.words_checked += 1;
+        if let Some(found) = solve_from_first_word(word, &valid_words_vec, available_letters, blanks, options.board_size, &mut search) {
+            return Ok(Solution::new(found, search.words_checked, failed_first_words));
+        }
+    }
+    if search.stopped() {
+        Err((SolveFailure::Stopped, search.words_checked, None))
+    }
+    else if past_deadline(search.deadline) {
+        Err((SolveFailure::Timeout, search.words_checked, best_partial(search.partial)))
+    }
+    else if search.words_checked > search.max_words_checked {
+        Err((SolveFailure::WordBudget, search.words_checked, best_partial(search.partial)))
+    }
+    else {
+        Err((SolveFailure::Unsolvable, search.words_checked, best_partial(search.partial)))
+    }
+}
+
+/// Gets the words for the solver to try first
+/// # Arguments
+/// * `first_word` - How to order the words
+/// * `anchor_word` - Word to always play first, if any
+/// * `words` - The words makeable from the hand, in the order of the dictionary
+/// # Returns
+/// * `Option<Vec<Word>>` - The words to try first, in order (only `anchor_word` if given), or `None` if `anchor_word` isn't one of the `words`
+fn first_words_to_try<const N: usize>(first_word: FirstWordStrategy, anchor_word: Option<&Word>, words: &[Word]) -> Option<Vec<Word>> {
+    match anchor_word {
+        Some(anchor_word) => words.contains(anchor_word).then(|| vec![anchor_word.clone()]),
+        None => Some(first_word.order::<N>(words).into_iter().cloned().collect())
+    }
+}
+
+/// Solves Bananagrams starting from a given first word, played across the middle of the board
+/// # Arguments
+/// * `word` - The first word
+/// * `valid_words_vec` - The words makeable from the hand, in the order to try them after the first word
+/// * `available_letters` - Array of the number of each letter in the hand
+/// * `blanks` - Number of blanks in the hand
+/// * `board_size` - Number of rows/columns in the board to solve on
+/// * `search` - The state of the search, with the words makeable from the hand and when to give up
+/// # Returns
+/// * `Option` - A tuple of (the board solution, minimum column, maximum column, minimum row, maximum row, the words played in order), or `None`
+///   if no solution starts with `word` (or the solver gave up, or `word` doesn't fit on the board)
+fn solve_from_first_word<'a, const N: usize>(word: &Word, valid_words_vec: &'a [Word], available_letters: Letters<N>, blanks: u8, board_size: usize, search: &mut Search<'a, N>) -> Option<Found> {
+    // Skip first words that don't fit across the middle of the board
+    let col_start = match (board_size/2).checked_sub(word.len()/2) {
+        Some(col_start) if col_start + word.len() < board_size => col_start,
+        _ => return None
+    };
+    let row = board_size/2;
+    let mut board = Board::new(board_size);
+    let mut use_letters: Letters<N> = available_letters;
+    let mut use_blanks = blanks;
+    let mut letters_on_board = [0u8; N];
+    for i in 0..word.len() {
+        board.set_val(row, col_start+i, word[i]);
+        letters_on_board[word[i] as usize] += 1;
+        if use_letters[word[i] as usize] > 0 {
+            use_letters[word[i] as usize] -= 1;
+        }
+        else {
+            // Should never underflow because we've verified that every word is playable with these letters and blanks
+            use_blanks -= 1;
+            board.set_blank(row, col_start+i);
+        }
+    }
+    let plays = vec![PlayedWord { word: word.clone(), row, col: col_start, direction: Direction::Horizontal }];
+    // Reduce the set of remaining words to check to those that can be played with the letters not in the first word (plus only one of the tiles played in the first word)
+    let word_letters: HashSet<&u8> = HashSet::from_iter(word.iter());
+    // (Every word is considered, including those longer than this first word, since they may still fit in later)
+    let mut new_valid_words_vec = search.scratch.take_candidates(valid_words_vec.len());
+    for other_word in valid_words_vec.iter() {
+        if check_filter_after_play(use_letters, use_blanks, other_word, &word_letters) {
+            new_valid_words_vec.push(other_word);
+        }
+    }
+    // If the result was bad (or we're out of bounds or we've reached the maximum number of iterations) then the next first word is tried
+    solve_from_board(board, plays, new_valid_words_vec, use_letters, use_blanks, letters_on_board, search)
+}
+
+/// Solves Bananagrams onward from a board that already has letters on it
+/// # Arguments
+/// * `board` - The board so far (its letters must be connected)
+/// * `plays` - The words played on the `board` so far
+/// * `candidates` - The words that could be played next, in the order to try them
+/// * `use_letters` - Array of the number of each letter left in the hand
+/// * `use_blanks` - Number of blanks left in the hand
+/// * `letters_on_board` - Array of the number of each letter on the `board`
+/// * `search` - The state of the search, with every word that could end up on the board and when to give up
+/// # Returns
+/// * `Option` - A tuple of (the board solution, minimum column, maximum column, minimum row, maximum row, the words played in order), or `None`
+///   if no solution was found (or the solver gave up)
+fn solve_from_board<'a, const N: usize>(mut board: Board, mut plays: Vec<PlayedWord>, candidates: Vec<&'a Word>, use_letters: Letters<N>, use_blanks: u8, mut letters_on_board: Letters<N>, search: &mut Search<'a, N>) -> Option<Found> {
+    let (min_col, max_col, min_row, max_row) = board.bounds().expect("Solving from an empty board!");
+    if let Some(partial) = search.partial.as_mut() {
+        partial.record(&board, min_col, max_col, min_row, max_row, &plays, &use_letters, use_blanks);
+    }
+    let tiles: usize = letters_on_board.iter().map(|&count| count as usize).sum();
+    if (use_letters.iter().all(|count| *count == 0) && use_blanks == 0) || search.target_tiles == Some(tiles) {
+        search.scratch.give_candidates(candidates);
+        return Some((board, min_col, max_col, min_row, max_row, plays));
+    }
+    // Begin the recursive processing
+    let result = play_further(&mut board, candidates, use_letters, use_blanks, &mut plays, &mut letters_on_board, search);
+    if let Ok(true) = result {
+        let (min_col, max_col, min_row, max_row) = board.bounds().expect("Solved board is empty!");
+        return Some((board, min_col, max_col, min_row, max_row, plays));
+    }
+    None
+}
+
+/// Completes a board already partly played, playing the given letters onto it like `play_bananagrams`
+/// # Arguments
+/// * `board` - The board to complete, which must be a legal board (see `validate_board`) under `dictionary`; its letters are moved to the
+///   middle of the board solved on
+/// * `available_letters` - Array of the number of each letter left to play (not counting those already on `board`)
+/// * `blanks` - Number of blanks left to play (see `play_bananagrams`)
+/// * `dictionary` - Vector of vectors representing valid words, tried in order (see `play_bananagrams`)
+/// * `options` - How to solve the hand, and when to give up (see `play_bananagrams`; `board_size` is made larger if `board` doesn't fit
+///   with room around it, and `dictionary_masks`, `target_tiles`, `first_word`, `anchor_word`, and `allow_partial` are ignored)
+/// # Returns
+/// * `Result`
+///     * `Ok` - If successful, the `Solution` with the words played onto `board` in order, and no first words since the search starts from
+///       `board`; if no letters are left to play, `board` as it is
+///     * `Err` - A tuple of (why no solution was found, the number of words checked before giving up), with `SolveFailure::InvalidBoard` if
+///       `board` isn't legal or has letters outside the alphabet of `available_letters`
+pub fn complete_board<const N: usize>(board: &Board, available_letters: Letters<N>, blanks: u8, dictionary: &Vec<Word>, options: &SolveOptions) -> Result<Solution, (SolveFailure, usize)> {
+    let min_word_length = options.min_word_length;
+    let dictionary_set: HashSet<Word> = dictionary.iter().filter(|word| word.len() >= min_word_length).cloned().collect();
+    if validate_board(board, &dictionary_set, min_word_length).is_err() {
+        return Err((SolveFailure::InvalidBoard, 0));
+    }
+    let (old_min_col, old_max_col, old_min_row, old_max_row) = board.bounds().expect("Validated board is empty!");
+    let (height, width) = (old_max_row-old_min_row+1, old_max_col-old_min_col+1);
+    // Leave room all around the board for words to be played off of it
+    let size = options.board_size.max(height.max(width) + 2);
+    let (row_offset, col_offset) = ((size-height)/2, (size-width)/2);
+    let mut start = Board::new(size);
+    let mut letters_on_board = [0u8; N];
+    for row in old_min_row..=old_max_row {
+        for col in old_min_col..=old_max_col {
+            let letter = board.get_val(row, col);
+            if letter == EMPTY_VALUE {
+                continue;
+            }
+            if letter as usize >= N {
+                return Err((SolveFailure::InvalidBoard, 0));
+            }
+            letters_on_board[letter as usize] += 1;
+            start.set_val(row-old_min_row+row_offset, col-old_min_col+col_offset, letter);
+            if board.is_blank(row, col) {
+                start.set_blank(row-old_min_row+row_offset, col-old_min_col+col_offset);
+            }
+        }
+    }
+    if available_letters.iter().all(|count| *count == 0) && blanks == 0 {
+        let (min_col, max_col, min_row, max_row) = start.bounds().expect("Validated board is empty!");
+        return Ok(Solution::new((start, min_col, max_col, min_row, max_row, Vec::new()), 0, 0));
+    }
+    // Every run of letters on the finished board is made of letters from the hand or already on the board
+    let mut all_letters = available_letters;
+    for (count, on_board) in all_letters.iter_mut().zip(letters_on_board.iter()) {
+        *count = count.saturating_add(*on_board);
+    }
+    let mut valid_words_vec: Vec<Word> = makeable_words(dictionary, None, &all_letters, blanks, min_word_length, None);
+    let valid_words_set: HashSet<Word> = HashSet::from_iter(valid_words_vec.iter().cloned());
+    options.heuristic.order(&mut valid_words_vec, &available_letters);
+    let mut search = Search::new(&valid_words_set, options);
+    search.target_tiles = None;
+    let mut candidates = search.scratch.take_candidates(valid_words_vec.len());
+    for word in valid_words_vec.iter() {
+        if check_filter_after_play_later(available_letters, letters_on_board, blanks, word) {
+            candidates.push(word);
+        }
+    }
+    if let Some(found) = solve_from_board(start, Vec::new(), candidates, available_letters, blanks, letters_on_board, &mut search) {
+        return Ok(Solution::new(found, search.words_checked, 0));
+    }
+    if search.stopped() {
+        Err((SolveFailure::Stopped, search.words_checked))
+    }
+    else if past_deadline(search.deadline) {
+        Err((SolveFailure::Timeout, search.words_checked))
+    }
+    else if search.words_checked > search.max_words_checked {
+        Err((SolveFailure::WordBudget, search.words_checked))
+    }
+    else {
+        Err((SolveFailure::Unsolvable, search.words_checked))
+    }
+}
+
+/// Adds one newly drawn tile to a solved board, as after a "peel" in a game of Bananagrams: first by only adding tiles (playing a word
+/// through letters already on the board), and if that's impossible, by taking one word's tiles back off the board (those not shared with
+/// another word, trying each word in turn) and playing them along with the new tile (see `complete_board`)
+/// # Arguments
+/// * `board` - The solved board, which must be a legal board under `dictionary`
+/// * `new_letter` - The letter of the new tile (which must be in the alphabet, less than `N`)
+/// * `dictionary` - Vector of vectors representing valid words, tried in order (see `play_bananagrams`)
+/// * `options` - When to give up, and the `board_size` and `min_word_length` to solve with (see `complete_board`); `max_words_checked` is
+///   for each way of adding the tile tried, and the words are always tried in the order of `dictionary` without remembering states
+/// # Returns
+/// * `Result`
+///     * `Ok` - As returned by `complete_board`, with the words played after any taken back off the board and the number of words checked
+///       across every way tried
+///     * `Err` - A tuple of (why the tile couldn't be added, the number of words checked across every way tried), with
+///       `SolveFailure::Unsolvable` if no way of adding it was found and `SolveFailure::InvalidBoard` if `board` isn't legal
+pub fn peel<const N: usize>(board: &Board, new_letter: u8, dictionary: &Vec<Word>, options: &SolveOptions) -> Result<Solution, (SolveFailure, usize)> {
+    let options = SolveOptions { heuristic: SearchHeuristic::Dictionary, memoize: false, ..*options };
+    let mut new_tile = [0u8; N];
+    new_tile[new_letter as usize] = 1;
+    let mut words_checked = match complete_board(board, new_tile, 0, dictionary, &options) {
+        Ok(result) => return Ok(result),
+        Err((reason @ (SolveFailure::InvalidBoard | SolveFailure::Stopped | SolveFailure::Timeout), words_checked)) => return Err((reason, words_checked)),
+        Err((_, words_checked)) => words_checked
+    };
+    let (min_col, max_col, min_row, max_row) = board.bounds().expect("Legal board is empty!");
+    let runs = word_runs(board, min_col, max_col, min_row, max_row);
+    let mut words_per_cell: HashMap<(usize, usize), usize> = HashMap::new();
+    for run in runs.iter() {
+        for cell in run_cells(run.row, run.col, run.word.len(), run.direction) {
+            *words_per_cell.entry(cell).or_insert(0) += 1;
+        }
+    }
+    for run in runs.iter() {
+        let lifted: Vec<(usize, usize)> = run_cells(run.row, run.col, run.word.len(), run.direction).into_iter().filter(|cell| words_per_cell[cell] == 1).collect();
+        if lifted.is_empty() {
+            continue;
+        }
+        let mut rest = board.clone();
+        let mut letters = new_tile;
+        let mut blanks = 0u8;
+        for &(row, col) in lifted.iter() {
+            if board.is_blank(row, col) {
+                blanks += 1;
+            }
+            else {
+                // (Every letter on the board was checked to be in the alphabet by `complete_board`)
+                letters[board.get_val(row, col) as usize] += 1;
+            }
+            rest.set_val(row, col, EMPTY_VALUE);
+        }
+        // Boards split apart (or emptied) by taking the word off aren't legal, so are skipped by `complete_board`
+        match complete_board(&rest, letters, blanks, dictionary, &options) {
+            Ok(mut result) => {
+                result.metrics.words_checked += words_checked;
+                return Ok(result);
+            },
+            Err((reason @ (SolveFailure::Stopped | SolveFailure::Timeout), checked)) => return Err((reason, words_checked + checked)),
+            Err((_, checked)) => words_checked += checked
+        }
+    }
+    Err((SolveFailure::Unsolvable, words_checked))
+}
+
+/// Plays a new bananagrams board using the given letters and dictionary like `play_bananagrams`, but tries the first words in parallel
+/// (on the current rayon thread pool), each on its own board
+/// # Arguments
+/// * `available_letters` - Array of the number of each letter to play with
+/// * `blanks` - Number of blanks to play with (see `play_bananagrams`)
+/// * `dictionary` - Vector of vectors representing valid words (see `play_bananagrams`)
+/// * `options` - How to solve the hand, and when to give up (see `play_bananagrams`; `max_words_checked` is across every thread, and each
+///   first word is searched with what's left of it when its search starts, so it may be slightly overrun; only one thread is used with an
+///   `anchor_word`; each thread remembers its own states with `memoize`; and `allow_partial` is ignored)
+/// * `deterministic` - Whether to always return the solution of the earliest first word (in the order of `first_word`) that has one, rather
+///   than whichever solution is found first; searches of later first words are then only stopped once an earlier one succeeds, so this
+///   is slower, and the result can still vary if the word budget or `deadline` runs out
+/// # Returns
+/// * `Result`
+///     * `Ok` - As returned by `play_bananagrams`, with the number of words checked across every thread (and the first words before the
+///       solution's counted whether they were given up on or not)
+///     * `Err` - A tuple of (why no solution was found, the number of words checked across every thread before giving up)
+#[cfg(feature = "parallel")]
+pub fn play_bananagrams_parallel<const N: usize>(available_letters: Letters<N>, blanks: u8, dictionary: &Vec<Word>, options: &SolveOptions, deterministic: bool) -> Result<Solution, (SolveFailure, usize)> {
+    let mut valid_words_vec: Vec<Word> = makeable_words(dictionary, options.dictionary_masks, &available_letters, blanks, options.min_word_length, options.target_tiles);
+    if valid_words_vec.is_empty() {
+        return Err((SolveFailure::NoWords, 0));
+    }
+    let first_words: Vec<Word> = match first_words_to_try::<N>(options.first_word, options.anchor_word, &valid_words_vec) {
+        Some(first_words) => first_words,
+        None => return Err((SolveFailure::NoWords, 0))
+    };
+    let valid_words_set: HashSet<Word> = HashSet::from_iter(valid_words_vec.iter().cloned());
+    options.heuristic.order(&mut valid_words_vec, &available_letters);
+    let stopped = || options.stop.is_some_and(|stop| stop.load(Ordering::Relaxed));
+    let words_checked = AtomicUsize::new(0);
+    // Set for each first word once its search is no longer needed because a solution was found (by an earlier first word, if deterministic)
+    let superseded: Vec<AtomicBool> = first_words.iter().map(|_| AtomicBool::new(false)).collect();
+    // The index of the first word of the solution kept so far, and the solution
+    let best: Mutex<Option<(usize, Found)>> = Mutex::new(None);
+    // Each thread keeps its own search (and so its own remembered states and buffers) from one first word to the next
+    first_words.par_iter().enumerate().for_each_init(|| Search::new(&valid_words_set, options), |search, (index, word)| {
+        if stopped() || superseded[index].load(Ordering::Relaxed) || past_deadline(options.deadline) {
+            return;
+        }
+        let budget = match options.max_words_checked.checked_sub(words_checked.fetch_add(1, Ordering::Relaxed) + 1) {
+            Some(budget) => budget,
+            None => return
+        };
+        (search.words_checked, search.max_words_checked, search.superseded) = (0, budget, Some(&superseded[index]));
+        let result = solve_from_first_word(word, &valid_words_vec, available_letters, blanks, options.board_size, search);
+        words_checked.fetch_add(search.words_checked, Ordering::Relaxed);
+        if let Some(solution) = result {
+            let mut best = best.lock().unwrap();
+            if best.as_ref().is_none_or(|(best_index, _)| index < *best_index) {
+                *best = Some((index, solution));
+                let stopping = if deterministic { &superseded[index+1..] } else { &superseded[..] };
+                for flag in stopping.iter() {
+                    flag.store(true, Ordering::Relaxed);
+                }
+            }
+        }
+    });
+    let words_checked = words_checked.into_inner();
+    match best.into_inner().unwrap() {
+        Some((index, found)) => Ok(Solution::new(found, words_checked, index)),
+        None if stopped() => Err((SolveFailure::Stopped, words_checked)),
+        None if past_deadline(options.deadline) => Err((SolveFailure::Timeout, words_checked)),
+        None if words_checked > options.max_words_checked => Err((SolveFailure::WordBudget, words_checked)),
+        None => Err((SolveFailure::Unsolvable, words_checked))
+    }
+}
+
+/// Plays up to `n` distinct bananagrams boards using the given letters and dictionary, by solving again with the words of each length
+/// shuffled (see `shuffle_dictionary`) after each solution and keeping only boards whose `canonical_form` hasn't already been found
+/// # Arguments
+/// * `available_letters` - Array of the number of each letter to play with
+/// * `blanks` - Number of blanks to play with (see `play_bananagrams`)
+/// * `dictionary` - Vector of vectors representing valid words, tried in order for the first solution (see `play_bananagrams`)
+/// * `n` - Maximum number of distinct boards to find (should be positive, since none are looked for otherwise)
+/// * `options` - How to solve the hand, and when to give up (see `play_bananagrams`; `max_words_checked` is across every attempt,
+///   `dictionary_masks` are only used for the first solution since the dictionary is reordered after it, and with `allow_partial` the best
+///   partial board is only returned if the hand can't be solved at all)
+/// * `rng` - Random number generator used to shuffle the dictionary between attempts
+/// # Returns
+/// * `Result`
+///     * `Ok` - The distinct boards found (at least one, and at most `n`), each as returned by `play_bananagrams` but with the total number of
+///       words checked up to finding it; fewer than `n` are returned if the word budget, time, or `MULTI_SOLUTION_ATTEMPTS` attempts per board
+///       run out first
+///     * `Err` - As returned by `play_bananagrams`, if no board was found at all
+pub fn play_bananagrams_multi<const N: usize>(available_letters: Letters<N>, blanks: u8, dictionary: &Vec<Word>, n: usize, options: &SolveOptions, rng: &mut impl Rng) -> Result<Vec<Solution>, Unsolved<N>> {
+    let mut solutions = Vec::with_capacity(n);
+    let mut seen: HashSet<Vec<u8>> = HashSet::new();
+    let mut words_checked = 0;
+    let mut shuffled: Option<Vec<Word>> = None;
+    for _ in 0..n.saturating_mul(MULTI_SOLUTION_ATTEMPTS) {
+        if solutions.len() >= n || words_checked >= options.max_words_checked {
+            break;
+        }
+        let ordering = shuffled.as_ref().unwrap_or(dictionary);
+        let attempt = SolveOptions {
+            dictionary_masks: if shuffled.is_some() { None } else { options.dictionary_masks },
+            max_words_checked: options.max_words_checked - words_checked,
+            allow_partial: options.allow_partial && solutions.is_empty(),
+            ..*options
+        };
+        match play_bananagrams(available_letters, blanks, ordering, &attempt) {
+            Ok(mut solution) => {
+                words_checked += solution.metrics.words_checked;
+                solution.metrics.words_checked = words_checked;
+                if seen.insert(canonical_form(&solution.board, solution.min_col, solution.max_col, solution.min_row, solution.max_row)) {
+                    solutions.push(solution);
+                }
+            },
+            Err((reason, checked, partial)) => {
+                words_checked += checked;
+                // Reordering the dictionary can't help once the search is exhausted, stopped, or out of budget or time
+                if solutions.is_empty() {
+                    return Err((reason, words_checked, partial));
+                }
+                break;
+            }
+        }
+        shuffled = Some(shuffle_dictionary(dictionary, rng));
+    }
+    Ok(solutions)
+}
+
+/// Takes the best partial board found by the solver
+/// # Arguments
+/// * `partial` - Tracker of the best partial board, if one was kept
+/// # Returns
+/// * `Option<Box<PartialSolution>>` - The best partial board, or `None` if none was kept
+fn best_partial<const N: usize>(partial: Option<PartialTracker<N>>) -> Option<Box<PartialSolution<N>>> {
+    partial.and_then(|partial| partial.best).map(|(_, _, solution)| Box::new(solution))
+}
+
+/// The pool of tiles that hands are drawn from
+#[derive(Clone)]
+pub struct TileSet {
+    /// Every tile in the pool, as its index in the alphabet (such as from 0 ('A') to 25 ('Z') in English), in order of the alphabet
+    tiles: Vec<u8>,
+    /// Number of letters in the alphabet of the tiles
+    alphabet_size: usize
+}
+impl TileSet {
+    /// Creates the standard 144-tile set of Bananagrams (see `TO_CHOOSE_FROM`)
+    /// # Returns
+    /// * `TileSet` - The standard tile set
+    pub fn standard() -> TileSet {
+        TileSet { tiles: TO_CHOOSE_FROM.iter().map(|c| (c - 65) as u8).collect(), alphabet_size: ENGLISH_ALPHABET_SIZE }
+    }
+
+    /// Creates a tile set with the given number of each letter
+    /// # Arguments
+    /// * `counts` - The number of each letter of the alphabet in the pool, in order of the alphabet (26 counts for English)
+    /// # Returns
+    /// * `Result` with the tile set, or an error message if any letter has more than 255 tiles (since hands store each letter's count in
+    ///   a byte) or there are no tiles at all
+    pub fn from_counts(counts: &[usize]) -> Result<TileSet, String> {
+        if let Some(letter) = counts.iter().position(|&count| count > u8::MAX as usize) {
+            return Err(format!("Too many tiles of letter {} of the alphabet (at most {} are allowed)", letter, u8::MAX));
+        }
+        let tiles: Vec<u8> = counts.iter().enumerate().flat_map(|(letter, &count)| std::iter::repeat_n(letter as u8, count)).collect();
+        if tiles.is_empty() {
+            return Err("The tile set contains no tiles".to_owned());
+        }
+        Ok(TileSet { tiles, alphabet_size: counts.len() })
+    }
+
+    /// Parses the text of a tile-set file
+    /// # Arguments
+    /// * `text` - One `LETTER COUNT` pair per line, like `A 13` (blank lines are ignored)
+    /// * `alphabet` - The alphabet the letters are from (such as `Alphabet::english`)
+    /// # Returns
+    /// * `Result` with the tile set, or an error message naming the line if a letter isn't in the `alphabet`, a count isn't a positive integer,
+    ///   or a letter is given more than once
+    pub fn parse(text: &str, alphabet: &Alphabet) -> Result<TileSet, String> {
+        let mut counts = vec![0usize; alphabet.len()];
+        for (line_num, line) in text.lines().enumerate() {
+            let mut fields = line.split_whitespace();
+            let (letter, count) = match (fields.next(), fields.next(), fields.next()) {
+                (None, _, _) => continue,
+                (Some(letter), Some(count), None) => (letter, count),
+                _ => return Err(format!("Line {} isn't of the form `LETTER COUNT`: {:?}", line_num+1, line))
+            };
+            let (letter, symbol) = match alphabet.index_of(letter) {
+                Some(index) => (index as usize, letter),
+                None => return Err(format!("Invalid letter {:?} on line {} (it isn't in the alphabet)", letter, line_num+1))
+            };
+            let count = match count.parse::<usize>() {
+                Ok(count) if count > 0 => count,
+                _ => return Err(format!("Invalid count {:?} on line {} (counts must be positive integers)", count, line_num+1))
+            };
+            if counts[letter] > 0 {
+                return Err(format!("The letter {} is given more than once (again on line {})", symbol.to_uppercase(), line_num+1));
+            }
+            if count > u8::MAX as usize {
+                return Err(format!("Too many tiles of the letter {} on line {} (at most {} are allowed)", symbol.to_uppercase(), line_num+1, u8::MAX));
+            }
+            counts[letter] = count;
+        }
+        TileSet::from_counts(&counts)
+    }
+
+    /// Loads a tile set from a file
+    /// # Arguments
+    /// * `path` - Path to the tile-set file (see `TileSet::parse`)
+    /// * `alphabet` - The alphabet the letters are from (such as `Alphabet::english`)
+    /// # Returns
+    /// * `Result` with the tile set, or an error message if the file can't be read or is invalid
+    #[cfg(feature = "native")]
+    pub fn load(path: &Path, alphabet: &Alphabet) -> Result<TileSet, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("Failed to read tile set {}: {}", path.display(), e))?;
+        TileSet::parse(&text, alphabet).map_err(|e| format!("Invalid tile set {}: {}", path.display(), e))
+    }
+
+    /// Gets the number of tiles in the pool
+    pub fn len(&self) -> usize {
+        self.tiles.len()
+    }
+
+    /// Gets whether the pool has no tiles (which a `TileSet` never does)
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+
+    /// Gets the default maximum size of hand to generate, which is half the tiles in the pool (72 for the standard set)
+    pub fn maximum_hand_size(&self) -> usize {
+        self.tiles.len() / 2
+    }
+
+    /// Gets the number of letters in the alphabet of the tiles
+    pub fn alphabet_size(&self) -> usize {
+        self.alphabet_size
+    }
+
+    /// Gets the number of each letter in the pool
+    /// # Returns
+    /// * `Letters<N>` - Array of the number of tiles of each letter (`N` must be at least `alphabet_size`, or this panics)
+    pub fn counts<const N: usize>(&self) -> Letters<N> {
+        assert!(self.alphabet_size <= N, "A tile set of {} letters doesn't fit in hands of {}", self.alphabet_size, N);
+        let mut counts = [0u8; N];
+        for &tile in self.tiles.iter() {
+            counts[tile as usize] += 1;
+        }
+        counts
+    }
+}
+
+/// A hand with more tiles of some letters than the pool it's supposedly drawn from has, so it could never come up in a real game (see
+/// `check_hand_against_pool`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HandError {
+    /// Each letter the hand has too many of, in order of the alphabet, as its index in the alphabet, the number of it in the hand, and the
+    /// number of tiles of it in the pool
+    pub excess: Vec<(usize, usize, usize)>
+}
+impl fmt::Display for HandError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Letters are named as English ones, which is what every hand checked so far is of
+        let letters: Vec<String> = self.excess.iter().map(|&(letter, requested, available)| match letter < ENGLISH_ALPHABET_SIZE {
+            true => format!("{} ({} requested, {} available)", (letter as u8 + b'A') as char, requested, available),
+            false => format!("letter {} of the alphabet ({} requested, {} available)", letter, requested, available)
+        }).collect();
+        write!(f, "Not enough tiles in the tile set for {}", letters.join(", "))
+    }
+}
+impl std::error::Error for HandError {}
+
+/// Checks that a hand could be drawn from a pool of tiles, having no more of any letter than the pool has
+/// # Arguments
+/// * `letters` - Number of each letter in the hand
+/// * `pool` - The tiles the hand should be drawable from
+/// # Returns
+/// * `Result` with nothing, or a `HandError` listing every letter the hand has more of than the pool (letters outside the pool's alphabet
+///   have no tiles)
+pub fn check_hand_against_pool<const N: usize>(letters: &Letters<N>, pool: &TileSet) -> Result<(), HandError> {
+    let mut available = [0usize; N];
+    for &tile in pool.tiles.iter().filter(|&&tile| (tile as usize) < N) {
+        available[tile as usize] += 1;
+    }
+    let excess: Vec<(usize, usize, usize)> = letters.iter().zip(available.iter()).enumerate()
+        .filter(|(_, (&requested, &available))| requested as usize > available)
+        .map(|(letter, (&requested, &available))| (letter, requested as usize, available))
+        .collect();
+    if excess.is_empty() { Ok(()) } else { Err(HandError { excess }) }
+}
+
+/// Gets the number of tiles each player starts a game with under the official rules
+/// # Arguments
+/// * `players` - Number of players (the rules cover 2 to 8)
+/// # Returns
+/// * `usize` - 21 tiles for up to 4 players, 15 for 5-6, or 11 for 7 or more
+pub fn official_hand_size(players: usize) -> usize {
+    match players {
+        0..=4 => 21,
+        5..=6 => 15,
+        _ => 11
+    }
+}
+
+/// How the sizes of random hands are chosen (see `generate_hand`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HandSizeDistribution {
+    /// Always the given size
+    Fixed(usize),
+    /// Any size from the first to the second (inclusive), each equally likely
+    Uniform(usize, usize),
+    /// Any size from the first to the second (inclusive), with smaller sizes more likely (on a logarithmic scale with base `BASE`)
+    LogScaled(usize, usize),
+    /// The size each player starts a game with the given number of players under the official rules (see `official_hand_size`)
+    Official(usize)
+}
+impl HandSizeDistribution {
+    /// The name of every kind of distribution (see `name`)
+    pub const NAMES: [&'static str; 4] = ["fixed", "uniform", "log_scaled", "official"];
+
+    /// Gets the name identifying the kind of distribution (in options)
+    /// # Returns
+    /// * `&'static str` - The name, in snake case
+    pub fn name(self) -> &'static str {
+        match self {
+            HandSizeDistribution::Fixed(_) => "fixed",
+            HandSizeDistribution::Uniform(_, _) => "uniform",
+            HandSizeDistribution::LogScaled(_, _) => "log_scaled",
+            HandSizeDistribution::Official(_) => "official"
+        }
+    }
+
+    /// Gets the smallest size the distribution can choose
+    pub fn minimum(self) -> usize {
+        match self {
+            HandSizeDistribution::Fixed(size) => size,
+            HandSizeDistribution::Uniform(minimum, _) | HandSizeDistribution::LogScaled(minimum, _) => minimum,
+            HandSizeDistribution::Official(players) => official_hand_size(players)
+        }
+    }
+
+    /// Gets the largest size the distribution can choose
+    pub fn maximum(self) -> usize {
+        match self {
+            HandSizeDistribution::Fixed(size) => size,
+            HandSizeDistribution::Uniform(_, maximum) | HandSizeDistribution::LogScaled(_, maximum) => maximum,
+            HandSizeDistribution::Official(players) => official_hand_size(players)
+        }
+    }
+
+    /// Chooses the size of a random hand
+    /// # Arguments
+    /// * `rng` - Random number generator (which isn't used by `Fixed` and `Official`)
+    /// # Returns
+    /// * `usize` - Number of letters in the hand
+    pub fn sample(self, rng: &mut impl Rng) -> usize {
+        match self {
+            HandSizeDistribution::Fixed(_) | HandSizeDistribution::Official(_) => self.minimum(),
+            HandSizeDistribution::Uniform(minimum, maximum) => rng.gen_range(minimum..=maximum),
+            HandSizeDistribution::LogScaled(minimum, maximum) => {
+                let (minimum, maximum) = (minimum as f32, maximum as f32);
+                // Calculate the logarithmic scaled value within [min, max]
+                let scaled_value = (maximum - minimum) * (BASE.powf(rng.gen()) - 1.0) / (BASE - 1.0) + minimum;
+
+                // Convert to an integer
+                scaled_value.round() as usize
+            }
+        }
+    }
+}
+
+/// Generates a random hand of letters pulled from a set of tiles
+/// # Arguments
+/// * `rng` - Random number generator
+/// * `tiles` - The tiles to draw from (see `TileSet::standard` for the standard set)
+/// * `hand_sizes` - How to choose the number of letters in the hand (whose maximum must be at most the number of tiles in `tiles`)
+/// * `min_vowels` - Minimum number of vowels (A, E, I, O, and U of the English alphabet) in the hand; if the hand drawn has fewer, random
+///   consonants in it are swapped for random vowels of the tiles not drawn (keeping its size) until it has enough or no more can be swapped
+/// # Returns
+/// * `(Letters<N>, usize)` - Number of each letter present in the hand (`N` must be at least the alphabet size of `tiles`, or this panics),
+///   and the number of consonants swapped for vowels
+pub fn generate_hand<const N: usize>(rng: &mut impl Rng, tiles: &TileSet, hand_sizes: HandSizeDistribution, min_vowels: usize) -> (Letters<N>, usize) {
+    assert!(tiles.alphabet_size <= N, "A tile set of {} letters doesn't fit in hands of {}", tiles.alphabet_size, N);
+    let size = hand_sizes.sample(rng);
+    let mut letters = [0u8; N];
+    tiles.tiles.choose_multiple(rng, size).for_each(|c| {
+        letters[*c as usize] += 1;
+    });
+    if count_vowels(&letters) >= min_vowels {
+        return (letters, 0);
+    }
+    let mut undrawn: Letters<N> = tiles.counts();
+    for (undrawn, &count) in undrawn.iter_mut().zip(letters.iter()) {
+        *undrawn -= count;
+    }
+    let swapped = swap_in_vowels(rng, &mut letters, &mut undrawn, min_vowels);
+    (letters, swapped)
+}
+
+/// Counts the vowels (A, E, I, O, and U of the English alphabet) in some letters
+/// # Arguments
+/// * `letters` - Number of each letter
+/// # Returns
+/// * `usize` - Number of vowels
+fn count_vowels<const N: usize>(letters: &Letters<N>) -> usize {
+    VOWELS.iter().filter(|&&vowel| (vowel as usize) < N).map(|&vowel| letters[vowel as usize] as usize).sum()
+}
+
+/// Swaps random consonants of a hand for random vowels of other tiles, one at a time, until the hand has enough vowels
+/// # Arguments
+/// * `rng` - Random number generator
+/// * `hand` - Number of each letter in the hand
+/// * `spare` - Number of each letter of the other tiles, which the consonants swapped out are added to
+/// * `min_vowels` - Number of vowels the hand should have
+/// # Returns
+/// * `usize` - Number of consonants swapped (fewer than needed if the hand or `spare` runs out)
+fn swap_in_vowels<const N: usize>(rng: &mut impl Rng, hand: &mut Letters<N>, spare: &mut Letters<N>, min_vowels: usize) -> usize {
+    let mut swapped = 0;
+    while count_vowels(hand) < min_vowels {
+        let (Some(consonant), Some(vowel)) = (random_tile(rng, hand, false), random_tile(rng, spare, true)) else {
+            break;
+        };
+        hand[consonant] -= 1;
+        spare[consonant] += 1;
+        spare[vowel] -= 1;
+        hand[vowel] += 1;
+        swapped += 1;
+    }
+    swapped
+}
+
+/// Chooses a random tile among the vowels or the consonants of some letters, each tile equally likely
+/// # Arguments
+/// * `rng` - Random number generator
+/// * `letters` - Number of each letter
+/// * `vowel` - Whether to choose a vowel rather than a consonant
+/// # Returns
+/// * `Option<usize>` - The letter chosen, or `None` if there are no tiles of the kind asked for
+fn random_tile<const N: usize>(rng: &mut impl Rng, letters: &Letters<N>, vowel: bool) -> Option<usize> {
+    let is_kind = |letter: &usize| VOWELS.contains(&(*letter as u8)) == vowel;
+    let total: usize = (0..N).filter(is_kind).map(|letter| letters[letter] as usize).sum();
+    if total == 0 {
+        return None;
+    }
+    let mut index = rng.gen_range(0..total);
+    (0..N).filter(is_kind).find(|&letter| {
+        if index < letters[letter] as usize {
+            return true;
+        }
+        index -= letters[letter] as usize;
+        false
+    })
+}
+
+/// A bag of tiles drawn from without replacement, so that hands drawn one after another (or dealt to several players at once) never have
+/// more of a letter between them than the tile set does
+#[derive(Clone)]
+pub struct TilePool {
+    /// The tile set the pool is filled from
+    tiles: TileSet,
+    /// The tiles left in the pool, as their index in the alphabet, in no particular order
+    remaining: Vec<u8>
+}
+impl TilePool {
+    /// Creates a full pool
+    /// # Arguments
+    /// * `tiles` - The tile set to fill the pool from
+    /// # Returns
+    /// * `TilePool` - The pool, holding every tile of `tiles`
+    pub fn new(tiles: &TileSet) -> TilePool {
+        TilePool { tiles: tiles.clone(), remaining: tiles.tiles.clone() }
+    }
+
+    /// Gets the number of tiles left in the pool
+    pub fn len(&self) -> usize {
+        self.remaining.len()
+    }
+
+    /// Gets whether every tile has been drawn
+    pub fn is_empty(&self) -> bool {
+        self.remaining.is_empty()
+    }
+
+    /// Puts every tile drawn back in the pool
+    pub fn refill(&mut self) {
+        self.remaining.clone_from(&self.tiles.tiles);
+    }
+
+    /// Gets the number of each letter left in the pool
+    /// # Returns
+    /// * `Letters<N>` - Array of the number of tiles of each letter left (`N` must be at least the alphabet size of the tile set, or this
+    ///   panics)
+    pub fn counts<const N: usize>(&self) -> Letters<N> {
+        assert!(self.tiles.alphabet_size <= N, "A tile set of {} letters doesn't fit in hands of {}", self.tiles.alphabet_size, N);
+        let mut counts = [0u8; N];
+        for &tile in self.remaining.iter() {
+            counts[tile as usize] += 1;
+        }
+        counts
+    }
+
+    /// Draws tiles at random
+    /// # Arguments
+    /// * `rng` - Random number generator
+    /// * `count` - Number of tiles to draw
+    /// # Returns
+    /// * `Option<Letters<N>>` - Number of each letter drawn (`N` must be at least the alphabet size of the tile set, or this panics), or
+    ///   `None` (drawing nothing) if fewer than `count` tiles are left
+    pub fn draw<const N: usize>(&mut self, rng: &mut impl Rng, count: usize) -> Option<Letters<N>> {
+        assert!(self.tiles.alphabet_size <= N, "A tile set of {} letters doesn't fit in hands of {}", self.tiles.alphabet_size, N);
+        if count > self.remaining.len() {
+            return None;
+        }
+        let mut letters = [0u8; N];
+        for _ in 0..count {
+            let tile = self.remaining.swap_remove(rng.gen_range(0..self.remaining.len()));
+            letters[tile as usize] += 1;
+        }
+        Some(letters)
+    }
+
+    /// Draws a random hand, of a size chosen like those of `generate_hand`
+    /// # Arguments
+    /// * `rng` - Random number generator
+    /// * `hand_sizes` - How to choose the number of letters in the hand
+    /// * `min_vowels` - Minimum number of vowels in the hand, which are swapped in for consonants from the tiles left like `generate_hand`
+    /// # Returns
+    /// * `Option<(Letters<N>, usize)>` - Number of each letter in the hand and the number of consonants swapped for vowels, or `None`
+    ///   (drawing nothing) if fewer tiles are left than the size chosen
+    pub fn draw_hand<const N: usize>(&mut self, rng: &mut impl Rng, hand_sizes: HandSizeDistribution, min_vowels: usize) -> Option<(Letters<N>, usize)> {
+        let size = hand_sizes.sample(rng);
+        let mut letters = self.draw(rng, size)?;
+        if count_vowels(&letters) >= min_vowels {
+            return Some((letters, 0));
+        }
+        let mut left: Letters<N> = self.counts();
+        let swapped = swap_in_vowels(rng, &mut letters, &mut left, min_vowels);
+        self.remaining.clear();
+        self.put_back(&left);
+        Some((letters, swapped))
+    }
+
+    /// Deals the same number of tiles to each of several players
+    /// # Arguments
+    /// * `rng` - Random number generator
+    /// * `players` - Number of players
+    /// * `tiles_each` - Number of tiles to deal each player
+    /// # Returns
+    /// * `Option<Vec<Letters<N>>>` - Each player's hand, or `None` (dealing nothing) if there aren't enough tiles left for every player
+    pub fn split<const N: usize>(&mut self, rng: &mut impl Rng, players: usize, tiles_each: usize) -> Option<Vec<Letters<N>>> {
+        if players.checked_mul(tiles_each).is_none_or(|total| total > self.remaining.len()) {
+            return None;
+        }
+        Some((0..players).map(|_| self.draw(rng, tiles_each).expect("Not enough tiles to deal!")).collect())
+    }
+
+    /// Puts tiles back in the pool (such as one dumped in a game)
+    /// # Arguments
+    /// * `letters` - Number of each letter to put back, which should have been drawn from the pool
+    pub fn put_back<const N: usize>(&mut self, letters: &Letters<N>) {
+        for (letter, &count) in letters.iter().enumerate() {
+            self.remaining.extend(std::iter::repeat_n(letter as u8, count as usize));
+        }
+    }
+}
+
+/// Creates a random number generator, seeded if a `seed` is given
+/// # Arguments
+/// * `seed` - Optional seed; if `None` the generator is seeded from the operating system's randomness (or, without the `native` feature,
+///   from a fixed seed, since WebAssembly has no randomness of its own)
+/// # Returns
+/// * `StdRng` - The random number generator
+pub fn seeded_rng(seed: Option<u64>) -> StdRng {
+    match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        #[cfg(feature = "native")]
+        None => StdRng::from_entropy(),
+        #[cfg(not(feature = "native"))]
+        None => StdRng::seed_from_u64(0)
+    }
+}
+
+/// A slow validator that checks a whole board by brute force, sharing no code with the solver's checks, for the tests to check those against
+#[cfg(test)]
+mod reference {
+    use super::*;
+
+    /// Finds every run of two or more letters on a board, by splitting each whole row and column at its empty cells
+    /// # Arguments
+    /// * `board` - `Board` to search
+    /// # Returns
+    /// * `Vec<Word>` - Every run, the rows' before the columns'
+    pub fn runs(board: &Board) -> Vec<Word> {
+        let size = board.size();
+        let rows = (0..size).map(|row| (0..size).map(|col| board.get_val(row, col)).collect::<Vec<u8>>());
+        let cols = (0..size).map(|col| (0..size).map(|row| board.get_val(row, col)).collect::<Vec<u8>>());
+        rows.chain(cols).flat_map(|line| line.split(|&val| val == EMPTY_VALUE).filter(|run| run.len() > 1).map(|run| run.iter().copied().collect::<Word>()).collect::<Vec<_>>()).collect()
+    }
+
+    /// Checks that every run of letters on a board is a valid word, without checking that the letters are connected (as the solver's
+    /// `is_board_valid_horizontal` and `is_board_valid_vertical` don't)
+    /// # Arguments
+    /// * `board` - `Board` to check
+    /// * `valid_words` - HashSet of all valid words as `Word`s
+    /// * `min_word_length` - Minimum length of a word
+    /// # Returns
+    /// * `bool` - Whether every run is a valid word of at least `min_word_length` letters
+    pub fn words_are_valid(board: &Board, valid_words: &HashSet<Word>, min_word_length: usize) -> bool {
+        runs(board).iter().all(|run| run.len() >= min_word_length && valid_words.contains(run))
+    }
+
+    /// Checks that a board's letters are connected, by growing the set of letters reached from the first until it stops growing
+    /// # Arguments
+    /// * `board` - `Board` to check
+    /// # Returns
+    /// * `bool` - Whether every letter can be reached from every other (false if the board is empty)
+    pub fn is_connected(board: &Board) -> bool {
+        let size = board.size();
+        let letters: Vec<(usize, usize)> = (0..size).flat_map(|row| (0..size).map(move |col| (row, col))).filter(|&(row, col)| board.get_val(row, col) != EMPTY_VALUE).collect();
+        let Some(&first) = letters.first() else {
+            return false;
+        };
+        let mut reached = vec![first];
+        loop {
+            let next: Vec<(usize, usize)> = letters.iter().copied()
+                .filter(|cell| !reached.contains(cell) && reached.iter().any(|reached| reached.0.abs_diff(cell.0) + reached.1.abs_diff(cell.1) == 1))
+                .collect();
+            if next.is_empty() {
+                return reached.len() == letters.len();
+            }
+            reached.extend(next);
+        }
+    }
+
+    /// Checks that a board is a legal Bananagrams board
+    /// # Arguments
+    /// * `board` - `Board` to check
+    /// * `valid_words` - HashSet of all valid words as `Word`s
+    /// * `min_word_length` - Minimum length of a word
+    /// # Returns
+    /// * `bool` - Whether the board's letters are connected and every run of them is a valid word
+    pub fn is_legal(board: &Board, valid_words: &HashSet<Word>, min_word_length: usize) -> bool {
+        is_connected(board) && words_are_valid(board, valid_words, min_word_length)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bench::{BENCH_DICTIONARY, BENCH_HANDS};
+
+    /// Converts the words of a small dictionary, given one per line
+    fn words(text: &str) -> Vec<Word> {
+        parse_dictionary(text, false).unwrap()
+    }
+
+    /// Solves seeded random hands with the bench dictionary, as realistic boards to make random plays on
+    fn random_solved_boards(rng: &mut StdRng, dictionary: &Vec<Word>, count: usize) -> Vec<Board> {
+        let mut boards = Vec::new();
+        while boards.len() < count {
+            let (letters, _) = generate_hand::<ENGLISH_ALPHABET_SIZE>(rng, &TileSet::standard(), HandSizeDistribution::Uniform(10, 25), 0);
+            if let Ok(Solution { board, .. }) = play_bananagrams(letters, 0, dictionary, &SolveOptions { max_words_checked: 10_000, first_word: FirstWordStrategy::Random(rng.gen()), ..SolveOptions::default() }) {
+                boards.push(board);
+            }
+        }
+        boards
+    }
+
+    /// Counts the letters on a board
+    fn board_letters(board: &Board) -> Letters {
+        let mut letters = [0; ENGLISH_ALPHABET_SIZE];
+        for (_, _, letter, _) in tiles(board) {
+            letters[letter as usize] += 1;
+        }
+        letters
+    }
+
+    /// Plays random words from the bench dictionary at random positions touching solved boards, from small random hands (so that some
+    /// plays run out of letters partway and some use blanks), checking that undoing each play restores the board and its letter counts
+    /// exactly; `check` is called with the board after each play, the play, and what `play_word` gave
+    fn play_randomly(seed: u64, mut check: impl FnMut(&Board, &PlayedWord, &PlayOutcome<ENGLISH_ALPHABET_SIZE>)) {
+        let dictionary = words(BENCH_DICTIONARY);
+        let mut rng = seeded_rng(Some(seed));
+        let mut positions = Vec::new();
+        for original in random_solved_boards(&mut rng, &dictionary, 10) {
+            let bounds = original.bounds().unwrap();
+            let (min_col, max_col, min_row, max_row) = bounds;
+            let occupied: Vec<(usize, usize)> = tiles(&original).into_iter().map(|(row, col, _, _)| (row, col)).collect();
+            let mut board = original.clone();
+            let mut letters_on_board = board_letters(&board);
+            for _ in 0..300 {
+                let word = dictionary.choose(&mut rng).unwrap();
+                let direction: Direction = rng.gen();
+                anchored_positions(&occupied, word.len(), direction, min_col, max_col, min_row, max_row, &mut positions);
+                let &(row, col) = positions.choose(&mut rng).unwrap();
+                let letters: Letters = std::array::from_fn(|_| rng.gen_range(0..3));
+                let outcome = play_word(word, row, col, &mut board, direction, &letters, rng.gen_range(0..3), &mut letters_on_board, None, Vec::new()).unwrap();
+                assert_eq!(letters_on_board, board_letters(&board));
+                check(&board, &PlayedWord { word: word.clone(), row, col, direction }, &outcome);
+                undo_play(&mut board, &outcome.1, &mut letters_on_board, bounds);
+                assert!(board.identical(&original), "Undoing {} {} at ({}, {}) left\n{}", convert_array_to_word(word), direction, row, col, board_to_string(&board));
+                assert_eq!(board.bounds(), Some(bounds));
+                assert_eq!(letters_on_board, board_letters(&original));
+            }
+        }
+    }
+
+    #[test]
+    fn undoing_random_plays_restores_the_board_and_its_letters() {
+        let (mut played, mut ran_out, mut used_blanks) = (0, 0, 0);
+        play_randomly(80, |board, _, outcome| {
+            played += outcome.0 as usize;
+            ran_out += matches!(outcome.3, LetterUsage::Overused) as usize;
+            used_blanks += !board.blanks().is_empty() as usize;
+        });
+        // Each kind of play was undone, not just those that failed before placing a tile
+        assert!(played > 0 && ran_out > 0 && used_blanks > 0, "{} played, {} ran out, {} used blanks", played, ran_out, used_blanks);
+    }
+
+    #[test]
+    fn validity_checks_after_random_plays_agree_with_the_reference_validator() {
+        let valid_words: HashSet<Word> = words(BENCH_DICTIONARY).into_iter().collect();
+        let (mut valid, mut invalid) = (0, 0);
+        play_randomly(81, |board, play, outcome| {
+            if !outcome.0 {
+                return;
+            }
+            // As the solver checks a play, knowing that the board was valid before it
+            let (min_col, max_col, min_row, max_row) = board.bounds().unwrap();
+            let end = play.word.len() - 1;
+            let is_valid = match play.direction {
+                Direction::Horizontal => is_board_valid_horizontal(board, min_col, max_col, min_row, max_row, play.row, play.col, play.col + end, &valid_words, DEFAULT_MIN_WORD_LENGTH),
+                Direction::Vertical => is_board_valid_vertical(board, min_col, max_col, min_row, max_row, play.row, play.row + end, play.col, &valid_words, DEFAULT_MIN_WORD_LENGTH)
+            };
+            assert_eq!(is_valid, reference::words_are_valid(board, &valid_words, DEFAULT_MIN_WORD_LENGTH), "{} {} at ({}, {}) on\n{}", convert_array_to_word(&play.word), play.direction, play.row, play.col, board_to_string(board));
+            assert_eq!(validate_board(board, &valid_words, DEFAULT_MIN_WORD_LENGTH).is_ok(), reference::is_legal(board, &valid_words, DEFAULT_MIN_WORD_LENGTH));
+            if is_valid {
+                valid += 1;
+                // And a board the solver would keep is saved with its tiles (and blanks) where they are
+                let (_, parsed, ..) = parse_bgb::<ENGLISH_ALPHABET_SIZE>(&bgb_file(&[(board_letters(board), board)])).unwrap().remove(0);
+                assert_eq!(tiles(&parsed), tiles(board));
+            }
+            else {
+                invalid += 1;
+            }
+        });
+        assert!(valid > 0 && invalid > 0, "{} valid, {} invalid", valid, invalid);
+    }
+
+    #[test]
+    fn validate_board_agrees_with_the_reference_validator_on_random_boards() {
+        let valid_words: HashSet<Word> = words("AT\nTA\nTO\nCAT\nACT\nCOT\nOAT\nTACO\nCOAT").into_iter().collect();
+        let mut rng = seeded_rng(Some(82));
+        let (mut legal, mut illegal) = (0, 0);
+        for _ in 0..2_000 {
+            // Letters scattered over a small corner of the board, dense enough that some happen to be connected words
+            let mut board = Board::new(16);
+            for _ in 0..rng.gen_range(1..8) {
+                board.set_val(rng.gen_range(0..4), rng.gen_range(0..4), *[0, 2, 14, 19].choose(&mut rng).unwrap());
+            }
+            let is_legal = reference::is_legal(&board, &valid_words, 2);
+            assert_eq!(validate_board(&board, &valid_words, 2).is_ok(), is_legal, "\n{}", board_to_string(&board));
+            legal += is_legal as usize;
+            illegal += !is_legal as usize;
+        }
+        assert!(legal > 0 && illegal > 0, "{} legal, {} illegal", legal, illegal);
+    }
+
+    /// Converts a hand of English letters (like `"AAEINRST"`)
+    fn hand(letters: &str) -> Letters {
+        Alphabet::english().hand(letters).unwrap()
+    }
+
+    #[test]
+    fn seeded_hands_and_solutions_repeat() {
+        let dictionary = words(BENCH_DICTIONARY);
+        let tiles = TileSet::standard();
+        let run = || {
+            let mut rng = seeded_rng(Some(42));
+            (0..3).map(|_| {
+                let (letters, _) = generate_hand::<ENGLISH_ALPHABET_SIZE>(&mut rng, &tiles, HandSizeDistribution::Fixed(12), 0);
+                let solution = play_bananagrams(letters, 0, &dictionary, &SolveOptions { first_word: FirstWordStrategy::Random(rng.gen()), ..SolveOptions::default() });
+                (letters, solution.ok().map(|Solution { board, plays, .. }| (board, plays)))
+            }).collect::<Vec<_>>()
+        };
+        let first = run();
+        assert!(first.iter().any(|(_, solution)| solution.is_some()));
+        assert_eq!(first, run());
+    }
+
+    /// Makes a board of the given size from its text (see `Board::from_str`), with the text's first character at `row` and `col`
+    fn board_at(text: &str, size: usize, row: usize, col: usize) -> Board {
+        let parsed: Board = text.parse().unwrap();
+        let mut board = Board::new(size);
+        for (r, c, val, is_blank) in tiles(&parsed) {
+            board.set_val(row + r, col + c, val);
+            if is_blank {
+                board.set_blank(row + r, col + c);
+            }
+        }
+        board
+    }
+
+    /// Lists the tiles on a board in row-major order, as (row, column, letter, whether it's a blank)
+    fn tiles(board: &Board) -> Vec<(usize, usize, u8, bool)> {
+        (0..board.size()).flat_map(|row| (0..board.size()).map(move |col| (row, col)))
+            .filter(|&(row, col)| board.get_val(row, col) != EMPTY_VALUE)
+            .map(|(row, col)| (row, col, board.get_val(row, col), board.is_blank(row, col))).collect()
+    }
+
+    /// Writes boards to the contents of a `.bgb` file
+    fn bgb_file(boards: &[(Letters, &Board)]) -> Vec<u8> {
+        let mut bytes = vec![BGB_FORMAT_VERSION];
+        for (letters, board) in boards {
+            bytes.extend(board_to_bytes(letters, board).unwrap());
+        }
+        bytes
+    }
+
+    #[test]
+    fn bgb_round_trips_hands_and_boards() {
+        let dictionary = words(BENCH_DICTIONARY);
+        let mut rng = seeded_rng(Some(9));
+        let mut boards: Vec<(Letters, Board)> = Vec::new();
+        while boards.len() < 5 {
+            let (letters, _) = generate_hand::<ENGLISH_ALPHABET_SIZE>(&mut rng, &TileSet::standard(), HandSizeDistribution::Uniform(11, 16), 0);
+            if let Ok(Solution { board, .. }) = play_bananagrams(letters, 0, &dictionary, &SolveOptions { first_word: FirstWordStrategy::Random(rng.gen()), ..SolveOptions::default() }) {
+                boards.push((letters, board));
+            }
+        }
+        let bytes = bgb_file(&boards.iter().map(|(letters, board)| (*letters, board)).collect::<Vec<_>>());
+        let parsed = parse_bgb::<ENGLISH_ALPHABET_SIZE>(&bytes).unwrap();
+        assert_eq!(parsed.len(), boards.len());
+        for ((letters, board), (parsed_letters, parsed_board, min_col, max_col, min_row, max_row)) in boards.iter().zip(parsed.iter()) {
+            assert_eq!(letters, parsed_letters);
+            // Tiles keep their positions, not just their arrangement
+            assert_eq!(tiles(board), tiles(parsed_board));
+            assert_eq!(board.bounds(), Some((*min_col, *max_col, *min_row, *max_row)));
+        }
+    }
+
+    #[test]
+    fn bgb_round_trips_empty_boards_and_zero_counts() {
+        let mut letters = [0u8; ENGLISH_ALPHABET_SIZE];
+        letters[2] = 1;
+        letters[19] = 3;
+        let board = board_at("CAT", 20, 4, 7);
+        let bytes = bgb_file(&[([0; ENGLISH_ALPHABET_SIZE], &Board::new(10)), (letters, &board), ([0; ENGLISH_ALPHABET_SIZE], &Board::new(BOARD_SIZE))]);
+        let parsed = parse_bgb::<ENGLISH_ALPHABET_SIZE>(&bytes).unwrap();
+        assert_eq!(parsed.len(), 3);
+        for (parsed_letters, parsed_board, min_col, max_col, min_row, max_row) in [&parsed[0], &parsed[2]] {
+            assert_eq!(*parsed_letters, [0; ENGLISH_ALPHABET_SIZE]);
+            assert!(parsed_board.bounds().is_none());
+            assert_eq!((*min_col, *max_col, *min_row, *max_row), (1, 0, 1, 0));
+        }
+        assert_eq!(parsed[1].0, letters);
+        assert_eq!(tiles(&parsed[1].1), tiles(&board));
+        assert_eq!((parsed[1].2, parsed[1].3, parsed[1].4, parsed[1].5), (7, 9, 4, 4));
+    }
+
+    #[test]
+    fn bgb_round_trips_blank_tiles() {
+        let board = board_at("CaT\nA  \nb  ", 12, 2, 3);
+        assert_eq!(board.blanks().len(), 2);
+        let bytes = bgb_file(&[(hand("CTAA"), &board)]);
+        // The flag is set in the letter value of each blank
+        assert_eq!(bytes.iter().filter(|&&byte| byte != BGB_BOARD_END && byte & BGB_BLANK_FLAG != 0).count(), 2);
+        let (letters, parsed, _, _, _, _) = parse_bgb::<ENGLISH_ALPHABET_SIZE>(&bytes).unwrap().remove(0);
+        assert_eq!(letters, hand("CTAA"));
+        assert_eq!(tiles(&parsed), tiles(&board));
+        assert!(parsed.is_blank(2, 4) && parsed.is_blank(4, 3) && !parsed.is_blank(2, 3));
+    }
+
+    #[test]
+    fn bgb_round_trips_the_largest_board() {
+        let mut board = board_at("AT", BGB_MAX_BOARD_SIZE, 0, 0);
+        board.set_val(BGB_MAX_BOARD_SIZE-1, BGB_MAX_BOARD_SIZE-2, 0);
+        board.set_val(BGB_MAX_BOARD_SIZE-1, BGB_MAX_BOARD_SIZE-1, 19);
+        let bytes = bgb_file(&[(hand("ATAT"), &board)]);
+        let (_, parsed, min_col, max_col, min_row, max_row) = parse_bgb::<ENGLISH_ALPHABET_SIZE>(&bytes).unwrap().remove(0);
+        assert_eq!(tiles(&parsed), tiles(&board));
+        assert_eq!((min_col, max_col, min_row, max_row), (0, BGB_MAX_BOARD_SIZE-1, 0, BGB_MAX_BOARD_SIZE-1));
+        assert!(board_to_bytes(&hand("AT"), &board_at("AT", BGB_MAX_BOARD_SIZE+1, 0, 0)).is_err());
+    }
+
+    #[test]
+    fn bgb_rejects_truncated_records() {
+        let board = board_at("TAB\nO  ", 10, 3, 3);
+        let bytes = bgb_file(&[(hand("TABO"), &board), (hand("TABO"), &board)]);
+        let first_end = 1 + board_to_bytes(&hand("TABO"), &board).unwrap().len();
+        assert_eq!(complete_bgb_records(&bytes).unwrap(), vec![first_end, bytes.len()]);
+        // Cut off within the second board's tiles, within its hand, and just before its end marker
+        for cut in [bytes.len() - 4, first_end + 10, bytes.len() - 1] {
+            let truncated = &bytes[..cut];
+            assert!(parse_bgb::<ENGLISH_ALPHABET_SIZE>(truncated).is_err(), "A file cut off at byte {} was parsed", cut);
+            assert_eq!(complete_bgb_records(truncated).unwrap(), vec![first_end]);
+        }
+        assert!(parse_bgb::<ENGLISH_ALPHABET_SIZE>(&[]).is_err());
+    }
+
+    #[test]
+    fn bgb_metrics_round_trip() {
+        let board = board_at("CAT\n  O\n  E", 10, 2, 2);
+        let metrics = [(SolveMetrics { words_checked: 1234, depth: 2, failed_first_words: 7 }, 56), (SolveMetrics { words_checked: usize::MAX, depth: 0, failed_first_words: 0 }, u128::MAX)];
+        let mut bytes = vec![BGB_METRICS_FORMAT_VERSION];
+        for (metrics, elapsed_ms) in metrics.iter() {
+            bytes.extend(board_to_bytes(&hand("CATOE"), &board).unwrap());
+            bytes.extend(metrics_to_bytes(metrics, *elapsed_ms));
+        }
+        let parsed = parse_bgb::<ENGLISH_ALPHABET_SIZE>(&bytes).unwrap();
+        assert_eq!(parsed.len(), 2);
+        assert!(parsed.iter().all(|(letters, parsed_board, _, _, _, _)| *letters == hand("CATOE") && tiles(parsed_board) == tiles(&board)));
+        // Values too large for a u32 are saved as u32::MAX
+        let saturated = SolveMetrics { words_checked: u32::MAX as usize, depth: 0, failed_first_words: 0 };
+        assert_eq!(parse_bgb_metrics::<ENGLISH_ALPHABET_SIZE>(&bytes).unwrap(), Some(vec![(metrics[0].0, 56), (saturated, u32::MAX as u64)]));
+        assert_eq!(complete_bgb_records(&bytes).unwrap().len(), 2);
+        // Metrics cut off are an error, and the board they follow isn't complete
+        assert!(parse_bgb::<ENGLISH_ALPHABET_SIZE>(&bytes[..bytes.len()-1]).is_err());
+        assert_eq!(complete_bgb_records(&bytes[..bytes.len()-1]).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn bgb_version_1_files_still_parse() {
+        let board = board_at("TO", 5, 1, 1);
+        let bytes = bgb_file(&[(hand("TO"), &board)]);
+        assert_eq!(bytes[0], BGB_FORMAT_VERSION);
+        assert_eq!(parse_bgb_metrics::<ENGLISH_ALPHABET_SIZE>(&bytes).unwrap(), None);
+        let (letters, parsed, _, _, _, _) = parse_bgb::<ENGLISH_ALPHABET_SIZE>(&bytes).unwrap().remove(0);
+        assert_eq!((letters, tiles(&parsed)), (hand("TO"), tiles(&board)));
+        let mut unsupported = bytes.clone();
+        unsupported[0] = 3;
+        assert!(parse_bgb::<ENGLISH_ALPHABET_SIZE>(&unsupported).unwrap_err().contains("Unsupported format version 3"));
+    }
+
+    #[test]
+    fn bgb_reports_the_offset_of_invalid_tiles() {
+        let mut bytes = bgb_file(&[(hand("TO"), &board_at("TO", 5, 1, 1))]);
+        let letter_offset = 1 + ENGLISH_ALPHABET_SIZE + 2;
+        bytes[letter_offset] = ENGLISH_ALPHABET_SIZE as u8;
+        assert!(parse_bgb::<ENGLISH_ALPHABET_SIZE>(&bytes).unwrap_err().contains(&format!("at byte {}", letter_offset)));
+        bytes[letter_offset] = 0;
+        bytes[letter_offset-2] = BGB_MAX_BOARD_SIZE as u8;
+        assert!(parse_bgb::<ENGLISH_ALPHABET_SIZE>(&bytes).unwrap_err().contains(&format!("at byte {}", letter_offset-2)));
+    }
+
+    #[test]
+    fn longer_words_are_played_after_a_shorter_first_word() {
+        // The hand is only solved by playing TOMBS through the T of CAT, which is played first, so TOMBS (earlier in the dictionary since
+        // it's longer) must still be a candidate after it
+        let dictionary = words("TOMBS\nCAT");
+        let anchor = convert_word_to_array("CAT");
+        let Solution { board, plays, .. } = play_bananagrams(hand("CATOMBS"), 0, &dictionary, &SolveOptions { anchor_word: Some(&anchor), ..SolveOptions::default() }).ok().expect("The hand wasn't solved");
+        assert_eq!(plays.iter().map(|play| convert_array_to_word(&play.word)).collect::<Vec<String>>(), ["CAT", "TOMBS"]);
+        assert_eq!(board_to_string(&board), "CAT\n  O\n  M\n  B\n  S");
+    }
+
+    #[test]
+    fn positions_near_the_board_edge_start_from_the_first_row_and_column() {
+        // CAT across the top row, starting in the second column, so a word of 4 letters would start before the first row or column
+        let occupied = [(0, 1), (0, 2), (0, 3)];
+        let mut positions = Vec::new();
+        anchored_positions(&occupied, 4, Direction::Horizontal, 1, 3, 0, 0, &mut positions);
+        assert_eq!(positions, [(0, 2), (0, 3), (0, 4), (1, 0), (1, 1), (1, 2), (1, 3)]);
+        anchored_positions(&occupied, 4, Direction::Vertical, 1, 3, 0, 0, &mut positions);
+        assert_eq!(positions, [(0, 0), (0, 1), (1, 1), (0, 2), (1, 2), (0, 3), (1, 3), (0, 4)]);
+    }
+
+    #[test]
+    fn small_boards_are_solved_like_large_ones() {
+        // On a board of 11, CAT is played across columns 4 to 6, so TOMB (4 letters) is tried from the first column
+        let dictionary = words("TOMB\nCAT");
+        let anchor = convert_word_to_array("CAT");
+        let solve = |board_size: usize| play_bananagrams(hand("CATOMB"), 0, &dictionary, &SolveOptions { board_size, anchor_word: Some(&anchor), ..SolveOptions::default() }).ok().expect("The hand wasn't solved");
+        let Solution { board, min_col, .. } = solve(11);
+        assert_eq!(min_col, 4);
+        assert_eq!(board, solve(BOARD_SIZE).board);
+        assert_eq!(board_to_string(&board), "CAT\n  O\n  M\n  B");
+    }
+
+    #[test]
+    fn words_after_the_first_borrow_at_most_one_letter_from_it() {
+        // CAT was played first, leaving O, M, and B in the hand
+        let first: Word = convert_word_to_array("CAT");
+        let on_board: HashSet<&u8> = first.iter().collect();
+        let letters = hand("OMB");
+        let playable = |word: &str, blanks: u8| check_filter_after_play(letters, blanks, &convert_word_to_array(word), &on_board);
+        assert!(playable("TOMB", 0));
+        assert!(playable("MOB", 0));
+        // Two copies of the same letter from the board
+        assert!(!playable("MOTT", 0));
+        // Two different letters from the board
+        assert!(!playable("ATOM", 0));
+        // A letter that isn't on the board at all
+        assert!(!playable("MOBS", 0));
+        // A blank stands in for the second missing letter
+        assert!(playable("MOTT", 1));
+        assert!(playable("ATOM", 1));
+        assert!(!playable("ATOMS", 1));
+    }
+
+    /// Plays words onto an empty board, as recorded in a solution
+    fn replay(plays: &[PlayedWord], size: usize) -> Board {
+        let mut board = Board::new(size);
+        for play in plays {
+            for (i, &letter) in play.word.iter().enumerate() {
+                match play.direction {
+                    Direction::Horizontal => board.set_val(play.row, play.col + i, letter),
+                    Direction::Vertical => board.set_val(play.row + i, play.col, letter)
+                }
+            }
+        }
+        board
+    }
+
+    #[test]
+    fn directions_display_their_own_names() {
+        assert_eq!(Direction::Horizontal.to_string(), "Horizontal");
+        assert_eq!(Direction::Vertical.to_string(), "Vertical");
+        let play = PlayedWord { word: convert_word_to_array("CAT"), row: 72, col: 70, direction: Direction::Vertical };
+        assert_eq!(play.to_string(), "CAT Vertical at row 72, column 70");
+    }
+
+    #[test]
+    fn recorded_plays_reconstruct_the_board() {
+        let dictionary = words(BENCH_DICTIONARY);
+        for hand_letters in ["AEEIIORSTNLDGHMPCUABY", "EEEEEEEEAAAAAIIIIIOOOOUURRRRRSSSSSTTTTTNNNNNLLLDDDGGHMPCBYWFKX"] {
+            let Solution { board, plays, metrics, .. } = play_bananagrams(hand(hand_letters), 0, &dictionary, &SolveOptions::default()).ok().expect("The hand wasn't solved");
+            assert_eq!(plays.len(), metrics.depth);
+            assert_eq!(plays[0].direction, Direction::Horizontal);
+            assert!(replay(&plays, BOARD_SIZE).identical(&board));
+        }
+    }
+
+    #[test]
+    fn anchored_positions_include_every_position_a_word_can_be_played() {
+        // Every position in the area the solver used to scan, where `play_word` accepts a word, must be among the anchored positions
+        let dictionary = words(BENCH_DICTIONARY);
+        let tile_set = TileSet::standard();
+        let mut rng = seeded_rng(Some(22));
+        let letters = [u8::MAX / 2; ENGLISH_ALPHABET_SIZE];
+        let mut positions = Vec::new();
+        let mut solved = 0;
+        let mut accepted = 0;
+        for _ in 0..10 {
+            let (hand, _) = generate_hand::<ENGLISH_ALPHABET_SIZE>(&mut rng, &tile_set, HandSizeDistribution::Uniform(10, 25), 0);
+            let (mut board, min_col, max_col, min_row, max_row) = match play_bananagrams(hand, 0, &dictionary, &SolveOptions { max_words_checked: 10_000, ..SolveOptions::default() }) {
+                Ok(Solution { board, min_col, max_col, min_row, max_row, .. }) => (board, min_col, max_col, min_row, max_row),
+                Err(_) => continue
+            };
+            solved += 1;
+            let occupied: Vec<(usize, usize)> = tiles(&board).into_iter().map(|(row, col, _, _)| (row, col)).collect();
+            let mut letters_on_board = [0u8; ENGLISH_ALPHABET_SIZE];
+            for word in dictionary.iter().step_by(100) {
+                for direction in [Direction::Horizontal, Direction::Vertical] {
+                    anchored_positions(&occupied, word.len(), direction, min_col, max_col, min_row, max_row, &mut positions);
+                    let (rows, cols) = match direction {
+                        Direction::Horizontal => (min_row-1..=max_row+1, min_col-word.len()..=max_col+1),
+                        Direction::Vertical => (min_row-word.len()..=max_row+1, min_col-1..=max_col+1)
+                    };
+                    for row in rows {
+                        for col in cols.clone() {
+                            let (played, indices, ..) = play_word(word, row, col, &mut board, direction, &letters, 0, &mut letters_on_board, None, Vec::new()).unwrap();
+                            undo_play(&mut board, &indices, &mut letters_on_board, (min_col, max_col, min_row, max_row));
+                            accepted += played as usize;
+                            assert!(!played || positions.contains(&(row, col)), "{} {} at ({}, {}) was left out", convert_array_to_word(word), direction, row, col);
+                        }
+                    }
+                }
+            }
+        }
+        assert!(solved >= 5 && accepted > 0);
+    }
+
+    /// The error for an invalid run of letters
+    fn invalid_word(word: &str, row: usize, col: usize, direction: Direction) -> BoardError {
+        BoardError::InvalidWord(PlayedWord { word: convert_word_to_array(word), row, col, direction })
+    }
+
+    #[test]
+    fn validate_board_accepts_legal_boards() {
+        let valid_words: HashSet<Word> = words("CAT\nTOMB\nAT").into_iter().collect();
+        assert_eq!(validate_board(&board_at("CAT\n  O\n  M\n  B", 16, 5, 5), &valid_words, 2), Ok(()));
+        assert_eq!(validate_board(&board_at("AT", 16, 0, 0), &valid_words, 2), Ok(()));
+        // A single tile has no words to check
+        assert_eq!(validate_board(&board_at("Q", 16, 5, 5), &valid_words, 2), Ok(()));
+    }
+
+    #[test]
+    fn validate_board_reports_each_problem() {
+        let valid_words: HashSet<Word> = words("CAT\nTOMB\nAT").into_iter().collect();
+        assert_eq!(validate_board(&Board::new(16), &valid_words, 2), Err(vec![BoardError::Empty]));
+        // Two islands of valid words
+        assert_eq!(validate_board(&board_at("CAT\n   \n   \nAT ", 16, 5, 5), &valid_words, 2), Err(vec![BoardError::Disconnected { components: 2 }]));
+        // A single stray tile
+        assert_eq!(validate_board(&board_at("CAT  \n     \n    A", 16, 5, 5), &valid_words, 2), Err(vec![BoardError::Disconnected { components: 2 }]));
+        // One invalid word crossing a valid one
+        assert_eq!(validate_board(&board_at("CAT\n  O\n  Q\n  B", 16, 5, 5), &valid_words, 2), Err(vec![invalid_word("TOQB", 5, 7, Direction::Vertical)]));
+        // A word shorter than the minimum length
+        assert_eq!(validate_board(&board_at("CAT\n  O\n  M\n  B", 16, 5, 5), &valid_words, 5), Err(vec![invalid_word("CAT", 5, 5, Direction::Horizontal), invalid_word("TOMB", 5, 7, Direction::Vertical)]));
+        // Every problem at once, with the invalid words in row-major order
+        assert_eq!(validate_board(&board_at("CAX\n  O\n   \nTA ", 16, 5, 5), &valid_words, 2), Err(vec![
+            BoardError::Disconnected { components: 2 },
+            invalid_word("CAX", 5, 5, Direction::Horizontal),
+            invalid_word("XO", 5, 7, Direction::Vertical),
+            invalid_word("TA", 8, 5, Direction::Horizontal)
+        ]));
+    }
+
+    /// Gets the canonical form of a board (see `canonical_form`)
+    fn canonical(board: &Board) -> Vec<u8> {
+        let (min_col, max_col, min_row, max_row) = board.bounds().unwrap();
+        canonical_form(board, min_col, max_col, min_row, max_row)
+    }
+
+    #[test]
+    fn every_symmetry_of_a_board_has_the_same_canonical_form() {
+        for text in ["CAT\n  O\n  M\n  B", "CaT\n  O\n  M\n  b"] {
+            let board = board_at(text, 16, 5, 5);
+            let (min_col, max_col, min_row, max_row) = board.bounds().unwrap();
+            for symmetry in Symmetry::ALL {
+                let (transformed, ..) = transform_board(&board, min_col, max_col, min_row, max_row, symmetry);
+                assert_eq!(canonical(&transformed), canonical(&board), "{:?} of {:?}", symmetry, text);
+            }
+        }
+        assert_eq!(canonical(&board_at("CAT\n  O\n  M\n  B", 16, 5, 5)), canonical(&board_at("C   \nA   \nTOMB", 16, 0, 0)));
+    }
+
+    #[test]
+    fn different_boards_have_different_canonical_forms() {
+        let boards = ["CAT\n  O\n  M\n  B", "CAT\n O \n M \n B ", "CAT\nO  \nM  \nB  ", "ACT\n  O\n  M\n  B", "CaT\n  O\n  M\n  B", "cAT\n  O\n  M\n  B", "CAT\n  o\n  M\n  B"];
+        let forms: Vec<Vec<u8>> = boards.iter().map(|text| canonical(&board_at(text, 16, 5, 5))).collect();
+        for (i, form) in forms.iter().enumerate() {
+            for (j, other) in forms.iter().enumerate().skip(i+1) {
+                assert_ne!(form, other, "{:?} and {:?}", boards[i], boards[j]);
+            }
+        }
+    }
+
+    #[test]
+    fn every_kind_of_corruption_makes_boards_invalid() {
+        let dictionary = words(BENCH_DICTIONARY);
+        let valid_words: HashSet<Word> = dictionary.iter().cloned().collect();
+        let letters = hand("AEEIIORSTNLDGHMPCUABY");
+        let Solution { board, .. } = play_bananagrams(letters, 0, &dictionary, &SolveOptions::default()).ok().expect("The hand wasn't solved");
+        let mut rng = seeded_rng(Some(29));
+        let mut seen = [false; Corruption::ALL.len()];
+        // Written as `training_data` writes its negatives file, with the label before each board
+        let mut bytes = vec![BGB_FORMAT_VERSION];
+        let mut corruptions = Vec::new();
+        for _ in 0..100 {
+            if let Some((corrupted, .., corruption)) = corrupt_board::<ENGLISH_ALPHABET_SIZE>(&board, &mut rng, 1) {
+                assert!(validate_board(&corrupted, &valid_words, DEFAULT_MIN_WORD_LENGTH).is_err(), "{} left the board valid:\n{}", corruption, board_to_string(&corrupted));
+                seen[corruption.label() as usize] = true;
+                bytes.push(corruption.label());
+                bytes.extend(board_to_bytes(&letters, &corrupted).unwrap());
+                corruptions.push((corruption, corrupted));
+            }
+        }
+        assert!(seen.iter().all(|&seen| seen));
+        let parsed = parse_labeled_bgb(&bytes).unwrap();
+        assert_eq!(parsed.len(), corruptions.len());
+        for ((label, (parsed_letters, parsed_board, ..)), (corruption, corrupted)) in parsed.iter().zip(corruptions.iter()) {
+            assert_eq!(Corruption::from_label(*label), Some(*corruption));
+            assert_eq!(*parsed_letters, letters);
+            assert_eq!(parsed_board, corrupted);
+        }
+    }
+
+    #[test]
+    fn replaced_letters_are_from_the_alphabet() {
+        // Words of the first 3 letters of the alphabet
+        let board = board_at("ABC\nB  \nA  ", 16, 5, 5);
+        let mut rng = seeded_rng(Some(29));
+        let mut replaced = 0;
+        for _ in 0..100 {
+            if let Some((corrupted, .., Corruption::ReplaceLetter)) = corrupt_board::<3>(&board, &mut rng, 1) {
+                replaced += 1;
+                assert!(tiles(&corrupted).iter().all(|&(_, _, letter, _)| letter < 3), "{}", board_to_string(&corrupted));
+                assert!(!corrupted.identical(&board));
+            }
+        }
+        assert!(replaced > 0);
+        // An alphabet of one letter has no other letter to replace one with
+        assert!((0..100).all(|_| !matches!(corrupt_board::<1>(&board_at("AA", 16, 5, 5), &mut rng, 1), Some((.., Corruption::ReplaceLetter)))));
+    }
+
+    /// Makes a hand and a board from its letters and text (see `board_at`), with the board's bounds, as `parse_bgb` returns them
+    fn hand_board(letters: &str, text: &str) -> HandBoard {
+        let board = board_at(text, 16, 3, 2);
+        let (min_col, max_col, min_row, max_row) = board.bounds().unwrap();
+        (hand(letters), board, min_col, max_col, min_row, max_row)
+    }
+
+    /// Checks that boards read back from a `.bgb2` file are the ones written, moved to the top-left corner
+    fn assert_same_boards(read: &[HandBoard], written: &[HandBoard]) {
+        assert_eq!(read.len(), written.len());
+        for ((letters, board, min_col, max_col, min_row, max_row), written) in read.iter().zip(written.iter()) {
+            assert_eq!(*letters, written.0);
+            assert_eq!(*board, written.1);
+            assert_eq!(board.bounds(), written.1.bounds().map(|(min_col, max_col, min_row, max_row)| (0, max_col - min_col, 0, max_row - min_row)));
+            assert_eq!(board.bounds().unwrap_or((1, 0, 1, 0)), (*min_col, *max_col, *min_row, *max_row));
+        }
+    }
+
+    /// Hands and boards to save, including blanks and an empty board
+    fn sample_hand_boards() -> Vec<HandBoard> {
+        vec![
+            hand_board("CATOMB", "CAT\n  O\n  M\n  B"),
+            hand_board("CTAA", "CaT\nA  \nb  "),
+            ([0; ENGLISH_ALPHABET_SIZE], Board::new(10), 1, 0, 1, 0),
+            hand_board("TOMBS", "TOMBS")
+        ]
+    }
+
+    #[test]
+    fn bgb2_round_trips_hands_and_boards() {
+        let boards = sample_hand_boards();
+        let bytes = write_bgb2(&boards).unwrap();
+        assert!(bytes.starts_with(b"BGB2\x02"));
+        assert_same_boards(&read_bgb2(&bytes).unwrap(), &boards);
+        // Each record can be read on its own, in any order
+        let offsets = index_bgb2(&bytes).unwrap();
+        assert_eq!(offsets.len(), boards.len());
+        assert_eq!(offsets[0], BGB2_MAGIC.len() + 1);
+        for (offset, written) in offsets.iter().zip(boards.iter()).rev() {
+            assert_same_boards(&[read_bgb2_record(&bytes, *offset).unwrap()], std::slice::from_ref(written));
+        }
+        // Files are detected by their magic bytes
+        assert_same_boards(&read_boards(&bytes).unwrap(), &boards);
+    }
+
+    #[test]
+    fn bgb2_rejects_boards_too_wide_to_save() {
+        let mut board = Board::new(300);
+        board.set_val(0, 0, 0);
+        board.set_val(0, BGB2_MAX_DIMENSION, 0);
+        assert!(board_to_bgb2(&hand("AA"), &board, 0, BGB2_MAX_DIMENSION, 0, 0).is_err());
+        assert!(board_to_bgb2(&hand("AA"), &board, 0, BGB2_MAX_DIMENSION - 1, 0, 0).is_ok());
+    }
+
+    #[test]
+    fn bgb_files_cut_off_partway_upgrade_their_complete_boards() {
+        let boards = sample_hand_boards();
+        let bytes = bgb_file(&boards.iter().map(|(letters, board, ..)| (*letters, board)).collect::<Vec<_>>());
+        let ends = complete_bgb_records(&bytes).unwrap();
+        assert_eq!(ends.len(), boards.len());
+        assert_eq!(ends.last(), Some(&bytes.len()));
+        // Cut off partway through the last board, as by an interrupted run
+        let cut = &bytes[..bytes.len() - 3];
+        assert!(parse_bgb::<ENGLISH_ALPHABET_SIZE>(cut).is_err());
+        assert_eq!(complete_bgb_records(cut).unwrap(), ends[..boards.len() - 1]);
+        let complete = parse_bgb::<ENGLISH_ALPHABET_SIZE>(&cut[..ends[boards.len() - 2]]).unwrap();
+        let upgraded = write_bgb2(&complete).unwrap();
+        assert_same_boards(&read_bgb2(&upgraded).unwrap(), &boards[..boards.len() - 1]);
+        // A .bgb2 file cut off partway through its last record is rejected, unless the incomplete record is left out
+        let cut = &upgraded[..upgraded.len() - 1];
+        assert!(index_bgb2(cut).unwrap_err().contains("truncated"));
+        assert!(read_bgb2(cut).is_err());
+        let offsets = index_bgb2(&upgraded).unwrap();
+        assert_eq!(index_bgb2_records(cut, true).unwrap(), offsets[..offsets.len() - 1]);
+        // Likewise partway through the length of the last record
+        let cut = &upgraded[..offsets[offsets.len() - 1] + 1];
+        assert!(index_bgb2(cut).unwrap_err().contains("truncated"));
+        assert_eq!(index_bgb2_records(cut, true).unwrap(), offsets[..offsets.len() - 1]);
+    }
+
+    /// Hands and boards for the compression tests, 100 in all
+    fn hundred_hand_boards() -> Vec<HandBoard> {
+        sample_hand_boards().into_iter().cycle().take(100).collect()
+    }
+
+    /// Checks that boards read back from a `.bgb` file are the ones written, in the same places
+    fn assert_same_bgb_boards(read: &[HandBoard], written: &[HandBoard]) {
+        assert_eq!(read.len(), written.len());
+        for ((letters, board, ..), written) in read.iter().zip(written.iter()) {
+            assert_eq!(*letters, written.0);
+            assert_eq!(tiles(board), tiles(&written.1));
+        }
+    }
+
+    /// Checks how `read_boards` reads a file that's cut off and one that's corrupt
+    /// # Arguments
+    /// * `boards` - The boards in the file
+    /// * `compressed` - The compressed file, flushed after each board
+    /// * `corrupt` - A copy of `compressed` with some bytes changed
+    #[cfg(any(feature = "gzip", feature = "zstd"))]
+    fn check_cut_off_and_corrupt(boards: &[HandBoard], compressed: &[u8], corrupt: &[u8]) {
+        assert!(!decompress(compressed).unwrap().1);
+        assert_same_bgb_boards(&read_boards(compressed).unwrap(), boards);
+        // Only the complete boards of a file cut off partway through are read
+        let (_, truncated) = decompress(&compressed[..compressed.len() / 2]).unwrap();
+        assert!(truncated);
+        let read = read_boards(&compressed[..compressed.len() / 2]).unwrap();
+        assert!(!read.is_empty() && read.len() < boards.len());
+        assert_same_bgb_boards(&read, &boards[..read.len()]);
+        assert!(decompress(corrupt).unwrap_err().contains("corrupt"));
+        assert!(read_boards(corrupt).unwrap_err().contains("corrupt"));
+    }
+
+    #[test]
+    #[cfg(feature = "gzip")]
+    fn gzip_files_are_read_whole_cut_off_or_corrupt() {
+        use std::io::Write;
+        let boards = hundred_hand_boards();
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&[BGB_FORMAT_VERSION]).unwrap();
+        for (letters, board, ..) in boards.iter() {
+            encoder.write_all(&board_to_bytes(letters, board).unwrap()).unwrap();
+            encoder.flush().unwrap();
+        }
+        let compressed = encoder.finish().unwrap();
+        assert!(compressed.starts_with(&GZIP_MAGIC));
+        // Caught by the checksum at the end of the stream
+        let mut corrupt = compressed.clone();
+        corrupt[compressed.len() / 2] ^= 0xff;
+        check_cut_off_and_corrupt(&boards, &compressed, &corrupt);
+    }
+
+    #[test]
+    #[cfg(feature = "zstd")]
+    fn zstd_files_are_read_whole_cut_off_or_corrupt() {
+        use std::io::Write;
+        let boards = hundred_hand_boards();
+        let mut encoder = zstd::stream::write::Encoder::new(Vec::new(), 3).unwrap();
+        encoder.include_checksum(true).unwrap();
+        encoder.write_all(&[BGB_FORMAT_VERSION]).unwrap();
+        for (letters, board, ..) in boards.iter() {
+            encoder.write_all(&board_to_bytes(letters, board).unwrap()).unwrap();
+            encoder.flush().unwrap();
+        }
+        let compressed = encoder.finish().unwrap();
+        assert!(compressed.starts_with(&ZSTD_MAGIC));
+        let mut corrupt = compressed.clone();
+        corrupt[compressed.len() - 2] ^= 0xff;
+        check_cut_off_and_corrupt(&boards, &compressed, &corrupt);
+    }
+
+    #[test]
+    fn uncompressed_files_are_read_whole_or_rejected() {
+        let boards = hundred_hand_boards();
+        let bytes = bgb_file(&boards.iter().map(|(letters, board, ..)| (*letters, board)).collect::<Vec<_>>());
+        let (decompressed, truncated) = decompress(&bytes).unwrap();
+        assert!(matches!(decompressed, Cow::Borrowed(_)));
+        assert!(!truncated);
+        assert_same_bgb_boards(&read_boards(&bytes).unwrap(), &boards);
+        // Without a compressed stream to tell that it was cut off, an incomplete board is an error, as is an invalid letter
+        assert!(read_boards(&bytes[..bytes.len() - 3]).is_err());
+        let mut corrupt = bytes.clone();
+        corrupt[1 + ENGLISH_ALPHABET_SIZE + 2] = 200;
+        assert!(read_boards(&corrupt).is_err());
+    }
+
+
+    /// A tile set of mostly vowels, with no tiles of most consonants
+    const VOWEL_HEAVY_TILES: &str = "A 20\nE 20\nI 12\nO 12\nU 6\n\nT 10\nS 10\nR 8\nN 8\nL 6\nD 4\n";
+
+    #[test]
+    fn hands_are_only_drawn_from_the_tiles_of_the_tile_set() {
+        let tiles = TileSet::parse(VOWEL_HEAVY_TILES, &Alphabet::english()).unwrap();
+        assert_eq!(tiles.len(), 116);
+        assert_eq!(tiles.maximum_hand_size(), 58);
+        let counts: Letters = tiles.counts();
+        assert_eq!(counts, hand("AAAAAAAAAAAAAAAAAAAAEEEEEEEEEEEEEEEEEEEEIIIIIIIIIIIIOOOOOOOOOOOOUUUUUUTTTTTTTTTTSSSSSSSSSSRRRRRRRRNNNNNNNNLLLLLLDDDD"));
+        let mut rng = seeded_rng(Some(42));
+        for hand_sizes in [HandSizeDistribution::Uniform(MINIMUM_HAND_SIZE as usize, tiles.maximum_hand_size()), HandSizeDistribution::Fixed(tiles.len())] {
+            for _ in 0..200 {
+                let (letters, _): (Letters, usize) = generate_hand(&mut rng, &tiles, hand_sizes, 0);
+                assert!(letters.iter().zip(counts.iter()).all(|(drawn, pool)| drawn <= pool), "{:?} isn't drawn from the tile set", letters);
+                assert!((MINIMUM_HAND_SIZE as usize..=tiles.len()).contains(&letters.iter().map(|&count| count as usize).sum::<usize>()));
+            }
+        }
+        // Drawing every tile gives back the whole set
+        let (letters, _): (Letters, usize) = generate_hand(&mut rng, &tiles, HandSizeDistribution::Fixed(tiles.len()), 0);
+        assert_eq!(letters, counts);
+    }
+
+    #[test]
+    fn the_standard_tile_set_has_144_tiles() {
+        let tiles = TileSet::standard();
+        assert_eq!(tiles.len(), 144);
+        assert_eq!(tiles.maximum_hand_size(), 72);
+        assert_eq!(tiles.counts::<ENGLISH_ALPHABET_SIZE>().iter().map(|&count| count as usize).sum::<usize>(), TO_CHOOSE_FROM.len());
+    }
+
+    #[test]
+    fn invalid_tile_sets_are_rejected() {
+        let english = Alphabet::english();
+        for (text, error) in [("A 3\n1 2", "Invalid letter \"1\" on line 2"), ("A 0", "Invalid count \"0\""), ("A -2", "Invalid count \"-2\""), ("A x", "Invalid count"),
+                              ("A 3\nB", "Line 2 isn't of the form"), ("A 3 4", "Line 1 isn't of the form"), ("A 3\na 2", "The letter A is given more than once"),
+                              ("A 256", "Too many tiles of the letter A"), ("", "no tiles")] {
+            match TileSet::parse(text, &english) {
+                Ok(_) => panic!("{:?} was parsed", text),
+                Err(e) => assert!(e.contains(error), "{:?} gave {:?}", text, e)
+            }
+        }
+        // Letters may be given in either case and any order
+        assert_eq!(TileSet::parse("z 1\nA 2", &english).unwrap().counts::<ENGLISH_ALPHABET_SIZE>(), hand("AAZ"));
+    }
+
+
+    #[test]
+    fn boards_of_a_30_letter_alphabet_are_solved_validated_and_saved() {
+        let alphabet = Alphabet::parse(&((b'A'..=b'Z').map(|c| (c as char).to_string()).collect::<Vec<_>>().join("\n") + "\nñ\nLL\nRR\nCH")).unwrap();
+        assert_eq!(alphabet.len(), 30);
+        // The longest symbol is matched at each point, so LLAMA starts with the LL tile
+        assert_eq!(alphabet.convert_word("llama").unwrap().as_slice(), [27, 0, 12, 0]);
+        assert_eq!(alphabet.convert_word("Año").unwrap().as_slice(), [0, 26, 14]);
+        let dictionary = alphabet.parse_dictionary("LLAMA\nAÑO\nPERRO\nOCHO\nCHAO\nMAÑANA\nAMO\nMAL\nOSO", false).unwrap();
+        let valid_words: HashSet<Word> = dictionary.iter().cloned().collect();
+        let letters: Letters<30> = alphabet.hand("LLAMAÑOPERR").unwrap();
+        assert_eq!(letters.iter().map(|&count| count as usize).sum::<usize>(), 9);
+        assert_eq!(alphabet.letters_to_string(&letters), "AAEMOPÑLLRR");
+        let tile_set = TileSet::parse("A 10\nÑ 2\nLL 2\nRR 2\nCH 2\nO 8\nM 3\nP 3\nE 8", &alphabet).unwrap();
+        assert_eq!(tile_set.alphabet_size(), 30);
+        assert!(check_hand_against_pool(&letters, &tile_set).is_ok());
+        let Solution { board, min_col, max_col, min_row, max_row, .. } = play_bananagrams(letters, 0, &dictionary, &SolveOptions::default()).ok().expect("The hand wasn't solved");
+        assert_eq!(validate_board(&board, &valid_words, DEFAULT_MIN_WORD_LENGTH), Ok(()));
+        let mut used = [0u8; 30];
+        for (_, _, val, _) in tiles(&board) {
+            used[val as usize] += 1;
+        }
+        assert_eq!(used, letters);
+        let words: Vec<String> = extract_words(&board, min_col, max_col, min_row, max_row).into_iter().map(|(word, ..)| word).collect();
+        assert!(!words.is_empty());
+        // Letters past Z keep their values in .bgb files
+        let mut bytes = vec![BGB_FORMAT_VERSION];
+        bytes.extend(board_to_bytes(&letters, &board).unwrap());
+        let (parsed_letters, parsed, ..) = parse_bgb::<30>(&bytes).unwrap().remove(0);
+        assert_eq!(parsed_letters, letters);
+        assert_eq!(tiles(&parsed), tiles(&board));
+        assert!(parse_bgb::<ENGLISH_ALPHABET_SIZE>(&bytes).is_err());
+        // Hands drawn from the tile set stay within its 30 letters
+        let mut rng = seeded_rng(Some(43));
+        for _ in 0..100 {
+            let (drawn, _): (Letters<30>, usize) = generate_hand(&mut rng, &tile_set, HandSizeDistribution::Uniform(5, 20), 0);
+            assert!(check_hand_against_pool(&drawn, &tile_set).is_ok());
+        }
+    }
+
+
+    #[test]
+    fn blanks_stand_for_letters_missing_from_the_hand() {
+        let cat = convert_word_to_array("CAT");
+        assert!(is_makeable(&cat, &hand("CAT"), 0));
+        assert!(is_makeable(&cat, &hand("CA"), 1));
+        assert!(is_makeable(&cat, &[0; ENGLISH_ALPHABET_SIZE], 3));
+        assert!(!is_makeable(&cat, &hand("CA"), 0));
+        assert!(!is_makeable(&cat, &hand("C"), 1));
+        // A word using a letter outside the alphabet of the hand is never makeable, even with blanks
+        assert!(!is_makeable(&vec![0u8, 30].into(), &hand("A"), 5));
+    }
+
+    #[test]
+    fn hands_only_solvable_with_a_blank_are_solved_with_it() {
+        let dictionary = words("TOMB\nCAT\nBAT\nMOB");
+        let valid_words: HashSet<Word> = dictionary.iter().cloned().collect();
+        let solve = |letters: &str, blanks: u8| play_bananagrams(hand(letters), blanks, &dictionary, &SolveOptions::default());
+        // Without an A, CAT can only cross TOMB with a blank
+        assert!(solve("TOMBC", 0).is_err());
+        let Solution { board, .. } = solve("TOMBC", 1).ok().expect("The hand wasn't solved");
+        assert_eq!(validate_board(&board, &valid_words, DEFAULT_MIN_WORD_LENGTH), Ok(()));
+        assert_eq!(board.blanks().len(), 1);
+        let (row, col) = board.blanks()[0];
+        // The board holds the letter the blank was played as
+        assert_eq!(board.get_val(row, col), 0);
+        assert_eq!(tiles(&board).len(), 6);
+        // Every blank in the hand is played, even when the letters alone would do
+        assert!(solve("TOMBCA", 1).is_ok_and(|Solution { board, .. }| board.blanks().len() == 1));
+        assert!(solve("TOMBC", 3).is_ok_and(|Solution { board, .. }| board.blanks().len() == 3 && tiles(&board).len() == 8));
+    }
+
+    #[test]
+    fn blanks_are_kept_through_bgb_and_bgb2_files() {
+        let dictionary = words("TOMB\nCAT\nBAT\nMOB");
+        let Solution { board, .. } = play_bananagrams(hand("TOMBC"), 1, &dictionary, &SolveOptions::default()).ok().expect("The hand wasn't solved");
+        let (_, parsed, ..) = parse_bgb::<ENGLISH_ALPHABET_SIZE>(&bgb_file(&[(hand("TOMBC"), &board)])).unwrap().remove(0);
+        assert_eq!(tiles(&parsed), tiles(&board));
+        assert_eq!(parsed.blanks(), board.blanks());
+    }
+
+    #[test]
+    fn clearing_a_blank_tile_returns_the_blank() {
+        let mut board = board_at("CAT", 8, 2, 2);
+        board.set_blank(2, 3);
+        // Replacing the letter a blank stands for keeps it a blank
+        board.set_val(2, 3, 14);
+        assert_eq!(board.blanks(), &[(2, 3)]);
+        board.set_val(2, 3, EMPTY_VALUE);
+        assert!(board.blanks().is_empty());
+        // So a letter played there later is an ordinary tile
+        board.set_val(2, 3, 0);
+        assert!(!board.is_blank(2, 3));
+        assert!(board.identical(&board_at("CAT", 8, 2, 2)));
+    }
+
+    #[test]
+    fn short_crossings_are_invalid_below_the_minimum_word_length() {
+        let valid_words: HashSet<Word> = words("CAT\nTO\nAT").into_iter().collect();
+        // Playing TO under CAT makes the two-letter crossings AT and TO
+        let horizontal = board_at("CAT\n TO", 16, 5, 5);
+        assert!(is_board_valid_horizontal(&horizontal, 5, 7, 5, 6, 6, 6, 7, &valid_words, 2));
+        assert!(!is_board_valid_horizontal(&horizontal, 5, 7, 5, 6, 6, 6, 7, &valid_words, 3));
+        let vertical = board_at("C \nAT\nTO", 16, 5, 5);
+        assert!(is_board_valid_vertical(&vertical, 5, 6, 5, 7, 6, 7, 6, &valid_words, 2));
+        assert!(!is_board_valid_vertical(&vertical, 5, 6, 5, 7, 6, 7, 6, &valid_words, 3));
+        assert_eq!(validate_board(&horizontal, &valid_words, 2), Ok(()));
+        assert_eq!(validate_board(&horizontal, &valid_words, 3), Err(vec![
+            invalid_word("AT", 5, 6, Direction::Vertical),
+            invalid_word("TO", 5, 7, Direction::Vertical),
+            invalid_word("TO", 6, 6, Direction::Horizontal)
+        ]));
+    }
+
+    #[test]
+    fn the_solver_only_plays_words_of_the_minimum_length() {
+        let dictionary = words("CAT\nTO\nAT\nCOAT\nTACO");
+        let solve = |letters: &str, min_word_length: usize| play_bananagrams(hand(letters), 0, &dictionary, &SolveOptions { min_word_length, ..SolveOptions::default() });
+        let Solution { board, .. } = solve("CATTO", 2).ok().expect("The hand wasn't solved");
+        assert_eq!(validate_board(&board, &dictionary.iter().cloned().collect(), 2), Ok(()));
+        // CATTO needs a two-letter word, but TACO doesn't
+        assert!(solve("CATTO", 3).is_err());
+        let Solution { board, .. } = solve("TACO", 3).ok().expect("The hand wasn't solved");
+        assert_eq!(validate_board(&board, &dictionary.iter().cloned().collect(), 3), Ok(()));
+        let mut short_words = dictionary.clone();
+        remove_short_words(&mut short_words, None, 3);
+        assert_eq!(short_words, words("CAT\nCOAT\nTACO"));
+    }
+
+
+    #[test]
+    fn dictionary_frequencies_are_read_alongside_their_words() {
+        let english = Alphabet::english();
+        let (dictionary, frequencies) = english.parse_weighted_dictionary_lines("cat 120\nTACO\n\nat 0.5\nCAT 3\ntomb  7e2".lines(), false).unwrap();
+        // Longest first, a repeated word keeping its first frequency, and words without one getting 1
+        assert_eq!(dictionary, words("TACO\nTOMB\nCAT\nAT"));
+        assert_eq!(frequencies, Some(vec![1.0, 700.0, 120.0, 0.5]));
+        assert_eq!(english.parse_weighted_dictionary_lines("CAT\nAT".lines(), false).unwrap().1, None);
+        for invalid in ["CAT -1", "CAT x", "CAT inf", "CAT 1 2"] {
+            assert!(matches!(english.parse_weighted_dictionary_lines([invalid], false), Err(DictionaryError::InvalidFrequency { line: 1, .. })), "{:?} was parsed", invalid);
+        }
+        let (mut dictionary, mut frequencies) = (dictionary, frequencies.unwrap());
+        remove_short_words(&mut dictionary, Some(&mut frequencies), 3);
+        assert_eq!((dictionary, frequencies), (words("TACO\nTOMB\nCAT"), vec![1.0, 700.0, 120.0]));
+    }
+
+    #[test]
+    fn a_temperature_of_0_chooses_words_in_order_of_frequency() {
+        let frequencies = [5.0, 40.0, 0.0, 40.0, 12.0];
+        for seed in 0..20 {
+            let mut rng = seeded_rng(Some(seed));
+            // The first of the most frequent, whatever the seed
+            assert_eq!(choose_by_frequency(0..5, &frequencies, 0.0, &mut rng), Some(1));
+            assert_eq!(choose_by_frequency([0, 2, 4], &frequencies, 0.0, &mut rng), Some(4));
+            assert_eq!(choose_by_frequency([2], &frequencies, 0.0, &mut rng), Some(2));
+        }
+        assert_eq!(choose_by_frequency([], &frequencies, 0.0, &mut seeded_rng(Some(0))), None);
+        let dictionary = words("TACO\nTOMB\nCOAT\nCAT\nBAT\nAT");
+        let frequencies = [2.0, 8.0, 30.0, 5.0, 9.0, 1.0];
+        for seed in 0..5 {
+            assert_eq!(order_by_frequency(&dictionary, &frequencies, 0.0, &mut seeded_rng(Some(seed))), words("COAT\nTOMB\nTACO\nBAT\nCAT\nAT"));
+        }
+    }
+
+    #[test]
+    fn higher_temperatures_sample_closer_to_uniformly() {
+        let frequencies = [1.0, 9.0];
+        let mut rng = seeded_rng(Some(47));
+        let share_of_frequent = |temperature: f64, rng: &mut StdRng| (0..10_000).filter(|_| choose_by_frequency(0..2, &frequencies, temperature, rng) == Some(1)).count() as f64 / 10_000.0;
+        // In proportion to frequency at 1, and approaching half and half as the temperature rises
+        assert!((share_of_frequent(1.0, &mut rng) - 0.9).abs() < 0.02);
+        assert!((share_of_frequent(1000.0, &mut rng) - 0.5).abs() < 0.02);
+        assert!(share_of_frequent(0.25, &mut rng) > 0.99);
+        // Words that are never used are never chosen, unless every word's frequency is 0
+        assert!((0..100).all(|_| choose_by_frequency(0..3, &[0.0, 1.0, 0.0], 1.0, &mut rng) == Some(1)));
+        assert!((0..100).any(|_| choose_by_frequency(0..2, &[0.0, 0.0], 1.0, &mut rng) == Some(0)));
+        // Ordering keeps the longest words first at any temperature
+        let dictionary = words("TACO\nTOMB\nCOAT\nCAT\nBAT\nAT");
+        let ordered = order_by_frequency(&dictionary, &[2.0, 8.0, 30.0, 5.0, 9.0, 1.0], 1.0, &mut rng);
+        assert!(ordered.windows(2).all(|pair| pair[0].len() >= pair[1].len()));
+        assert_eq!(ordered.iter().collect::<HashSet<_>>(), dictionary.iter().collect::<HashSet<_>>());
+    }
+
+
+    #[test]
+    fn banned_words_are_only_found_as_whole_words() {
+        let banned: HashSet<Word> = parse_dictionary("cat\nOM", false).unwrap().into_iter().collect();
+        // CATS and TOMB contain banned words but aren't banned themselves
+        let board = board_at("CATS\n   O\n   M\n   B", 16, 5, 5);
+        assert_eq!(find_banned_words(&board, 5, 8, 5, 8, &banned), Vec::new());
+        let board = board_at("CAT\n  O\n  M\n  B", 16, 5, 5);
+        assert_eq!(find_banned_words(&board, 5, 7, 5, 8, &banned), vec![PlayedWord { word: convert_word_to_array("CAT"), row: 5, col: 5, direction: Direction::Horizontal }]);
+        // Including one formed by a crossing, which here is MA down from the M of TOMB
+        let board = board_at("TOMB\n  AT", 16, 5, 5);
+        let banned: HashSet<Word> = parse_dictionary("ma", false).unwrap().into_iter().collect();
+        assert_eq!(find_banned_words(&board, 5, 8, 5, 6, &banned), vec![PlayedWord { word: convert_word_to_array("MA"), row: 5, col: 7, direction: Direction::Vertical }]);
+        assert!(find_banned_words(&board, 5, 8, 5, 6, &HashSet::new()).is_empty());
+    }
+
+    #[test]
+    fn hands_only_solvable_with_a_banned_word_are_unsolved() {
+        let mut dictionary = words("TOMB\nCAT\nMOB\nBAT");
+        let solve = |dictionary: &Vec<Word>| play_bananagrams(hand("TOMBCA"), 0, dictionary, &SolveOptions::default());
+        assert!(solve(&dictionary).is_ok());
+        let banned: HashSet<Word> = parse_dictionary("Cat", false).unwrap().into_iter().collect();
+        retain_words(&mut dictionary, None, |word| !banned.contains(word));
+        assert_eq!(dictionary, words("TOMB\nMOB\nBAT"));
+        assert!(solve(&dictionary).is_err());
+    }
+
+
+    /// Solves a hand without blanks, returning the best partial board if it can't be solved
+    fn solve_partially(letters: &str, dictionary: &Vec<Word>, allow_partial: bool) -> Result<Solution, Unsolved> {
+        play_bananagrams(hand(letters), 0, dictionary, &SolveOptions { allow_partial, ..SolveOptions::default() })
+    }
+
+    #[test]
+    fn a_lone_q_is_left_over_from_a_partial_board() {
+        let dictionary = words("TOMB\nCAT\nBAT\nQUA\nQAT");
+        let valid_words: HashSet<Word> = dictionary.iter().cloned().collect();
+        // QAT is the only word with a Q, and playing it leaves a B that can't be played
+        let (reason, _, partial) = solve_partially("TOMBCAQ", &dictionary, true).expect_err("The hand was solved");
+        assert_eq!(reason, SolveFailure::Unsolvable);
+        let partial = partial.expect("No partial board was returned");
+        assert_eq!(partial.unused_letters, hand("Q"));
+        assert_eq!(partial.unused_blanks, 0);
+        assert_eq!(validate_board(&partial.board, &valid_words, DEFAULT_MIN_WORD_LENGTH), Ok(()));
+        assert_eq!(partial.board.bounds(), Some((partial.min_col, partial.max_col, partial.min_row, partial.max_row)));
+        assert_eq!(tiles(&partial.board).len(), 6);
+        assert!(replay(&partial.plays, BOARD_SIZE).identical(&partial.board));
+        // Also when the solver runs out of words to check
+        let (reason, _, partial) = play_bananagrams(hand("TOMBCAQ"), 0, &dictionary, &SolveOptions { max_words_checked: 2, allow_partial: true, ..SolveOptions::default() }).expect_err("The hand was solved");
+        assert_eq!(reason, SolveFailure::WordBudget);
+        assert!(partial.is_some_and(|partial| validate_board(&partial.board, &valid_words, DEFAULT_MIN_WORD_LENGTH).is_ok()));
+        // Only asked for partial boards are kept
+        assert!(solve_partially("TOMBCAQ", &dictionary, false).is_err_and(|(_, _, partial)| partial.is_none()));
+        assert!(solve_partially("TOMBCA", &dictionary, true).is_ok());
+    }
+
+    #[test]
+    fn partial_boards_with_as_many_tiles_leave_the_least_rare_letters() {
+        // CATE and QATE can't both be played, and C is in more words than Q, so leaving the C over is better
+        let dictionary = words("CATE\nQATE\nACE\nEAT");
+        let (_, _, partial) = solve_partially("CATQE", &dictionary, true).expect_err("The hand was solved");
+        let partial = partial.expect("No partial board was returned");
+        assert_eq!(partial.unused_letters, hand("C"));
+        assert_eq!(partial.plays.iter().map(|play| convert_array_to_word(&play.word)).collect::<Vec<_>>(), ["QATE"]);
+    }
+
+
+    #[test]
+    fn several_distinct_boards_are_found_for_a_hand() {
+        let dictionary = words("CAT\nDOG\nCOG\nDOT\nTAG\nGOAT\nTOAD\nCOD\nACT\nGOD\nDOC\nCOT\nTOG");
+        let valid_words: HashSet<Word> = dictionary.iter().cloned().collect();
+        let solve = |n: usize, seed: u64| play_bananagrams_multi(hand("CATDOG"), 0, &dictionary, n, &SolveOptions::default(), &mut seeded_rng(Some(seed)));
+        let solutions = solve(4, 52).ok().expect("The hand wasn't solved");
+        assert!(solutions.len() >= 2);
+        let forms: HashSet<Vec<u8>> = solutions.iter().map(|Solution { board, .. }| canonical(board)).collect();
+        assert_eq!(forms.len(), solutions.len());
+        for Solution { board, min_col, max_col, min_row, max_row, metrics, .. } in solutions.iter() {
+            assert_eq!(validate_board(board, &valid_words, DEFAULT_MIN_WORD_LENGTH), Ok(()));
+            assert_eq!(board.bounds(), Some((*min_col, *max_col, *min_row, *max_row)));
+            assert_eq!(tiles(board).len(), 6);
+            assert!(metrics.words_checked > 0);
+        }
+        // The words checked add up across the boards found
+        assert!(solutions.windows(2).all(|pair| pair[0].metrics.words_checked <= pair[1].metrics.words_checked));
+        assert_eq!(solve(1, 52).ok().map(|solutions| solutions.len()), Some(1));
+        let repeated = solve(4, 52).ok().expect("The hand wasn't solved");
+        assert!(solutions.iter().zip(repeated.iter()).all(|(a, b)| a.board.identical(&b.board)));
+    }
+
+    #[test]
+    fn hands_with_one_board_return_only_it() {
+        // TOMB and CAT can only cross at the T, so every board is a symmetry of the same one
+        let dictionary = words("TOMB\nCAT");
+        let solutions = play_bananagrams_multi(hand("TOMBCA"), 0, &dictionary, 5, &SolveOptions::default(), &mut seeded_rng(Some(0))).ok().expect("The hand wasn't solved");
+        assert_eq!(solutions.len(), 1);
+    }
+
+
+    #[test]
+    fn shuffled_dictionaries_keep_the_longest_words_first() {
+        let dictionary = words(BENCH_DICTIONARY);
+        let shuffled = shuffle_dictionary(&dictionary, &mut seeded_rng(Some(53)));
+        assert!(shuffled.windows(2).all(|pair| pair[0].len() >= pair[1].len()));
+        assert_eq!(shuffled.iter().collect::<HashSet<_>>(), dictionary.iter().collect::<HashSet<_>>());
+        assert_ne!(shuffled, dictionary);
+        assert_eq!(shuffle_dictionary(&dictionary, &mut seeded_rng(Some(53))), shuffled);
+        assert_ne!(shuffle_dictionary(&dictionary, &mut seeded_rng(Some(54))), shuffled);
+    }
+
+    #[test]
+    fn shuffling_the_dictionary_changes_the_board_but_not_whether_one_is_found() {
+        let dictionary = words(BENCH_DICTIONARY);
+        let solve = |letters: &str, dictionary: &Vec<Word>| play_bananagrams(hand(letters), 0, dictionary, &SolveOptions::default())
+            .ok().map(|Solution { board, min_col, max_col, min_row, max_row, .. }| canonical_form(&board, min_col, max_col, min_row, max_row));
+        for bench_hand in BENCH_HANDS[..2].iter() {
+            let boards: Vec<Option<Vec<u8>>> = (0..5).map(|seed| solve(bench_hand.letters, &shuffle_dictionary(&dictionary, &mut seeded_rng(Some(seed))))).collect();
+            assert!(boards.iter().all(Option::is_some), "The {} hand wasn't solved in every order", bench_hand.name);
+            // The same seed gives the same board, and different seeds usually give different ones
+            assert_eq!(solve(bench_hand.letters, &shuffle_dictionary(&dictionary, &mut seeded_rng(Some(0)))), boards[0]);
+            assert!(boards.iter().collect::<HashSet<_>>().len() > 1, "The {} hand was solved the same way in every order", bench_hand.name);
+        }
+    }
+
+    #[test]
+    fn bounds_are_restored_when_an_undone_play_was_the_only_tile_in_its_row_or_column() {
+        let letters = [u8::MAX / 2; ENGLISH_ALPHABET_SIZE];
+        // Down through the A of CAT across, putting the only tiles in rows 4 and 6; and across through the A of CAT down, putting the only
+        // tiles in columns 4 and 6
+        for (cat, word, row, col, direction, played_bounds) in [("CAT", "BAT", 4, 6, Direction::Vertical, (5, 7, 4, 6)), ("C\nA\nT", "BAT", 6, 4, Direction::Horizontal, (4, 6, 5, 7))] {
+            let cat = board_at(cat, 16, 5, 5);
+            let bounds = cat.bounds().unwrap();
+            let mut board = cat.clone();
+            let mut letters_on_board = [0u8; ENGLISH_ALPHABET_SIZE];
+            let (played, indices, ..) = play_word(&convert_word_to_array(word), row, col, &mut board, direction, &letters, 0, &mut letters_on_board, None, Vec::new()).unwrap();
+            assert!(played);
+            assert_eq!(indices.len(), 2);
+            assert_eq!(board.bounds(), Some(played_bounds));
+            undo_play(&mut board, &indices, &mut letters_on_board, bounds);
+            assert_eq!(board.bounds(), Some(bounds));
+            assert!(board.identical(&cat));
+            assert_eq!(board_to_string(&board), board_to_string(&cat));
+            assert_eq!(letters_on_board, [0; ENGLISH_ALPHABET_SIZE]);
+        }
+    }
+
+    #[test]
+    fn bounds_shrink_when_the_only_tile_on_an_edge_is_cleared() {
+        let mut board = board_at("  B\nCAT\n  O", 16, 4, 5);
+        assert_eq!(board.bounds(), Some((5, 7, 4, 6)));
+        // Clearing a tile inside the region, or on an edge with other tiles, leaves it as it was
+        board.set_val(5, 6, EMPTY_VALUE);
+        assert_eq!(board.bounds(), Some((5, 7, 4, 6)));
+        board.set_val(5, 7, EMPTY_VALUE);
+        assert_eq!(board.bounds(), Some((5, 7, 4, 6)));
+        // The B was the only tile in the top row
+        board.set_val(4, 7, EMPTY_VALUE);
+        assert_eq!(board.bounds(), Some((5, 7, 5, 6)));
+        // The C was the only tile in the leftmost column
+        board.set_val(5, 5, EMPTY_VALUE);
+        assert_eq!(board.bounds(), Some((7, 7, 6, 6)));
+        board.set_val(6, 7, EMPTY_VALUE);
+        assert_eq!(board.bounds(), None);
+        board.set_val(10, 2, 0);
+        assert_eq!(board.bounds(), Some((2, 2, 10, 10)));
+    }
+
+    #[test]
+    fn cells_off_the_board_are_rejected_even_where_they_would_wrap_onto_another_row() {
+        let board = board_at("CAT", 4, 0, 0);
+        // (0, 4) is within the underlying vector, at the start of the second row
+        for (row, col) in [(0, 4), (4, 0), (3, 7), (usize::MAX, 0)] {
+            assert!(std::panic::catch_unwind(|| board.get_val(row, col)).is_err(), "Got (row {}, col {}) of a 4x4 board", row, col);
+            let mut cleared = board.clone();
+            assert!(std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| cleared.set_val(row, col, 0))).is_err(), "Set (row {}, col {}) of a 4x4 board", row, col);
+            assert!(cleared.identical(&board));
+        }
+        assert!(std::panic::catch_unwind(|| is_board_valid_horizontal(&board, 0, 4, 0, 0, 0, 0, 2, &HashSet::new(), 2)).is_err());
+        assert!(std::panic::catch_unwind(|| is_board_valid_vertical(&board, 0, 2, 0, 0, 0, 4, 0, &HashSet::new(), 2)).is_err());
+    }
+
+    /// Serializes a board to JSON and to bincode and back, checking that each is rebuilt exactly
+    #[cfg(feature = "serde")]
+    fn assert_serde_round_trip(board: &Board) {
+        let from_json: Board = serde_json::from_str(&serde_json::to_string(board).unwrap()).unwrap();
+        let from_bincode: Board = bincode::deserialize(&bincode::serialize(board).unwrap()).unwrap();
+        for rebuilt in [from_json, from_bincode] {
+            assert!(rebuilt.identical(board));
+            assert_eq!(rebuilt.size(), board.size());
+            assert_eq!(rebuilt.bounds(), board.bounds());
+            assert_eq!(tiles(&rebuilt), tiles(board));
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn boards_round_trip_through_json_and_bincode() {
+        // Lowercase letters are blanks, which must come back as blanks rather than as the letters they stand for
+        assert_serde_round_trip(&board_at("CAT\n  o\n  M\n  b", 16, 5, 5));
+        assert_serde_round_trip(&board_at("q", BOARD_SIZE, 0, 0));
+        assert_serde_round_trip(&board_at("  Z\n  A\nWAX", BOARD_SIZE, BOARD_SIZE - 3, BOARD_SIZE - 3));
+        assert_serde_round_trip(&Board::new(BOARD_SIZE));
+        let dictionary = words(BENCH_DICTIONARY);
+        let Solution { board, .. } = play_bananagrams(hand(BENCH_HANDS[1].letters), 1, &dictionary, &SolveOptions::default()).ok().expect("The hand wasn't solved");
+        assert!(tiles(&board).iter().any(|&(_, _, _, is_blank)| is_blank));
+        assert_serde_round_trip(&board);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn boards_are_serialized_as_their_occupied_rows() {
+        let board = board_at("CAT\n  o", 16, 5, 4);
+        assert_eq!(serde_json::to_value(&board).unwrap(), serde_json::json!({ "size": 16, "row": 5, "col": 4, "rows": ["CAT", "  o"] }));
+        // Rows that would run off the board, or that aren't letters, are rejected rather than wrapping or panicking
+        assert!(serde_json::from_str::<Board>(r#"{"size": 4, "row": 2, "col": 2, "rows": ["CAT"]}"#).is_err());
+        assert!(serde_json::from_str::<Board>(r#"{"size": 16, "row": 0, "col": 0, "rows": ["C4T"]}"#).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn played_words_round_trip_through_json_and_bincode() {
+        let played = PlayedWord { word: convert_word_to_array("TOMB"), row: 5, col: 7, direction: Direction::Vertical };
+        let json = serde_json::to_string(&played).unwrap();
+        assert_eq!(serde_json::from_str::<PlayedWord>(&json).unwrap(), played);
+        assert_eq!(bincode::deserialize::<PlayedWord>(&bincode::serialize(&played).unwrap()).unwrap(), played);
+        assert!(serde_json::from_str::<PlayedWord>(&json.replace("TOMB", "tomb")).is_err());
+        for direction in [Direction::Horizontal, Direction::Vertical] {
+            assert_eq!(bincode::deserialize::<Direction>(&bincode::serialize(&direction).unwrap()).unwrap(), direction);
+        }
+    }
+
+    /// Repairs a board with the words CAT and TOMB, checking that the repaired board is legal and has all the changes listed
+    fn repair_cat_tomb(board: &Board, max_edits: usize) -> Option<Vec<Edit>> {
+        let valid_words: HashSet<Word> = words("CAT\nTOMB").into_iter().collect();
+        let (repaired, edits) = repair_board(board, &valid_words, DEFAULT_MIN_WORD_LENGTH, max_edits)?;
+        assert_eq!(validate_board(&repaired, &valid_words, DEFAULT_MIN_WORD_LENGTH), Ok(()));
+        let mut replayed = board.clone();
+        for edit in edits.iter() {
+            edit.apply(&mut replayed);
+        }
+        assert!(replayed.identical(&repaired));
+        Some(edits)
+    }
+
+    #[test]
+    fn boards_one_edit_from_legal_are_repaired_with_that_edit() {
+        let legal = board_at("CAT\n  O\n  M\n  B", 16, 5, 5);
+        assert_eq!(repair_cat_tomb(&legal, 0), Some(Vec::new()));
+        let wrong_letter = board_at("CAT\n  O\n  X\n  B", 16, 5, 5);
+        assert_eq!(repair_cat_tomb(&wrong_letter, 1), Some(vec![Edit::ReplaceLetter { row: 7, col: 7, letter: 12 }]));
+        let stray_tile = board_at("CAT  \n  O  \n  M  \n  B Q", 16, 5, 5);
+        assert_eq!(repair_cat_tomb(&stray_tile, 1), Some(vec![Edit::DeleteTile { row: 8, col: 9 }]));
+        // CAT moved up off the top of TOMB can be moved back down, or the rest of TOMB moved up to meet it
+        let shifted = board_at("CAT\n   \n  O\n  M\n  B", 16, 4, 5);
+        let edits = repair_cat_tomb(&shifted, 1).expect("The shifted word wasn't moved back");
+        assert!(matches!(edits[..], [Edit::ShiftWord { row_shift: 1, col_shift: 0, .. }] | [Edit::ShiftWord { row_shift: -1, col_shift: 0, .. }]), "{:?}", edits);
+    }
+
+    #[test]
+    fn boards_too_many_edits_from_legal_are_left_unrepaired() {
+        let two_edits = board_at("CAT  \n  O  \n  X  \n  B Q", 16, 5, 5);
+        assert_eq!(repair_cat_tomb(&two_edits, 1), None);
+        assert_eq!(repair_cat_tomb(&two_edits, 2).map(|edits| edits.len()), Some(2));
+        // No letter makes a word of ZZZ, and it's two deletions from a lone tile
+        assert_eq!(repair_cat_tomb(&board_at("ZZZ", 16, 5, 5), 1), None);
+    }
+
+    #[test]
+    fn corrupted_solutions_are_repaired_within_a_few_edits() {
+        let dictionary = words(BENCH_DICTIONARY);
+        let valid_words: HashSet<Word> = dictionary.iter().cloned().collect();
+        let Solution { board, .. } = play_bananagrams(hand(BENCH_HANDS[0].letters), 0, &dictionary, &SolveOptions::default()).ok().expect("The hand wasn't solved");
+        let mut rng = seeded_rng(Some(68));
+        let mut repaired = 0;
+        for _ in 0..40 {
+            // Putting the replaced letter back makes every word it's in valid again, so that edit is always proposed
+            if let Some((corrupted, .., Corruption::ReplaceLetter)) = corrupt_board::<ENGLISH_ALPHABET_SIZE>(&board, &mut rng, 1) {
+                let (fixed, edits) = repair_board(&corrupted, &valid_words, DEFAULT_MIN_WORD_LENGTH, 1).unwrap_or_else(|| panic!("The replaced letter wasn't repaired:\n{}", board_to_string(&corrupted)));
+                assert_eq!(edits.len(), 1);
+                assert_eq!(validate_board(&fixed, &valid_words, DEFAULT_MIN_WORD_LENGTH), Ok(()));
+                repaired += 1;
+            }
+        }
+        assert!(repaired > 0);
+    }
+
+    /// Counts each letter on a board, including those placed with a blank
+    fn letters_on(board: &Board) -> Letters {
+        let mut counts: Letters = [0; ENGLISH_ALPHABET_SIZE];
+        for (_, _, letter, _) in tiles(board) {
+            counts[letter as usize] += 1;
+        }
+        counts
+    }
+
+    #[test]
+    fn banana_is_completed_with_the_letters_of_gram() {
+        let dictionary = words(&format!("{}\nBANANA", BENCH_DICTIONARY));
+        let valid_words: HashSet<Word> = dictionary.iter().cloned().collect();
+        let banana = board_at("BANANA", 16, 3, 2);
+        let Solution { board, min_col, max_col, min_row, max_row, plays, metrics } = complete_board(&banana, hand("GRAM"), 0, &dictionary, &SolveOptions::default()).expect("BANANA wasn't completed");
+        assert_eq!(validate_board(&board, &valid_words, DEFAULT_MIN_WORD_LENGTH), Ok(()));
+        assert_eq!(board.bounds(), Some((min_col, max_col, min_row, max_row)));
+        // Every tile of BANANA and of the hand is on the board, and nothing else
+        assert_eq!(letters_on(&board), letters_on(&board_at("BANANAGRAM", 16, 0, 0)));
+        assert!(extract_words(&board, min_col, max_col, min_row, max_row).iter().any(|(word, _, _, direction)| word == "BANANA" && *direction == Direction::Horizontal));
+        assert!(!plays.is_empty());
+        assert_eq!(metrics.depth, plays.len());
+        // The words played go onto the board where they were recorded
+        for play in plays.iter() {
+            for (i, letter) in play.word.iter().enumerate() {
+                let (row, col) = match play.direction {
+                    Direction::Horizontal => (play.row, play.col + i),
+                    Direction::Vertical => (play.row + i, play.col)
+                };
+                assert_eq!(board.get_val(row, col), *letter);
+            }
+        }
+    }
+
+    #[test]
+    fn only_legal_boards_are_completed() {
+        let dictionary = words("CAT\nTOMB\nCAB");
+        let complete = |board: &Board, letters: &str| complete_board(board, hand(letters), 0, &dictionary, &SolveOptions::default());
+        assert!(matches!(complete(&board_at("CAX", 16, 5, 5), "OMB"), Err((SolveFailure::InvalidBoard, 0))));
+        assert!(matches!(complete(&board_at("CAT\n   \nCAT", 16, 5, 5), "OMB"), Err((SolveFailure::InvalidBoard, 0))));
+        assert!(matches!(complete(&Board::new(16), "CAT"), Err((SolveFailure::InvalidBoard, 0))));
+        // With nothing left to play, the board is returned as it is
+        let Solution { board, .. } = complete(&board_at("CAT", 16, 5, 5), "").expect("A legal board wasn't returned");
+        assert_eq!(board_to_string(&board), "CAT");
+        let Solution { board, .. } = complete(&board_at("CAT", 16, 5, 5), "OMB").expect("CAT wasn't completed");
+        assert_eq!(board_to_string(&board), "CAT\n  O\n  M\n  B");
+        assert!(complete(&board_at("CAT", 16, 5, 5), "QQ").is_err());
+    }
+
+    #[test]
+    fn every_anchored_board_has_the_anchor_across_the_middle() {
+        let dictionary = words(BENCH_DICTIONARY);
+        let anchor = convert_word_to_array("PYTHON");
+        let tile_set = TileSet::standard();
+        let mut rng = seeded_rng(Some(70));
+        let mut solved = 0;
+        for _ in 0..10 {
+            // Hands drawn with the anchor's letters added, as the binary redraws hands until they can make it
+            let (mut letters, _) = generate_hand::<ENGLISH_ALPHABET_SIZE>(&mut rng, &tile_set, HandSizeDistribution::Uniform(8, 14), 0);
+            for &letter in anchor.iter() {
+                letters[letter as usize] += 1;
+            }
+            let mut solutions: Vec<Solution> = play_bananagrams(letters, 0, &dictionary, &SolveOptions { max_words_checked: 20_000, first_word: FirstWordStrategy::RarestLetters, anchor_word: Some(&anchor), ..SolveOptions::default() }).into_iter().collect();
+            solutions.extend(play_bananagrams_multi(letters, 0, &dictionary, 2, &SolveOptions { max_words_checked: 20_000, anchor_word: Some(&anchor), ..SolveOptions::default() }, &mut rng).into_iter().flatten());
+            #[cfg(feature = "parallel")]
+            solutions.extend(play_bananagrams_parallel(letters, 0, &dictionary, &SolveOptions { max_words_checked: 20_000, anchor_word: Some(&anchor), ..SolveOptions::default() }, true));
+            for Solution { board, min_col, max_col, min_row, max_row, plays, .. } in solutions.iter() {
+                solved += 1;
+                assert_eq!(plays[0], PlayedWord { word: anchor.clone(), row: BOARD_SIZE / 2, col: plays[0].col, direction: Direction::Horizontal });
+                assert!(extract_words(board, *min_col, *max_col, *min_row, *max_row).iter().any(|(word, ..)| word == "PYTHON"));
+            }
+        }
+        assert!(solved >= 5);
+    }
+
+    #[test]
+    fn anchors_the_hand_cant_make_leave_it_unsolved() {
+        let dictionary = words("CAT\nTOMB\nACT\nTOMBS");
+        let solve = |anchor: &str| play_bananagrams(hand("CATOMB"), 0, &dictionary, &SolveOptions { anchor_word: Some(&convert_word_to_array(anchor)), ..SolveOptions::default() });
+        // In the dictionary but not the hand, and in the hand but not the dictionary
+        assert!(matches!(solve("TOMBS"), Err((SolveFailure::NoWords, 0, _))));
+        assert!(matches!(solve("BOAT"), Err((SolveFailure::NoWords, 0, _))));
+        // Either of the words the hand can start with is played first when it's the anchor
+        for anchor in ["CAT", "ACT"] {
+            let Solution { plays, .. } = solve(anchor).ok().expect("The hand wasn't solved");
+            assert_eq!(convert_array_to_word(&plays[0].word), anchor);
+        }
+    }
+
+    #[test]
+    fn drawing_the_whole_pool_gives_exactly_the_tile_set() {
+        let tile_set = TileSet::standard();
+        let full: Letters = tile_set.counts();
+        let mut pool = TilePool::new(&tile_set);
+        assert_eq!(pool.counts::<ENGLISH_ALPHABET_SIZE>(), full);
+        let mut rng = seeded_rng(Some(73));
+        let mut drawn: Letters = [0; ENGLISH_ALPHABET_SIZE];
+        let add = |drawn: &mut Letters, letters: &Letters| drawn.iter_mut().zip(letters.iter()).for_each(|(total, count)| *total += count);
+        // Hands of every size the distribution allows, including those with vowels swapped in, until too few tiles are left for one
+        while let Some((letters, _)) = pool.draw_hand::<ENGLISH_ALPHABET_SIZE>(&mut rng, HandSizeDistribution::Uniform(11, 21), 4) {
+            add(&mut drawn, &letters);
+            let mut so_far = drawn;
+            add(&mut so_far, &pool.counts());
+            assert_eq!(so_far, full);
+        }
+        let rest = pool.len();
+        add(&mut drawn, &pool.draw(&mut rng, rest).unwrap());
+        assert!(pool.is_empty());
+        assert_eq!(drawn, full);
+        assert!(pool.draw::<ENGLISH_ALPHABET_SIZE>(&mut rng, 1).is_none());
+        pool.refill();
+        assert_eq!(pool.counts::<ENGLISH_ALPHABET_SIZE>(), full);
+    }
+
+    #[test]
+    fn pools_are_dealt_and_put_back_without_losing_tiles() {
+        let tile_set = TileSet::standard();
+        let mut pool = TilePool::new(&tile_set);
+        let mut rng = seeded_rng(Some(73));
+        let hands = pool.split::<ENGLISH_ALPHABET_SIZE>(&mut rng, 4, 21).unwrap();
+        assert!(hands.iter().all(|hand| hand.iter().map(|&count| count as usize).sum::<usize>() == 21));
+        assert_eq!(pool.len(), tile_set.len() - 84);
+        // Too many tiles for the players deals none of them
+        assert!(pool.split::<ENGLISH_ALPHABET_SIZE>(&mut rng, 4, 21).is_none());
+        assert_eq!(pool.len(), tile_set.len() - 84);
+        for hand in hands.iter() {
+            pool.put_back(hand);
+        }
+        assert_eq!(pool.counts::<ENGLISH_ALPHABET_SIZE>(), tile_set.counts());
+    }
+
+    #[test]
+    fn pretty_boards_are_drawn_as_grids() {
+        let board = board_at("CaT\n  O", 16, 5, 5);
+        assert_eq!(board_to_pretty_string(&board, 5, 7, 5, 6, &PrettyOptions::default()), [
+            "┌───┬───┬───┐",
+            "│ C │ a │ T │",
+            "├───┼───┼───┤",
+            "│   │   │ O │",
+            "└───┴───┴───┘"
+        ].join("\n"));
+        // Coordinates count from the part of the board shown, and a part with empty edges is drawn as it is
+        assert_eq!(board_to_pretty_string(&board, 6, 7, 4, 6, &PrettyOptions { coordinates: true, ..PrettyOptions::default() }), [
+            "    0   1",
+            "  ┌───┬───┐",
+            "0 │   │   │",
+            "  ├───┼───┤",
+            "1 │ a │ T │",
+            "  ├───┼───┤",
+            "2 │   │ O │",
+            "  └───┴───┘"
+        ].join("\n"));
+    }
+
+    #[test]
+    fn pretty_boards_are_padded_for_two_digit_coordinates() {
+        let board = board_at("C\nA\nT\nS\nC\nA\nT\nS\nC\nA\nT", 16, 0, 0);
+        let text = board_to_pretty_string(&board, 0, 0, 0, 10, &PrettyOptions { coordinates: true, ..PrettyOptions::default() });
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "     0");
+        assert_eq!(lines[1], "   ┌───┐");
+        assert_eq!(lines[2], "0  │ C │");
+        assert_eq!(lines[20], "9  │ A │");
+        assert_eq!(lines[22], "10 │ T │");
+        assert_eq!(lines[23], "   └───┘");
+    }
+
+    #[test]
+    fn pretty_boards_color_vowels_consonants_and_highlights() {
+        let board = board_at("CaT\n  O", 16, 5, 5);
+        let options = PrettyOptions { coordinates: true, color: true, highlight: &[(5, 7), (6, 7)] };
+        let colored = board_to_pretty_string(&board, 5, 7, 5, 6, &options);
+        assert!(colored.contains(&format!(" {}C{} │", ANSI_CONSONANT, ANSI_RESET)));
+        assert!(colored.contains(&format!(" {}a{} │", ANSI_VOWEL, ANSI_RESET)));
+        assert!(colored.contains(&format!(" {}T{} │", ANSI_HIGHLIGHT, ANSI_RESET)));
+        assert!(colored.contains(&format!(" {}O{} │", ANSI_HIGHLIGHT, ANSI_RESET)));
+        // Without the escape codes, it's the uncolored grid
+        let uncolored = [ANSI_VOWEL, ANSI_CONSONANT, ANSI_HIGHLIGHT, ANSI_RESET].iter().fold(colored, |text, code| text.replace(code, ""));
+        assert_eq!(uncolored, board_to_pretty_string(&board, 5, 7, 5, 6, &PrettyOptions { color: false, ..options }));
+    }
+
+
+    /// Takes the argmax over the channels of each cell of a one-hot grid (see `board_to_onehot`)
+    fn onehot_argmax(grid: &[u8], height: usize, width: usize) -> Vec<Vec<usize>> {
+        (0..height).map(|row| (0..width).map(|col| {
+            (0..ONEHOT_CHANNELS).max_by_key(|&channel| grid[(channel * height + row) * width + col]).unwrap()
+        }).collect()).collect()
+    }
+
+    #[test]
+    fn onehot_argmax_reproduces_the_centered_board() {
+        let board = board_at("CaT\n  O\n  M\n  b", 16, 5, 5);
+        let grid = board_to_onehot(&board, 5, 7, 5, 8, 7, 6).unwrap();
+        assert_eq!(grid.len(), ONEHOT_CHANNELS * 7 * 6);
+        // Each cell is 1 in exactly one channel
+        for cell in 0..7 * 6 {
+            assert_eq!((0..ONEHOT_CHANNELS).map(|channel| grid[channel * 7 * 6 + cell]).sum::<u8>(), 1);
+        }
+        // Centered, with the odd row and column of padding at the bottom and right; blanks are their letters
+        let (c, a, t, o, m, b) = (3, 1, 20, 15, 13, 2);
+        assert_eq!(onehot_argmax(&grid, 7, 6), vec![
+            vec![0, 0, 0, 0, 0, 0],
+            vec![0, c, a, t, 0, 0],
+            vec![0, 0, 0, o, 0, 0],
+            vec![0, 0, 0, m, 0, 0],
+            vec![0, 0, 0, b, 0, 0],
+            vec![0, 0, 0, 0, 0, 0],
+            vec![0, 0, 0, 0, 0, 0]
+        ]);
+    }
+
+    #[test]
+    fn onehot_argmax_reproduces_solved_boards() {
+        let dictionary = words(BENCH_DICTIONARY);
+        for board in random_solved_boards(&mut seeded_rng(Some(87)), &dictionary, 10) {
+            let (min_col, max_col, min_row, max_row) = board.bounds().unwrap();
+            // Padded to exactly the board's size, the argmax is the board's letters with 0 for empty cells
+            let (height, width) = (max_row + 1 - min_row, max_col + 1 - min_col);
+            let expected: Vec<Vec<usize>> = (min_row..max_row+1).map(|row| (min_col..max_col+1).map(|col| match board.get_val(row, col) {
+                EMPTY_VALUE => 0,
+                letter => letter as usize + 1
+            }).collect()).collect();
+            assert_eq!(onehot_argmax(&board_to_onehot(&board, min_col, max_col, min_row, max_row, height, width).unwrap(), height, width), expected);
+            assert_eq!(board_to_onehot(&board, min_col, max_col, min_row, max_row, height - 1, width), None);
+            assert_eq!(board_to_onehot(&board, min_col, max_col, min_row, max_row, height, width - 1), None);
+        }
+    }
+
+    #[test]
+    fn msgpack_values_have_their_known_encodings() {
+        use msgpack::Value;
+        let cases: Vec<(Value, Vec<u8>)> = vec![
+            (Value::Nil, vec![0xc0]),
+            (Value::Bool(false), vec![0xc2]),
+            (Value::Bool(true), vec![0xc3]),
+            (Value::UInt(5), vec![0x05]),
+            (Value::UInt(200), vec![0xcc, 0xc8]),
+            (Value::UInt(0x1234), vec![0xcd, 0x12, 0x34]),
+            (Value::UInt(0x1234_5678), vec![0xce, 0x12, 0x34, 0x56, 0x78]),
+            (Value::UInt(1 << 32), vec![0xcf, 0, 0, 0, 1, 0, 0, 0, 0]),
+            (Value::Int(-1), vec![0xff]),
+            (Value::Int(-32), vec![0xe0]),
+            (Value::Int(-33), vec![0xd0, 0xdf]),
+            (Value::Int(-200), vec![0xd1, 0xff, 0x38]),
+            (Value::Int(-40_000), vec![0xd2, 0xff, 0xff, 0x63, 0xc0]),
+            (Value::Int(i64::MIN), vec![0xd3, 0x80, 0, 0, 0, 0, 0, 0, 0]),
+            (Value::Float(1.5), vec![0xcb, 0x3f, 0xf8, 0, 0, 0, 0, 0, 0]),
+            (Value::String("CAT".to_string()), vec![0xa3, b'C', b'A', b'T']),
+            (Value::String("A".repeat(32)), [vec![0xd9, 32], vec![b'A'; 32]].concat()),
+            (Value::Binary(vec![1, 2]), vec![0xc4, 2, 1, 2]),
+            (Value::Array(vec![Value::UInt(1), Value::Nil]), vec![0x92, 0x01, 0xc0]),
+            (Value::Array(vec![Value::UInt(0); 16]), [vec![0xdc, 0, 16], vec![0; 16]].concat()),
+            (Value::Map(vec![(Value::String("a".to_string()), Value::UInt(1))]), vec![0x81, 0xa1, b'a', 0x01])
+        ];
+        for (value, bytes) in cases {
+            let mut encoded = Vec::new();
+            value.encode(&mut encoded);
+            assert_eq!(encoded, bytes, "{}", value);
+            assert_eq!(msgpack::decode(&bytes), Ok((value, bytes.len())));
+        }
+        // Non-negative integers have one representation, and 32-bit floats are read too
+        let mut encoded = Vec::new();
+        Value::Int(5).encode(&mut encoded);
+        assert_eq!(encoded, vec![0x05]);
+        assert_eq!(msgpack::decode(&[0xca, 0x3f, 0xc0, 0, 0]), Ok((Value::Float(1.5), 5)));
+        assert!(msgpack::decode(&[0xc1]).unwrap_err().contains("Unsupported"));
+    }
+
+    /// A record like those `training_data --format msgpack` writes, and its known encoding
+    fn msgpack_record() -> (msgpack::Value, Vec<u8>) {
+        use msgpack::Value;
+        let value = Value::Map(vec![
+            (Value::String("letters".to_string()), Value::String("CAT".to_string())),
+            (Value::String("tiles".to_string()), Value::Array(vec![Value::Array(vec![Value::UInt(0), Value::UInt(0), Value::UInt(2)])]))
+        ]);
+        let bytes = [
+            vec![24, 0, 0, 0, 0x82, 0xa7],
+            b"letters".to_vec(),
+            vec![0xa3],
+            b"CAT".to_vec(),
+            vec![0xa5],
+            b"tiles".to_vec(),
+            vec![0x91, 0x93, 0, 0, 2]
+        ].concat();
+        (value, bytes)
+    }
+
+    #[test]
+    fn msgpack_records_have_their_known_encoding() {
+        let (value, bytes) = msgpack_record();
+        assert_eq!(msgpack::encode_record(&value), bytes);
+        let file = [bytes.clone(), bytes.clone()].concat();
+        assert_eq!(msgpack::complete_records(&file), vec![28, 56]);
+        assert_eq!(msgpack::read_records(&file), Ok(vec![value.clone(), value]));
+        assert_eq!(msgpack::complete_records(&[]), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn msgpack_records_cut_off_anywhere_are_left_out() {
+        let (value, bytes) = msgpack_record();
+        let file = [bytes.clone(), bytes].concat();
+        for len in 0..file.len() {
+            // Whether it's the length or the value that is cut off
+            let complete = if len < 28 { 0 } else { 1 };
+            assert_eq!(msgpack::complete_records(&file[..len]), vec![28, 56][..complete], "Cut off at {}", len);
+            assert_eq!(msgpack::read_records(&file[..len]), Ok(vec![value.clone(); complete]));
+        }
+    }
+
+    #[test]
+    fn msgpack_records_whose_length_is_wrong_are_rejected() {
+        let (_, mut bytes) = msgpack_record();
+        // A length too short cuts the value off, and one too long leaves bytes after it
+        bytes[0] = 23;
+        assert!(msgpack::read_records(&bytes).unwrap_err().contains("cut off"));
+        bytes[0] = 25;
+        bytes.push(0);
+        assert!(msgpack::read_records(&bytes).unwrap_err().contains("1 bytes after its value"));
+    }
+}
@@ -0,0 +1,303 @@
+//! A minimal MessagePack encoder and decoder for the `.msgpack` board files written by `training_data --format msgpack`: a stream of
+//! records, each a MessagePack value preceded by its length as 4 little-endian bytes
+use std::fmt;
+
+/// Size of the length before each record of a `.msgpack` file
+pub const RECORD_LENGTH_SIZE: usize = 4;
+
+/// A MessagePack value (extension types aren't supported, since no board file has them)
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    /// `nil`
+    Nil,
+    /// A boolean
+    Bool(bool),
+    /// A negative integer (non-negative integers are always `UInt`)
+    Int(i64),
+    /// A non-negative integer
+    UInt(u64),
+    /// A floating-point number
+    Float(f64),
+    /// A UTF-8 string
+    String(String),
+    /// A byte array
+    Binary(Vec<u8>),
+    /// An array of values
+    Array(Vec<Value>),
+    /// A map, as its keys and values in order
+    Map(Vec<(Value, Value)>)
+}
+impl Value {
+    /// Appends the value in MessagePack's most compact encoding
+    /// # Arguments
+    /// * `out` - Bytes to append to
+    pub fn encode(&self, out: &mut Vec<u8>) {
+        match self {
+            Value::Nil => out.push(0xc0),
+            Value::Bool(value) => out.push(if *value { 0xc3 } else { 0xc2 }),
+            Value::Int(value) if *value >= 0 => Value::UInt(*value as u64).encode(out),
+            Value::Int(value) => match *value {
+                -32..=-1 => out.push(*value as u8),
+                value if value >= i8::MIN as i64 => out.extend([0xd0, value as u8]),
+                value if value >= i16::MIN as i64 => encode_with_marker(out, 0xd1, &(value as i16).to_be_bytes()),
+                value if value >= i32::MIN as i64 => encode_with_marker(out, 0xd2, &(value as i32).to_be_bytes()),
+                value => encode_with_marker(out, 0xd3, &value.to_be_bytes())
+            },
+            Value::UInt(value) => match *value {
+                0..=0x7f => out.push(*value as u8),
+                0x80..=0xff => out.extend([0xcc, *value as u8]),
+                0x100..=0xffff => encode_with_marker(out, 0xcd, &(*value as u16).to_be_bytes()),
+                0x1_0000..=0xffff_ffff => encode_with_marker(out, 0xce, &(*value as u32).to_be_bytes()),
+                value => encode_with_marker(out, 0xcf, &value.to_be_bytes())
+            },
+            Value::Float(value) => encode_with_marker(out, 0xcb, &value.to_be_bytes()),
+            Value::String(value) => {
+                match value.len() {
+                    len @ 0..=31 => out.push(0xa0 | len as u8),
+                    len => encode_length(out, [0xd9, 0xda, 0xdb], len)
+                }
+                out.extend_from_slice(value.as_bytes());
+            },
+            Value::Binary(value) => {
+                encode_length(out, [0xc4, 0xc5, 0xc6], value.len());
+                out.extend_from_slice(value);
+            },
+            Value::Array(values) => {
+                match values.len() {
+                    len @ 0..=15 => out.push(0x90 | len as u8),
+                    len => encode_length(out, [0, 0xdc, 0xdd], len)
+                }
+                for value in values.iter() {
+                    value.encode(out);
+                }
+            },
+            Value::Map(entries) => {
+                match entries.len() {
+                    len @ 0..=15 => out.push(0x80 | len as u8),
+                    len => encode_length(out, [0, 0xde, 0xdf], len)
+                }
+                for (key, value) in entries.iter() {
+                    key.encode(out);
+                    value.encode(out);
+                }
+            }
+        }
+    }
+
+    /// Gets the value of a key of a map
+    /// # Arguments
+    /// * `key` - The key, which must be a string
+    /// # Returns
+    /// * `Option<&Value>` - The value, or `None` if this isn't a map or doesn't have the key
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Map(entries) => entries.iter().find(|(entry_key, _)| matches!(entry_key, Value::String(entry_key) if entry_key == key)).map(|(_, value)| value),
+            _ => None
+        }
+    }
+}
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Nil => write!(f, "nil"),
+            Value::Bool(value) => write!(f, "{}", value),
+            Value::Int(value) => write!(f, "{}", value),
+            Value::UInt(value) => write!(f, "{}", value),
+            Value::Float(value) => write!(f, "{}", value),
+            Value::String(value) => write!(f, "{:?}", value),
+            Value::Binary(value) => write!(f, "<{} bytes>", value.len()),
+            Value::Array(values) => write!(f, "[{}]", values.iter().map(|value| value.to_string()).collect::<Vec<_>>().join(", ")),
+            Value::Map(entries) => write!(f, "{{{}}}", entries.iter().map(|(key, value)| format!("{}: {}", key, value)).collect::<Vec<_>>().join(", "))
+        }
+    }
+}
+
+/// Appends a marker byte followed by big-endian bytes
+fn encode_with_marker(out: &mut Vec<u8>, marker: u8, bytes: &[u8]) {
+    out.push(marker);
+    out.extend_from_slice(bytes);
+}
+
+/// Appends the marker and length of a string, byte array, array, or map too long for its compact form
+/// # Arguments
+/// * `out` - Bytes to append to
+/// * `markers` - Marker of the form with a 1-byte, 2-byte, and 4-byte length (arrays and maps have no 1-byte form, so theirs is unused)
+/// * `len` - The length
+fn encode_length(out: &mut Vec<u8>, markers: [u8; 3], len: usize) {
+    match len {
+        0..=0xff if markers[0] != 0 => out.extend([markers[0], len as u8]),
+        0..=0xffff => encode_with_marker(out, markers[1], &(len as u16).to_be_bytes()),
+        _ => encode_with_marker(out, markers[2], &(len as u32).to_be_bytes())
+    }
+}
+
+/// Reads values from MessagePack bytes in order
+struct Decoder<'a> {
+    /// The bytes
+    bytes: &'a [u8],
+    /// Where the next value starts
+    offset: usize
+}
+impl<'a> Decoder<'a> {
+    /// Reads the next `len` bytes
+    /// # Arguments
+    /// * `len` - Number of bytes
+    /// # Returns
+    /// * `Result` with the bytes, or an error message if there aren't that many left
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let slice = self.bytes.get(self.offset..self.offset + len).ok_or_else(|| "Value is cut off".to_string())?;
+        self.offset += len;
+        Ok(slice)
+    }
+
+    /// Reads the next `N` bytes as an array (for converting from big-endian)
+    fn take_array<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        self.take(N).map(|slice| slice.try_into().expect("Wrong number of bytes!"))
+    }
+
+    /// Reads the length of a string, byte array, array, or map that follows its marker
+    /// # Arguments
+    /// * `size` - Number of bytes the length takes up (1, 2, or 4)
+    fn length(&mut self, size: usize) -> Result<usize, String> {
+        Ok(match size {
+            1 => self.take_array::<1>()?[0] as usize,
+            2 => u16::from_be_bytes(self.take_array()?) as usize,
+            _ => u32::from_be_bytes(self.take_array()?) as usize
+        })
+    }
+
+    /// Reads a string of `len` bytes
+    fn string(&mut self, len: usize) -> Result<Value, String> {
+        let value = std::str::from_utf8(self.take(len)?).map_err(|e| format!("Invalid string: {}", e))?;
+        Ok(Value::String(value.to_string()))
+    }
+
+    /// Reads an array of `len` values
+    fn array(&mut self, len: usize) -> Result<Value, String> {
+        // Capped so that a corrupted length can't allocate more than the bytes could hold
+        let mut values = Vec::with_capacity(len.min(self.bytes.len() - self.offset));
+        for _ in 0..len {
+            values.push(self.value()?);
+        }
+        Ok(Value::Array(values))
+    }
+
+    /// Reads a map of `len` keys and values
+    fn map(&mut self, len: usize) -> Result<Value, String> {
+        let mut entries = Vec::with_capacity(len.min(self.bytes.len() - self.offset));
+        for _ in 0..len {
+            entries.push((self.value()?, self.value()?));
+        }
+        Ok(Value::Map(entries))
+    }
+
+    /// Reads the next value
+    /// # Returns
+    /// * `Result` with the value, or an error message if it's invalid, cut off, or an extension type
+    fn value(&mut self) -> Result<Value, String> {
+        let marker = self.take_array::<1>()?[0];
+        match marker {
+            0x00..=0x7f => Ok(Value::UInt(marker as u64)),
+            0x80..=0x8f => self.map((marker & 0x0f) as usize),
+            0x90..=0x9f => self.array((marker & 0x0f) as usize),
+            0xa0..=0xbf => self.string((marker & 0x1f) as usize),
+            0xc0 => Ok(Value::Nil),
+            0xc2 => Ok(Value::Bool(false)),
+            0xc3 => Ok(Value::Bool(true)),
+            0xc4..=0xc6 => {
+                let len = self.length(1 << (marker - 0xc4))?;
+                Ok(Value::Binary(self.take(len)?.to_vec()))
+            },
+            0xca => Ok(Value::Float(f32::from_be_bytes(self.take_array()?) as f64)),
+            0xcb => Ok(Value::Float(f64::from_be_bytes(self.take_array()?))),
+            0xcc => Ok(Value::UInt(self.take_array::<1>()?[0] as u64)),
+            0xcd => Ok(Value::UInt(u16::from_be_bytes(self.take_array()?) as u64)),
+            0xce => Ok(Value::UInt(u32::from_be_bytes(self.take_array()?) as u64)),
+            0xcf => Ok(Value::UInt(u64::from_be_bytes(self.take_array()?))),
+            0xd0 => Ok(int_value(self.take_array::<1>()?[0] as i8 as i64)),
+            0xd1 => Ok(int_value(i16::from_be_bytes(self.take_array()?) as i64)),
+            0xd2 => Ok(int_value(i32::from_be_bytes(self.take_array()?) as i64)),
+            0xd3 => Ok(int_value(i64::from_be_bytes(self.take_array()?))),
+            0xd9..=0xdb => {
+                let len = self.length(1 << (marker - 0xd9))?;
+                self.string(len)
+            },
+            0xdc | 0xdd => {
+                let len = self.length(if marker == 0xdc { 2 } else { 4 })?;
+                self.array(len)
+            },
+            0xde | 0xdf => {
+                let len = self.length(if marker == 0xde { 2 } else { 4 })?;
+                self.map(len)
+            },
+            0xe0..=0xff => Ok(Value::Int(marker as i8 as i64)),
+            _ => Err(format!("Unsupported MessagePack type 0x{:02x}", marker))
+        }
+    }
+}
+
+/// Decodes a MessagePack value
+/// # Arguments
+/// * `bytes` - The encoded value, which may be followed by other bytes
+/// # Returns
+/// * `Result` with the value and the number of bytes it took up, or an error message if it's invalid, cut off, or an extension type
+pub fn decode(bytes: &[u8]) -> Result<(Value, usize), String> {
+    let mut decoder = Decoder { bytes, offset: 0 };
+    let value = decoder.value()?;
+    Ok((value, decoder.offset))
+}
+
+/// Converts a signed integer to a `Value`, keeping non-negative integers as `UInt` so that every integer has one representation
+fn int_value(value: i64) -> Value {
+    if value >= 0 { Value::UInt(value as u64) } else { Value::Int(value) }
+}
+
+/// Encodes a value as a record of a `.msgpack` file
+/// # Arguments
+/// * `value` - The value
+/// # Returns
+/// * `Vec<u8>` - The value's length as `RECORD_LENGTH_SIZE` little-endian bytes, followed by the encoded value
+pub fn encode_record(value: &Value) -> Vec<u8> {
+    let mut record = vec![0; RECORD_LENGTH_SIZE];
+    value.encode(&mut record);
+    let len = (record.len() - RECORD_LENGTH_SIZE) as u32;
+    record[..RECORD_LENGTH_SIZE].copy_from_slice(&len.to_le_bytes());
+    record
+}
+
+/// Finds where each complete record in the contents of a `.msgpack` file ends, stopping at the first that is cut off (such as one left
+/// half-written by an interrupted run)
+/// # Arguments
+/// * `bytes` - Contents of the file
+/// # Returns
+/// * `Vec<usize>` - The offset just past each complete record
+pub fn complete_records(bytes: &[u8]) -> Vec<usize> {
+    let mut ends = Vec::new();
+    let mut offset = 0;
+    while let Some(len) = bytes.get(offset..offset + RECORD_LENGTH_SIZE) {
+        let end = offset + RECORD_LENGTH_SIZE + u32::from_le_bytes(len.try_into().expect("Wrong number of bytes!")) as usize;
+        if end > bytes.len() {
+            break;
+        }
+        ends.push(end);
+        offset = end;
+    }
+    ends
+}
+
+/// Decodes every complete record in the contents of a `.msgpack` file
+/// # Arguments
+/// * `bytes` - Contents of the file (see `complete_records`)
+/// # Returns
+/// * `Result` with each record's value, or an error message if a record isn't a single valid value
+pub fn read_records(bytes: &[u8]) -> Result<Vec<Value>, String> {
+    let mut start = 0;
+    complete_records(bytes).into_iter().enumerate().map(|(i, end)| {
+        let (value, size) = decode(&bytes[start + RECORD_LENGTH_SIZE..end]).map_err(|e| format!("Invalid record {}: {}", i, e))?;
+        if start + RECORD_LENGTH_SIZE + size != end {
+            return Err(format!("Invalid record {}: {} bytes after its value", i, end - start - RECORD_LENGTH_SIZE - size));
+        }
+        start = end;
+        Ok(value)
+    }).collect()
+}
@@ -0,0 +1,277 @@
+//! Rendering boards as images: SVG always, and PNG with the `png` feature (drawn with a built-in pixel font, so that no font or
+//! rasterizer is needed)
+use hashbrown::HashSet;
+use crate::{Board, BoardError, Direction, Word, EMPTY_VALUE, letter_to_char, validate_board};
+
+/// Default width and height of each cell of a rendered board, in pixels
+pub const DEFAULT_CELL_SIZE: usize = 40;
+/// Smallest width and height of each cell that a letter fits in
+pub const MIN_CELL_SIZE: usize = 8;
+
+/// Options for rendering a board
+#[derive(Clone, Debug)]
+pub struct RenderOptions {
+    /// Width and height of each cell, in pixels (at least `MIN_CELL_SIZE`)
+    pub cell_size: usize,
+    /// Color of the background
+    pub background: [u8; 3],
+    /// Color of each tile
+    pub tile_color: [u8; 3],
+    /// Color of the outline of each tile
+    pub outline_color: [u8; 3],
+    /// Color of each letter
+    pub letter_color: [u8; 3],
+    /// Color of each letter played with a blank
+    pub blank_color: [u8; 3],
+    /// Color of each highlighted tile
+    pub highlight_color: [u8; 3],
+    /// The (row, column) of each tile to highlight, such as those of invalid words (see `invalid_cells`)
+    pub highlight: HashSet<(usize, usize)>
+}
+impl Default for RenderOptions {
+    fn default() -> RenderOptions {
+        RenderOptions {
+            cell_size: DEFAULT_CELL_SIZE,
+            background: [255, 255, 255],
+            tile_color: [250, 226, 150],
+            outline_color: [140, 110, 40],
+            letter_color: [30, 30, 30],
+            blank_color: [150, 150, 150],
+            highlight_color: [240, 128, 128],
+            highlight: HashSet::new()
+        }
+    }
+}
+
+/// Parses a color written as hexadecimal (like `#f08080` or `f08080`)
+/// # Arguments
+/// * `text` - The color
+/// # Returns
+/// * `Result` with the red, green, and blue components, or an error message if `text` isn't a color
+pub fn parse_color(text: &str) -> Result<[u8; 3], String> {
+    let hex = text.strip_prefix('#').unwrap_or(text);
+    if hex.len() != 6 || !hex.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return Err(format!("Invalid color {:?} (must be like #f08080)", text));
+    }
+    let component = |i: usize| u8::from_str_radix(&hex[2*i..2*i+2], 16).expect("Checked to be hexadecimal!");
+    Ok([component(0), component(1), component(2)])
+}
+
+/// Finds the tiles of every invalid word on a `board`, for highlighting
+/// # Arguments
+/// * `board` - `Board` to check
+/// * `valid_words` - HashSet of all valid words
+/// * `min_word_length` - Minimum length of a word, below which runs of letters are invalid
+/// # Returns
+/// * `HashSet<(usize, usize)>` - The (row, column) of each tile that is part of an invalid word (see `validate_board`)
+pub fn invalid_cells(board: &Board, valid_words: &HashSet<Word>, min_word_length: usize) -> HashSet<(usize, usize)> {
+    let mut cells = HashSet::new();
+    if let Err(errors) = validate_board(board, valid_words, min_word_length) {
+        for error in errors {
+            if let BoardError::InvalidWord(word) = error {
+                let (row_step, col_step) = match word.direction {
+                    Direction::Horizontal => (0, 1),
+                    Direction::Vertical => (1, 0)
+                };
+                cells.extend((0..word.word.len()).map(|i| (word.row + i*row_step, word.col + i*col_step)));
+            }
+        }
+    }
+    cells
+}
+
+/// A tile to draw, with where it is on the image
+struct Tile {
+    /// Column of the tile on the image, counting the margin
+    x: usize,
+    /// Row of the tile on the image, counting the margin
+    y: usize,
+    /// The letter (0 for A)
+    letter: u8,
+    /// Whether the tile was played with a blank
+    blank: bool,
+    /// Whether the tile is highlighted
+    highlighted: bool
+}
+
+/// Finds the tiles to draw on a board, cropped to its letters with a margin of one cell on each side
+/// # Arguments
+/// * `board` - The board
+/// * `options` - Options for rendering it
+/// # Returns
+/// * `(usize, usize, Vec<Tile>)` - The number of columns and rows of the image (in cells) and each tile
+fn layout(board: &Board, options: &RenderOptions) -> (usize, usize, Vec<Tile>) {
+    let (min_col, max_col, min_row, max_row) = match board.bounds() {
+        Some(bounds) => bounds,
+        None => return (2, 2, Vec::new())
+    };
+    let mut tiles = Vec::new();
+    for row in min_row..max_row+1 {
+        for col in min_col..max_col+1 {
+            let letter = board.get_val(row, col);
+            if letter != EMPTY_VALUE {
+                tiles.push(Tile { x: col - min_col + 1, y: row - min_row + 1, letter, blank: board.is_blank(row, col), highlighted: options.highlight.contains(&(row, col)) });
+            }
+        }
+    }
+    (max_col - min_col + 3, max_row - min_row + 3, tiles)
+}
+
+/// Formats a color for SVG
+/// # Arguments
+/// * `color` - The red, green, and blue components
+/// # Returns
+/// * `String` - The color as `#rrggbb`
+fn hex(color: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", color[0], color[1], color[2])
+}
+
+/// Renders a board as SVG: each tile a rounded square with its letter, cropped to the board's letters with a margin of one cell
+/// # Arguments
+/// * `board` - The board to render
+/// * `options` - Options for rendering it
+/// # Returns
+/// * `String` - The SVG document
+pub fn board_to_svg(board: &Board, options: &RenderOptions) -> String {
+    let (columns, rows, tiles) = layout(board, options);
+    let cell = options.cell_size;
+    let (inset, radius, font_size) = (cell as f64 * 0.05, cell as f64 * 0.15, cell as f64 * 0.6);
+    let mut svg = format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">\n", columns*cell, rows*cell, columns*cell, rows*cell);
+    svg.push_str(&format!("<rect width=\"100%\" height=\"100%\" fill=\"{}\"/>\n", hex(options.background)));
+    for tile in tiles.iter() {
+        let (x, y) = ((tile.x * cell) as f64, (tile.y * cell) as f64);
+        let fill = if tile.highlighted { options.highlight_color } else { options.tile_color };
+        svg.push_str(&format!("<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" rx=\"{:.1}\" fill=\"{}\" stroke=\"{}\" stroke-width=\"{:.1}\"/>\n",
+            x + inset, y + inset, cell as f64 - 2.0*inset, cell as f64 - 2.0*inset, radius, hex(fill), hex(options.outline_color), inset));
+        let color = if tile.blank { options.blank_color } else { options.letter_color };
+        svg.push_str(&format!("<text x=\"{:.1}\" y=\"{:.1}\" font-family=\"sans-serif\" font-size=\"{:.1}\" font-weight=\"bold\" text-anchor=\"middle\" dominant-baseline=\"central\" fill=\"{}\">{}</text>\n",
+            x + cell as f64 / 2.0, y + cell as f64 / 2.0, font_size, hex(color), letter_to_char(tile.letter)));
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// The letters A-Z drawn 5 pixels wide and 7 tall, each row a byte whose five lowest bits are the pixels from left to right
+#[cfg(feature = "png")]
+const FONT: [[u8; 7]; 26] = [
+    [0b01110, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001],
+    [0b11110, 0b10001, 0b10001, 0b11110, 0b10001, 0b10001, 0b11110],
+    [0b01110, 0b10001, 0b10000, 0b10000, 0b10000, 0b10001, 0b01110],
+    [0b11110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b11110],
+    [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b11111],
+    [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b10000],
+    [0b01110, 0b10001, 0b10000, 0b10111, 0b10001, 0b10001, 0b01111],
+    [0b10001, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001],
+    [0b01110, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110],
+    [0b00111, 0b00010, 0b00010, 0b00010, 0b00010, 0b10010, 0b01100],
+    [0b10001, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010, 0b10001],
+    [0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b11111],
+    [0b10001, 0b11011, 0b10101, 0b10101, 0b10001, 0b10001, 0b10001],
+    [0b10001, 0b10001, 0b11001, 0b10101, 0b10011, 0b10001, 0b10001],
+    [0b01110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110],
+    [0b11110, 0b10001, 0b10001, 0b11110, 0b10000, 0b10000, 0b10000],
+    [0b01110, 0b10001, 0b10001, 0b10001, 0b10101, 0b10010, 0b01101],
+    [0b11110, 0b10001, 0b10001, 0b11110, 0b10100, 0b10010, 0b10001],
+    [0b01111, 0b10000, 0b10000, 0b01110, 0b00001, 0b00001, 0b11110],
+    [0b11111, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100],
+    [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110],
+    [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01010, 0b00100],
+    [0b10001, 0b10001, 0b10001, 0b10101, 0b10101, 0b10101, 0b01010],
+    [0b10001, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001, 0b10001],
+    [0b10001, 0b10001, 0b01010, 0b00100, 0b00100, 0b00100, 0b00100],
+    [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b11111]
+];
+
+/// Checks whether a pixel is inside a square with rounded corners
+/// # Arguments
+/// * `x` - Column of the pixel, relative to the square's top-left corner
+/// * `y` - Row of the pixel, relative to the square's top-left corner
+/// * `size` - Width and height of the square
+/// * `radius` - Radius of the corners
+/// # Returns
+/// * `bool` - Whether the center of the pixel is inside the square
+#[cfg(feature = "png")]
+fn in_rounded_square(x: f64, y: f64, size: f64, radius: f64) -> bool {
+    if x < 0.0 || y < 0.0 || x >= size || y >= size {
+        return false;
+    }
+    // Distance into the corner region, which is only inside if within the radius of the corner's center
+    let (dx, dy) = ((radius - x).max(x - (size - radius)).max(0.0), (radius - y).max(y - (size - radius)).max(0.0));
+    dx*dx + dy*dy <= radius*radius
+}
+
+/// Writes a PNG chunk
+/// # Arguments
+/// * `png` - The PNG so far, which the chunk is added to
+/// * `kind` - The chunk's four-letter type
+/// * `data` - The chunk's contents
+#[cfg(feature = "png")]
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend((data.len() as u32).to_be_bytes());
+    let mut crc = flate2::Crc::new();
+    crc.update(kind);
+    crc.update(data);
+    png.extend(kind);
+    png.extend(data);
+    png.extend(crc.sum().to_be_bytes());
+}
+
+/// Renders a board as PNG, laid out the same as by `board_to_svg` (with its letters drawn in a built-in pixel font)
+/// # Arguments
+/// * `board` - The board to render
+/// * `options` - Options for rendering it
+/// # Returns
+/// * `Vec<u8>` - The PNG file
+#[cfg(feature = "png")]
+pub fn board_to_png(board: &Board, options: &RenderOptions) -> Vec<u8> {
+    use std::io::Write;
+    let (columns, rows, tiles) = layout(board, options);
+    let cell = options.cell_size;
+    let (width, height) = (columns*cell, rows*cell);
+    let mut pixels: Vec<[u8; 3]> = vec![options.background; width*height];
+    let (inset, radius, stroke) = (cell as f64 * 0.05, cell as f64 * 0.15, (cell as f64 * 0.05).max(1.0));
+    let size = cell as f64 - 2.0*inset;
+    // Each pixel of the font is drawn as a square of this many pixels, leaving the letter about 60% of the cell tall
+    let scale = (cell * 6 / 70).max(1);
+    for tile in tiles.iter() {
+        let (left, top) = (tile.x * cell, tile.y * cell);
+        let fill = if tile.highlighted { options.highlight_color } else { options.tile_color };
+        for y in 0..cell {
+            for x in 0..cell {
+                let (px, py) = (x as f64 + 0.5 - inset, y as f64 + 0.5 - inset);
+                if in_rounded_square(px, py, size, radius) {
+                    let inner = in_rounded_square(px - stroke, py - stroke, size - 2.0*stroke, (radius - stroke).max(0.0));
+                    pixels[(top + y)*width + left + x] = if inner { fill } else { options.outline_color };
+                }
+            }
+        }
+        let color = if tile.blank { options.blank_color } else { options.letter_color };
+        let (glyph_left, glyph_top) = (left + (cell - 5*scale) / 2, top + (cell - 7*scale) / 2);
+        // Letters outside A-Z have no glyph, and are left blank
+        for (glyph_row, bits) in FONT.get(tile.letter as usize).into_iter().flatten().enumerate() {
+            for glyph_col in (0..5).filter(|glyph_col| bits & (0b10000 >> glyph_col) != 0) {
+                for y in 0..scale {
+                    for x in 0..scale {
+                        pixels[(glyph_top + glyph_row*scale + y)*width + glyph_left + glyph_col*scale + x] = color;
+                    }
+                }
+            }
+        }
+    }
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    let mut header = Vec::with_capacity(13);
+    header.extend((width as u32).to_be_bytes());
+    header.extend((height as u32).to_be_bytes());
+    // 8 bits per channel, RGB, deflate, no filtering, and no interlacing
+    header.extend([8, 2, 0, 0, 0]);
+    write_chunk(&mut png, b"IHDR", &header);
+    let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+    for row in pixels.chunks(width) {
+        // Each row starts with its filter type, which is none
+        encoder.write_all(&[0]).expect("Failed to compress image!");
+        encoder.write_all(row.as_flattened()).expect("Failed to compress image!");
+    }
+    write_chunk(&mut png, b"IDAT", &encoder.finish().expect("Failed to compress image!"));
+    write_chunk(&mut png, b"IEND", &[]);
+    png
+}
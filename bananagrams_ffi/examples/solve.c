@@ -0,0 +1,70 @@
+/*
+ * Solves a hand and checks a few boards through the C interface, exiting with a nonzero status if anything is unexpected.
+ *
+ * From the workspace root:
+ *   cargo build -p bananagrams_ffi
+ *   cc bananagrams_ffi/examples/solve.c -Ibananagrams_ffi/include -Ltarget/debug -lbananagrams -o target/debug/bgm_solve
+ *   LD_LIBRARY_PATH=target/debug target/debug/bgm_solve new_short_dictionary.txt
+ */
+#include <stdio.h>
+#include <string.h>
+#include "bananagrams.h"
+
+#define CHECK(condition) do { if (!(condition)) { fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__, #condition); return 1; } } while (0)
+
+/* Fills `counts` with the letters of `hand` */
+static void hand_counts(const char *hand, uint8_t counts[26]) {
+    memset(counts, 0, 26);
+    for (; *hand; hand++) {
+        counts[*hand - 'A']++;
+    }
+}
+
+int main(int argc, char **argv) {
+    CHECK(argc == 2);
+    CHECK(bgm_load_dictionary("no/such/dictionary.txt") == NULL);
+    BgmDictionary *dictionary = bgm_load_dictionary(argv[1]);
+    CHECK(dictionary != NULL);
+
+    uint8_t counts[26];
+    char board[4096];
+    hand_counts("BANANAGRAMSPUZZLE", counts);
+    int32_t length = bgm_solve(dictionary, counts, board, sizeof board);
+    CHECK(length > 0 && (size_t)length == strlen(board));
+    printf("%s\n\n", board);
+    CHECK(bgm_solve(dictionary, counts, board, 4) == BGM_BUFFER_TOO_SMALL);
+    hand_counts("QQQQ", counts);
+    CHECK(bgm_solve(dictionary, counts, board, sizeof board) == BGM_NO_SOLUTION);
+    CHECK(bgm_solve(NULL, counts, board, sizeof board) == BGM_INVALID_ARGUMENT);
+
+    /* CAT across, with A starting ANT down */
+    const uint8_t valid[] = {
+        3, 1, 20,
+        0, 14, 0,
+        0, 20, 0
+    };
+    BgmError errors[4];
+    CHECK(bgm_validate(dictionary, valid, 3, 3, errors, 4) == 0);
+
+    /* QZ across, and a separate X */
+    const uint8_t invalid[] = {
+        17, 26, 0,
+        0, 0, 0,
+        0, 0, 24
+    };
+    int32_t found = bgm_validate(dictionary, invalid, 3, 3, errors, 4);
+    CHECK(found == 2);
+    CHECK(errors[0].kind == BGM_ERROR_KIND_DISCONNECTED && errors[0].components == 2);
+    CHECK(errors[1].kind == BGM_ERROR_KIND_INVALID_WORD && errors[1].row == 0 && errors[1].col == 0 && errors[1].length == 2 && errors[1].horizontal);
+    printf("Invalid board: %d problems\n", found);
+    CHECK(bgm_validate(dictionary, invalid, 3, 3, NULL, 0) == 2);
+
+    const uint8_t empty[] = {0, 0, 0, 0};
+    CHECK(bgm_validate(dictionary, empty, 2, 2, errors, 4) == 1 && errors[0].kind == BGM_ERROR_KIND_EMPTY);
+    const uint8_t out_of_range[] = {27};
+    CHECK(bgm_validate(dictionary, out_of_range, 1, 1, errors, 4) == BGM_INVALID_ARGUMENT);
+
+    bgm_free_dictionary(dictionary);
+    bgm_free_dictionary(NULL);
+    return 0;
+}
@@ -0,0 +1,141 @@
+/*
+ * C interface to the Bananagrams solver and board validator (link with -lbananagrams).
+ *
+ * Memory ownership:
+ * - A BgmDictionary is allocated by bgm_load_dictionary and owned by the caller until it is passed to bgm_free_dictionary; it is
+ *   never modified after loading, so it can be used from several threads at once.
+ * - Every other pointer passed in (paths, hands, grids, output buffers, error arrays) stays owned by the caller, and is only read or
+ *   written during the call; the library never keeps or frees it.
+ * - Nothing returned other than a BgmDictionary is allocated by the library, so there is nothing else to free.
+ */
+
+#ifndef BANANAGRAMS_H
+#define BANANAGRAMS_H
+
+/* Generated by cbindgen from bananagrams_ffi/src/lib.rs; edit the doc comments there rather than this file */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Returned by `bgm_solve` when the hand can't be played as a single board
+#define BGM_NO_SOLUTION -1
+
+// Returned by `bgm_solve` when the board (and its terminating NUL) doesn't fit in the buffer
+#define BGM_BUFFER_TOO_SMALL -2
+
+// Returned when a required pointer is NULL or an argument is out of range
+#define BGM_INVALID_ARGUMENT -3
+
+// Returned if the call failed unexpectedly (a bug), rather than letting the panic unwind into C
+#define BGM_INTERNAL_ERROR -4
+
+// Largest number of rows or columns `bgm_validate` takes (the same as for boards saved as `.bgb` files)
+#define BGM_MAX_BOARD_SIZE 254
+
+// The kind of a `BgmError`
+enum BgmErrorKind
+#ifdef __cplusplus
+  : uint32_t
+#endif // __cplusplus
+ {
+  // There are no letters on the board
+  BGM_ERROR_KIND_EMPTY = 0,
+  // The letters on the board form more than one connected group (see `components`)
+  BGM_ERROR_KIND_DISCONNECTED = 1,
+  // A horizontal or vertical run of two or more letters is not a valid word (see `row`, `col`, `length`, and `horizontal`)
+  BGM_ERROR_KIND_INVALID_WORD = 2,
+};
+#ifndef __cplusplus
+typedef uint32_t BgmErrorKind;
+#endif // __cplusplus
+
+// A dictionary loaded by `bgm_load_dictionary`, which C only ever holds a pointer to
+typedef struct BgmDictionary BgmDictionary;
+
+// A reason a board is not a legal Bananagrams board, written by `bgm_validate`; fields that don't apply to its `kind` are 0
+typedef struct BgmError {
+  // What is wrong with the board
+  BgmErrorKind kind;
+  // Number of separate groups of letters (`BGM_ERROR_KIND_DISCONNECTED` only)
+  uint32_t components;
+  // Row of the invalid word's first letter
+  uint32_t row;
+  // Column of the invalid word's first letter
+  uint32_t col;
+  // Number of letters in the invalid word
+  uint32_t length;
+  // Whether the invalid word runs left to right (rather than top to bottom)
+  bool horizontal;
+} BgmError;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Loads a dictionary for `bgm_solve` and `bgm_validate`, leaving out words shorter than 2 letters
+//
+// The caller owns the returned handle and must release it with `bgm_free_dictionary` (and not use it afterwards); `path` is only read
+// during the call. A handle can be shared between threads, since no call modifies it.
+// # Arguments
+// * `path` - NUL-terminated UTF-8 path to a dictionary file, with one word per line (each optionally followed by whitespace and its
+//   frequency)
+// # Returns
+// * `BgmDictionary*` - The dictionary, or NULL if `path` is NULL or not UTF-8, the file couldn't be read or has an invalid word, or it
+//   has no words of at least 2 letters
+// # Safety
+// * `path` must be NULL or point to a NUL-terminated string
+BgmDictionary *bgm_load_dictionary(const char *path);
+
+// Releases a dictionary returned by `bgm_load_dictionary`
+// # Arguments
+// * `dictionary` - The dictionary, or NULL (which does nothing)
+// # Safety
+// * `dictionary` must be NULL or a handle returned by `bgm_load_dictionary` that hasn't already been released, and no other call may be
+//   using it
+void bgm_free_dictionary(BgmDictionary *dictionary);
+
+// Solves a hand, writing the board as text to a buffer owned by the caller
+// # Arguments
+// * `dictionary` - Dictionary from `bgm_load_dictionary`
+// * `counts` - Number of tiles of each letter in the hand, from 'A' to 'Z'
+// * `out_buf` - Buffer to write the board to: the occupied part of the board, a line per row (separated by '\n', with spaces for empty
+//   cells and no trailing newline), then a terminating NUL
+// * `out_len` - Size of `out_buf` in bytes
+// # Returns
+// * `int32_t` - Length of the board's text (not counting the NUL), `BGM_NO_SOLUTION` if the hand can't be played, `BGM_BUFFER_TOO_SMALL`
+//   if `out_buf` is too small (in which case nothing is written), or `BGM_INVALID_ARGUMENT` if a pointer is NULL
+// # Safety
+// * `dictionary` must be a live handle from `bgm_load_dictionary`, `counts` must point to 26 bytes, and `out_buf` must point to
+//   `out_len` writable bytes
+int32_t bgm_solve(const BgmDictionary *dictionary, const uint8_t *counts, char *out_buf, size_t out_len);
+
+// Checks whether a board is a legal Bananagrams board, i.e. all of its letters are connected and every run of two or more letters is a
+// word in the dictionary
+// # Arguments
+// * `dictionary` - Dictionary from `bgm_load_dictionary`
+// * `grid` - The board, row by row, where 0 is empty and 1 ('A') through 26 ('Z') are letters
+// * `w` - Number of columns in `grid`
+// * `h` - Number of rows in `grid`
+// * `errors` - Array owned by the caller to write the problems with the board to, in the order of `validate_board` (may be NULL if
+//   `max_errors` is 0)
+// * `max_errors` - Length of `errors`; any problems past it are counted but not written
+// # Returns
+// * `int32_t` - Number of problems with the board (0 if it is legal, which may be more than `max_errors`), or `BGM_INVALID_ARGUMENT` if
+//   a pointer is NULL, `w` or `h` is more than `BGM_MAX_BOARD_SIZE`, or a value in `grid` is more than 26
+// # Safety
+// * `dictionary` must be a live handle from `bgm_load_dictionary`, `grid` must point to `w * h` bytes, and `errors` must point to
+//   `max_errors` writable `BgmError`s
+int32_t bgm_validate(const BgmDictionary *dictionary,
+                     const uint8_t *grid,
+                     uint32_t w,
+                     uint32_t h,
+                     BgmError *errors,
+                     size_t max_errors);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* BANANAGRAMS_H */
@@ -0,0 +1,322 @@
+//! C bindings for the solver and board validation of `bananagrams_core`, declared in `include/bananagrams.h` (generated by cbindgen with
+//! `cbindgen.toml`, so the doc comments here are what C callers read)
+use std::{ffi::{c_char, CStr}, panic::{self, AssertUnwindSafe}, path::Path, ptr, slice};
+use hashbrown::HashSet;
+use bananagrams_core::{Board, BoardError, Direction, LetterMask, Letters, SolveOptions, Word, DEFAULT_MIN_WORD_LENGTH, ENGLISH_ALPHABET_SIZE, board_to_string, letter_masks, load_dictionary, play_bananagrams, remove_short_words, validate_board};
+
+/// Returned by `bgm_solve` when the hand can't be played as a single board
+pub const BGM_NO_SOLUTION: i32 = -1;
+/// Returned by `bgm_solve` when the board (and its terminating NUL) doesn't fit in the buffer
+pub const BGM_BUFFER_TOO_SMALL: i32 = -2;
+/// Returned when a required pointer is NULL or an argument is out of range
+pub const BGM_INVALID_ARGUMENT: i32 = -3;
+/// Returned if the call failed unexpectedly (a bug), rather than letting the panic unwind into C
+pub const BGM_INTERNAL_ERROR: i32 = -4;
+/// Largest number of rows or columns `bgm_validate` takes (the same as for boards saved as `.bgb` files)
+pub const BGM_MAX_BOARD_SIZE: u32 = 254;
+
+/// A dictionary loaded by `bgm_load_dictionary`, which C only ever holds a pointer to
+pub struct BgmDictionary {
+    /// Vector of valid words, sorted longest first
+    dictionary: Vec<Word>,
+    /// The letters in each word of `dictionary`, for filtering it by each hand solved
+    masks: Vec<LetterMask>,
+    /// HashSet version of `dictionary` for faster membership checking
+    valid_words: HashSet<Word>
+}
+
+/// The kind of a `BgmError`
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BgmErrorKind {
+    /// There are no letters on the board
+    Empty = 0,
+    /// The letters on the board form more than one connected group (see `components`)
+    Disconnected = 1,
+    /// A horizontal or vertical run of two or more letters is not a valid word (see `row`, `col`, `length`, and `horizontal`)
+    InvalidWord = 2
+}
+
+/// A reason a board is not a legal Bananagrams board, written by `bgm_validate`; fields that don't apply to its `kind` are 0
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BgmError {
+    /// What is wrong with the board
+    pub kind: BgmErrorKind,
+    /// Number of separate groups of letters (`BGM_ERROR_KIND_DISCONNECTED` only)
+    pub components: u32,
+    /// Row of the invalid word's first letter
+    pub row: u32,
+    /// Column of the invalid word's first letter
+    pub col: u32,
+    /// Number of letters in the invalid word
+    pub length: u32,
+    /// Whether the invalid word runs left to right (rather than top to bottom)
+    pub horizontal: bool
+}
+impl From<&BoardError> for BgmError {
+    fn from(error: &BoardError) -> Self {
+        let empty = BgmError { kind: BgmErrorKind::Empty, components: 0, row: 0, col: 0, length: 0, horizontal: false };
+        match error {
+            BoardError::Empty => empty,
+            BoardError::Disconnected { components } => BgmError { kind: BgmErrorKind::Disconnected, components: *components as u32, ..empty },
+            BoardError::InvalidWord(word) => BgmError {
+                kind: BgmErrorKind::InvalidWord,
+                row: word.row as u32,
+                col: word.col as u32,
+                length: word.word.len() as u32,
+                horizontal: word.direction == Direction::Horizontal,
+                ..empty
+            }
+        }
+    }
+}
+
+/// Runs `f`, turning a panic into `BGM_INTERNAL_ERROR` since unwinding into C is undefined behavior
+/// # Arguments
+/// * `f` - The body of an exported function
+/// # Returns
+/// * `i32` - What `f` returned, or `BGM_INTERNAL_ERROR` if it panicked
+fn catch_panic(f: impl FnOnce() -> i32) -> i32 {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or(BGM_INTERNAL_ERROR)
+}
+
+/// Loads a dictionary for `bgm_solve` and `bgm_validate`, leaving out words shorter than 2 letters
+///
+/// The caller owns the returned handle and must release it with `bgm_free_dictionary` (and not use it afterwards); `path` is only read
+/// during the call. A handle can be shared between threads, since no call modifies it.
+/// # Arguments
+/// * `path` - NUL-terminated UTF-8 path to a dictionary file, with one word per line (each optionally followed by whitespace and its
+///   frequency)
+/// # Returns
+/// * `BgmDictionary*` - The dictionary, or NULL if `path` is NULL or not UTF-8, the file couldn't be read or has an invalid word, or it
+///   has no words of at least 2 letters
+/// # Safety
+/// * `path` must be NULL or point to a NUL-terminated string
+#[no_mangle]
+pub unsafe extern "C" fn bgm_load_dictionary(path: *const c_char) -> *mut BgmDictionary {
+    if path.is_null() {
+        return ptr::null_mut();
+    }
+    let path = match CStr::from_ptr(path).to_str() {
+        Ok(path) => path,
+        Err(_) => return ptr::null_mut()
+    };
+    let dictionary = panic::catch_unwind(|| {
+        let mut dictionary = load_dictionary(Path::new(path), false).ok()?;
+        remove_short_words(&mut dictionary, None, DEFAULT_MIN_WORD_LENGTH);
+        if dictionary.is_empty() {
+            return None;
+        }
+        Some(BgmDictionary { masks: letter_masks(&dictionary), valid_words: dictionary.iter().cloned().collect(), dictionary })
+    });
+    match dictionary {
+        Ok(Some(dictionary)) => Box::into_raw(Box::new(dictionary)),
+        _ => ptr::null_mut()
+    }
+}
+
+/// Releases a dictionary returned by `bgm_load_dictionary`
+/// # Arguments
+/// * `dictionary` - The dictionary, or NULL (which does nothing)
+/// # Safety
+/// * `dictionary` must be NULL or a handle returned by `bgm_load_dictionary` that hasn't already been released, and no other call may be
+///   using it
+#[no_mangle]
+pub unsafe extern "C" fn bgm_free_dictionary(dictionary: *mut BgmDictionary) {
+    if !dictionary.is_null() {
+        drop(Box::from_raw(dictionary));
+    }
+}
+
+/// Solves a hand, writing the board as text to a buffer owned by the caller
+/// # Arguments
+/// * `dictionary` - Dictionary from `bgm_load_dictionary`
+/// * `counts` - Number of tiles of each letter in the hand, from 'A' to 'Z'
+/// * `out_buf` - Buffer to write the board to: the occupied part of the board, a line per row (separated by '\n', with spaces for empty
+///   cells and no trailing newline), then a terminating NUL
+/// * `out_len` - Size of `out_buf` in bytes
+/// # Returns
+/// * `int32_t` - Length of the board's text (not counting the NUL), `BGM_NO_SOLUTION` if the hand can't be played, `BGM_BUFFER_TOO_SMALL`
+///   if `out_buf` is too small (in which case nothing is written), or `BGM_INVALID_ARGUMENT` if a pointer is NULL
+/// # Safety
+/// * `dictionary` must be a live handle from `bgm_load_dictionary`, `counts` must point to 26 bytes, and `out_buf` must point to
+///   `out_len` writable bytes
+#[no_mangle]
+pub unsafe extern "C" fn bgm_solve(dictionary: *const BgmDictionary, counts: *const u8, out_buf: *mut c_char, out_len: usize) -> i32 {
+    if dictionary.is_null() || counts.is_null() || out_buf.is_null() {
+        return BGM_INVALID_ARGUMENT;
+    }
+    let dictionary = &*dictionary;
+    let mut letters: Letters = [0; ENGLISH_ALPHABET_SIZE];
+    letters.copy_from_slice(slice::from_raw_parts(counts, ENGLISH_ALPHABET_SIZE));
+    catch_panic(|| {
+        let board = match play_bananagrams(letters, 0, &dictionary.dictionary, &SolveOptions { dictionary_masks: Some(&dictionary.masks), ..SolveOptions::default() }) {
+            Ok(solution) => solution.board,
+            Err(_) => return BGM_NO_SOLUTION
+        };
+        let text = board_to_string(&board);
+        if text.len() >= out_len {
+            return BGM_BUFFER_TOO_SMALL;
+        }
+        let out = slice::from_raw_parts_mut(out_buf as *mut u8, out_len);
+        out[..text.len()].copy_from_slice(text.as_bytes());
+        out[text.len()] = 0;
+        text.len() as i32
+    })
+}
+
+/// Checks whether a board is a legal Bananagrams board, i.e. all of its letters are connected and every run of two or more letters is a
+/// word in the dictionary
+/// # Arguments
+/// * `dictionary` - Dictionary from `bgm_load_dictionary`
+/// * `grid` - The board, row by row, where 0 is empty and 1 ('A') through 26 ('Z') are letters
+/// * `w` - Number of columns in `grid`
+/// * `h` - Number of rows in `grid`
+/// * `errors` - Array owned by the caller to write the problems with the board to, in the order of `validate_board` (may be NULL if
+///   `max_errors` is 0)
+/// * `max_errors` - Length of `errors`; any problems past it are counted but not written
+/// # Returns
+/// * `int32_t` - Number of problems with the board (0 if it is legal, which may be more than `max_errors`), or `BGM_INVALID_ARGUMENT` if
+///   a pointer is NULL, `w` or `h` is more than `BGM_MAX_BOARD_SIZE`, or a value in `grid` is more than 26
+/// # Safety
+/// * `dictionary` must be a live handle from `bgm_load_dictionary`, `grid` must point to `w * h` bytes, and `errors` must point to
+///   `max_errors` writable `BgmError`s
+#[no_mangle]
+pub unsafe extern "C" fn bgm_validate(dictionary: *const BgmDictionary, grid: *const u8, w: u32, h: u32, errors: *mut BgmError, max_errors: usize) -> i32 {
+    // Checked before anything is allocated, since the board is as many rows and columns as the longer side
+    if w > BGM_MAX_BOARD_SIZE || h > BGM_MAX_BOARD_SIZE {
+        return BGM_INVALID_ARGUMENT;
+    }
+    let (w, h) = (w as usize, h as usize);
+    if dictionary.is_null() || (grid.is_null() && w * h > 0) || (errors.is_null() && max_errors > 0) {
+        return BGM_INVALID_ARGUMENT;
+    }
+    let dictionary = &*dictionary;
+    let grid = if w * h > 0 { slice::from_raw_parts(grid, w * h) } else { &[] };
+    let errors = if max_errors > 0 { slice::from_raw_parts_mut(errors, max_errors) } else { &mut [] };
+    catch_panic(|| {
+        let mut board = Board::new(w.max(h).max(1));
+        for (i, &val) in grid.iter().enumerate() {
+            match val {
+                0 => {},
+                1..=26 => board.set_val(i / w, i % w, val - 1),
+                _ => return BGM_INVALID_ARGUMENT
+            }
+        }
+        match validate_board(&board, &dictionary.valid_words, DEFAULT_MIN_WORD_LENGTH) {
+            Ok(()) => 0,
+            Err(found) => {
+                for (error, found) in errors.iter_mut().zip(found.iter()) {
+                    *error = BgmError::from(found);
+                }
+                found.len() as i32
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{ffi::CString, fs};
+
+    /// Loads a dictionary of the given words (one per line) through `bgm_load_dictionary`
+    fn dictionary(name: &str, words: &str) -> *mut BgmDictionary {
+        let path = std::env::temp_dir().join(format!("bananagrams_ffi_{}_{}.txt", name, std::process::id()));
+        fs::write(&path, words).unwrap();
+        let dictionary = unsafe { bgm_load_dictionary(CString::new(path.to_str().unwrap()).unwrap().as_ptr()) };
+        fs::remove_file(&path).unwrap();
+        dictionary
+    }
+
+    /// Gets the count of each letter in a hand (like `"CATOMB"`)
+    fn counts(hand: &str) -> [u8; ENGLISH_ALPHABET_SIZE] {
+        let mut counts = [0; ENGLISH_ALPHABET_SIZE];
+        for letter in hand.bytes() {
+            counts[(letter - b'A') as usize] += 1;
+        }
+        counts
+    }
+
+    #[test]
+    fn dictionaries_without_usable_words_are_not_loaded() {
+        assert!(dictionary("short", "A\nI\n").is_null());
+        assert!(dictionary("invalid", "CAT\nC4T\n").is_null());
+        unsafe {
+            assert!(bgm_load_dictionary(ptr::null()).is_null());
+            // Not UTF-8
+            assert!(bgm_load_dictionary(c"\xff\xfe".as_ptr()).is_null());
+        }
+    }
+
+    #[test]
+    fn solve_writes_the_board_only_if_it_and_its_nul_fit() {
+        let dictionary = dictionary("solve", "CAT\nTOMB\n");
+        assert!(!dictionary.is_null());
+        let expected = "C   \nA   \nTOMB";
+        unsafe {
+            // One byte short of room for the NUL, the buffer is left alone
+            let mut buffer = vec![b'x' as c_char; expected.len()];
+            assert_eq!(bgm_solve(dictionary, counts("CATOMB").as_ptr(), buffer.as_mut_ptr(), buffer.len()), BGM_BUFFER_TOO_SMALL);
+            assert!(buffer.iter().all(|&byte| byte == b'x' as c_char));
+            let mut buffer = vec![b'x' as c_char; expected.len() + 1];
+            assert_eq!(bgm_solve(dictionary, counts("CATOMB").as_ptr(), buffer.as_mut_ptr(), buffer.len()), expected.len() as i32);
+            assert_eq!(CStr::from_ptr(buffer.as_ptr()).to_str().unwrap(), expected);
+            assert_eq!(bgm_solve(dictionary, counts("CATOMBZ").as_ptr(), buffer.as_mut_ptr(), buffer.len()), BGM_NO_SOLUTION);
+            assert_eq!(bgm_solve(dictionary, ptr::null(), buffer.as_mut_ptr(), buffer.len()), BGM_INVALID_ARGUMENT);
+            assert_eq!(bgm_solve(dictionary, counts("CATOMB").as_ptr(), ptr::null_mut(), 0), BGM_INVALID_ARGUMENT);
+            bgm_free_dictionary(dictionary);
+        }
+    }
+
+    #[test]
+    fn validate_counts_every_problem_but_writes_at_most_max_errors() {
+        let dictionary = dictionary("validate", "CAT\nTOMB\n");
+        // XY across, a separate Q, and a separate ZZ down: 2 groups too many, and 2 invalid words
+        let grid = [
+            24, 25, 0, 0,
+            0, 0, 0, 17,
+            26, 0, 0, 0,
+            26, 0, 0, 0
+        ];
+        let untouched = BgmError { kind: BgmErrorKind::Empty, components: 9, row: 9, col: 9, length: 9, horizontal: true };
+        let mut errors = [untouched; 3];
+        unsafe {
+            assert_eq!(bgm_validate(dictionary, grid.as_ptr(), 4, 4, errors.as_mut_ptr(), 2), 3);
+            assert_eq!(errors[0], BgmError { kind: BgmErrorKind::Disconnected, components: 3, row: 0, col: 0, length: 0, horizontal: false });
+            assert_eq!(errors[1], BgmError { kind: BgmErrorKind::InvalidWord, components: 0, row: 0, col: 0, length: 2, horizontal: true });
+            assert_eq!(errors[2], untouched);
+            assert_eq!(bgm_validate(dictionary, grid.as_ptr(), 4, 4, errors.as_mut_ptr(), 3), 3);
+            assert_eq!(errors[2], BgmError { kind: BgmErrorKind::InvalidWord, components: 0, row: 2, col: 0, length: 2, horizontal: false });
+            // Boards that aren't square are read row by row
+            assert_eq!(bgm_validate(dictionary, [3, 1, 20, 0].as_ptr(), 4, 1, errors.as_mut_ptr(), 3), 0);
+            assert_eq!(bgm_validate(dictionary, [3, 1, 20, 0].as_ptr(), 1, 4, errors.as_mut_ptr(), 3), 0);
+            assert_eq!(bgm_validate(dictionary, [20, 1, 3].as_ptr(), 1, 3, errors.as_mut_ptr(), 3), 1);
+            assert_eq!(errors[0], BgmError { kind: BgmErrorKind::InvalidWord, components: 0, row: 0, col: 0, length: 3, horizontal: false });
+            assert_eq!(bgm_validate(dictionary, ptr::null(), 0, 0, ptr::null_mut(), 0), 1);
+            assert_eq!(bgm_validate(dictionary, grid.as_ptr(), 4, 4, ptr::null_mut(), 1), BGM_INVALID_ARGUMENT);
+            bgm_free_dictionary(dictionary);
+        }
+    }
+
+    #[test]
+    fn validate_rejects_boards_too_big_to_save() {
+        assert_eq!(BGM_MAX_BOARD_SIZE as usize, bananagrams_core::BGB_MAX_BOARD_SIZE);
+        let dictionary = dictionary("too_big", "CAT\nTOMB\n");
+        let side = BGM_MAX_BOARD_SIZE as usize;
+        // CAT down the last column of the largest board allowed
+        let mut grid = vec![0; side * side];
+        for (row, letter) in [3, 1, 20].into_iter().enumerate() {
+            grid[row * side + side - 1] = letter;
+        }
+        unsafe {
+            assert_eq!(bgm_validate(dictionary, grid.as_ptr(), BGM_MAX_BOARD_SIZE, BGM_MAX_BOARD_SIZE, ptr::null_mut(), 0), 0);
+            assert_eq!(bgm_validate(dictionary, grid.as_ptr(), BGM_MAX_BOARD_SIZE + 1, 1, ptr::null_mut(), 0), BGM_INVALID_ARGUMENT);
+            assert_eq!(bgm_validate(dictionary, grid.as_ptr(), 1, BGM_MAX_BOARD_SIZE + 1, ptr::null_mut(), 0), BGM_INVALID_ARGUMENT);
+            // Past the cap the grid is never read, so even one far too short for the sizes is rejected safely
+            assert_eq!(bgm_validate(dictionary, grid.as_ptr(), u32::MAX, u32::MAX, ptr::null_mut(), 0), BGM_INVALID_ARGUMENT);
+            bgm_free_dictionary(dictionary);
+        }
+    }
+}
@@ -0,0 +1,21 @@
+//! Compiles `examples/solve.c` against `include/bananagrams.h` and the built library, and runs it, so that the C interface is tested as C
+//! sees it (it exits with a nonzero status if any of its checks fail)
+use std::{env, path::Path, process::Command};
+
+#[test]
+fn c_example_compiles_and_passes_its_checks() {
+    let crate_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    // For the tests, the shared library is only built next to them in `target/<profile>/deps`, not copied up to `target/<profile>`
+    let library_dir = env::current_exe().unwrap().parent().unwrap().to_path_buf();
+    assert!(library_dir.join("libbananagrams.so").exists() || library_dir.join("libbananagrams.dylib").exists(), "No library in {}", library_dir.display());
+    let program = Path::new(env!("CARGO_TARGET_TMPDIR")).join("bgm_solve");
+    let compiled = Command::new(env::var("CC").unwrap_or_else(|_| "cc".to_string()))
+        .arg(crate_dir.join("examples/solve.c")).arg("-I").arg(crate_dir.join("include")).arg("-L").arg(&library_dir).args(["-lbananagrams", "-o"]).arg(&program)
+        .output().expect("Failed to run the C compiler!");
+    assert!(compiled.status.success(), "Failed to compile examples/solve.c: {}", String::from_utf8_lossy(&compiled.stderr));
+    let ran = Command::new(&program).arg(crate_dir.join("../new_short_dictionary.txt"))
+        .env("LD_LIBRARY_PATH", &library_dir).env("DYLD_LIBRARY_PATH", &library_dir)
+        .output().unwrap();
+    assert!(ran.status.success(), "examples/solve.c failed: {}", String::from_utf8_lossy(&ran.stderr));
+    assert!(String::from_utf8_lossy(&ran.stdout).ends_with("Invalid board: 2 problems\n"));
+}
@@ -0,0 +1,199 @@
+//! WebAssembly bindings for checking boards drawn in the browser without a server; only the validator is exposed, since the solver is
+//! too slow to be worth running here
+use std::cell::RefCell;
+use hashbrown::HashSet;
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+use bananagrams_core::{Board, BoardError, Direction, Word, BGB_MAX_BOARD_SIZE, DEFAULT_MIN_WORD_LENGTH, ENGLISH_ALPHABET_SIZE, extract_words, parse_weighted_dictionary, remove_short_words, validate_board};
+
+thread_local! {
+    /// The words boards are checked against, set by `init_dictionary` (WebAssembly in the browser runs on a single thread)
+    static VALID_WORDS: RefCell<Option<HashSet<Word>>> = const { RefCell::new(None) };
+}
+
+/// The result of `validate`, converted to a JavaScript object
+#[derive(Serialize)]
+struct Validation {
+    /// Whether the board is legal
+    valid: bool,
+    /// Each problem with the board, described for a person to read (see `BoardError`)
+    errors: Vec<String>,
+    /// Each horizontal and vertical run of two or more letters: the horizontal ones row by row, then the vertical ones column by column
+    words: Vec<CheckedWord>
+}
+
+/// A run of letters on a board checked by `validate`
+#[derive(Serialize)]
+struct CheckedWord {
+    /// The letters, in uppercase
+    word: String,
+    /// Row of the first letter in the grid
+    row: usize,
+    /// Column of the first letter in the grid
+    col: usize,
+    /// Direction the word runs in
+    direction: Direction,
+    /// Whether the run is a word in the dictionary of at least the minimum word length
+    valid: bool
+}
+
+/// Parses a dictionary for `init_dictionary`, leaving out words shorter than 2 letters
+/// # Arguments
+/// * `text` - The dictionary, with one word per line (each optionally followed by whitespace and its frequency)
+/// # Returns
+/// * `Result` with the words, or an error message if a word is invalid (with its line), or there are no words of at least 2 letters
+fn parse_words(text: &str) -> Result<HashSet<Word>, String> {
+    let (mut words, _) = parse_weighted_dictionary(text, false).map_err(|e| e.to_string())?;
+    remove_short_words(&mut words, None, DEFAULT_MIN_WORD_LENGTH);
+    if words.is_empty() {
+        return Err(format!("The dictionary has no words of at least {} letters", DEFAULT_MIN_WORD_LENGTH));
+    }
+    Ok(words.into_iter().collect())
+}
+
+/// Checks a board for `validate`, without converting anything to JavaScript (so that it can be tested natively)
+/// # Arguments
+/// * `grid` - The board, row by row, where 0 is empty and 1 ('A') through 26 ('Z') are letters
+/// * `width` - Number of columns in the `grid`
+/// * `valid_words` - The words the board is checked against
+/// # Returns
+/// * `Result` with the board's `Validation`, or an error message if `width` is 0 or doesn't divide the length of the `grid`, the `grid`
+///   has more than `BGB_MAX_BOARD_SIZE` rows or columns, or a value in the `grid` is more than 26
+fn check_grid(grid: &[u8], width: usize, valid_words: &HashSet<Word>) -> Result<Validation, String> {
+    if width == 0 || !grid.len().is_multiple_of(width) {
+        return Err(format!("A grid of {} cells can't have {} columns", grid.len(), width));
+    }
+    let height = grid.len() / width;
+    // The board is as many rows and columns as the longer side, so a long thin grid would otherwise allocate far more than it holds
+    if width.max(height) > BGB_MAX_BOARD_SIZE {
+        return Err(format!("A {}x{} grid (columns by rows) is larger than the maximum of {} each way", width, height, BGB_MAX_BOARD_SIZE));
+    }
+    let mut board = Board::new(width.max(height));
+    for (i, &val) in grid.iter().enumerate() {
+        match val {
+            0 => {},
+            1..=26 => board.set_val(i / width, i % width, val - 1),
+            _ => return Err(format!("Invalid value {} at row {}, column {} (must be 0 for empty or 1-{} for a letter)", val, i / width, i % width, ENGLISH_ALPHABET_SIZE))
+        }
+    }
+    let errors = validate_board(&board, valid_words, DEFAULT_MIN_WORD_LENGTH).err().unwrap_or_default();
+    let invalid: Vec<(usize, usize, Direction)> = errors.iter().filter_map(|error| match error {
+        BoardError::InvalidWord(word) => Some((word.row, word.col, word.direction)),
+        _ => None
+    }).collect();
+    let words = match board.bounds() {
+        Some((min_col, max_col, min_row, max_row)) => extract_words(&board, min_col, max_col, min_row, max_row),
+        None => Vec::new()
+    };
+    Ok(Validation {
+        valid: errors.is_empty(),
+        errors: errors.iter().map(|error| error.to_string()).collect(),
+        words: words.into_iter().map(|(word, row, col, direction)| CheckedWord { valid: !invalid.contains(&(row, col, direction)), word, row, col, direction }).collect()
+    })
+}
+
+/// Sets the dictionary that `validate` checks boards against, replacing any set before, and leaving out words shorter than 2 letters
+/// # Arguments
+/// * `text` - The dictionary, with one word per line (each optionally followed by whitespace and its frequency)
+/// # Returns
+/// * `number` - Number of words in the dictionary
+/// # Raises
+/// * `Error` - If a word is invalid (with its line in the message), or there are no words of at least 2 letters
+#[wasm_bindgen]
+pub fn init_dictionary(text: &str) -> Result<usize, JsError> {
+    let words = parse_words(text).map_err(|e| JsError::new(&e))?;
+    let count = words.len();
+    VALID_WORDS.with(|valid_words| *valid_words.borrow_mut() = Some(words));
+    Ok(count)
+}
+
+/// Checks whether a board is a legal Bananagrams board, i.e. all of its letters are connected and every run of two or more letters is a
+/// word in the dictionary set by `init_dictionary`
+/// # Arguments
+/// * `grid` - The board, row by row (such as a `Uint8Array`), where 0 is empty and 1 ('A') through 26 ('Z') are letters
+/// * `width` - Number of columns in the `grid`
+/// # Returns
+/// * `object` - `{valid, errors, words}`, where `errors` is a list of strings and each of `words` is `{word, row, col, direction, valid}`
+///   with `direction` either `"Horizontal"` or `"Vertical"`
+/// # Raises
+/// * `Error` - If `init_dictionary` hasn't been called, `width` is 0 or doesn't divide the length of the `grid`, the `grid` has more than
+///   254 rows or columns, or a value in the `grid` is more than 26
+#[wasm_bindgen]
+pub fn validate(grid: &[u8], width: u32) -> Result<JsValue, JsError> {
+    let validation = VALID_WORDS.with(|valid_words| match valid_words.borrow().as_ref() {
+        Some(valid_words) => check_grid(grid, width as usize, valid_words).map_err(|e| JsError::new(&e)),
+        None => Err(JsError::new("No dictionary has been set (call init_dictionary first)"))
+    })?;
+    serde_wasm_bindgen::to_value(&validation).map_err(|e| JsError::new(&e.to_string()))
+}
+
+// `wasm-pack test` isn't available offline, and `JsValue`/`JsError` can only be made on wasm32, so these check the functions the bindings call
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Makes a grid for `check_grid`
+    /// # Arguments
+    /// * `rows` - Rows of the board of equal length, with '.' for an empty cell
+    /// # Returns
+    /// * `(Vec<u8>, usize)` - The grid and its width
+    fn grid(rows: &[&str]) -> (Vec<u8>, usize) {
+        let cells = rows.iter().flat_map(|row| row.bytes()).map(|c| if c == b'.' { 0 } else { c - b'A' + 1 }).collect();
+        (cells, rows[0].len())
+    }
+
+    #[test]
+    fn a_legal_board_is_valid_with_its_words_listed() {
+        let valid_words = parse_words("CAT\nTOMB 12\nA\n").unwrap();
+        assert_eq!(valid_words.len(), 2);
+        let (cells, width) = grid(&["CAT.", "..O.", "..M.", "..B."]);
+        let validation = check_grid(&cells, width, &valid_words).unwrap();
+        assert!(validation.valid);
+        assert!(validation.errors.is_empty());
+        let words: Vec<(&str, usize, usize, Direction, bool)> = validation.words.iter().map(|w| (w.word.as_str(), w.row, w.col, w.direction, w.valid)).collect();
+        assert_eq!(words, vec![("CAT", 0, 0, Direction::Horizontal, true), ("TOMB", 0, 2, Direction::Vertical, true)]);
+    }
+
+    #[test]
+    fn an_illegal_board_says_what_is_wrong() {
+        let valid_words = parse_words("CAT\nTOMB\n").unwrap();
+        // TOXB isn't a word, and the A is on its own
+        let (cells, width) = grid(&["CAT.", "..O.", "..X.", "..B.", "....", "...A"]);
+        let validation = check_grid(&cells, width, &valid_words).unwrap();
+        assert!(!validation.valid);
+        assert_eq!(validation.errors.len(), 2);
+        assert!(validation.errors.iter().any(|error| error.starts_with("Invalid word")), "{:?}", validation.errors);
+        assert!(validation.errors.contains(&"The letters form 2 separate groups".to_string()), "{:?}", validation.errors);
+        let flagged: Vec<(&str, bool)> = validation.words.iter().map(|w| (w.word.as_str(), w.valid)).collect();
+        assert_eq!(flagged, vec![("CAT", true), ("TOXB", false)]);
+        // As is one with no letters at all
+        let empty = check_grid(&[0; 9], 3, &valid_words).unwrap();
+        assert!(!empty.valid);
+        assert_eq!(empty.errors, vec!["The board is empty".to_string()]);
+        assert!(empty.words.is_empty());
+    }
+
+    #[test]
+    fn malformed_grids_and_dictionaries_are_errors() {
+        let valid_words = parse_words("CAT\n").unwrap();
+        assert_eq!(check_grid(&[3, 1, 20], 0, &valid_words).err().unwrap(), "A grid of 3 cells can't have 0 columns");
+        assert_eq!(check_grid(&[3, 1, 20, 0], 3, &valid_words).err().unwrap(), "A grid of 4 cells can't have 3 columns");
+        assert_eq!(check_grid(&[3, 1, 27, 0], 2, &valid_words).err().unwrap(), "Invalid value 27 at row 1, column 0 (must be 0 for empty or 1-26 for a letter)");
+        assert!(parse_words("A\nI\n").err().unwrap().starts_with("The dictionary has no words"));
+        assert!(parse_words("").is_err());
+        assert!(parse_words("CAT\nC4T\n").is_err());
+    }
+
+    #[test]
+    fn grids_with_too_many_rows_or_columns_are_errors() {
+        let valid_words = parse_words("CAT\n").unwrap();
+        // CAT at the end of the widest row allowed, and down the end of the tallest column
+        let mut cells = vec![0; BGB_MAX_BOARD_SIZE];
+        cells[BGB_MAX_BOARD_SIZE-3..].copy_from_slice(&[3, 1, 20]);
+        assert!(check_grid(&cells, BGB_MAX_BOARD_SIZE, &valid_words).unwrap().valid);
+        assert!(check_grid(&cells, 1, &valid_words).unwrap().valid);
+        cells.push(0);
+        assert_eq!(check_grid(&cells, cells.len(), &valid_words).err().unwrap(), "A 255x1 grid (columns by rows) is larger than the maximum of 254 each way");
+        assert_eq!(check_grid(&cells, 1, &valid_words).err().unwrap(), "A 1x255 grid (columns by rows) is larger than the maximum of 254 each way");
+    }
+}
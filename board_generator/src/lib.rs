@@ -1,292 +1,2066 @@
 use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict, PyString};
 use rand::prelude::*;
-use rand::distributions::Standard;
-use array2d::Array2D;
-use std::collections::{HashSet, VecDeque};
-
-type Board = Array2D<usize>;
-
-/// Dimensions of the board
-const BOARD_SIZE: usize = 144;
-/// The maximum length of any word in the dictionary
-const MAX_WORD_LENGTH: usize = 17;
-
-#[derive(Copy, Clone)]
-enum Direction {
-    Horizontal,
-    Vertical,
-}
-impl Distribution<Direction> for Standard {
-    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Direction {
-        match rng.gen_range(0..=1) {
-            0 => Direction::Horizontal,
-            1 => Direction::Vertical
+use pyo3::exceptions::{PyOSError, PyValueError};
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use hashbrown::HashSet;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::{fs, hash::{Hash, Hasher}, path::PathBuf, thread, time::Duration};
+use std::collections::hash_map::DefaultHasher;
+use serde::Serialize;
+use bananagrams_core::dictionary_cache::{PreprocessedDictionary, load_preprocessed_dictionary};
+use bananagrams_core::{msgpack, render, Alphabet, Board, BoardError, BoardParseError, DictionaryError, DictionaryIndex, Direction, Edit, FirstWordStrategy, HandSizeDistribution, LetterMask, Letters, PlayedWord, SolveFailure, SolveOptions, TileSet, Word, BOARD_SIZE, DEFAULT_FREQUENCY_TEMPERATURE, DEFAULT_MIN_WORD_LENGTH, EMPTY_VALUE, MINIMUM_HAND_SIZE, board_to_string, check_hand_against_pool, choose_by_frequency, complete_board, convert_array_to_word, decompress, distinct_symmetries, extract_words, generate_hand, is_board_valid_horizontal, is_makeable, is_board_valid_vertical, letter_masks, letters_to_string, load_weighted_dictionary, play_bananagrams, play_bananagrams_multi, play_bananagrams_parallel, read_boards, remove_short_words, repair_board, seeded_rng, validate_board};
+#[cfg(feature = "numpy")]
+use bananagrams_core::{ONEHOT_CHANNELS, board_to_onehot};
+#[cfg(feature = "numpy")]
+use numpy::{IntoPyArray, PyArray2, PyArray3, ndarray::{Array1, Array2, Array3}};
+
+/// Maximum number of times to try generating each board before giving up on it
+const MAXIMUM_GENERATION_ATTEMPTS: usize = 1000;
+/// Maximum number of words to try adding to a single board before giving up on it
+const MAXIMUM_PLAY_ATTEMPTS: usize = 1000;
+
+/// Places a word on the `board` (modifying it in-place), without any of the checks the solver makes
+/// # Arguments
+/// * `board` - Board to change in-place
+/// * `word` - Word to place
+/// * `dir` - Direction to place the `word`
+/// * `start_row` - Row of the `word`'s first letter
+/// * `start_col` - Column of the `word`'s first letter
+/// * `played_positions` - Set of previously played (row, column) positions; will be modified in-place to add newly played positions
+/// # Returns
+/// * `Vec<(usize, usize)>` - The (row, column) positions that were empty before the `word` was placed, so it can be removed again
+fn place_word(board: &mut Board, word: &Word, dir: Direction, start_row: usize, start_col: usize, played_positions: &mut BTreeSet<(usize, usize)>) -> Vec<(usize, usize)> {
+    let mut new_positions = Vec::with_capacity(word.len());
+    for (i, letter) in word.iter().enumerate() {
+        let (row, col) = match dir {
+            Direction::Horizontal => (start_row, start_col+i),
+            Direction::Vertical => (start_row+i, start_col)
+        };
+        if board.get_val(row, col) == EMPTY_VALUE {
+            new_positions.push((row, col));
         }
+        board.set_val(row, col, *letter);
+        played_positions.insert((row, col));
     }
+    new_positions
 }
-impl Direction {
-    /// Gets the opposite direction of the current (i.e. vertical -> horizontal or vice versa)
-    fn opposite(self) -> Direction {
-        match self {
-            Direction::Horizontal => Direction::Vertical,
-            Direction::Vertical => Direction::Horizontal
-        }
+
+/// Removes a placed word from the `board` (modifying it in-place)
+/// # Arguments
+/// * `board` - Board to change in-place
+/// * `new_positions` - The positions newly played, as returned by `place_word`
+/// * `played_positions` - Set of played (row, column) positions; will be modified in-place to remove `new_positions`
+fn remove_word(board: &mut Board, new_positions: &[(usize, usize)], played_positions: &mut BTreeSet<(usize, usize)>) {
+    for (row, col) in new_positions.iter() {
+        board.set_val(*row, *col, EMPTY_VALUE);
+        played_positions.remove(&(*row, *col));
     }
 }
 
-/// Checks that a `board` is valid after a word is played horizontally, given the specified list of `valid_word`s
-/// Note that this does not check if all words are contiguous; this condition must be enforced elsewhere.
-/// # Arguments
-/// * `board` - `Board` being checked
-/// * `min_col` - Minimum x (column) index of the subsection of the `board` to be checked
-/// * `max_col` - Maximum x (column) index of the subsection of the `board` to be checked
-/// * `min_row` - Minimum y (row) index of the subsection of the `board` to be checked
-/// * `max_row` - Maximum y (row) index of the subsection of the `board` to be checked
-/// * `row` - Row of the word played
-/// * `start_col` - Starting column of the word played
-/// * `end_col` - Ending column of the word played
-/// * `valid_words` - HashSet of all valid words as `Vec<usize>`s
-/// # Returns
-/// `bool` - whether the given `board` is made only of valid words
-fn is_board_valid_horizontal(board: &Board, min_col: usize, max_col: usize, min_row: usize, max_row: usize, row: usize, start_col: usize, end_col: usize, valid_words: &HashSet<Vec<usize>>) -> bool {
-    let mut current_letters: Vec<usize> = Vec::with_capacity(MAX_WORD_LENGTH);
-    // Check across the row where the word was played
-    for col_idx in min_col..max_col+1 {
-        // If we're not at an empty square, add it to the current word we're looking at
-        if board[(row, col_idx)] != 0 {
-            current_letters.push(board[(row, col_idx)]);
+/// Chooses a random word from a dictionary, weighted by frequency if the dictionary has frequencies
+/// # Arguments
+/// * `candidates` - Indices of the words in the dictionary to choose from
+/// * `frequencies` - The frequency of each word in the dictionary, if any (otherwise the word is chosen uniformly)
+/// * `frequency_temperature` - How strongly to weight by frequency (see `bananagrams_core::choose_by_frequency`)
+/// * `rng` - Random number generator
+/// # Returns
+/// * `Option<usize>` - The index of the chosen word, or `None` if there are no `candidates`
+fn choose_word(candidates: impl Iterator<Item = usize>, frequencies: Option<&[f64]>, frequency_temperature: f64, rng: &mut impl Rng) -> Option<usize> {
+    match frequencies {
+        Some(frequencies) => choose_by_frequency(candidates, frequencies, frequency_temperature, rng),
+        None => candidates.choose(rng)
+    }
+}
+
+/// Generates a random board by repeatedly playing words from the `dictionary` that overlap letters already on the board
+/// # Arguments
+/// * `dictionary` - Vector of valid words (see `convert_word_to_array`)
+/// * `frequencies` - The frequency of each word in `dictionary`, if any, to play more frequent words more often
+/// * `frequency_temperature` - How strongly to weight by frequency (see `bananagrams_core::choose_by_frequency`)
+/// * `valid_words` - HashSet version of `dictionary` for faster membership checking
+/// * `index` - Index of the words in `dictionary` containing each letter
+/// * `target_size` - Minimum number of tiles to place on the board
+/// * `min_word_length` - Minimum length of a word, below which crossings formed on the board are invalid
+/// * `rng` - Random number generator
+/// # Returns
+/// * `Option`
+///     * `None` - If no word fits `target_size`, or `MAXIMUM_PLAY_ATTEMPTS` plays were tried without reaching `target_size` tiles
+///     * `Some` - A tuple of (the generated board, minimum column, maximum column, minimum row, maximum row); every word on the board is in `valid_words`
+#[allow(clippy::too_many_arguments, reason = "the dictionary's preprocessed parts are borrowed separately so that `BoardGenerator` and the free functions can share this")]
+fn generate_board(dictionary: &[Word], frequencies: Option<&[f64]>, frequency_temperature: f64, valid_words: &HashSet<Word>, index: &DictionaryIndex, target_size: usize, min_word_length: usize, rng: &mut impl Rng) -> Option<(Board, usize, usize, usize, usize)> {
+    let mut board = Board::new(BOARD_SIZE);
+    let start_word = &dictionary[choose_word((0..dictionary.len()).filter(|&i| dictionary[i].len() <= target_size), frequencies, frequency_temperature, rng)?];
+    // Play the first word in a random direction in the middle of the board
+    let mut dir: Direction = rng.gen();
+    // Ordered (rather than hashed) so that choosing from it is reproducible for a given seed
+    let mut played_positions = BTreeSet::new();
+    let (start_row, start_col) = match dir {
+        Direction::Horizontal => (BOARD_SIZE/2, BOARD_SIZE/2 - start_word.len()/2),
+        Direction::Vertical => (BOARD_SIZE/2 - start_word.len()/2, BOARD_SIZE/2)
+    };
+    place_word(&mut board, start_word, dir, start_row, start_col, &mut played_positions);
+    let (mut min_col, mut max_col, mut min_row, mut max_row) = match dir {
+        Direction::Horizontal => (start_col, start_col + start_word.len() - 1, start_row, start_row),
+        Direction::Vertical => (start_col, start_col, start_row, start_row + start_word.len() - 1)
+    };
+    let mut tiles_placed = start_word.len();
+    // The second word is played in the opposite direction, after which the direction is random
+    dir = dir.opposite();
+    let mut play_attempts = 0;
+    while tiles_placed < target_size {
+        play_attempts += 1;
+        if play_attempts > MAXIMUM_PLAY_ATTEMPTS {
+            return None;
+        }
+        if play_attempts > 1 {
+            dir = rng.gen();
+        }
+        let (play_row, play_col) = *played_positions.iter().choose(rng)?;
+        let play_letter = board.get_val(play_row, play_col);
+        // Choose a random word that overlaps
+        let word = match choose_word(index.words_containing(play_letter as usize).iter().copied(), frequencies, frequency_temperature, rng) {
+            Some(word_idx) => &dictionary[word_idx],
+            None => continue
+        };
+        // Choose a random position of overlapping
+        let pos = word.iter().enumerate().filter_map(|(idx, c)| if *c == play_letter { Some(idx) } else { None }).choose(rng).unwrap();
+        // Skip placements that would start or end off the board
+        let start = match dir {
+            Direction::Horizontal => play_col.checked_sub(pos).map(|col| (play_row, col)),
+            Direction::Vertical => play_row.checked_sub(pos).map(|row| (row, play_col))
+        };
+        let (row, col) = match start {
+            Some(start) => start,
+            None => continue
+        };
+        let (end_row, end_col) = match dir {
+            Direction::Horizontal => (row, col + word.len() - 1),
+            Direction::Vertical => (row + word.len() - 1, col)
+        };
+        if end_row >= BOARD_SIZE || end_col >= BOARD_SIZE {
+            continue;
+        }
+        // Skip placements that would overwrite a different letter or wouldn't place any new tiles
+        let cells: Vec<(usize, usize)> = match dir {
+            Direction::Horizontal => (col..end_col+1).map(|c| (row, c)).collect(),
+            Direction::Vertical => (row..end_row+1).map(|r| (r, col)).collect()
+        };
+        if cells.iter().zip(word.iter()).any(|((r, c), letter)| board.get_val(*r, *c) != EMPTY_VALUE && board.get_val(*r, *c) != *letter) {
+            continue;
+        }
+        if cells.iter().all(|(r, c)| board.get_val(*r, *c) != EMPTY_VALUE) {
+            continue;
+        }
+        // Play the word, keeping the previous bounds around in case it needs to be rolled back
+        let previous_bounds = (min_col, max_col, min_row, max_row);
+        let new_positions = place_word(&mut board, word, dir, row, col, &mut played_positions);
+        min_col = min_col.min(col);
+        max_col = max_col.max(end_col);
+        min_row = min_row.min(row);
+        max_row = max_row.max(end_row);
+        let valid = match dir {
+            Direction::Horizontal => is_board_valid_horizontal(&board, min_col, max_col, min_row, max_row, row, col, end_col, valid_words, min_word_length),
+            Direction::Vertical => is_board_valid_vertical(&board, min_col, max_col, min_row, max_row, row, end_row, col, valid_words, min_word_length)
+        };
+        if valid {
+            tiles_placed += new_positions.len();
         }
         else {
-            if current_letters.len() > 1 && !valid_words.contains(&current_letters) {
-                return false;
-            }
-            current_letters.clear();
-            if col_idx > end_col {
-                break;
-            }
+            // If the play formed some invalid words, undo it
+            remove_word(&mut board, &new_positions, &mut played_positions);
+            (min_col, max_col, min_row, max_row) = previous_bounds;
         }
     }
-    if current_letters.len() > 1 && !valid_words.contains(&current_letters) {
-        return false;
+    Some((board, min_col, max_col, min_row, max_row))
+}
+
+/// Checks that a frequency temperature can be used to weight words
+/// # Arguments
+/// * `frequency_temperature` - Requested temperature of the word frequencies
+/// # Returns
+/// * `PyResult` - Empty if `frequency_temperature` is valid, otherwise a `ValueError`
+fn check_frequency_temperature(frequency_temperature: f64) -> PyResult<()> {
+    if frequency_temperature.is_nan() || frequency_temperature < 0.0 {
+        return Err(PyValueError::new_err("frequency_temperature must be a non-negative number"));
+    }
+    Ok(())
+}
+
+/// Checks that a target number of tiles for the solver can be reached
+/// # Arguments
+/// * `target_tiles` - Requested number of tiles to finish each board with, if any
+/// # Returns
+/// * `PyResult` - Empty if `target_tiles` is valid, otherwise a `ValueError`
+fn check_target_tiles(target_tiles: Option<usize>) -> PyResult<()> {
+    if target_tiles == Some(0) {
+        return Err(PyValueError::new_err("target_tiles must be at least 1"));
     }
-    // Check down each column where a letter was played
-    for col_idx in start_col..end_col+1 {
-        current_letters.clear();
-        for row_idx in min_row..max_row+1 {
-            if board[(row_idx, col_idx)] != 0 {
-                current_letters.push(board[(row_idx, col_idx)]);
+    Ok(())
+}
+
+/// Converts the name of a first-word strategy from Python
+/// # Arguments
+/// * `name` - Name of the strategy (one of `FirstWordStrategy::NAMES`)
+/// * `seed` - Seed to shuffle the first words with if the strategy is `"random"`
+/// # Returns
+/// * `FirstWordStrategy` - The strategy
+/// # Raises
+/// * `ValueError` - If there isn't a strategy with that name
+fn first_word_from_py(name: &str, seed: u64) -> PyResult<FirstWordStrategy> {
+    FirstWordStrategy::from_name(name, seed).ok_or_else(|| PyValueError::new_err(format!("Invalid first_word {:?} (must be one of {})", name, FirstWordStrategy::NAMES.join(", "))))
+}
+
+/// Checks that `target_size` is a number of tiles that a board can hold
+/// # Arguments
+/// * `target_size` - Requested minimum number of tiles on a board
+/// # Returns
+/// * `PyResult` - Empty if `target_size` is valid, otherwise a `ValueError`
+fn check_target_size(target_size: usize) -> PyResult<()> {
+    if target_size == 0 || target_size > BOARD_SIZE*BOARD_SIZE {
+        return Err(PyValueError::new_err(format!("target_size must be between 1 and {}", BOARD_SIZE*BOARD_SIZE)));
+    }
+    Ok(())
+}
+
+/// Generates up to `count` boards, stopping early if generation repeatedly fails
+/// # Arguments
+/// * `dictionary` - Vector of valid words, each represented as a vector of numbers (see `convert_word_to_array`)
+/// * `frequencies` - The frequency of each word in `dictionary`, if any
+/// * `frequency_temperature` - How strongly to weight by frequency (see `bananagrams_core::choose_by_frequency`)
+/// * `valid_words` - HashSet version of `dictionary` for faster membership checking
+/// * `index` - Index of the words in `dictionary` containing each letter
+/// * `target_size` - Minimum number of tiles to place on each board
+/// * `min_word_length` - Minimum length of a word, below which crossings formed on a board are invalid
+/// * `count` - Number of boards to generate
+/// * `rng` - Random number generator
+/// # Returns
+/// * `Vec<Board>` - The generated boards
+#[allow(clippy::too_many_arguments, reason = "the dictionary's preprocessed parts are borrowed separately so that `BoardGenerator` and the free functions can share this")]
+fn generate_many(dictionary: &Vec<Word>, frequencies: Option<&[f64]>, frequency_temperature: f64, valid_words: &HashSet<Word>, index: &DictionaryIndex, target_size: usize, min_word_length: usize, count: usize, rng: &mut impl Rng) -> Vec<Board> {
+    let mut boards = Vec::with_capacity(count);
+    while boards.len() < count {
+        match (0..MAXIMUM_GENERATION_ATTEMPTS).find_map(|_| generate_board(dictionary, frequencies, frequency_temperature, valid_words, index, target_size, min_word_length, rng)) {
+            Some((board, _, _, _, _)) => boards.push(board),
+            // If every attempt failed, the remaining boards are unlikely to succeed either
+            None => break
+        }
+    }
+    boards
+}
+
+/// Generates random boards using words from the given dictionary
+/// # Arguments
+/// * `dictionary` - List of valid words, each optionally followed by whitespace and its frequency (like `"CAT 120"`)
+/// * `target_size` - Minimum number of tiles on each board
+/// * `count` - Number of boards to generate
+/// * `seed` - Optional seed for reproducible generation
+/// * `min_word_length` - Minimum length of a word; shorter words are removed from the `dictionary`, and crossings shorter than this are invalid
+/// * `frequency_temperature` - If the `dictionary` gives frequencies, how strongly to prefer more frequent words: each word is weighted by
+///   its frequency to the power of `1/frequency_temperature`, so 0 always plays the most frequent word that fits and higher temperatures
+///   approach uniform sampling
+/// * `as_string` - Whether to return the boards in string form (as before `Board` was added) rather than as `Board`s
+/// # Returns
+/// * `list[Board] | list[str]` - The generated boards; if generation repeatedly fails, fewer than `count` boards are returned
+/// # Raises
+/// * `ValueError` - If a word in the `dictionary` is invalid (see `bananagrams_core::DictionaryError`), the `dictionary` contains no words of at
+///   least `min_word_length` letters, `target_size` is 0 or more than the board can fit, or `frequency_temperature` is negative
+#[pyfunction]
+#[pyo3(signature = (dictionary, target_size, count, seed=None, min_word_length=DEFAULT_MIN_WORD_LENGTH, frequency_temperature=DEFAULT_FREQUENCY_TEMPERATURE, as_string=false))]
+#[allow(clippy::too_many_arguments, reason = "mirrors the keyword arguments of the Python function")]
+fn generate_boards(py: Python, dictionary: Vec<String>, target_size: usize, count: usize, seed: Option<u64>, min_word_length: usize, frequency_temperature: f64, as_string: bool) -> PyResult<Vec<PyObject>> {
+    let (dictionary, frequencies) = solver_dictionary(&dictionary, min_word_length)?;
+    if dictionary.is_empty() {
+        return Err(no_words_error(min_word_length));
+    }
+    check_target_size(target_size)?;
+    check_frequency_temperature(frequency_temperature)?;
+    let valid_words: HashSet<Word> = dictionary.iter().cloned().collect();
+    let index = DictionaryIndex::new(&dictionary);
+    Ok(generate_many(&dictionary, frequencies.as_deref(), frequency_temperature, &valid_words, &index, target_size, min_word_length, count, &mut seeded_rng(seed)).into_iter().map(|board| board_into_py(py, board, as_string)).collect())
+}
+
+/// Converts a board to return to Python
+/// # Arguments
+/// * `py` - Python GIL token
+/// * `board` - Board to return
+/// * `as_string` - Whether to return the board in string form rather than as a `Board`
+/// # Returns
+/// * `PyObject` - A `Board`, or a `str` if `as_string`
+fn board_into_py(py: Python, board: Board, as_string: bool) -> PyObject {
+    if as_string {
+        board_to_string(&board).into_py(py)
+    }
+    else {
+        PyBoard { board }.into_py(py)
+    }
+}
+
+/// Creates the error raised when a dictionary has no words to use
+/// # Arguments
+/// * `min_word_length` - Minimum length of a word
+/// # Returns
+/// * `PyErr` - A `ValueError` saying the dictionary has no words of at least `min_word_length` letters
+fn no_words_error(min_word_length: usize) -> PyErr {
+    PyValueError::new_err(format!("The dictionary contains no words of at least {} letters", min_word_length))
+}
+
+/// Converts a problem loading a dictionary into a Python exception
+/// # Arguments
+/// * `error` - The problem
+/// # Returns
+/// * `PyErr` - An `OSError` if the dictionary file couldn't be read, otherwise a `ValueError`, with the message of the `error`
+fn dictionary_error(error: DictionaryError) -> PyErr {
+    match error {
+        DictionaryError::Read { .. } => PyOSError::new_err(error.to_string()),
+        _ => PyValueError::new_err(error.to_string())
+    }
+}
+
+/// Converts a dictionary from Python into the representation used by the solver
+/// # Arguments
+/// * `dictionary` - List of valid words, each optionally followed by whitespace and its frequency
+/// * `min_word_length` - Minimum length of a word, below which words are left out
+/// # Returns
+/// * `(Vec<Word>, Option<Vec<f64>>)` - Tuple of (the words in numeric form (see `bananagrams_core::parse_dictionary`) sorted longest first,
+///   the frequency of each word if any were given)
+/// # Raises
+/// * `ValueError` - If a word or frequency is invalid, such as a word having a character other than A-Z (with the position of the word in
+///   `dictionary`, from 1, as its line)
+fn solver_dictionary(dictionary: &[String], min_word_length: usize) -> PyResult<(Vec<Word>, Option<Vec<f64>>)> {
+    let (mut words, mut frequencies) = Alphabet::english().parse_weighted_dictionary_lines(dictionary.iter().map(String::as_str), false).map_err(dictionary_error)?;
+    remove_short_words(&mut words, frequencies.as_mut(), min_word_length);
+    Ok((words, frequencies))
+}
+
+/// Computes what the solver and board generator need from a dictionary up front (as cached by `load_preprocessed_dictionary`)
+/// # Arguments
+/// * `dictionary` - The words, sorted longest first, and the frequency of each word if any were given
+/// # Returns
+/// * `PreprocessedDictionary` - The words along with their letter masks and index
+fn preprocess((words, frequencies): (Vec<Word>, Option<Vec<f64>>)) -> PreprocessedDictionary {
+    PreprocessedDictionary { masks: letter_masks(&words), index: DictionaryIndex::new(&words), words, frequencies }
+}
+
+/// Converts a hand of letters from Python into the number of each letter
+/// # Arguments
+/// * `letters` - Either a string of letters (like `"AAEINRST"`) or a dict mapping each letter to its count
+/// # Returns
+/// * `Letters` - Length-26 array of the number of each letter in the hand
+/// # Raises
+/// * `ValueError` - If any of the letters isn't A-Z (naming the offending character), or there are more than 255 of a letter
+fn letters_from_py(letters: &PyAny) -> PyResult<Letters> {
+    let mut counts = [0usize; 26];
+    if let Ok(letters) = letters.extract::<String>() {
+        for c in letters.chars() {
+            if !c.is_ascii_alphabetic() {
+                return Err(PyValueError::new_err(format!("Invalid letter {:?} in hand", c)));
             }
-            else {
-                if current_letters.len() > 1 && !valid_words.contains(&current_letters) {
-                    return false;
-                }
-                current_letters.clear();
-                if row_idx > row {
-                    break;
-                }
+            counts[c.to_ascii_uppercase() as usize - 65] += 1;
+        }
+    }
+    else {
+        let letters: HashMap<String, usize> = letters.extract()?;
+        for (letter, count) in letters.iter() {
+            let mut chars = letter.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) if c.is_ascii_alphabetic() => counts[c.to_ascii_uppercase() as usize - 65] += count,
+                _ => return Err(PyValueError::new_err(format!("Invalid letter {:?} in hand", letter)))
             }
         }
-        if current_letters.len() > 1 && !valid_words.contains(&current_letters) {
-            return false;
+    }
+    let mut hand: Letters = [0u8; 26];
+    for (i, count) in counts.iter().enumerate() {
+        hand[i] = u8::try_from(*count).map_err(|_| PyValueError::new_err(format!("Too many of the letter {:?} in hand (at most 255 are allowed)", (i as u8 + 65) as char)))?;
+    }
+    Ok(hand)
+}
+
+/// Converts a hand of letters from Python like `letters_from_py`, checking that it could be drawn from a pool of tiles
+/// # Arguments
+/// * `letters` - Either a string of letters (like `"AAEINRST"`) or a dict mapping each letter to its count
+/// * `pool` - The tiles the hand must be drawable from, or `None` to allow any hand
+/// # Returns
+/// * `Letters` - Length-26 array of the number of each letter in the hand
+/// # Raises
+/// * `ValueError` - If `letters_from_py` does, or the hand has more of a letter than the `pool` (listing each such letter)
+fn hand_from_py(letters: &PyAny, pool: Option<&TileSet>) -> PyResult<Letters> {
+    let letters = letters_from_py(letters)?;
+    if let Some(pool) = pool {
+        check_hand_against_pool(&letters, pool).map_err(|e| PyValueError::new_err(format!("{} (pass check_pool=False to allow this)", e)))?;
+    }
+    Ok(letters)
+}
+
+/// Solves a hand of letters using the given dictionary
+/// # Arguments
+/// * `letters` - Either a string of letters (like `"AAEINRST"`) or a dict mapping each letter to its count
+/// * `dictionary` - List of valid words
+/// * `min_word_length` - Minimum length of a word; shorter words are removed from the `dictionary`, and crossings shorter than this are invalid
+/// * `target_tiles` - Optional number of tiles to finish the board with, leaving the rest of the letters unplayed (the whole hand is played
+///   if it has fewer letters)
+/// * `first_word` - How to order the words tried first, which largely decides the board found: `"longest"`, `"random"`, `"most_vowels"`, or
+///   `"rarest_letters"` (trying words with letters like Q, Z, X, and J first)
+/// * `threads` - Number of threads to try first words on at once
+/// * `deterministic` - When using several `threads`, whether to always return the board the single-threaded solver would (given enough
+///   word budget), rather than whichever board is found first
+/// * `as_string` - Whether to return the board in string form (as before `Board` was added) rather than as a `Board`
+/// * `anchor_word` - Optional word to always play first, across the middle of the board, so that it's on every board (`first_word` is
+///   then ignored)
+/// * `check_pool` - Whether to raise a `ValueError` if the hand has more of a letter than the standard 144 tiles (so it could never come up
+///   in a real game)
+/// # Returns
+/// * `Board | str | None` - The solved board, or `None` if no solution was found within the word-check budget
+/// # Raises
+/// * `ValueError` - If any of the letters isn't A-Z, a word in the `dictionary` is invalid, the `dictionary` contains no words of at least
+///   `min_word_length` letters, `target_tiles` or `threads` is 0, `first_word` isn't a strategy, `anchor_word` isn't in the `dictionary` or
+///   can't be made from the `letters`, or the hand can't be drawn from the standard tiles (with `check_pool`)
+#[pyfunction]
+#[pyo3(signature = (letters, dictionary, min_word_length=DEFAULT_MIN_WORD_LENGTH, target_tiles=None, first_word="longest", threads=1, deterministic=false, as_string=false, anchor_word=None, check_pool=true))]
+#[allow(clippy::too_many_arguments, reason = "mirrors the keyword arguments of the Python function")]
+fn solve_hand(py: Python, letters: &PyAny, dictionary: Vec<String>, min_word_length: usize, target_tiles: Option<usize>, first_word: &str, threads: usize, deterministic: bool, as_string: bool, anchor_word: Option<&str>, check_pool: bool) -> PyResult<Option<PyObject>> {
+    let (dictionary, _) = solver_dictionary(&dictionary, min_word_length)?;
+    if dictionary.is_empty() {
+        return Err(no_words_error(min_word_length));
+    }
+    Ok(solve_letters(letters, check_pool.then(TileSet::standard).as_ref(), &dictionary, None, min_word_length, target_tiles, first_word, anchor_word, threads, deterministic)?.map(|board| board_into_py(py, board, as_string)))
+}
+
+/// Solves a hand of letters from Python, on one thread or several
+/// # Arguments
+/// * `letters` - Either a string of letters (like `"AAEINRST"`) or a dict mapping each letter to its count
+/// * `pool` - The tiles the hand must be drawable from, or `None` to allow any hand
+/// * `dictionary` - The words to solve with
+/// * `dictionary_masks` - The letters in each word of `dictionary`, if already computed
+/// * `min_word_length` - Minimum length of a word on the board
+/// * `target_tiles` - Optional number of tiles to finish the board with
+/// * `first_word` - Name of the first-word strategy
+/// * `anchor_word` - Optional word to always play first
+/// * `threads` - Number of threads to try first words on at once (see `play_bananagrams_parallel`)
+/// * `deterministic` - Whether the board found with several `threads` must be the one found with one
+/// # Returns
+/// * `Option<Board>` - The solved board, or `None` if no solution was found within the word-check budget
+/// # Raises
+/// * `ValueError` - If any of the letters isn't A-Z, the hand can't be drawn from the `pool`, `target_tiles` or `threads` is 0,
+///   `first_word` isn't a strategy, or `anchor_word` isn't in the `dictionary` or can't be made from the `letters`
+#[allow(clippy::too_many_arguments, reason = "takes the keyword arguments of the Python functions that call it")]
+fn solve_letters(letters: &PyAny, pool: Option<&TileSet>, dictionary: &Vec<Word>, dictionary_masks: Option<&[LetterMask]>, min_word_length: usize, target_tiles: Option<usize>, first_word: &str, anchor_word: Option<&str>, threads: usize, deterministic: bool) -> PyResult<Option<Board>> {
+    let letters = hand_from_py(letters, pool)?;
+    check_target_tiles(target_tiles)?;
+    let first_word = first_word_from_py(first_word, thread_rng().gen())?;
+    let anchor_word = anchor_word.map(|word| anchor_from_py(word, &letters, dictionary)).transpose()?;
+    let result = match threads {
+        0 => return Err(PyValueError::new_err("threads must be at least 1")),
+        1 => play_bananagrams(letters, 0, dictionary, &SolveOptions { dictionary_masks, min_word_length, target_tiles, first_word, anchor_word: anchor_word.as_ref(), ..SolveOptions::default() }).ok(),
+        threads => {
+            let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build().map_err(|e| PyValueError::new_err(e.to_string()))?;
+            pool.install(|| play_bananagrams_parallel(letters, 0, dictionary, &SolveOptions { dictionary_masks, min_word_length, target_tiles, first_word, anchor_word: anchor_word.as_ref(), ..SolveOptions::default() }, deterministic)).ok()
         }
+    };
+    Ok(result.map(|solution| solution.board))
+}
+
+/// Converts a word to always play first from Python, checking that it can be played
+/// # Arguments
+/// * `word` - The word
+/// * `letters` - Number of each letter in the hand
+/// * `dictionary` - The words to solve with
+/// # Returns
+/// * `Word` - The word in numeric form
+/// # Raises
+/// * `ValueError` - If the `word` isn't in the `dictionary` or can't be made from the `letters`
+fn anchor_from_py(word: &str, letters: &Letters, dictionary: &[Word]) -> PyResult<Word> {
+    let anchor = Alphabet::english().convert_word(word).filter(|anchor| dictionary.contains(anchor))
+        .ok_or_else(|| PyValueError::new_err(format!("The anchor word {:?} isn't in the dictionary", word)))?;
+    if !is_makeable(&anchor, letters, 0) {
+        return Err(PyValueError::new_err(format!("The hand doesn't have the letters of the anchor word {:?}", word)));
+    }
+    Ok(anchor)
+}
+
+/// Finds up to `n` distinct boards for a hand (see `play_bananagrams_multi`)
+/// # Arguments
+/// * `letters` - Number of each letter in the hand
+/// * `dictionary` - The words to solve with
+/// * `dictionary_masks` - The letters in each word of `dictionary`, if already computed
+/// * `n` - Maximum number of boards to find
+/// * `min_word_length` - Minimum length of a word on the board
+/// * `target_tiles` - Optional number of tiles to finish each board with
+/// * `seed` - Optional seed for reproducible shuffling of the `dictionary` between solutions
+/// # Returns
+/// * `Vec<String>` - The distinct boards found in string form (empty if the hand couldn't be solved)
+/// # Raises
+/// * `ValueError` - If `n` or `target_tiles` is 0
+fn solve_distinct(letters: Letters, dictionary: &Vec<Word>, dictionary_masks: Option<&[LetterMask]>, n: usize, min_word_length: usize, target_tiles: Option<usize>, seed: Option<u64>) -> PyResult<Vec<String>> {
+    if n == 0 {
+        return Err(PyValueError::new_err("n must be at least 1"));
+    }
+    check_target_tiles(target_tiles)?;
+    let solutions = play_bananagrams_multi(letters, 0, dictionary, n, &SolveOptions { dictionary_masks, min_word_length, target_tiles, ..SolveOptions::default() }, &mut seeded_rng(seed)).unwrap_or_default();
+    Ok(solutions.into_iter().map(|solution| board_to_string(&solution.board)).collect())
+}
+
+/// A solved hand, as returned to Python by `solve_hand_record`
+#[derive(Serialize)]
+struct SolveRecord<'a> {
+    /// The solved board
+    board: &'a Board,
+    /// The words played to make the board, in order
+    plays: &'a [PlayedWord],
+    /// Number of words the solver checked to find the board
+    words_checked: usize,
+    /// Number of words played to make the board
+    depth: usize,
+    /// Number of first words the solver gave up on before finding the board
+    failed_first_words: usize
+}
+
+/// Solves a hand of letters using the given dictionary like `solve_hand`, but also gives how the board was made
+/// # Arguments
+/// * `letters` - Either a string of letters (like `"AAEINRST"`) or a dict mapping each letter to its count
+/// * `dictionary` - List of valid words
+/// * `min_word_length` - Minimum length of a word; shorter words are removed from the `dictionary`, and crossings shorter than this are invalid
+/// * `target_tiles` - Optional number of tiles to finish the board with (see `solve_hand`)
+/// * `first_word` - How to order the words tried first (see `solve_hand`)
+/// * `check_pool` - Whether to raise a `ValueError` if the hand has more of a letter than the standard 144 tiles (so it could never come up
+///   in a real game)
+/// # Returns
+/// * `dict | None` - The solved board as a dict of the board's `size`, the `row` and `col` of the top-left corner of its letters, and its
+///   letters as a list of `rows` (like the lines of `solve_hand`'s string, but all the same length), along with the `plays` made (each a
+///   dict of the `word`, the `row` and `col` of its first letter, and its `direction`, `"Horizontal"` or `"Vertical"`), the number of
+///   `words_checked`, the `depth` (number of words played), and the number of `failed_first_words` the solver gave up on before finding it;
+///   or `None` if no solution was found within the word-check budget
+/// # Raises
+/// * `ValueError` - If any of the letters isn't A-Z, a word in the `dictionary` is invalid, the `dictionary` contains no words of at least
+///   `min_word_length` letters, `target_tiles` is 0, `first_word` isn't a strategy, or the hand can't be drawn from the standard tiles (with
+///   `check_pool`)
+#[pyfunction]
+#[pyo3(signature = (letters, dictionary, min_word_length=DEFAULT_MIN_WORD_LENGTH, target_tiles=None, first_word="longest", check_pool=true))]
+fn solve_hand_record(py: Python, letters: &PyAny, dictionary: Vec<String>, min_word_length: usize, target_tiles: Option<usize>, first_word: &str, check_pool: bool) -> PyResult<Option<PyObject>> {
+    let letters = hand_from_py(letters, check_pool.then(TileSet::standard).as_ref())?;
+    check_target_tiles(target_tiles)?;
+    let first_word = first_word_from_py(first_word, thread_rng().gen())?;
+    let (dictionary, _) = solver_dictionary(&dictionary, min_word_length)?;
+    if dictionary.is_empty() {
+        return Err(no_words_error(min_word_length));
+    }
+    match play_bananagrams(letters, 0, &dictionary, &SolveOptions { min_word_length, target_tiles, first_word, ..SolveOptions::default() }) {
+        Ok(solution) => Ok(Some(pythonize::pythonize(py, &SolveRecord {
+            board: &solution.board,
+            plays: &solution.plays,
+            words_checked: solution.metrics.words_checked,
+            depth: solution.metrics.depth,
+            failed_first_words: solution.metrics.failed_first_words
+        })?)),
+        Err(_) => Ok(None)
+    }
+}
+
+/// Solves a hand of letters several different ways using the given dictionary
+/// # Arguments
+/// * `letters` - Either a string of letters (like `"AAEINRST"`) or a dict mapping each letter to its count
+/// * `dictionary` - List of valid words
+/// * `n` - Maximum number of distinct boards to return (rotations and reflections of a board aren't distinct)
+/// * `seed` - Optional seed for reproducible solving (the dictionary is shuffled after each board found)
+/// * `min_word_length` - Minimum length of a word; shorter words are removed from the `dictionary`, and crossings shorter than this are invalid
+/// * `target_tiles` - Optional number of tiles to finish each board with (see `solve_hand`)
+/// * `check_pool` - Whether to raise a `ValueError` if the hand has more of a letter than the standard 144 tiles (so it could never come up
+///   in a real game)
+/// # Returns
+/// * `list[str]` - The solved boards in string form; fewer than `n` (or none) if no more were found within the word-check budget
+/// # Raises
+/// * `ValueError` - If any of the letters isn't A-Z, a word in the `dictionary` is invalid, the `dictionary` contains no words of at least
+///   `min_word_length` letters, `n` or `target_tiles` is 0, or the hand can't be drawn from the standard tiles (with `check_pool`)
+#[pyfunction]
+#[pyo3(signature = (letters, dictionary, n, seed=None, min_word_length=DEFAULT_MIN_WORD_LENGTH, target_tiles=None, check_pool=true))]
+fn solve_hand_multi(letters: &PyAny, dictionary: Vec<String>, n: usize, seed: Option<u64>, min_word_length: usize, target_tiles: Option<usize>, check_pool: bool) -> PyResult<Vec<String>> {
+    let letters = hand_from_py(letters, check_pool.then(TileSet::standard).as_ref())?;
+    let (dictionary, _) = solver_dictionary(&dictionary, min_word_length)?;
+    if dictionary.is_empty() {
+        return Err(no_words_error(min_word_length));
     }
-    return true;
-}
-
-/// Checks that a `board` is valid after a word is played vertically, given the specified list of `valid_word`s
-/// Note that this does not check if all words are contiguous; this condition must be enforced elsewhere.
-/// # Arguments
-/// * `board` - `Board` being checked
-/// * `min_col` - Minimum x (column) index of the subsection of the `board` to be checked
-/// * `max_col` - Maximum x (column) index of the subsection of the `board` to be checked
-/// * `min_row` - Minimum y (row) index of the subsection of the `board` to be checked
-/// * `max_row` - Maximum y (row) index of the subsection of the `board` to be checked
-/// * `start_row` - Starting row of the word played
-/// * `end_row` - Ending row of the word played
-/// * `col` - Column of the word played
-/// * `valid_words` - HashSet of all valid words as `Vec<usize>`s
-/// # Returns
-/// `bool` - whether the given `board` is made only of valid words
-fn is_board_valid_vertical(board: &Board, min_col: usize, max_col: usize, min_row: usize, max_row: usize, start_row: usize, end_row: usize, col: usize, valid_words: &HashSet<Vec<usize>>) -> bool {
-    let mut current_letters: Vec<usize> = Vec::with_capacity(MAX_WORD_LENGTH);
-    // Check down the column where the word was played
-    for row_idx in min_row..max_row+1 {
-        // If it's not an empty value, add it to the current word
-        if board[(row_idx, col)] != 0 {
-            current_letters.push(board[(row_idx, col)]);
+    solve_distinct(letters, &dictionary, None, n, min_word_length, target_tiles, seed)
+}
+
+/// Converts a tile set from Python into the pool hands are drawn from
+/// # Arguments
+/// * `tiles` - Optional dict mapping each letter to its number of tiles (the standard 144 tiles are used if not given)
+/// # Returns
+/// * `TileSet` - The tile set
+/// # Raises
+/// * `ValueError` - If a letter isn't A-Z, a count isn't positive, or there are more than 255 of a letter
+fn tiles_from_py(tiles: Option<HashMap<String, usize>>) -> PyResult<TileSet> {
+    let tiles = match tiles {
+        Some(tiles) => tiles,
+        None => return Ok(TileSet::standard())
+    };
+    let mut counts = [0usize; 26];
+    for (letter, count) in tiles.iter() {
+        let mut chars = letter.chars();
+        let c = match (chars.next(), chars.next()) {
+            (Some(c), None) if c.is_ascii_alphabetic() => c.to_ascii_uppercase(),
+            _ => return Err(PyValueError::new_err(format!("Invalid letter {:?} in tile set (letters must be A-Z)", letter)))
+        };
+        if *count == 0 {
+            return Err(PyValueError::new_err(format!("Invalid count for the letter {:?} in tile set (counts must be positive)", letter)));
         }
-        else {
-            // Otherwise, check if we have more than one letter - if so, check if the word is valid
-            if current_letters.len() > 1 && !valid_words.contains(&current_letters) {
-                return false;
-            }
-            current_letters.clear();
-            // If we're past the end of the played word, no need to check farther
-            if row_idx > end_row {
-                break;
-            }
+        counts[c as usize - 65] += count;
+        if counts[c as usize - 65] > u8::MAX as usize {
+            return Err(PyValueError::new_err(format!("Too many tiles of the letter {:?} in tile set (at most {} are allowed)", c, u8::MAX)));
         }
     }
-    // In case we don't hit the `else` in the previous loop
-    if current_letters.len() > 1 {
-        if !valid_words.contains(&current_letters) {
-            return false;
-        }
+    TileSet::from_counts(&counts).map_err(PyValueError::new_err)
+}
+
+/// Converts hand-size options from Python into a distribution of hand sizes that can be drawn from a tile set
+/// # Arguments
+/// * `tiles` - The tile set
+/// * `hand_sizes` - Name of the distribution (see `HandSizeDistribution::name`)
+/// * `min_hand_size` - Minimum number of letters in each hand, for `"log_scaled"` and `"uniform"`
+/// * `max_hand_size` - Maximum number of letters in each hand, for `"log_scaled"` and `"uniform"`
+/// * `hand_size` - Number of letters in every hand, which must be given for (and only for) `"fixed"`
+/// * `players` - Number of players whose starting hands to draw, which must be given for (and only for) `"official"`
+/// # Returns
+/// * `HandSizeDistribution` - The distribution
+/// # Raises
+/// * `ValueError` - If `hand_sizes` isn't a distribution, `hand_size` or `players` is given or missing when it shouldn't be, `players`
+///   isn't between 2 and 8, or the sizes don't satisfy 1 <= smallest <= largest <= the number of tiles
+fn hand_sizes_from_py(tiles: &TileSet, hand_sizes: &str, min_hand_size: usize, max_hand_size: usize, hand_size: Option<usize>, players: Option<usize>) -> PyResult<HandSizeDistribution> {
+    if hand_size.is_some() != (hand_sizes == "fixed") || players.is_some() != (hand_sizes == "official") {
+        return Err(PyValueError::new_err("hand_size must be given with (and only with) hand_sizes=\"fixed\", and players with (and only with) hand_sizes=\"official\""));
     }
-    // Check across each row where a letter was played
-    for row_idx in start_row..end_row+1 {
-        current_letters.clear();
-        for col_idx in min_col..max_col+1 {
-            if board[(row_idx, col_idx)] != 0 {
-                current_letters.push(board[(row_idx, col_idx)]);
-            }
-            else {
-                if current_letters.len() > 1 && !valid_words.contains(&current_letters) {
-                    return false;
-                }
-                current_letters.clear();
-                if col_idx > col {
-                    break;
+    let distribution = match (hand_sizes, hand_size, players) {
+        ("log_scaled", _, _) => HandSizeDistribution::LogScaled(min_hand_size, max_hand_size),
+        ("uniform", _, _) => HandSizeDistribution::Uniform(min_hand_size, max_hand_size),
+        ("fixed", Some(size), _) => HandSizeDistribution::Fixed(size),
+        ("official", _, Some(players)) if (2..=8).contains(&players) => HandSizeDistribution::Official(players),
+        ("official", _, _) => return Err(PyValueError::new_err("players must be between 2 and 8")),
+        _ => return Err(PyValueError::new_err(format!("Invalid hand_sizes {:?} (must be one of {})", hand_sizes, HandSizeDistribution::NAMES.join(", "))))
+    };
+    if distribution.minimum() == 0 || distribution.minimum() > distribution.maximum() || distribution.maximum() > tiles.len() {
+        return Err(PyValueError::new_err(format!("Hand sizes must satisfy 1 <= min_hand_size (or hand_size) <= max_hand_size <= {} (the number of tiles)", tiles.len())));
+    }
+    Ok(distribution)
+}
+
+/// Checks that every hand drawn with a distribution of hand sizes can have a minimum number of vowels
+/// # Arguments
+/// * `hand_sizes` - The distribution of hand sizes
+/// * `min_vowels` - Minimum number of vowels in each hand
+/// # Raises
+/// * `ValueError` - If `min_vowels` is more than the smallest hand size
+fn check_min_vowels(hand_sizes: &HandSizeDistribution, min_vowels: usize) -> PyResult<()> {
+    if min_vowels > hand_sizes.minimum() {
+        return Err(PyValueError::new_err(format!("min_vowels must be at most {} (the smallest hand size)", hand_sizes.minimum())));
+    }
+    Ok(())
+}
+
+/// Generates random hands and solves them across several threads
+/// # Arguments
+/// * `dictionary` - List of valid words
+/// * `n` - Number of solved hands to return
+/// * `min_hand_size` - Minimum number of letters in each hand (unused if `hand_sizes` is `"fixed"` or `"official"`)
+/// * `max_hand_size` - Maximum number of letters in each hand (unused if `hand_sizes` is `"fixed"` or `"official"`)
+/// * `threads` - Number of threads to use (defaults to the available parallelism)
+/// * `seed` - Optional seed for reproducible generation (each thread uses the seed plus its index)
+/// * `tiles` - Optional dict mapping each letter to its number of tiles to draw hands from (the standard 144 tiles are used if not given)
+/// * `min_word_length` - Minimum length of a word; shorter words are removed from the `dictionary`, and crossings shorter than this are invalid
+/// * `target_tiles` - Optional number of tiles to finish every board with, leaving the rest of each hand unplayed
+/// * `first_word` - How to order the words tried first (see `solve_hand`)
+/// * `hand_sizes` - How to choose the size of each hand: `"log_scaled"` (from `min_hand_size` to `max_hand_size`, with smaller sizes more
+///   likely), `"uniform"` (from `min_hand_size` to `max_hand_size`, each equally likely), `"fixed"` (always `hand_size`), or `"official"`
+///   (the size each of `players` players starts a game with: 21 for 2-4 players, 15 for 5-6, or 11 for 7-8)
+/// * `hand_size` - Number of letters in every hand, for `hand_sizes="fixed"`
+/// * `players` - Number of players, for `hand_sizes="official"`
+/// * `min_vowels` - Minimum number of vowels (A, E, I, O, and U) in each hand; hands drawn with fewer have random consonants swapped for
+///   vowels from the tiles not drawn (keeping their size), so that fewer are unsolvable
+/// # Returns
+/// * `list[tuple[str, str]]` - Tuples of (the hand as a string like `"AAEINRST"`, the solved board in string form)
+/// # Raises
+/// * `ValueError` - If a word in the `dictionary` is invalid, the `dictionary` contains no words of at least `min_word_length` letters, the
+///   tile set is invalid, the hand sizes are invalid, `target_tiles` isn't between 1 and the smallest hand size, `min_vowels` is more than
+///   the smallest hand size, or `first_word` isn't a strategy
+/// * `KeyboardInterrupt` - If interrupted; the boards being solved at the time are finished first
+#[pyfunction]
+#[pyo3(signature = (dictionary, n, min_hand_size, max_hand_size, threads=None, seed=None, tiles=None, min_word_length=DEFAULT_MIN_WORD_LENGTH, target_tiles=None, first_word="longest", hand_sizes="log_scaled", hand_size=None, players=None, min_vowels=0))]
+#[allow(clippy::too_many_arguments, reason = "mirrors the keyword arguments of the Python function")]
+fn generate_training_batch(py: Python, dictionary: Vec<String>, n: usize, min_hand_size: usize, max_hand_size: usize, threads: Option<usize>, seed: Option<u64>, tiles: Option<HashMap<String, usize>>, min_word_length: usize, target_tiles: Option<usize>, first_word: &str, hand_sizes: &str, hand_size: Option<usize>, players: Option<usize>, min_vowels: usize) -> PyResult<Vec<(String, String)>> {
+    let (dictionary, _) = solver_dictionary(&dictionary, min_word_length)?;
+    if dictionary.is_empty() {
+        return Err(no_words_error(min_word_length));
+    }
+    let tiles = tiles_from_py(tiles)?;
+    let hand_sizes = hand_sizes_from_py(&tiles, hand_sizes, min_hand_size, max_hand_size, hand_size, players)?;
+    check_target_tiles(target_tiles)?;
+    // Every board should have exactly the target number of tiles, so every hand must have at least that many
+    if target_tiles.is_some_and(|target_tiles| target_tiles > hand_sizes.minimum()) {
+        return Err(PyValueError::new_err("target_tiles must be at most the smallest hand size"));
+    }
+    check_min_vowels(&hand_sizes, min_vowels)?;
+    first_word_from_py(first_word, 0)?;
+    let dictionary_masks = letter_masks(&dictionary);
+    let threads = match threads {
+        Some(0) => return Err(PyValueError::new_err("threads must be at least 1")),
+        Some(threads) => threads,
+        None => thread::available_parallelism().map(|p| p.get()).unwrap_or(1)
+    };
+    let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build().map_err(|e| PyValueError::new_err(e.to_string()))?;
+    let master_seed = seed.unwrap_or_else(|| thread_rng().gen());
+    let stop = AtomicBool::new(false);
+    let mut interrupted = None;
+    let batches = thread::scope(|s| {
+        let workers = s.spawn(|| {
+            // Each thread solves its own share of the batch, so a seeded batch doesn't depend on how the threads are scheduled
+            pool.install(|| (0..threads).into_par_iter().map(|thread_num| {
+                let mut rng = seeded_rng(Some(master_seed.wrapping_add(thread_num as u64)));
+                let quota = n/threads + usize::from(thread_num < n % threads);
+                let mut batch: Vec<(String, String)> = Vec::with_capacity(quota);
+                while batch.len() < quota && !stop.load(Ordering::Relaxed) {
+                    let (letters, _) = generate_hand(&mut rng, &tiles, hand_sizes, min_vowels);
+                    let first_word = FirstWordStrategy::from_name(first_word, rng.gen()).expect("First-word strategy already checked!");
+                    if let Ok(solution) = play_bananagrams(letters, 0, &dictionary, &SolveOptions { dictionary_masks: Some(&dictionary_masks), stop: Some(&stop), min_word_length, target_tiles, first_word, ..SolveOptions::default() }) {
+                        batch.push((letters_to_string(&letters), board_to_string(&solution.board)));
+                    }
                 }
+                batch
+            }).collect::<Vec<_>>())
+        });
+        // Wait for the workers without holding the GIL, periodically checking for Ctrl-C
+        while !workers.is_finished() {
+            py.allow_threads(|| thread::sleep(Duration::from_millis(50)));
+            if let Err(e) = py.check_signals() {
+                stop.store(true, Ordering::Relaxed);
+                interrupted = Some(e);
             }
         }
-        if current_letters.len() > 1 && !valid_words.contains(&current_letters) {
-            return false;
-        }
+        workers.join().unwrap()
+    });
+    match interrupted {
+        Some(e) => Err(e),
+        None => Ok(batches.into_iter().flatten().collect())
+    }
+}
+
+/// Generates a random hand of letters the way `training_data` does, so that hands sampled in Python follow the same distribution as the
+/// hands of a dataset generated with the same options
+/// # Arguments
+/// * `size` - Optional number of letters in the hand, for `distribution="fixed"` (which is the default when it's given)
+/// * `distribution` - How to choose the size of the hand (see `generate_training_batch`); defaults to `"fixed"` if `size` is given, and
+///   otherwise to `"log_scaled"`
+/// * `tiles` - Optional dict mapping each letter to its number of tiles to draw the hand from (the standard 144 tiles are used if not given)
+/// * `seed` - Optional seed for a reproducible hand
+/// * `min_hand_size` - Minimum number of letters in the hand, for `"log_scaled"` and `"uniform"`
+/// * `max_hand_size` - Maximum number of letters in the hand, for `"log_scaled"` and `"uniform"` (defaults to the largest hand the tile
+///   set allows)
+/// * `players` - Number of players, for `distribution="official"`
+/// * `min_vowels` - Minimum number of vowels in the hand (see `generate_training_batch`)
+/// * `as_array` - Whether to return the number of each letter from A to Z as a list rather than as a dict
+/// # Returns
+/// * `dict[str, int] | list[int]` - The number of each letter in the hand (letters not in it are left out of the dict)
+/// # Raises
+/// * `ValueError` - If the tile set is invalid, the hand-size options are invalid (see `generate_training_batch`), or `min_vowels` is more
+///   than the smallest hand size
+#[pyfunction]
+#[pyo3(name = "generate_hand", signature = (size=None, distribution=None, tiles=None, seed=None, min_hand_size=MINIMUM_HAND_SIZE as usize, max_hand_size=None, players=None, min_vowels=0, as_array=false))]
+#[allow(clippy::too_many_arguments, reason = "mirrors the keyword arguments of the Python function")]
+fn py_generate_hand(py: Python, size: Option<usize>, distribution: Option<&str>, tiles: Option<HashMap<String, usize>>, seed: Option<u64>, min_hand_size: usize, max_hand_size: Option<usize>, players: Option<usize>, min_vowels: usize, as_array: bool) -> PyResult<PyObject> {
+    let tiles = tiles_from_py(tiles)?;
+    let distribution = distribution.unwrap_or(if size.is_some() { "fixed" } else { "log_scaled" });
+    // Checked here too so that the error names this function's arguments
+    if size.is_some() != (distribution == "fixed") || players.is_some() != (distribution == "official") {
+        return Err(PyValueError::new_err("size must be given with (and only with) distribution=\"fixed\", and players with (and only with) distribution=\"official\""));
+    }
+    let max_hand_size = max_hand_size.unwrap_or(tiles.maximum_hand_size());
+    let hand_sizes = hand_sizes_from_py(&tiles, distribution, min_hand_size, max_hand_size, size, players)?;
+    check_min_vowels(&hand_sizes, min_vowels)?;
+    let (letters, _): (Letters, usize) = generate_hand(&mut seeded_rng(seed), &tiles, hand_sizes, min_vowels);
+    Ok(hand_into_py(py, &letters, as_array))
+}
+
+/// Converts the number of each letter in a hand to Python
+/// # Arguments
+/// * `letters` - Number of each letter in the hand
+/// * `as_array` - Whether to convert it to a list of the number of each letter from A to Z rather than a dict
+/// # Returns
+/// * `PyObject` - A dict mapping each letter in the hand to its count (leaving out letters not in it), or a list of 26 counts
+fn hand_into_py(py: Python, letters: &Letters, as_array: bool) -> PyObject {
+    if as_array {
+        return letters.to_vec().into_py(py);
     }
-    return true;
+    let counts: HashMap<String, u8> = letters.iter().enumerate().filter(|(_, &count)| count > 0).map(|(letter, &count)| (((letter as u8 + 65) as char).to_string(), count)).collect();
+    counts.into_py(py)
 }
 
-/// Plays a word on the `board` (modifying it in-place)
+/// Converts a hand of letters to a string of its letters in alphabetical order
 /// # Arguments
-/// * `board` - Array2D board to change in-place
-/// * `word` - Word to play represented as a vector of numbers
-/// * `dir` - Direction to play the `word`
-/// * `start_x` - x position of the `word`'s first letter
-/// * `start_y` - y position of the `word`'s first letter
-/// * `played_positions` - HashSet of previously played (x, y) positions; will be modified in-place to add newly played positions
-fn play_word(board: &mut Board, word: &Vec<usize>, dir: Direction, start_x: usize, start_y: usize, played_positions: &mut HashSet<(usize, usize)>) {
-    match dir {
-        Direction::Horizontal => {
-            for i in 0..word.len() {
-                board[(i+start_x, start_y)] = word[i];
-                played_positions.insert((i+start_x, start_y));
+/// * `hand` - A dict mapping each letter to its count, a list of the number of each letter from A to Z (as returned by
+///   `generate_hand(as_array=True)`), or a string of letters
+/// # Returns
+/// * `str` - The hand as a string like `"AAEINRST"`
+/// # Raises
+/// * `ValueError` - If any of the letters isn't A-Z, a list doesn't have 26 counts, or there are more than 255 of a letter
+#[pyfunction]
+fn hand_to_string(hand: &PyAny) -> PyResult<String> {
+    let letters = match hand.extract::<Vec<usize>>() {
+        Ok(counts) => {
+            if counts.len() != 26 {
+                return Err(PyValueError::new_err(format!("A hand given as a list must have 26 counts (one for each letter from A to Z), not {}", counts.len())));
             }
+            let mut letters: Letters = [0u8; 26];
+            for (i, count) in counts.iter().enumerate() {
+                letters[i] = u8::try_from(*count).map_err(|_| PyValueError::new_err(format!("Too many of the letter {:?} in hand (at most 255 are allowed)", (i as u8 + 65) as char)))?;
+            }
+            letters
         },
-        Direction::Vertical => {
-            for i in 0..word.len() {
-                board[(start_x, start_y+i)] = word[i];
-                played_positions.insert((i+start_x, start_y));
+        Err(_) => letters_from_py(hand)?
+    };
+    Ok(letters_to_string(&letters))
+}
+
+/// Converts a string of letters to the number of each letter in it
+/// # Arguments
+/// * `text` - The letters, like `"AAEINRST"` (in either case)
+/// * `as_array` - Whether to return the number of each letter from A to Z as a list rather than as a dict
+/// # Returns
+/// * `dict[str, int] | list[int]` - The number of each letter in the hand (letters not in it are left out of the dict)
+/// # Raises
+/// * `ValueError` - If any of the letters isn't A-Z, or there are more than 255 of a letter
+#[pyfunction]
+#[pyo3(signature = (text, as_array=false))]
+fn string_to_hand(py: Python, text: &str, as_array: bool) -> PyResult<PyObject> {
+    let letters = letters_from_py(PyString::new(py, text))?;
+    Ok(hand_into_py(py, &letters, as_array))
+}
+
+/// Converts a solved board into a grid of letter values, optionally padded to a fixed shape
+/// # Arguments
+/// * `board` - Solved board
+/// * `min_col` - Minimum occupied column index
+/// * `max_col` - Maximum occupied column index
+/// * `min_row` - Minimum occupied row index
+/// * `max_row` - Maximum occupied row index
+/// * `pad_to` - Optional (rows, columns) shape of the grid, with the board centered inside it
+/// # Returns
+/// * `Array2<u8>` - The grid, where 0 is empty and 1 ('A') through 26 ('Z') are letters
+/// # Raises
+/// * `ValueError` - If the board doesn't fit within `pad_to`
+#[cfg(feature = "numpy")]
+fn board_to_grid(board: &Board, min_col: usize, max_col: usize, min_row: usize, max_row: usize, pad_to: Option<(usize, usize)>) -> PyResult<Array2<u8>> {
+    let height = max_row + 1 - min_row;
+    let width = max_col + 1 - min_col;
+    let (rows, cols) = pad_to.unwrap_or((height, width));
+    if height > rows || width > cols {
+        return Err(PyValueError::new_err(format!("The solution is {}x{}, which doesn't fit in {}x{}", height, width, rows, cols)));
+    }
+    let row_offset = (rows - height)/2;
+    let col_offset = (cols - width)/2;
+    let mut grid = Array2::<u8>::zeros((rows, cols));
+    for row in min_row..max_row+1 {
+        for col in min_col..max_col+1 {
+            let val = board.get_val(row, col);
+            if val != EMPTY_VALUE {
+                grid[(row - min_row + row_offset, col - min_col + col_offset)] = val + 1;
             }
         }
     }
+    Ok(grid)
 }
 
-fn generate_board(dictionary: &Vec<Vec<usize>>, target_size: usize) -> Option<Board> {
-    let mut board: Board = Array2D::filled_with(0, BOARD_SIZE, BOARD_SIZE);
-    let mut rng = thread_rng();
-    if let Some(start_word) = dictionary.iter().filter(|w| w.len() <= target_size).choose(&mut rng) {
-        // Play the first word in a random direction in the middle of the board
-        let mut dir: Direction = rand::random();
-        let mid = BOARD_SIZE/2;
-        let mut played_positions = HashSet::new();
-        let (start_x, start_y) = match dir {
-            Direction::Horizontal => (BOARD_SIZE/2, BOARD_SIZE/2 - start_word.len()/2),
-            Direction::Vertical => (BOARD_SIZE/2 - start_word.len()/2, BOARD_SIZE/2)
-        };
-        play_word(&mut board, &start_word, dir, start_x, start_y, &mut played_positions);
-        // If the word chosen was the target length, we're done
-        if start_word.len() == target_size {
-            return Some(board);
-        }
-        // Otherwise, play the second word at a random location in the opposite direction
-        dir = dir.opposite();
-        let second_pos = played_positions.iter().choose(&mut rng).unwrap();
-        let second_pos_letter = board[*second_pos];
-        // Choose a random word that overlaps
-        let word = dictionary.iter().filter(|w| w.contains(&second_pos_letter)).choose(&mut rng).unwrap();
-        // Choose a random position of overlapping
-        let pos = word.iter().enumerate().filter_map(|(idx, c)| if *c == second_pos_letter { Some(idx) } else { None }).choose(&mut rng).unwrap();
-        // Play the word
-        match dir {
-            Direction::Horizontal => play_word(&mut board, &word, dir, second_pos.0-pos, second_pos.1, &mut played_positions),
-            Direction::Vertical => play_word(&mut board, &word, dir, second_pos.0, second_pos.1-pos, &mut played_positions)
+/// Solves a hand of letters using the given dictionary, returning the board as a NumPy array
+/// # Arguments
+/// * `letters` - Either a string of letters (like `"AAEINRST"`) or a dict mapping each letter to its count
+/// * `dictionary` - List of valid words
+/// * `pad_to` - Optional (rows, columns) shape of the returned array, with the board centered inside it
+/// * `min_word_length` - Minimum length of a word; shorter words are removed from the `dictionary`, and crossings shorter than this are invalid
+/// * `target_tiles` - Optional number of tiles to finish the board with (see `solve_hand`)
+/// * `check_pool` - Whether to raise a `ValueError` if the hand has more of a letter than the standard 144 tiles (so it could never come up
+///   in a real game)
+/// # Returns
+/// * `numpy.ndarray | None` - 2-D `uint8` array where 0 is empty and 1 ('A') through 26 ('Z') are letters (cropped to the
+///   solution unless `pad_to` is given), or `None` if no solution was found within the word-check budget
+/// # Raises
+/// * `ValueError` - If any of the letters isn't A-Z, a word in the `dictionary` is invalid, the `dictionary` contains no words of at least
+///   `min_word_length` letters, `target_tiles` is 0, the hand can't be drawn from the standard tiles (with `check_pool`), or the solution
+///   doesn't fit within `pad_to`
+#[cfg(feature = "numpy")]
+#[pyfunction]
+#[pyo3(signature = (letters, dictionary, pad_to=None, min_word_length=DEFAULT_MIN_WORD_LENGTH, target_tiles=None, check_pool=true))]
+fn solve_hand_array<'py>(py: Python<'py>, letters: &PyAny, dictionary: Vec<String>, pad_to: Option<(usize, usize)>, min_word_length: usize, target_tiles: Option<usize>, check_pool: bool) -> PyResult<Option<&'py PyArray2<u8>>> {
+    // Raise an `ImportError` up front rather than panicking when creating the array if NumPy isn't installed
+    py.import("numpy")?;
+    let letters = hand_from_py(letters, check_pool.then(TileSet::standard).as_ref())?;
+    check_target_tiles(target_tiles)?;
+    let (dictionary, _) = solver_dictionary(&dictionary, min_word_length)?;
+    if dictionary.is_empty() {
+        return Err(no_words_error(min_word_length));
+    }
+    match play_bananagrams(letters, 0, &dictionary, &SolveOptions { min_word_length, target_tiles, ..SolveOptions::default() }) {
+        Ok(solution) => Ok(Some(board_to_grid(&solution.board, solution.min_col, solution.max_col, solution.min_row, solution.max_row, pad_to)?.into_pyarray(py))),
+        Err(_) => Ok(None)
+    }
+}
+
+/// Converts a board into nested rows of letter values, cropped to the occupied region
+/// # Arguments
+/// * `board` - Board to convert
+/// * `min_col` - Minimum occupied column index
+/// * `max_col` - Maximum occupied column index
+/// * `min_row` - Minimum occupied row index
+/// * `max_row` - Maximum occupied row index
+/// # Returns
+/// * `Vec<Vec<u8>>` - The rows of the board, where 0 is empty and 1 ('A') through 26 ('Z') are letters
+fn board_to_rows(board: &Board, min_col: usize, max_col: usize, min_row: usize, max_row: usize) -> Vec<Vec<u8>> {
+    (min_row..max_row+1).map(|row| {
+        (min_col..max_col+1).map(|col| {
+            let val = board.get_val(row, col);
+            if val == EMPTY_VALUE { 0 } else { val + 1 }
+        }).collect()
+    }).collect()
+}
+
+/// Loads the boards saved in a `.bgb` file by the `training_data` binary (or a `.bgb2` file), decompressing it first if it is gzip- or
+/// zstd-compressed; the format and compression are detected from the file's first bytes (see `bananagrams_core::read_boards`)
+/// # Arguments
+/// * `path` - Path to the `.bgb` or `.bgb2` file
+/// # Returns
+/// * `list[numpy.ndarray] | list[list[list[int]]]` - Each board cropped to its letters, where 0 is empty and 1 ('A') through 26 ('Z')
+///   are letters; these are 2-D `uint8` arrays if NumPy is available, otherwise nested lists of rows
+/// # Raises
+/// * `OSError` - If the file can't be read
+/// * `ValueError` - If the file is not a valid `.bgb` or `.bgb2` file (giving the byte offset of the problem)
+#[pyfunction]
+fn load_boards(py: Python, path: PathBuf) -> PyResult<Vec<PyObject>> {
+    let bytes = fs::read(path)?;
+    let boards = read_boards(&bytes).map_err(PyValueError::new_err)?;
+    Ok(boards.iter().map(|(_, board, min_col, max_col, min_row, max_row)| board_to_py(py, board, *min_col, *max_col, *min_row, *max_row)).collect())
+}
+
+/// Loads the boards saved in a `.msgpack` file by the `training_data` binary (with `--format msgpack`), decompressing it first if it is
+/// gzip- or zstd-compressed; a record cut off at the end of the file (such as by an interrupted run) is left out
+/// # Arguments
+/// * `path` - Path to the `.msgpack` file
+/// # Returns
+/// * `list[dict]` - Each board's record, with the same keys as a line of a `.jsonl` file (so `board` is a list of rows as strings); `hand`
+///   is a `uint8` NumPy array of the number of each letter if NumPy is available, otherwise a list
+/// # Raises
+/// * `OSError` - If the file can't be read
+/// * `ValueError` - If the file is not a valid `.msgpack` file
+#[pyfunction]
+fn read_msgpack_boards(py: Python, path: PathBuf) -> PyResult<Vec<PyObject>> {
+    let bytes = fs::read(path)?;
+    let (bytes, _) = decompress(&bytes).map_err(PyValueError::new_err)?;
+    let records = msgpack::read_records(&bytes).map_err(PyValueError::new_err)?;
+    #[cfg(feature = "numpy")]
+    let has_numpy = py.import("numpy").is_ok();
+    // The records can't refer to each other, so there's nothing for the cyclic garbage collector to find while they're created, but it
+    // would otherwise keep scanning every record made so far (which takes longer than making them)
+    let gc = py.import("gc")?;
+    let gc_enabled: bool = gc.call_method0("isenabled")?.extract()?;
+    gc.call_method0("disable")?;
+    let mut keys = HashMap::new();
+    let boards = records.iter().map(|record| {
+        let dict = msgpack_to_py(py, record, &mut keys)?;
+        #[cfg(feature = "numpy")]
+        if has_numpy {
+            if let Some(msgpack::Value::Array(hand)) = record.get("hand") {
+                let hand: Option<Vec<u8>> = hand.iter().map(|count| match count {
+                    msgpack::Value::UInt(count) => u8::try_from(*count).ok(),
+                    _ => None
+                }).collect();
+                if let Some(hand) = hand {
+                    dict.as_ref(py).set_item("hand", Array1::from_vec(hand).into_pyarray(py))?;
+                }
+            }
         }
-        // If we've reached the target size, we're done
-        if played_positions.len() >= target_size {
-            return Some(board);
+        Ok(dict)
+    }).collect();
+    if gc_enabled {
+        gc.call_method0("enable")?;
+    }
+    boards
+}
+
+/// Converts a MessagePack value into the equivalent Python object
+/// # Arguments
+/// * `py` - Python GIL token
+/// * `value` - The value
+/// * `keys` - The string map keys converted so far, reused since every record has the same keys (which saves creating and hashing them
+///   again)
+/// # Returns
+/// * `PyObject` - `None`, a `bool`, `int`, `float`, `str`, `bytes`, `list`, or `dict`
+/// # Raises
+/// * `TypeError` - If a map has a key Python can't hash (a list or dict)
+fn msgpack_to_py(py: Python, value: &msgpack::Value, keys: &mut HashMap<String, PyObject>) -> PyResult<PyObject> {
+    Ok(match value {
+        msgpack::Value::Nil => py.None(),
+        msgpack::Value::Bool(value) => value.into_py(py),
+        msgpack::Value::Int(value) => value.into_py(py),
+        msgpack::Value::UInt(value) => value.into_py(py),
+        msgpack::Value::Float(value) => value.into_py(py),
+        msgpack::Value::String(value) => value.into_py(py),
+        msgpack::Value::Binary(value) => PyBytes::new(py, value).into_py(py),
+        msgpack::Value::Array(values) => values.iter().map(|value| msgpack_to_py(py, value, keys)).collect::<PyResult<Vec<_>>>()?.into_py(py),
+        msgpack::Value::Map(entries) => {
+            let dict = PyDict::new(py);
+            for (key, value) in entries.iter() {
+                let key = match key {
+                    msgpack::Value::String(key) => keys.entry(key.clone()).or_insert_with(|| key.into_py(py)).clone_ref(py),
+                    key => msgpack_to_py(py, key, keys)?
+                };
+                dict.set_item(key, msgpack_to_py(py, value, keys)?)?;
+            }
+            dict.into_py(py)
         }
-        // Otherwise, keep trying until we hit the proper size
-        while played_positions.len() < target_size {
-            'outer: loop {
-                dir = rand::random();
-                let play_pos = played_positions.iter().choose(&mut rng).unwrap();
-                let play_letter = board[*play_pos];
-                // Choose a random word that overlaps
-                let word = dictionary.iter().filter(|w| w.contains(&play_letter)).choose(&mut rng).unwrap();
-                // Choose a random position of overlapping 
-                let mut possible_positions: Vec<usize> = word.iter().enumerate().filter_map(|(idx, c)| if *c == play_letter { Some(idx) } else { None }).collect();
-                possible_positions.shuffle(&mut rng);
-                for pos in possible_positions {
-                    let success = match dir {
-                        Direction::Horizontal => play_word(&mut board, &word, dir, second_pos.0-pos, second_pos.1, &mut played_positions),
-                        Direction::Vertical => play_word(&mut board, &word, dir, second_pos.0, second_pos.1-pos, &mut played_positions)
-                    };
-                    if success {
-                        break 'outer;
-                    }
-                }
+    })
+}
+
+/// Converts a board into a Python grid of letter values, cropped to the occupied region
+/// # Arguments
+/// * `py` - Python GIL token
+/// * `board` - Board to convert
+/// * `min_col` - Minimum occupied column index
+/// * `max_col` - Maximum occupied column index
+/// * `min_row` - Minimum occupied row index
+/// * `max_row` - Maximum occupied row index
+/// # Returns
+/// * `PyObject` - A 2-D `uint8` NumPy array if NumPy is available, otherwise nested lists of rows (see `board_to_rows`)
+fn board_to_py(py: Python, board: &Board, min_col: usize, max_col: usize, min_row: usize, max_row: usize) -> PyObject {
+    #[cfg(feature = "numpy")]
+    if py.import("numpy").is_ok() {
+        let rows = board_to_rows(board, min_col, max_col, min_row, max_row);
+        let width = max_col + 1 - min_col;
+        // The rows all have the same length, so this can't fail
+        return Array2::from_shape_vec((rows.len(), width), rows.concat()).unwrap().into_pyarray(py).into_py(py);
+    }
+    board_to_rows(board, min_col, max_col, min_row, max_row).into_py(py)
+}
+
+/// Converts a grid of letter values from Python into a board
+/// # Arguments
+/// * `grid` - 2-D array (or nested list of rows) where 0 is empty and 1 ('A') through 26 ('Z') are letters
+/// # Returns
+/// * `(Board, usize, usize)` - Tuple of (the smallest square board holding the `grid` with its top-left corner at (0, 0), number of rows in the `grid`,
+///   number of columns in the `grid`)
+/// # Raises
+/// * `ValueError` - If the rows aren't all the same length, or any value is more than 26
+fn board_from_py(grid: &PyAny) -> PyResult<(Board, usize, usize)> {
+    let rows: Vec<Vec<u8>> = grid.extract()?;
+    let width = rows.first().map_or(0, |row| row.len());
+    if rows.iter().any(|row| row.len() != width) {
+        return Err(PyValueError::new_err("Every row of the board must be the same length"));
+    }
+    let mut board = Board::new(rows.len().max(width));
+    for (row_idx, row) in rows.iter().enumerate() {
+        for (col_idx, val) in row.iter().enumerate() {
+            match *val {
+                0 => {},
+                1..=26 => board.set_val(row_idx, col_idx, val - 1),
+                _ => return Err(PyValueError::new_err(format!("Invalid value {} at row {}, column {} (must be 0 for empty or 1-26 for a letter)", val, row_idx, col_idx)))
             }
-            let played_vec: Vec<&(usize, usize)> = played_positions.iter().collect();
-            let second_pos = played_positions.iter().choose(&mut rng).unwrap();
-            let second_pos_letter = board[*second_pos];
-            // Choose a random word that overlaps
-            let word = dictionary.iter().filter(|w| w.contains(&second_pos_letter)).choose(&mut rng).unwrap();
         }
-        Some(board)
     }
-    else {
-        None
+    Ok((board, rows.len(), width))
+}
+
+/// Checks whether a board is a legal Bananagrams board, i.e. all of its letters are connected and every run of two or more letters is in the dictionary
+/// # Arguments
+/// * `board` - 2-D array (or nested list of rows) where 0 is empty and 1 ('A') through 26 ('Z') are letters
+/// * `dictionary` - List of valid words
+/// * `min_word_length` - Minimum length of a word, below which runs of letters are invalid even if in the `dictionary`
+/// # Returns
+/// * `list[str]` - A description of each problem with the board (like `"Invalid word QZ Horizontal at row 2, column 0"`); empty if it is legal
+/// # Raises
+/// * `ValueError` - If the `board` isn't a rectangular grid of values from 0 to 26, or a word in the `dictionary` is invalid
+#[pyfunction]
+#[pyo3(name = "validate_board", signature = (board, dictionary, min_word_length=DEFAULT_MIN_WORD_LENGTH))]
+fn py_validate_board(board: &PyAny, dictionary: Vec<String>, min_word_length: usize) -> PyResult<Vec<String>> {
+    let valid_words: HashSet<Word> = solver_dictionary(&dictionary, min_word_length)?.0.into_iter().collect();
+    board_errors(board, &valid_words, min_word_length)
+}
+
+/// Checks whether a board from Python is legal
+/// # Arguments
+/// * `board` - 2-D array (or nested list of rows) where 0 is empty and 1 ('A') through 26 ('Z') are letters
+/// * `valid_words` - HashSet of all valid words
+/// * `min_word_length` - Minimum length of a word
+/// # Returns
+/// * `PyResult<Vec<String>>` - A description of each problem with the board; empty if it is legal
+fn board_errors(board: &PyAny, valid_words: &HashSet<Word>, min_word_length: usize) -> PyResult<Vec<String>> {
+    let (board, _, _) = board_from_py(board)?;
+    match validate_board(&board, valid_words, min_word_length) {
+        Ok(()) => Ok(Vec::new()),
+        Err(errors) => Ok(errors.iter().map(|e| e.to_string()).collect())
     }
 }
 
-fn board_to_string(board: &Array2D<char>) -> String {
-    let mut s = "".to_string();
-    for i in 0..board.num_rows() {
-        for j in 0..board.num_columns() {
-            if board[(i, j)] == '.' {
-                s += " ";
+/// Converts a grid of letter codes from Python into a board, working out whether the letters are numbered 1 ('A') through 26 ('Z') or
+/// are ASCII codes (65 ('A') through 90 ('Z'), or 97 ('a') through 122 ('z'))
+/// # Arguments
+/// * `grid` - 2-D array (or nested list of rows) of integers where 0 is empty, or a `Board`
+/// # Returns
+/// * `Board` - The smallest square board holding the `grid` with its top-left corner at (0, 0)
+/// # Raises
+/// * `ValueError` - If the rows aren't all the same length, or the letters aren't all in one of the encodings
+fn board_from_codes(grid: &PyAny) -> PyResult<Board> {
+    if let Ok(board) = grid.extract::<PyRef<PyBoard>>() {
+        return Ok(board.board.clone());
+    }
+    let rows: Vec<Vec<i64>> = grid.extract()?;
+    let width = rows.first().map_or(0, |row| row.len());
+    if rows.iter().any(|row| row.len() != width) {
+        return Err(PyValueError::new_err("Every row of the board must be the same length"));
+    }
+    let codes = || rows.iter().flatten().filter(|&&code| code != 0);
+    let offset = if codes().all(|code| (1..=26).contains(code)) {
+        1
+    }
+    else if codes().all(|code| (65..=90).contains(code) || (97..=122).contains(code)) {
+        65
+    }
+    else {
+        // Report the first value that fits neither encoding, or else the first that doesn't fit the one most values are in
+        let (letters, ascii) = (codes().filter(|code| (1..=26).contains(*code)).count(), codes().filter(|code| (65..=90).contains(*code) || (97..=122).contains(*code)).count());
+        let (row_idx, col_idx, code) = rows.iter().enumerate().flat_map(|(row_idx, row)| row.iter().enumerate().map(move |(col_idx, &code)| (row_idx, col_idx, code)))
+            .find(|&(_, _, code)| code != 0 && if letters >= ascii { !(1..=26).contains(&code) } else { !((65..=90).contains(&code) || (97..=122).contains(&code)) })
+            .expect("Some value fits neither encoding!");
+        return Err(PyValueError::new_err(format!("Invalid value {} at row {}, column {} (letters must all be 1-26, or all ASCII codes for A-Z or a-z)", code, row_idx, col_idx)));
+    };
+    let mut board = Board::new(rows.len().max(width));
+    for (row_idx, row) in rows.iter().enumerate() {
+        for (col_idx, &code) in row.iter().enumerate() {
+            if code != 0 {
+                // Lowercase ASCII letters are the same letters as uppercase ones
+                board.set_val(row_idx, col_idx, ((code - offset) % 32) as u8);
             }
-            else {
-                s += &board[(i, j)].to_string();
+        }
+    }
+    Ok(board)
+}
+
+/// Scores how close a board is to being legal, such as one made by a generator being trained
+/// # Arguments
+/// * `py` - Python GIL token
+/// * `board` - The board to score
+/// * `valid_words` - HashSet of all valid words
+/// * `min_word_length` - Minimum length of a word, below which runs of letters are invalid
+/// # Returns
+/// * `PyResult<PyObject>` - The score as described for `score_board`
+fn board_score(py: Python, board: &Board, valid_words: &HashSet<Word>, min_word_length: usize) -> PyResult<PyObject> {
+    let (min_col, max_col, min_row, max_row) = board.bounds().unwrap_or((1, 0, 1, 0));
+    let tiles = (min_row..max_row+1).flat_map(|row| (min_col..max_col+1).map(move |col| (row, col))).filter(|&(row, col)| board.get_val(row, col) != EMPTY_VALUE).count();
+    let words = extract_words(board, min_col, max_col, min_row, max_row).len();
+    let (mut components, mut invalid_words) = (usize::from(tiles > 0), Vec::new());
+    if let Err(errors) = validate_board(board, valid_words, min_word_length) {
+        for error in errors {
+            match error {
+                BoardError::Empty => components = 0,
+                BoardError::Disconnected { components: count } => components = count,
+                BoardError::InvalidWord(played) => invalid_words.push((convert_array_to_word(&played.word), played.row, played.col, played.direction.to_string()))
             }
         }
-        s += "\n";
     }
-    s
+    let valid_count = words - invalid_words.len();
+    // A board with no words (such as a lone tile) has nothing right about it
+    let score = if words == 0 { 0.0 } else { valid_count as f64 / words as f64 / components as f64 };
+    let result = PyDict::new(py);
+    result.set_item("tiles", tiles)?;
+    result.set_item("valid_words", valid_count)?;
+    result.set_item("invalid_words", invalid_words)?;
+    result.set_item("components", components)?;
+    result.set_item("score", score)?;
+    Ok(result.into())
 }
 
-/// Formats the sum of two numbers as string.
+/// Scores how close a board is to being a legal Bananagrams board, as feedback for a generator or discriminator being trained
+/// # Arguments
+/// * `board` - 2-D array (or nested list of rows) of integers where 0 is empty and the letters are either 1 ('A') through 26 ('Z') or ASCII
+///   codes (either case), whichever the values fit; or a `Board`
+/// * `dictionary` - List of valid words
+/// * `min_word_length` - Minimum length of a word, below which runs of letters are invalid even if in the `dictionary`
+/// # Returns
+/// * `dict` - The number of `tiles`, the number of `valid_words`, the `invalid_words` (each a tuple of the word, the row and column of its
+///   first letter, and its direction, `"Horizontal"` or `"Vertical"`), the number of connected `components` of letters, and a `score` from
+///   0 to 1 (the fraction of words that are valid, divided by the number of `components`; 1 only for a legal board, and 0 for a board with
+///   no words)
+/// # Raises
+/// * `ValueError` - If the `board` isn't a rectangular grid, its letters don't all fit one encoding, or a word in the `dictionary` is invalid
 #[pyfunction]
-fn sum_as_string(a: usize, b: usize) -> PyResult<String> {
-    let dictionary = vec![
-        "apple".to_string(),
-        "banana".to_string(),
-        "orange".to_string(),
-        "grape".to_string(),
-        "peach".to_string(),
-        // Add more words as needed
-    ];
-
-    let target_size = 21;
-    let board = generate_board(&dictionary, target_size);
-    println!("{}", board_to_string(&board));
-    Ok((a + b).to_string())
+#[pyo3(signature = (board, dictionary, min_word_length=DEFAULT_MIN_WORD_LENGTH))]
+fn score_board(py: Python, board: &PyAny, dictionary: Vec<String>, min_word_length: usize) -> PyResult<PyObject> {
+    let board = board_from_codes(board)?;
+    let valid_words: HashSet<Word> = solver_dictionary(&dictionary, min_word_length)?.0.into_iter().collect();
+    board_score(py, &board, &valid_words, min_word_length)
+}
+
+/// Converts an edit made by `repair_board` into a Python dict
+/// # Arguments
+/// * `py` - Python GIL token
+/// * `edit` - The edit
+/// # Returns
+/// * `PyResult<PyObject>` - A dict of the kind of `edit` (`"ReplaceLetter"`, `"DeleteTile"`, or `"ShiftWord"`) and its fields (see
+///   `repair_board`)
+fn edit_to_py(py: Python, edit: Edit) -> PyResult<PyObject> {
+    let dict = PyDict::new(py);
+    match edit {
+        Edit::ReplaceLetter { row, col, letter } => {
+            dict.set_item("edit", "ReplaceLetter")?;
+            dict.set_item("row", row)?;
+            dict.set_item("col", col)?;
+            dict.set_item("letter", ((letter + 65) as char).to_string())?;
+        },
+        Edit::DeleteTile { row, col } => {
+            dict.set_item("edit", "DeleteTile")?;
+            dict.set_item("row", row)?;
+            dict.set_item("col", col)?;
+        },
+        Edit::ShiftWord { row, col, length, direction, row_shift, col_shift } => {
+            dict.set_item("edit", "ShiftWord")?;
+            dict.set_item("row", row)?;
+            dict.set_item("col", col)?;
+            dict.set_item("length", length)?;
+            dict.set_item("direction", direction.to_string())?;
+            dict.set_item("row_shift", row_shift)?;
+            dict.set_item("col_shift", col_shift)?;
+        }
+    }
+    Ok(dict.into())
+}
+
+/// Repairs a board from Python, converting the result back
+/// # Arguments
+/// * `py` - Python GIL token
+/// * `board` - The board to repair (see `board_from_codes`)
+/// * `valid_words` - HashSet of all valid words
+/// * `min_word_length` - Minimum length of a word
+/// * `max_edits` - Maximum number of edits to make
+/// # Returns
+/// * `PyResult<Option<(PyObject, Vec<PyObject>)>>` - The repaired board and edits as described for `repair_board`, or `None`
+fn board_repair(py: Python, board: &PyAny, valid_words: &HashSet<Word>, min_word_length: usize, max_edits: usize) -> PyResult<Option<(PyObject, Vec<PyObject>)>> {
+    let board = board_from_codes(board)?;
+    // Large `max_edits` can take a long time, so other Python threads are let run meanwhile
+    match py.allow_threads(|| repair_board(&board, valid_words, min_word_length, max_edits)) {
+        Some((board, edits)) => Ok(Some((PyBoard { board }.into_py(py), edits.into_iter().map(|edit| edit_to_py(py, edit)).collect::<PyResult<_>>()?))),
+        None => Ok(None)
+    }
+}
+
+/// Tries to make a nearly legal board legal with as few small edits as possible: replacing a tile's letter (with one that makes an invalid
+/// word valid), deleting a tile, or moving a word one cell
+/// # Arguments
+/// * `board` - 2-D array (or nested list of rows) of integers where 0 is empty and the letters are either 1-26 or ASCII codes (see
+///   `score_board`), or a `Board`
+/// * `dictionary` - List of valid words
+/// * `max_edits` - Maximum number of edits to make; the time taken grows quickly with this, so it should be kept small
+/// * `min_word_length` - Minimum length of a word, below which runs of letters are invalid even if in the `dictionary`
+/// # Returns
+/// * `tuple[Board, list[dict]] | None` - The repaired board and the edits made, in order (none if the `board` was already legal), or `None`
+///   if no legal board is within `max_edits` edits; each edit is a dict of its kind (`"edit"`) and the `row` and `col` (of the `board`) of
+///   the tile changed: a `"ReplaceLetter"` also gives its new `letter`, a `"DeleteTile"` nothing more, and a `"ShiftWord"` the `length` and
+///   `direction` of the word starting there and the `row_shift` and `col_shift` it was moved by
+/// # Raises
+/// * `ValueError` - If the `board` isn't a rectangular grid, its letters don't all fit one encoding, or a word in the `dictionary` is invalid
+#[pyfunction]
+#[pyo3(name = "repair_board", signature = (board, dictionary, max_edits=2, min_word_length=DEFAULT_MIN_WORD_LENGTH))]
+fn py_repair_board(py: Python, board: &PyAny, dictionary: Vec<String>, max_edits: usize, min_word_length: usize) -> PyResult<Option<(PyObject, Vec<PyObject>)>> {
+    let valid_words: HashSet<Word> = solver_dictionary(&dictionary, min_word_length)?.0.into_iter().collect();
+    board_repair(py, board, &valid_words, min_word_length, max_edits)
+}
+
+/// Completes a partly played board by playing a hand of letters onto it, such as to finish a board from a game in progress
+/// # Arguments
+/// * `board` - 2-D array (or nested list of rows) of integers where 0 is empty and the letters are either 1-26 or ASCII codes (see
+///   `score_board`), or a `Board`; it must be a legal board under the `dictionary`
+/// * `letters` - The letters left to play, either a string of letters (like `"GRAM"`) or a dict mapping each letter to its count
+/// * `dictionary` - List of valid words
+/// * `min_word_length` - Minimum length of a word; shorter words are removed from the `dictionary`, and crossings shorter than this are invalid
+/// * `as_string` - Whether to return the board in string form rather than as a `Board`
+/// # Returns
+/// * `Board | str | None` - The completed board (the `board` itself, moved, if there are no `letters`), or `None` if no way of playing every
+///   letter onto it was found within the word-check budget
+/// # Raises
+/// * `ValueError` - If the `board` isn't a rectangular grid, its letters don't all fit one encoding, or it isn't legal (listing its problems);
+///   or any of the letters isn't A-Z, or a word in the `dictionary` is invalid
+#[pyfunction]
+#[pyo3(name = "complete_board", signature = (board, letters, dictionary, min_word_length=DEFAULT_MIN_WORD_LENGTH, as_string=false))]
+fn py_complete_board(py: Python, board: &PyAny, letters: &PyAny, dictionary: Vec<String>, min_word_length: usize, as_string: bool) -> PyResult<Option<PyObject>> {
+    let board = board_from_codes(board)?;
+    let letters = letters_from_py(letters)?;
+    let (dictionary, _) = solver_dictionary(&dictionary, min_word_length)?;
+    match py.allow_threads(|| complete_board(&board, letters, 0, &dictionary, &SolveOptions { min_word_length, ..SolveOptions::default() })) {
+        Ok(solution) => Ok(Some(board_into_py(py, solution.board, as_string))),
+        Err((SolveFailure::InvalidBoard, _)) => {
+            let valid_words: HashSet<Word> = dictionary.into_iter().collect();
+            let errors = validate_board(&board, &valid_words, min_word_length).err().unwrap_or_default();
+            let problems: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+            Err(PyValueError::new_err(format!("The board to complete isn't legal: {}", problems.join("; "))))
+        },
+        Err(_) => Ok(None)
+    }
+}
+
+/// Renders a board as an image, such as to look at the boards made by a generator being trained
+/// # Arguments
+/// * `board` - 2-D array (or nested list of rows) of integers where 0 is empty and the letters are either 1-26 or ASCII codes (see
+///   `score_board`), or a `Board`
+/// * `format` - `"svg"`, or `"png"` if built with the `png` feature
+/// * `dictionary` - List of valid words, against which the tiles of invalid words are highlighted, if given
+/// * `cell_size` - Width and height of each cell of the board, in pixels
+/// * `min_word_length` - Minimum length of a word, below which runs of letters are highlighted as invalid even if in the `dictionary`
+/// # Returns
+/// * `str | bytes` - The SVG document, or the PNG file; either shows each tile as a rounded square with its letter, cropped to the board's
+///   letters with a margin of one cell
+/// # Raises
+/// * `ValueError` - If the `board` isn't a rectangular grid or its letters don't all fit one encoding, the `format` isn't supported, the
+///   `cell_size` is too small, or a word in the `dictionary` is invalid
+#[pyfunction]
+#[pyo3(signature = (board, format="svg", dictionary=None, cell_size=render::DEFAULT_CELL_SIZE, min_word_length=DEFAULT_MIN_WORD_LENGTH))]
+fn render_board(py: Python, board: &PyAny, format: &str, dictionary: Option<Vec<String>>, cell_size: usize, min_word_length: usize) -> PyResult<PyObject> {
+    let board = board_from_codes(board)?;
+    if cell_size < render::MIN_CELL_SIZE {
+        return Err(PyValueError::new_err(format!("cell_size must be at least {}", render::MIN_CELL_SIZE)));
+    }
+    let highlight = match dictionary {
+        Some(dictionary) => {
+            let valid_words: HashSet<Word> = solver_dictionary(&dictionary, min_word_length)?.0.into_iter().collect();
+            render::invalid_cells(&board, &valid_words, min_word_length)
+        },
+        None => HashSet::new()
+    };
+    let options = render::RenderOptions { cell_size, highlight, ..Default::default() };
+    match format {
+        "svg" => Ok(render::board_to_svg(&board, &options).into_py(py)),
+        #[cfg(feature = "png")]
+        "png" => Ok(PyBytes::new(py, &render::board_to_png(&board, &options)).into()),
+        _ => Err(PyValueError::new_err(format!("Unsupported format {:?} (must be \"svg\"{})", format, if cfg!(feature = "png") { " or \"png\"" } else { ", or \"png\" if built with the png feature" })))
+    }
+}
+
+/// Gets every distinct rotation and reflection of a board, for data augmentation
+/// # Arguments
+/// * `board` - 2-D array (or nested list of rows) where 0 is empty and 1 ('A') through 26 ('Z') are letters
+/// # Returns
+/// * `list[numpy.ndarray] | list[list[list[int]]]` - The distinct transformed boards, starting with the `board` itself; these are 2-D `uint8` arrays
+///   if NumPy is available, otherwise nested lists of rows
+/// # Raises
+/// * `ValueError` - If the `board` isn't a rectangular grid of values from 0 to 26
+#[pyfunction]
+fn augment(py: Python, board: &PyAny) -> PyResult<Vec<PyObject>> {
+    let (board, rows, cols) = board_from_py(board)?;
+    if rows == 0 || cols == 0 {
+        return Ok(vec![board_to_py(py, &board, 1, 0, 1, 0)]);
+    }
+    Ok(distinct_symmetries(&board, 0, cols-1, 0, rows-1).iter().map(|(board, min_col, max_col, min_row, max_row)| board_to_py(py, board, *min_col, *max_col, *min_row, *max_row)).collect())
+}
+
+/// A board of letters, as returned by `generate_boards` and `solve_hand` (called `Board` in Python)
+#[pyclass(name = "Board", module = "board_generator")]
+struct PyBoard {
+    /// The board
+    board: Board
+}
+
+impl PyBoard {
+    /// Gets the occupied region of the board
+    /// # Returns
+    /// * `(usize, usize, usize, usize)` - Tuple of (minimum column, maximum column, minimum row, maximum row) with letters; for an empty
+    ///   board the minimums are one more than the maximums
+    fn bounds(&self) -> (usize, usize, usize, usize) {
+        self.board.bounds().unwrap_or((1, 0, 1, 0))
+    }
+}
+
+#[pymethods]
+impl PyBoard {
+    /// Creates a board from its string form
+    /// # Arguments
+    /// * `text` - The board with a line per row, where a space or period is empty, an uppercase letter is a tile, and a lowercase letter is
+    ///   a tile placed with a blank (as given by `to_string`); the board is empty if not given
+    /// # Raises
+    /// * `ValueError` - If a line is longer than the first (or shorter, other than the last), or has a character that isn't a letter, space,
+    ///   or period
+    #[new]
+    #[pyo3(signature = (text=""))]
+    fn new(text: &str) -> PyResult<Self> {
+        let board = text.parse().map_err(|e: BoardParseError| PyValueError::new_err(e.to_string()))?;
+        Ok(PyBoard { board })
+    }
+
+    /// Gets the size of the board's letters
+    /// # Returns
+    /// * `tuple[int, int]` - The number of (rows, columns) spanned by the board's letters
+    #[getter]
+    fn shape(&self) -> (usize, usize) {
+        let (min_col, max_col, min_row, max_row) = self.bounds();
+        (max_row + 1 - min_row, max_col + 1 - min_col)
+    }
+
+    /// Converts the board to a NumPy array
+    /// # Arguments
+    /// * `pad_to` - Optional (rows, columns) shape of the returned array, with the board centered inside it
+    /// # Returns
+    /// * `numpy.ndarray` - 2-D `uint8` array where 0 is empty and 1 ('A') through 26 ('Z') are letters, cropped to the board's letters
+    ///   unless `pad_to` is given
+    /// # Raises
+    /// * `ValueError` - If the board doesn't fit within `pad_to`
+    #[cfg(feature = "numpy")]
+    #[pyo3(signature = (pad_to=None))]
+    fn to_numpy<'py>(&self, py: Python<'py>, pad_to: Option<(usize, usize)>) -> PyResult<&'py PyArray2<u8>> {
+        // Raise an `ImportError` up front rather than panicking when creating the array if NumPy isn't installed
+        py.import("numpy")?;
+        let (min_col, max_col, min_row, max_row) = self.bounds();
+        Ok(board_to_grid(&self.board, min_col, max_col, min_row, max_row, pad_to)?.into_pyarray(py))
+    }
+
+    /// Converts the board to a one-hot NumPy array (see `bananagrams_core::board_to_onehot`), such as for training on boards as images
+    /// # Arguments
+    /// * `pad_to` - Optional (rows, columns) shape of each channel, with the board centered inside it
+    /// # Returns
+    /// * `numpy.ndarray` - 3-D `uint8` array of shape (27, rows, columns), where channel 0 is 1 in each empty cell and channels 1 ('A')
+    ///   through 26 ('Z') are 1 in each cell with that letter (so the argmax over the channels is the array given by `to_numpy`), cropped
+    ///   to the board's letters unless `pad_to` is given
+    /// # Raises
+    /// * `ValueError` - If the board doesn't fit within `pad_to`
+    #[cfg(feature = "numpy")]
+    #[pyo3(signature = (pad_to=None))]
+    fn to_onehot<'py>(&self, py: Python<'py>, pad_to: Option<(usize, usize)>) -> PyResult<&'py PyArray3<u8>> {
+        py.import("numpy")?;
+        let (rows, cols) = self.shape();
+        let (height, width) = pad_to.unwrap_or((rows, cols));
+        let (min_col, max_col, min_row, max_row) = self.bounds();
+        let onehot = board_to_onehot(&self.board, min_col, max_col, min_row, max_row, height, width)
+            .ok_or_else(|| PyValueError::new_err(format!("The board is {}x{}, which doesn't fit in {}x{}", rows, cols, height, width)))?;
+        // The array has exactly as many values as its shape, so this can't fail
+        Ok(Array3::from_shape_vec((ONEHOT_CHANNELS, height, width), onehot).unwrap().into_pyarray(py))
+    }
+
+    /// Converts the board to a string
+    /// # Returns
+    /// * `str` - The board cropped to its letters, with a line per row (spaces for empty cells, and lowercase letters for tiles placed with a
+    ///   blank)
+    #[pyo3(name = "to_string")]
+    fn py_to_string(&self) -> String {
+        board_to_string(&self.board)
+    }
+
+    /// Gets the words on the board
+    /// # Returns
+    /// * `list[tuple[str, int, int, str]]` - Each word with the row and column of its first letter (counted from the top-left of the board's
+    ///   letters) and its direction (`"Horizontal"` or `"Vertical"`); the horizontal words (row by row) come before the vertical ones
+    fn words(&self) -> Vec<(String, usize, usize, String)> {
+        let (min_col, max_col, min_row, max_row) = self.bounds();
+        extract_words(&self.board, min_col, max_col, min_row, max_row).into_iter().map(|(word, row, col, direction)| (word, row - min_row, col - min_col, direction.to_string())).collect()
+    }
+
+    /// Checks whether the board is a legal Bananagrams board (see `validate_board`)
+    /// # Arguments
+    /// * `dictionary` - List of valid words
+    /// * `min_word_length` - Minimum length of a word, below which runs of letters are invalid even if in the `dictionary`
+    /// # Returns
+    /// * `bool` - Whether all of the letters are connected and every run of two or more letters is in the `dictionary`
+    /// # Raises
+    /// * `ValueError` - If a word in the `dictionary` is invalid
+    #[pyo3(signature = (dictionary, min_word_length=DEFAULT_MIN_WORD_LENGTH))]
+    fn is_valid(&self, dictionary: Vec<String>, min_word_length: usize) -> PyResult<bool> {
+        let valid_words: HashSet<Word> = solver_dictionary(&dictionary, min_word_length)?.0.into_iter().collect();
+        Ok(validate_board(&self.board, &valid_words, min_word_length).is_ok())
+    }
+
+    fn __str__(&self) -> String {
+        board_to_string(&self.board)
+    }
+
+    fn __repr__(&self) -> String {
+        let (rows, cols) = self.shape();
+        format!("Board ({}x{}):\n{}", rows, cols, board_to_string(&self.board))
+    }
+
+    /// Boards are equal if they have the same letters (and blanks) in the same arrangement, wherever they are on the board
+    fn __eq__(&self, other: PyRef<PyBoard>) -> bool {
+        self.board == other.board
+    }
+
+    fn __hash__(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.board.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Gets the board to pickle
+    /// # Returns
+    /// * `dict` - The board's `size`, the `row` and `col` of the top-left corner of its letters, and its letters as a list of `rows`
+    fn __getstate__(&self, py: Python) -> PyResult<PyObject> {
+        Ok(pythonize::pythonize(py, &self.board)?)
+    }
+
+    /// Restores the board when unpickling
+    /// # Arguments
+    /// * `state` - The board as given by `__getstate__`
+    /// # Raises
+    /// * `ValueError` - If the `state` isn't a valid board
+    fn __setstate__(&mut self, state: &PyAny) -> PyResult<()> {
+        self.board = pythonize::depythonize(state).map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(())
+    }
+}
+
+/// Generates boards from a dictionary that is preprocessed once on construction
+#[pyclass]
+struct BoardGenerator {
+    /// Vector of valid words, sorted longest first
+    dictionary: Vec<Word>,
+    /// The frequency of each word in `dictionary`, if the dictionary gave any
+    frequencies: Option<Vec<f64>>,
+    /// How strongly to prefer more frequent words when generating boards (see `bananagrams_core::choose_by_frequency`)
+    frequency_temperature: f64,
+    /// HashSet version of `dictionary` for faster membership checking
+    valid_words: HashSet<Word>,
+    /// Index of the words in `dictionary` containing each letter
+    index: DictionaryIndex,
+    /// The letters in each word of `dictionary`, for filtering it by each hand solved
+    masks: Vec<LetterMask>,
+    /// The tiles hands are drawn from
+    tiles: TileSet,
+    /// Minimum length of a word on a board
+    min_word_length: usize
+}
+
+#[pymethods]
+impl BoardGenerator {
+    /// Creates a new `BoardGenerator`
+    /// # Arguments
+    /// * `dictionary` - Either a list of valid words, or the path to a file with one valid word per line; each word can be followed by
+    ///   whitespace and its frequency
+    /// * `tiles` - Optional dict mapping each letter to its number of tiles to draw hands from (the standard 144 tiles are used if not given)
+    /// * `min_word_length` - Minimum length of a word; shorter words are removed from the `dictionary`, and crossings shorter than this are
+    ///   invalid
+    /// * `frequency_temperature` - If the `dictionary` gives frequencies, how strongly to prefer more frequent words when generating boards
+    ///   (see `generate_boards`)
+    /// * `cache` - Whether to keep the preprocessed words of a dictionary file in a cache file next to it (named like it, with
+    ///   `.bgdcache` added), which later generators load it from much faster while the file is unchanged
+    /// # Raises
+    /// * `ValueError` - If a word or frequency is invalid (such as a word containing anything other than the letters A-Z, with its line in
+    ///   the message), there are no words of at least `min_word_length` letters, the tile set is invalid, or `frequency_temperature` is
+    ///   negative
+    /// * `OSError` - If the dictionary file couldn't be read
+    #[new]
+    #[pyo3(signature = (dictionary, tiles=None, min_word_length=DEFAULT_MIN_WORD_LENGTH, frequency_temperature=DEFAULT_FREQUENCY_TEMPERATURE, cache=true))]
+    fn new(dictionary: &PyAny, tiles: Option<HashMap<String, usize>>, min_word_length: usize, frequency_temperature: f64, cache: bool) -> PyResult<Self> {
+        let tiles = tiles_from_py(tiles)?;
+        check_frequency_temperature(frequency_temperature)?;
+        let preprocessed = match dictionary.extract::<Vec<String>>() {
+            Ok(words) => preprocess(solver_dictionary(&words, min_word_length)?),
+            Err(_) => {
+                let path: PathBuf = dictionary.extract()?;
+                match cache {
+                    true => load_preprocessed_dictionary(&path, false, min_word_length).map_err(dictionary_error)?,
+                    false => {
+                        let (mut words, mut frequencies) = load_weighted_dictionary(&path, false).map_err(dictionary_error)?;
+                        remove_short_words(&mut words, frequencies.as_mut(), min_word_length);
+                        preprocess((words, frequencies))
+                    }
+                }
+            }
+        };
+        if preprocessed.words.is_empty() {
+            return Err(no_words_error(min_word_length));
+        }
+        let PreprocessedDictionary { words, frequencies, masks, index } = preprocessed;
+        let valid_words = words.iter().cloned().collect();
+        Ok(BoardGenerator { index, masks, dictionary: words, frequencies, frequency_temperature, valid_words, tiles, min_word_length })
+    }
+
+    /// Generates a single board
+    /// # Arguments
+    /// * `target_size` - Minimum number of tiles on the board
+    /// * `seed` - Optional seed for reproducible generation
+    /// # Returns
+    /// * `str | None` - The generated board in string form, or `None` if generation repeatedly failed
+    /// # Raises
+    /// * `ValueError` - If `target_size` is 0 or more than the board can fit
+    #[pyo3(signature = (target_size, seed=None))]
+    fn generate(&self, target_size: usize, seed: Option<u64>) -> PyResult<Option<String>> {
+        check_target_size(target_size)?;
+        Ok(generate_many(&self.dictionary, self.frequencies.as_deref(), self.frequency_temperature, &self.valid_words, &self.index, target_size, self.min_word_length, 1, &mut seeded_rng(seed)).pop().map(|board| board_to_string(&board)))
+    }
+
+    /// Generates several boards
+    /// # Arguments
+    /// * `target_size` - Minimum number of tiles on each board
+    /// * `n` - Number of boards to generate
+    /// * `seed` - Optional seed for reproducible generation
+    /// # Returns
+    /// * `list[str]` - The generated boards in string form; if generation repeatedly fails, fewer than `n` boards are returned
+    /// # Raises
+    /// * `ValueError` - If `target_size` is 0 or more than the board can fit
+    #[pyo3(signature = (target_size, n, seed=None))]
+    fn generate_many(&self, target_size: usize, n: usize, seed: Option<u64>) -> PyResult<Vec<String>> {
+        check_target_size(target_size)?;
+        Ok(generate_many(&self.dictionary, self.frequencies.as_deref(), self.frequency_temperature, &self.valid_words, &self.index, target_size, self.min_word_length, n, &mut seeded_rng(seed)).iter().map(board_to_string).collect())
+    }
+
+    /// Draws a random hand from the tile set
+    /// # Arguments
+    /// * `min_hand_size` - Minimum number of letters in the hand (for `hand_sizes` of `"log_scaled"` and `"uniform"`)
+    /// * `max_hand_size` - Maximum number of letters in the hand (defaults to half the tiles in the tile set)
+    /// * `seed` - Optional seed for reproducible generation
+    /// * `hand_sizes` - How to choose the size of the hand (see `generate_training_batch`)
+    /// * `hand_size` - Number of letters in the hand, for `hand_sizes="fixed"`
+    /// * `players` - Number of players, for `hand_sizes="official"`
+    /// * `min_vowels` - Minimum number of vowels in the hand (see `generate_training_batch`)
+    /// # Returns
+    /// * `str` - The hand as a string like `"AAEINRST"`
+    /// # Raises
+    /// * `ValueError` - If the hand-size options are invalid (see `generate_training_batch`) or `min_vowels` is more than the smallest hand
+    ///   size
+    #[pyo3(signature = (min_hand_size=MINIMUM_HAND_SIZE as usize, max_hand_size=None, seed=None, hand_sizes="log_scaled", hand_size=None, players=None, min_vowels=0))]
+    #[allow(clippy::too_many_arguments, reason = "mirrors the keyword arguments of the Python method")]
+    fn random_hand(&self, min_hand_size: usize, max_hand_size: Option<usize>, seed: Option<u64>, hand_sizes: &str, hand_size: Option<usize>, players: Option<usize>, min_vowels: usize) -> PyResult<String> {
+        let max_hand_size = max_hand_size.unwrap_or(self.tiles.maximum_hand_size());
+        let hand_sizes = hand_sizes_from_py(&self.tiles, hand_sizes, min_hand_size, max_hand_size, hand_size, players)?;
+        check_min_vowels(&hand_sizes, min_vowels)?;
+        Ok(letters_to_string(&generate_hand(&mut seeded_rng(seed), &self.tiles, hand_sizes, min_vowels).0))
+    }
+
+    /// Gets the number of each letter in the tile set
+    /// # Returns
+    /// * `dict[str, int]` - The number of tiles of each letter in the tile set (letters without tiles are left out)
+    #[getter]
+    fn tiles(&self) -> HashMap<String, usize> {
+        let counts: Letters = self.tiles.counts();
+        counts.iter().enumerate().filter(|(_, &count)| count > 0).map(|(letter, &count)| (((letter as u8 + 65) as char).to_string(), count as usize)).collect()
+    }
+
+    /// Solves a hand of letters using the dictionary
+    /// # Arguments
+    /// * `letters` - Either a string of letters (like `"AAEINRST"`) or a dict mapping each letter to its count
+    /// * `target_tiles` - Optional number of tiles to finish the board with (see `solve_hand`)
+    /// * `first_word` - How to order the words tried first (see `solve_hand`)
+    /// * `threads` - Number of threads to try first words on at once
+    /// * `deterministic` - When using several `threads`, whether to always return the board found with one (see `solve_hand`)
+    /// * `anchor_word` - Optional word to always play first (see `solve_hand`)
+    /// * `check_pool` - Whether to raise a `ValueError` if the hand has more of a letter than the generator's tile set
+    /// # Returns
+    /// * `str | None` - The solved board in string form, or `None` if no solution was found within the word-check budget
+    /// # Raises
+    /// * `ValueError` - If any of the letters isn't A-Z, `target_tiles` or `threads` is 0, `first_word` isn't a strategy, `anchor_word` isn't
+    ///   in the dictionary or can't be made from the `letters`, or the hand can't be drawn from the tile set (with `check_pool`)
+    #[pyo3(signature = (letters, target_tiles=None, first_word="longest", threads=1, deterministic=false, anchor_word=None, check_pool=true))]
+    #[allow(clippy::too_many_arguments, reason = "mirrors the keyword arguments of the Python method")]
+    fn solve(&self, letters: &PyAny, target_tiles: Option<usize>, first_word: &str, threads: usize, deterministic: bool, anchor_word: Option<&str>, check_pool: bool) -> PyResult<Option<String>> {
+        Ok(solve_letters(letters, check_pool.then_some(&self.tiles), &self.dictionary, Some(&self.masks), self.min_word_length, target_tiles, first_word, anchor_word, threads, deterministic)?.map(|board| board_to_string(&board)))
+    }
+
+    /// Solves a hand of letters several different ways using the dictionary
+    /// # Arguments
+    /// * `letters` - Either a string of letters (like `"AAEINRST"`) or a dict mapping each letter to its count
+    /// * `n` - Maximum number of distinct boards to return (see `solve_hand_multi`)
+    /// * `seed` - Optional seed for reproducible solving
+    /// * `target_tiles` - Optional number of tiles to finish each board with (see `solve_hand`)
+    /// * `check_pool` - Whether to raise a `ValueError` if the hand has more of a letter than the generator's tile set
+    /// # Returns
+    /// * `list[str]` - The solved boards in string form; fewer than `n` (or none) if no more were found within the word-check budget
+    /// # Raises
+    /// * `ValueError` - If any of the letters isn't A-Z, `n` or `target_tiles` is 0, or the hand can't be drawn from the tile set (with
+    ///   `check_pool`)
+    #[pyo3(signature = (letters, n, seed=None, target_tiles=None, check_pool=true))]
+    fn solve_multi(&self, letters: &PyAny, n: usize, seed: Option<u64>, target_tiles: Option<usize>, check_pool: bool) -> PyResult<Vec<String>> {
+        let letters = hand_from_py(letters, check_pool.then_some(&self.tiles))?;
+        solve_distinct(letters, &self.dictionary, Some(&self.masks), n, self.min_word_length, target_tiles, seed)
+    }
+
+    /// Checks whether a board is a legal Bananagrams board using the dictionary
+    /// # Arguments
+    /// * `board` - 2-D array (or nested list of rows) where 0 is empty and 1 ('A') through 26 ('Z') are letters
+    /// # Returns
+    /// * `list[str]` - A description of each problem with the board; empty if it is legal
+    /// # Raises
+    /// * `ValueError` - If the `board` isn't a rectangular grid of values from 0 to 26
+    fn validate(&self, board: &PyAny) -> PyResult<Vec<String>> {
+        board_errors(board, &self.valid_words, self.min_word_length)
+    }
+
+    /// Scores how close a board is to being legal using the dictionary
+    /// # Arguments
+    /// * `board` - 2-D array (or nested list of rows) of integers where 0 is empty and the letters are either 1-26 or ASCII codes, or a `Board`
+    /// # Returns
+    /// * `dict` - The score (see `score_board`)
+    /// # Raises
+    /// * `ValueError` - If the `board` isn't a rectangular grid, or its letters don't all fit one encoding
+    fn score(&self, py: Python, board: &PyAny) -> PyResult<PyObject> {
+        board_score(py, &board_from_codes(board)?, &self.valid_words, self.min_word_length)
+    }
+
+    /// Tries to make a nearly legal board legal using the dictionary
+    /// # Arguments
+    /// * `board` - 2-D array (or nested list of rows) of integers where 0 is empty and the letters are either 1-26 or ASCII codes, or a `Board`
+    /// * `max_edits` - Maximum number of edits to make (see `repair_board`)
+    /// # Returns
+    /// * `tuple[Board, list[dict]] | None` - The repaired board and the edits made, or `None` if no legal board is within `max_edits` edits
+    /// # Raises
+    /// * `ValueError` - If the `board` isn't a rectangular grid, or its letters don't all fit one encoding
+    #[pyo3(signature = (board, max_edits=2))]
+    fn repair(&self, py: Python, board: &PyAny, max_edits: usize) -> PyResult<Option<(PyObject, Vec<PyObject>)>> {
+        board_repair(py, board, &self.valid_words, self.min_word_length, max_edits)
+    }
+
+    /// Gets the number of words in the dictionary
+    fn __len__(&self) -> usize {
+        self.dictionary.len()
+    }
 }
 
 /// A Python module implemented in Rust.
 #[pymodule]
-fn board_generator(_py: Python, m: &PyModule) -> PyResult<()> {
-    m.add_function(wrap_pyfunction!(sum_as_string, m)?)?;
+fn board_generator(py: Python, m: &PyModule) -> PyResult<()> {
+    // Sends the solver's log messages (down to its trace messages) to Python's `logging` module, unless another logger was already installed
+    let _ = pyo3_log::Logger::new(py, pyo3_log::Caching::LoggersAndLevels)?.filter(log::LevelFilter::Trace).install();
+    m.add_function(wrap_pyfunction!(generate_boards, m)?)?;
+    m.add_function(wrap_pyfunction!(solve_hand, m)?)?;
+    m.add_function(wrap_pyfunction!(solve_hand_multi, m)?)?;
+    m.add_function(wrap_pyfunction!(solve_hand_record, m)?)?;
+    m.add_function(wrap_pyfunction!(generate_training_batch, m)?)?;
+    m.add_function(wrap_pyfunction!(py_generate_hand, m)?)?;
+    m.add_function(wrap_pyfunction!(hand_to_string, m)?)?;
+    m.add_function(wrap_pyfunction!(string_to_hand, m)?)?;
+    m.add_function(wrap_pyfunction!(load_boards, m)?)?;
+    m.add_function(wrap_pyfunction!(read_msgpack_boards, m)?)?;
+    m.add_function(wrap_pyfunction!(py_validate_board, m)?)?;
+    m.add_function(wrap_pyfunction!(augment, m)?)?;
+    m.add_function(wrap_pyfunction!(score_board, m)?)?;
+    m.add_function(wrap_pyfunction!(py_repair_board, m)?)?;
+    m.add_function(wrap_pyfunction!(py_complete_board, m)?)?;
+    m.add_function(wrap_pyfunction!(render_board, m)?)?;
+    #[cfg(feature = "numpy")]
+    m.add_function(wrap_pyfunction!(solve_hand_array, m)?)?;
+    m.add_class::<PyBoard>()?;
+    m.add_class::<BoardGenerator>()?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bananagrams_core::{convert_word_to_array, parse_dictionary};
+    #[cfg(feature = "numpy")]
+    use bananagrams_core::Solution;
+
+    /// Words that cross each other in many ways, for generating small boards
+    const SMALL_DICTIONARY: &str = "CAT\nACT\nTAB\nBAT\nCAB\nTACO\nCOAT\nOAT\nTAO\nBOA\nAT\nTA\nTO\nAB\nBA\nOBA";
+
+    /// Converts words into the dictionary, set of valid words, and index that `generate_board` takes
+    fn small_dictionary(text: &str) -> (Vec<Word>, HashSet<Word>, DictionaryIndex) {
+        let dictionary = parse_dictionary(text, false).unwrap();
+        let valid_words = dictionary.iter().cloned().collect();
+        let index = DictionaryIndex::new(&dictionary);
+        (dictionary, valid_words, index)
+    }
+
+    /// Counts the tiles on a board
+    fn tile_count(board: &Board) -> usize {
+        (0..board.size()).flat_map(|row| (0..board.size()).map(move |col| (row, col))).filter(|&(row, col)| board.get_val(row, col) != EMPTY_VALUE).count()
+    }
+
+    /// Solves a hand with the small dictionary
+    #[cfg(feature = "numpy")]
+    fn solve_small(letters: &str) -> Solution {
+        let (dictionary, _, _) = small_dictionary(SMALL_DICTIONARY);
+        let letters: Letters = Alphabet::english().hand(letters).unwrap();
+        play_bananagrams(letters, 0, &dictionary, &SolveOptions::default()).ok().expect("The hand wasn't solved")
+    }
+
+    #[test]
+    fn generate_board_reaches_target_size_with_valid_words() {
+        let (dictionary, valid_words, index) = small_dictionary(SMALL_DICTIONARY);
+        for seed in 0..20 {
+            let (board, min_col, max_col, min_row, max_row) = generate_board(&dictionary, None, DEFAULT_FREQUENCY_TEMPERATURE, &valid_words, &index, 10, DEFAULT_MIN_WORD_LENGTH, &mut seeded_rng(Some(seed))).expect("No board was generated");
+            assert!(tile_count(&board) >= 10);
+            assert_eq!(validate_board(&board, &valid_words, DEFAULT_MIN_WORD_LENGTH), Ok(()));
+            assert_eq!(board.bounds(), Some((min_col, max_col, min_row, max_row)));
+        }
+    }
+
+    #[test]
+    fn generate_board_gives_up_when_no_word_fits() {
+        let (dictionary, valid_words, index) = small_dictionary("TACO\nCOAT");
+        assert!(generate_board(&dictionary, None, DEFAULT_FREQUENCY_TEMPERATURE, &valid_words, &index, 3, DEFAULT_MIN_WORD_LENGTH, &mut seeded_rng(Some(0))).is_none());
+    }
+
+    #[test]
+    fn generate_board_gives_up_after_maximum_play_attempts() {
+        // Each attempt places at most one short word, so the whole board can't be filled before running out of attempts
+        let (dictionary, valid_words, index) = small_dictionary(SMALL_DICTIONARY);
+        assert!(generate_board(&dictionary, None, DEFAULT_FREQUENCY_TEMPERATURE, &valid_words, &index, BOARD_SIZE*BOARD_SIZE, DEFAULT_MIN_WORD_LENGTH, &mut seeded_rng(Some(0))).is_none());
+    }
+
+    /// Converts a grid from `board_to_grid` back into the form of `board_to_string`, leaving out the rows and columns of padding
+    #[cfg(feature = "numpy")]
+    fn grid_to_string(grid: &Array2<u8>) -> String {
+        let occupied: Vec<(usize, usize)> = grid.indexed_iter().filter(|(_, &val)| val != 0).map(|(cell, _)| cell).collect();
+        let (min_row, max_row) = (occupied.iter().map(|cell| cell.0).min().unwrap(), occupied.iter().map(|cell| cell.0).max().unwrap());
+        let (min_col, max_col) = (occupied.iter().map(|cell| cell.1).min().unwrap(), occupied.iter().map(|cell| cell.1).max().unwrap());
+        let rows: Vec<String> = (min_row..max_row+1).map(|row| (min_col..max_col+1).map(|col| match grid[(row, col)] {
+            0 => ' ',
+            val => (b'A' + val - 1) as char
+        }).collect()).collect();
+        rows.join("\n").trim_end().to_owned()
+    }
+
+    #[test]
+    #[cfg(feature = "numpy")]
+    fn board_to_grid_round_trips_through_board_to_string() {
+        for letters in ["CATBOAT", "TACOBAT", "ABOTAC"] {
+            let Solution { board, min_col, max_col, min_row, max_row, .. } = solve_small(letters);
+            let grid = board_to_grid(&board, min_col, max_col, min_row, max_row, None).unwrap();
+            assert_eq!(grid.dim(), (max_row + 1 - min_row, max_col + 1 - min_col));
+            assert_eq!(grid_to_string(&grid), board_to_string(&board));
+            // Padding centers the same board
+            let padded = board_to_grid(&board, min_col, max_col, min_row, max_row, Some((32, 32))).unwrap();
+            assert_eq!(padded.dim(), (32, 32));
+            assert_eq!(padded.iter().filter(|&&val| val != 0).count(), grid.iter().filter(|&&val| val != 0).count());
+            assert_eq!(grid_to_string(&padded), board_to_string(&board));
+            assert!(board_to_grid(&board, min_col, max_col, min_row, max_row, Some((1, 1))).is_err());
+        }
+    }
+
+    #[test]
+    fn seeded_generation_repeats() {
+        let (dictionary, valid_words, index) = small_dictionary(SMALL_DICTIONARY);
+        let run = |seed| generate_many(&dictionary, None, DEFAULT_FREQUENCY_TEMPERATURE, &valid_words, &index, 8, DEFAULT_MIN_WORD_LENGTH, 5, &mut seeded_rng(Some(seed)));
+        let first = run(42);
+        assert_eq!(first.len(), 5);
+        assert_eq!(first, run(42));
+        assert_ne!(first, run(43));
+    }
+
+    /// Gets the (row, column) positions of the tiles on a board
+    fn occupied(board: &Board) -> BTreeSet<(usize, usize)> {
+        (0..board.size()).flat_map(|row| (0..board.size()).map(move |col| (row, col))).filter(|&(row, col)| board.get_val(row, col) != EMPTY_VALUE).collect()
+    }
+
+    #[test]
+    fn placed_words_are_recorded_where_they_were_written() {
+        let mut board = Board::new(16);
+        let mut played_positions = BTreeSet::new();
+        let cat = place_word(&mut board, &convert_word_to_array("CAT"), Direction::Horizontal, 5, 4, &mut played_positions);
+        assert_eq!(cat, [(5, 4), (5, 5), (5, 6)]);
+        // TACO crosses CAT at its T, so only the three letters below it are new
+        let taco = place_word(&mut board, &convert_word_to_array("TACO"), Direction::Vertical, 5, 6, &mut played_positions);
+        assert_eq!(taco, [(6, 6), (7, 6), (8, 6)]);
+        assert_eq!(played_positions, occupied(&board));
+        assert_eq!(board_to_string(&board), "CAT\n  A\n  C\n  O");
+        remove_word(&mut board, &taco, &mut played_positions);
+        assert_eq!(played_positions, occupied(&board));
+        assert_eq!(board_to_string(&board), "CAT");
+    }
+
+
+    #[test]
+    fn tile_sets_from_python_have_the_counts_given() {
+        let tiles = tiles_from_py(Some(HashMap::from([("a".to_owned(), 20), ("E".to_owned(), 20), ("i".to_owned(), 12), ("T".to_owned(), 8)]))).unwrap();
+        assert_eq!(tiles.len(), 60);
+        let counts: Letters = tiles.counts();
+        for (letter, &count) in counts.iter().enumerate() {
+            assert_eq!(count, match (letter as u8 + b'A') as char { 'A' | 'E' => 20, 'I' => 12, 'T' => 8, _ => 0 });
+        }
+        assert_eq!(tiles_from_py(None).unwrap().len(), 144);
+        for invalid in [("1", 2), ("AB", 2), ("", 2), ("A", 0), ("A", 256)] {
+            assert!(tiles_from_py(Some(HashMap::from([(invalid.0.to_owned(), invalid.1)]))).is_err(), "{:?} was accepted", invalid);
+        }
+    }
+
+
+    #[test]
+    fn a_frequency_temperature_of_0_starts_with_the_most_frequent_word() {
+        let (dictionary, valid_words, index) = small_dictionary(SMALL_DICTIONARY);
+        let mut frequencies = vec![1.0; dictionary.len()];
+        let coat = dictionary.iter().position(|word| *word == convert_word_to_array("COAT")).unwrap();
+        frequencies[coat] = 50.0;
+        for seed in 0..10 {
+            let (board, ..) = generate_board(&dictionary, Some(&frequencies), 0.0, &valid_words, &index, 4, DEFAULT_MIN_WORD_LENGTH, &mut seeded_rng(Some(seed))).expect("No board was generated");
+            let letters: Vec<u8> = occupied(&board).into_iter().map(|(row, col)| board.get_val(row, col)).collect();
+            assert_eq!(letters, convert_word_to_array("COAT").to_vec());
+        }
+    }
+
+
+    #[test]
+    fn distinct_boards_are_returned_as_a_list() {
+        let dictionary = parse_dictionary("CAT\nDOG\nCOG\nDOT\nTAG\nGOAT\nTOAD\nCOD\nACT\nGOD\nDOC\nCOT\nTOG", false).unwrap();
+        let letters: Letters = Alphabet::english().hand("CATDOG").unwrap();
+        let boards = solve_distinct(letters, &dictionary, None, 4, DEFAULT_MIN_WORD_LENGTH, None, Some(52)).unwrap();
+        assert!(boards.len() >= 2);
+        assert_eq!(boards.iter().collect::<HashSet<_>>().len(), boards.len());
+        assert!(solve_distinct(letters, &dictionary, None, 0, DEFAULT_MIN_WORD_LENGTH, None, None).is_err());
+        // An unsolvable hand gives no boards rather than an error
+        assert_eq!(solve_distinct(Alphabet::english().hand("QQQ").unwrap(), &dictionary, None, 4, DEFAULT_MIN_WORD_LENGTH, None, None).unwrap(), Vec::<String>::new());
+    }
+
+    /// What `score_board` returns: the number of tiles, the number of valid words, the invalid words, the number of components, and the score
+    type Score = (usize, usize, Vec<(String, usize, usize, String)>, usize, f64);
+
+    /// Scores a grid of letter codes with `score_board` as called from Python
+    fn score(grid: &[Vec<i64>], dictionary: &[&str]) -> PyResult<Score> {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let dictionary = dictionary.iter().map(|word| word.to_string()).collect();
+            let result = score_board(py, grid.to_vec().into_py(py).as_ref(py), dictionary, DEFAULT_MIN_WORD_LENGTH)?;
+            let result: &PyDict = result.downcast(py)?;
+            let get = |key: &str| result.get_item(key).map(|item| item.expect("Key missing from the score"));
+            Ok((get("tiles")?.extract()?, get("valid_words")?.extract()?, get("invalid_words")?.extract()?, get("components")?.extract()?, get("score")?.extract()?))
+        })
+    }
+
+    /// Converts rows of text to letter codes 1 ('A') through 26 ('Z'), with 0 for spaces
+    fn letter_codes(rows: &[&str]) -> Vec<Vec<i64>> {
+        rows.iter().map(|row| row.bytes().map(|letter| if letter == b' ' { 0 } else { (letter - b'A' + 1) as i64 }).collect()).collect()
+    }
+
+    #[test]
+    fn legal_boards_score_1_in_either_encoding() {
+        let codes = letter_codes(&["CAT", "  O", "  M", "  B"]);
+        let expected: Score = (6, 2, Vec::new(), 1, 1.0);
+        assert_eq!(score(&codes, &["CAT", "TOMB"]).unwrap(), expected);
+        let ascii: Vec<Vec<i64>> = codes.iter().map(|row| row.iter().map(|&code| if code == 0 { 0 } else { code + 64 }).collect()).collect();
+        assert_eq!(score(&ascii, &["CAT", "TOMB"]).unwrap(), expected);
+        let lowercase: Vec<Vec<i64>> = ascii.iter().map(|row| row.iter().map(|&code| if code == 0 { 0 } else { code + 32 }).collect()).collect();
+        assert_eq!(score(&lowercase, &["CAT", "TOMB"]).unwrap(), expected);
+    }
+
+    #[test]
+    fn slightly_broken_boards_list_what_is_wrong() {
+        // One bad crossing
+        assert_eq!(score(&letter_codes(&["CAT", "  O", "  X", "  B"]), &["CAT", "TOMB"]).unwrap(), (6, 1, vec![("TOXB".to_string(), 0, 2, "Vertical".to_string())], 1, 0.5));
+        // Two legal words that don't touch
+        assert_eq!(score(&letter_codes(&["CAT", "   ", "AT "]), &["CAT", "AT"]).unwrap(), (5, 2, Vec::new(), 2, 0.5));
+        // A two-letter crossing that isn't in the dictionary
+        assert_eq!(score(&letter_codes(&["CAT", "  O"]), &["CAT"]).unwrap(), (4, 1, vec![("TO".to_string(), 0, 2, "Vertical".to_string())], 1, 0.5));
+    }
+
+    #[test]
+    fn boards_without_words_score_0() {
+        assert_eq!(score(&[], &["CAT"]).unwrap(), (0, 0, Vec::new(), 0, 0.0));
+        assert_eq!(score(&letter_codes(&["   ", "   "]), &["CAT"]).unwrap(), (0, 0, Vec::new(), 0, 0.0));
+        assert_eq!(score(&letter_codes(&["Q"]), &["CAT"]).unwrap(), (1, 0, Vec::new(), 1, 0.0));
+    }
+
+    #[test]
+    fn garbage_boards_are_scored_or_rejected() {
+        let mut rng = seeded_rng(Some(67));
+        for _ in 0..20 {
+            let grid: Vec<Vec<i64>> = (0..8).map(|_| (0..8).map(|_| if rng.gen_bool(0.5) { 0 } else { rng.gen_range(1..=26) }).collect()).collect();
+            let (tiles, valid_words, invalid_words, components, score) = score(&grid, &["CAT", "TOMB", "AT"]).unwrap();
+            assert_eq!(tiles, grid.iter().flatten().filter(|&&code| code != 0).count());
+            assert!(components >= 1 && components <= tiles);
+            assert!((0.0..=1.0).contains(&score));
+            assert!(valid_words + invalid_words.len() > 0 || score == 0.0);
+        }
+        // Ragged rows, values fitting neither encoding, and a mix of the two encodings
+        assert!(score(&[vec![1, 2], vec![3]], &["CAT"]).is_err());
+        assert!(score(&[vec![1, 27]], &["CAT"]).is_err());
+        assert!(score(&[vec![-1, 0]], &["CAT"]).is_err());
+        assert!(score(&[vec![i64::MAX, 0]], &["CAT"]).is_err());
+        assert!(score(&[vec![3, 65]], &["CAT"]).is_err());
+    }
+
+    #[test]
+    fn anchor_words_are_played_first_or_raise_an_error() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let dictionary: Vec<String> = ["CAT", "ACT", "TOMB", "TOMBS"].iter().map(|word| word.to_string()).collect();
+            let solve = |anchor_word: &str, threads: usize| solve_hand(py, PyString::new(py, "CATOMB"), dictionary.clone(), DEFAULT_MIN_WORD_LENGTH, None, "longest", threads, true, true, Some(anchor_word), true);
+            for threads in [1, 2] {
+                let board: String = solve("ACT", threads).unwrap().expect("The hand wasn't solved").extract(py).unwrap();
+                assert_eq!(board, "ACT\n  O\n  M\n  B");
+                // In the dictionary but not the hand, and in the hand but not the dictionary
+                for anchor_word in ["TOMBS", "BOAT"] {
+                    let error = solve(anchor_word, threads).unwrap_err();
+                    assert!(error.is_instance_of::<PyValueError>(py), "{}", error);
+                }
+            }
+        });
+    }
+
+
+    /// Draws a hand with `generate_hand` as called from Python, as a list of 26 counts
+    fn python_hand(py: Python, size: Option<usize>, distribution: Option<&str>, tiles: Option<HashMap<String, usize>>, seed: u64) -> PyResult<Letters> {
+        let hand = py_generate_hand(py, size, distribution, tiles, Some(seed), MINIMUM_HAND_SIZE as usize, None, None, 0, true)?;
+        let counts: Vec<u8> = hand.extract(py)?;
+        Ok(counts.try_into().expect("Not 26 counts"))
+    }
+
+    /// Gets the share of all the letters of some hands that each letter makes up
+    fn letter_frequencies(hands: &[Letters]) -> Vec<f64> {
+        let total: usize = hands.iter().flatten().map(|&count| count as usize).sum();
+        (0..26).map(|letter| hands.iter().map(|hand| hand[letter] as usize).sum::<usize>() as f64 / total as f64).collect()
+    }
+
+    #[test]
+    fn python_hands_are_drawn_like_training_data_hands() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let tiles = TileSet::standard();
+            // The defaults are the same as training_data's, so a seed gives the same hand
+            for seed in 0..50 {
+                let expected = generate_hand(&mut seeded_rng(Some(seed)), &tiles, HandSizeDistribution::LogScaled(MINIMUM_HAND_SIZE as usize, tiles.maximum_hand_size()), 0).0;
+                assert_eq!(python_hand(py, None, None, None, seed).unwrap(), expected);
+            }
+            // Over many hands, those drawn one per seed from Python have the letters and sizes of those a training_data thread draws in turn
+            let python: Vec<Letters> = (0..2000).map(|seed| python_hand(py, None, None, None, seed).unwrap()).collect();
+            let mut rng = seeded_rng(Some(12345));
+            let rust: Vec<Letters> = (0..2000).map(|_| generate_hand(&mut rng, &tiles, HandSizeDistribution::LogScaled(MINIMUM_HAND_SIZE as usize, tiles.maximum_hand_size()), 0).0).collect();
+            for (letter, (python, rust)) in letter_frequencies(&python).into_iter().zip(letter_frequencies(&rust)).enumerate() {
+                assert!((python - rust).abs() < 0.005, "{}: {:.4} from Python but {:.4} from Rust", (letter as u8 + b'A') as char, python, rust);
+            }
+            let mean_size = |hands: &[Letters]| hands.iter().flatten().map(|&count| count as f64).sum::<f64>() / hands.len() as f64;
+            assert!((mean_size(&python) - mean_size(&rust)).abs() < 2.0, "Mean sizes {} and {}", mean_size(&python), mean_size(&rust));
+        });
+    }
+
+    #[test]
+    fn python_hands_honor_the_tiles_and_sizes_given() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let tiles = HashMap::from([("A".to_owned(), 20), ("E".to_owned(), 20), ("I".to_owned(), 12), ("T".to_owned(), 8)]);
+            for seed in 0..20 {
+                let hand = python_hand(py, Some(15), None, Some(tiles.clone()), seed).unwrap();
+                assert_eq!(hand.iter().map(|&count| count as usize).sum::<usize>(), 15);
+                for (letter, &count) in hand.iter().enumerate() {
+                    let letter = ((letter as u8 + b'A') as char).to_string();
+                    assert!(count as usize <= tiles.get(&letter).copied().unwrap_or(0), "{} of {} in {:?}", count, letter, hand);
+                }
+                let size: usize = python_hand(py, None, Some("uniform"), None, seed).unwrap().iter().map(|&count| count as usize).sum();
+                assert!((MINIMUM_HAND_SIZE as usize..=TileSet::standard().maximum_hand_size()).contains(&size));
+            }
+            // A size only goes with a fixed distribution, and every hand must be able to have the vowels asked for
+            for error in [
+                py_generate_hand(py, Some(15), Some("uniform"), None, None, MINIMUM_HAND_SIZE as usize, None, None, 0, false).unwrap_err(),
+                py_generate_hand(py, None, Some("fixed"), None, None, MINIMUM_HAND_SIZE as usize, None, None, 0, false).unwrap_err(),
+                py_generate_hand(py, None, Some("log"), None, None, MINIMUM_HAND_SIZE as usize, None, None, 0, false).unwrap_err(),
+                py_generate_hand(py, Some(5), None, None, None, MINIMUM_HAND_SIZE as usize, None, None, 6, false).unwrap_err()
+            ] {
+                assert!(error.is_instance_of::<PyValueError>(py), "{}", error);
+            }
+        });
+    }
+
+    #[test]
+    fn hands_convert_between_strings_dicts_and_lists() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let dict: HashMap<String, u8> = string_to_hand(py, "banana", false).unwrap().extract(py).unwrap();
+            assert_eq!(dict, HashMap::from([("A".to_owned(), 3), ("B".to_owned(), 1), ("N".to_owned(), 2)]));
+            let list: Vec<u8> = string_to_hand(py, "BANANA", true).unwrap().extract(py).unwrap();
+            assert_eq!(list.len(), 26);
+            assert_eq!((list[0], list[1], list[13]), (3, 1, 2));
+            for hand in [dict.into_py(py), list.into_py(py), "nabana".into_py(py)] {
+                assert_eq!(hand_to_string(hand.as_ref(py)).unwrap(), "AAABNN");
+            }
+            for invalid in [vec![1u16; 25].into_py(py), vec![256u16; 26].into_py(py), "B4NANA".into_py(py)] {
+                assert!(hand_to_string(invalid.as_ref(py)).is_err(), "{} was accepted", invalid);
+            }
+            assert!(string_to_hand(py, "CAT!", false).is_err());
+        });
+    }
+}
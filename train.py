@@ -1,3 +1,6 @@
 from board_generator import board_generator
 
-print(board_generator.sum_as_string(3, 4))
\ No newline at end of file
+with open("new_short_dictionary.txt") as f:
+    words = f.read().split()
+for board in board_generator.generate_boards(words, 21, 10):
+    print(board, end="\n\n")
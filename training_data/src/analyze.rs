@@ -0,0 +1,112 @@
+//! Pairwise letter statistics of an existing dataset of boards (the `analyze adjacency` subcommand), such as for a baseline model to
+//! compare a GAN against
+use std::{fs, path::Path};
+use bananagrams_core::{Board, EMPTY_VALUE, ENGLISH_ALPHABET_SIZE};
+
+/// A count for each pair of letters, indexed by the first letter then the second
+type LetterPairs = [[u64; ENGLISH_ALPHABET_SIZE]; ENGLISH_ALPHABET_SIZE];
+
+/// Name of the file the horizontal neighbor counts are written to
+const HORIZONTAL_FILE_NAME: &str = "horizontal.csv";
+/// Name of the file the vertical neighbor counts are written to
+const VERTICAL_FILE_NAME: &str = "vertical.csv";
+/// Name of the file the co-occurrence counts are written to
+const COOCCURRENCE_FILE_NAME: &str = "cooccurrence.csv";
+
+/// How often each pair of letters are next to each other, and are on the same board, across the boards of a dataset
+pub struct AdjacencyStats {
+    /// Number of boards recorded
+    boards: u64,
+    /// Number of times each letter is immediately left of each letter
+    horizontal: LetterPairs,
+    /// Number of times each letter is immediately above each letter
+    vertical: LetterPairs,
+    /// Number of boards with both letters of each pair (so the diagonal is the number of boards with each letter)
+    cooccurrence: LetterPairs
+}
+impl Default for AdjacencyStats {
+    fn default() -> Self {
+        let pairs = [[0; ENGLISH_ALPHABET_SIZE]; ENGLISH_ALPHABET_SIZE];
+        AdjacencyStats { boards: 0, horizontal: pairs, vertical: pairs, cooccurrence: pairs }
+    }
+}
+impl AdjacencyStats {
+    /// Records a board, counting only pairs of tiles (never an empty cell); tiles played with a blank count as their letter
+    /// # Arguments
+    /// * `board` - The board
+    pub fn record_board(&mut self, board: &Board) {
+        self.boards += 1;
+        let (min_col, max_col, min_row, max_row) = match board.bounds() {
+            Some(bounds) => bounds,
+            None => return
+        };
+        let mut present = [false; ENGLISH_ALPHABET_SIZE];
+        for row in min_row..max_row+1 {
+            for col in min_col..max_col+1 {
+                let val = board.get_val(row, col);
+                if val == EMPTY_VALUE {
+                    continue;
+                }
+                present[val as usize] = true;
+                if col < max_col && board.get_val(row, col+1) != EMPTY_VALUE {
+                    self.horizontal[val as usize][board.get_val(row, col+1) as usize] += 1;
+                }
+                if row < max_row && board.get_val(row+1, col) != EMPTY_VALUE {
+                    self.vertical[val as usize][board.get_val(row+1, col) as usize] += 1;
+                }
+            }
+        }
+        for first in (0..ENGLISH_ALPHABET_SIZE).filter(|&letter| present[letter]) {
+            for second in (0..ENGLISH_ALPHABET_SIZE).filter(|&letter| present[letter]) {
+                self.cooccurrence[first][second] += 1;
+            }
+        }
+    }
+
+    /// Gets the number of boards recorded
+    pub fn boards(&self) -> u64 {
+        self.boards
+    }
+
+    /// Writes the horizontal neighbor, vertical neighbor, and co-occurrence counts to CSV files
+    /// # Arguments
+    /// * `out` - Directory to write `horizontal.csv`, `vertical.csv`, and `cooccurrence.csv` to (created if needed)
+    /// * `normalize` - Whether to write proportions rather than counts: each row of the neighbor counts is divided by its total (giving the
+    ///   chance of each letter following the row's letter), and the co-occurrence counts are divided by the number of boards (giving the
+    ///   chance of a board having both letters)
+    /// # Returns
+    /// * `Result` with nothing, or an error message if a file couldn't be written
+    pub fn write_csv(&self, out: &Path, normalize: bool) -> Result<(), String> {
+        fs::create_dir_all(out).map_err(|e| format!("Failed to create output directory {}: {}", out.display(), e))?;
+        let files = [(HORIZONTAL_FILE_NAME, &self.horizontal, None), (VERTICAL_FILE_NAME, &self.vertical, None), (COOCCURRENCE_FILE_NAME, &self.cooccurrence, Some(self.boards))];
+        for (name, pairs, total) in files {
+            let path = out.join(name);
+            fs::write(&path, matrix_to_csv(pairs, normalize, total)).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        }
+        Ok(())
+    }
+}
+
+/// Converts counts of letter pairs to a CSV matrix
+/// # Arguments
+/// * `pairs` - The counts
+/// * `normalize` - Whether to write proportions rather than counts
+/// * `total` - What to divide every count by when normalizing, or `None` to divide each row by its own total
+/// # Returns
+/// * `String` - A header row of the letters A-Z, then a row for each first letter starting with the letter and followed by its count (or
+///   proportion) with each second letter; a row or matrix with a total of 0 is all 0
+fn matrix_to_csv(pairs: &LetterPairs, normalize: bool, total: Option<u64>) -> String {
+    let letters: Vec<String> = (0..ENGLISH_ALPHABET_SIZE).map(|letter| ((letter as u8 + b'A') as char).to_string()).collect();
+    let mut csv = format!(",{}\n", letters.join(","));
+    for (letter, row) in letters.iter().zip(pairs.iter()) {
+        let values: Vec<String> = match normalize {
+            true => {
+                let total = total.unwrap_or_else(|| row.iter().sum()).max(1) as f64;
+                row.iter().map(|&count| (count as f64 / total).to_string()).collect()
+            },
+            false => row.iter().map(|count| count.to_string()).collect()
+        };
+        csv.push_str(&format!("{},{}\n", letter, values.join(",")));
+    }
+    csv
+}
@@ -0,0 +1,235 @@
+//! Statistics about an existing dataset of boards (the `stats` subcommand), gathered a board at a time so that the boards never have to
+//! all be in memory at once
+use std::collections::BTreeMap;
+use hashbrown::{HashMap, HashSet};
+use serde::Serialize;
+use bananagrams_core::{Board, Letters, SolveFailure, SolveMetrics, TileSet, EMPTY_VALUE, ENGLISH_ALPHABET_SIZE, extract_words};
+use crate::board_hash;
+use crate::summary::Distribution;
+
+/// Number of the most common words to report
+const TOP_WORDS: usize = 50;
+
+/// Statistics collected about the boards in a dataset and the hands that couldn't be solved
+#[derive(Default)]
+pub struct DatasetStats {
+    /// Number of boards solved and number of hands failed, for each hand size
+    by_hand_size: BTreeMap<usize, (usize, usize)>,
+    /// Number of hands failed, by the reason why (see `SolveFailure::name`)
+    failures: BTreeMap<&'static str, usize>,
+    /// Number of boards with each number of tiles
+    tiles: BTreeMap<usize, usize>,
+    /// Number of boards with each width
+    widths: BTreeMap<usize, usize>,
+    /// Number of boards with each height
+    heights: BTreeMap<usize, usize>,
+    /// Number of tiles of each letter across every board
+    letters: [usize; ENGLISH_ALPHABET_SIZE],
+    /// Number of times each word appears across every board
+    words: HashMap<String, usize>,
+    /// Hash of the canonical form of each distinct board (see `board_hash`)
+    distinct: HashSet<u64>,
+    /// Number of boards that are a rotation, reflection, or copy of an earlier board
+    duplicates: usize,
+    /// How hard each board that recorded it was to solve, along with the time taken in milliseconds
+    metrics: Vec<(SolveMetrics, u64)>
+}
+impl DatasetStats {
+    /// Records a board
+    /// # Arguments
+    /// * `hand` - The hand that was solved
+    /// * `board` - The solved board
+    /// * `metrics` - How hard it was to solve along with the time taken in milliseconds, if its file recorded it
+    pub fn record_board(&mut self, hand: &Letters, board: &Board, metrics: Option<(SolveMetrics, u64)>) {
+        self.by_hand_size.entry(hand.iter().map(|&count| count as usize).sum()).or_default().0 += 1;
+        self.metrics.extend(metrics);
+        let (min_col, max_col, min_row, max_row) = match board.bounds() {
+            Some(bounds) => bounds,
+            None => {
+                *self.tiles.entry(0).or_default() += 1;
+                return;
+            }
+        };
+        let mut tiles = 0;
+        for row in min_row..max_row+1 {
+            for col in min_col..max_col+1 {
+                let val = board.get_val(row, col);
+                if val != EMPTY_VALUE {
+                    self.letters[val as usize] += 1;
+                    tiles += 1;
+                }
+            }
+        }
+        *self.tiles.entry(tiles).or_default() += 1;
+        *self.widths.entry(max_col + 1 - min_col).or_default() += 1;
+        *self.heights.entry(max_row + 1 - min_row).or_default() += 1;
+        for (word, _, _, _) in extract_words(board, min_col, max_col, min_row, max_row) {
+            *self.words.entry(word).or_default() += 1;
+        }
+        if !self.distinct.insert(board_hash(board, min_col, max_col, min_row, max_row)) {
+            self.duplicates += 1;
+        }
+    }
+
+    /// Records a hand that couldn't be solved
+    /// # Arguments
+    /// * `hand_size` - Number of letters in the hand
+    /// * `reason` - Why it couldn't be solved
+    pub fn record_failure(&mut self, hand_size: usize, reason: SolveFailure) {
+        self.by_hand_size.entry(hand_size).or_default().1 += 1;
+        *self.failures.entry(reason.name()).or_default() += 1;
+    }
+
+    /// Summarizes the statistics
+    /// # Returns
+    /// * `DatasetReport` - The summary
+    pub fn report(&self) -> DatasetReport {
+        let standard: Letters = TileSet::standard().counts();
+        let (total_tiles, total_standard) = (self.letters.iter().sum::<usize>(), standard.iter().map(|&count| count as usize).sum::<usize>());
+        let mut top_words: Vec<WordCount> = self.words.iter().map(|(word, &count)| WordCount { word: word.clone(), count }).collect();
+        top_words.sort_unstable_by(|a, b| b.count.cmp(&a.count).then_with(|| a.word.cmp(&b.word)));
+        top_words.truncate(TOP_WORDS);
+        DatasetReport {
+            boards: self.by_hand_size.values().map(|&(solved, _)| solved).sum(),
+            by_hand_size: self.by_hand_size.iter().map(|(&hand_size, &(solved, failed))| HandSizeCount {
+                hand_size,
+                solved,
+                failed,
+                failure_rate: failed as f64 / (solved + failed) as f64
+            }).collect(),
+            failures: self.failures.clone(),
+            tiles: self.tiles.clone(),
+            widths: self.widths.clone(),
+            heights: self.heights.clone(),
+            letters: (0..ENGLISH_ALPHABET_SIZE).map(|letter| LetterUsage {
+                letter: (letter as u8 + b'A') as char,
+                count: self.letters[letter],
+                share: if total_tiles == 0 { 0.0 } else { self.letters[letter] as f64 / total_tiles as f64 },
+                standard_share: standard[letter] as f64 / total_standard as f64
+            }).collect(),
+            top_words,
+            distinct_boards: self.distinct.len(),
+            duplicates: self.duplicates,
+            difficulty: (!self.metrics.is_empty()).then(|| Difficulty {
+                boards: self.metrics.len(),
+                words_checked: Distribution::of(self.metrics.iter().map(|(metrics, _)| metrics.words_checked).collect()),
+                depth: Distribution::of(self.metrics.iter().map(|(metrics, _)| metrics.depth).collect()),
+                failed_first_words: Distribution::of(self.metrics.iter().map(|(metrics, _)| metrics.failed_first_words).collect()),
+                elapsed_ms: Distribution::of(self.metrics.iter().map(|&(_, elapsed_ms)| elapsed_ms as usize).collect())
+            })
+        }
+    }
+}
+
+/// Summary of a dataset, as printed by the `stats` subcommand
+#[derive(Serialize)]
+pub struct DatasetReport {
+    /// Number of boards
+    boards: usize,
+    /// Boards solved and hands failed of each size
+    by_hand_size: Vec<HandSizeCount>,
+    /// Number of hands failed, by the reason why
+    failures: BTreeMap<&'static str, usize>,
+    /// Number of boards with each number of tiles
+    tiles: BTreeMap<usize, usize>,
+    /// Number of boards with each width
+    widths: BTreeMap<usize, usize>,
+    /// Number of boards with each height
+    heights: BTreeMap<usize, usize>,
+    /// How often each letter is played, compared with the standard tile set
+    letters: Vec<LetterUsage>,
+    /// The most common words, most common first
+    top_words: Vec<WordCount>,
+    /// Number of boards that aren't a rotation, reflection, or copy of another
+    distinct_boards: usize,
+    /// Number of boards that are a rotation, reflection, or copy of an earlier board
+    duplicates: usize,
+    /// How hard the boards that recorded it were to solve
+    difficulty: Option<Difficulty>
+}
+impl DatasetReport {
+    /// Prints the summary
+    pub fn print(&self) {
+        println!("{:>9} {:>10} {:>10} {:>12}", "Hand size", "Solved", "Failed", "Failure rate");
+        for count in self.by_hand_size.iter() {
+            println!("{:>9} {:>10} {:>10} {:>11.1}%", count.hand_size, count.solved, count.failed, 100.0 * count.failure_rate);
+        }
+        let (solved, failed) = self.by_hand_size.iter().fold((0, 0), |(solved, failed), count| (solved + count.solved, failed + count.failed));
+        println!("{:>9} {:>10} {:>10} {:>11.1}%", "Total", solved, failed, if solved + failed == 0 { 0.0 } else { 100.0 * failed as f64 / (solved + failed) as f64 });
+        if !self.failures.is_empty() {
+            let reasons: Vec<String> = self.failures.iter().map(|(reason, count)| format!("{} {}", count, reason)).collect();
+            println!("Failures by reason: {}", reasons.join(", "));
+        }
+        if let Some(difficulty) = &self.difficulty {
+            println!("Difficulty of the {} boards that recorded it:", difficulty.boards);
+            let distributions = [("Words checked", &difficulty.words_checked), ("Words played", &difficulty.depth), ("First words given up on", &difficulty.failed_first_words), ("Time (ms)", &difficulty.elapsed_ms)];
+            for (name, distribution) in distributions {
+                println!("  {}: {}", name, distribution.describe().expect("No boards!"));
+            }
+        }
+        if self.boards == 0 {
+            return;
+        }
+        let distribution = |counts: &BTreeMap<usize, usize>| counts.iter().map(|(size, count)| format!("{}: {}", size, count)).collect::<Vec<String>>().join(", ");
+        println!("Tiles per board: {}", distribution(&self.tiles));
+        println!("Board widths: {}", distribution(&self.widths));
+        println!("Board heights: {}", distribution(&self.heights));
+        println!("Distinct boards: {} ({} duplicates, counting rotations and reflections)", self.distinct_boards, self.duplicates);
+        println!("{:>6} {:>10} {:>7} {:>9} {:>6}", "Letter", "Tiles", "Share", "Standard", "Ratio");
+        for usage in self.letters.iter() {
+            println!("{:>6} {:>10} {:>6.2}% {:>8.2}% {:>6.2}", usage.letter, usage.count, 100.0 * usage.share, 100.0 * usage.standard_share, usage.share / usage.standard_share);
+        }
+        let words: Vec<String> = self.top_words.iter().map(|word| format!("{} ({})", word.word, word.count)).collect();
+        println!("Most common words: {}", words.join(", "));
+    }
+}
+
+/// Boards solved and hands failed of one size
+#[derive(Serialize)]
+struct HandSizeCount {
+    /// Number of letters in the hands
+    hand_size: usize,
+    /// Number of boards solved
+    solved: usize,
+    /// Number of hands that couldn't be solved
+    failed: usize,
+    /// Proportion of the hands that couldn't be solved
+    failure_rate: f64
+}
+
+/// How often a letter is played
+#[derive(Serialize)]
+struct LetterUsage {
+    /// The letter
+    letter: char,
+    /// Number of tiles of the letter across every board
+    count: usize,
+    /// Proportion of all tiles played that are the letter
+    share: f64,
+    /// Proportion of the standard tile set that is the letter
+    standard_share: f64
+}
+
+/// How often a word is played
+#[derive(Serialize)]
+struct WordCount {
+    /// The word
+    word: String,
+    /// Number of times it appears across every board
+    count: usize
+}
+
+/// How hard the boards that recorded it were to solve
+#[derive(Serialize)]
+struct Difficulty {
+    /// Number of boards that recorded it
+    boards: usize,
+    /// Number of words checked to solve each board
+    words_checked: Distribution,
+    /// Number of words played to make each board
+    depth: Distribution,
+    /// Number of first words given up on before solving each board
+    failed_first_words: Distribution,
+    /// Time taken to solve each board, in milliseconds
+    elapsed_ms: Distribution
+}
@@ -0,0 +1,195 @@
+//! Simulated multi-player games of Bananagrams, played by the solver from a shared pool of tiles
+use std::sync::atomic::AtomicBool;
+use rand::prelude::*;
+use serde::Serialize;
+use bananagrams_core::{Board, Letters, SolveOptions, TilePool, TileSet, Word, ENGLISH_ALPHABET_SIZE, board_rows, complete_board, letters_to_string, official_hand_size, peel, play_bananagrams};
+
+/// Smallest number of players in a game
+pub const MINIMUM_PLAYERS: usize = 2;
+/// Largest number of players in a game
+pub const MAXIMUM_PLAYERS: usize = 8;
+/// Number of tiles drawn in a dump, in exchange for the one put back
+const TILES_PER_DUMP: usize = 3;
+/// Maximum number of rounds to play before calling the game off without a winner (every player gets a turn each round)
+const MAXIMUM_ROUNDS: usize = 500;
+
+/// Options the solver plays each game with
+pub struct GameOptions<'a> {
+    /// The words to play with, sorted longest first
+    pub dictionary: &'a Vec<Word>,
+    /// The tiles the pool starts with
+    pub tiles: &'a TileSet,
+    /// Maximum number of words the solver checks each time a player tries to use up their tiles
+    pub max_words_checked: usize,
+    /// Number of rows/columns in the boards to solve on
+    pub board_size: usize,
+    /// Minimum length of a word on the board
+    pub min_word_length: usize,
+    /// Flag that, once set, ends every game as soon as possible
+    pub stop: &'a AtomicBool
+}
+
+/// A player of a game
+struct Player {
+    /// The player's board, on which every word is valid, if they've played any of their tiles
+    board: Option<Board>,
+    /// Number of each letter the player has drawn but not yet played
+    unplayed: Letters,
+    /// Number of times the player dumped a tile
+    dumps: usize
+}
+impl Player {
+    /// Plays as many of the player's unplayed tiles as possible: all of them onto their board if the solver can find a way, otherwise
+    /// (when the player has no board yet) as many as fit on the best partial board the solver found
+    /// # Arguments
+    /// * `options` - Options the game is played with
+    /// # Returns
+    /// * `bool` - Whether every tile was played
+    fn play(&mut self, options: &GameOptions) -> bool {
+        let tiles: usize = self.unplayed.iter().map(|&count| count as usize).sum();
+        if tiles == 0 {
+            return true;
+        }
+        let solve_options = SolveOptions {
+            max_words_checked: options.max_words_checked,
+            stop: Some(options.stop),
+            board_size: options.board_size,
+            min_word_length: options.min_word_length,
+            ..SolveOptions::default()
+        };
+        let result = match self.board.as_ref() {
+            Some(board) if tiles == 1 => {
+                let letter = self.unplayed.iter().position(|&count| count > 0).expect("No unplayed tile!") as u8;
+                peel::<ENGLISH_ALPHABET_SIZE>(board, letter, options.dictionary, &solve_options).map_err(|_| None)
+            },
+            Some(board) => complete_board(board, self.unplayed, 0, options.dictionary, &solve_options).map_err(|_| None),
+            None => play_bananagrams(self.unplayed, 0, options.dictionary, &SolveOptions { allow_partial: true, ..solve_options }).map_err(|(_, _, partial)| partial)
+        };
+        match result {
+            Ok(solution) => {
+                self.board = Some(solution.board);
+                self.unplayed = [0; ENGLISH_ALPHABET_SIZE];
+                true
+            },
+            Err(Some(partial)) => {
+                self.board = Some(partial.board);
+                self.unplayed = partial.unused_letters;
+                false
+            },
+            Err(None) => false
+        }
+    }
+
+    /// Dumps the unplayed tile the player is least likely to be able to play (the scarcest letter in the tile set), drawing `TILES_PER_DUMP`
+    /// in its place and then putting it back in the pool
+    /// # Arguments
+    /// * `pool` - The tiles left to draw
+    /// * `scarcity` - Number of tiles of each letter in the full tile set
+    /// * `rng` - Random number generator to draw with
+    /// # Returns
+    /// * `bool` - Whether a tile was dumped (which is only allowed while the pool has at least `TILES_PER_DUMP` tiles)
+    fn dump(&mut self, pool: &mut TilePool, scarcity: &Letters, rng: &mut impl Rng) -> bool {
+        let letter = match (0..ENGLISH_ALPHABET_SIZE).filter(|&letter| self.unplayed[letter] > 0).min_by_key(|&letter| scarcity[letter]) {
+            Some(letter) => letter,
+            None => return false
+        };
+        let drawn: Letters = match pool.draw(rng, TILES_PER_DUMP) {
+            Some(drawn) => drawn,
+            None => return false
+        };
+        self.unplayed[letter] -= 1;
+        let mut dumped: Letters = [0; ENGLISH_ALPHABET_SIZE];
+        dumped[letter] = 1;
+        pool.put_back(&dumped);
+        self.draw(&drawn);
+        self.dumps += 1;
+        true
+    }
+
+    /// Adds tiles drawn from the pool to the player's unplayed tiles
+    /// # Arguments
+    /// * `drawn` - Number of each letter drawn
+    fn draw(&mut self, drawn: &Letters) {
+        for (unplayed, count) in self.unplayed.iter_mut().zip(drawn.iter()) {
+            *unplayed += count;
+        }
+    }
+}
+
+/// A player's part in a finished game, written as part of a `GameRecord`
+#[derive(Serialize)]
+pub struct PlayerRecord {
+    /// The player's final board cropped to its letters as a list of rows (see `board_rows`), empty if they never played a tile
+    board: Vec<String>,
+    /// The letters the player drew but never played
+    unplayed: String,
+    /// Number of times the player dumped a tile
+    dumps: usize,
+    /// Whether the player won the game
+    winner: bool
+}
+
+/// A finished game, written as a line of the games file
+#[derive(Serialize)]
+pub struct GameRecord {
+    /// ID of the game
+    game: usize,
+    /// Seed of the game's random number generator
+    seed: u64,
+    /// Number of rounds played (in each of which every player with unplayed tiles tries to play them)
+    rounds: usize,
+    /// Number of peels (in each of which every player drew a tile)
+    peels: usize,
+    /// Index of the player who won, if anyone did before `MAXIMUM_ROUNDS` rounds
+    pub winner: Option<usize>,
+    /// Each player's final state, in turn order
+    players: Vec<PlayerRecord>
+}
+
+/// Simulates a game of Bananagrams: each player draws their starting tiles (see `official_hand_size`) from a full pool; then every
+/// round each player with unplayed tiles tries to play them all, dumping one if they can't; once any player has played all of their
+/// tiles, every player draws another (a peel), unless fewer tiles are left than players, in which case such a player wins
+/// # Arguments
+/// * `game` - ID of the game
+/// * `players` - Number of players, from `MINIMUM_PLAYERS` to `MAXIMUM_PLAYERS`
+/// * `seed` - Seed to draw the tiles with
+/// * `options` - Options to play with
+/// # Returns
+/// * `GameRecord` - The finished game
+pub fn play_game(game: usize, players: usize, seed: u64, options: &GameOptions) -> GameRecord {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let scarcity: Letters = options.tiles.counts();
+    let mut pool = TilePool::new(options.tiles);
+    // (A tile set too small for the official starting hands is split evenly instead)
+    let tiles_each = official_hand_size(players).min(pool.len() / players);
+    let mut hands: Vec<Player> = pool.split(&mut rng, players, tiles_each).expect("Not enough tiles to deal!").into_iter()
+        .map(|unplayed| Player { board: None, unplayed, dumps: 0 }).collect();
+    let (mut rounds, mut peels, mut winner) = (0, 0, None);
+    while rounds < MAXIMUM_ROUNDS && !options.stop.load(std::sync::atomic::Ordering::Relaxed) {
+        rounds += 1;
+        for player in hands.iter_mut() {
+            if !player.play(options) {
+                player.dump(&mut pool, &scarcity, &mut rng);
+            }
+        }
+        let done: Vec<usize> = (0..players).filter(|&index| hands[index].unplayed.iter().all(|&count| count == 0)).collect();
+        if !done.is_empty() {
+            if pool.len() < players {
+                // Players finishing in the same round are equally fast, so the one calling "Bananas" first is chosen at random
+                winner = done.choose(&mut rng).copied();
+                break;
+            }
+            for player in hands.iter_mut() {
+                player.draw(&pool.draw(&mut rng, 1).expect("Peeled from an empty pool!"));
+            }
+            peels += 1;
+        }
+    }
+    let players = hands.into_iter().enumerate().map(|(index, player)| PlayerRecord {
+        board: player.board.as_ref().map(board_rows).unwrap_or_default(),
+        unplayed: letters_to_string(&player.unplayed),
+        dumps: player.dumps,
+        winner: winner == Some(index)
+    }).collect();
+    GameRecord { game, seed, rounds, peels, winner, players }
+}
@@ -1,886 +1,2502 @@
-use std::{cmp, f32::consts::E, fmt, fs, thread};
-use hashbrown::HashSet;     // For faster default hash (ahash)
+use std::{fs::{self, File, OpenOptions}, hash::{DefaultHasher, Hash, Hasher}, io::{self, BufWriter, IsTerminal, Write}, iter, path::{Path, PathBuf}, process, thread, time::{Duration, Instant}};
+use std::sync::{Arc, Mutex, mpsc, atomic::{AtomicBool, AtomicUsize, Ordering}};
 use rand::prelude::*;
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
-use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
-
-/// A numeric representation of a word
-type Word = Vec<usize>;
-/// Represents a hand of letters
-type Letters = [usize; 26];
-
-/// The maximum length of any word in the dictionary
-const MAX_WORD_LENGTH: usize = 17;
-/// Value of an empty cell on the board
-const EMPTY_VALUE: usize = 30;
-/// Number rows/columns in the board
-const BOARD_SIZE: usize = 144;
-/// Number of letters present on the board that can be used in a word (higher will result in fewer words being filtered out)
-const FILTER_LETTERS_ON_BOARD: u8 = 2;
-/// Maximum number of words to check before the solver stops trying a given word
-const MAXIMUM_WORDS_CHECKED: usize = 500_000;
-/// Minimum size of hand of letters to generate
-const MINIMUM_HAND_SIZE: f32 = 11.0;
-/// Maximum size of hand of letters to generate
-const MAXIMUM_HAND_SIZE: f32 = 72.0;
-/// Base to use when generating the 
-const BASE: f32 = E;
-/// All the letters present in standard Bananagrams as ASCII values
-const TO_CHOOSE_FROM: [usize; 144] = [65, 65, 65, 65, 65, 65, 65, 65, 65, 65, 65, 65, 65, 66, 66, 66, 67, 67, 67, 68, 68, 68, 68, 68,
-                                      68, 69, 69, 69, 69, 69, 69, 69, 69, 69, 69, 69, 69, 69, 69, 69, 69, 69, 69, 70, 70, 70, 71, 71,
-                                      71, 71, 72, 72, 72, 73, 73, 73, 73, 73, 73, 73, 73, 73, 73, 73, 73, 74, 74, 75, 75, 76, 76, 76,
-                                      76, 76, 77, 77, 77, 78, 78, 78, 78, 78, 78, 78, 78, 79, 79, 79, 79, 79, 79, 79, 79, 79, 79, 79,
-                                      80, 80, 80, 81, 81, 82, 82, 82, 82, 82, 82, 82, 82, 82, 83, 83, 83, 83, 83, 83, 84, 84, 84, 84,
-                                      84, 84, 84, 84, 84, 85, 85, 85, 85, 85, 85, 86, 86, 86, 87, 87, 87, 88, 88, 89, 89, 89, 90, 90];
-
-/// A thin wrapper for handling the board
-#[derive(Clone)]
-struct Board {
-    /// The underlying vector of the board (as in optimization level 0 the array overflows the stack)
-    arr: Vec<usize>
-}
-impl Board {
-    /// Creates a new board of dimensions `BOARD_SIZE`x`BOARD_SIZE` filled with the `EMPTY_VALUE`
-    fn new() -> Board {
-        return Board { arr: vec![EMPTY_VALUE; BOARD_SIZE*BOARD_SIZE] }
-    }
-
-    /// Unsafely gets a value from the board at the given index
+use clap::{Args, Parser, Subcommand, ValueEnum, builder::PossibleValuesParser};
+use hashbrown::HashSet;
+use flate2::write::GzEncoder;
+use serde::{Deserialize, Serialize, Serializer, ser::SerializeStruct};
+use log::{Level, debug, log_enabled};
+use bananagrams_core::{bench, msgpack, render, Alphabet, Board, BoardParseError, Direction, FirstWordStrategy, HandSizeDistribution, Letters, PlayedWord, PrettyOptions, SearchHeuristic, SolveFailure, SolveMetrics, SolveOptions, Solution, TilePool, TileSet, Word, BGB_FORMAT_VERSION, BGB_METRICS_FORMAT_VERSION, DEFAULT_FREQUENCY_TEMPERATURE, DEFAULT_MIN_WORD_LENGTH, EMPTY_VALUE, ENGLISH_ALPHABET_SIZE, BGB_MAX_BOARD_SIZE, BOARD_SIZE, MAXIMUM_WORDS_CHECKED, MINIMUM_HAND_SIZE, board_rows, board_to_bytes, board_to_pretty_string, board_to_string, canonical_form, check_hand_against_pool, complete_bgb_records, complete_board, convert_array_to_word, corrupt_board, decompress, dictionary_hash, distinct_symmetries, extract_words, find_banned_words, generate_hand, is_makeable, letter_masks, letters_to_string, load_dictionary, load_weighted_dictionary, metrics_to_bytes, order_by_frequency, parse_bgb, parse_bgb_metrics, peel, parse_weighted_dictionary, play_bananagrams, play_bananagrams_multi, read_boards, remove_short_words, retain_words, seeded_rng, shuffle_dictionary, validate_board, write_bgb2};
+#[cfg(any(feature = "sqlite", feature = "arrow"))]
+use bananagrams_core::BGB_BLANK_FLAG;
+use bananagrams_core::dictionary_cache::load_preprocessed_dictionary;
+
+#[cfg(feature = "sqlite")]
+mod sqlite;
+mod analyze;
+mod dataset;
+mod game;
+mod merge;
+mod npy;
+#[cfg(feature = "arrow")]
+mod parquet;
+mod progress;
+#[cfg(feature = "serve")]
+mod serve;
+mod shuffle;
+mod split;
+mod stream;
+mod stratify;
+mod summary;
+mod validate;
+mod writer;
+
+use analyze::AdjacencyStats;
+use dataset::DatasetStats;
+use progress::Progress;
+use stratify::{BucketCount, Strata};
+use stream::StreamFormat;
+use summary::RunStats;
+use writer::{GeneratedBoard, Writer, WriterEvent};
+
+/// Number of boards between each log message of progress
+const PROGRESS_LOG_INTERVAL: usize = 50;
+/// Maximum number of times to try corrupting a board into an invalid one before giving up on that corrupted copy
+const MAXIMUM_CORRUPTION_ATTEMPTS: usize = 100;
+/// Name of the file in the output directory listing every file of boards written
+const MANIFEST_FILE_NAME: &str = "manifest.json";
+/// Name of the file in the output directory the summary of the run is written to with `--summary`
+const SUMMARY_FILE_NAME: &str = "summary.json";
+
+/// Format of the file each thread writes its boards to
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    /// The compact binary `.bgb` format (see `board_to_bytes`)
+    Bgb,
+    /// JSON Lines, with an object per board that also records how it was made (see `BoardRecord::to_json`)
+    Jsonl,
+    /// CSV with a row per tile (see `BoardRecord::to_csv`)
+    Csv,
+    /// A NumPy `.npy` array of every board in one-hot form, centered in the size given by `--pad-to` (see `board_to_onehot`)
+    NpyOnehot,
+    /// MessagePack, with a map per board holding the same fields as `Jsonl`, each preceded by its length (see `BoardRecord::to_msgpack`)
+    Msgpack,
+    /// An Apache Parquet table with a row per board, written a row group at a time (see `parquet::ParquetBoard`)
+    #[cfg(feature = "arrow")]
+    Parquet,
+    /// A single SQLite database written to by every thread, with tables of boards and of the words on them (see `sqlite::SqliteBoard`)
+    #[cfg(feature = "sqlite")]
+    Sqlite
+}
+impl OutputFormat {
+    /// Gets the extension of files in the format
+    fn extension(self) -> &'static str {
+        match self {
+            OutputFormat::Bgb => "bgb",
+            OutputFormat::Jsonl => "jsonl",
+            OutputFormat::Csv => "csv",
+            OutputFormat::NpyOnehot => "npy",
+            OutputFormat::Msgpack => "msgpack",
+            #[cfg(feature = "arrow")]
+            OutputFormat::Parquet => "parquet",
+            #[cfg(feature = "sqlite")]
+            OutputFormat::Sqlite => "sqlite"
+        }
+    }
+
+    /// Gets the bytes each file in the format starts with
+    fn header(self) -> Vec<u8> {
+        match self {
+            OutputFormat::Bgb => vec![BGB_FORMAT_VERSION],
+            OutputFormat::Jsonl => Vec::new(),
+            OutputFormat::Csv => b"board_id,row,col,letter\n".to_vec(),
+            // Depends on the size the boards are padded to, so it's written by `GenerateArgs::board_header`
+            OutputFormat::NpyOnehot => Vec::new(),
+            OutputFormat::Msgpack => Vec::new(),
+            // Written by `parquet::ParquetWriter`
+            #[cfg(feature = "arrow")]
+            OutputFormat::Parquet => Vec::new(),
+            #[cfg(feature = "sqlite")]
+            OutputFormat::Sqlite => Vec::new()
+        }
+    }
+
+    /// Gets the version of the format, if it has one
+    fn version(self) -> Option<u8> {
+        match self {
+            OutputFormat::Bgb => Some(BGB_FORMAT_VERSION),
+            OutputFormat::Jsonl | OutputFormat::Csv | OutputFormat::NpyOnehot | OutputFormat::Msgpack => None,
+            #[cfg(feature = "arrow")]
+            OutputFormat::Parquet => None,
+            #[cfg(feature = "sqlite")]
+            OutputFormat::Sqlite => None
+        }
+    }
+
+    /// Counts the boards in a file in the format
     /// # Arguments
-    /// * `row` - Row index of the value to get (must be less than `BOARD_SIZE`)
-    /// * `col` - Column index of the value to get (must be less than `BOARD_SIZE`)
+    /// * `path` - Path to the file, which may be compressed
     /// # Returns
-    /// `usize` - The value in the board at `(row, col)` (if either `row` or `col` are greater than `BOARD_SIZE` this will be undefined behavior)
-    fn get_val(&self, row: usize, col: usize) -> usize {
-        return unsafe { *self.arr.get_unchecked(row*BOARD_SIZE + col) };
+    /// * `Result` with the number of boards, or an error message if the file couldn't be read or is invalid or cut off
+    fn count_boards(self, path: &Path) -> Result<usize, String> {
+        let read = || {
+            let bytes = fs::read(path).map_err(|e| e.to_string())?;
+            match decompress(&bytes)? {
+                (_, true) => Err("File is cut off".to_string()),
+                (bytes, false) => Ok(bytes.into_owned())
+            }
+        };
+        match self {
+            OutputFormat::Bgb => parse_bgb::<ENGLISH_ALPHABET_SIZE>(&read()?).map(|boards| boards.len()),
+            OutputFormat::Jsonl => Ok(read()?.split(|&byte| byte == b'\n').filter(|line| !line.is_empty()).count()),
+            OutputFormat::Csv => {
+                let bytes = read()?;
+                let text = std::str::from_utf8(&bytes).map_err(|e| format!("File isn't valid UTF-8: {}", e))?;
+                // The rows of each board are consecutive, so each change of board ID starts a new board
+                let mut count = 0;
+                let mut previous_id = None;
+                for line in text.lines().skip(1).filter(|line| !line.is_empty()) {
+                    let id = line.split(',').next();
+                    if id != previous_id {
+                        count += 1;
+                        previous_id = id;
+                    }
+                }
+                Ok(count)
+            },
+            OutputFormat::NpyOnehot => npy::count_boards(&read()?),
+            OutputFormat::Msgpack => {
+                let bytes = read()?;
+                let ends = msgpack::complete_records(&bytes);
+                if ends.last().copied().unwrap_or(0) != bytes.len() {
+                    return Err("File is cut off".to_string());
+                }
+                Ok(ends.len())
+            },
+            #[cfg(feature = "arrow")]
+            OutputFormat::Parquet => parquet::count_boards(path),
+            #[cfg(feature = "sqlite")]
+            OutputFormat::Sqlite => sqlite::count_boards(path)
+        }
     }
+}
+
+/// Compression applied to the file each thread writes its boards to
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+enum Compression {
+    /// Write the boards uncompressed
+    None,
+    /// Compress with gzip (adding `.gz` to the file name)
+    Gzip,
+    /// Compress with zstd (adding `.zst` to the file name)
+    Zstd
+}
+impl Compression {
+    /// Gets the suffix added to the names of files with the compression
+    fn suffix(self) -> &'static str {
+        match self {
+            Compression::None => "",
+            Compression::Gzip => ".gz",
+            Compression::Zstd => ".zst"
+        }
+    }
+}
 
-    /// Unsafely sets a value in the board at the given index
+/// Order the solver tries the words of the dictionary in for each hand
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+enum WordOrder {
+    /// Longest first, in the order of the dictionary file within each length (the same for every hand)
+    Longest,
+    /// A random order for each hand, regardless of length (which makes solving slower, so more hands run out of words to check or time)
+    Random,
+    /// Longest first, in a random order within each length for each hand
+    ShuffledWithinLength
+}
+impl WordOrder {
+    /// Orders a dictionary for solving a hand
     /// # Arguments
-    /// * `row` - Row index of the value to get (must be less than `BOARD_SIZE`)
-    /// * `col` - Column index of the value to get (must be less than `BOARD_SIZE`)
-    /// * `val` - Value to set at `(row, col)` in the board (if either `row` or `col` are greater than `BOARD_SIZE` this will be undefined behavior)
-    fn set_val(&mut self, row: usize, col: usize, val: usize) {
-        let v = unsafe { self.arr.get_unchecked_mut(row*BOARD_SIZE + col) };
-        *v = val;
+    /// * `dictionary` - The words of the dictionary, longest first
+    /// * `rng` - Random number generator
+    /// # Returns
+    /// * `Option<Vec<Word>>` - The words in their new order, or `None` to keep the order of the `dictionary`
+    fn apply(self, dictionary: &[Word], rng: &mut impl Rng) -> Option<Vec<Word>> {
+        match self {
+            WordOrder::Longest => None,
+            WordOrder::Random => {
+                let mut shuffled = dictionary.to_vec();
+                shuffled.shuffle(rng);
+                Some(shuffled)
+            },
+            WordOrder::ShuffledWithinLength => Some(shuffle_dictionary(dictionary, rng))
+        }
     }
 }
 
-/// Converts a `board` to a `String`
-/// # Arguments
-/// * `board` - Board to display
-/// * `min_col` - Minimum occupied column index
-/// * `max_col` - Maximum occupied column index
-/// * `min_row` - Minimum occupied row index
-/// * `max_row` - Maximum occupied row index
-/// # Returns
-/// * `String` - `board` in string form (with all numbers converted to letters)
-fn board_to_string(board: &Board, min_col: usize, max_col: usize, min_row: usize, max_row: usize) -> String {
-    let mut board_string: Vec<char> = Vec::with_capacity((max_row-min_row)*(max_col-min_col));
-    for row in min_row..max_row+1 {
-        for col in min_col..max_col+1 {
-            if board.get_val(row, col) == EMPTY_VALUE {
-                board_string.push(' ');
-            }
-            else {
-                board_string.push((board.get_val(row, col) as u8+65) as char);
-            }
+/// Heuristic the solver orders the words it tries after the first by
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+enum Heuristic {
+    /// Try them in the order of the dictionary (see --word-order)
+    None,
+    /// Try those using the most letters scarce in the hand first (see `SearchHeuristic::RareFirst`)
+    RareFirst
+}
+impl From<Heuristic> for SearchHeuristic {
+    fn from(heuristic: Heuristic) -> SearchHeuristic {
+        match heuristic {
+            Heuristic::None => SearchHeuristic::Dictionary,
+            Heuristic::RareFirst => SearchHeuristic::RareFirst
         }
-        board_string.push('\n');
     }
-    let s: String = board_string.iter().collect();
-    return s.trim_end().to_owned();
 }
 
+/// How the sizes of the hands to solve are chosen (see `HandSizeDistribution`)
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+enum HandSizes {
+    /// Any size from --min-hand-size to --max-hand-size, with smaller sizes more likely (on a logarithmic scale)
+    LogScaled,
+    /// Any size from --min-hand-size to --max-hand-size, each equally likely
+    Uniform,
+    /// Always --hand-size
+    Fixed,
+    /// The size each player starts a game of --players with under the official rules (21 for 2-4 players, 15 for 5-6, or 11 for 7-8)
+    Official
+}
 
-/// Converts a word into a numeric vector representation
-/// # Arguments
-/// * `word` - String word to convert
-/// # Returns
-/// `Word` - numeric representation of `word`, with each letter converted from 65 ('A') to 90 ('Z')
-/// # See also
-/// `convert_array_to_word`
-fn convert_word_to_array(word: &str) -> Word {
-    word.chars().filter(|c| c.is_ascii_uppercase()).map(|c| (c as usize - 65)).collect()
+/// Writer for a file of boards, compressing them if requested
+enum BoardWriter {
+    /// Writes the boards uncompressed
+    Plain(BufWriter<File>),
+    /// Writes the boards as a gzip stream
+    Gzip(GzEncoder<BufWriter<File>>),
+    /// Writes the boards as a zstd frame
+    Zstd(zstd::Encoder<'static, BufWriter<File>>)
 }
+impl BoardWriter {
+    /// Creates a new `BoardWriter`
+    /// # Arguments
+    /// * `file` - File to write to
+    /// * `compression` - Compression to apply
+    /// # Returns
+    /// * `io::Result<BoardWriter>` - The writer, or an error if the encoder couldn't be created
+    fn new(file: File, compression: Compression) -> io::Result<BoardWriter> {
+        let writer = BufWriter::new(file);
+        Ok(match compression {
+            Compression::None => BoardWriter::Plain(writer),
+            Compression::Gzip => BoardWriter::Gzip(GzEncoder::new(writer, flate2::Compression::default())),
+            Compression::Zstd => BoardWriter::Zstd(zstd::Encoder::new(writer, 0)?)
+        })
+    }
 
-/// Checks whether a `word` can be made using the given `letters`
-/// # Arguments
-/// * `word` - The vector form of the word to check
-/// * `letters` - Length-26 array of the number of each letter in the hand
-/// # Returns
-/// * `bool` - Whether `word` can be made using `letters`
-fn is_makeable(word: &Word, letters: &Letters) -> bool {
-    let mut available_letters = letters.clone();
-    for letter in word.iter() {
-        if unsafe { available_letters.get_unchecked(*letter) } == &0 {
-            return false;
+    /// Writes out any remaining data and ends the compressed stream, so that the file is complete
+    /// # Returns
+    /// * `io::Result<()>` - Empty, or an error if the data couldn't be written
+    fn finish(self) -> io::Result<()> {
+        match self {
+            BoardWriter::Plain(mut writer) => writer.flush(),
+            BoardWriter::Gzip(encoder) => encoder.finish()?.flush(),
+            BoardWriter::Zstd(encoder) => encoder.finish()?.flush()
         }
-        let elem = unsafe { available_letters.get_unchecked_mut(*letter) };
-        *elem -= 1;
     }
-    return true;
 }
+impl Write for BoardWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            BoardWriter::Plain(writer) => writer.write(buf),
+            BoardWriter::Gzip(encoder) => encoder.write(buf),
+            BoardWriter::Zstd(encoder) => encoder.write(buf)
+        }
+    }
 
-/// Checks that a `board` is valid after a word is played horizontally, given the specified list of `valid_word`s
-/// Note that this does not check if all words are contiguous; this condition must be enforced elsewhere.
-/// # Arguments
-/// * `board` - `Board` being checked
-/// * `min_col` - Minimum x (column) index of the subsection of the `board` to be checked
-/// * `max_col` - Maximum x (column) index of the subsection of the `board` to be checked
-/// * `min_row` - Minimum y (row) index of the subsection of the `board` to be checked
-/// * `max_row` - Maximum y (row) index of the subsection of the `board` to be checked
-/// * `row` - Row of the word played
-/// * `start_col` - Starting column of the word played
-/// * `end_col` - Ending column of the word played
-/// * `valid_words` - HashSet of all valid words as `Vec<usize>`s
-/// # Returns
-/// `bool` - whether the given `board` is made only of valid words
-fn is_board_valid_horizontal(board: &Board, min_col: usize, max_col: usize, min_row: usize, max_row: usize, row: usize, start_col: usize, end_col: usize, valid_words: &HashSet<Word>) -> bool {
-    let mut current_letters: Vec<usize> = Vec::with_capacity(MAX_WORD_LENGTH);
-    // Find the furtherest left column that the new play is connected to
-    let mut minimum_col = start_col;
-    while minimum_col > min_col {
-        if board.get_val(row, minimum_col) == EMPTY_VALUE {
-            minimum_col += 1;
-            break;
+    /// Flushes everything written so far through to the file; for the compressed formats this ends the current block, so an interrupted
+    /// run still leaves a file that decompresses up to the last flush
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            BoardWriter::Plain(writer) => writer.flush(),
+            BoardWriter::Gzip(encoder) => encoder.flush(),
+            BoardWriter::Zstd(encoder) => encoder.flush()
         }
-        minimum_col -= 1;
     }
-    minimum_col = cmp::max(minimum_col, min_col);
-    // Check across the row where the word was played
-    for col_idx in minimum_col..max_col+1 {
-        // If we're not at an empty square, add it to the current word we're looking at
-        if board.get_val(row, col_idx) != EMPTY_VALUE {
-            current_letters.push(board.get_val(row, col_idx));
+}
+
+/// Everything written by a run, saved as `manifest.json` in the output directory
+#[derive(Serialize, Deserialize)]
+struct Manifest {
+    /// Format of the board files (as given to `--format`)
+    format: String,
+    /// Version of the format, if it has one
+    format_version: Option<u8>,
+    /// Compression of the board files (as given to `--compress`)
+    compression: String,
+    /// Seed that each thread's seed was derived from
+    seed: u64,
+    /// Hash of the dictionary used (see `dictionary_hash`), as hexadecimal
+    dictionary_hash: String,
+    /// Maximum number of boards generated into each file, if the files were sharded
+    shard_size: Option<usize>,
+    /// Every file of boards written, ordered by thread and then by index
+    shards: Vec<Shard>,
+    /// Boards generated from hands in each range of sizes, if stratified (see `--stratify`)
+    strata: Option<Vec<BucketCount>>
+}
+
+/// A file of boards listed in a `Manifest`
+#[derive(Serialize, Deserialize)]
+struct Shard {
+    /// Name of the file, relative to the directory of the manifest
+    file: String,
+    /// Index of the thread that wrote the file (0 for a SQLite database, which every thread writes to)
+    thread: usize,
+    /// Index of the file among those written by the thread
+    index: usize,
+    /// Number of boards in the file (including the rotations and reflections written by `--augment`)
+    boards: usize,
+    /// Size of the file
+    bytes: u64,
+    /// Seed of the thread that wrote the file (the seed of the run for a SQLite database)
+    seed: u64
+}
+
+/// A hand that couldn't be solved, written as a line of the file given to `--log-failures`
+#[derive(Serialize, Deserialize)]
+struct FailureRecord {
+    /// Number of each letter in the hand
+    hand: Letters,
+    /// Number of blanks in the hand
+    #[serde(default)]
+    blanks: u8,
+    /// Number of letters in the hand (not counting blanks)
+    hand_size: usize,
+    /// Why the hand couldn't be solved (see `SolveFailure::name`)
+    reason: String,
+    /// Number of words the solver checked before giving up
+    words_checked: usize,
+    /// Time spent trying to solve the hand, in milliseconds
+    elapsed_ms: u128
+}
+
+/// A solved board along with how it was made, from which every output format is written
+struct BoardRecord {
+    /// The hand of letters that was solved
+    hand: Letters,
+    /// Number of blanks in the hand (the tiles played with them are marked on the `board`)
+    blanks: u8,
+    /// The solved board
+    board: Board,
+    /// Minimum occupied column index
+    min_col: usize,
+    /// Maximum occupied column index
+    max_col: usize,
+    /// Minimum occupied row index
+    min_row: usize,
+    /// Maximum occupied row index
+    max_row: usize,
+    /// Number of words the solver checked to find the solution
+    words_checked: usize,
+    /// Number of words played to make the board
+    depth: usize,
+    /// Number of first words the solver gave up on before finding the solution (unknown for a partial solution)
+    failed_first_words: Option<usize>,
+    /// Time taken to solve the hand, in milliseconds
+    elapsed_ms: u128,
+    /// Index of the thread that solved the hand
+    thread: usize,
+    /// Seed of the thread's random number generator
+    seed: u64,
+    /// The number of each letter and the number of blanks left unplayed, if the board is only a partial solution (see `--allow-partial`)
+    unused: Option<(Letters, u8)>,
+    /// The ID of the game the board is a step of and the number of the step (0 for the solved hand, then 1 after the first peel, and so on),
+    /// if the board is from a simulated game (see `--simulate-game`)
+    sequence: Option<(usize, usize)>
+}
+// Serialized as an object giving the `hand` as the number of each letter, the number of `blanks`, the `board` cropped to its letters as a
+// list of rows (see `board_rows`), the `words_checked`, `depth`, `failed_first_words`, `elapsed_ms`, `thread`, and `seed`, whether the
+// board is `partial`, the `unused`
+// letters of a partial board as a string (with a `?` for each blank), and the `sequence` and `step` of a board from a simulated game; the board is only written as rows, since that's all that's needed to
+// train on (and is what earlier files have), so records can't be deserialized
+impl Serialize for BoardRecord {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut record = serializer.serialize_struct("BoardRecord", 11 + 2*usize::from(self.sequence.is_some()))?;
+        record.serialize_field("hand", &self.hand)?;
+        record.serialize_field("blanks", &self.blanks)?;
+        record.serialize_field("board", &board_rows(&self.board))?;
+        record.serialize_field("words_checked", &self.words_checked)?;
+        record.serialize_field("depth", &self.depth)?;
+        record.serialize_field("failed_first_words", &self.failed_first_words)?;
+        record.serialize_field("elapsed_ms", &self.elapsed_ms)?;
+        record.serialize_field("thread", &self.thread)?;
+        record.serialize_field("seed", &self.seed)?;
+        record.serialize_field("partial", &self.unused.is_some())?;
+        record.serialize_field("unused", &self.unused.map(|(letters, blanks)| letters_to_string(&letters) + &"?".repeat(blanks as usize)).unwrap_or_default())?;
+        if let Some((sequence, step)) = self.sequence {
+            record.serialize_field("sequence", &sequence)?;
+            record.serialize_field("step", &step)?;
         }
-        else {
-            // Turns out that checking with a set is faster than using a trie, at least for smaller hands
-            if current_letters.len() > 1 && !valid_words.contains(&current_letters) {
-                return false;
+        record.end()
+    }
+}
+impl BoardRecord {
+    /// Converts the record to the `.bgb` format (which keeps only the hand and the board)
+    /// # Returns
+    /// * `Vec<u8>` - The record as returned by `board_to_bytes`
+    fn to_bgb(&self) -> Vec<u8> {
+        // The board size was checked up front, so it can always be converted
+        board_to_bytes(&self.hand, &self.board).expect("Board too large to save!")
+    }
+
+    /// Converts the record to the `.bgb` format followed by how hard it was to solve (see `--bgb-metrics`)
+    /// # Returns
+    /// * `Vec<u8>` - The record as returned by `board_to_bytes`, followed by the bytes returned by `metrics_to_bytes`
+    fn to_bgb_with_metrics(&self) -> Vec<u8> {
+        let metrics = SolveMetrics { words_checked: self.words_checked, depth: self.depth, failed_first_words: self.failed_first_words.unwrap_or_default() };
+        let mut bytes = self.to_bgb();
+        bytes.extend(metrics_to_bytes(&metrics, self.elapsed_ms));
+        bytes
+    }
+
+    /// Converts the record to JSON
+    /// # Returns
+    /// * `String` - The record as serialized by its `Serialize` implementation
+    fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("Failed to convert board to JSON!")
+    }
+
+    /// Converts the record to a MessagePack record (see `msgpack::encode_record`)
+    /// # Returns
+    /// * `Vec<u8>` - The record as serialized by its `Serialize` implementation, preceded by its length
+    fn to_msgpack(&self) -> Vec<u8> {
+        let record = serde_json::to_value(self).expect("Failed to convert board to MessagePack!");
+        msgpack::encode_record(&json_to_msgpack(&record))
+    }
+
+    /// Converts the record to CSV (which keeps only the board)
+    /// # Arguments
+    /// * `board_id` - ID identifying the board's rows in the file
+    /// # Returns
+    /// * `String` - A `board_id,row,col,letter` line for each tile, with the rows and columns counted from the board's top-left corner and the
+    ///   letters written as A-Z (or a-z for tiles played with a blank)
+    fn to_csv(&self, board_id: usize) -> String {
+        let mut csv = String::new();
+        for row in self.min_row..self.max_row+1 {
+            for col in self.min_col..self.max_col+1 {
+                let val = self.board.get_val(row, col);
+                if val != EMPTY_VALUE {
+                    let letter = if self.board.is_blank(row, col) { (val + 97) as char } else { (val + 65) as char };
+                    csv.push_str(&format!("{},{},{},{}\n", board_id, row - self.min_row, col - self.min_col, letter));
+                }
             }
-            current_letters.clear();
-            if col_idx > end_col {
-                break;
+        }
+        csv
+    }
+
+    /// Gets the tiles of the board cropped to its letters (as stored by `--format sqlite` and `--format parquet`)
+    /// # Returns
+    /// * `Vec<u8>` - Each cell row by row, with 0 for an empty cell and 1-26 for A-Z (with `BGB_BLANK_FLAG` set for tiles played with a
+    ///   blank)
+    #[cfg(any(feature = "sqlite", feature = "arrow"))]
+    fn cropped_tiles(&self) -> Vec<u8> {
+        let mut tiles: Vec<u8> = Vec::with_capacity((self.max_row + 1 - self.min_row) * (self.max_col + 1 - self.min_col));
+        for row in self.min_row..self.max_row+1 {
+            for col in self.min_col..self.max_col+1 {
+                tiles.push(match self.board.get_val(row, col) {
+                    EMPTY_VALUE => 0,
+                    val if self.board.is_blank(row, col) => (val + 1) | BGB_BLANK_FLAG,
+                    val => val + 1
+                });
             }
         }
+        tiles
     }
-    if current_letters.len() > 1 && !valid_words.contains(&current_letters) {
-        return false;
+}
+
+/// Generates solved Bananagrams boards as training data
+#[derive(Parser)]
+#[command(version, about, args_conflicts_with_subcommands = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+    #[command(flatten)]
+    args: GenerateArgs
+}
+
+/// Tools other than generating boards
+#[derive(Subcommand)]
+enum Command {
+    /// Work with existing `.bgb` files
+    Bgb {
+        #[command(subcommand)]
+        command: BgbCommand
+    },
+    /// Check that every file listed in the manifest of a previous run is present and has the number of boards listed
+    VerifyManifest {
+        /// The `manifest.json` file to check
+        manifest: PathBuf
+    },
+    /// Summarize the boards in a directory (their hand sizes, sizes, letters, most common words, duplicates, and how hard they were to
+    /// solve, if recorded), and the hands that couldn't be solved
+    Stats {
+        /// Directory of `.bgb`, `.bgb2`, and JSON Lines board files (boards written by --augment are counted once per rotation or
+        /// reflection, each a duplicate of the first)
+        data: PathBuf,
+        /// File of hands that couldn't be solved, as written by --log-failures
+        #[arg(long)]
+        failures: Option<PathBuf>,
+        /// Print the statistics as JSON
+        #[arg(long)]
+        json: bool
+    },
+    /// Compute statistics of the boards in a directory for other kinds of models
+    Analyze {
+        #[command(subcommand)]
+        command: AnalyzeCommand
+    },
+    /// Split a generated dataset into training, validation, and test sets (in the `train`, `validation`, and `test` directories), keeping
+    /// every rotation and reflection of a board in the same set
+    Split {
+        /// Directory of the dataset, with its `manifest.json` (only for --format bgb and jsonl)
+        data: PathBuf,
+        /// Directory to write the sets to
+        #[arg(long)]
+        out: PathBuf,
+        /// Proportion of the boards to put in the training, validation, and test sets, summing to 1
+        #[arg(long, value_delimiter = ',', default_value = "0.8,0.1,0.1")]
+        ratios: Vec<f64>,
+        /// Seed deciding which set each board goes in
+        #[arg(long, default_value_t = 0)]
+        seed: u64
+    },
+    /// Write the boards of a file in a random order, so that they can be trained on without the order they were generated in
+    Shuffle {
+        /// The `.bgb2` or JSON Lines file (uncompressed, so that it needn't fit in memory), or a `.bgb` file, which is upgraded to `.bgb2`
+        input: PathBuf,
+        /// Where to write the shuffled file
+        #[arg(long)]
+        out: PathBuf,
+        /// Seed for the random number generator (random if not given)
+        #[arg(long)]
+        seed: Option<u64>
+    },
+    /// Write a random sample of the boards of a file, in the order they are in the file
+    Sample {
+        /// The file, as given to `shuffle`
+        input: PathBuf,
+        /// Where to write the sample
+        #[arg(long)]
+        out: PathBuf,
+        /// Number of boards to sample (every board if the file has fewer)
+        #[arg(long)]
+        n: usize,
+        /// Seed for the random number generator (random if not given)
+        #[arg(long)]
+        seed: Option<u64>
+    },
+    /// Simulate multi-player games, writing a line of JSON Lines for each game with every player's final board and who won
+    Game(GameArgs),
+    /// Solve a single hand, printing the board and the words played (exiting with 0 if it was solved, 1 if not, or 2 on an error)
+    Solve(SolveArgs),
+    /// Check whether boards are legal, printing each invalid word and each group of letters cut off from the others (exiting with 0 if
+    /// every board is legal, 1 if not, or 2 on an error)
+    Validate(ValidateArgs),
+    /// Render each board in a file as an image, optionally highlighting invalid words
+    Render(RenderArgs),
+    /// Serve the solver over HTTP: `POST /solve` with a JSON body like `{"letters": "AAEINRST", "timeout_ms": 1000}`, `POST /validate`
+    /// with a body like `{"board": ["CAT", "A..", "B.."]}`, and `GET /generate?hand_size=21` to solve a random hand (with optional `seed`
+    /// and `timeout_ms` parameters)
+    #[cfg(feature = "serve")]
+    Serve(ServeArgs),
+    /// Time the solver on a fixed suite of hands and print a table of the results (like `cargo bench`, for where cargo isn't available)
+    Bench {
+        /// Number of times to run each benchmark
+        #[arg(long, default_value_t = 10)]
+        iterations: usize
     }
-    // Check down each column where a letter was played
-    for col_idx in start_col..end_col+1 {
-        current_letters.clear();
-        // Find the furtherest up row that the word is connected to
-        let mut minimum_row = row;
-        while minimum_row > min_row {
-            if board.get_val(minimum_row, col_idx) == EMPTY_VALUE {
-                minimum_row += 1;
-                break;
-            }
-            minimum_row -= 1;
+}
+
+/// Options for simulating games
+#[derive(Args)]
+struct GameArgs {
+    /// Number of players in each game, which decides how many tiles each starts with (21 for 2-4 players, 15 for 5-6, or 11 for 7-8)
+    #[arg(long, default_value_t = 4)]
+    players: usize,
+    /// Number of games to simulate
+    #[arg(long, default_value_t = 100)]
+    games: usize,
+    /// File to write the games to
+    #[arg(long, default_value = "games.jsonl")]
+    out: PathBuf,
+    /// File of valid words, one per line (the dictionary built in at compile time is used if not given)
+    #[arg(long)]
+    dictionary: Option<PathBuf>,
+    /// File of the tiles to play with, with one `LETTER COUNT` pair per line (the standard 144 tiles are used if not given)
+    #[arg(long)]
+    tiles: Option<PathBuf>,
+    /// Minimum length of a word; shorter words are removed from the dictionary
+    #[arg(long, default_value_t = DEFAULT_MIN_WORD_LENGTH)]
+    min_word_length: usize,
+    /// Maximum number of words to check each time a player tries to play their tiles
+    #[arg(long, default_value_t = MAXIMUM_WORDS_CHECKED)]
+    max_words_checked: usize,
+    /// Number of rows/columns in the boards to play on
+    #[arg(long, default_value_t = BOARD_SIZE)]
+    board_size: usize,
+    /// Seed for the random number generator, with each game shuffling the tiles with the seed plus its index (random if not given)
+    #[arg(long)]
+    seed: Option<u64>
+}
+
+/// Options for solving a single hand
+#[derive(Args)]
+struct SolveArgs {
+    /// The hand, as a string of letters (like AAEINRSTQUZ) or as the count of each letter (like A:3,E:2,Q:1); read from standard input
+    /// if not given
+    #[arg(long)]
+    letters: Option<String>,
+    /// File of valid words, one per line (the dictionary built in at compile time is used if not given)
+    #[arg(long)]
+    dictionary: Option<PathBuf>,
+    /// File of the tiles the hand must be drawable from, with one `LETTER COUNT` pair per line (the standard 144 tiles are used if not given)
+    #[arg(long)]
+    tiles: Option<PathBuf>,
+    /// Allow hands with more of a letter than the tile set has
+    #[arg(long)]
+    no_pool_check: bool,
+    /// Maximum number of seconds to spend solving the hand
+    #[arg(long)]
+    timeout: Option<f64>,
+    /// Minimum length of a word; shorter words are removed from the dictionary
+    #[arg(long, default_value_t = DEFAULT_MIN_WORD_LENGTH)]
+    min_word_length: usize,
+    /// Maximum number of words to check before giving up
+    #[arg(long, default_value_t = MAXIMUM_WORDS_CHECKED)]
+    max_words_checked: usize
+}
+
+/// Options for serving the solver over HTTP
+#[cfg(feature = "serve")]
+#[derive(Args)]
+struct ServeArgs {
+    /// Address to listen on (port 0 picks any free port, which is printed)
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    bind: String,
+    /// Number of requests to handle at once
+    #[arg(long, default_value_t = 4)]
+    threads: usize,
+    /// File of valid words, one per line (the dictionary built in at compile time is used if not given)
+    #[arg(long)]
+    dictionary: Option<PathBuf>,
+    /// Minimum length of a word; shorter words are removed from the dictionary
+    #[arg(long, default_value_t = DEFAULT_MIN_WORD_LENGTH)]
+    min_word_length: usize,
+    /// Maximum number of words to check before giving up on a hand
+    #[arg(long, default_value_t = MAXIMUM_WORDS_CHECKED)]
+    max_words_checked: usize,
+    /// Longest time to spend solving a hand, in seconds (requests can ask for less with `timeout_ms`)
+    #[arg(long, default_value_t = 10.0)]
+    max_timeout: f64
+}
+
+/// Format of a file of boards to check
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+enum BoardFileFormat {
+    /// A single board as text, with a line per row, a space or period for each empty cell, and lowercase letters for tiles placed with a
+    /// blank (as printed by `solve`)
+    Text,
+    /// A `.bgb` or `.bgb2` file, which may be compressed
+    Bgb,
+    /// A JSON Lines file with an object per board, whose `board` is a list of rows (as written by `--format jsonl`), which may be
+    /// compressed
+    Jsonl
+}
+
+impl BoardFileFormat {
+    /// Guesses the format of a file from its name
+    /// # Arguments
+    /// * `path` - Path to the file
+    /// # Returns
+    /// * `BoardFileFormat` - `Bgb` for a `.bgb` or `.bgb2` file, `Jsonl` for a `.jsonl` file (either of which may be compressed), and
+    ///   otherwise `Text`
+    fn of_path(path: &Path) -> BoardFileFormat {
+        let name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+        let name = name.trim_end_matches(Compression::Gzip.suffix()).trim_end_matches(Compression::Zstd.suffix());
+        if name.ends_with(".bgb") || name.ends_with(".bgb2") {
+            BoardFileFormat::Bgb
         }
-        minimum_row = cmp::max(minimum_row, min_row);
-        for row_idx in minimum_row..max_row+1 {
-            if board.get_val(row_idx, col_idx) != EMPTY_VALUE {
-                current_letters.push(board.get_val(row_idx, col_idx));
-            }
-            else {
-                if current_letters.len() > 1 && !valid_words.contains(&current_letters) {
-                    return false;
-                }
-                current_letters.clear();
-                if row_idx > row {
-                    break;
-                }
-            }
+        else if name.ends_with(".jsonl") {
+            BoardFileFormat::Jsonl
         }
-        if current_letters.len() > 1 && !valid_words.contains(&current_letters) {
-            return false;
+        else {
+            BoardFileFormat::Text
         }
     }
-    return true;
 }
 
-/// Checks that a `board` is valid after a word is played vertically, given the specified list of `valid_word`s
-/// Note that this does not check if all words are contiguous; this condition must be enforced elsewhere.
-/// # Arguments
-/// * `board` - `Board` being checked
-/// * `min_col` - Minimum x (column) index of the subsection of the `board` to be checked
-/// * `max_col` - Maximum x (column) index of the subsection of the `board` to be checked
-/// * `min_row` - Minimum y (row) index of the subsection of the `board` to be checked
-/// * `max_row` - Maximum y (row) index of the subsection of the `board` to be checked
-/// * `start_row` - Starting row of the word played
-/// * `end_row` - Ending row of the word played
-/// * `col` - Column of the word played
-/// * `valid_words` - HashSet of all valid words as `Vec<usize>`s
-/// # Returns
-/// `bool` - whether the given `board` is made only of valid words
-fn is_board_valid_vertical(board: &Board, min_col: usize, max_col: usize, min_row: usize, max_row: usize, start_row: usize, end_row: usize, col: usize, valid_words: &HashSet<Word>) -> bool {
-    let mut current_letters: Vec<usize> = Vec::with_capacity(MAX_WORD_LENGTH);
-    // Find the furtherest up row that the new play is connected to
-    let mut minimum_row = start_row;
-    while minimum_row > min_row {
-        if board.get_val(minimum_row, col) == EMPTY_VALUE {
-            minimum_row += 1;
-            break;
+/// Format of the images boards are rendered as
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+enum ImageFormat {
+    /// SVG (see `render::board_to_svg`)
+    Svg,
+    /// PNG (see `render::board_to_png`)
+    #[cfg(feature = "png")]
+    Png
+}
+
+/// Options for rendering boards
+#[derive(Args)]
+struct RenderArgs {
+    /// File of the boards to render
+    input: PathBuf,
+    /// Format of the file (guessed from its name if not given)
+    #[arg(long, value_enum)]
+    format: Option<BoardFileFormat>,
+    /// Directory to write the images to, named by the index of each board in the file (like board_007.svg)
+    #[arg(long)]
+    out: PathBuf,
+    /// Maximum number of boards to render, starting from the first
+    #[arg(long)]
+    limit: Option<usize>,
+    /// Format of the images
+    #[arg(long, value_enum, default_value_t = ImageFormat::Svg)]
+    image_format: ImageFormat,
+    /// Width and height of each cell of the board, in pixels
+    #[arg(long, default_value_t = render::DEFAULT_CELL_SIZE)]
+    cell_size: usize,
+    /// Color the tiles of invalid words (checked against the dictionary)
+    #[arg(long)]
+    highlight_invalid: bool,
+    /// Color of the tiles of invalid words, as hexadecimal
+    #[arg(long, default_value = "#f08080")]
+    highlight_color: String,
+    /// File of valid words, one per line, for --highlight-invalid (the dictionary built in at compile time is used if not given)
+    #[arg(long)]
+    dictionary: Option<PathBuf>,
+    /// Minimum length of a word for --highlight-invalid; shorter words are invalid
+    #[arg(long, default_value_t = DEFAULT_MIN_WORD_LENGTH)]
+    min_word_length: usize
+}
+
+/// Options for checking boards
+#[derive(Args)]
+struct ValidateArgs {
+    /// File of the boards to check
+    #[arg(long)]
+    input: PathBuf,
+    /// Format of the file
+    #[arg(long, value_enum, default_value_t = BoardFileFormat::Text)]
+    format: BoardFileFormat,
+    /// File of valid words, one per line (the dictionary built in at compile time is used if not given)
+    #[arg(long)]
+    dictionary: Option<PathBuf>,
+    /// Minimum length of a word; shorter words are invalid
+    #[arg(long, default_value_t = DEFAULT_MIN_WORD_LENGTH)]
+    min_word_length: usize,
+    /// Print a line of JSON for each board instead (see `validate::BoardCheck`)
+    #[arg(long)]
+    json: bool
+}
+
+/// Tools for working with existing `.bgb` files
+#[derive(Subcommand)]
+enum BgbCommand {
+    /// Convert a `.bgb` file to the `.bgb2` format, which supports random access
+    Upgrade {
+        /// The `.bgb` file to convert
+        old: PathBuf,
+        /// Where to write the `.bgb2` file
+        new: PathBuf
+    },
+    /// Merge `.bgb` files into one, dropping every board that is a rotation, reflection, or copy of one earlier in the files, and write a
+    /// manifest of how many boards were dropped from each file next to it
+    Merge {
+        /// The `.bgb` files to merge (of either format version), in order
+        #[arg(required = true)]
+        inputs: Vec<PathBuf>,
+        /// Where to write the merged `.bgb` file, in the newest format version of the files merged
+        #[arg(long)]
+        out: PathBuf,
+        /// Compare boards with the same hash in full rather than taking them to be the same (which keeps every board in memory, rather than
+        /// only its hash)
+        #[arg(long)]
+        exact: bool
+    }
+}
+
+/// Statistics `analyze` can compute
+#[derive(Subcommand)]
+enum AnalyzeCommand {
+    /// Count how often each pair of letters are next to each other (left-right and top-bottom) and are on the same board, writing each as a
+    /// 26x26 CSV matrix
+    Adjacency {
+        /// Directory of board files, as read by `stats`
+        data: PathBuf,
+        /// Directory to write `horizontal.csv`, `vertical.csv`, and `cooccurrence.csv` to
+        #[arg(long, default_value = ".")]
+        out: PathBuf,
+        /// Write proportions rather than counts (each row of the neighbor matrices sums to 1, and the co-occurrence matrix is divided by
+        /// the number of boards)
+        #[arg(long)]
+        normalize: bool
+    }
+}
+
+/// Options for generating boards
+#[derive(Args)]
+struct GenerateArgs {
+    /// Total number of boards to generate across all threads
+    #[arg(long, default_value_t = 1500)]
+    boards: usize,
+    /// Directory to write the boards to
+    #[arg(long, default_value = "data")]
+    out: PathBuf,
+    /// File of valid words, one per line, each optionally followed by whitespace and its frequency (the dictionary built in at compile time
+    /// is used if not given)
+    #[arg(long)]
+    dictionary: Option<PathBuf>,
+    /// Treat single-letter words in the dictionary as an error rather than a warning
+    #[arg(long)]
+    strict: bool,
+    /// File of words that must never appear on a board, one per line in any case: they are removed from the dictionary, and any solution
+    /// with one as a whole word (such as formed by a crossing) is discarded and counted as a failure
+    #[arg(long)]
+    banned_words: Option<PathBuf>,
+    /// How strongly the solver prefers more frequent words when the dictionary gives frequencies: for each hand the words of each length
+    /// are ordered by sampling in proportion to frequency^(1/temperature), so 0 tries them strictly from most to least frequent and higher
+    /// temperatures approach a uniformly random order
+    #[arg(long, default_value_t = DEFAULT_FREQUENCY_TEMPERATURE)]
+    frequency_temperature: f64,
+    /// Order the solver tries the words of the dictionary in for each hand, which changes which board is found for a hand but not whether
+    /// one is (not allowed with a dictionary that gives frequencies, whose words are ordered by --frequency-temperature)
+    #[arg(long, value_enum, default_value_t = WordOrder::Longest)]
+    word_order: WordOrder,
+    /// How the solver orders the words it tries first, which largely decides the board found; given several (separated by commas), each
+    /// hand uses one at random and the summary compares their solve rates
+    #[arg(long, value_delimiter = ',', value_parser = PossibleValuesParser::new(FirstWordStrategy::NAMES), default_value = "longest")]
+    first_word: Vec<String>,
+    /// Word to play first on every board, across the middle (instead of the words chosen by --first-word); it must be in the dictionary,
+    /// and hands without its letters are drawn again
+    #[arg(long)]
+    anchor_word: Option<String>,
+    /// How the solver orders the words it tries after the first, at every depth of its search
+    #[arg(long, value_enum, default_value_t = Heuristic::None)]
+    heuristic: Heuristic,
+    /// Remember the states the solver fails to finish a board from, so they aren't searched again when reached by playing the same words
+    /// in another order (which speeds up large hands, but slows down small ones)
+    #[arg(long)]
+    memoize: bool,
+    /// Maximum number of words to check before the solver stops trying a given word
+    #[arg(long, default_value_t = MAXIMUM_WORDS_CHECKED)]
+    max_words_checked: usize,
+    /// Maximum number of seconds to spend solving each hand before moving on to a new one
+    #[arg(long)]
+    timeout_per_hand: Option<f64>,
+    /// Minimum size of hand of letters to generate
+    #[arg(long, default_value_t = MINIMUM_HAND_SIZE as usize)]
+    min_hand_size: usize,
+    /// Maximum size of hand of letters to generate (half the tiles in the tile set if not given)
+    #[arg(long)]
+    max_hand_size: Option<usize>,
+    /// How to choose the size of each hand
+    #[arg(long, value_enum, default_value_t = HandSizes::LogScaled)]
+    hand_sizes: HandSizes,
+    /// Size of every hand with --hand-sizes fixed
+    #[arg(long)]
+    hand_size: Option<usize>,
+    /// Number of players whose starting hands to draw with --hand-sizes official
+    #[arg(long)]
+    players: Option<usize>,
+    /// Minimum number of vowels (A, E, I, O, and U) in each hand (at most the smallest hand size); hands drawn with fewer have random
+    /// consonants swapped for vowels from the tiles not drawn, so that fewer hands are unsolvable without changing their sizes
+    #[arg(long, default_value_t = 0)]
+    min_vowels: usize,
+    /// Generate --per-bucket boards from hands in each of these ranges of sizes, separated by commas (like 11-20,21-40,41-72), instead of
+    /// --boards boards from hands of any size, so that small hands don't make up most of the boards; only with --hand-sizes log-scaled or
+    /// uniform (whose distribution is used within each range, in place of --min-hand-size and --max-hand-size) and without --resume
+    #[arg(long, value_delimiter = ',')]
+    stratify: Vec<String>,
+    /// Number of boards to generate from hands in each range with --stratify
+    #[arg(long)]
+    per_bucket: Option<usize>,
+    /// Maximum number of hands to draw in each range with --stratify, after which the range is given up on even if it has fewer than
+    /// --per-bucket boards (100 times --per-bucket if not given)
+    #[arg(long)]
+    max_hands_per_bucket: Option<usize>,
+    /// File of the tiles to draw hands from, with one `LETTER COUNT` pair per line (the standard 144 tiles are used if not given)
+    #[arg(long)]
+    tiles: Option<PathBuf>,
+    /// Draw each thread's hands one after another from the same pool of tiles without putting them back, so that no run of hands has more
+    /// of a letter than the tile set (refilling the pool once it has too few tiles left for the next hand), rather than each from every tile
+    #[arg(long)]
+    realistic_pool: bool,
+    /// Number of blanks to add to each hand on top of its letters, each of which the solver can play as any letter
+    #[arg(long, default_value_t = 0)]
+    blanks: u8,
+    /// Number of tiles to finish each board with, leaving the rest of the hand unplayed (at most --min-hand-size plus --blanks, so every
+    /// board has exactly this many)
+    #[arg(long)]
+    target_tiles: Option<usize>,
+    /// Number of distinct boards to look for from each hand (by solving it again with the dictionary reshuffled), each of which counts
+    /// toward --boards; fewer are kept if the word budget or time per hand runs out first
+    #[arg(long, default_value_t = 1)]
+    solutions_per_hand: usize,
+    /// Text file of a legal board to play each hand onto rather than solving each from an empty board, with a line per row of letters A-Z
+    /// (lowercase for blanks) and spaces or dots for empty cells; the hands are the letters left to play, not counting those on the board
+    #[arg(long)]
+    complete_from: Option<PathBuf>,
+    /// Allow a --complete-from board with more of a letter than the tile set has
+    #[arg(long)]
+    no_pool_check: bool,
+    /// After solving each hand, play out the rest of a game: draw the tiles left in the tile set one at a time and add each to the board
+    /// (rearranging a word if needed), writing the board after every step with the game's `sequence` ID and the `step` number (only for
+    /// --format jsonl); each board counts toward --boards, and a game ends when a tile can't be added
+    #[arg(long)]
+    simulate_game: bool,
+    /// Minimum length of a word; shorter words are removed from the dictionary, and boards where they'd be formed by a crossing are invalid
+    #[arg(long, default_value_t = DEFAULT_MIN_WORD_LENGTH)]
+    min_word_length: usize,
+    /// Number of rows/columns in the board to solve on
+    #[arg(long, default_value_t = BOARD_SIZE)]
+    board_size: usize,
+    /// Format to write the boards in
+    #[arg(long, value_enum, default_value_t = OutputFormat::Bgb)]
+    format: OutputFormat,
+    /// Compression to apply to the board files (each flush ends a compressed block, so this works best with a larger --flush-every)
+    #[arg(long, value_enum, default_value_t = Compression::None)]
+    compress: Compression,
+    /// Size of the grid (like `32x32`, as rows by columns) each board is centered in with --format npy-onehot; boards that don't fit are
+    /// skipped
+    #[arg(long, value_parser = parse_pad_to)]
+    pad_to: Option<(usize, usize)>,
+    /// Number of boards in each row group of a --format parquet file; each thread keeps a row group in memory until it's full
+    #[cfg(feature = "arrow")]
+    #[arg(long, default_value_t = 10_000)]
+    row_group_size: usize,
+    /// Also write the statistics printed at the end of the run to `summary.json` in the output directory
+    #[arg(long)]
+    summary: bool,
+    /// File to append a line to (in JSON Lines) for each hand that couldn't be solved, giving the hand, why it couldn't be solved, and the
+    /// number of words checked
+    #[arg(long)]
+    log_failures: Option<PathBuf>,
+    /// Number of boards after which each thread starts a new board file (named like `shard_{thread}_{index}`, and likewise the plays,
+    /// words, and negatives files)
+    #[arg(long)]
+    shard_size: Option<usize>,
+    /// Number of boards each thread generates between flushes of its output file
+    #[arg(long, default_value_t = 1)]
+    flush_every: usize,
+    /// Print the words played to make each board
+    #[arg(long)]
+    verbose: bool,
+    /// Don't report progress while generating
+    #[arg(long)]
+    quiet: bool,
+    /// Also write the words played to make each board to a JSON Lines file next to each board file
+    #[arg(long)]
+    plays: bool,
+    /// Follow each board in a .bgb board file with how hard it was to solve (the words checked, time taken, depth, and failed first
+    /// words), writing version 2 of the format (only for --format bgb)
+    #[arg(long)]
+    bgb_metrics: bool,
+    /// Also write the hand, bounding box, and words of each board to a JSON Lines file next to each board file
+    #[arg(long)]
+    dump_words: bool,
+    /// Also write every distinct rotation and reflection of each board to the board file (the plays and words files still get one line per board)
+    #[arg(long)]
+    augment: bool,
+    /// Number of corrupted (invalid) copies of each board to write to a labeled board file next to each board file
+    #[arg(long, default_value_t = 0)]
+    negatives_per_board: usize,
+    /// When a hand can't be solved, write the board with the most tiles the solver found to a file next to each board file (a labeled `.bgb`
+    /// file labeled by why the hand couldn't be solved, or a JSON Lines file with the unused letters; only for --format bgb and jsonl)
+    #[arg(long)]
+    allow_partial: bool,
+    /// Number of perturbations made to each corrupted copy of a board
+    #[arg(long, default_value_t = 1)]
+    negative_severity: usize,
+    /// Skip boards that are a rotation or reflection of one already generated
+    #[arg(long)]
+    dedupe: bool,
+    /// Number of threads to generate boards with (defaults to the available parallelism), each of which generates an even share of the boards
+    #[arg(long)]
+    threads: Option<usize>,
+    /// Seed for the random number generator, with each thread using the seed plus its index (random if not given); runs with the same seed
+    /// and number of threads write the same boards to each thread's files, unless the threads depend on each other or on timing (with
+    /// --stratify, --dedupe, --timeout-per-hand, or --format csv or parquet, whose board IDs are shared)
+    #[arg(long)]
+    seed: Option<u64>,
+    /// Continue a previous run into the same output directory (which must have used the same options), counting the boards it wrote
+    /// toward --boards, cutting off any board it left incomplete, and appending to its files
+    #[arg(long)]
+    resume: bool,
+    /// Write the boards to standard output as they're generated (in place of --format, --out, and --boards), flushing after each, until
+    /// standard input or standard output is closed or the run is interrupted; progress and the summary go to standard error
+    #[arg(long)]
+    stream: bool,
+    /// Format of the boards written with --stream
+    #[arg(long, value_enum, default_value_t = StreamFormat::Msgpack)]
+    stream_format: StreamFormat
+}
+impl GenerateArgs {
+    /// Gets the name (without extension) shared by the files a thread writes
+    /// # Arguments
+    /// * `thread` - Index of the thread
+    /// * `shard_index` - Index of the file among those written by the thread (only used with `--shard-size`)
+    /// # Returns
+    /// * `String` - The name
+    fn file_stem(&self, thread: usize, shard_index: usize) -> String {
+        match self.shard_size {
+            Some(_) => format!("shard_{}_{}", thread, shard_index),
+            None => format!("{}_board5", thread)
         }
-        minimum_row -= 1;
     }
-    minimum_row = cmp::max(minimum_row, min_row);
-    // Check down the column where the word was played
-    for row_idx in minimum_row..max_row+1 {
-        // If it's not an empty value, add it to the current word
-        if board.get_val(row_idx, col) != EMPTY_VALUE {
-            current_letters.push(board.get_val(row_idx, col));
+
+    /// Gets the name of a board file a thread writes
+    /// # Arguments
+    /// * `thread` - Index of the thread
+    /// * `shard_index` - Index of the file among those written by the thread (only used with `--shard-size`)
+    /// # Returns
+    /// * `String` - The name
+    fn board_file_name(&self, thread: usize, shard_index: usize) -> String {
+        format!("{}.{}{}", self.file_stem(thread, shard_index), self.format.extension(), self.compress.suffix())
+    }
+
+    /// Gets the version of the format the board files are written in, if it has one
+    fn format_version(&self) -> Option<u8> {
+        if self.bgb_metrics { Some(BGB_METRICS_FORMAT_VERSION) } else { self.format.version() }
+    }
+
+    /// Gets the bytes each board file starts with
+    fn board_header(&self) -> Vec<u8> {
+        match (self.format, self.pad_to) {
+            // The number of boards is filled in once the file is finished (see `npy::set_board_count`)
+            (OutputFormat::NpyOnehot, Some((height, width))) => npy::header(0, height, width),
+            _ if self.bgb_metrics => vec![BGB_METRICS_FORMAT_VERSION],
+            _ => self.format.header()
         }
-        else {
-            // Otherwise, check if we have more than one letter - if so, check if the word is valid
-            if current_letters.len() > 1 && !valid_words.contains(&current_letters) {
-                return false;
-            }
-            current_letters.clear();
-            // If we're past the end of the played word, no need to check farther
-            if row_idx > end_row {
-                break;
-            }
+    }
+
+    /// Describes a finished board file for the manifest
+    /// # Arguments
+    /// * `thread` - Index of the thread that wrote the file
+    /// * `shard_index` - Index of the file among those written by the thread
+    /// * `boards` - Number of boards in the file
+    /// * `seed` - Seed of the thread
+    /// # Returns
+    /// * `Shard` - The description of the file
+    fn shard(&self, thread: usize, shard_index: usize, boards: usize, seed: u64) -> Shard {
+        let file = self.board_file_name(thread, shard_index);
+        let bytes = fs::metadata(self.out.join(&file)).expect("Failed to read board data file!").len();
+        Shard { file, thread, index: shard_index, boards, bytes, seed }
+    }
+}
+
+/// The files a thread of a previous run wrote, found when resuming it
+#[derive(Default)]
+struct ResumedThread {
+    /// Number of boards in all of the files
+    boards: usize,
+    /// Files that were finished (only with `--shard-size`)
+    shards: Vec<Shard>,
+    /// Number of boards in the file that is to be appended to
+    current_boards: usize
+}
+
+/// Converts a word on a board to JSON
+/// # Arguments
+/// * `word` - The word
+/// * `row` - Row index of the word's first letter
+/// * `col` - Column index of the word's first letter
+/// * `direction` - Direction of the word
+/// # Returns
+/// * `String` - JSON object giving the `word`, its starting `row` and `col`, and its `direction`
+fn word_to_json(word: &str, row: usize, col: usize, direction: Direction) -> String {
+    format!("{{\"word\":\"{}\",\"row\":{},\"col\":{},\"direction\":\"{}\"}}", word, row, col, direction)
+}
+
+/// Converts the words played to make a board to JSON
+/// # Arguments
+/// * `plays` - The words played, in order
+/// # Returns
+/// * `String` - JSON array with an object for each word giving its `word`, starting `row` and `col`, and `direction`
+fn plays_to_json(plays: &[PlayedWord]) -> String {
+    serde_json::to_string(plays).expect("Failed to convert plays to JSON!")
+}
+
+/// Converts the words on a solved board to JSON
+/// # Arguments
+/// * `letters` - The hand that was solved
+/// * `board` - The solved board
+/// * `min_col` - Minimum occupied column index
+/// * `max_col` - Maximum occupied column index
+/// * `min_row` - Minimum occupied row index
+/// * `max_row` - Maximum occupied row index
+/// # Returns
+/// * `String` - JSON object giving the `hand` as a string, the bounding box as `min_row`, `max_row`, `min_col`, and `max_col`, and the
+///   `words` on the board (see `extract_words`) as objects like those of `plays_to_json`
+fn words_to_json(letters: &Letters, board: &Board, min_col: usize, max_col: usize, min_row: usize, max_row: usize) -> String {
+    let words: Vec<String> = extract_words(board, min_col, max_col, min_row, max_row).iter().map(|(word, row, col, direction)| word_to_json(word, *row, *col, *direction)).collect();
+    format!("{{\"hand\":\"{}\",\"min_row\":{},\"max_row\":{},\"min_col\":{},\"max_col\":{},\"words\":[{}]}}", letters_to_string(letters), min_row, max_row, min_col, max_col, words.join(","))
+}
+
+/// Converts a `.bgb` file to the `.bgb2` format
+/// # Arguments
+/// * `old` - Path to the `.bgb` file (which may be compressed, see `read_boards`)
+/// * `new` - Path to write the `.bgb2` file to
+/// # Returns
+/// * `Result` with the number of boards converted, or an error message if either file couldn't be used
+fn upgrade_bgb(old: &Path, new: &Path) -> Result<usize, String> {
+    let bytes = fs::read(old).map_err(|e| format!("Failed to read {}: {}", old.display(), e))?;
+    let boards = read_boards(&bytes).map_err(|e| format!("Invalid board file {}: {}", old.display(), e))?;
+    let upgraded = write_bgb2(&boards).map_err(|e| format!("Failed to convert {}: {}", old.display(), e))?;
+    fs::write(new, upgraded).map_err(|e| format!("Failed to write {}: {}", new.display(), e))?;
+    Ok(boards.len())
+}
+
+/// Hashes a board so that it can be recognized among generated boards even if rotated or reflected
+/// # Arguments
+/// * `board` - The board
+/// * `min_col` - Minimum occupied column index
+/// * `max_col` - Maximum occupied column index
+/// * `min_row` - Minimum occupied row index
+/// * `max_row` - Maximum occupied row index
+/// # Returns
+/// * `u64` - Hash of the board's canonical form (see `canonical_form`)
+fn board_hash(board: &Board, min_col: usize, max_col: usize, min_row: usize, max_row: usize) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    canonical_form(board, min_col, max_col, min_row, max_row).hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Opens a file to write to
+/// # Arguments
+/// * `path` - Path to the file
+/// * `append` - Whether to append to the file if it already exists rather than replacing it
+/// # Returns
+/// * `io::Result<File>` - The file, or an error if it couldn't be opened
+fn open_output(path: &Path, append: bool) -> io::Result<File> {
+    if append {
+        OpenOptions::new().create(true).append(true).open(path)
+    }
+    else {
+        File::create(path)
+    }
+}
+
+/// Finds where each complete line of a file ends
+/// # Arguments
+/// * `bytes` - Contents of the file
+/// # Returns
+/// * `Vec<usize>` - Offset just past the newline ending each line
+fn complete_lines(bytes: &[u8]) -> Vec<usize> {
+    bytes.iter().enumerate().filter(|(_, &byte)| byte == b'\n').map(|(i, _)| i + 1).collect()
+}
+
+/// Resumes one of the board files of a previous run, cutting it (and the plays and words files alongside it) off after the last board
+/// that is complete in all of them
+/// # Arguments
+/// * `args` - The command-line arguments
+/// * `thread` - Index of the thread that wrote the file
+/// * `shard_index` - Index of the file among those written by the thread
+/// * `seen_boards` - Hashes of the boards found so far (see `board_hash`), added to if `--dedupe` was given
+/// # Returns
+/// * `Result` with the number of boards kept, `None` if the file doesn't exist, or an error message if the files couldn't be resumed
+fn resume_file(args: &GenerateArgs, thread: usize, shard_index: usize, seen_boards: &mut HashSet<u64>) -> Result<Option<usize>, String> {
+    let path = args.out.join(args.board_file_name(thread, shard_index));
+    let bytes = match fs::read(&path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e))
+    };
+    let ends = match args.format {
+        OutputFormat::Bgb if !bytes.is_empty() => complete_bgb_records(&bytes).map_err(|e| format!("Invalid board file {}: {}", path.display(), e))?,
+        _ => complete_lines(&bytes)
+    };
+    let mut files = vec![(path, ends)];
+    let stem = args.file_stem(thread, shard_index);
+    for (enabled, extension) in [(args.plays, "plays.jsonl"), (args.dump_words, "words.jsonl")] {
+        if enabled {
+            let path = args.out.join(format!("{}.{}", stem, extension));
+            // The board is written first, so an interrupted run may have left it without its line in these files
+            let bytes = match fs::read(&path) {
+                Ok(bytes) => bytes,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+                Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e))
+            };
+            files.push((path, complete_lines(&bytes)));
         }
     }
-    // In case we don't hit the `else` in the previous loop
-    if current_letters.len() > 1 {
-        if !valid_words.contains(&current_letters) {
-            return false;
+    let count = files.iter().map(|(_, ends)| ends.len()).min().unwrap_or(0);
+    for (path, ends) in files.iter() {
+        if path.exists() {
+            let length = if count == 0 { 0 } else { ends[count-1] };
+            OpenOptions::new().write(true).open(path).and_then(|file| file.set_len(length as u64)).map_err(|e| format!("Failed to cut off {}: {}", path.display(), e))?;
         }
     }
-    // Check across each row where a letter was played
-    for row_idx in start_row..end_row+1 {
-        current_letters.clear();
-        // Find the furtherest left column that the word is connected to
-        let mut minimum_col = col;
-        while minimum_col > min_col {
-            if board.get_val(row_idx, minimum_col) == EMPTY_VALUE {
-                minimum_col += 1;
-                break;
-            }
-            minimum_col -= 1;
+    if args.dedupe && count > 0 {
+        for (_, board, min_col, max_col, min_row, max_row) in parse_bgb::<ENGLISH_ALPHABET_SIZE>(&bytes[..files[0].1[count-1]])? {
+            seen_boards.insert(board_hash(&board, min_col, max_col, min_row, max_row));
         }
-        minimum_col = cmp::max(minimum_col, min_col);
-        for col_idx in minimum_col..max_col+1 {
-            if board.get_val(row_idx, col_idx) != EMPTY_VALUE {
-                current_letters.push(board.get_val(row_idx, col_idx));
-            }
-            else {
-                if current_letters.len() > 1 && !valid_words.contains(&current_letters) {
-                    return false;
-                }
-                current_letters.clear();
-                if col_idx > col {
+    }
+    Ok(Some(count))
+}
+
+/// Resumes the board files of a thread of a previous run (see `resume_file`)
+/// # Arguments
+/// * `args` - The command-line arguments
+/// * `thread` - Index of the thread
+/// * `seed` - Seed of the thread
+/// * `seen_boards` - Hashes of the boards found so far, added to if `--dedupe` was given
+/// # Returns
+/// * `Result` with the files found, or an error message if they couldn't be resumed
+fn resume_thread(args: &GenerateArgs, thread: usize, seed: u64, seen_boards: &mut HashSet<u64>) -> Result<ResumedThread, String> {
+    let mut resumed = ResumedThread::default();
+    match args.shard_size {
+        Some(shard_size) => {
+            // Every file but the last is full
+            while let Some(count) = resume_file(args, thread, resumed.shards.len(), seen_boards)? {
+                resumed.boards += count;
+                if count < shard_size {
+                    resumed.current_boards = count;
                     break;
                 }
+                resumed.shards.push(args.shard(thread, resumed.shards.len(), count, seed));
+            }
+        },
+        None => {
+            if let Some(count) = resume_file(args, thread, 0, seen_boards)? {
+                resumed.boards = count;
+                resumed.current_boards = count;
             }
         }
-        if current_letters.len() > 1 && !valid_words.contains(&current_letters) {
-            return false;
+    }
+    Ok(resumed)
+}
+
+/// Counts the threads of a previous run from the names of the board files in the output directory
+/// # Arguments
+/// * `args` - The command-line arguments
+/// # Returns
+/// * `Result` with one more than the largest index of a thread that wrote a file, or an error message if the directory couldn't be read
+fn count_resumed_threads(args: &GenerateArgs) -> Result<usize, String> {
+    let entries = fs::read_dir(&args.out).map_err(|e| format!("Failed to read {}: {}", args.out.display(), e))?;
+    let mut num_threads = 0;
+    for entry in entries {
+        let name = entry.map_err(|e| format!("Failed to read {}: {}", args.out.display(), e))?.file_name();
+        let name = name.to_string_lossy();
+        let thread = match args.shard_size {
+            Some(_) => name.strip_prefix("shard_").and_then(|rest| rest.split_once('_')).map(|(thread, _)| thread),
+            None => name.split_once("_board5.").map(|(thread, _)| thread)
+        };
+        if let Some(thread) = thread.and_then(|thread| thread.parse::<usize>().ok()) {
+            num_threads = num_threads.max(thread + 1);
         }
     }
-    return true;
+    Ok(num_threads)
 }
 
-/// Enumeration of how many letters have been used
-#[derive(Copy, Clone)]
-enum LetterUsage {
-    /// There are still unused letters
-    Remaining,
-    /// More letters have been used than are available
-    Overused,
-    /// All letters have been used
-    Finished
+/// Checks whether a file name (without extensions) is one given to board files by `GenerateArgs::file_stem`
+/// # Arguments
+/// * `stem` - The file name
+/// # Returns
+/// * `bool` - Whether it's the name of a board file
+fn is_board_file_stem(stem: &str) -> bool {
+    let is_index = |index: &str| !index.is_empty() && index.bytes().all(|byte| byte.is_ascii_digit());
+    match (stem.strip_suffix("_board5"), stem.strip_prefix("shard_").and_then(|rest| rest.split_once('_'))) {
+        (Some(thread), _) => is_index(thread),
+        (None, Some((thread, shard_index))) => is_index(thread) && is_index(shard_index),
+        (None, None) => false
+    }
 }
-impl fmt::Display for LetterUsage {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-       match self {
-            LetterUsage::Remaining => write!(f, "Remaining"),
-            LetterUsage::Overused => write!(f, "Overused"),
-            LetterUsage::Finished => write!(f, "Finished")
-       }
+
+/// Converts a JSON value to the MessagePack value with the same contents
+/// # Arguments
+/// * `value` - The JSON value
+/// # Returns
+/// * `msgpack::Value` - The value, with integers kept as integers and other numbers as floats
+fn json_to_msgpack(value: &serde_json::Value) -> msgpack::Value {
+    match value {
+        serde_json::Value::Null => msgpack::Value::Nil,
+        serde_json::Value::Bool(value) => msgpack::Value::Bool(*value),
+        serde_json::Value::Number(number) => match (number.as_u64(), number.as_i64()) {
+            (Some(number), _) => msgpack::Value::UInt(number),
+            (None, Some(number)) => msgpack::Value::Int(number),
+            (None, None) => msgpack::Value::Float(number.as_f64().unwrap_or(f64::NAN))
+        },
+        serde_json::Value::String(value) => msgpack::Value::String(value.clone()),
+        serde_json::Value::Array(values) => msgpack::Value::Array(values.iter().map(json_to_msgpack).collect()),
+        serde_json::Value::Object(entries) => msgpack::Value::Map(entries.iter().map(|(key, value)| (msgpack::Value::String(key.clone()), json_to_msgpack(value))).collect())
     }
 }
-impl fmt::Debug for LetterUsage {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-             LetterUsage::Remaining => write!(f, "Remaining"),
-             LetterUsage::Overused => write!(f, "Overused"),
-             LetterUsage::Finished => write!(f, "Finished")
-        }
-     }
+
+/// Converts a MessagePack value to the JSON value with the same contents
+/// # Arguments
+/// * `value` - The MessagePack value
+/// # Returns
+/// * `Result` with the JSON value, or an error message if it has byte arrays, map keys that aren't strings, or floats that aren't finite,
+///   which JSON can't represent
+fn msgpack_to_json(value: &msgpack::Value) -> Result<serde_json::Value, String> {
+    Ok(match value {
+        msgpack::Value::Nil => serde_json::Value::Null,
+        msgpack::Value::Bool(value) => serde_json::Value::Bool(*value),
+        msgpack::Value::Int(value) => serde_json::Value::from(*value),
+        msgpack::Value::UInt(value) => serde_json::Value::from(*value),
+        msgpack::Value::Float(value) => serde_json::Number::from_f64(*value).map(serde_json::Value::Number).ok_or_else(|| format!("Invalid number {}", value))?,
+        msgpack::Value::String(value) => serde_json::Value::String(value.clone()),
+        msgpack::Value::Binary(_) => return Err("Unexpected byte array".to_string()),
+        msgpack::Value::Array(values) => serde_json::Value::Array(values.iter().map(msgpack_to_json).collect::<Result<_, _>>()?),
+        msgpack::Value::Map(entries) => serde_json::Value::Object(entries.iter().map(|(key, value)| match key {
+            msgpack::Value::String(key) => Ok((key.clone(), msgpack_to_json(value)?)),
+            key => Err(format!("Unexpected key {}", key))
+        }).collect::<Result<_, String>>()?)
+    })
 }
 
-/// Enumeration of the direction a word is played
-#[derive(Copy, Clone, PartialEq)]
-enum Direction {
-    /// The word was played horizontally
-    Horizontal,
-    /// The word was played vertically
-    Vertical
+/// A board record read back from a file: the hand, the board, and how hard it was to solve along with the time taken in milliseconds (if
+/// the record has them)
+type ParsedRecord = (Letters, Board, Option<(SolveMetrics, u64)>);
+
+/// Parses a line of a JSON Lines board file (see `BoardRecord::to_json`)
+/// # Arguments
+/// * `line` - The line
+/// # Returns
+/// * `Result` with the hand, the board, and how hard it was to solve along with the time taken in milliseconds (if the line recorded it),
+///   or an error message if the line isn't a board record
+fn parse_jsonl_record(line: &[u8]) -> Result<ParsedRecord, String> {
+    parse_board_record(serde_json::from_slice(line).map_err(|e| e.to_string())?)
 }
-impl fmt::Display for Direction {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-       match self {
-            Direction::Vertical => write!(f, "Horizontal"),
-            Direction::Horizontal => write!(f, "Vertical")
-       }
-    }
+
+/// Parses a board record as serialized by `BoardRecord`'s `Serialize` implementation (the form of each line of a JSON Lines board file and
+/// each record of a MessagePack one)
+/// # Arguments
+/// * `record` - The record
+/// # Returns
+/// * `Result` with the hand, the board, and how hard it was to solve along with the time taken in milliseconds (if the record has it),
+///   or an error message if it isn't a board record
+fn parse_board_record(record: serde_json::Value) -> Result<ParsedRecord, String> {
+    let hand: Letters = serde_json::from_value(record["hand"].clone()).map_err(|e| e.to_string())?;
+    let rows: Vec<String> = serde_json::from_value(record["board"].clone()).map_err(|e| e.to_string())?;
+    let board: Board = rows.join("\n").parse().map_err(|e: BoardParseError| e.to_string())?;
+    // Files written before the depth and failed first words were recorded have no metrics worth comparing
+    let count = |field: &str| record[field].as_u64();
+    let metrics = match (count("words_checked"), count("depth"), count("failed_first_words"), count("elapsed_ms")) {
+        (Some(words_checked), Some(depth), Some(failed_first_words), Some(elapsed_ms)) => {
+            Some((SolveMetrics { words_checked: words_checked as usize, depth: depth as usize, failed_first_words: failed_first_words as usize }, elapsed_ms))
+        },
+        _ => None
+    };
+    Ok((hand, board, metrics))
 }
 
-/// Plays a word on the board
+/// Reads every board in a directory a file at a time, along with how hard each was to solve if its file recorded it, so that only one
+/// file's boards are ever in memory at once
 /// # Arguments
-/// * `word` - The word to be played
-/// * `row_idx` - The starting row at which to play the word
-/// * `col_idx` - The starting column at which to play the word
-/// * `board` - The current board (is modified in-place)
-/// * `direction` - The `Direction` in which to play the word
-/// * `letters` - The number of each letter currently in the hand
-/// * `letters_on_board` - The number of each letter on the board (is modified in-place)
+/// * `directory` - The directory, whose `.bgb` and `.bgb2` files (of any format version) and JSON Lines board files (named as by
+///   `GenerateArgs::file_stem`) and MessagePack files, which may be compressed, are read, along with a SQLite database if built with the
+///   `sqlite` feature
+/// * `visit` - Called with the hand, the board, and how hard it was to solve along with the time taken in milliseconds (only recorded by
+///   JSON Lines and MessagePack files, SQLite databases, and `.bgb` files written with `--bgb-metrics`) of each board, in the order of the
+///   files' names
 /// # Returns
-/// *`Result` with:*
-/// * `bool` - Whether the word could be validly played
-/// * `Vec<(usize, usize)>` - Vector of the indices played in `board`
-/// * `[usize; 26]`- The remaining letters
-/// * `LetterUsage` - How many letters were used
-/// 
-/// *or empty `Err` if out-of-bounds*
-fn play_word(word: &Word, row_idx: usize, col_idx: usize, board: &mut Board, direction: Direction, letters: &Letters, letters_on_board: &mut Letters) -> Result<(bool, Vec<(usize, usize)>, [usize; 26], LetterUsage), ()> {
-    let mut played_indices: Vec<(usize, usize)> = Vec::with_capacity(MAX_WORD_LENGTH);
-    match direction {
-        Direction::Horizontal => {
-            if col_idx + word.len() >= BOARD_SIZE {
-                return Err(());
-            }
-            let mut remaining_letters = letters.clone();
-            // Check if the word will start or end at a letter
-            let mut valid_loc = (col_idx != 0 && board.get_val(row_idx, col_idx-1) != EMPTY_VALUE) || (BOARD_SIZE-col_idx <= word.len() && board.get_val(row_idx, col_idx+word.len()) != EMPTY_VALUE);
-            // Check if the word will border any letters on the top or bottom
-            valid_loc |= (col_idx..col_idx+word.len()).any(|c_idx| (row_idx < BOARD_SIZE-1 && board.get_val(row_idx+1, c_idx) != EMPTY_VALUE) || (row_idx > 0 && board.get_val(row_idx-1, c_idx) != EMPTY_VALUE));
-            if !valid_loc {
-                return Ok((false, played_indices, remaining_letters, LetterUsage::Remaining));
-            }
-            else {
-                let mut entirely_overlaps = true;
-                for i in 0..word.len() {
-                    if board.get_val(row_idx, col_idx+i) == EMPTY_VALUE {
-                        board.set_val(row_idx, col_idx+i, word[i]);
-                        letters_on_board[word[i]] += 1;
-                        played_indices.push((row_idx, col_idx+i));
-                        entirely_overlaps = false;
-                        let elem = unsafe { remaining_letters.get_unchecked_mut(word[i]) };
-                        if *elem == 0 {
-                            return Ok((false, played_indices, remaining_letters, LetterUsage::Overused));
-                        }
-                        *elem -= 1;
-                    }
-                    else if board.get_val(row_idx, col_idx+i) != word[i] {
-                        return Ok((false, played_indices, remaining_letters, LetterUsage::Remaining));
-                    }
-                }
-                if remaining_letters.iter().all(|count| *count == 0) && !entirely_overlaps {
-                    return Ok((true, played_indices, remaining_letters, LetterUsage::Finished));
-                }
-                else {
-                    return Ok((!entirely_overlaps, played_indices, remaining_letters, LetterUsage::Remaining));
-                }
+/// * `Result` with nothing, or an error message if a file couldn't be read
+fn for_each_board(directory: &Path, mut visit: impl FnMut(&Letters, &Board, Option<(SolveMetrics, u64)>)) -> Result<(), String> {
+    let entries = fs::read_dir(directory).map_err(|e| format!("Failed to read {}: {}", directory.display(), e))?;
+    let mut paths: Vec<PathBuf> = entries.map(|entry| entry.map(|entry| entry.path())).collect::<Result<_, _>>().map_err(|e| format!("Failed to read {}: {}", directory.display(), e))?;
+    paths.sort();
+    for path in paths.iter() {
+        let name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+        let name = name.trim_end_matches(Compression::Gzip.suffix()).trim_end_matches(Compression::Zstd.suffix());
+        // The negatives and partial boards files alongside each board file aren't solved boards, and nor are JSON Lines files other than
+        // board files (such as the plays and words files)
+        if name.ends_with(".negatives.bgb") || name.ends_with(".partial.bgb") || name.strip_suffix(".jsonl").is_some_and(|stem| !is_board_file_stem(stem)) {
+            continue;
+        }
+        #[cfg(feature = "sqlite")]
+        if name.ends_with(".sqlite") {
+            sqlite::read_boards(path, &mut visit)?;
+            continue;
+        }
+        if !(name.ends_with(".bgb") || name.ends_with(".bgb2") || name.ends_with(".jsonl") || name.ends_with(".msgpack")) {
+            continue;
+        }
+        let bytes = fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        if name.ends_with(".jsonl") {
+            let (bytes, _) = decompress(&bytes).map_err(|e| format!("Invalid board file {}: {}", path.display(), e))?;
+            // Only complete lines, since the last may have been cut off
+            let mut start = 0;
+            for end in complete_lines(&bytes) {
+                let (hand, board, metrics) = parse_jsonl_record(&bytes[start..end]).map_err(|e| format!("Invalid board file {}: {}", path.display(), e))?;
+                visit(&hand, &board, metrics);
+                start = end;
             }
-        },
-        Direction::Vertical => {
-            if row_idx + word.len() >= BOARD_SIZE {
-                return Err(());
-            }
-            let mut remaining_letters = letters.clone();
-            // Check if the word will start or end at a letter
-            let mut valid_loc = (row_idx != 0 && board.get_val(row_idx-1, col_idx) != EMPTY_VALUE) || (BOARD_SIZE-row_idx <= word.len() && board.get_val(row_idx+word.len(), col_idx) != EMPTY_VALUE);
-            // Check if the word will border any letters on the right or left
-            valid_loc |= (row_idx..row_idx+word.len()).any(|r_idx| (col_idx < BOARD_SIZE-1 && board.get_val(r_idx, col_idx+1) != EMPTY_VALUE) || (col_idx > 0 && board.get_val(r_idx, col_idx-1) != EMPTY_VALUE));
-            if !valid_loc {
-                return Ok((false, played_indices, remaining_letters, LetterUsage::Remaining));
-            }
-            else {
-                let mut entirely_overlaps = true;
-                for i in 0..word.len() {
-                    if board.get_val(row_idx+i, col_idx) == EMPTY_VALUE {
-                        board.set_val(row_idx+i, col_idx, word[i]);
-                        letters_on_board[word[i]] += 1;
-                        played_indices.push((row_idx+i, col_idx));
-                        entirely_overlaps = false;
-                        let elem = unsafe { remaining_letters.get_unchecked_mut(word[i]) };
-                        if *elem == 0 {
-                            return Ok((false, played_indices, remaining_letters, LetterUsage::Overused));
-                        }
-                        *elem -= 1;
-                    }
-                    else if board.get_val(row_idx+i, col_idx) != word[i] {
-                        return Ok((false, played_indices, remaining_letters, LetterUsage::Remaining));
-                    }
-                }
-                if remaining_letters.iter().all(|count| *count == 0) && !entirely_overlaps {
-                    return Ok((true, played_indices, remaining_letters, LetterUsage::Finished));
-                }
-                else {
-                    return Ok((!entirely_overlaps, played_indices, remaining_letters, LetterUsage::Remaining));
-                }
+        }
+        else if name.ends_with(".msgpack") {
+            let (bytes, _) = decompress(&bytes).map_err(|e| format!("Invalid board file {}: {}", path.display(), e))?;
+            // Only complete records, since the last may have been cut off
+            for record in msgpack::read_records(&bytes).map_err(|e| format!("Invalid board file {}: {}", path.display(), e))?.iter() {
+                let (hand, board, metrics) = msgpack_to_json(record).and_then(parse_board_record).map_err(|e| format!("Invalid board file {}: {}", path.display(), e))?;
+                visit(&hand, &board, metrics);
+            }
+        }
+        else {
+            let boards = read_boards(&bytes).map_err(|e| format!("Invalid board file {}: {}", path.display(), e))?;
+            let (bytes, _) = decompress(&bytes).map_err(|e| format!("Invalid board file {}: {}", path.display(), e))?;
+            let metrics = match bytes.first() {
+                // Only complete boards, since the last may have been cut off
+                Some(&BGB_METRICS_FORMAT_VERSION) => {
+                    let ends = complete_bgb_records(&bytes).map_err(|e| format!("Invalid board file {}: {}", path.display(), e))?;
+                    let end = ends.last().copied().unwrap_or(1);
+                    parse_bgb_metrics::<ENGLISH_ALPHABET_SIZE>(&bytes[..end]).map_err(|e| format!("Invalid board file {}: {}", path.display(), e))?
+                },
+                _ => None
+            };
+            let mut metrics = metrics.map(|metrics| metrics.into_iter());
+            for (letters, board, _, _, _, _) in boards.iter() {
+                visit(letters, board, metrics.as_mut().and_then(|metrics| metrics.next()));
             }
         }
     }
+    Ok(())
 }
 
-/// Checks which words can be played after the first
+/// Prints statistics about the boards in a directory: the number by hand size along with how often hands of each size couldn't be solved,
+/// how hard they were to solve, their sizes, how often each letter and word is played, and how many are duplicates
 /// # Arguments
-/// * `letters` - Length-26 array of originally available letters
-/// * `word_being_checked` - Word that is being checked if playable
-/// * `played_on_board` - Set of the letters played on the board
+/// * `data` - The directory of boards (see `for_each_board`)
+/// * `failures` - File of hands that couldn't be solved (see `FailureRecord`), if any
+/// * `json` - Whether to print the statistics as JSON (see `dataset::DatasetReport`) instead of as tables
 /// # Returns
-/// * `bool` - Whether the `word_being_checked` is playable
-fn check_filter_after_play(mut letters: Letters, word_being_checked: &Word, played_on_board: &HashSet<&usize>) -> bool {
-    let mut already_seen_negative = false;
-    for letter in word_being_checked.iter() {
-        let elem = unsafe { letters.get_unchecked_mut(*letter) };
-        if *elem == 0 && !played_on_board.contains(letter) {
-            return false;
-        }
-        else if *elem <= 0 && already_seen_negative {
-            return false;
+/// * `Result` with nothing, or an error message if a file couldn't be read
+fn print_stats(data: &Path, failures: Option<&Path>, json: bool) -> Result<(), String> {
+    let mut stats = DatasetStats::default();
+    for_each_board(data, |hand, board, metrics| stats.record_board(hand, board, metrics))?;
+    if let Some(failures) = failures {
+        let text = fs::read_to_string(failures).map_err(|e| format!("Failed to read {}: {}", failures.display(), e))?;
+        for (line_num, line) in text.lines().enumerate().filter(|(_, line)| !line.is_empty()) {
+            let record: FailureRecord = serde_json::from_str(line).map_err(|e| format!("Invalid failures file {} (line {}): {}", failures.display(), line_num+1, e))?;
+            let reason = SolveFailure::from_name(&record.reason).ok_or_else(|| format!("Invalid failures file {} (line {}): unknown reason {}", failures.display(), line_num+1, record.reason))?;
+            stats.record_failure(record.hand_size, reason);
         }
-        else if *elem == 0 {
-            already_seen_negative = true;
+    }
+    let report = stats.report();
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report).expect("Failed to convert the statistics to JSON!"));
+    }
+    else {
+        report.print();
+    }
+    Ok(())
+}
+
+/// Writes a JSON file (such as the manifest), first writing it to a temporary file that is then renamed so that a crash never leaves a
+/// partial file
+/// # Arguments
+/// * `path` - Path to write the file to
+/// * `value` - The contents of the file
+/// # Returns
+/// * `io::Result<()>` - Empty, or an error if the file couldn't be written
+fn write_json(path: &Path, value: &impl Serialize) -> io::Result<()> {
+    let temp_path = path.with_extension("json.tmp");
+    let mut file = File::create(&temp_path)?;
+    serde_json::to_writer_pretty(&mut file, value)?;
+    file.write_all(b"\n")?;
+    file.sync_all()?;
+    fs::rename(&temp_path, path)
+}
+
+/// Checks that every file listed in a manifest is present with the size and number of boards listed
+/// # Arguments
+/// * `path` - Path to the manifest
+/// # Returns
+/// * `Result` with the number of files and boards checked, or an error message for each problem found
+fn verify_manifest(path: &Path) -> Result<(usize, usize), Vec<String>> {
+    let text = fs::read_to_string(path).map_err(|e| vec![format!("Failed to read {}: {}", path.display(), e)])?;
+    let manifest: Manifest = serde_json::from_str(&text).map_err(|e| vec![format!("Invalid manifest {}: {}", path.display(), e)])?;
+    let format = OutputFormat::from_str(&manifest.format, false).map_err(|e| vec![format!("Invalid manifest {}: {}", path.display(), e)])?;
+    let directory = path.parent().unwrap_or(Path::new("."));
+    let mut errors: Vec<String> = Vec::new();
+    for shard in manifest.shards.iter() {
+        let shard_path = directory.join(&shard.file);
+        let bytes = match fs::metadata(&shard_path) {
+            Ok(metadata) => metadata.len(),
+            Err(e) => {
+                errors.push(format!("Failed to read {}: {}", shard_path.display(), e));
+                continue;
+            }
+        };
+        if bytes != shard.bytes {
+            errors.push(format!("{} is {} bytes but the manifest lists {}", shard_path.display(), bytes, shard.bytes));
         }
-        else {
-            *elem -= 1;
+        match format.count_boards(&shard_path) {
+            Ok(count) if count != shard.boards => errors.push(format!("{} has {} boards but the manifest lists {}", shard_path.display(), count, shard.boards)),
+            Ok(_) => {},
+            Err(e) => errors.push(format!("Invalid board file {}: {}", shard_path.display(), e))
         }
     }
-    return true;
+    if errors.is_empty() {
+        Ok((manifest.shards.len(), manifest.shards.iter().map(|shard| shard.boards).sum()))
+    }
+    else {
+        Err(errors)
+    }
 }
 
-/// Removes words that can't be played with `current_letters` plus a set number of `board_letters`
+/// Plays out the rest of a game from a solved hand, drawing the tiles left in the tile set one at a time in a random order and adding each
+/// to the board (see `peel`) until the tiles run out, one can't be added, or the run is stopped
 /// # Arguments
-/// * `current_letters` - Letters currently available in the hand
-/// * `board_letters` - Letters played on the board
-/// * `word_being_checked` - Word to check if it contains the appropriate number of letters
+/// * `first` - The solved hand, as returned by `play_bananagrams`
+/// * `letters` - Number of each letter in the hand
+/// * `tiles` - The tile set the hand was drawn from
+/// * `dictionary` - The words to solve with
+/// * `banned_words` - Words that must never appear on a board (the game ends if adding a tile forms one)
+/// * `args` - Options for generating boards
+/// * `timeout_per_tile` - Maximum time to spend adding each tile, if any
+/// * `stop` - Flag that, once set, ends the game as soon as possible
+/// * `rng` - Random number generator to draw the tiles with
 /// # Returns
-/// * `bool` - Whether `word_being_checked` should pass the filter
-fn check_filter_after_play_later(mut current_letters: Letters, mut board_letters: Letters, word_being_checked: &Word) -> bool {
-    let mut num_from_board = 0u8;
-    for letter in word_being_checked.iter() {
-        let num_in_hand = unsafe { current_letters.get_unchecked_mut(*letter)};
-        if *num_in_hand == 0 {
-            if num_from_board == FILTER_LETTERS_ON_BOARD {
-                return false;
-            }
-            let num_on_board = unsafe { board_letters.get_unchecked_mut(*letter)};
-            if *num_on_board == 0 {
-                return false;
-            }
-            *num_on_board -= 1;
-            num_from_board += 1;
+/// * `Vec<(Letters, Solution)>` - The number of each letter on the board and the board
+///   (as returned by `peel`) after each step of the game, starting with the solved hand
+#[allow(clippy::too_many_arguments, reason = "the game needs the solved hand, its tile set and dictionary, and the options for solving")]
+fn simulate_game(first: Solution, letters: Letters, tiles: &TileSet, dictionary: &Vec<Word>, banned_words: &HashSet<Word>, args: &GenerateArgs, timeout_per_tile: Option<Duration>, stop: &AtomicBool, rng: &mut impl Rng) -> Vec<(Letters, Solution)> {
+    let mut pool: Vec<u8> = tiles.counts::<ENGLISH_ALPHABET_SIZE>().iter().zip(letters.iter()).enumerate()
+        .flat_map(|(letter, (in_set, in_hand))| iter::repeat_n(letter as u8, in_set.saturating_sub(*in_hand) as usize)).collect();
+    pool.shuffle(rng);
+    let mut steps = vec![(letters, first)];
+    for new_letter in pool {
+        if stop.load(Ordering::Relaxed) {
+            break;
         }
-        else {
-            *num_in_hand -= 1;
+        let (on_board, Solution { board, .. }) = steps.last().expect("No steps!");
+        let deadline = timeout_per_tile.map(|timeout| Instant::now() + timeout);
+        match peel::<ENGLISH_ALPHABET_SIZE>(board, new_letter, dictionary, &SolveOptions { max_words_checked: args.max_words_checked, stop: Some(stop), deadline, board_size: args.board_size, min_word_length: args.min_word_length, ..SolveOptions::default() }) {
+            Ok(result) if find_banned_words(&result.board, result.min_col, result.max_col, result.min_row, result.max_row, banned_words).is_empty() => {
+                let mut on_board = *on_board;
+                on_board[new_letter as usize] += 1;
+                steps.push((on_board, result));
+            },
+            _ => break
         }
     }
-    return true;
+    steps
 }
 
-/// Undoes a play on the `board`
+/// Prints a board for a person to read: as a grid with coordinates and colors if printing to a terminal (see `board_to_pretty_string`), or
+/// otherwise in the plain form of `board_to_string`
 /// # Arguments
-/// * `board` - `Board` being undone (is modified in-place)
-/// * `played_indices` - Vector of the indices in `board` that need to be reset
-/// * `letters_on_board` - Length-26 array of the number of each letter on the board (is modified in place)
-fn undo_play(board: &mut Board, played_indices: &Vec<(usize, usize)>, letters_on_board: &mut Letters) {
-    for index in played_indices.iter() {
-        letters_on_board[board.get_val(index.0, index.1)] -= 1;
-        board.set_val(index.0, index.1, EMPTY_VALUE);
+/// * `board` - The board, which mustn't be empty
+/// * `highlight` - The (row, column) of each tile to highlight in a terminal
+fn print_board(board: &Board, highlight: &[(usize, usize)]) {
+    if io::stdout().is_terminal() {
+        let (min_col, max_col, min_row, max_row) = board.bounds().expect("Board is empty!");
+        println!("{}", board_to_pretty_string(board, min_col, max_col, min_row, max_row, &PrettyOptions { coordinates: true, color: true, highlight }));
+    }
+    else {
+        println!("{}", board_to_string(board));
     }
 }
 
-/// Recursively solves Bananagrams
+/// Loads the dictionary a subcommand plays with
 /// # Arguments
-/// * `board` - The `Board` to modify in-place
-/// * `min_col` - Minimum occupied column index in `board`
-/// * `max_col` - Maximum occupied column index in `board`
-/// * `min_row` - Minimum occupied row index in `board`
-/// * `max_row` - Maximum occupied row index in `board`
-/// * `valid_words_vec` - Vector of vectors, each representing a word (see `convert_word_to_array`)
-/// * `valid_words_set` - HashSet of vectors, each representing a word (a HashSet version of `valid_words_vec` for faster membership checking)
-/// * `letters` - Length-26 array of the number of each letter in the hand
-/// * `depth` - Depth of the current recursive call
-/// * `words_checked` - The number of words checked in total
-/// * `letters_on_board` - Length-26 array of the number of each letter currently present on the `board`
+/// * `path` - File of valid words, one per line, or `None` for the dictionary built in at compile time
+/// * `min_word_length` - Minimum length of a word; shorter words are removed
 /// # Returns
-/// *`Result` with:*
-/// * `bool` - Whether the word could be validly played
-/// * `usize` - Minimum occupied column index in `board`
-/// * `usize` - Maximum occupied column index in `board`
-/// * `usize` - Minimum occupied row index in `board`
-/// * `usize` - Maximum occupied row index in `board`
-/// 
-/// *or empty `Err` on if out-of-bounds or past the maximum number of words to check*
-fn play_further(board: &mut Board, min_col: usize, max_col: usize, min_row: usize, max_row: usize, valid_words_vec: Vec<&Word>, valid_words_set: &HashSet<Word>, letters: Letters, depth: usize, words_checked: &mut usize, letters_on_board: &mut Letters) -> Result<(bool, usize, usize, usize, usize), ()> {
-    if *words_checked > MAXIMUM_WORDS_CHECKED {
-        return Err(());
-    }
-    // If we're at an odd depth, play horizontally first (trying to alternate horizontal-vertical-horizontal as a heuristic to solve faster)
-    if depth % 2 == 1 {
-        for word in valid_words_vec.iter() {
-            *words_checked += 1;
-            // Try across all rows (starting from one before to one after)
-            for row_idx in min_row-1..max_row+2 {
-                // For each row, try across all columns (starting from the farthest out the word could be played)
-                for col_idx in min_col-word.len()..max_col+2 {
-                    // Using the ? because `play_word` can give an `Err` if the index is out of bounds
-                    let res = play_word(word, row_idx, col_idx, board, Direction::Horizontal, &letters, letters_on_board)?;
-                    if res.0 {
-                        // If the word was played successfully (i.e. it's not a complete overlap and it borders at least one existing tile), then check the validity of the new words it forms
-                        let new_min_col = cmp::min(min_col, col_idx);
-                        let new_max_col = cmp::max(max_col, col_idx+word.len());
-                        let new_min_row = cmp::min(min_row, row_idx);
-                        let new_max_row = cmp::max(max_row, row_idx);
-                        if is_board_valid_horizontal(board, new_min_col, new_max_col, new_min_row, new_max_row, row_idx, col_idx, col_idx+word.len()-1, valid_words_set) {
-                            // If it's valid, go to the next recursive level (unless we've all the letters, at which point we're done)
-                            match res.3 {
-                                LetterUsage::Finished => {
-                                    return Ok((true, new_min_col, new_max_col, new_min_row, new_max_row));
-                                },
-                                LetterUsage::Remaining => {
-                                    // Another option: let new_valid_words_vec: Vec<&Word> = valid_words_vec.clone().into_iter().filter(|w| check_filter_after_play_later(letters.clone(), letters_on_board.clone(), w)).collect();
-                                    // I think doing it that way might be less efficient however due to the `clone` of `valid_words_vec`
-                                    let mut new_valid_words_vec: Vec<&Word> = Vec::with_capacity(valid_words_vec.len()/2);
-                                    for i in 0..valid_words_vec.len() {
-                                        if check_filter_after_play_later(letters.clone(), letters_on_board.clone(), valid_words_vec[i]) {
-                                            new_valid_words_vec.push(valid_words_vec[i]);
-                                        }
-                                    }
-                                    let res2 = play_further(board, new_min_col, new_max_col, new_min_row, new_max_row, new_valid_words_vec, valid_words_set, res.2, depth+1, words_checked, letters_on_board)?;
-                                    if res2.0 {
-                                        // If that recursive stack finishes successfully, we're done! (could have used another Result or Option rather than a bool in the returned tuple, but oh well)
-                                        return Ok(res2);
-                                    }
-                                    else {
-                                        // Otherwise, undo the previous play (cloning the board before each play so we don't have to undo is *way* slower)
-                                        undo_play(board, &res.1, letters_on_board);
-                                    }
-                                },
-                                LetterUsage::Overused => unreachable!()
-                            }
-                        }
-                        else {
-                            // If the play formed some invalid words, undo the previous play
-                            undo_play(board, &res.1, letters_on_board);
-                        }
-                    }
-                    else {
-                        // If trying to play the board was invalid, undo the play
-                        undo_play(board, &res.1, letters_on_board);
-                    }
-                }
-            }
-        }
-        // If trying every word horizontally didn't work, try vertically instead
-        for word in valid_words_vec.iter() {
-            *words_checked += 1;
-            // Try down all columns
-            for col_idx in min_col-1..max_col+2 {
-                // This is analgous to the above
-                for row_idx in min_row-word.len()..max_row+2 {
-                    let res = play_word(word, row_idx, col_idx, board, Direction::Vertical, &letters, letters_on_board)?;
-                    if res.0 {
-                        let new_min_col = cmp::min(min_col, col_idx);
-                        let new_max_col = cmp::max(max_col, col_idx);
-                        let new_min_row = cmp::min(min_row, row_idx);
-                        let new_max_row = cmp::max(max_row, row_idx+word.len());
-                        if is_board_valid_vertical(board, new_min_col, new_max_col, new_min_row, new_max_row, row_idx, row_idx+word.len()-1, col_idx, valid_words_set) {
-                            match res.3 {
-                                LetterUsage::Finished => {
-                                    return Ok((true, new_min_col, new_max_col, new_min_row, new_max_row));
-                                },
-                                LetterUsage::Remaining => {
-                                    let mut new_valid_words_vec: Vec<&Word> = Vec::with_capacity(valid_words_vec.len()/2);
-                                    for i in 0..valid_words_vec.len() {
-                                        if check_filter_after_play_later(letters.clone(), letters_on_board.clone(), valid_words_vec[i]) {
-                                            new_valid_words_vec.push(valid_words_vec[i]);
-                                        }
-                                    }
-                                    let res2 = play_further(board, new_min_col, new_max_col, new_min_row, new_max_row, new_valid_words_vec, valid_words_set, res.2, depth+1, words_checked, letters_on_board)?;
-                                    if res2.0 {
-                                        return Ok(res2);
-                                    }
-                                    else {
-                                        undo_play(board, &res.1, letters_on_board);
-                                    }
-                                },
-                                LetterUsage::Overused => unreachable!()
-                            }
-                        }
-                        else {
-                            undo_play(board, &res.1, letters_on_board);
-                        }
-                    }
-                    else {
-                        undo_play(board, &res.1, letters_on_board);
-                    }
-                }
-            }
+/// * `Result` with the words, or an error message if the file couldn't be read or has no words long enough
+fn load_words(path: Option<&Path>, min_word_length: usize) -> Result<Vec<Word>, String> {
+    let dictionary = match path {
+        // Read from the dictionary's cache if it has one (see `dictionary_cache`), since this is done on every start
+        Some(path) => load_preprocessed_dictionary(path, false, min_word_length).map(|dictionary| dictionary.words),
+        None => parse_weighted_dictionary(include_str!("../../new_short_dictionary.txt"), false).map(|(mut words, _)| {
+            remove_short_words(&mut words, None, min_word_length);
+            words
+        })
+    }.map_err(|e| e.to_string())?;
+    if dictionary.is_empty() {
+        return Err(format!("The dictionary has no words of at least {} letters (--min-word-length)", min_word_length));
+    }
+    Ok(dictionary)
+}
+
+/// Parses the size of the grid given to `--pad-to`
+/// # Arguments
+/// * `text` - The size, as rows by columns (like `32x32`)
+/// # Returns
+/// * `Result` with the number of rows and columns, or an error message if `text` isn't a size
+fn parse_pad_to(text: &str) -> Result<(usize, usize), String> {
+    let size = text.to_ascii_lowercase().split_once('x').and_then(|(height, width)| Some((height.trim().parse::<usize>().ok()?, width.trim().parse::<usize>().ok()?)));
+    match size {
+        Some((height, width)) if height > 0 && width > 0 => Ok((height, width)),
+        _ => Err(format!("Invalid size {:?} (must be like 32x32)", text))
+    }
+}
+
+/// Parses a hand of letters given to the `solve` subcommand
+/// # Arguments
+/// * `text` - The hand, as a string of letters (like `AAEINRSTQUZ`) or as the count of each letter (like `A:3,E:2,Q:1`), in either case
+///   and with any whitespace ignored
+/// # Returns
+/// * `Result` with the number of each letter, or an error message if `text` isn't a hand
+fn parse_hand(text: &str) -> Result<Letters, String> {
+    let text: String = text.chars().filter(|c| !c.is_whitespace()).collect::<String>().to_ascii_uppercase();
+    let mut counts = [0usize; ENGLISH_ALPHABET_SIZE];
+    if text.contains(':') {
+        for entry in text.split(',').filter(|entry| !entry.is_empty()) {
+            let count = match entry.split_once(':') {
+                Some((letter, count)) if letter.len() == 1 && letter.as_bytes()[0].is_ascii_uppercase() => count.parse::<usize>().map(|count| (letter.as_bytes()[0], count)).ok(),
+                _ => None
+            };
+            let (letter, count) = count.ok_or_else(|| format!("Invalid letter count {:?} (must be like A:3)", entry))?;
+            counts[(letter - b'A') as usize] += count;
         }
-        return Ok((false, min_col, max_col, min_row, max_row));
     }
-    // If we're at an even depth, play vertically first. Otherwise this is analgous to the above.
     else {
-        for word in valid_words_vec.iter() {
-            *words_checked += 1;
-            // Try down all columns
-            for col_idx in min_col-1..max_col+2 {
-                for row_idx in min_row-word.len()..max_row+2 {
-                    let res = play_word(word, row_idx, col_idx, board, Direction::Vertical, &letters, letters_on_board)?;
-                    if res.0 {
-                        let new_min_col = cmp::min(min_col, col_idx);
-                        let new_max_col = cmp::max(max_col, col_idx);
-                        let new_min_row = cmp::min(min_row, row_idx);
-                        let new_max_row = cmp::max(max_row, row_idx+word.len());
-                        if is_board_valid_vertical(board, new_min_col, new_max_col, new_min_row, new_max_row, row_idx, row_idx+word.len()-1, col_idx, valid_words_set) {
-                            match res.3 {
-                                LetterUsage::Finished => {
-                                    return Ok((true, new_min_col, new_max_col, new_min_row, new_max_row));
-                                },
-                                LetterUsage::Remaining => {
-                                    let mut new_valid_words_vec: Vec<&Word> = Vec::with_capacity(valid_words_vec.len()/2);
-                                    for i in 0..valid_words_vec.len() {
-                                        if check_filter_after_play_later(letters.clone(), letters_on_board.clone(), valid_words_vec[i]) {
-                                            new_valid_words_vec.push(valid_words_vec[i]);
-                                        }
-                                    }
-                                    let res2 = play_further(board, new_min_col, new_max_col, new_min_row, new_max_row, new_valid_words_vec, valid_words_set, res.2, depth+1, words_checked, letters_on_board)?;
-                                    if res2.0 {
-                                        return Ok(res2);
-                                    }
-                                    else {
-                                        undo_play(board, &res.1, letters_on_board);
-                                    }
-                                },
-                                LetterUsage::Overused => unreachable!()
-                            }
-                        }
-                        else {
-                            undo_play(board, &res.1, letters_on_board);
-                        }
-                    }
-                    else {
-                        undo_play(board, &res.1, letters_on_board);
-                    }
-                }
+        for c in text.chars() {
+            if !c.is_ascii_uppercase() {
+                return Err(format!("Invalid letter {:?} in hand", c));
             }
+            counts[c as usize - 'A' as usize] += 1;
         }
-        // No point in checking horizontally for the first depth, since it would have to form a vertical word that was already checked and failed
-        if depth == 0 {
-            return Ok((false, min_col, max_col, min_row, max_row));
-        }
-        for word in valid_words_vec.iter() {
-            *words_checked += 1;
-            // Try across all rows
-            for row_idx in min_row-1..max_row+2 {
-                for col_idx in min_col-word.len()..max_col+2 {
-                    let res = play_word(word, row_idx, col_idx, board, Direction::Horizontal, &letters, letters_on_board)?;
-                    if res.0 {
-                        let new_min_col = cmp::min(min_col, col_idx);
-                        let new_max_col = cmp::max(max_col, col_idx+word.len());
-                        let new_min_row = cmp::min(min_row, row_idx);
-                        let new_max_row = cmp::max(max_row, row_idx);
-                        if is_board_valid_horizontal(board, new_min_col, new_max_col, new_min_row, new_max_row, row_idx, col_idx, col_idx+word.len()-1, valid_words_set) {
-                            match res.3 {
-                                LetterUsage::Finished => {
-                                    return Ok((true, new_min_col, new_max_col, new_min_row, new_max_row));
-                                },
-                                LetterUsage::Remaining => {
-                                    let mut new_valid_words_vec: Vec<&Word> = Vec::with_capacity(valid_words_vec.len()/2);
-                                    for i in 0..valid_words_vec.len() {
-                                        if check_filter_after_play_later(letters.clone(), letters_on_board.clone(), valid_words_vec[i]) {
-                                            new_valid_words_vec.push(valid_words_vec[i]);
-                                        }
-                                    }
-                                    let res2 = play_further(board, new_min_col, new_max_col, new_min_row, new_max_row, new_valid_words_vec, valid_words_set, res.2, depth+1, words_checked, letters_on_board)?;
-                                    if res2.0 {
-                                        return Ok(res2);
-                                    }
-                                    else {
-                                        undo_play(board, &res.1, letters_on_board);
-                                    }
-                                },
-                                LetterUsage::Overused => unreachable!()
-                            }
-                        }
-                        else {
-                            undo_play(board, &res.1, letters_on_board);
-                        }
-                    }
-                    else {
-                        undo_play(board, &res.1, letters_on_board);
-                    }
-                }
+    }
+    if counts.iter().all(|&count| count == 0) {
+        return Err("The hand has no letters".to_string());
+    }
+    let mut hand: Letters = [0; ENGLISH_ALPHABET_SIZE];
+    for (letter, (&count, slot)) in counts.iter().zip(hand.iter_mut()).enumerate() {
+        *slot = u8::try_from(count).map_err(|_| format!("Too many of the letter {} in hand (at most 255 are allowed)", (letter as u8 + b'A') as char))?;
+    }
+    Ok(hand)
+}
+
+/// Solves a single hand (see `SolveArgs`), printing the board, the words played (with rows and columns counted from the board's top-left
+/// corner), the number of words checked, and the time taken, or why no solution was found
+/// # Arguments
+/// * `args` - Options for solving the hand
+/// # Returns
+/// * `Result` with whether the hand was solved, or an error message if it couldn't be tried
+fn solve_hand(args: &SolveArgs) -> Result<bool, String> {
+    let text = match &args.letters {
+        Some(letters) => letters.clone(),
+        None => io::read_to_string(io::stdin()).map_err(|e| format!("Failed to read the hand from standard input: {}", e))?
+    };
+    let letters = parse_hand(&text)?;
+    if !args.no_pool_check {
+        let tiles = match &args.tiles {
+            Some(path) => TileSet::load(path, &Alphabet::english())?,
+            None => TileSet::standard()
+        };
+        check_hand_against_pool(&letters, &tiles).map_err(|e| format!("{} (use --no-pool-check to allow this)", e))?;
+    }
+    let deadline = match args.timeout {
+        Some(seconds) => Some(Instant::now() + Duration::try_from_secs_f64(seconds).ok().filter(|timeout| !timeout.is_zero()).ok_or("--timeout must be a positive number of seconds")?),
+        None => None
+    };
+    let dictionary = load_words(args.dictionary.as_deref(), args.min_word_length)?;
+    let start = Instant::now();
+    let result = play_bananagrams(letters, 0, &dictionary, &SolveOptions { max_words_checked: args.max_words_checked, deadline, min_word_length: args.min_word_length, ..SolveOptions::default() });
+    let elapsed_ms = start.elapsed().as_millis();
+    match result {
+        Ok(solution) => {
+            print_board(&solution.board, &[]);
+            println!("Words:");
+            for play in solution.plays.iter() {
+                println!("  {} {} at row {}, column {}", convert_array_to_word(&play.word), play.direction, play.row - solution.min_row, play.col - solution.min_col);
             }
+            println!("Solved in {} ms after checking {} words", elapsed_ms, solution.metrics.words_checked);
+            Ok(true)
+        },
+        Err((reason, words_checked, _)) => {
+            println!("No solution found within budget: {} (after checking {} words in {} ms)", reason, words_checked, elapsed_ms);
+            Ok(false)
+        }
+    }
+}
+
+/// Reads the boards to check or render from a file
+/// # Arguments
+/// * `path` - Path to the file
+/// * `format` - Format of the file
+/// # Returns
+/// * `Result` with each board in the file, or an error message if it couldn't be read or has no boards
+fn read_board_file(path: &Path, format: BoardFileFormat) -> Result<Vec<Board>, String> {
+    let bytes = fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let invalid = |e: String| format!("Invalid board file {}: {}", path.display(), e);
+    let boards: Vec<Board> = match format {
+        BoardFileFormat::Text => {
+            let text = String::from_utf8(bytes).map_err(|e| invalid(e.to_string()))?;
+            vec![text.trim_end_matches(['\n', '\r']).parse().map_err(|e: BoardParseError| invalid(e.to_string()))?]
+        },
+        BoardFileFormat::Bgb => read_boards(&bytes).map_err(invalid)?.into_iter().map(|(_, board, _, _, _, _)| board).collect(),
+        BoardFileFormat::Jsonl => {
+            let (bytes, _) = decompress(&bytes).map_err(invalid)?;
+            let text = std::str::from_utf8(&bytes).map_err(|e| invalid(e.to_string()))?;
+            text.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()).map(|(i, line)| {
+                let record: serde_json::Value = serde_json::from_str(line).map_err(|e| invalid(format!("line {}: {}", i+1, e)))?;
+                let rows: Vec<String> = serde_json::from_value(record["board"].clone()).map_err(|e| invalid(format!("line {}: {}", i+1, e)))?;
+                rows.join("\n").parse().map_err(|e: BoardParseError| invalid(format!("line {}: {}", i+1, e)))
+            }).collect::<Result<_, _>>()?
         }
-        return Ok((false, min_col, max_col, min_row, max_row));
+    };
+    if boards.is_empty() {
+        return Err(format!("No boards in {}", path.display()));
     }
+    Ok(boards)
 }
 
-/// Plays a new bananagrams board using the given letters and dictionary
+/// Checks whether each board in a file is legal, printing the result for each
 /// # Arguments
-/// * `available_letters` - Array of the number of each letter to play with
-/// * `dictionary` - Vector of vectors representing valid words
+/// * `args` - Options for checking the boards
 /// # Returns
-/// * `Option`
-///     * `None` - If no valid play was possible
-///     * `Some` - If successful, a tuple of (the board solution, minimum column, maximum column, minimum row, maximum row)
-fn play_bananagrams(available_letters: [usize; 26], dictionary: &Vec<Word>) -> Option<(Board, usize, usize, usize, usize)> {
-    // Get a vector of all valid words
-    let valid_words_vec: Vec<Word> = dictionary.iter().filter(|word| is_makeable(word, &available_letters)).map(|word| word.clone()).collect();
-    if valid_words_vec.len() == 0 {
-        return None;
-    }
-    let mut words_checked = 0;
-    // Loop through each word and play it on a new board
-    for (word_num, word) in valid_words_vec.iter().enumerate() {
-        words_checked += 1;
-        let mut board = Board::new();
-        let col_start = BOARD_SIZE/2 - word.len()/2;
-        let row = BOARD_SIZE/2;
-        let mut use_letters: [usize; 26] = available_letters.clone();
-        let mut letters_on_board = [0usize; 26];
-        for i in 0..word.len() {
-            board.set_val(row, col_start+i, word[i]);
-            letters_on_board[word[i]] += 1;
-            use_letters[word[i]] -= 1;  // Should never underflow because we've verified that every word is playable with these letters
-        }
-        let min_col = col_start;
-        let min_row = row;
-        let max_col = col_start + (word.len()-1);
-        let max_row = row;
-        if use_letters.iter().all(|count| *count == 0) {
-            return Some((board.clone(), min_col, max_col, min_row, max_row));
+/// * `Result` with whether every board is legal, or an error message if the file or dictionary couldn't be read
+fn validate_boards(args: &ValidateArgs) -> Result<bool, String> {
+    let boards = read_board_file(&args.input, args.format)?;
+    let valid_words: HashSet<Word> = load_words(args.dictionary.as_deref(), args.min_word_length)?.into_iter().collect();
+    let mut all_valid = true;
+    for (i, board) in boards.iter().enumerate() {
+        let check = validate::check_board(i, board, &valid_words, args.min_word_length);
+        all_valid &= check.valid;
+        if args.json {
+            println!("{}", serde_json::to_string(&check).expect("Failed to convert the result to JSON!"));
         }
         else {
-            // Reduce the set of remaining words to check to those that can be played with the letters not in the first word (plus only one of the tiles played in the first word)
-            let word_letters: HashSet<&usize> = HashSet::from_iter(word.iter());
-            let mut new_valid_words_vec: Vec<&Word> = Vec::with_capacity(valid_words_vec.len()-word_num);
-            for i in word_num..valid_words_vec.len() {
-                if check_filter_after_play(use_letters.clone(), &valid_words_vec[i], &word_letters) {
-                    new_valid_words_vec.push(&valid_words_vec[i]);
-                }
+            if boards.len() > 1 {
+                print!("Board {}: ", i);
             }
-            let valid_words_set: HashSet<Word> = HashSet::from_iter(valid_words_vec.iter().cloned());
-            // Begin the recursive processing
-            let result = play_further(&mut board, min_col, max_col, min_row, max_row, new_valid_words_vec, &valid_words_set, use_letters, 0, &mut words_checked, &mut letters_on_board);
-            match result {
-                // If the result was good, then we're done (otherwise we continue)
-                Ok(res) => {
-                    if res.0 {
-                        return Some((board.clone(), res.1, res.2, res.3, res.4));
-                    }
-                },
-                // If an error (we're out of bounds or we've reached the maximum number of iterations) then we continue
-                Err(()) => {}
+            println!("{}", check.describe());
+            // Invalid boards are shown too, with the tiles of their invalid words highlighted in a terminal
+            if !check.valid && check.tiles > 0 {
+                let highlight: Vec<(usize, usize)> = render::invalid_cells(board, &valid_words, args.min_word_length).into_iter().collect();
+                print_board(board, &highlight);
             }
         }
     }
-    None
+    Ok(all_valid)
 }
 
-/// Generates a random hand of letters pulled from the entire set of Bananagrams tiles
+/// Renders each board in a file as an image
 /// # Arguments
-/// * `rng` - Thread random number generator
+/// * `args` - Options for rendering the boards
 /// # Returns
-/// * `[usize; 26]` - Number of each letter present in the hand
-fn generate_hand(rng: &mut ThreadRng) -> Letters {    
-    // Calculate the logarithmic scaled value within [min, max]
-    let scaled_value = (MAXIMUM_HAND_SIZE - MINIMUM_HAND_SIZE) * (BASE.powf(rng.gen()) - 1.0) / (BASE - 1.0) + MINIMUM_HAND_SIZE;
-    
-    // Convert to an integer
-    let size = scaled_value.round() as usize;
-    let mut letters = [0usize; 26];
-    TO_CHOOSE_FROM.choose_multiple(rng, size).for_each(|c| {
-        letters[(*c) - 65] += 1;
-    });
-    letters
+/// * `Result` with the number of images written, or an error message if the boards couldn't be read or the images written
+fn render_boards(args: &RenderArgs) -> Result<usize, String> {
+    if args.cell_size < render::MIN_CELL_SIZE {
+        return Err(format!("--cell-size must be at least {}", render::MIN_CELL_SIZE));
+    }
+    let highlight_color = render::parse_color(&args.highlight_color)?;
+    let mut boards = read_board_file(&args.input, args.format.unwrap_or_else(|| BoardFileFormat::of_path(&args.input)))?;
+    boards.truncate(args.limit.unwrap_or(usize::MAX));
+    let valid_words: Option<HashSet<Word>> = match args.highlight_invalid {
+        true => Some(load_words(args.dictionary.as_deref(), args.min_word_length)?.into_iter().collect()),
+        false => None
+    };
+    fs::create_dir_all(&args.out).map_err(|e| format!("Failed to create {}: {}", args.out.display(), e))?;
+    // Padded so that the files sort in the order of the boards
+    let digits = boards.len().saturating_sub(1).to_string().len();
+    for (i, board) in boards.iter().enumerate() {
+        let options = render::RenderOptions {
+            cell_size: args.cell_size,
+            highlight_color,
+            highlight: valid_words.as_ref().map(|valid_words| render::invalid_cells(board, valid_words, args.min_word_length)).unwrap_or_default(),
+            ..Default::default()
+        };
+        let (extension, image) = match args.image_format {
+            ImageFormat::Svg => ("svg", render::board_to_svg(board, &options).into_bytes()),
+            #[cfg(feature = "png")]
+            ImageFormat::Png => ("png", render::board_to_png(board, &options))
+        };
+        let path = args.out.join(format!("board_{:0digits$}.{}", i, extension, digits = digits));
+        fs::write(&path, image).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    }
+    Ok(boards.len())
 }
 
-/// Converts the `board` to a bytes representation for saving
+/// Simulates games of Bananagrams (see `game::play_game`) in parallel, writing them to a JSON Lines file in order
 /// # Arguments
-/// * `board` - Board to save
-/// * `min_col` - Minimum column with letters
-/// * `max_col` - Maximum column with letters
-/// * `min_row` - Minimum row with letters
-/// * `max_row` - Maximum row with letters
+/// * `args` - Options for simulating games
 /// # Returns
-/// * `Vec<u8>` - Vector where each non-empty cell on the `board` is represented by the \[row index, column index, letter value\],
-/// with all letters in succession. At the end will always be 255 (to serve as the demarcation between boards when saving).
-fn board_to_bytes(board: &Board, min_col: usize, max_col: usize, min_row: usize, max_row: usize) -> Vec<u8> {
-    let mut board_bytes: Vec<u8> = Vec::with_capacity((max_row-min_row)*(max_col-min_col));
-    for row in min_row..max_row+1 {
-        for col in min_col..max_col+1 {
-            if board.get_val(row, col) != EMPTY_VALUE {
-                board_bytes.push(row as u8);
-                board_bytes.push(col as u8);
-                board_bytes.push(board.get_val(row, col) as u8);
-            }
-        }
+/// * `Result<usize, String>` - The number of games someone won, or a description of why the games couldn't be simulated
+fn simulate_games(args: &GameArgs) -> Result<usize, String> {
+    if !(game::MINIMUM_PLAYERS..=game::MAXIMUM_PLAYERS).contains(&args.players) {
+        return Err(format!("--players must be between {} and {}", game::MINIMUM_PLAYERS, game::MAXIMUM_PLAYERS));
     }
-    board_bytes.push(255);
-    board_bytes
+    let dictionary = load_words(args.dictionary.as_deref(), args.min_word_length)?;
+    let tiles = match &args.tiles {
+        Some(path) => TileSet::load(path, &Alphabet::english())?,
+        None => TileSet::standard()
+    };
+    if tiles.alphabet_size() != ENGLISH_ALPHABET_SIZE {
+        return Err("Games can only be played with tiles of the English alphabet".to_string());
+    }
+    let mut writer = BufWriter::new(File::create(&args.out).map_err(|e| format!("Failed to create {}: {}", args.out.display(), e))?);
+    let master_seed = args.seed.unwrap_or_else(|| thread_rng().gen());
+    let stop = AtomicBool::new(false);
+    let options = game::GameOptions { dictionary: &dictionary, tiles: &tiles, max_words_checked: args.max_words_checked, board_size: args.board_size, min_word_length: args.min_word_length, stop: &stop };
+    let games: Vec<game::GameRecord> = (0..args.games).into_par_iter().map(|index| game::play_game(index, args.players, master_seed.wrapping_add(index as u64), &options)).collect();
+    let mut won = 0;
+    for record in games.iter() {
+        won += usize::from(record.winner.is_some());
+        writeln!(writer, "{}", serde_json::to_string(record).expect("Failed to convert game to JSON!")).map_err(|e| format!("Failed to write {}: {}", args.out.display(), e))?;
+    }
+    writer.flush().map_err(|e| format!("Failed to write {}: {}", args.out.display(), e))?;
+    Ok(won)
 }
 
 fn main() {
-    let mut dictionary: Vec<Word> = include_str!("../../new_short_dictionary.txt").lines().map(convert_word_to_array).collect();
-    dictionary.sort_by(|w1, w2| w2.len().cmp(&w1.len()));
-    let mut default_parallelism_approx = 1usize;
-    match thread::available_parallelism() {
-        Ok(available_parallelism) => {
-            default_parallelism_approx = available_parallelism.into();
+    // Warnings (such as about the dictionary) are shown unless RUST_LOG says otherwise
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
+    let cli = Cli::parse();
+    match cli.command {
+        Some(Command::Bgb { command: BgbCommand::Upgrade { old, new } }) => {
+            match upgrade_bgb(&old, &new) {
+                Ok(count) => println!("Converted {} boards", count),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    process::exit(1);
+                }
+            }
         },
-        _ => {}
-    }
-    const NUMBER_OF_BOARDS_TO_GENERATE: usize = 1500;
-    let m = MultiProgress::new();
-    let pbars: Vec<(usize, ProgressBar)> = (0..default_parallelism_approx).into_iter().map(|i| (i, m.add(ProgressBar::new(NUMBER_OF_BOARDS_TO_GENERATE as u64).with_prefix(format!("Thread {}", i+1))))).collect();
-    pbars.into_par_iter().for_each(|(thread_num, pbar)| {
-        let mut rng = thread_rng();
-        let mut boards_generated: usize = 0;
-        let mut all_board_bytes: Vec<u8> = Vec::new();
-        pbar.set_style(ProgressStyle::with_template("{elapsed_precise} {wide_bar} {pos}/{len} [{eta_precise} left ({per_sec})]").expect("Invalid template!"));
-        while boards_generated < NUMBER_OF_BOARDS_TO_GENERATE {
-            let letters = generate_hand(&mut rng);
-            let res = play_bananagrams(letters, &dictionary);
+        Some(Command::Bgb { command: BgbCommand::Merge { inputs, out, exact } }) => {
+            match merge::merge_bgb(&inputs, &out, exact) {
+                Ok(manifest) => {
+                    for input in manifest.inputs.iter() {
+                        println!("{}: {} boards, {} duplicates dropped{}", input.file.display(), input.boards, input.duplicates, if exact { format!(", {} hash collisions", input.collisions) } else { String::new() });
+                    }
+                    println!("Merged {} boards", manifest.boards);
+                },
+                Err(e) => {
+                    eprintln!("{}", e);
+                    process::exit(1);
+                }
+            }
+        },
+        Some(Command::VerifyManifest { manifest }) => {
+            match verify_manifest(&manifest) {
+                Ok((shards, boards)) => println!("Verified {} files with {} boards", shards, boards),
+                Err(errors) => {
+                    for error in errors.iter() {
+                        eprintln!("{}", error);
+                    }
+                    process::exit(1);
+                }
+            }
+        },
+        Some(Command::Stats { data, failures, json }) => {
+            if let Err(e) = print_stats(&data, failures.as_deref(), json) {
+                eprintln!("{}", e);
+                process::exit(1);
+            }
+        },
+        Some(Command::Analyze { command: AnalyzeCommand::Adjacency { data, out, normalize } }) => {
+            let mut stats = AdjacencyStats::default();
+            let res = for_each_board(&data, |_, board, _| stats.record_board(board)).and_then(|()| stats.write_csv(&out, normalize));
             match res {
-                Some(result) => {
-                    all_board_bytes.extend(board_to_bytes(&result.0, result.1, result.2, result.3, result.4));
-                    boards_generated += 1;
-                    pbar.inc(1);
-                    // if boards_generated % 50 == 0 {
-                    //     println!("Thread {} has generated {}", thread_num, boards_generated);
-                    // }
+                Ok(()) => println!("Wrote the letter pairs of {} boards to {}", stats.boards(), out.display()),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    process::exit(1);
+                }
+            }
+        },
+        Some(Command::Split { data, out, ratios, seed }) => {
+            if ratios.len() != split::SPLIT_NAMES.len() || ratios.iter().any(|ratio| !(0.0..=1.0).contains(ratio)) || (ratios.iter().sum::<f64>() - 1.0).abs() > 1e-6 {
+                eprintln!("--ratios must be {} proportions between 0 and 1 that sum to 1", split::SPLIT_NAMES.len());
+                process::exit(1);
+            }
+            match split::split_dataset(&data, &out, &ratios, seed) {
+                Ok(counts) => {
+                    let total: usize = counts.iter().sum();
+                    for (name, count) in split::SPLIT_NAMES.iter().zip(counts) {
+                        println!("{}: {} boards ({:.1}%)", name, count, if total == 0 { 0.0 } else { 100.0 * count as f64 / total as f64 });
+                    }
                 },
-                None => {/* Continue without incrementing since we failed to make a board */}
+                Err(e) => {
+                    eprintln!("{}", e);
+                    process::exit(1);
+                }
+            }
+        },
+        Some(Command::Shuffle { input, out, seed }) => {
+            match shuffle::shuffle_records(&input, &out, None, seed) {
+                Ok((_, written)) => println!("Shuffled {} boards", written),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    process::exit(1);
+                }
+            }
+        },
+        Some(Command::Sample { input, out, n, seed }) => {
+            match shuffle::shuffle_records(&input, &out, Some(n), seed) {
+                Ok((total, written)) => println!("Sampled {} of {} boards", written, total),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    process::exit(1);
+                }
+            }
+        },
+        Some(Command::Game(args)) => {
+            match simulate_games(&args) {
+                Ok(won) => println!("Simulated {} games ({} with a winner)", args.games, won),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    process::exit(1);
+                }
+            }
+        },
+        Some(Command::Solve(args)) => {
+            match solve_hand(&args) {
+                Ok(true) => {},
+                Ok(false) => process::exit(1),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    process::exit(2);
+                }
+            }
+        },
+        Some(Command::Validate(args)) => {
+            match validate_boards(&args) {
+                Ok(true) => {},
+                Ok(false) => process::exit(1),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    process::exit(2);
+                }
+            }
+        },
+        Some(Command::Render(args)) => {
+            match render_boards(&args) {
+                Ok(count) => println!("Rendered {} boards", count),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    process::exit(1);
+                }
+            }
+        },
+        #[cfg(feature = "serve")]
+        Some(Command::Serve(args)) => {
+            if let Err(e) = serve::serve(&args) {
+                eprintln!("{}", e);
+                process::exit(1);
+            }
+        },
+        Some(Command::Bench { iterations }) => {
+            if iterations == 0 {
+                eprintln!("--iterations must be at least 1");
+                process::exit(1);
             }
+            match bench::run_benchmarks(iterations) {
+                Ok(results) => print!("{}", bench::format_table(&results)),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    process::exit(1);
+                }
+            }
+        },
+        None => generate(cli.args)
+    }
+}
+
+/// Generates boards as given by the command-line arguments
+/// # Arguments
+/// * `args` - The command-line arguments
+fn generate(mut args: GenerateArgs) {
+    let master_seed = args.seed.unwrap_or_else(|| thread_rng().gen());
+    let dictionary = match &args.dictionary {
+        Some(path) => load_weighted_dictionary(path, args.strict),
+        None => parse_weighted_dictionary(include_str!("../../new_short_dictionary.txt"), args.strict)
+    };
+    let (mut dictionary, mut frequencies) = dictionary.unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(1);
+    });
+    remove_short_words(&mut dictionary, frequencies.as_mut(), args.min_word_length);
+    let banned_words: HashSet<Word> = match &args.banned_words {
+        Some(path) => load_dictionary(path, false).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            process::exit(1);
+        }).into_iter().collect(),
+        None => HashSet::new()
+    };
+    retain_words(&mut dictionary, frequencies.as_mut(), |word| !banned_words.contains(word));
+    if dictionary.is_empty() {
+        eprintln!("The dictionary has no words of at least {} letters (--min-word-length)", args.min_word_length);
+        process::exit(1);
+    }
+    if args.frequency_temperature.is_nan() || args.frequency_temperature < 0.0 {
+        eprintln!("--frequency-temperature must be a non-negative number");
+        process::exit(1);
+    }
+    let tiles = match &args.tiles {
+        Some(path) => TileSet::load(path, &Alphabet::english()).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            process::exit(1);
+        }),
+        None => TileSet::standard()
+    };
+    if args.hand_size.is_some() != (args.hand_sizes == HandSizes::Fixed) || args.players.is_some() != (args.hand_sizes == HandSizes::Official) {
+        eprintln!("--hand-size must be given with (and only with) --hand-sizes fixed, and --players with (and only with) --hand-sizes official");
+        process::exit(1);
+    }
+    let max_hand_size = args.max_hand_size.unwrap_or(tiles.maximum_hand_size());
+    let hand_sizes = match (args.hand_sizes, args.hand_size, args.players) {
+        (HandSizes::LogScaled, _, _) => HandSizeDistribution::LogScaled(args.min_hand_size, max_hand_size),
+        (HandSizes::Uniform, _, _) => HandSizeDistribution::Uniform(args.min_hand_size, max_hand_size),
+        (HandSizes::Fixed, Some(size), _) => HandSizeDistribution::Fixed(size),
+        (HandSizes::Official, _, Some(players)) => {
+            if !(game::MINIMUM_PLAYERS..=game::MAXIMUM_PLAYERS).contains(&players) {
+                eprintln!("--players must be between {} and {}", game::MINIMUM_PLAYERS, game::MAXIMUM_PLAYERS);
+                process::exit(1);
+            }
+            HandSizeDistribution::Official(players)
+        },
+        _ => unreachable!("Hand-size options already checked")
+    };
+    if hand_sizes.minimum() == 0 || hand_sizes.minimum() > hand_sizes.maximum() || hand_sizes.maximum() > tiles.len() {
+        eprintln!("Hand sizes must satisfy 1 <= --min-hand-size (or --hand-size) <= --max-hand-size <= {} (the number of tiles)", tiles.len());
+        process::exit(1);
+    }
+    let strata: Option<Strata> = match args.per_bucket {
+        _ if args.stratify.is_empty() => {
+            if args.per_bucket.is_some() || args.max_hands_per_bucket.is_some() {
+                eprintln!("--per-bucket and --max-hands-per-bucket can only be used with --stratify");
+                process::exit(1);
+            }
+            None
+        },
+        Some(per_bucket) if per_bucket > 0 && args.max_hands_per_bucket != Some(0) => {
+            if !matches!(args.hand_sizes, HandSizes::LogScaled | HandSizes::Uniform) || args.resume {
+                eprintln!("--stratify can only be used with --hand-sizes log-scaled or uniform, and without --resume");
+                process::exit(1);
+            }
+            let buckets = stratify::parse_buckets(&args.stratify, tiles.len()).unwrap_or_else(|e| {
+                eprintln!("{} (--stratify)", e);
+                process::exit(1);
+            });
+            Some(Strata::new(buckets, per_bucket, args.max_hands_per_bucket.unwrap_or(100 * per_bucket)))
+        },
+        _ => {
+            eprintln!("--stratify needs a --per-bucket of at least 1 (and --max-hands-per-bucket must be at least 1)");
+            process::exit(1);
+        }
+    };
+    // The checks below and the number of boards to generate then depend on the ranges stratified by, rather than on --min-hand-size,
+    // --max-hand-size, and --boards
+    let hand_sizes = match strata.as_ref() {
+        Some(strata) => {
+            args.boards = strata.total_boards();
+            let counts = strata.counts();
+            let (min, max) = (counts[0].min_size, counts[counts.len()-1].max_size);
+            match hand_sizes {
+                HandSizeDistribution::Uniform(_, _) => HandSizeDistribution::Uniform(min, max),
+                _ => HandSizeDistribution::LogScaled(min, max)
+            }
+        },
+        None => hand_sizes
+    };
+    if args.min_vowels > hand_sizes.minimum() {
+        eprintln!("--min-vowels must be at most {} (the smallest hand size)", hand_sizes.minimum());
+        process::exit(1);
+    }
+    if frequencies.is_some() && args.word_order != WordOrder::Longest {
+        eprintln!("--word-order can't be used with a dictionary that gives frequencies (see --frequency-temperature)");
+        process::exit(1);
+    }
+    if args.solutions_per_hand == 0 {
+        eprintln!("--solutions-per-hand must be at least 1");
+        process::exit(1);
+    }
+    let complete_from: Option<Board> = args.complete_from.as_ref().map(|path| {
+        if args.solutions_per_hand > 1 || args.target_tiles.is_some() || args.allow_partial {
+            eprintln!("--complete-from can't be used with --solutions-per-hand, --target-tiles, or --allow-partial");
+            process::exit(1);
+        }
+        let board: Board = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+            .and_then(|text| text.parse().map_err(|e| format!("Invalid board file {}: {}", path.display(), e)))
+            .unwrap_or_else(|e| {
+                eprintln!("{}", e);
+                process::exit(1);
+            });
+        if let Err(errors) = validate_board(&board, &dictionary.iter().cloned().collect(), args.min_word_length) {
+            eprintln!("The board in {} isn't legal:", path.display());
+            for error in errors {
+                eprintln!("  {}", error);
+            }
+            process::exit(1);
+        }
+        // Every tile on a board from a real game was drawn from the tile set (blanks aside), so a board that couldn't have been is a mistake
+        if !args.no_pool_check {
+            let mut on_board: Letters = [0; ENGLISH_ALPHABET_SIZE];
+            let (min_col, max_col, min_row, max_row) = board.bounds().expect("Legal boards aren't empty!");
+            for row in min_row..=max_row {
+                for col in min_col..=max_col {
+                    let val = board.get_val(row, col);
+                    if val != EMPTY_VALUE && !board.is_blank(row, col) {
+                        on_board[val as usize] = on_board[val as usize].saturating_add(1);
+                    }
+                }
+            }
+            if let Err(e) = check_hand_against_pool(&on_board, &tiles) {
+                eprintln!("The board in {} can't be made from the tile set: {} (use --no-pool-check to allow this)", path.display(), e);
+                process::exit(1);
+            }
+        }
+        board
+    });
+    let anchor_word: Option<Word> = args.anchor_word.as_ref().map(|word| {
+        let anchor = Alphabet::english().convert_word(word).filter(|anchor| dictionary.contains(anchor)).unwrap_or_else(|| {
+            eprintln!("The anchor word {} isn't in the dictionary (--anchor-word)", word);
+            process::exit(1);
+        });
+        // Otherwise no hand could ever be drawn with its letters
+        if !is_makeable(&anchor, &tiles.counts::<ENGLISH_ALPHABET_SIZE>(), args.blanks) || anchor.len() > hand_sizes.maximum() + args.blanks as usize || args.target_tiles.is_some_and(|target_tiles| anchor.len() > target_tiles) {
+            eprintln!("The anchor word {} can't be made from a hand of at most {} tiles (the largest hand size plus --blanks) drawn from the tile set, or is longer than --target-tiles", word, hand_sizes.maximum() + args.blanks as usize);
+            process::exit(1);
+        }
+        if complete_from.is_some() {
+            eprintln!("--anchor-word can't be used with --complete-from");
+            process::exit(1);
+        }
+        anchor
+    });
+    if args.simulate_game && ((args.format != OutputFormat::Jsonl && !args.stream) || args.solutions_per_hand > 1 || args.target_tiles.is_some() || args.complete_from.is_some() || args.allow_partial || args.dedupe || args.resume) {
+        eprintln!("--simulate-game only supports --format jsonl (or --stream), without --solutions-per-hand, --target-tiles, --complete-from, --allow-partial, --dedupe, or --resume");
+        process::exit(1);
+    }
+    if let Some(target_tiles) = args.target_tiles {
+        if target_tiles == 0 || target_tiles > hand_sizes.minimum() + args.blanks as usize {
+            eprintln!("--target-tiles must be between 1 and {} (the smallest hand size plus --blanks)", hand_sizes.minimum() + args.blanks as usize);
+            process::exit(1);
+        }
+    }
+    let mut default_parallelism_approx = 1usize;
+    if let Ok(available_parallelism) = thread::available_parallelism() {
+        default_parallelism_approx = available_parallelism.into();
+    }
+    let num_threads = args.threads.unwrap_or(default_parallelism_approx);
+    if num_threads == 0 {
+        eprintln!("--threads must be at least 1");
+        process::exit(1);
+    }
+    if args.board_size == 0 || args.board_size > BGB_MAX_BOARD_SIZE {
+        eprintln!("--board-size must be between 1 and {}", BGB_MAX_BOARD_SIZE);
+        process::exit(1);
+    }
+    if args.flush_every == 0 {
+        eprintln!("--flush-every must be at least 1");
+        process::exit(1);
+    }
+    let timeout_per_hand = args.timeout_per_hand.map(|seconds| Duration::try_from_secs_f64(seconds).ok().filter(|timeout| !timeout.is_zero()).unwrap_or_else(|| {
+        eprintln!("--timeout-per-hand must be a positive number of seconds");
+        process::exit(1);
+    }));
+    if args.shard_size == Some(0) {
+        eprintln!("--shard-size must be at least 1");
+        process::exit(1);
+    }
+    if args.resume && (args.compress != Compression::None || args.augment || args.negatives_per_board > 0 || !matches!(args.format, OutputFormat::Bgb | OutputFormat::Jsonl)) {
+        eprintln!("--resume only supports uncompressed .bgb and JSON Lines files, without --augment or --negatives-per-board");
+        process::exit(1);
+    }
+    if args.bgb_metrics && args.format != OutputFormat::Bgb {
+        eprintln!("--bgb-metrics only supports --format bgb");
+        process::exit(1);
+    }
+    if args.allow_partial && !matches!(args.format, OutputFormat::Bgb | OutputFormat::Jsonl) {
+        eprintln!("--allow-partial only supports --format bgb and jsonl");
+        process::exit(1);
+    }
+    if args.resume && args.dedupe && args.format != OutputFormat::Bgb {
+        eprintln!("--resume only supports --dedupe with .bgb files");
+        process::exit(1);
+    }
+    if (args.format == OutputFormat::NpyOnehot) != args.pad_to.is_some() {
+        eprintln!("--format npy-onehot needs --pad-to, which only applies to it");
+        process::exit(1);
+    }
+    if args.format == OutputFormat::NpyOnehot && args.compress != Compression::None {
+        eprintln!("--format npy-onehot can't be used with --compress");
+        process::exit(1);
+    }
+    #[cfg(feature = "arrow")]
+    if args.format == OutputFormat::Parquet && args.compress != Compression::None {
+        eprintln!("--format parquet can't be used with --compress");
+        process::exit(1);
+    }
+    #[cfg(feature = "arrow")]
+    if args.row_group_size == 0 {
+        eprintln!("--row-group-size must be at least 1");
+        process::exit(1);
+    }
+    #[cfg(feature = "sqlite")]
+    if args.format == OutputFormat::Sqlite && (args.shard_size.is_some() || args.compress != Compression::None) {
+        eprintln!("--format sqlite can't be used with --shard-size or --compress");
+        process::exit(1);
+    }
+    if args.stream && (args.resume || args.summary || args.plays || args.dump_words || args.negatives_per_board > 0 || args.allow_partial || args.shard_size.is_some() || args.compress != Compression::None || !args.stratify.is_empty() || args.pad_to.is_some() || args.bgb_metrics) {
+        eprintln!("--stream can't be used with --resume, --summary, --plays, --dump-words, --negatives-per-board, --allow-partial, --shard-size, --compress, --stratify, --pad-to, or --bgb-metrics");
+        process::exit(1);
+    }
+    if !args.stream {
+        if let Err(e) = fs::create_dir_all(&args.out) {
+            eprintln!("Failed to create output directory {}: {}", args.out.display(), e);
+            process::exit(1);
         }
-        fs::write(format!("data/{}_board5.bgb", thread_num), all_board_bytes).expect("Failed to write board data!");
-        pbar.finish_with_message(format!("Thread {} done!", thread_num+1));
+    }
+    // The first Ctrl-C (or SIGTERM) stops the threads after they write what they have; a second forces an exit
+    let interrupted = Arc::new(AtomicBool::new(false));
+    let handler_interrupted = Arc::clone(&interrupted);
+    ctrlc::set_handler(move || {
+        if handler_interrupted.swap(true, Ordering::Relaxed) {
+            process::exit(130);
+        }
+        eprintln!("Interrupted, finishing up (press Ctrl-C again to exit immediately)");
+    }).expect("Failed to set the Ctrl-C handler!");
+    // With --stream the run goes on until the reader is done with it
+    if args.stream {
+        stream::watch_stdin(Arc::clone(&interrupted));
+    }
+    let boards_target = if args.stream { usize::MAX } else { args.boards };
+    rayon::ThreadPoolBuilder::new().num_threads(num_threads).build_global().expect("Failed to build the thread pool!");
+    // No point in starting more threads than there are boards to generate
+    let num_workers = num_threads.min(boards_target);
+    let valid_words: HashSet<Word> = dictionary.iter().cloned().collect();
+    // Used to filter the dictionary for each hand solved with it in its own order
+    let dictionary_masks = letter_masks(&dictionary);
+    let mut seen_boards: HashSet<u64> = HashSet::new();
+    let mut resumed: Vec<ResumedThread> = if args.resume {
+        let num_resumed_threads = count_resumed_threads(&args).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            process::exit(1);
+        });
+        (0..num_workers.max(num_resumed_threads)).map(|thread| resume_thread(&args, thread, master_seed.wrapping_add(thread as u64), &mut seen_boards)).collect::<Result<_, _>>().unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            process::exit(1);
+        })
+    }
+    else {
+        (0..num_workers).map(|_| ResumedThread::default()).collect()
+    };
+    let boards_resumed: usize = resumed.iter().map(|thread| thread.boards).sum();
+    if args.resume {
+        println!("Resuming with {} boards already generated", boards_resumed);
+    }
+    // Threads of the previous run beyond those running now just have their files listed in the manifest
+    let extra_shards: Vec<Shard> = resumed.split_off(num_workers).into_iter().enumerate().flat_map(|(i, thread)| {
+        let thread_num = num_workers + i;
+        let mut shards = thread.shards;
+        if thread.current_boards > 0 {
+            shards.push(args.shard(thread_num, shards.len(), thread.current_boards, master_seed.wrapping_add(thread_num as u64)));
+        }
+        shards
+    }).collect();
+    // Counts the boards claimed by the threads, which the writer thread then writes
+    let boards_generated = AtomicUsize::new(boards_resumed);
+    // Shared so that the IDs of simulated games are unique across threads
+    let next_sequence_id = AtomicUsize::new(0);
+    // Hashes of the canonical forms of every board generated so far, so that rotations and reflections of them can be skipped
+    let seen_boards: Mutex<HashSet<u64>> = Mutex::new(seen_boards);
+    let duplicates = AtomicUsize::new(0);
+    let progress = Progress::start((!args.stream).then_some(args.boards), boards_resumed, args.quiet);
+    let resumed_boards: Vec<usize> = resumed.iter().map(|thread| thread.boards).collect();
+    // Rather than each thread writing its own files, every thread sends its boards to one thread writing everything
+    let writer = Writer::new(&args, master_seed, resumed, &progress, &interrupted).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(1);
     });
-    
-    // let letters = "EEEHILNNOOOQSTTTTUUWZ"; //"AAAACDEGIILLLNNNNNOSTTTUUVVWYZ"; //"CEEHHKLMMNOOOOSSTUVXZ"; //"CCEEEGHIIINNOOPRRSSSSSTTTTTWX"; //"CCEEEGHIIINNOOPRRSSTTTTWX";
-    // let mut vals = [0usize; 26];
-    // for c in letters.chars() {
-    //     vals[c as usize - 65] += 1;
-    // }
-    // let now = std::time::Instant::now();
-    // let res = play_bananagrams(vals, &dictionary);
-    // match res {
-    //     Some(result) => {
-    //         println!("{}", board_to_string(&result.0, result.1, result.2, result.3, result.4));
-    //     },
-    //     None => println!("Failed!")
-    // }
-    // println!("{:?}", now.elapsed());
+    let (thread_results, written) = thread::scope(|scope| {
+        let (sender, receiver) = mpsc::sync_channel(writer::CHANNEL_CAPACITY);
+        let writer_thread = scope.spawn(|| writer.run(receiver));
+        let thread_results: Vec<(usize, RunStats)> = (0..num_workers).into_par_iter().map(|thread_num| {
+            let thread_seed = master_seed.wrapping_add(thread_num as u64);
+            // A resumed thread is seeded differently so that it doesn't generate the same hands again
+            let rng_seed = match resumed_boards[thread_num] {
+                0 => thread_seed,
+                boards => thread_seed ^ (boards as u64).wrapping_mul(0x9e3779b97f4a7c15)
+            };
+            let mut rng = seeded_rng(Some(rng_seed));
+            // Each thread generates its own share of the boards left (rather than racing the others for them), so that which boards it writes
+            // doesn't depend on how fast the other threads are
+            let boards_left = boards_target.saturating_sub(boards_resumed);
+            let thread_boards = boards_left / num_workers + usize::from(thread_num < boards_left % num_workers);
+            let mut thread_boards_generated: usize = 0;
+            let mut stats = RunStats::default();
+            let mut pool: Option<TilePool> = args.realistic_pool.then(|| TilePool::new(&tiles));
+            while thread_boards_generated < thread_boards && !interrupted.load(Ordering::Relaxed) {
+                // With --stratify, each hand is drawn from a range of sizes that still needs boards, until none do
+                let bucket = match strata.as_ref().map(|strata| strata.choose(&mut rng)) {
+                    Some(None) => break,
+                    bucket => bucket.flatten()
+                };
+                let hand_sizes = bucket.map_or(hand_sizes, |bucket| strata.as_ref().expect("Not stratified!").hand_sizes(bucket, hand_sizes));
+                let (letters, vowels_swapped) = match pool.as_mut() {
+                    Some(pool) => pool.draw_hand(&mut rng, hand_sizes, args.min_vowels).unwrap_or_else(|| {
+                        pool.refill();
+                        pool.draw_hand(&mut rng, hand_sizes, args.min_vowels).expect("Hand larger than the tile set!")
+                    }),
+                    None => generate_hand(&mut rng, &tiles, hand_sizes, args.min_vowels)
+                };
+                if anchor_word.as_ref().is_some_and(|anchor_word| !is_makeable(anchor_word, &letters, args.blanks)) {
+                    continue;
+                }
+                let ordered_dictionary = match frequencies.as_ref() {
+                    Some(frequencies) => Some(order_by_frequency(&dictionary, frequencies, args.frequency_temperature, &mut rng)),
+                    None => args.word_order.apply(&dictionary, &mut rng)
+                };
+                let start = Instant::now();
+                let first_word = FirstWordStrategy::from_name(args.first_word.choose(&mut rng).expect("No first-word strategy!"), rng.gen()).expect("Invalid first-word strategy!");
+                let ordering = ordered_dictionary.as_ref().unwrap_or(&dictionary);
+                let ordering_masks = if ordered_dictionary.is_none() { Some(dictionary_masks.as_slice()) } else { None };
+                let options = SolveOptions {
+                    dictionary_masks: ordering_masks,
+                    max_words_checked: args.max_words_checked,
+                    stop: Some(&interrupted),
+                    deadline: timeout_per_hand.map(|timeout| start + timeout),
+                    board_size: args.board_size,
+                    min_word_length: args.min_word_length,
+                    target_tiles: args.target_tiles,
+                    first_word,
+                    anchor_word: anchor_word.as_ref(),
+                    heuristic: args.heuristic.into(),
+                    memoize: args.memoize,
+                    allow_partial: args.allow_partial
+                };
+                let res = if let Some(board) = &complete_from {
+                    complete_board(board, letters, args.blanks, ordering, &options).map(|result| vec![result]).map_err(|(reason, words_checked)| (reason, words_checked, None))
+                }
+                else if args.solutions_per_hand > 1 {
+                    play_bananagrams_multi(letters, args.blanks, ordering, args.solutions_per_hand, &options, &mut rng)
+                }
+                else {
+                    play_bananagrams(letters, args.blanks, ordering, &options).map(|result| vec![result])
+                };
+                // Banned words are already out of the dictionary, but the finished boards are checked again in case one was formed some other way
+                let res = res.and_then(|results| {
+                    let words_checked = results.last().map_or(0, |result| result.metrics.words_checked);
+                    let results: Vec<_> = results.into_iter().filter(|result| match find_banned_words(&result.board, result.min_col, result.max_col, result.min_row, result.max_row, &banned_words).first() {
+                        Some(banned) => {
+                            debug!("Thread {} discarded a solution of {} with the banned word {}", thread_num+1, letters_to_string(&letters), banned);
+                            false
+                        },
+                        None => true
+                    }).collect();
+                    if results.is_empty() { Err((SolveFailure::BannedWord, words_checked, None)) } else { Ok(results) }
+                });
+                let elapsed_ms = start.elapsed().as_millis();
+                let hand_size: usize = letters.iter().map(|&count| count as usize).sum();
+                // Each board is written with the tiles on it, which only differ from the hand in a simulated game
+                let res = res.map(|results| match args.simulate_game {
+                    true => simulate_game(results.into_iter().next().expect("No solution!"), letters, &tiles, ordering, &banned_words, &args, timeout_per_hand, &interrupted, &mut rng),
+                    false => results.into_iter().map(|result| (letters, result)).collect::<Vec<_>>()
+                });
+                match res {
+                    Ok(results) => {
+                        if log_enabled!(Level::Debug) {
+                            debug!("Thread {} found {} board(s) of {} after checking {} words in {} ms", thread_num+1, results.len(), letters_to_string(&letters) + &"?".repeat(args.blanks as usize), results[results.len()-1].1.metrics.words_checked, elapsed_ms);
+                        }
+                        // The hand is counted once, by its first board
+                        let first = &results[0].1;
+                        stats.record_solve(hand_size, first_word, &first.metrics, elapsed_ms, first.max_col + 1 - first.min_col, first.max_row + 1 - first.min_row);
+                        if vowels_swapped > 0 {
+                            stats.record_vowels_swapped(true);
+                        }
+                        let sequence = args.simulate_game.then(|| next_sequence_id.fetch_add(1, Ordering::Relaxed));
+                        for (step, (hand, result)) in results.iter().enumerate() {
+                            if args.dedupe && !seen_boards.lock().unwrap().insert(board_hash(&result.board, result.min_col, result.max_col, result.min_row, result.max_row)) {
+                                duplicates.fetch_add(1, Ordering::Relaxed);
+                                continue;
+                            }
+                            if thread_boards_generated == thread_boards {
+                                break;
+                            }
+                            // Claim one of the remaining boards, discarding this one if another thread already reached the target (for the hand's
+                            // range of sizes, if stratified)
+                            if bucket.is_some_and(|bucket| !strata.as_ref().expect("Not stratified!").claim(bucket)) {
+                                break;
+                            }
+                            if boards_generated.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| if n < boards_target { Some(n+1) } else { None }).is_err() {
+                                break;
+                            }
+                            let mut boards = vec![(result.board.clone(), result.min_col, result.max_col, result.min_row, result.max_row)];
+                            if args.augment {
+                                // The first of the symmetries is the board itself, which is already included
+                                boards.extend(distinct_symmetries(&result.board, result.min_col, result.max_col, result.min_row, result.max_row).into_iter().skip(1));
+                            }
+                            let records = boards.into_iter().map(|(board, min_col, max_col, min_row, max_row)| {
+                                BoardRecord { hand: *hand, blanks: args.blanks, board, min_col, max_col, min_row, max_row, words_checked: result.metrics.words_checked, depth: result.metrics.depth, failed_first_words: Some(result.metrics.failed_first_words), elapsed_ms, thread: thread_num, seed: rng_seed, unused: None, sequence: sequence.map(|sequence| (sequence, step)) }
+                            }).collect();
+                            // Only corruptions that really make the board invalid are kept
+                            let negatives = (0..args.negatives_per_board).filter_map(|_| {
+                                (0..MAXIMUM_CORRUPTION_ATTEMPTS).find_map(|_| {
+                                    corrupt_board::<ENGLISH_ALPHABET_SIZE>(&result.board, &mut rng, args.negative_severity).filter(|corrupted| validate_board(&corrupted.0, &valid_words, args.min_word_length).is_err())
+                                }).map(|(board, _, _, _, _, corruption)| (corruption.label(), board))
+                            }).collect();
+                            // Blocks while the writer thread is behind, which only stops early if writing fails or standard output is closed
+                            if sender.send(WriterEvent::Board(GeneratedBoard { records, plays: result.plays.clone(), negatives })).is_err() {
+                                break;
+                            }
+                            thread_boards_generated += 1;
+                        }
+                    },
+                    Err((reason, words_checked, partial)) => {
+                        // Continue without incrementing since we failed to make a board
+                        // Being stopped says nothing about the hand
+                        if reason == SolveFailure::Stopped {
+                            continue;
+                        }
+                        stats.record_failure(hand_size, first_word, reason);
+                        if vowels_swapped > 0 {
+                            stats.record_vowels_swapped(false);
+                        }
+                        if let Some(partial) = partial {
+                            stats.record_partial();
+                            let record = BoardRecord { hand: letters, blanks: args.blanks, board: partial.board, min_col: partial.min_col, max_col: partial.max_col, min_row: partial.min_row, max_row: partial.max_row, words_checked, depth: partial.plays.len(), failed_first_words: None, elapsed_ms, thread: thread_num, seed: rng_seed, unused: Some((partial.unused_letters, partial.unused_blanks)), sequence: None };
+                            if sender.send(WriterEvent::Partial(record, reason)).is_err() {
+                                break;
+                            }
+                        }
+                        if args.log_failures.is_some() {
+                            let record = FailureRecord { hand: letters, blanks: args.blanks, hand_size, reason: reason.name().to_string(), words_checked, elapsed_ms };
+                            if sender.send(WriterEvent::Failure(record)).is_err() {
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+            (thread_boards_generated, stats)
+        }).collect();
+        // The writer thread finishes once every sender is dropped
+        drop(sender);
+        (thread_results, writer_thread.join().expect("Writer panicked!"))
+    });
+    progress.finish();
+    let written = written.unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(1);
+    });
+    // Each thread's results are only combined once every thread has finished
+    let mut thread_boards: Vec<usize> = Vec::new();
+    let mut stats = RunStats::default();
+    for (count, thread_stats) in thread_results {
+        thread_boards.push(count);
+        stats.merge(thread_stats);
+    }
+    let mut shards = written.shards;
+    shards.extend(extra_shards);
+    // With --stream there are no files to list
+    if !args.stream {
+        let manifest = Manifest {
+            format: args.format.to_possible_value().expect("Every format has a name!").get_name().to_string(),
+            format_version: args.format_version(),
+            compression: args.compress.to_possible_value().expect("Every compression has a name!").get_name().to_string(),
+            seed: master_seed,
+            dictionary_hash: format!("{:016x}", dictionary_hash(&dictionary)),
+            shard_size: args.shard_size,
+            shards,
+            strata: strata.as_ref().map(Strata::counts)
+        };
+        let manifest_path = args.out.join(MANIFEST_FILE_NAME);
+        if let Err(e) = write_json(&manifest_path, &manifest) {
+            eprintln!("Failed to write {}: {}", manifest_path.display(), e);
+            process::exit(1);
+        }
+    }
+    // With --stream, standard output has the boards, so the report goes to standard error
+    let report = |line: String| if args.stream { eprintln!("{}", line) } else { println!("{}", line) };
+    if interrupted.load(Ordering::Relaxed) {
+        for (thread_num, count) in thread_boards.iter().enumerate() {
+            report(format!("Thread {} generated {} boards", thread_num+1, count));
+        }
+    }
+    report(format!("Generated {} boards in total", boards_generated.load(Ordering::Relaxed)));
+    if args.dedupe {
+        report(format!("Discarded {} duplicate boards", duplicates.load(Ordering::Relaxed)));
+    }
+    if let Some((height, width)) = args.pad_to {
+        report(format!("Skipped {} boards that don't fit in {}x{} (--pad-to)", written.boards_too_large, height, width));
+    }
+    let summary = stats.summary(boards_generated.load(Ordering::Relaxed) - boards_resumed, strata.as_ref().map(Strata::counts));
+    if args.stream { eprint!("{}", summary) } else { print!("{}", summary) }
+    if args.summary {
+        let summary_path = args.out.join(SUMMARY_FILE_NAME);
+        if let Err(e) = write_json(&summary_path, &summary) {
+            eprintln!("Failed to write {}: {}", summary_path.display(), e);
+            process::exit(1);
+        }
+    }
 }
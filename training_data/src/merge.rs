@@ -0,0 +1,117 @@
+//! Merging `.bgb` files from several runs into one (the `bgb merge` subcommand), dropping every board that is a rotation, reflection, or
+//! copy of one already merged
+use std::{fs::{self, File}, io::{BufWriter, Write}, path::{Path, PathBuf}};
+use hashbrown::{HashMap, HashSet};
+use serde::Serialize;
+use bananagrams_core::{BGB_FORMAT_VERSION, BGB_METRICS_FORMAT_VERSION, BGB_METRICS_SIZE, ENGLISH_ALPHABET_SIZE, canonical_form, complete_bgb_records, decompress, parse_bgb};
+use crate::{board_hash, write_json};
+
+/// What was merged, written next to the merged file
+#[derive(Serialize)]
+pub struct MergeManifest {
+    /// Format version of the merged file
+    pub format_version: u8,
+    /// Number of boards in the merged file
+    pub boards: usize,
+    /// Each file merged, in the order given
+    pub inputs: Vec<MergedFile>
+}
+
+/// A file merged, listed in a `MergeManifest`
+#[derive(Serialize)]
+pub struct MergedFile {
+    /// Path to the file
+    pub file: PathBuf,
+    /// Format version of the file
+    pub format_version: u8,
+    /// Number of complete boards in the file
+    pub boards: usize,
+    /// Number of its boards dropped as a rotation, reflection, or copy of a board merged before it
+    pub duplicates: usize,
+    /// Number of its boards whose hash matched that of a different board merged before it (only counted with `exact`, since otherwise they
+    /// are dropped as duplicates)
+    pub collisions: usize
+}
+
+/// The boards seen so far, remembered by the hash of their canonical form (see `board_hash`)
+enum SeenBoards {
+    /// Only the hashes, so that boards with the same hash are taken to be the same
+    Hashes(HashSet<u64>),
+    /// The canonical forms with each hash, so that boards with the same hash are compared in full
+    Exact(HashMap<u64, Vec<Vec<u8>>>)
+}
+
+/// Merges `.bgb` files into one, written in the newest format version of any of them, keeping only the first of each board and its
+/// rotations and reflections; the files are read one at a time, and (unless `exact`) only a 64-bit hash of each board is kept in memory
+/// # Arguments
+/// * `inputs` - The `.bgb` files (of either format version, and possibly compressed), of which only complete boards are read
+/// * `out` - Path to write the merged file to; the manifest (see `MergeManifest`) is written next to it, with the extension
+///   `.manifest.json`
+/// * `exact` - Whether to compare boards with the same hash in full, keeping both if they differ (which keeps every board's canonical form
+///   in memory)
+/// # Returns
+/// * `Result` with the manifest, or an error message if a file couldn't be read or written; boards from a version `BGB_FORMAT_VERSION`
+///   file merged into a version `BGB_METRICS_FORMAT_VERSION` file have their metrics written as zeros, since they weren't recorded
+pub fn merge_bgb(inputs: &[PathBuf], out: &Path, exact: bool) -> Result<MergeManifest, String> {
+    if inputs.iter().any(|input| input == out) {
+        return Err(format!("{} can't be both an input and the output", out.display()));
+    }
+    // The version of every file is needed before the first board is written
+    let mut versions: Vec<u8> = Vec::with_capacity(inputs.len());
+    for input in inputs.iter() {
+        let bytes = fs::read(input).map_err(|e| format!("Failed to read {}: {}", input.display(), e))?;
+        let (bytes, _) = decompress(&bytes).map_err(|e| format!("Invalid board file {}: {}", input.display(), e))?;
+        complete_bgb_records(&bytes).map_err(|e| format!("Invalid board file {}: {}", input.display(), e))?;
+        versions.push(bytes[0]);
+    }
+    let format_version = versions.iter().copied().max().unwrap_or(BGB_FORMAT_VERSION);
+    if let Some(parent) = out.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create output directory {}: {}", parent.display(), e))?;
+    }
+    let mut writer = BufWriter::new(File::create(out).map_err(|e| format!("Failed to create {}: {}", out.display(), e))?);
+    writer.write_all(&[format_version]).map_err(|e| format!("Failed to write {}: {}", out.display(), e))?;
+    let mut seen = if exact { SeenBoards::Exact(HashMap::new()) } else { SeenBoards::Hashes(HashSet::new()) };
+    let mut manifest = MergeManifest { format_version, boards: 0, inputs: Vec::with_capacity(inputs.len()) };
+    for (input, &version) in inputs.iter().zip(versions.iter()) {
+        let bytes = fs::read(input).map_err(|e| format!("Failed to read {}: {}", input.display(), e))?;
+        let (bytes, _) = decompress(&bytes).map_err(|e| format!("Invalid board file {}: {}", input.display(), e))?;
+        let ends = complete_bgb_records(&bytes).map_err(|e| format!("Invalid board file {}: {}", input.display(), e))?;
+        let boards = parse_bgb::<ENGLISH_ALPHABET_SIZE>(&bytes[..ends.last().copied().unwrap_or(1)]).map_err(|e| format!("Invalid board file {}: {}", input.display(), e))?;
+        let mut merged = MergedFile { file: input.clone(), format_version: version, boards: boards.len(), duplicates: 0, collisions: 0 };
+        let mut start = 1;
+        for ((_, board, min_col, max_col, min_row, max_row), &end) in boards.iter().zip(ends.iter()) {
+            let hash = board_hash(board, *min_col, *max_col, *min_row, *max_row);
+            let is_new = match &mut seen {
+                SeenBoards::Hashes(hashes) => hashes.insert(hash),
+                SeenBoards::Exact(forms) => {
+                    let forms = forms.entry(hash).or_default();
+                    let form = canonical_form(board, *min_col, *max_col, *min_row, *max_row);
+                    if forms.contains(&form) {
+                        false
+                    }
+                    else {
+                        merged.collisions += usize::from(!forms.is_empty());
+                        forms.push(form);
+                        true
+                    }
+                }
+            };
+            if is_new {
+                writer.write_all(&bytes[start..end]).map_err(|e| format!("Failed to write {}: {}", out.display(), e))?;
+                if version == BGB_FORMAT_VERSION && format_version == BGB_METRICS_FORMAT_VERSION {
+                    writer.write_all(&[0; BGB_METRICS_SIZE]).map_err(|e| format!("Failed to write {}: {}", out.display(), e))?;
+                }
+                manifest.boards += 1;
+            }
+            else {
+                merged.duplicates += 1;
+            }
+            start = end;
+        }
+        manifest.inputs.push(merged);
+    }
+    writer.flush().map_err(|e| format!("Failed to write {}: {}", out.display(), e))?;
+    let manifest_path = out.with_extension("manifest.json");
+    write_json(&manifest_path, &manifest).map_err(|e| format!("Failed to write {}: {}", manifest_path.display(), e))?;
+    Ok(manifest)
+}
@@ -0,0 +1,66 @@
+//! Writing boards in one-hot form as a NumPy `.npy` array (see `board_to_onehot`), which can be loaded with `numpy.load` as a
+//! (boards, 27, height, width) `uint8` array
+use std::{fs::OpenOptions, io::{self, Seek, SeekFrom, Write}, path::Path};
+use bananagrams_core::ONEHOT_CHANNELS;
+
+/// Size of the header each file starts with, which is always the same so that it can be rewritten with the final number of boards
+pub const HEADER_SIZE: usize = 128;
+/// Bytes each `.npy` file starts with: the magic string, then version 1.0 of the format
+const MAGIC: &[u8] = b"\x93NUMPY\x01\x00";
+
+/// Gets the header of a file of boards
+/// # Arguments
+/// * `boards` - Number of boards in the file
+/// * `height` - Number of rows in each board
+/// * `width` - Number of columns in each board
+/// # Returns
+/// * `Vec<u8>` - The `HEADER_SIZE` bytes the file starts with, describing the array that follows
+pub fn header(boards: usize, height: usize, width: usize) -> Vec<u8> {
+    let description = format!("{{'descr': '|u1', 'fortran_order': False, 'shape': ({}, {}, {}, {}), }}", boards, ONEHOT_CHANNELS, height, width);
+    let length = HEADER_SIZE - MAGIC.len() - 2;
+    let mut header = MAGIC.to_vec();
+    header.extend((length as u16).to_le_bytes());
+    // The description is padded with spaces and ends with a newline
+    header.extend(format!("{:<width$}\n", description, width = length - 1).into_bytes());
+    header
+}
+
+/// Rewrites the header of a finished file of boards with the number of boards in it (since it isn't known when the file is started)
+/// # Arguments
+/// * `path` - Path to the file
+/// * `boards` - Number of boards in the file
+/// * `height` - Number of rows in each board
+/// * `width` - Number of columns in each board
+/// # Returns
+/// * `io::Result<()>` - Empty, or an error if the file couldn't be written
+pub fn set_board_count(path: &Path, boards: usize, height: usize, width: usize) -> io::Result<()> {
+    let mut file = OpenOptions::new().write(true).open(path)?;
+    file.seek(SeekFrom::Start(0))?;
+    file.write_all(&header(boards, height, width))?;
+    file.sync_all()
+}
+
+/// Counts the boards in a file of boards
+/// # Arguments
+/// * `bytes` - Contents of the file
+/// # Returns
+/// * `Result` with the number of boards given by the header, or an error message if the header is invalid or doesn't match the size of the file
+pub fn count_boards(bytes: &[u8]) -> Result<usize, String> {
+    if bytes.len() < HEADER_SIZE || !bytes.starts_with(MAGIC) {
+        return Err("File doesn't start with a .npy header".to_string());
+    }
+    let description = std::str::from_utf8(&bytes[MAGIC.len() + 2..HEADER_SIZE]).map_err(|_| "Invalid .npy header".to_string())?;
+    let shape: Vec<usize> = description.split_once("'shape': (").and_then(|(_, rest)| rest.split_once(')'))
+        .and_then(|(shape, _)| shape.split(',').map(|dimension| dimension.trim().parse().ok()).collect())
+        .ok_or_else(|| "Invalid shape in .npy header".to_string())?;
+    match shape[..] {
+        [boards, channels, height, width] if channels == ONEHOT_CHANNELS => {
+            let expected = HEADER_SIZE + boards * channels * height * width;
+            if bytes.len() != expected {
+                return Err(format!("File is {} bytes, but its header describes {} boards ({} bytes with the header)", bytes.len(), boards, expected));
+            }
+            Ok(boards)
+        },
+        _ => Err(format!("Shape in .npy header isn't (boards, {}, height, width)", ONEHOT_CHANNELS))
+    }
+}
@@ -0,0 +1,654 @@
+//! Writing boards to Apache Parquet files (`--format parquet`), for loading a dataset with Arrow-based tools; pages are written
+//! uncompressed and PLAIN-encoded, with their headers and the footer encoded with Thrift's compact protocol as the format requires
+use std::{fs::{self, File}, io::{self, BufWriter, Write}, path::Path};
+use bananagrams_core::{ENGLISH_ALPHABET_SIZE, extract_words};
+use crate::BoardRecord;
+
+/// Bytes each Parquet file starts and ends with
+const MAGIC: &[u8; 4] = b"PAR1";
+/// Size a page of a column grows to before it's ended and a new one started
+const PAGE_SIZE: usize = 1 << 20;
+
+/// Thrift compact protocol type of a boolean field whose value is true (only used when skipping fields)
+const THRIFT_TRUE: u8 = 1;
+/// Thrift compact protocol type of a boolean field whose value is false (only used when skipping fields)
+const THRIFT_FALSE: u8 = 2;
+/// Thrift compact protocol type of a single byte
+const THRIFT_BYTE: u8 = 3;
+/// Thrift compact protocol type of a 16-bit integer
+const THRIFT_I16: u8 = 4;
+/// Thrift compact protocol type of a 32-bit integer (including enums)
+const THRIFT_I32: u8 = 5;
+/// Thrift compact protocol type of a 64-bit integer
+const THRIFT_I64: u8 = 6;
+/// Thrift compact protocol type of a double
+const THRIFT_DOUBLE: u8 = 7;
+/// Thrift compact protocol type of a string or binary
+const THRIFT_BINARY: u8 = 8;
+/// Thrift compact protocol type of a list
+const THRIFT_LIST: u8 = 9;
+/// Thrift compact protocol type of a set
+const THRIFT_SET: u8 = 10;
+/// Thrift compact protocol type of a map
+const THRIFT_MAP: u8 = 11;
+/// Thrift compact protocol type of a struct
+const THRIFT_STRUCT: u8 = 12;
+
+/// Parquet physical type of a 32-bit integer
+const TYPE_INT32: i32 = 1;
+/// Parquet physical type of a 64-bit integer
+const TYPE_INT64: i32 = 2;
+/// Parquet physical type of a variable-length byte array
+const TYPE_BYTE_ARRAY: i32 = 6;
+/// Parquet physical type of a fixed-length byte array
+const TYPE_FIXED_LEN_BYTE_ARRAY: i32 = 7;
+/// Parquet repetition of a field with exactly one value
+const REQUIRED: i32 = 0;
+/// Parquet repetition of a field with any number of values
+const REPEATED: i32 = 2;
+/// Parquet converted type of a UTF-8 string
+const CONVERTED_UTF8: i32 = 0;
+/// Parquet converted type of a list
+const CONVERTED_LIST: i32 = 3;
+/// Parquet converted type of an unsigned 64-bit integer
+const CONVERTED_UINT_64: i32 = 14;
+/// Parquet encoding of values written one after another
+const ENCODING_PLAIN: i32 = 0;
+/// Parquet encoding of levels as a hybrid of run-length encoding and bit-packing
+const ENCODING_RLE: i32 = 3;
+
+/// A field of the schema: (name, physical type, length of a fixed-length byte array, repetition, number of children, converted type),
+/// listed depth first as Parquet stores them
+type SchemaField = (&'static str, Option<i32>, Option<i32>, Option<i32>, Option<i32>, Option<i32>);
+
+/// The schema of every file, whose leaves are the columns of `COLUMNS`; `words` is a list of strings in the three-level form Parquet
+/// specifies for lists
+const SCHEMA: [SchemaField; 12] = [
+    ("schema", None, None, None, Some(9), None),
+    ("board_id", Some(TYPE_INT64), None, Some(REQUIRED), None, None),
+    ("width", Some(TYPE_INT32), None, Some(REQUIRED), None, None),
+    ("height", Some(TYPE_INT32), None, Some(REQUIRED), None, None),
+    ("tiles", Some(TYPE_BYTE_ARRAY), None, Some(REQUIRED), None, None),
+    ("hand", Some(TYPE_FIXED_LEN_BYTE_ARRAY), Some(ENGLISH_ALPHABET_SIZE as i32), Some(REQUIRED), None, None),
+    ("words", None, None, Some(REQUIRED), Some(1), Some(CONVERTED_LIST)),
+    ("list", None, None, Some(REPEATED), Some(1), None),
+    ("element", Some(TYPE_BYTE_ARRAY), None, Some(REQUIRED), None, Some(CONVERTED_UTF8)),
+    ("words_checked", Some(TYPE_INT64), None, Some(REQUIRED), None, None),
+    ("elapsed_ms", Some(TYPE_INT64), None, Some(REQUIRED), None, None),
+    ("seed", Some(TYPE_INT64), None, Some(REQUIRED), None, Some(CONVERTED_UINT_64))
+];
+
+/// The path through the schema and physical type of each column, in the order of `ParquetBoard::column_values`
+const COLUMNS: [(&[&str], i32); 9] = [
+    (&["board_id"], TYPE_INT64),
+    (&["width"], TYPE_INT32),
+    (&["height"], TYPE_INT32),
+    (&["tiles"], TYPE_BYTE_ARRAY),
+    (&["hand"], TYPE_FIXED_LEN_BYTE_ARRAY),
+    (&["words", "list", "element"], TYPE_BYTE_ARRAY),
+    (&["words_checked"], TYPE_INT64),
+    (&["elapsed_ms"], TYPE_INT64),
+    (&["seed"], TYPE_INT64)
+];
+/// Index in `COLUMNS` of the only repeated column, which is the only one with repetition and definition levels
+const WORDS_COLUMN: usize = 5;
+
+/// A board as stored in a row of a Parquet file
+pub struct ParquetBoard {
+    /// ID of the board, unique across the files of a run
+    board_id: u64,
+    /// Number of columns the board's letters span
+    width: usize,
+    /// Number of rows the board's letters span
+    height: usize,
+    /// The board cropped to its letters (see `BoardRecord::cropped_tiles`)
+    tiles: Vec<u8>,
+    /// Number of each letter in the hand that was solved
+    hand: [u8; ENGLISH_ALPHABET_SIZE],
+    /// The words on the board, as returned by `extract_words`
+    words: Vec<String>,
+    /// Number of words the solver checked to find the solution
+    words_checked: usize,
+    /// Time taken to solve the hand, in milliseconds
+    elapsed_ms: u128,
+    /// Seed of the random number generator of the thread that solved the hand
+    seed: u64
+}
+impl ParquetBoard {
+    /// Creates a new `ParquetBoard`
+    /// # Arguments
+    /// * `board_id` - ID of the board, unique across the files of a run
+    /// * `record` - The board and how it was made
+    /// # Returns
+    /// * `ParquetBoard` - The board ready to be written
+    pub fn new(board_id: u64, record: &BoardRecord) -> ParquetBoard {
+        ParquetBoard {
+            board_id,
+            width: record.max_col + 1 - record.min_col,
+            height: record.max_row + 1 - record.min_row,
+            tiles: record.cropped_tiles(),
+            hand: record.hand,
+            words: extract_words(&record.board, record.min_col, record.max_col, record.min_row, record.max_row).into_iter().map(|(word, _, _, _)| word).collect(),
+            words_checked: record.words_checked,
+            elapsed_ms: record.elapsed_ms,
+            seed: record.seed
+        }
+    }
+
+    /// Encodes the board's value of each column
+    /// # Returns
+    /// * `Vec<Vec<Vec<u8>>>` - For each column of `COLUMNS`, each of its values PLAIN-encoded (so every column has one value except
+    ///   `words`, which has one per word)
+    fn column_values(&self) -> Vec<Vec<Vec<u8>>> {
+        let byte_array = |bytes: &[u8]| [&(bytes.len() as u32).to_le_bytes(), bytes].concat();
+        vec![
+            vec![self.board_id.to_le_bytes().to_vec()],
+            vec![(self.width as i32).to_le_bytes().to_vec()],
+            vec![(self.height as i32).to_le_bytes().to_vec()],
+            vec![byte_array(&self.tiles)],
+            vec![self.hand.to_vec()],
+            self.words.iter().map(|word| byte_array(word.as_bytes())).collect(),
+            vec![(self.words_checked as i64).to_le_bytes().to_vec()],
+            vec![(self.elapsed_ms as i64).to_le_bytes().to_vec()],
+            vec![self.seed.to_le_bytes().to_vec()]
+        ]
+    }
+}
+
+/// Encodes Thrift structs with the compact protocol
+struct CompactWriter {
+    /// The encoded bytes
+    bytes: Vec<u8>,
+    /// ID of the last field written in each struct being written, innermost last (field headers give the difference from it)
+    last_field_ids: Vec<i16>
+}
+impl CompactWriter {
+    /// Creates a new `CompactWriter`, ready to write the fields of a struct
+    fn new() -> CompactWriter {
+        CompactWriter { bytes: Vec::new(), last_field_ids: vec![0] }
+    }
+
+    /// Writes an unsigned variable-length integer
+    fn varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.bytes.push((value as u8) | 0x80);
+            value >>= 7;
+        }
+        self.bytes.push(value as u8);
+    }
+
+    /// Writes a signed variable-length integer, zigzag-encoded
+    fn zigzag(&mut self, value: i64) {
+        self.varint(((value << 1) ^ (value >> 63)) as u64);
+    }
+
+    /// Writes the header of a field of the current struct
+    fn field(&mut self, id: i16, kind: u8) {
+        let last = self.last_field_ids.last_mut().expect("Not in a struct!");
+        match id - *last {
+            delta @ 1..=15 => self.bytes.push(((delta as u8) << 4) | kind),
+            _ => {
+                self.bytes.push(kind);
+                self.zigzag(id as i64);
+            }
+        }
+        *self.last_field_ids.last_mut().expect("Not in a struct!") = id;
+    }
+
+    /// Writes a 32-bit integer (or enum) field
+    fn i32_field(&mut self, id: i16, value: i32) {
+        self.field(id, THRIFT_I32);
+        self.zigzag(value as i64);
+    }
+
+    /// Writes a 64-bit integer field
+    fn i64_field(&mut self, id: i16, value: i64) {
+        self.field(id, THRIFT_I64);
+        self.zigzag(value);
+    }
+
+    /// Writes a string or binary field
+    fn binary_field(&mut self, id: i16, value: &[u8]) {
+        self.field(id, THRIFT_BINARY);
+        self.binary(value);
+    }
+
+    /// Writes a string or binary, as an element of a list or the value of a field
+    fn binary(&mut self, value: &[u8]) {
+        self.varint(value.len() as u64);
+        self.bytes.extend_from_slice(value);
+    }
+
+    /// Writes the header of a list field, which must be followed by its `len` elements of type `kind`
+    fn list_field(&mut self, id: i16, kind: u8, len: usize) {
+        self.field(id, THRIFT_LIST);
+        match len {
+            0..=14 => self.bytes.push(((len as u8) << 4) | kind),
+            _ => {
+                self.bytes.push(0xf0 | kind);
+                self.varint(len as u64);
+            }
+        }
+    }
+
+    /// Starts a struct, as the value of field `id` or (if `None`) an element of a list
+    fn begin_struct(&mut self, id: Option<i16>) {
+        if let Some(id) = id {
+            self.field(id, THRIFT_STRUCT);
+        }
+        self.last_field_ids.push(0);
+    }
+
+    /// Ends the current struct
+    fn end_struct(&mut self) {
+        self.bytes.push(0);
+        self.last_field_ids.pop();
+    }
+}
+
+/// Encodes repetition or definition levels of at most 1 as a data page stores them: the length of the encoded levels as 4 bytes, then a
+/// run-length encoded run for each run of equal levels
+/// # Arguments
+/// * `levels` - The levels
+/// # Returns
+/// * `Vec<u8>` - The encoded levels
+fn encode_levels(levels: &[u8]) -> Vec<u8> {
+    let mut runs = CompactWriter::new();
+    let mut start = 0;
+    while start < levels.len() {
+        let length = levels[start..].iter().take_while(|&&level| level == levels[start]).count();
+        // A run's header is its length shifted left once (the low bit marking a bit-packed run instead), followed by its level in a byte
+        runs.varint((length as u64) << 1);
+        runs.bytes.push(levels[start]);
+        start += length;
+    }
+    let mut encoded = (runs.bytes.len() as u32).to_le_bytes().to_vec();
+    encoded.extend(runs.bytes);
+    encoded
+}
+
+/// The pages of a column of the row group being written
+#[derive(Default)]
+struct ColumnChunk {
+    /// The finished pages, each with its header
+    pages: Vec<u8>,
+    /// Number of values (counting each empty list as one) in the finished pages
+    num_values: usize,
+    /// PLAIN-encoded values of the current page
+    values: Vec<u8>,
+    /// Repetition level of each value of the current page (only for `WORDS_COLUMN`), 0 if it starts a row
+    repetition_levels: Vec<u8>,
+    /// Definition level of each value of the current page (only for `WORDS_COLUMN`), 0 for an empty list
+    definition_levels: Vec<u8>,
+    /// Number of values (counting each empty list as one) in the current page
+    page_values: usize
+}
+impl ColumnChunk {
+    /// Ends the current page, adding it with its header to the finished pages
+    fn end_page(&mut self) {
+        if self.page_values == 0 {
+            return;
+        }
+        let mut body: Vec<u8> = Vec::new();
+        if !self.repetition_levels.is_empty() {
+            body.extend(encode_levels(&self.repetition_levels));
+            body.extend(encode_levels(&self.definition_levels));
+        }
+        body.append(&mut self.values);
+        let mut header = CompactWriter::new();
+        // PageHeader: a data page, not compressed
+        header.i32_field(1, 0);
+        header.i32_field(2, body.len() as i32);
+        header.i32_field(3, body.len() as i32);
+        // DataPageHeader
+        header.begin_struct(Some(5));
+        header.i32_field(1, self.page_values as i32);
+        header.i32_field(2, ENCODING_PLAIN);
+        header.i32_field(3, ENCODING_RLE);
+        header.i32_field(4, ENCODING_RLE);
+        header.end_struct();
+        header.end_struct();
+        self.pages.extend(header.bytes);
+        self.pages.extend(body);
+        self.num_values += self.page_values;
+        self.page_values = 0;
+        self.repetition_levels.clear();
+        self.definition_levels.clear();
+    }
+}
+
+/// Where a column chunk was written, for the footer
+struct ColumnChunkMetadata {
+    /// Byte offset of its first page in the file
+    offset: u64,
+    /// Total size of its pages, with their headers
+    size: usize,
+    /// Number of values (counting each empty list as one)
+    num_values: usize
+}
+
+/// Writes boards to a Parquet file a row group at a time, so only the current row group is ever in memory; the file is only readable once
+/// it's finished, since the footer listing the row groups is written last
+pub struct ParquetWriter {
+    /// The file
+    writer: BufWriter<File>,
+    /// Number of bytes written to the file
+    offset: u64,
+    /// Number of boards in each row group
+    row_group_size: usize,
+    /// Each column of the row group being written
+    columns: Vec<ColumnChunk>,
+    /// Number of boards in the row group being written
+    rows: usize,
+    /// The number of boards in, and columns of, each row group written
+    row_groups: Vec<(usize, Vec<ColumnChunkMetadata>)>
+}
+impl ParquetWriter {
+    /// Creates a new `ParquetWriter`
+    /// # Arguments
+    /// * `file` - File to write to, which must be empty
+    /// * `row_group_size` - Number of boards in each row group (only the last can have fewer)
+    /// # Returns
+    /// * `io::Result<ParquetWriter>` - The writer, or an error if the start of the file couldn't be written
+    pub fn new(file: File, row_group_size: usize) -> io::Result<ParquetWriter> {
+        let mut writer = BufWriter::new(file);
+        writer.write_all(MAGIC)?;
+        Ok(ParquetWriter { writer, offset: MAGIC.len() as u64, row_group_size, columns: COLUMNS.iter().map(|_| ColumnChunk::default()).collect(), rows: 0, row_groups: Vec::new() })
+    }
+
+    /// Adds a board to the row group being written, writing the row group to the file once it's full
+    /// # Arguments
+    /// * `board` - The board
+    /// # Returns
+    /// * `io::Result<()>` - Empty, or an error if a row group couldn't be written
+    pub fn write_board(&mut self, board: &ParquetBoard) -> io::Result<()> {
+        for (i, (column, values)) in self.columns.iter_mut().zip(board.column_values()).enumerate() {
+            if i == WORDS_COLUMN {
+                if values.is_empty() {
+                    column.repetition_levels.push(0);
+                    column.definition_levels.push(0);
+                    column.page_values += 1;
+                }
+                for j in 0..values.len() {
+                    column.repetition_levels.push(u8::from(j > 0));
+                    column.definition_levels.push(1);
+                }
+            }
+            column.page_values += values.len();
+            for value in values {
+                column.values.extend(value);
+            }
+            // Pages only end between boards, so that a board's words are never split across pages
+            if column.values.len() >= PAGE_SIZE {
+                column.end_page();
+            }
+        }
+        self.rows += 1;
+        if self.rows == self.row_group_size {
+            self.write_row_group()?;
+        }
+        Ok(())
+    }
+
+    /// Writes the row group being written to the file, and starts a new one
+    /// # Returns
+    /// * `io::Result<()>` - Empty, or an error if the row group couldn't be written
+    fn write_row_group(&mut self) -> io::Result<()> {
+        if self.rows == 0 {
+            return Ok(());
+        }
+        let mut metadata = Vec::with_capacity(self.columns.len());
+        for column in self.columns.iter_mut() {
+            column.end_page();
+            self.writer.write_all(&column.pages)?;
+            metadata.push(ColumnChunkMetadata { offset: self.offset, size: column.pages.len(), num_values: column.num_values });
+            self.offset += column.pages.len() as u64;
+            *column = ColumnChunk::default();
+        }
+        self.row_groups.push((self.rows, metadata));
+        self.rows = 0;
+        Ok(())
+    }
+
+    /// Writes the last row group and the footer, so that the file is complete
+    /// # Returns
+    /// * `io::Result<()>` - Empty, or an error if the data couldn't be written
+    pub fn finish(mut self) -> io::Result<()> {
+        self.write_row_group()?;
+        let mut footer = CompactWriter::new();
+        // FileMetaData
+        footer.i32_field(1, 1);
+        footer.list_field(2, THRIFT_STRUCT, SCHEMA.len());
+        for (name, physical_type, type_length, repetition, num_children, converted_type) in SCHEMA {
+            footer.begin_struct(None);
+            if let Some(physical_type) = physical_type {
+                footer.i32_field(1, physical_type);
+            }
+            if let Some(type_length) = type_length {
+                footer.i32_field(2, type_length);
+            }
+            if let Some(repetition) = repetition {
+                footer.i32_field(3, repetition);
+            }
+            footer.binary_field(4, name.as_bytes());
+            if let Some(num_children) = num_children {
+                footer.i32_field(5, num_children);
+            }
+            if let Some(converted_type) = converted_type {
+                footer.i32_field(6, converted_type);
+            }
+            footer.end_struct();
+        }
+        footer.i64_field(3, self.row_groups.iter().map(|(rows, _)| *rows as i64).sum());
+        footer.list_field(4, THRIFT_STRUCT, self.row_groups.len());
+        for (rows, columns) in self.row_groups.iter() {
+            // RowGroup
+            footer.begin_struct(None);
+            footer.list_field(1, THRIFT_STRUCT, columns.len());
+            for ((path, physical_type), column) in COLUMNS.iter().zip(columns.iter()) {
+                // ColumnChunk, with its ColumnMetaData
+                footer.begin_struct(None);
+                footer.i64_field(2, column.offset as i64);
+                footer.begin_struct(Some(3));
+                footer.i32_field(1, *physical_type);
+                let encodings = if path.len() > 1 { vec![ENCODING_PLAIN, ENCODING_RLE] } else { vec![ENCODING_PLAIN] };
+                footer.list_field(2, THRIFT_I32, encodings.len());
+                for encoding in encodings {
+                    footer.zigzag(encoding as i64);
+                }
+                footer.list_field(3, THRIFT_BINARY, path.len());
+                for name in path.iter() {
+                    footer.binary(name.as_bytes());
+                }
+                footer.i32_field(4, 0);
+                footer.i64_field(5, column.num_values as i64);
+                footer.i64_field(6, column.size as i64);
+                footer.i64_field(7, column.size as i64);
+                footer.i64_field(9, column.offset as i64);
+                footer.end_struct();
+                footer.end_struct();
+            }
+            footer.i64_field(2, columns.iter().map(|column| column.size as i64).sum());
+            footer.i64_field(3, *rows as i64);
+            footer.end_struct();
+        }
+        footer.binary_field(6, format!("training_data version {}", env!("CARGO_PKG_VERSION")).as_bytes());
+        footer.end_struct();
+        self.writer.write_all(&footer.bytes)?;
+        self.writer.write_all(&(footer.bytes.len() as u32).to_le_bytes())?;
+        self.writer.write_all(MAGIC)?;
+        self.writer.flush()
+    }
+}
+
+/// Decodes just enough of Thrift's compact protocol to find fields of a struct
+struct CompactReader<'a> {
+    /// The encoded bytes
+    bytes: &'a [u8],
+    /// Position of the next byte to read
+    position: usize
+}
+impl CompactReader<'_> {
+    /// Reads a byte
+    fn byte(&mut self) -> Result<u8, String> {
+        let byte = *self.bytes.get(self.position).ok_or("Footer is cut off")?;
+        self.position += 1;
+        Ok(byte)
+    }
+
+    /// Reads an unsigned variable-length integer
+    fn varint(&mut self) -> Result<u64, String> {
+        let mut value = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err("Invalid integer in footer".to_string())
+    }
+
+    /// Reads a signed, zigzag-encoded variable-length integer
+    fn zigzag(&mut self) -> Result<i64, String> {
+        let value = self.varint()?;
+        Ok((value >> 1) as i64 ^ -((value & 1) as i64))
+    }
+
+    /// Reads the header of the next field of a struct whose last field had ID `last_id`
+    /// # Returns
+    /// * `Result` with the field's type and ID, or `None` at the end of the struct
+    fn field(&mut self, last_id: i16) -> Result<Option<(u8, i16)>, String> {
+        let header = self.byte()?;
+        if header == 0 {
+            return Ok(None);
+        }
+        let id = match header >> 4 {
+            0 => self.zigzag()? as i16,
+            delta => last_id + delta as i16
+        };
+        Ok(Some((header & 0x0f, id)))
+    }
+
+    /// Skips a value
+    /// # Arguments
+    /// * `kind` - Type of the value
+    /// * `in_collection` - Whether the value is an element of a list, set, or map (where booleans take a byte, rather than being
+    ///   stored in the field's type)
+    fn skip(&mut self, kind: u8, in_collection: bool) -> Result<(), String> {
+        match kind {
+            THRIFT_TRUE | THRIFT_FALSE if !in_collection => {},
+            THRIFT_TRUE | THRIFT_FALSE | THRIFT_BYTE => self.position += 1,
+            THRIFT_I16 | THRIFT_I32 | THRIFT_I64 => { self.varint()?; },
+            THRIFT_DOUBLE => self.position += 8,
+            THRIFT_BINARY => {
+                let len = self.varint()? as usize;
+                self.position += len;
+            },
+            THRIFT_LIST | THRIFT_SET => {
+                let header = self.byte()?;
+                let len = match header >> 4 {
+                    0x0f => self.varint()? as usize,
+                    len => len as usize
+                };
+                for _ in 0..len {
+                    self.skip(header & 0x0f, true)?;
+                }
+            },
+            THRIFT_MAP => {
+                let len = self.varint()? as usize;
+                if len > 0 {
+                    let kinds = self.byte()?;
+                    for _ in 0..len {
+                        self.skip(kinds >> 4, true)?;
+                        self.skip(kinds & 0x0f, true)?;
+                    }
+                }
+            },
+            THRIFT_STRUCT => {
+                let mut last_id = 0;
+                while let Some((kind, id)) = self.field(last_id)? {
+                    self.skip(kind, false)?;
+                    last_id = id;
+                }
+            },
+            _ => return Err(format!("Invalid type {} in footer", kind))
+        }
+        if self.position > self.bytes.len() { Err("Footer is cut off".to_string()) } else { Ok(()) }
+    }
+}
+
+/// Counts the boards in a Parquet file, as given by its footer
+/// # Arguments
+/// * `path` - Path to the file
+/// # Returns
+/// * `Result` with the number of boards, or an error message if the file couldn't be read or isn't a finished Parquet file
+pub fn count_boards(path: &Path) -> Result<usize, String> {
+    let bytes = fs::read(path).map_err(|e| e.to_string())?;
+    if bytes.len() < 2*MAGIC.len() + 4 || !bytes.starts_with(MAGIC) || !bytes.ends_with(MAGIC) {
+        return Err("Not a finished Parquet file".to_string());
+    }
+    let footer_end = bytes.len() - MAGIC.len() - 4;
+    let footer_length = u32::from_le_bytes(bytes[footer_end..footer_end+4].try_into().expect("Not 4 bytes!")) as usize;
+    let footer_start = footer_end.checked_sub(footer_length).filter(|&start| start >= MAGIC.len()).ok_or("Footer is cut off")?;
+    let mut reader = CompactReader { bytes: &bytes[footer_start..footer_end], position: 0 };
+    let mut last_id = 0;
+    while let Some((kind, id)) = reader.field(last_id)? {
+        // FileMetaData's num_rows
+        if id == 3 && kind == THRIFT_I64 {
+            return Ok(reader.zigzag()? as usize);
+        }
+        reader.skip(kind, false)?;
+        last_id = id;
+    }
+    Err("Footer has no number of rows".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Makes a board of the known-good file
+    /// # Arguments
+    /// * `board_id` - ID of the board
+    /// * `rows` - Rows of the board cropped to its letters, with '.' for an empty cell
+    /// * `words` - The words on the board
+    /// # Returns
+    /// * `ParquetBoard` - The board, with its hand made of its letters
+    fn board(board_id: u64, rows: &[&str], words: &[&str]) -> ParquetBoard {
+        let mut hand = [0; ENGLISH_ALPHABET_SIZE];
+        let tiles: Vec<u8> = rows.iter().flat_map(|row| row.bytes()).map(|c| if c == b'.' { 0 } else { hand[(c - b'A') as usize] += 1; c - b'A' + 1 }).collect();
+        ParquetBoard { board_id, width: rows[0].len(), height: rows.len(), tiles, hand, words: words.iter().map(|word| word.to_string()).collect(), words_checked: 10*board_id as usize, elapsed_ms: 7, seed: u64::MAX - board_id }
+    }
+
+    #[test]
+    fn boards_are_written_as_the_known_good_file() {
+        // Decoded independently of this module when it was checked in: three rows in row groups of two and one, the last with no words
+        let path = std::env::temp_dir().join(format!("training_data_parquet_{}.parquet", std::process::id()));
+        let mut writer = ParquetWriter::new(File::create(&path).unwrap(), 2).unwrap();
+        writer.write_board(&board(0, &["CAT.", "..O.", "..M.", "..B."], &["CAT", "TOMB"])).unwrap();
+        writer.write_board(&board(1, &["ZA"], &["ZA"])).unwrap();
+        writer.write_board(&board(2, &["Q"], &[])).unwrap();
+        writer.finish().unwrap();
+        let written = fs::read(&path).unwrap();
+        assert_eq!(count_boards(&path), Ok(3));
+        fs::remove_file(&path).unwrap();
+        assert!(written == include_bytes!("../tests/data/boards.parquet"), "The written file differs from tests/data/boards.parquet");
+        assert_eq!(count_boards(Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/boards.parquet"))), Ok(3));
+    }
+
+    #[test]
+    fn unfinished_files_are_not_counted() {
+        let path = std::env::temp_dir().join(format!("training_data_parquet_unfinished_{}.parquet", std::process::id()));
+        let good = include_bytes!("../tests/data/boards.parquet");
+        // Before the footer is written, then cut off partway through the footer, then with a footer length longer than the file
+        let mut too_long = good.to_vec();
+        let end = too_long.len() - MAGIC.len();
+        too_long[end-4..end].copy_from_slice(&(good.len() as u32).to_le_bytes());
+        for bytes in [&good[..good.len()/2], &good[good.len()-20..], &too_long[..]] {
+            fs::write(&path, bytes).unwrap();
+            assert!(count_boards(&path).is_err());
+        }
+        fs::remove_file(&path).unwrap();
+    }
+}
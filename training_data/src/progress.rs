@@ -0,0 +1,155 @@
+//! Reporting the progress of a run from a single thread, so that the output of the threads generating boards doesn't interleave
+use std::{io::{self, IsTerminal}, sync::mpsc::{self, RecvTimeoutError, Sender}, thread::{self, JoinHandle}, time::{Duration, Instant}};
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+
+/// Time between lines of progress when standard output isn't a terminal
+const LINE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Something the threads generating boards tell the reporter thread
+enum ProgressEvent {
+    /// Another board was generated
+    Board,
+    /// A message to print (such as the words of a board with `--verbose`)
+    Message(String)
+}
+
+/// Reports the progress of a run, sending updates from the threads generating boards to a thread that shows them
+pub struct Progress {
+    /// Sender to the reporter thread, or `None` if progress isn't being reported
+    sender: Option<Sender<ProgressEvent>>,
+    /// The reporter thread, if progress is being reported
+    reporter: Option<JoinHandle<()>>,
+    /// Whether progress is shown on standard error rather than standard output (which has the boards with `--stream`)
+    to_stderr: bool
+}
+impl Progress {
+    /// Starts reporting progress, as a progress bar if standard output is a terminal and otherwise as a line every `LINE_INTERVAL`
+    /// # Arguments
+    /// * `total` - Number of boards to generate, or `None` if boards are generated until the run is stopped (with `--stream`, in which case
+    ///   progress is reported on standard error instead)
+    /// * `done` - Number of boards already generated (such as by a resumed run)
+    /// * `quiet` - Whether to not report progress at all
+    /// # Returns
+    /// * `Progress` - The progress reporter
+    pub fn start(total: Option<usize>, done: usize, quiet: bool) -> Progress {
+        let to_stderr = total.is_none();
+        if quiet {
+            return Progress { sender: None, reporter: None, to_stderr };
+        }
+        let (sender, receiver) = mpsc::channel::<ProgressEvent>();
+        let is_terminal = if to_stderr { io::stderr().is_terminal() } else { io::stdout().is_terminal() };
+        let reporter = if is_terminal {
+            thread::spawn(move || {
+                let target = if to_stderr { ProgressDrawTarget::stderr() } else { ProgressDrawTarget::stdout() };
+                let template = match total {
+                    Some(_) => "{elapsed_precise} {wide_bar} {pos}/{len} [{eta_precise} left ({per_sec})]",
+                    None => "{elapsed_precise} {pos} boards ({per_sec})"
+                };
+                let pbar = ProgressBar::with_draw_target(total.map(|total| total as u64), target).with_style(ProgressStyle::with_template(template).expect("Invalid template!"));
+                pbar.set_position(done as u64);
+                pbar.reset_eta();
+                // Ends once every sender is dropped
+                for event in receiver {
+                    match event {
+                        ProgressEvent::Board => pbar.inc(1),
+                        ProgressEvent::Message(message) => pbar.suspend(|| print_line(&message, to_stderr))
+                    }
+                }
+                pbar.finish();
+            })
+        }
+        else {
+            thread::spawn(move || {
+                let start = Instant::now();
+                let mut generated = 0;
+                let mut last_line = start;
+                loop {
+                    match receiver.recv_timeout(LINE_INTERVAL.saturating_sub(last_line.elapsed())) {
+                        Ok(ProgressEvent::Board) => generated += 1,
+                        Ok(ProgressEvent::Message(message)) => print_line(&message, to_stderr),
+                        Err(RecvTimeoutError::Timeout) => {},
+                        Err(RecvTimeoutError::Disconnected) => break
+                    }
+                    if last_line.elapsed() >= LINE_INTERVAL {
+                        print_line(&progress_line(done + generated, total, generated, start.elapsed()), to_stderr);
+                        last_line = Instant::now();
+                    }
+                }
+                print_line(&progress_line(done + generated, total, generated, start.elapsed()), to_stderr);
+            })
+        };
+        Progress { sender: Some(sender), reporter: Some(reporter), to_stderr }
+    }
+
+    /// Reports that another board was generated
+    pub fn board_generated(&self) {
+        if let Some(sender) = self.sender.as_ref() {
+            // The reporter thread only stops once this is dropped, so this can't fail
+            let _ = sender.send(ProgressEvent::Board);
+        }
+    }
+
+    /// Prints a message without disrupting the progress shown
+    /// # Arguments
+    /// * `message` - The message
+    pub fn message(&self, message: String) {
+        match self.sender.as_ref() {
+            Some(sender) => { let _ = sender.send(ProgressEvent::Message(message)); },
+            None => print_line(&message, self.to_stderr)
+        }
+    }
+
+    /// Stops reporting progress, waiting for the reporter thread to show the final progress
+    pub fn finish(self) {
+        drop(self.sender);
+        if let Some(reporter) = self.reporter {
+            reporter.join().expect("Progress reporter panicked!");
+        }
+    }
+}
+
+/// Prints a line to standard output, or to standard error if `to_stderr`
+fn print_line(line: &str, to_stderr: bool) {
+    if to_stderr {
+        eprintln!("{}", line);
+    }
+    else {
+        println!("{}", line);
+    }
+}
+
+/// Describes the progress of a run in a line of text
+/// # Arguments
+/// * `done` - Number of boards generated, including any from before the run was resumed
+/// * `total` - Number of boards to generate, if there's a limit
+/// * `generated` - Number of boards generated since the run started
+/// * `elapsed` - Time since the run started
+/// # Returns
+/// * `String` - The number of boards generated, the rate of generating them, and the estimated time remaining (if there's a limit)
+fn progress_line(done: usize, total: Option<usize>, generated: usize, elapsed: Duration) -> String {
+    let per_second = generated as f64 / elapsed.as_secs_f64().max(f64::EPSILON);
+    let total = match total {
+        Some(total) => total,
+        None => return format!("{} boards ({:.2}/s)", done, per_second)
+    };
+    let remaining = match total.saturating_sub(done) {
+        0 => "0s".to_string(),
+        _ if generated == 0 => "unknown".to_string(),
+        left => format_duration(Duration::from_secs_f64(left as f64 / per_second))
+    };
+    format!("{}/{} boards ({:.2}/s, {} left)", done, total, per_second, remaining)
+}
+
+/// Formats a duration as hours, minutes, and seconds
+/// # Arguments
+/// * `duration` - The duration
+/// # Returns
+/// * `String` - The duration like `1h02m03s`, leaving off the hours and minutes if zero
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    match (seconds / 3600, seconds / 60 % 60, seconds % 60) {
+        (0, 0, seconds) => format!("{}s", seconds),
+        (0, minutes, seconds) => format!("{}m{:02}s", minutes, seconds),
+        (hours, minutes, seconds) => format!("{}h{:02}m{:02}s", hours, minutes, seconds)
+    }
+}
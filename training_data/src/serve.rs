@@ -0,0 +1,314 @@
+//! A small HTTP server exposing the solver and the rules (the `serve` subcommand, built with the `serve` feature), for tools that can't
+//! call the Rust or Python libraries; every response is JSON, with errors given as `{"error": message}`
+use std::{collections::HashMap, io::{self, BufRead, BufReader, Read, Write}, net::{TcpListener, TcpStream}, sync::Arc, time::{Duration, Instant}};
+use hashbrown::HashSet;
+use serde::{Deserialize, Serialize};
+use bananagrams_core::{Board, BoardParseError, HandSizeDistribution, LetterMask, Letters, PlayedWord, SolveOptions, TileSet, Word, board_rows, generate_hand, letter_masks, letters_to_string, play_bananagrams, seeded_rng, validate_board};
+use crate::{load_words, parse_hand, ServeArgs};
+use crate::validate::check_board;
+
+/// Largest request body accepted, in bytes
+const MAX_BODY_SIZE: usize = 1 << 20;
+/// Largest number of header lines accepted in a request
+const MAX_HEADERS: usize = 100;
+/// Time to wait for a client to send its request before giving up on it
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+/// Hand size of `GET /generate` if not given (the size each player starts with in a game of two to four players)
+const DEFAULT_HAND_SIZE: usize = 21;
+
+/// What every request shares, loaded once at startup
+struct Server {
+    /// The words to solve with
+    dictionary: Vec<Word>,
+    /// The letters in each word of `dictionary`, for filtering it by each hand solved
+    masks: Vec<LetterMask>,
+    /// HashSet version of `dictionary` for checking boards
+    valid_words: HashSet<Word>,
+    /// The tiles random hands are drawn from
+    tiles: TileSet,
+    /// Minimum length of a word
+    min_word_length: usize,
+    /// Maximum number of words to check before giving up on a hand
+    max_words_checked: usize,
+    /// Longest time to spend solving a hand, which requests can only shorten
+    max_timeout: Duration
+}
+
+/// A request that was read successfully
+struct Request {
+    /// The method, like `GET`
+    method: String,
+    /// The path, without the query string
+    path: String,
+    /// Each parameter of the query string
+    query: HashMap<String, String>,
+    /// The body (empty if none was sent)
+    body: Vec<u8>
+}
+
+/// A response to send, as its status code and JSON body
+type Response = (u16, String);
+
+/// Body of `POST /solve`
+#[derive(Deserialize)]
+struct SolveRequest {
+    /// The hand, as a string of letters (like `AAEINRSTQUZ`) or as the count of each letter (like `A:3,E:2,Q:1`)
+    letters: String,
+    /// Longest time to spend solving the hand, in milliseconds (at most the server's `--max-timeout`)
+    timeout_ms: Option<u64>
+}
+
+/// Body of `POST /validate`
+#[derive(Deserialize)]
+struct ValidateRequest {
+    /// The board as a list of rows, where a space or period is empty, an uppercase letter is a tile, and a lowercase letter is a tile
+    /// placed with a blank
+    board: Vec<String>
+}
+
+/// Response to `POST /solve` and `GET /generate`
+#[derive(Serialize)]
+struct SolveResponse {
+    /// The hand solved
+    hand: String,
+    /// Whether a board was found
+    solved: bool,
+    /// Why no board was found (see `SolveFailure::name`), if one wasn't
+    reason: Option<&'static str>,
+    /// The board cropped to its letters as a list of rows (see `board_rows`), empty if none was found
+    board: Vec<String>,
+    /// The words played to make the board, in order, with rows and columns counted from its top-left corner
+    words: Vec<PlayedWord>,
+    /// Number of words the solver checked
+    words_checked: usize,
+    /// Time taken, in milliseconds
+    elapsed_ms: u128
+}
+
+/// Response to `POST /validate`
+#[derive(Serialize)]
+struct ValidateResponse {
+    /// Whether the board is legal
+    valid: bool,
+    /// Each problem with the board, described for a person to read (see `BoardError`)
+    errors: Vec<String>,
+    /// Each word that isn't in the dictionary (or is too short), with rows and columns counted from the board's top-left corner
+    invalid_words: Vec<PlayedWord>,
+    /// The (row, column) of each tile in each group of connected letters
+    components: Vec<Vec<(usize, usize)>>
+}
+
+/// Runs the server until the process is stopped, handling each connection on a thread pool
+/// # Arguments
+/// * `args` - Options for the server
+/// # Returns
+/// * `Result` that is only returned with an error message, if the dictionary couldn't be loaded or the address couldn't be bound
+pub fn serve(args: &ServeArgs) -> Result<(), String> {
+    if args.threads == 0 {
+        return Err("--threads must be at least 1".to_string());
+    }
+    let max_timeout = Duration::try_from_secs_f64(args.max_timeout).ok().filter(|timeout| !timeout.is_zero()).ok_or("--max-timeout must be a positive number of seconds")?;
+    let dictionary = load_words(args.dictionary.as_deref(), args.min_word_length)?;
+    let server = Arc::new(Server {
+        masks: letter_masks(&dictionary),
+        valid_words: dictionary.iter().cloned().collect(),
+        dictionary,
+        tiles: TileSet::standard(),
+        min_word_length: args.min_word_length,
+        max_words_checked: args.max_words_checked,
+        max_timeout
+    });
+    let pool = rayon::ThreadPoolBuilder::new().num_threads(args.threads).build().map_err(|e| format!("Failed to start the thread pool: {}", e))?;
+    let listener = TcpListener::bind(&args.bind).map_err(|e| format!("Failed to listen on {}: {}", args.bind, e))?;
+    let address = listener.local_addr().map_err(|e| format!("Failed to listen on {}: {}", args.bind, e))?;
+    println!("Listening on http://{}", address);
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                let server = Arc::clone(&server);
+                pool.spawn(move || {
+                    if let Err(e) = handle_connection(stream, &server) {
+                        log::debug!("Failed to answer a request: {}", e);
+                    }
+                });
+            },
+            Err(e) => log::warn!("Failed to accept a connection: {}", e)
+        }
+    }
+    Ok(())
+}
+
+/// Reads a request from a connection and writes the response, then closes the connection
+/// # Arguments
+/// * `stream` - The connection
+/// * `server` - What every request shares
+/// # Returns
+/// * `io::Result<()>` - Empty, or an error if the response couldn't be written
+fn handle_connection(mut stream: TcpStream, server: &Server) -> io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let (status, body) = match read_request(&stream) {
+        Ok(request) => route(&request, server),
+        Err(response) => response
+    };
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        408 => "Request Timeout",
+        413 => "Payload Too Large",
+        _ => "Internal Server Error"
+    };
+    write!(stream, "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", status, reason, body.len(), body)?;
+    stream.flush()
+}
+
+/// Gets the body of an error response
+/// # Arguments
+/// * `message` - What went wrong
+/// # Returns
+/// * `String` - The JSON object `{"error": message}`
+fn error_body(message: &str) -> String {
+    serde_json::json!({ "error": message }).to_string()
+}
+
+/// Reads a request
+/// # Arguments
+/// * `stream` - The connection to read from
+/// # Returns
+/// * `Result` with the request, or the error response to send if it couldn't be read
+fn read_request(stream: &TcpStream) -> Result<Request, Response> {
+    let mut reader = BufReader::new(stream);
+    let read_error = |e: io::Error| match e.kind() {
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => (408, error_body("Timed out waiting for the request")),
+        _ => (400, error_body(&format!("Failed to read the request: {}", e)))
+    };
+    let mut line = String::new();
+    reader.read_line(&mut line).map_err(read_error)?;
+    let mut parts = line.split_whitespace();
+    let (method, target) = match (parts.next(), parts.next()) {
+        (Some(method), Some(target)) => (method.to_string(), target.to_string()),
+        _ => return Err((400, error_body("Invalid request line")))
+    };
+    let mut content_length = 0;
+    for _ in 0..MAX_HEADERS {
+        line.clear();
+        reader.read_line(&mut line).map_err(read_error)?;
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().map_err(|_| (400, error_body("Invalid Content-Length")))?;
+            }
+        }
+    }
+    if content_length > MAX_BODY_SIZE {
+        return Err((413, error_body(&format!("The body can be at most {} bytes", MAX_BODY_SIZE))));
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).map_err(read_error)?;
+    let (path, query) = target.split_once('?').unwrap_or((&target, ""));
+    let query = query.split('&').filter(|pair| !pair.is_empty()).map(|pair| {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        (key.to_string(), value.to_string())
+    }).collect();
+    Ok(Request { method, path: path.to_string(), query, body })
+}
+
+/// Answers a request
+/// # Arguments
+/// * `request` - The request
+/// * `server` - What every request shares
+/// # Returns
+/// * `Response` - The status code and body to send
+fn route(request: &Request, server: &Server) -> Response {
+    let res = match (request.method.as_str(), request.path.as_str()) {
+        ("POST", "/solve") => solve(request, server),
+        ("POST", "/validate") => validate(request, server),
+        ("GET", "/generate") => generate(request, server),
+        (_, "/solve" | "/validate") => return (405, error_body("Use POST")),
+        (_, "/generate") => return (405, error_body("Use GET")),
+        _ => return (404, error_body(&format!("No endpoint {}", request.path)))
+    };
+    match res {
+        Ok(body) => (200, body),
+        Err(message) => (400, error_body(&message))
+    }
+}
+
+/// Solves a hand, giving up once the deadline passes
+/// # Arguments
+/// * `letters` - The hand
+/// * `timeout` - Longest time to spend, in milliseconds (at most the server's maximum, which is used if not given)
+/// * `server` - What every request shares
+/// # Returns
+/// * `String` - The result as a `SolveResponse`
+fn solve_letters(letters: Letters, timeout: Option<u64>, server: &Server) -> String {
+    let timeout = timeout.map_or(server.max_timeout, |timeout| Duration::from_millis(timeout).min(server.max_timeout));
+    let start = Instant::now();
+    let result = play_bananagrams(letters, 0, &server.dictionary, &SolveOptions { dictionary_masks: Some(&server.masks), max_words_checked: server.max_words_checked, deadline: Some(start + timeout), min_word_length: server.min_word_length, ..SolveOptions::default() });
+    let elapsed_ms = start.elapsed().as_millis();
+    let hand = letters_to_string(&letters);
+    let response = match result {
+        Ok(solution) => SolveResponse {
+            hand,
+            solved: true,
+            reason: None,
+            board: board_rows(&solution.board),
+            words: solution.plays.into_iter().map(|play| PlayedWord { row: play.row - solution.min_row, col: play.col - solution.min_col, ..play }).collect(),
+            words_checked: solution.metrics.words_checked,
+            elapsed_ms
+        },
+        Err((reason, words_checked, _)) => SolveResponse { hand, solved: false, reason: Some(reason.name()), board: Vec::new(), words: Vec::new(), words_checked, elapsed_ms }
+    };
+    serde_json::to_string(&response).expect("Failed to convert the solution to JSON!")
+}
+
+/// Answers `POST /solve`
+/// # Arguments
+/// * `request` - The request, whose body is a `SolveRequest`
+/// * `server` - What every request shares
+/// # Returns
+/// * `Result` with the body of the response, or an error message if the request is invalid
+fn solve(request: &Request, server: &Server) -> Result<String, String> {
+    let body: SolveRequest = serde_json::from_slice(&request.body).map_err(|e| format!("Invalid body: {}", e))?;
+    let letters = parse_hand(&body.letters)?;
+    Ok(solve_letters(letters, body.timeout_ms, server))
+}
+
+/// Answers `POST /validate`
+/// # Arguments
+/// * `request` - The request, whose body is a `ValidateRequest`
+/// * `server` - What every request shares
+/// # Returns
+/// * `Result` with the body of the response (a `ValidateResponse`), or an error message if the request is invalid
+fn validate(request: &Request, server: &Server) -> Result<String, String> {
+    let body: ValidateRequest = serde_json::from_slice(&request.body).map_err(|e| format!("Invalid body: {}", e))?;
+    let board: Board = body.board.join("\n").parse().map_err(|e: BoardParseError| format!("Invalid board: {}", e))?;
+    let errors = match validate_board(&board, &server.valid_words, server.min_word_length) {
+        Ok(()) => Vec::new(),
+        Err(errors) => errors.iter().map(|error| error.to_string()).collect()
+    };
+    let check = check_board(0, &board, &server.valid_words, server.min_word_length);
+    let response = ValidateResponse { valid: check.valid, errors, invalid_words: check.invalid_words, components: check.components };
+    Ok(serde_json::to_string(&response).expect("Failed to convert the check to JSON!"))
+}
+
+/// Answers `GET /generate`, drawing a random hand from the standard tiles and solving it
+/// # Arguments
+/// * `request` - The request, whose query may give the `hand_size` (21 if not given), a `seed` for drawing the hand, and a `timeout_ms`
+/// * `server` - What every request shares
+/// # Returns
+/// * `Result` with the body of the response (see `solve_letters`), or an error message if the query is invalid
+fn generate(request: &Request, server: &Server) -> Result<String, String> {
+    let number = |name: &str| request.query.get(name).map(|value| value.parse::<u64>().map_err(|_| format!("Invalid {} {:?}", name, value))).transpose();
+    let hand_size = number("hand_size")?.map_or(DEFAULT_HAND_SIZE, |hand_size| hand_size as usize);
+    if hand_size == 0 || hand_size > server.tiles.len() {
+        return Err(format!("hand_size must be between 1 and {}", server.tiles.len()));
+    }
+    let mut rng = seeded_rng(number("seed")?);
+    let (letters, _) = generate_hand(&mut rng, &server.tiles, HandSizeDistribution::Fixed(hand_size), 0);
+    Ok(solve_letters(letters, number("timeout_ms")?, server))
+}
@@ -0,0 +1,139 @@
+//! Shuffling and sampling the records of a board file (the `shuffle` and `sample` subcommands), since files are written in the order the
+//! boards were generated; only the offset of each record is kept in memory, and the records are copied from the file in their new order
+use std::{borrow::Cow, fs::{self, File}, io::{self, BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write}, path::Path};
+use rand::prelude::*;
+use bananagrams_core::{BGB2_FORMAT_VERSION, BGB2_MAGIC, board_to_bgb2, decompress, read_boards, seeded_rng};
+
+/// Where each record of a board file is
+struct RecordIndex {
+    /// Bytes the file starts with, before its first record
+    header: Vec<u8>,
+    /// The byte offset and length of each record
+    records: Vec<(u64, usize)>,
+    /// Contents of the file, if it had to be converted in memory (rather than read from disk as needed)
+    contents: Option<Vec<u8>>
+}
+
+/// Finds every record of a board file
+/// # Arguments
+/// * `path` - Path to the file: an uncompressed `.bgb2` file or JSON Lines file (whose records are found by scanning the file, without
+///   reading it all into memory), or a `.bgb` file of either format version, which may be compressed (which is read into memory and
+///   upgraded to the `.bgb2` format, dropping any metrics)
+/// # Returns
+/// * `Result` with where each complete record is, or an error message if the file couldn't be read or isn't a supported format
+fn index_records(path: &Path) -> Result<RecordIndex, String> {
+    let name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    let file = File::open(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let mut reader = BufReader::new(file);
+    let start = reader.fill_buf().map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    if start.starts_with(&BGB2_MAGIC) {
+        let mut header = [0u8; BGB2_MAGIC.len() + 1];
+        reader.read_exact(&mut header).map_err(|e| format!("Invalid board file {}: {}", path.display(), e))?;
+        if header[BGB2_MAGIC.len()] != BGB2_FORMAT_VERSION {
+            return Err(format!("Invalid board file {}: unsupported format version {} (expected {})", path.display(), header[BGB2_MAGIC.len()], BGB2_FORMAT_VERSION));
+        }
+        let file_length = fs::metadata(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?.len();
+        let mut records = Vec::new();
+        let mut offset = header.len() as u64;
+        let mut length = [0u8; 2];
+        // Only complete records, since the last may have been cut off
+        while reader.read_exact(&mut length).is_ok() {
+            let record_length = 2 + u16::from_le_bytes(length) as usize;
+            if offset + record_length as u64 > file_length {
+                break;
+            }
+            records.push((offset, record_length));
+            offset += record_length as u64;
+            reader.seek_relative(record_length as i64 - 2).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        }
+        return Ok(RecordIndex { header: header.to_vec(), records, contents: None });
+    }
+    if name.trim_end_matches(".gz").trim_end_matches(".zst").ends_with(".jsonl") {
+        // A compressed file can't be read from partway through
+        if !matches!(decompress(start), Ok((Cow::Borrowed(_), _))) {
+            return Err(format!("{} is compressed, so must be decompressed first", path.display()));
+        }
+        let mut records = Vec::new();
+        let mut offset = 0;
+        let mut line: Vec<u8> = Vec::new();
+        loop {
+            line.clear();
+            let length = reader.read_until(b'\n', &mut line).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            // Only complete lines, since the last may have been cut off
+            if length == 0 || !line.ends_with(b"\n") {
+                break;
+            }
+            if line.iter().any(|byte| !byte.is_ascii_whitespace()) {
+                records.push((offset, length));
+            }
+            offset += length as u64;
+        }
+        return Ok(RecordIndex { header: Vec::new(), records, contents: None });
+    }
+    // Any other file is taken to be a `.bgb` file, which has to be parsed to find its records, so it's upgraded to `.bgb2` at the same time
+    let bytes = fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let boards = read_boards(&bytes).map_err(|e| format!("Invalid board file {}: {}", path.display(), e))?;
+    let mut contents: Vec<u8> = Vec::new();
+    let mut records = Vec::with_capacity(boards.len());
+    for (letters, board, min_col, max_col, min_row, max_row) in boards.iter() {
+        let record = board_to_bgb2(letters, board, *min_col, *max_col, *min_row, *max_row).map_err(|e| format!("Failed to upgrade {}: {}", path.display(), e))?;
+        records.push((contents.len() as u64, record.len()));
+        contents.extend(record);
+    }
+    let mut header = BGB2_MAGIC.to_vec();
+    header.push(BGB2_FORMAT_VERSION);
+    Ok(RecordIndex { header, records, contents: Some(contents) })
+}
+
+/// Writes the records of a board file in a random order, or a random sample of them
+/// # Arguments
+/// * `input` - The board file (see `index_records`; a `.bgb` file is written as a `.bgb2` file)
+/// * `out` - Path to write the new file to
+/// * `sample` - Number of records to pick uniformly at random (keeping their order in `input`), or `None` to write every record in a
+///   uniformly random order (see `SliceRandom::shuffle`, a Fisher–Yates shuffle)
+/// * `seed` - Seed for the random number generator, if any
+/// # Returns
+/// * `Result` with the number of records in `input` and the number written, or an error message if a file couldn't be read or written
+pub fn shuffle_records(input: &Path, out: &Path, sample: Option<usize>, seed: Option<u64>) -> Result<(usize, usize), String> {
+    if fs::canonicalize(out).is_ok_and(|out| fs::canonicalize(input).is_ok_and(|input| input == out)) {
+        return Err(format!("{} can't be both the input and the output", out.display()));
+    }
+    let index = index_records(input)?;
+    let mut records = index.records;
+    let total = records.len();
+    let mut rng = seeded_rng(seed);
+    match sample {
+        Some(count) => {
+            let count = count.min(total);
+            records.partial_shuffle(&mut rng, count);
+            records.truncate(count);
+            // Reading the sample in file order is faster
+            records.sort_unstable();
+        },
+        None => records.shuffle(&mut rng)
+    }
+    let mut writer = BufWriter::new(File::create(out).map_err(|e| format!("Failed to create {}: {}", out.display(), e))?);
+    writer.write_all(&index.header).map_err(|e| format!("Failed to write {}: {}", out.display(), e))?;
+    let copy = |writer: &mut BufWriter<File>| -> io::Result<()> {
+        match index.contents.as_ref() {
+            Some(contents) => {
+                for &(offset, length) in records.iter() {
+                    writer.write_all(&contents[offset as usize..offset as usize + length])?;
+                }
+            },
+            None => {
+                let mut file = File::open(input)?;
+                let mut record = Vec::new();
+                for &(offset, length) in records.iter() {
+                    record.resize(length, 0);
+                    file.seek(SeekFrom::Start(offset))?;
+                    file.read_exact(&mut record)?;
+                    writer.write_all(&record)?;
+                }
+            }
+        }
+        writer.flush()
+    };
+    copy(&mut writer).map_err(|e| format!("Failed to copy {} to {}: {}", input.display(), out.display(), e))?;
+    Ok((total, records.len()))
+}
@@ -0,0 +1,130 @@
+//! Splitting a generated dataset into training, validation, and test sets (the `split` subcommand), choosing the set of each board by its
+//! canonical form so that a board and its rotations and reflections (such as those written by `--augment`) are always in the same set
+use std::{fs::{self, File}, hash::{DefaultHasher, Hash, Hasher}, io::Write, path::Path};
+use clap::ValueEnum;
+use bananagrams_core::{complete_bgb_records, decompress, read_boards};
+use crate::{board_hash, complete_lines, parse_jsonl_record, write_json, BoardWriter, Compression, Manifest, OutputFormat, Shard, MANIFEST_FILE_NAME};
+
+/// Name of each set a dataset is split into, which is also the name of its directory
+pub const SPLIT_NAMES: [&str; 3] = ["train", "validation", "test"];
+
+/// Chooses the set a board goes in
+/// # Arguments
+/// * `hash` - Hash of the board's canonical form (see `board_hash`)
+/// * `seed` - Seed of the split, so that different seeds split the boards differently
+/// * `ratios` - Proportion of the boards to put in each set, summing to 1
+/// # Returns
+/// * `usize` - Index of the set in `ratios`
+pub fn choose_split(hash: u64, seed: u64, ratios: &[f64]) -> usize {
+    let mut hasher = DefaultHasher::new();
+    (seed, hash).hash(&mut hasher);
+    // The top 53 bits give a uniformly distributed position in [0, 1)
+    let position = (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64;
+    let mut cumulative = 0.0;
+    for (i, ratio) in ratios.iter().enumerate() {
+        cumulative += ratio;
+        if position < cumulative {
+            return i;
+        }
+    }
+    // Only reached if the ratios sum to a hair under 1
+    ratios.iter().rposition(|&ratio| ratio > 0.0).unwrap_or(0)
+}
+
+/// Splits the boards of a generated dataset into a directory for each set (see `SPLIT_NAMES`), each with a board file for each file in the
+/// dataset (of the same format, format version, and compression) and a manifest, reading a file at a time
+/// # Arguments
+/// * `data` - Directory of the dataset, with its `manifest.json` (only `.bgb` and JSON Lines datasets are supported)
+/// * `out` - Directory to create the directory of each set in
+/// * `ratios` - Proportion of the boards to put in each set, summing to 1
+/// * `seed` - Seed of the split (see `choose_split`)
+/// # Returns
+/// * `Result` with the number of boards put in each set, or an error message if the dataset couldn't be read or a file couldn't be written
+pub fn split_dataset(data: &Path, out: &Path, ratios: &[f64], seed: u64) -> Result<Vec<usize>, String> {
+    let manifest_path = data.join(MANIFEST_FILE_NAME);
+    let text = fs::read_to_string(&manifest_path).map_err(|e| format!("Failed to read {}: {}", manifest_path.display(), e))?;
+    let manifest: Manifest = serde_json::from_str(&text).map_err(|e| format!("Invalid manifest {}: {}", manifest_path.display(), e))?;
+    let format = OutputFormat::from_str(&manifest.format, false).map_err(|e| format!("Invalid manifest {}: {}", manifest_path.display(), e))?;
+    if !matches!(format, OutputFormat::Bgb | OutputFormat::Jsonl) {
+        return Err(format!("Only .bgb and JSON Lines datasets can be split (this one is {})", manifest.format));
+    }
+    let compression = Compression::from_str(&manifest.compression, false).map_err(|e| format!("Invalid manifest {}: {}", manifest_path.display(), e))?;
+    for name in SPLIT_NAMES {
+        let directory = out.join(name);
+        if directory.join(MANIFEST_FILE_NAME).exists() {
+            return Err(format!("{} already has a dataset in it", directory.display()));
+        }
+        fs::create_dir_all(&directory).map_err(|e| format!("Failed to create output directory {}: {}", directory.display(), e))?;
+    }
+    let mut split_shards: Vec<Vec<Shard>> = SPLIT_NAMES.iter().map(|_| Vec::new()).collect();
+    for shard in manifest.shards.iter() {
+        let path = data.join(&shard.file);
+        let bytes = fs::read(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let (bytes, _) = decompress(&bytes).map_err(|e| format!("Invalid board file {}: {}", path.display(), e))?;
+        // Only complete records, since the last may have been cut off; each is copied byte for byte into its set's file
+        let (header, ends, hashes): (&[u8], Vec<usize>, Vec<u64>) = match format {
+            OutputFormat::Jsonl => {
+                let ends = complete_lines(&bytes);
+                let mut start = 0;
+                let mut hashes = Vec::with_capacity(ends.len());
+                for &end in ends.iter() {
+                    let (_, board, _) = parse_jsonl_record(&bytes[start..end]).map_err(|e| format!("Invalid board file {}: {}", path.display(), e))?;
+                    let (min_col, max_col, min_row, max_row) = board.bounds().unwrap_or((1, 0, 1, 0));
+                    hashes.push(board_hash(&board, min_col, max_col, min_row, max_row));
+                    start = end;
+                }
+                (&[], ends, hashes)
+            },
+            _ => {
+                let ends = complete_bgb_records(&bytes).map_err(|e| format!("Invalid board file {}: {}", path.display(), e))?;
+                let boards = read_boards(&bytes[..ends.last().copied().unwrap_or(1)]).map_err(|e| format!("Invalid board file {}: {}", path.display(), e))?;
+                (&bytes[..1], ends, boards.iter().map(|(_, board, min_col, max_col, min_row, max_row)| board_hash(board, *min_col, *max_col, *min_row, *max_row)).collect())
+            }
+        };
+        let mut writers: Vec<Option<BoardWriter>> = SPLIT_NAMES.iter().map(|_| None).collect();
+        let mut counts = vec![0; SPLIT_NAMES.len()];
+        let mut start = header.len();
+        for (&end, &hash) in ends.iter().zip(hashes.iter()) {
+            let split = choose_split(hash, seed, ratios);
+            let split_path = out.join(SPLIT_NAMES[split]).join(&shard.file);
+            let writer = match writers[split].as_mut() {
+                Some(writer) => writer,
+                None => {
+                    let file = File::create(&split_path).map_err(|e| format!("Failed to create {}: {}", split_path.display(), e))?;
+                    let mut writer = BoardWriter::new(file, compression).map_err(|e| format!("Failed to create {}: {}", split_path.display(), e))?;
+                    writer.write_all(header).map_err(|e| format!("Failed to write {}: {}", split_path.display(), e))?;
+                    writers[split].insert(writer)
+                }
+            };
+            writer.write_all(&bytes[start..end]).map_err(|e| format!("Failed to write {}: {}", split_path.display(), e))?;
+            counts[split] += 1;
+            start = end;
+        }
+        for (split, writer) in writers.into_iter().enumerate() {
+            if let Some(writer) = writer {
+                let split_path = out.join(SPLIT_NAMES[split]).join(&shard.file);
+                writer.finish().map_err(|e| format!("Failed to write {}: {}", split_path.display(), e))?;
+                let bytes = fs::metadata(&split_path).map_err(|e| format!("Failed to read {}: {}", split_path.display(), e))?.len();
+                split_shards[split].push(Shard { file: shard.file.clone(), thread: shard.thread, index: shard.index, boards: counts[split], bytes, seed: shard.seed });
+            }
+        }
+    }
+    let mut counts = Vec::with_capacity(SPLIT_NAMES.len());
+    for (name, shards) in SPLIT_NAMES.iter().zip(split_shards) {
+        counts.push(shards.iter().map(|shard| shard.boards).sum());
+        // The strata of the dataset no longer describe any one set
+        let split_manifest = Manifest {
+            format: manifest.format.clone(),
+            format_version: manifest.format_version,
+            compression: manifest.compression.clone(),
+            seed: manifest.seed,
+            dictionary_hash: manifest.dictionary_hash.clone(),
+            shard_size: manifest.shard_size,
+            shards,
+            strata: None
+        };
+        let split_manifest_path = out.join(name).join(MANIFEST_FILE_NAME);
+        write_json(&split_manifest_path, &split_manifest).map_err(|e| format!("Failed to write {}: {}", split_manifest_path.display(), e))?;
+    }
+    Ok(counts)
+}
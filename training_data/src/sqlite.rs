@@ -0,0 +1,229 @@
+//! Writing boards to a SQLite database, for querying a dataset without writing a parser
+use std::{fs, io, path::{Path, PathBuf}};
+use rusqlite::{params, Connection};
+use bananagrams_core::{Board, Letters, SolveMetrics, BGB_BLANK_FLAG, ENGLISH_ALPHABET_SIZE, extract_words};
+use crate::BoardRecord;
+
+/// Name of the database in the output directory
+pub const SQLITE_FILE_NAME: &str = "boards.sqlite";
+/// Number of boards inserted in each transaction
+const BOARDS_PER_TRANSACTION: usize = 100;
+
+/// Statements creating the tables of the database
+const SCHEMA: &str = "
+CREATE TABLE boards (
+    id INTEGER PRIMARY KEY,
+    width INTEGER NOT NULL,
+    height INTEGER NOT NULL,
+    num_tiles INTEGER NOT NULL,
+    tiles BLOB NOT NULL,
+    hand BLOB NOT NULL,
+    words_checked INTEGER NOT NULL,
+    depth INTEGER NOT NULL,
+    failed_first_words INTEGER,
+    elapsed_ms INTEGER NOT NULL,
+    seed INTEGER NOT NULL
+);
+CREATE TABLE words (
+    board_id INTEGER NOT NULL REFERENCES boards(id),
+    word TEXT NOT NULL,
+    row INTEGER NOT NULL,
+    col INTEGER NOT NULL,
+    direction TEXT NOT NULL
+);
+CREATE INDEX words_board_id ON words(board_id);
+CREATE INDEX words_word ON words(word);
+";
+
+/// A board as stored in the database
+pub struct SqliteBoard {
+    /// Number of columns the board's letters span
+    width: usize,
+    /// Number of rows the board's letters span
+    height: usize,
+    /// Number of tiles on the board
+    num_tiles: usize,
+    /// The board cropped to its letters (see `BoardRecord::cropped_tiles`)
+    tiles: Vec<u8>,
+    /// Number of each letter in the hand that was solved
+    hand: Vec<u8>,
+    /// Number of words the solver checked to find the solution
+    words_checked: usize,
+    /// Number of words played to make the board
+    depth: usize,
+    /// Number of first words the solver gave up on before finding the solution, if known
+    failed_first_words: Option<usize>,
+    /// Time taken to solve the hand, in milliseconds
+    elapsed_ms: u128,
+    /// Seed of the random number generator of the thread that solved the hand
+    seed: u64,
+    /// The words on the board, as returned by `extract_words` but with rows and columns counted from the board's top-left corner
+    words: Vec<(String, usize, usize, String)>
+}
+impl SqliteBoard {
+    /// Creates a new `SqliteBoard`
+    /// # Arguments
+    /// * `record` - The board and how it was made
+    /// # Returns
+    /// * `SqliteBoard` - The board ready to be inserted
+    pub fn new(record: &BoardRecord) -> SqliteBoard {
+        let tiles = record.cropped_tiles();
+        let words = extract_words(&record.board, record.min_col, record.max_col, record.min_row, record.max_row).into_iter()
+            .map(|(word, row, col, direction)| (word, row - record.min_row, col - record.min_col, direction.to_string()))
+            .collect();
+        SqliteBoard {
+            width: record.max_col + 1 - record.min_col,
+            height: record.max_row + 1 - record.min_row,
+            num_tiles: tiles.iter().filter(|&&tile| tile != 0).count(),
+            tiles,
+            hand: record.hand.to_vec(),
+            words_checked: record.words_checked,
+            depth: record.depth,
+            failed_first_words: record.failed_first_words,
+            elapsed_ms: record.elapsed_ms,
+            seed: record.seed,
+            words
+        }
+    }
+}
+
+/// Inserts boards into the database in a single transaction
+/// # Arguments
+/// * `connection` - Connection to the database
+/// * `boards` - The boards to insert
+/// # Returns
+/// * `rusqlite::Result<()>` - Empty, or an error if the boards couldn't be inserted
+fn insert_boards(connection: &mut Connection, boards: &[SqliteBoard]) -> rusqlite::Result<()> {
+    let transaction = connection.transaction()?;
+    {
+        let mut insert_board = transaction.prepare_cached("INSERT INTO boards (width, height, num_tiles, tiles, hand, words_checked, depth, failed_first_words, elapsed_ms, seed) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)")?;
+        let mut insert_word = transaction.prepare_cached("INSERT INTO words (board_id, word, row, col, direction) VALUES (?1, ?2, ?3, ?4, ?5)")?;
+        for board in boards.iter() {
+            // SQLite integers are signed, so seeds above i64::MAX are stored as their two's complement
+            insert_board.execute(params![board.width as i64, board.height as i64, board.num_tiles as i64, board.tiles, board.hand, board.words_checked as i64, board.depth as i64, board.failed_first_words.map(|count| count as i64), board.elapsed_ms as i64, board.seed as i64])?;
+            let board_id = transaction.last_insert_rowid();
+            for (word, row, col, direction) in board.words.iter() {
+                insert_word.execute(params![board_id, word, *row as i64, *col as i64, direction])?;
+            }
+        }
+    }
+    transaction.commit()
+}
+
+/// Writes boards to a database, a transaction of `BOARDS_PER_TRANSACTION` at a time; owned by the thread writing the output (see
+/// `writer::Writer`), since SQLite doesn't allow multiple writers at once
+pub struct SqliteWriter {
+    /// Connection to the database
+    connection: Connection,
+    /// Path to the database, for error messages
+    path: PathBuf,
+    /// Boards waiting to be inserted in the next transaction
+    batch: Vec<SqliteBoard>,
+    /// Number of boards inserted so far
+    boards_written: usize
+}
+impl SqliteWriter {
+    /// Creates the database
+    /// # Arguments
+    /// * `path` - Path to create the database at, replacing any file already there
+    /// # Returns
+    /// * `Result` with the writer, or an error message if the database couldn't be created
+    pub fn create(path: &Path) -> Result<SqliteWriter, String> {
+        match fs::remove_file(path) {
+            Ok(()) => {},
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {},
+            Err(e) => return Err(format!("Failed to replace {}: {}", path.display(), e))
+        }
+        let connection = Connection::open(path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+        connection.execute_batch(SCHEMA).map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+        Ok(SqliteWriter { connection, path: path.to_path_buf(), batch: Vec::with_capacity(BOARDS_PER_TRANSACTION), boards_written: 0 })
+    }
+
+    /// Adds a board to the database, inserting the boards waiting once there are enough for a transaction
+    /// # Arguments
+    /// * `board` - The board
+    /// # Returns
+    /// * `Result` with nothing, or an error message if the boards couldn't be inserted
+    pub fn write_board(&mut self, board: SqliteBoard) -> Result<(), String> {
+        self.batch.push(board);
+        if self.batch.len() == BOARDS_PER_TRANSACTION {
+            self.insert_batch()?;
+        }
+        Ok(())
+    }
+
+    /// Inserts the boards waiting
+    fn insert_batch(&mut self) -> Result<(), String> {
+        insert_boards(&mut self.connection, &self.batch).map_err(|e| format!("Failed to write to {}: {}", self.path.display(), e))?;
+        self.boards_written += self.batch.len();
+        self.batch.clear();
+        Ok(())
+    }
+
+    /// Inserts any boards still waiting and closes the database
+    /// # Returns
+    /// * `Result` with the number of boards in the database, or an error message if the boards couldn't be inserted
+    pub fn finish(mut self) -> Result<usize, String> {
+        self.insert_batch()?;
+        self.connection.close().map_err(|(_, e)| format!("Failed to write to {}: {}", self.path.display(), e))?;
+        Ok(self.boards_written)
+    }
+}
+
+/// Counts the boards in a database
+/// # Arguments
+/// * `path` - Path to the database
+/// # Returns
+/// * `Result` with the number of boards, or an error message if the database couldn't be read
+pub fn count_boards(path: &Path) -> Result<usize, String> {
+    let connection = Connection::open_with_flags(path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY).map_err(|e| e.to_string())?;
+    let count: i64 = connection.query_row("SELECT COUNT(*) FROM boards", [], |row| row.get(0)).map_err(|e| e.to_string())?;
+    Ok(count as usize)
+}
+
+/// Reads every board in a database a row at a time, along with how hard it was to solve
+/// # Arguments
+/// * `path` - Path to the database
+/// * `visit` - Called with the hand, the board (cropped to its letters), and how hard it was to solve along with the time taken in
+///   milliseconds (unless the database was written before the depth and failed first words were recorded) of each board, in order
+/// # Returns
+/// * `Result` with nothing, or an error message if the database couldn't be read
+pub fn read_boards(path: &Path, visit: &mut impl FnMut(&Letters, &Board, Option<(SolveMetrics, u64)>)) -> Result<(), String> {
+    let read_error = |e: rusqlite::Error| format!("Failed to read {}: {}", path.display(), e);
+    let connection = Connection::open_with_flags(path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY).map_err(read_error)?;
+    let has_metrics: bool = connection.query_row("SELECT COUNT(*) > 0 FROM pragma_table_info('boards') WHERE name = 'depth'", [], |row| row.get(0)).map_err(read_error)?;
+    let query = match has_metrics {
+        true => "SELECT width, height, tiles, hand, words_checked, depth, failed_first_words, elapsed_ms FROM boards ORDER BY id",
+        false => "SELECT width, height, tiles, hand FROM boards ORDER BY id"
+    };
+    let mut statement = connection.prepare(query).map_err(read_error)?;
+    let mut rows = statement.query([]).map_err(read_error)?;
+    while let Some(row) = rows.next().map_err(read_error)? {
+        let (width, height, tiles, hand): (i64, i64, Vec<u8>, Vec<u8>) = (row.get(0).map_err(read_error)?, row.get(1).map_err(read_error)?, row.get(2).map_err(read_error)?, row.get(3).map_err(read_error)?);
+        let metrics: Option<(i64, i64, Option<i64>, i64)> = match has_metrics {
+            true => Some((row.get(4).map_err(read_error)?, row.get(5).map_err(read_error)?, row.get(6).map_err(read_error)?, row.get(7).map_err(read_error)?)),
+            false => None
+        };
+        let hand = Letters::try_from(hand.as_slice()).map_err(|_| format!("Invalid board in {}: hand isn't 26 letter counts", path.display()))?;
+        let (width, height) = (width as usize, height as usize);
+        if tiles.len() != width * height {
+            return Err(format!("Invalid board in {}: {} tiles for a {}x{} board", path.display(), tiles.len(), width, height));
+        }
+        let mut board = Board::new(width.max(height));
+        for (i, &tile) in tiles.iter().enumerate().filter(|(_, &tile)| tile != 0) {
+            let val = (tile & !BGB_BLANK_FLAG).wrapping_sub(1);
+            if val as usize >= ENGLISH_ALPHABET_SIZE {
+                return Err(format!("Invalid board in {}: tile value {}", path.display(), tile));
+            }
+            board.set_val(i / width, i % width, val);
+            if tile & BGB_BLANK_FLAG != 0 {
+                board.set_blank(i / width, i % width);
+            }
+        }
+        let metrics = metrics.map(|(words_checked, depth, failed_first_words, elapsed_ms)| {
+            (SolveMetrics { words_checked: words_checked as usize, depth: depth as usize, failed_first_words: failed_first_words.unwrap_or_default() as usize }, elapsed_ms as u64)
+        });
+        visit(&hand, &board, metrics);
+    }
+    Ok(())
+}
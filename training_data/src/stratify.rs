@@ -0,0 +1,134 @@
+//! Generating the same number of boards from hands in each of several ranges of sizes (`--stratify`), since otherwise small hands, which
+//! solve almost instantly, make up most of the boards
+use std::sync::atomic::{AtomicUsize, Ordering};
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+use bananagrams_core::HandSizeDistribution;
+
+/// Parses the ranges of hand sizes to stratify by
+/// # Arguments
+/// * `ranges` - The ranges, each like `11-20` (or just `21` for a single size)
+/// * `maximum_hand_size` - Largest hand size that can be drawn (the number of tiles in the tile set)
+/// # Returns
+/// * `Result<Vec<(usize, usize)>, String>` - The smallest and largest hand size of each range, or why they're invalid (such as if they
+///   overlap or aren't in ascending order)
+pub fn parse_buckets(ranges: &[String], maximum_hand_size: usize) -> Result<Vec<(usize, usize)>, String> {
+    let mut buckets: Vec<(usize, usize)> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        let (min, max) = range.split_once('-').unwrap_or((range, range));
+        let bucket = match (min.trim().parse::<usize>(), max.trim().parse::<usize>()) {
+            (Ok(min), Ok(max)) if min >= 1 && min <= max && max <= maximum_hand_size => (min, max),
+            _ => return Err(format!("Invalid hand-size range {:?} (must be like 11-20, with sizes from 1 to {})", range, maximum_hand_size))
+        };
+        if buckets.last().is_some_and(|&(_, previous_max)| bucket.0 <= previous_max) {
+            return Err(format!("Hand-size range {:?} overlaps or comes before the one before it (ranges must be in ascending order)", range));
+        }
+        buckets.push(bucket);
+    }
+    if buckets.is_empty() {
+        return Err("No hand-size ranges given".to_string());
+    }
+    Ok(buckets)
+}
+
+/// Boards generated from hands in one range of sizes, as reported in the manifest and summary
+#[derive(Serialize, Deserialize)]
+pub struct BucketCount {
+    /// Smallest hand size in the range
+    pub min_size: usize,
+    /// Largest hand size in the range
+    pub max_size: usize,
+    /// Number of boards generated
+    pub boards: usize,
+    /// Number of hands drawn
+    pub hands_drawn: usize,
+    /// Whether hands stopped being drawn before enough boards were generated, since too many had been drawn already
+    pub gave_up: bool
+}
+
+/// The ranges of hand sizes being stratified by and the boards generated in each so far, shared across threads
+pub struct Strata {
+    /// The smallest and largest hand size of each range
+    buckets: Vec<(usize, usize)>,
+    /// Number of boards to generate from hands in each range
+    per_bucket: usize,
+    /// Maximum number of hands to draw in each range
+    max_hands: usize,
+    /// Number of boards generated from hands in each range
+    boards: Vec<AtomicUsize>,
+    /// Number of hands drawn in each range
+    hands_drawn: Vec<AtomicUsize>
+}
+impl Strata {
+    /// Starts stratifying by ranges of hand sizes
+    /// # Arguments
+    /// * `buckets` - The smallest and largest hand size of each range (see `parse_buckets`)
+    /// * `per_bucket` - Number of boards to generate from hands in each range
+    /// * `max_hands` - Maximum number of hands to draw in each range, after which it's given up on (so that a range whose hands are rarely
+    ///   solved can't keep the run going forever)
+    /// # Returns
+    /// * `Strata` - The strata, with no boards generated
+    pub fn new(buckets: Vec<(usize, usize)>, per_bucket: usize, max_hands: usize) -> Strata {
+        let boards = buckets.iter().map(|_| AtomicUsize::new(0)).collect();
+        let hands_drawn = buckets.iter().map(|_| AtomicUsize::new(0)).collect();
+        Strata { buckets, per_bucket, max_hands, boards, hands_drawn }
+    }
+
+    /// Gets the total number of boards to generate
+    pub fn total_boards(&self) -> usize {
+        self.buckets.len() * self.per_bucket
+    }
+
+    /// Chooses a range to draw the next hand from, counting the hand as drawn in it
+    /// # Arguments
+    /// * `rng` - Random number generator
+    /// # Returns
+    /// * `Option<usize>` - Index of a random range that still needs boards and hasn't been given up on, or `None` if there are none left
+    pub fn choose(&self, rng: &mut impl Rng) -> Option<usize> {
+        loop {
+            let open: Vec<usize> = (0..self.buckets.len()).filter(|&bucket| {
+                self.boards[bucket].load(Ordering::Relaxed) < self.per_bucket && self.hands_drawn[bucket].load(Ordering::Relaxed) < self.max_hands
+            }).collect();
+            let &bucket = open.choose(rng)?;
+            // Another thread may have drawn the last hand allowed in the meantime, in which case another range is chosen
+            if self.hands_drawn[bucket].fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| if n < self.max_hands { Some(n+1) } else { None }).is_ok() {
+                return Some(bucket);
+            }
+        }
+    }
+
+    /// Restricts a distribution of hand sizes to a range
+    /// # Arguments
+    /// * `bucket` - Index of the range
+    /// * `hand_sizes` - The distribution, which must be `LogScaled` or `Uniform`
+    /// # Returns
+    /// * `HandSizeDistribution` - The same kind of distribution over just the sizes in the range
+    pub fn hand_sizes(&self, bucket: usize, hand_sizes: HandSizeDistribution) -> HandSizeDistribution {
+        let (min, max) = self.buckets[bucket];
+        match hand_sizes {
+            HandSizeDistribution::LogScaled(_, _) => HandSizeDistribution::LogScaled(min, max),
+            HandSizeDistribution::Uniform(_, _) => HandSizeDistribution::Uniform(min, max),
+            _ => panic!("Only log-scaled and uniform hand sizes can be stratified!")
+        }
+    }
+
+    /// Claims one of the boards left to generate in a range
+    /// # Arguments
+    /// * `bucket` - Index of the range
+    /// # Returns
+    /// * `bool` - Whether a board was claimed, which is false once the range has enough
+    pub fn claim(&self, bucket: usize) -> bool {
+        self.boards[bucket].fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| if n < self.per_bucket { Some(n+1) } else { None }).is_ok()
+    }
+
+    /// Gets the boards generated in each range so far
+    /// # Returns
+    /// * `Vec<BucketCount>` - The boards and hands of each range, in ascending order
+    pub fn counts(&self) -> Vec<BucketCount> {
+        self.buckets.iter().enumerate().map(|(bucket, &(min_size, max_size))| {
+            let boards = self.boards[bucket].load(Ordering::Relaxed);
+            let hands_drawn = self.hands_drawn[bucket].load(Ordering::Relaxed);
+            BucketCount { min_size, max_size, boards, hands_drawn, gave_up: boards < self.per_bucket && hands_drawn >= self.max_hands }
+        }).collect()
+    }
+}
@@ -0,0 +1,24 @@
+//! Options for writing boards to standard output as they're generated with `--stream`, for a training script reading them from a pipe
+//! (the boards themselves are written by `writer::Writer`)
+use std::{io, sync::{Arc, atomic::{AtomicBool, Ordering}}, thread};
+use clap::ValueEnum;
+
+/// Format of the records written to standard output
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum StreamFormat {
+    /// MessagePack, with each record preceded by its length (see `bananagrams_core::msgpack`)
+    Msgpack,
+    /// JSON Lines
+    Jsonl
+}
+
+/// Starts a thread that sets `stop` once standard input is closed, so that a parent process can end the run by closing the pipe it
+/// writes to (anything read is discarded)
+/// # Arguments
+/// * `stop` - Flag to set
+pub fn watch_stdin(stop: Arc<AtomicBool>) {
+    thread::spawn(move || {
+        let _ = io::copy(&mut io::stdin().lock(), &mut io::sink());
+        stop.store(true, Ordering::Relaxed);
+    });
+}
@@ -0,0 +1,376 @@
+//! Statistics about a run, printed once it finishes and optionally saved as `summary.json`
+use std::{collections::BTreeMap, fmt};
+use serde::Serialize;
+use bananagrams_core::{FirstWordStrategy, SolveFailure, SolveMetrics};
+use crate::stratify::BucketCount;
+
+/// Number of hand sizes grouped together when reporting the solve rate by hand size
+const HAND_SIZE_BUCKET_WIDTH: usize = 10;
+
+/// Statistics collected by a thread about the hands it tried to solve, which are merged once every thread finishes
+#[derive(Default)]
+pub struct RunStats {
+    /// Number of hands tried (not counting any the solver was stopped partway through)
+    hands_attempted: usize,
+    /// Number of hands tried but given up on, by the reason why (see `SolveFailure::name`)
+    failures: BTreeMap<&'static str, usize>,
+    /// Number of hands given up on for which a partial board was written (see `--allow-partial`)
+    partial_boards: usize,
+    /// Number of hands tried and number solved, by the first hand size in their bucket (see `HAND_SIZE_BUCKET_WIDTH`)
+    by_hand_size: BTreeMap<usize, (usize, usize)>,
+    /// Number of hands tried of each size
+    hand_sizes: BTreeMap<usize, usize>,
+    /// Number of hands tried and number solved that had consonants swapped for vowels (see `--min-vowels`)
+    vowels_swapped: (usize, usize),
+    /// Number of hands tried, number solved, and total words checked to solve them, by the name of the first-word strategy used (see
+    /// `FirstWordStrategy::name`)
+    by_first_word: BTreeMap<&'static str, (usize, usize, usize)>,
+    /// Number of words checked to solve each hand solved
+    words_checked: Vec<usize>,
+    /// Number of words played to make the board of each hand solved
+    depths: Vec<usize>,
+    /// Number of first words given up on before solving each hand solved
+    failed_first_words: Vec<usize>,
+    /// Time taken to solve each hand solved, in milliseconds
+    elapsed_ms: Vec<u128>,
+    /// Number of boards solved with each width
+    widths: BTreeMap<usize, usize>,
+    /// Number of boards solved with each height
+    heights: BTreeMap<usize, usize>
+}
+impl RunStats {
+    /// Records a hand that was solved
+    /// # Arguments
+    /// * `hand_size` - Number of letters in the hand
+    /// * `first_word` - How the solver ordered the first words it tried
+    /// * `metrics` - How hard it was to solve
+    /// * `elapsed_ms` - Time taken to solve it, in milliseconds
+    /// * `width` - Number of columns the solved board spans
+    /// * `height` - Number of rows the solved board spans
+    pub fn record_solve(&mut self, hand_size: usize, first_word: FirstWordStrategy, metrics: &SolveMetrics, elapsed_ms: u128, width: usize, height: usize) {
+        self.hands_attempted += 1;
+        let bucket = self.by_hand_size.entry(hand_size / HAND_SIZE_BUCKET_WIDTH * HAND_SIZE_BUCKET_WIDTH).or_default();
+        bucket.0 += 1;
+        bucket.1 += 1;
+        *self.hand_sizes.entry(hand_size).or_default() += 1;
+        let strategy = self.by_first_word.entry(first_word.name()).or_default();
+        strategy.0 += 1;
+        strategy.1 += 1;
+        strategy.2 += metrics.words_checked;
+        self.words_checked.push(metrics.words_checked);
+        self.depths.push(metrics.depth);
+        self.failed_first_words.push(metrics.failed_first_words);
+        self.elapsed_ms.push(elapsed_ms);
+        *self.widths.entry(width).or_default() += 1;
+        *self.heights.entry(height).or_default() += 1;
+    }
+
+    /// Records a hand that was given up on
+    /// # Arguments
+    /// * `hand_size` - Number of letters in the hand
+    /// * `first_word` - How the solver ordered the first words it tried
+    /// * `reason` - Why the hand was given up on (which shouldn't be `SolveFailure::Stopped`, since that says nothing about the hand)
+    pub fn record_failure(&mut self, hand_size: usize, first_word: FirstWordStrategy, reason: SolveFailure) {
+        self.hands_attempted += 1;
+        *self.failures.entry(reason.name()).or_default() += 1;
+        self.by_hand_size.entry(hand_size / HAND_SIZE_BUCKET_WIDTH * HAND_SIZE_BUCKET_WIDTH).or_default().0 += 1;
+        *self.hand_sizes.entry(hand_size).or_default() += 1;
+        self.by_first_word.entry(first_word.name()).or_default().0 += 1;
+    }
+
+    /// Records that a hand had consonants swapped for vowels (which should also be recorded with `record_solve` or `record_failure`)
+    /// # Arguments
+    /// * `solved` - Whether the hand was solved
+    pub fn record_vowels_swapped(&mut self, solved: bool) {
+        self.vowels_swapped.0 += 1;
+        self.vowels_swapped.1 += usize::from(solved);
+    }
+
+    /// Records a partial board written for a hand that was given up on (which should also be recorded with `record_failure`)
+    pub fn record_partial(&mut self) {
+        self.partial_boards += 1;
+    }
+
+    /// Adds the statistics of another thread to these
+    /// # Arguments
+    /// * `other` - The other thread's statistics
+    pub fn merge(&mut self, other: RunStats) {
+        self.hands_attempted += other.hands_attempted;
+        self.partial_boards += other.partial_boards;
+        self.vowels_swapped.0 += other.vowels_swapped.0;
+        self.vowels_swapped.1 += other.vowels_swapped.1;
+        for (reason, count) in other.failures {
+            *self.failures.entry(reason).or_default() += count;
+        }
+        for (bucket, (attempted, solved)) in other.by_hand_size {
+            let totals = self.by_hand_size.entry(bucket).or_default();
+            totals.0 += attempted;
+            totals.1 += solved;
+        }
+        for (size, count) in other.hand_sizes {
+            *self.hand_sizes.entry(size).or_default() += count;
+        }
+        for (strategy, (attempted, solved, words_checked)) in other.by_first_word {
+            let totals = self.by_first_word.entry(strategy).or_default();
+            totals.0 += attempted;
+            totals.1 += solved;
+            totals.2 += words_checked;
+        }
+        self.words_checked.extend(other.words_checked);
+        self.depths.extend(other.depths);
+        self.failed_first_words.extend(other.failed_first_words);
+        self.elapsed_ms.extend(other.elapsed_ms);
+        for (width, count) in other.widths {
+            *self.widths.entry(width).or_default() += count;
+        }
+        for (height, count) in other.heights {
+            *self.heights.entry(height).or_default() += count;
+        }
+    }
+
+    /// Summarizes the statistics
+    /// # Arguments
+    /// * `boards_produced` - Number of boards written (which may be fewer than the hands solved if some were duplicates or solved after
+    ///   enough boards were generated)
+    /// * `strata` - Boards generated from hands in each range of sizes, if stratified (see `--stratify`)
+    /// # Returns
+    /// * `Summary` - The summary
+    pub fn summary(&self, boards_produced: usize, strata: Option<Vec<BucketCount>>) -> Summary {
+        Summary {
+            hands_attempted: self.hands_attempted,
+            hands_solved: self.words_checked.len(),
+            boards_produced,
+            solve_rate: rate(self.words_checked.len(), self.hands_attempted),
+            failures: self.failures.clone(),
+            partial_boards: self.partial_boards,
+            by_hand_size: self.by_hand_size.iter().map(|(&min_size, &(attempted, solved))| HandSizeBucket {
+                min_size,
+                max_size: min_size + HAND_SIZE_BUCKET_WIDTH - 1,
+                attempted,
+                solved,
+                solve_rate: rate(solved, attempted)
+            }).collect(),
+            hand_sizes: self.hand_sizes.clone(),
+            strata,
+            vowels_swapped: (self.vowels_swapped.0 > 0).then(|| {
+                let (attempted, solved) = self.vowels_swapped;
+                let (others_attempted, others_solved) = (self.hands_attempted - attempted, self.words_checked.len() - solved);
+                VowelsSwapped { attempted, solved, solve_rate: rate(solved, attempted), others_attempted, others_solved, others_solve_rate: rate(others_solved, others_attempted) }
+            }),
+            by_first_word: self.by_first_word.iter().map(|(&strategy, &(attempted, solved, words_checked))| FirstWordStats {
+                strategy,
+                attempted,
+                solved,
+                solve_rate: rate(solved, attempted),
+                mean_words_checked: if solved == 0 { None } else { Some(words_checked as f64 / solved as f64) }
+            }).collect(),
+            words_checked: Distribution::of(self.words_checked.clone()),
+            depth: Distribution::of(self.depths.clone()),
+            failed_first_words: Distribution::of(self.failed_first_words.clone()),
+            mean_elapsed_ms: mean(self.elapsed_ms.iter().map(|&elapsed| elapsed as f64)),
+            widths: self.widths.clone(),
+            heights: self.heights.clone()
+        }
+    }
+}
+
+/// Calculates the proportion of attempts that succeeded
+/// # Arguments
+/// * `successes` - Number of successes
+/// * `attempts` - Number of attempts
+/// # Returns
+/// * `f64` - The proportion, or 0 if there were no attempts
+fn rate(successes: usize, attempts: usize) -> f64 {
+    if attempts == 0 { 0.0 } else { successes as f64 / attempts as f64 }
+}
+
+/// Calculates the mean of some values
+/// # Arguments
+/// * `values` - The values
+/// # Returns
+/// * `Option<f64>` - The mean, or `None` if there are no values
+fn mean(values: impl Iterator<Item = f64>) -> Option<f64> {
+    let (sum, count) = values.fold((0.0, 0usize), |(sum, count), value| (sum + value, count + 1));
+    if count == 0 { None } else { Some(sum / count as f64) }
+}
+
+/// Finds a percentile of some values with the nearest-rank method
+/// # Arguments
+/// * `sorted` - The values, in ascending order
+/// * `fraction` - The percentile, as a fraction between 0 and 1
+/// # Returns
+/// * `Option<usize>` - The smallest value at least `fraction` of the values are no greater than, or `None` if there are no values
+fn percentile(sorted: &[usize], fraction: f64) -> Option<usize> {
+    let rank = (fraction * sorted.len() as f64).ceil() as usize;
+    sorted.get(rank.max(1) - 1).copied()
+}
+
+/// Summary of a run, as saved in `summary.json`
+#[derive(Serialize)]
+pub struct Summary {
+    /// Number of hands tried
+    hands_attempted: usize,
+    /// Number of hands solved
+    hands_solved: usize,
+    /// Number of boards written
+    boards_produced: usize,
+    /// Proportion of hands tried that were solved
+    solve_rate: f64,
+    /// Number of hands given up on, by the reason why
+    failures: BTreeMap<&'static str, usize>,
+    /// Number of partial boards written for hands given up on
+    partial_boards: usize,
+    /// Hands tried and solved, grouped by hand size
+    by_hand_size: Vec<HandSizeBucket>,
+    /// Number of hands tried of each size (to check the distribution hand sizes were drawn from)
+    hand_sizes: BTreeMap<usize, usize>,
+    /// Boards generated from hands in each range of sizes, if stratified
+    strata: Option<Vec<BucketCount>>,
+    /// Hands tried and solved that had consonants swapped for vowels, compared with the rest (if any had)
+    vowels_swapped: Option<VowelsSwapped>,
+    /// Hands tried and solved, grouped by the first-word strategy used
+    by_first_word: Vec<FirstWordStats>,
+    /// Number of words checked to solve each hand solved
+    words_checked: Distribution,
+    /// Number of words played to make the board of each hand solved
+    depth: Distribution,
+    /// Number of first words given up on before solving each hand solved
+    failed_first_words: Distribution,
+    /// Mean time taken to solve each hand solved, in milliseconds
+    mean_elapsed_ms: Option<f64>,
+    /// Number of boards solved with each width
+    widths: BTreeMap<usize, usize>,
+    /// Number of boards solved with each height
+    heights: BTreeMap<usize, usize>
+}
+// Written as the lines printed at the end of a run
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Solved {} of {} hands tried ({:.1}%)", self.hands_solved, self.hands_attempted, 100.0 * self.solve_rate)?;
+        let failures: Vec<String> = SolveFailure::ALL.iter().filter_map(|reason| self.failures.get(reason.name()).map(|count| format!("{} {}", count, reason.name()))).collect();
+        if !failures.is_empty() {
+            writeln!(f, "Gave up on {} hands ({})", self.hands_attempted - self.hands_solved, failures.join(", "))?;
+        }
+        if self.partial_boards > 0 {
+            writeln!(f, "Wrote {} partial boards", self.partial_boards)?;
+        }
+        let distribution = |counts: &BTreeMap<usize, usize>| counts.iter().map(|(size, count)| format!("{}: {}", size, count)).collect::<Vec<String>>().join(", ");
+        if !self.hand_sizes.is_empty() {
+            writeln!(f, "Hand sizes: {}", distribution(&self.hand_sizes))?;
+        }
+        if let Some(strata) = &self.strata {
+            let counts: Vec<String> = strata.iter().map(|bucket| match bucket.gave_up {
+                true => format!("{}-{}: {} (gave up after {} hands)", bucket.min_size, bucket.max_size, bucket.boards, bucket.hands_drawn),
+                false => format!("{}-{}: {} ({} hands)", bucket.min_size, bucket.max_size, bucket.boards, bucket.hands_drawn)
+            }).collect();
+            writeln!(f, "Boards by hand-size range: {}", counts.join(", "))?;
+        }
+        if let Some(swapped) = &self.vowels_swapped {
+            writeln!(f, "Solve rate of hands given vowels for consonants: {:.1}% of {} (other hands: {:.1}% of {})", 100.0 * swapped.solve_rate, swapped.attempted, 100.0 * swapped.others_solve_rate, swapped.others_attempted)?;
+        }
+        let buckets: Vec<String> = self.by_hand_size.iter().map(|bucket| format!("{}-{}: {:.1}% of {}", bucket.min_size, bucket.max_size, 100.0 * bucket.solve_rate, bucket.attempted)).collect();
+        if !buckets.is_empty() {
+            writeln!(f, "Solve rate by hand size: {}", buckets.join(", "))?;
+        }
+        // Only worth comparing if more than one strategy was used
+        if self.by_first_word.len() > 1 {
+            let strategies: Vec<String> = self.by_first_word.iter().map(|stats| match stats.mean_words_checked {
+                Some(mean) => format!("{}: {:.1}% of {} (mean {:.1} words checked)", stats.strategy, 100.0 * stats.solve_rate, stats.attempted, mean),
+                None => format!("{}: {:.1}% of {}", stats.strategy, 100.0 * stats.solve_rate, stats.attempted)
+            }).collect();
+            writeln!(f, "Solve rate by first-word strategy: {}", strategies.join(", "))?;
+        }
+        for (name, distribution) in [("Words checked", &self.words_checked), ("Words played", &self.depth), ("First words given up on", &self.failed_first_words)] {
+            if let Some(description) = distribution.describe() {
+                writeln!(f, "{} per solve: {}", name, description)?;
+            }
+        }
+        if let Some(mean_elapsed_ms) = self.mean_elapsed_ms {
+            writeln!(f, "Time per solve: mean {:.1} ms", mean_elapsed_ms)?;
+        }
+        if !self.widths.is_empty() {
+            writeln!(f, "Board widths: {}", distribution(&self.widths))?;
+            writeln!(f, "Board heights: {}", distribution(&self.heights))?;
+        }
+        Ok(())
+    }
+}
+
+/// Hands tried and solved with sizes in a range
+#[derive(Serialize)]
+struct HandSizeBucket {
+    /// Smallest hand size in the range
+    min_size: usize,
+    /// Largest hand size in the range
+    max_size: usize,
+    /// Number of hands tried
+    attempted: usize,
+    /// Number of hands solved
+    solved: usize,
+    /// Proportion of hands tried that were solved
+    solve_rate: f64
+}
+
+/// Hands tried and solved that had consonants swapped for vowels, and the rest
+#[derive(Serialize)]
+struct VowelsSwapped {
+    /// Number of hands with vowels swapped in tried
+    attempted: usize,
+    /// Number of hands with vowels swapped in solved
+    solved: usize,
+    /// Proportion of hands with vowels swapped in tried that were solved
+    solve_rate: f64,
+    /// Number of other hands tried
+    others_attempted: usize,
+    /// Number of other hands solved
+    others_solved: usize,
+    /// Proportion of other hands tried that were solved
+    others_solve_rate: f64
+}
+
+/// Hands tried and solved with a first-word strategy
+#[derive(Serialize)]
+struct FirstWordStats {
+    /// Name of the strategy (see `FirstWordStrategy::name`)
+    strategy: &'static str,
+    /// Number of hands tried
+    attempted: usize,
+    /// Number of hands solved
+    solved: usize,
+    /// Proportion of hands tried that were solved
+    solve_rate: f64,
+    /// Mean number of words checked to solve each hand solved
+    mean_words_checked: Option<f64>
+}
+
+/// Statistics of a count recorded for each hand solved (such as the number of words checked)
+#[derive(Serialize)]
+pub struct Distribution {
+    /// The mean
+    mean: Option<f64>,
+    /// The median
+    median: Option<usize>,
+    /// The 95th percentile
+    p95: Option<usize>
+}
+impl Distribution {
+    /// Calculates the statistics of some counts
+    /// # Arguments
+    /// * `values` - The counts, in any order
+    /// # Returns
+    /// * `Distribution` - The statistics, which are all `None` if there are no counts
+    pub fn of(mut values: Vec<usize>) -> Distribution {
+        values.sort_unstable();
+        Distribution { mean: mean(values.iter().map(|&count| count as f64)), median: percentile(&values, 0.5), p95: percentile(&values, 0.95) }
+    }
+
+    /// Describes the statistics for printing
+    /// # Returns
+    /// * `Option<String>` - The mean, median, and 95th percentile, or `None` if there were no counts
+    pub fn describe(&self) -> Option<String> {
+        match (self.mean, self.median, self.p95) {
+            (Some(mean), Some(median), Some(p95)) => Some(format!("mean {:.1}, median {}, 95th percentile {}", mean, median, p95)),
+            _ => None
+        }
+    }
+}
@@ -0,0 +1,81 @@
+//! Checking whether boards are legal (the `validate` subcommand), reporting each invalid word and each group of letters cut off from the
+//! others
+use serde::Serialize;
+use hashbrown::HashSet;
+use bananagrams_core::{Board, BoardError, PlayedWord, Word, connected_groups, extract_words, validate_board};
+
+/// Whether a board is legal, and if not, why; the rows and columns are counted from the top-left corner of the board's letters
+#[derive(Serialize)]
+pub struct BoardCheck {
+    /// Index of the board in its file, counting from 0
+    pub board: usize,
+    /// Whether the board is legal
+    pub valid: bool,
+    /// Number of tiles on the board
+    pub tiles: usize,
+    /// Number of words on the board (every run of two or more letters, valid or not)
+    pub words: usize,
+    /// Each word that isn't in the dictionary (or is too short), in the order of `validate_board`
+    pub invalid_words: Vec<PlayedWord>,
+    /// The (row, column) of each tile in each group of connected letters, in row-major order; a legal board has one group
+    pub components: Vec<Vec<(usize, usize)>>
+}
+impl BoardCheck {
+    /// Describes the result for a person to read
+    /// # Returns
+    /// * `String` - `VALID` or `INVALID` with the number of tiles and words, followed by a line for each problem (each invalid word, and the
+    ///   tiles of each group of letters if there's more than one)
+    pub fn describe(&self) -> String {
+        let mut description = format!("{} ({} tiles, {} words)", if self.valid { "VALID" } else { "INVALID" }, self.tiles, self.words);
+        if self.components.is_empty() {
+            description.push_str(&format!("\n  {}", BoardError::Empty));
+        }
+        for word in self.invalid_words.iter() {
+            description.push_str(&format!("\n  Invalid word {}", word));
+        }
+        if self.components.len() > 1 {
+            description.push_str(&format!("\n  {}:", BoardError::Disconnected { components: self.components.len() }));
+            for (i, group) in self.components.iter().enumerate() {
+                let tiles: Vec<String> = group.iter().map(|(row, col)| format!("({}, {})", row, col)).collect();
+                description.push_str(&format!("\n    Group {}: {}", i+1, tiles.join(" ")));
+            }
+        }
+        description
+    }
+}
+
+/// Checks whether a board is legal
+/// # Arguments
+/// * `index` - Index of the board in its file
+/// * `board` - The board to check
+/// * `valid_words` - HashSet of all valid words
+/// * `min_word_length` - Minimum length of a word, below which runs of letters are invalid
+/// # Returns
+/// * `BoardCheck` - Whether the board is legal, and if not, why
+pub fn check_board(index: usize, board: &Board, valid_words: &HashSet<Word>, min_word_length: usize) -> BoardCheck {
+    let (min_col, max_col, min_row, max_row) = board.bounds().unwrap_or((0, 0, 0, 0));
+    let mut components = connected_groups(board);
+    for group in components.iter_mut() {
+        for (row, col) in group.iter_mut() {
+            *row -= min_row;
+            *col -= min_col;
+        }
+        group.sort_unstable();
+    }
+    let words = if components.is_empty() { 0 } else { extract_words(board, min_col, max_col, min_row, max_row).len() };
+    let invalid_words = match validate_board(board, valid_words, min_word_length) {
+        Ok(()) => Vec::new(),
+        Err(errors) => errors.into_iter().filter_map(|error| match error {
+            BoardError::InvalidWord(word) => Some(PlayedWord { row: word.row - min_row, col: word.col - min_col, ..word }),
+            _ => None
+        }).collect()
+    };
+    BoardCheck {
+        board: index,
+        valid: components.len() == 1 && invalid_words.is_empty(),
+        tiles: components.iter().map(|group| group.len()).sum(),
+        words,
+        invalid_words,
+        components
+    }
+}
@@ -0,0 +1,457 @@
+//! The thread writing the output of a run, which every thread generating boards sends them to over a bounded channel, so that only one
+//! thread ever touches the files (or the database, or standard output with `--stream`) and a slow writer holds the others up rather than
+//! letting boards pile up in memory
+use std::{fs::File, io::{self, BufWriter, Write}, sync::{atomic::{AtomicBool, Ordering}, mpsc::Receiver}};
+use log::{info, warn};
+use bananagrams_core::{Board, PlayedWord, SolveFailure, BGB_FORMAT_VERSION, board_to_bytes, board_to_onehot};
+use crate::{npy, open_output, plays_to_json, words_to_json, BoardRecord, BoardWriter, FailureRecord, GenerateArgs, OutputFormat, ResumedThread, Shard, PROGRESS_LOG_INTERVAL};
+#[cfg(feature = "arrow")]
+use crate::parquet;
+#[cfg(feature = "sqlite")]
+use crate::sqlite;
+use crate::progress::Progress;
+use crate::stream::StreamFormat;
+
+/// Number of things that can be waiting for the writer thread before the threads generating boards have to wait too
+pub const CHANNEL_CAPACITY: usize = 256;
+
+/// Something a thread generating boards sends the writer thread
+pub enum WriterEvent {
+    /// A board was generated
+    Board(GeneratedBoard),
+    /// A hand couldn't be solved, and this is the board with the most tiles the solver found for it (with `--allow-partial`)
+    Partial(BoardRecord, SolveFailure),
+    /// A hand couldn't be solved (with `--log-failures`)
+    Failure(FailureRecord)
+}
+
+/// A board generated by a thread, along with everything written alongside it
+pub struct GeneratedBoard {
+    /// The board, followed by its other rotations and reflections with `--augment`
+    pub records: Vec<BoardRecord>,
+    /// The words played to make the board, in order
+    pub plays: Vec<PlayedWord>,
+    /// Corrupted (invalid) copies of the board, each with the label of how it was corrupted (with `--negatives-per-board`)
+    pub negatives: Vec<(u8, Board)>
+}
+
+/// What the writer thread wrote, once every thread generating boards has finished
+pub struct Written {
+    /// Every file of boards written, ordered by thread and then by index (then the SQLite database, if any)
+    pub shards: Vec<Shard>,
+    /// Number of boards skipped for not fitting in --pad-to
+    pub boards_too_large: usize
+}
+
+/// The files a thread's boards are written to
+#[derive(Default)]
+struct ThreadFiles {
+    /// Files finished so far
+    shards: Vec<Shard>,
+    /// Number of boards of the thread written so far in this run
+    boards_generated: usize,
+    /// Number of boards of the thread counted toward the current file, including any boards skipped for not fitting in --pad-to
+    shard_boards_generated: usize,
+    /// Number of boards (including rotations and reflections) written into the current file
+    shard_boards_written: usize,
+    /// Writer of the board file, once it has a board
+    board_writer: Option<BoardWriter>,
+    /// Writer of the Parquet board file, once it has a board
+    #[cfg(feature = "arrow")]
+    parquet_writer: Option<parquet::ParquetWriter>,
+    /// Writer of the plays file (with `--plays`)
+    plays_writer: Option<BufWriter<File>>,
+    /// Writer of the words file (with `--dump-words`)
+    words_writer: Option<BufWriter<File>>,
+    /// Writer of the negatives file (with `--negatives-per-board`)
+    negatives_writer: Option<BufWriter<File>>,
+    /// Writer of the partial boards file (with `--allow-partial`)
+    partial_writer: Option<BufWriter<File>>
+}
+impl ThreadFiles {
+    /// Flushes everything written so far through to the files
+    /// # Returns
+    /// * `io::Result<()>` - Empty, or an error if the data couldn't be written
+    fn flush(&mut self) -> io::Result<()> {
+        if let Some(board_writer) = self.board_writer.as_mut() {
+            board_writer.flush()?;
+        }
+        for writer in [&mut self.plays_writer, &mut self.words_writer, &mut self.negatives_writer, &mut self.partial_writer].into_iter().flatten() {
+            writer.flush()?;
+        }
+        Ok(())
+    }
+}
+
+/// Owns everything a run writes to, and writes what the threads generating boards send it
+pub struct Writer<'a> {
+    /// The command-line arguments
+    args: &'a GenerateArgs,
+    /// Seed that each thread's seed is derived from
+    master_seed: u64,
+    /// The files of each thread generating boards
+    threads: Vec<ThreadFiles>,
+    /// The database every board is written to (with `--format sqlite`)
+    #[cfg(feature = "sqlite")]
+    sqlite: Option<sqlite::SqliteWriter>,
+    /// Standard output, which every board is written to (with `--stream`)
+    stdout: Option<io::Stdout>,
+    /// The file of hands that couldn't be solved (with `--log-failures`)
+    failures_writer: Option<BufWriter<File>>,
+    /// ID of the next board written to a CSV or Parquet file
+    next_board_id: usize,
+    /// Number of boards generated so far, including any from before the run was resumed
+    boards_generated: usize,
+    /// Number of boards skipped for not fitting in --pad-to
+    boards_too_large: usize,
+    /// Reporter of the run's progress
+    progress: &'a Progress,
+    /// Flag set to stop the threads generating boards if writing fails (or standard output is closed)
+    stop: &'a AtomicBool
+}
+impl<'a> Writer<'a> {
+    /// Opens what the run writes to that's shared between threads, so that a problem with it is found before any boards are generated
+    /// # Arguments
+    /// * `args` - The command-line arguments
+    /// * `master_seed` - Seed that each thread's seed is derived from
+    /// * `resumed` - What each thread generating boards wrote in the run being resumed (the default for every thread if not resuming)
+    /// * `progress` - Reporter of the run's progress
+    /// * `stop` - Flag to set to stop the threads generating boards
+    /// # Returns
+    /// * `Result` with the writer, or an error message if a file couldn't be created
+    pub fn new(args: &'a GenerateArgs, master_seed: u64, resumed: Vec<ResumedThread>, progress: &'a Progress, stop: &'a AtomicBool) -> Result<Writer<'a>, String> {
+        let boards_generated = resumed.iter().map(|thread| thread.boards).sum();
+        let threads = resumed.into_iter().map(|thread| ThreadFiles {
+            shards: thread.shards,
+            shard_boards_generated: thread.current_boards,
+            shard_boards_written: thread.current_boards,
+            ..ThreadFiles::default()
+        }).collect();
+        let failures_writer = match args.log_failures.as_ref() {
+            Some(path) => Some(BufWriter::new(open_output(path, true).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?)),
+            None => None
+        };
+        Ok(Writer {
+            args,
+            master_seed,
+            threads,
+            #[cfg(feature = "sqlite")]
+            sqlite: match args.format {
+                OutputFormat::Sqlite if !args.stream => Some(sqlite::SqliteWriter::create(&args.out.join(sqlite::SQLITE_FILE_NAME))?),
+                _ => None
+            },
+            stdout: args.stream.then(io::stdout),
+            failures_writer,
+            next_board_id: 0,
+            boards_generated,
+            boards_too_large: 0,
+            progress,
+            stop
+        })
+    }
+
+    /// Writes everything sent to the writer thread until every sender is dropped, then finishes every file
+    /// # Arguments
+    /// * `receiver` - Receiver of what the threads generating boards send
+    /// # Returns
+    /// * `Result` with what was written, or an error message if something couldn't be written (in which case the threads generating
+    ///   boards are stopped)
+    pub fn run(mut self, receiver: Receiver<WriterEvent>) -> Result<Written, String> {
+        let stop = self.stop;
+        let res = self.write_all(receiver).and_then(|()| self.finish());
+        if res.is_err() {
+            stop.store(true, Ordering::Relaxed);
+        }
+        res
+    }
+
+    /// Writes everything sent to the writer thread until every sender is dropped, or standard output is closed with `--stream`
+    fn write_all(&mut self, receiver: Receiver<WriterEvent>) -> Result<(), String> {
+        for event in receiver {
+            match event {
+                WriterEvent::Board(board) => {
+                    if !self.write_board(board)? {
+                        // The reader is gone, which is how a run with --stream is expected to end
+                        self.stop.store(true, Ordering::Relaxed);
+                        break;
+                    }
+                },
+                WriterEvent::Partial(record, reason) => self.write_partial(&record, reason)?,
+                WriterEvent::Failure(record) => {
+                    let line = format!("{}\n", serde_json::to_string(&record).expect("Failed to write failures!"));
+                    if let Some(failures_writer) = self.failures_writer.as_mut() {
+                        failures_writer.write_all(line.as_bytes()).map_err(|e| format!("Failed to write failures: {}", e))?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes a board generated by a thread, along with everything written alongside it
+    /// # Arguments
+    /// * `board` - The board
+    /// # Returns
+    /// * `Result` with whether to keep going (`false` once standard output is closed with `--stream`), or an error message if the board
+    ///   couldn't be written
+    fn write_board(&mut self, board: GeneratedBoard) -> Result<bool, String> {
+        let args = self.args;
+        let thread = board.records[0].thread;
+        if let Some(stdout) = self.stdout.as_mut() {
+            for record in board.records.iter() {
+                let record_bytes = match args.stream_format {
+                    StreamFormat::Msgpack => record.to_msgpack(),
+                    StreamFormat::Jsonl => format!("{}\n", record.to_json()).into_bytes()
+                };
+                // Flushed after every board so that the reader gets it as soon as it's made
+                match stdout.write_all(&record_bytes).and_then(|()| stdout.flush()) {
+                    Ok(()) => {},
+                    Err(e) if e.kind() == io::ErrorKind::BrokenPipe => return Ok(false),
+                    Err(e) => return Err(format!("Failed to write to standard output: {}", e))
+                }
+            }
+        }
+        else {
+            let mut board_bytes: Vec<u8> = Vec::new();
+            #[cfg(feature = "arrow")]
+            let mut parquet_boards: Vec<parquet::ParquetBoard> = Vec::new();
+            let mut boards_skipped = 0;
+            for record in board.records.iter() {
+                match args.format {
+                    OutputFormat::Bgb if args.bgb_metrics => board_bytes.extend(record.to_bgb_with_metrics()),
+                    OutputFormat::Bgb => board_bytes.extend(record.to_bgb()),
+                    OutputFormat::Jsonl => board_bytes.extend(format!("{}\n", record.to_json()).into_bytes()),
+                    OutputFormat::Msgpack => board_bytes.extend(record.to_msgpack()),
+                    OutputFormat::Csv => {
+                        board_bytes.extend(record.to_csv(self.next_board_id).into_bytes());
+                        self.next_board_id += 1;
+                    },
+                    OutputFormat::NpyOnehot => {
+                        let (height, width) = args.pad_to.expect("No --pad-to!");
+                        match board_to_onehot(&record.board, record.min_col, record.max_col, record.min_row, record.max_row, height, width) {
+                            Some(onehot) => board_bytes.extend(onehot),
+                            None => {
+                                warn!("Skipped a {}x{} board that doesn't fit in {}x{} (--pad-to)", record.max_row + 1 - record.min_row, record.max_col + 1 - record.min_col, height, width);
+                                self.boards_too_large += 1;
+                                boards_skipped += 1;
+                            }
+                        }
+                    },
+                    #[cfg(feature = "arrow")]
+                    OutputFormat::Parquet => {
+                        parquet_boards.push(parquet::ParquetBoard::new(self.next_board_id as u64, record));
+                        self.next_board_id += 1;
+                    },
+                    #[cfg(feature = "sqlite")]
+                    OutputFormat::Sqlite => self.sqlite.as_mut().expect("SQLite database not created!").write_board(sqlite::SqliteBoard::new(record))?
+                }
+            }
+            let files = &mut self.threads[thread];
+            let shard_index = files.shards.len();
+            if !board_bytes.is_empty() {
+                if files.board_writer.is_none() {
+                    files.board_writer = Some(open_board_file(args, thread, shard_index)?);
+                }
+                // Each board (with its symmetries) is written in one call so that only whole boards ever reach the file
+                files.board_writer.as_mut().expect("No board file!").write_all(&board_bytes).map_err(|e| board_file_error(args, thread, shard_index, e))?;
+                files.shard_boards_written += board.records.len() - boards_skipped;
+            }
+            #[cfg(feature = "arrow")]
+            if !parquet_boards.is_empty() {
+                if files.parquet_writer.is_none() {
+                    let file = open_output(&args.out.join(args.board_file_name(thread, shard_index)), false).map_err(|e| board_file_error(args, thread, shard_index, e))?;
+                    files.parquet_writer = Some(parquet::ParquetWriter::new(file, args.row_group_size).map_err(|e| board_file_error(args, thread, shard_index, e))?);
+                }
+                let parquet_writer = files.parquet_writer.as_mut().expect("No board file!");
+                for parquet_board in parquet_boards.iter() {
+                    parquet_writer.write_board(parquet_board).map_err(|e| board_file_error(args, thread, shard_index, e))?;
+                }
+                files.shard_boards_written += parquet_boards.len();
+            }
+            let record = &board.records[0];
+            if args.plays {
+                let plays_writer = open_alongside(&mut files.plays_writer, args, thread, shard_index, "plays.jsonl", args.resume, &[])?;
+                writeln!(plays_writer, "{}", plays_to_json(&board.plays)).map_err(|e| format!("Failed to write plays: {}", e))?;
+            }
+            if args.negatives_per_board > 0 {
+                let negatives_writer = open_alongside(&mut files.negatives_writer, args, thread, shard_index, "negatives.bgb", false, &[BGB_FORMAT_VERSION])?;
+                for (label, negative) in board.negatives.iter() {
+                    let mut negative_bytes = vec![*label];
+                    negative_bytes.extend(board_to_bytes(&record.hand, negative).expect("Board too large to save!"));
+                    negatives_writer.write_all(&negative_bytes).map_err(|e| format!("Failed to write negatives: {}", e))?;
+                }
+            }
+            if args.dump_words {
+                let words_writer = open_alongside(&mut files.words_writer, args, thread, shard_index, "words.jsonl", args.resume, &[])?;
+                writeln!(words_writer, "{}", words_to_json(&record.hand, &record.board, record.min_col, record.max_col, record.min_row, record.max_row)).map_err(|e| format!("Failed to write words: {}", e))?;
+            }
+        }
+        let files = &mut self.threads[thread];
+        files.boards_generated += 1;
+        files.shard_boards_generated += 1;
+        if files.boards_generated.is_multiple_of(args.flush_every) {
+            files.flush().map_err(|e| format!("Failed to write board data: {}", e))?;
+            if let Some(failures_writer) = self.failures_writer.as_mut() {
+                failures_writer.flush().map_err(|e| format!("Failed to write failures: {}", e))?;
+            }
+        }
+        if args.verbose {
+            let plays: Vec<String> = board.plays.iter().map(|play| format!("  {}", play)).collect();
+            self.progress.message(format!("Thread {} board {}:\n{}", thread+1, files.boards_generated, plays.join("\n")));
+        }
+        if args.shard_size == Some(files.shard_boards_generated) {
+            self.finish_shard(thread)?;
+        }
+        self.progress.board_generated();
+        self.boards_generated += 1;
+        if self.boards_generated.is_multiple_of(PROGRESS_LOG_INTERVAL) {
+            match args.stream {
+                true => info!("Generated {} boards", self.boards_generated),
+                false => info!("Generated {} of {} boards", self.boards_generated, args.boards)
+            }
+        }
+        Ok(true)
+    }
+
+    /// Writes the board with the most tiles found for a hand that couldn't be solved to the partial boards file of the thread that tried it
+    /// # Arguments
+    /// * `record` - The partial board
+    /// * `reason` - Why the hand couldn't be solved
+    /// # Returns
+    /// * `Result` with nothing, or an error message if the board couldn't be written
+    fn write_partial(&mut self, record: &BoardRecord, reason: SolveFailure) -> Result<(), String> {
+        let args = self.args;
+        let files = &mut self.threads[record.thread];
+        let shard_index = files.shards.len();
+        let extension = format!("partial.{}", args.format.extension());
+        let partial_writer = open_alongside(&mut files.partial_writer, args, record.thread, shard_index, &extension, args.resume, &args.format.header())?;
+        let mut partial_bytes: Vec<u8> = Vec::new();
+        match args.format {
+            OutputFormat::Jsonl => partial_bytes.extend(format!("{}\n", record.to_json()).into_bytes()),
+            // Labeled by why the hand couldn't be solved, since the unused letters are the hand less the tiles on the board
+            _ => {
+                partial_bytes.push(reason.label());
+                partial_bytes.extend(record.to_bgb());
+            }
+        }
+        partial_writer.write_all(&partial_bytes).map_err(|e| format!("Failed to write partial boards: {}", e))
+    }
+
+    /// Finishes a thread's current files, listing its board file among the thread's shards, so that the thread's next board starts new ones
+    /// # Arguments
+    /// * `thread` - Index of the thread
+    /// # Returns
+    /// * `Result` with nothing, or an error message if the files couldn't be finished
+    fn finish_shard(&mut self, thread: usize) -> Result<(), String> {
+        self.finish_files(thread)?;
+        let files = &mut self.threads[thread];
+        files.shards.push(self.args.shard(thread, files.shards.len(), files.shard_boards_written, self.master_seed.wrapping_add(thread as u64)));
+        files.shard_boards_generated = 0;
+        files.shard_boards_written = 0;
+        Ok(())
+    }
+
+    /// Finishes a thread's current files, filling in the number of boards of a `.npy` file
+    /// # Arguments
+    /// * `thread` - Index of the thread
+    /// # Returns
+    /// * `Result` with nothing, or an error message if the files couldn't be finished
+    fn finish_files(&mut self, thread: usize) -> Result<(), String> {
+        let args = self.args;
+        let files = &mut self.threads[thread];
+        let shard_index = files.shards.len();
+        if let Some(board_writer) = files.board_writer.take() {
+            board_writer.finish().map_err(|e| board_file_error(args, thread, shard_index, e))?;
+            if let Some((height, width)) = args.pad_to {
+                npy::set_board_count(&args.out.join(args.board_file_name(thread, shard_index)), files.shard_boards_written, height, width).map_err(|e| board_file_error(args, thread, shard_index, e))?;
+            }
+        }
+        #[cfg(feature = "arrow")]
+        if let Some(parquet_writer) = files.parquet_writer.take() {
+            parquet_writer.finish().map_err(|e| board_file_error(args, thread, shard_index, e))?;
+        }
+        for mut writer in [files.plays_writer.take(), files.words_writer.take(), files.negatives_writer.take(), files.partial_writer.take()].into_iter().flatten() {
+            writer.flush().map_err(|e| format!("Failed to write board data: {}", e))?;
+        }
+        Ok(())
+    }
+
+    /// Finishes every file once every thread generating boards has finished
+    /// # Returns
+    /// * `Result` with what was written, or an error message if a file couldn't be finished
+    fn finish(mut self) -> Result<Written, String> {
+        for thread in 0..self.threads.len() {
+            self.finish_files(thread)?;
+            let files = &mut self.threads[thread];
+            if files.shard_boards_written > 0 {
+                files.shards.push(self.args.shard(thread, files.shards.len(), files.shard_boards_written, self.master_seed.wrapping_add(thread as u64)));
+            }
+        }
+        if let Some(mut failures_writer) = self.failures_writer.take() {
+            failures_writer.flush().map_err(|e| format!("Failed to write failures: {}", e))?;
+        }
+        #[cfg_attr(not(feature = "sqlite"), allow(unused_mut))]
+        let mut shards: Vec<Shard> = self.threads.into_iter().flat_map(|files| files.shards).collect();
+        #[cfg(feature = "sqlite")]
+        if let Some(sqlite) = self.sqlite {
+            let boards = sqlite.finish()?;
+            let path = self.args.out.join(sqlite::SQLITE_FILE_NAME);
+            shards.push(Shard {
+                file: sqlite::SQLITE_FILE_NAME.to_string(),
+                thread: 0,
+                index: 0,
+                boards,
+                bytes: std::fs::metadata(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?.len(),
+                seed: self.master_seed
+            });
+        }
+        Ok(Written { shards, boards_too_large: self.boards_too_large })
+    }
+}
+
+/// Opens a thread's board file, writing the header of the format if it's new
+/// # Arguments
+/// * `args` - The command-line arguments
+/// * `thread` - Index of the thread
+/// * `shard_index` - Index of the file among those written by the thread
+/// # Returns
+/// * `Result` with the writer, or an error message if the file couldn't be created
+fn open_board_file(args: &GenerateArgs, thread: usize, shard_index: usize) -> Result<BoardWriter, String> {
+    let file = open_output(&args.out.join(args.board_file_name(thread, shard_index)), args.resume).map_err(|e| board_file_error(args, thread, shard_index, e))?;
+    let is_new = file.metadata().map_err(|e| board_file_error(args, thread, shard_index, e))?.len() == 0;
+    let mut writer = BoardWriter::new(file, args.compress).map_err(|e| board_file_error(args, thread, shard_index, e))?;
+    if is_new {
+        writer.write_all(&args.board_header()).map_err(|e| board_file_error(args, thread, shard_index, e))?;
+    }
+    Ok(writer)
+}
+
+/// Opens one of the files written next to a thread's board file (named like it, with another extension) if it isn't already open
+/// # Arguments
+/// * `writer` - The writer of the file, if it's already open
+/// * `args` - The command-line arguments
+/// * `thread` - Index of the thread
+/// * `shard_index` - Index of the file among those written by the thread
+/// * `extension` - Extension of the file
+/// * `append` - Whether to append to the file if it already exists rather than replacing it
+/// * `header` - Bytes the file starts with, written if it's new
+/// # Returns
+/// * `Result` with the writer, or an error message if the file couldn't be created
+fn open_alongside<'w>(writer: &'w mut Option<BufWriter<File>>, args: &GenerateArgs, thread: usize, shard_index: usize, extension: &str, append: bool, header: &[u8]) -> Result<&'w mut BufWriter<File>, String> {
+    if writer.is_none() {
+        let path = args.out.join(format!("{}.{}", args.file_stem(thread, shard_index), extension));
+        let file = open_output(&path, append).map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+        let is_new = file.metadata().map_err(|e| format!("Failed to create {}: {}", path.display(), e))?.len() == 0;
+        let mut new_writer = BufWriter::new(file);
+        if is_new {
+            new_writer.write_all(header).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        }
+        *writer = Some(new_writer);
+    }
+    Ok(writer.as_mut().expect("File not opened!"))
+}
+
+/// Describes an error writing a thread's board file
+fn board_file_error(args: &GenerateArgs, thread: usize, shard_index: usize, e: io::Error) -> String {
+    format!("Failed to write {}: {}", args.out.join(args.board_file_name(thread, shard_index)).display(), e)
+}
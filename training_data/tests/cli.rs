@@ -0,0 +1,654 @@
+//! Smoke tests of the `training_data` binary, each running it on a small number of boards in a temporary directory
+use std::{fs, hash::{DefaultHasher, Hash, Hasher}, io::Read, path::{Path, PathBuf}, process::{Command, Output, Stdio}, thread, time::Duration};
+use bananagrams_core::{board_to_bytes, board_to_string, canonical_form, extract_words, parse_bgb, parse_dictionary, parse_labeled_bgb, read_bgb2, transform_board, validate_board, Board, Corruption, Direction, PlayedWord, Symmetry, Word, BGB_FORMAT_VERSION, BOARD_SIZE, DEFAULT_MIN_WORD_LENGTH, EMPTY_VALUE, ENGLISH_ALPHABET_SIZE, ONEHOT_CHANNELS};
+use hashbrown::HashSet;
+
+/// Number of boards that can be waiting for the writer thread (`writer::CHANNEL_CAPACITY`)
+const CHANNEL_CAPACITY: usize = 256;
+/// Most bytes a pipe holds before writing to it blocks (on Linux and macOS, unless it's been resized)
+const PIPE_CAPACITY: usize = 1 << 16;
+
+/// Makes an empty temporary directory for a test
+/// # Arguments
+/// * `name` - Name of the test, so that tests running at the same time don't share a directory
+/// # Returns
+/// * `PathBuf` - Path to the directory
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("training_data_cli_{}_{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).expect("Failed to create the temporary directory!");
+    dir
+}
+
+/// Runs the binary, checking that it succeeded
+/// # Arguments
+/// * `args` - Arguments to run it with
+/// # Returns
+/// * `Output` - What it wrote to standard output and standard error
+fn run(args: &[&str]) -> Output {
+    let output = Command::new(env!("CARGO_BIN_EXE_training_data")).args(args).output().expect("Failed to run training_data!");
+    assert!(output.status.success(), "training_data {:?} failed: {}", args, String::from_utf8_lossy(&output.stderr));
+    output
+}
+
+/// Reads the board files a run wrote, in order of their names
+/// # Arguments
+/// * `dir` - Directory the run wrote to
+/// * `extension` - Extension of the board files
+/// # Returns
+/// * `Vec<(String, Vec<u8>)>` - The name and contents of each file
+fn board_files(dir: &Path, extension: &str) -> Vec<(String, Vec<u8>)> {
+    let mut files: Vec<(String, Vec<u8>)> = fs::read_dir(dir).expect("Failed to read the output directory!").map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == extension))
+        .map(|path| (path.file_name().unwrap().to_string_lossy().into_owned(), fs::read(&path).unwrap())).collect();
+    files.sort();
+    files
+}
+
+#[test]
+fn seeded_runs_write_identical_boards() {
+    let dir = temp_dir("seeded");
+    let outputs: Vec<Vec<(String, Vec<u8>)>> = ["a", "b"].iter().map(|name| {
+        let out = dir.join(name);
+        run(&["--boards", "6", "--threads", "2", "--seed", "42", "--max-hand-size", "15", "--quiet", "--out", out.to_str().unwrap()]);
+        board_files(&out, "bgb")
+    }).collect();
+    assert!(!outputs[0].is_empty());
+    assert_eq!(outputs[0], outputs[1]);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn plays_files_reconstruct_each_board() {
+    let dir = temp_dir("plays");
+    run(&["--boards", "4", "--threads", "1", "--seed", "7", "--max-hand-size", "15", "--plays", "--quiet", "--out", dir.to_str().unwrap()]);
+    let boards = board_files(&dir, "bgb");
+    let plays = board_files(&dir, "jsonl");
+    assert_eq!(boards.len(), 1);
+    assert_eq!(plays.len(), 1);
+    let boards = parse_bgb::<ENGLISH_ALPHABET_SIZE>(&boards[0].1).unwrap();
+    let plays: Vec<Vec<PlayedWord>> = String::from_utf8(plays[0].1.clone()).unwrap().lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    assert_eq!(boards.len(), 4);
+    assert_eq!(plays.len(), boards.len());
+    for ((_, board, ..), plays) in boards.iter().zip(plays.iter()) {
+        let mut replayed = Board::new(BOARD_SIZE);
+        for play in plays {
+            for (i, &letter) in play.word.iter().enumerate() {
+                match play.direction {
+                    Direction::Horizontal => replayed.set_val(play.row, play.col + i, letter),
+                    Direction::Vertical => replayed.set_val(play.row + i, play.col, letter)
+                }
+            }
+        }
+        assert_eq!(&replayed, board);
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn negatives_are_labeled_invalid_boards() {
+    let dir = temp_dir("negatives");
+    run(&["--boards", "4", "--threads", "1", "--seed", "29", "--max-hand-size", "15", "--negatives-per-board", "3", "--quiet", "--out", dir.to_str().unwrap()]);
+    let files = board_files(&dir, "bgb");
+    let (_, negatives) = files.iter().find(|(name, _)| name.ends_with(".negatives.bgb")).expect("No negatives file was written");
+    let negatives = parse_labeled_bgb(negatives).unwrap();
+    assert_eq!(negatives.len(), 4 * 3);
+    let valid_words: HashSet<Word> = parse_dictionary(include_str!("../../new_short_dictionary.txt"), false).unwrap().into_iter().collect();
+    for (label, (_, board, ..)) in negatives.iter() {
+        assert!(Corruption::from_label(*label).is_some());
+        assert!(validate_board(board, &valid_words, DEFAULT_MIN_WORD_LENGTH).is_err());
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn csv_rows_rebuild_the_boards_of_the_same_seed() {
+    let dir = temp_dir("csv");
+    let run_format = |format: &str| {
+        let out = dir.join(format);
+        run(&["--boards", "5", "--threads", "1", "--seed", "31", "--max-hand-size", "15", "--format", format, "--quiet", "--out", out.to_str().unwrap()]);
+        let mut files = board_files(&out, format);
+        assert_eq!(files.len(), 1);
+        files.remove(0).1
+    };
+    let boards = parse_bgb::<ENGLISH_ALPHABET_SIZE>(&run_format("bgb")).unwrap();
+    let csv = String::from_utf8(run_format("csv")).unwrap();
+    let mut lines = csv.lines();
+    assert_eq!(lines.next(), Some("board_id,row,col,letter"));
+    let mut csv_boards: Vec<Board> = Vec::new();
+    for line in lines {
+        let fields: Vec<&str> = line.split(',').collect();
+        let (board_id, row, col): (usize, usize, usize) = (fields[0].parse().unwrap(), fields[1].parse().unwrap(), fields[2].parse().unwrap());
+        // Board IDs count up from 0 in the order the boards are written
+        if board_id == csv_boards.len() {
+            csv_boards.push(Board::new(BOARD_SIZE));
+        }
+        let letter = fields[3].as_bytes()[0];
+        csv_boards[board_id].set_val(row, col, letter.to_ascii_uppercase() - b'A');
+        if letter.is_ascii_lowercase() {
+            csv_boards[board_id].set_blank(row, col);
+        }
+    }
+    assert_eq!(csv_boards.len(), boards.len());
+    for (csv_board, (_, board, ..)) in csv_boards.iter().zip(boards.iter()) {
+        assert_eq!(csv_board, board);
+        // Coordinates start from 0 on each board
+        assert_eq!(csv_board.bounds().map(|(min_col, _, min_row, _)| (min_col, min_row)), Some((0, 0)));
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn bgb_files_upgrade_to_bgb2() {
+    let dir = temp_dir("upgrade");
+    run(&["--boards", "5", "--threads", "1", "--seed", "32", "--max-hand-size", "15", "--quiet", "--out", dir.to_str().unwrap()]);
+    let (name, bytes) = board_files(&dir, "bgb").remove(0);
+    let new = dir.join("upgraded.bgb2");
+    let output = run(&["bgb", "upgrade", dir.join(name).to_str().unwrap(), new.to_str().unwrap()]);
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "Converted 5 boards");
+    let boards = parse_bgb::<ENGLISH_ALPHABET_SIZE>(&bytes).unwrap();
+    let upgraded = read_bgb2(&fs::read(&new).unwrap()).unwrap();
+    assert_eq!(upgraded.len(), boards.len());
+    for ((letters, board, ..), (upgraded_letters, upgraded_board, ..)) in boards.iter().zip(upgraded.iter()) {
+        assert_eq!(letters, upgraded_letters);
+        assert_eq!(board, upgraded_board);
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+#[cfg(feature = "sqlite")]
+fn sqlite_databases_can_be_queried_for_boards() {
+    let dir = temp_dir("sqlite");
+    let bgb = dir.join("bgb");
+    run(&["--boards", "20", "--threads", "1", "--seed", "35", "--max-hand-size", "15", "--quiet", "--out", bgb.to_str().unwrap()]);
+    let boards = parse_bgb::<ENGLISH_ALPHABET_SIZE>(&board_files(&bgb, "bgb").remove(0).1).unwrap();
+    let out = dir.join("sqlite");
+    run(&["--boards", "20", "--threads", "1", "--seed", "35", "--max-hand-size", "15", "--format", "sqlite", "--quiet", "--out", out.to_str().unwrap()]);
+    let connection = rusqlite::Connection::open(out.join("boards.sqlite")).unwrap();
+    let count: i64 = connection.query_row("SELECT COUNT(*) FROM boards", [], |row| row.get(0)).unwrap();
+    assert_eq!(count, 20);
+    assert_eq!(boards.len(), 20);
+    let mut statement = connection.prepare("SELECT id, width, height, num_tiles, tiles, hand FROM boards ORDER BY id").unwrap();
+    // ID, width, height, number of tiles, tiles and hand
+    type BoardRow = (i64, usize, usize, usize, Vec<u8>, Vec<u8>);
+    let rows: Vec<BoardRow> = statement.query_map([], |row| {
+        Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?, row.get(5)?))
+    }).unwrap().map(Result::unwrap).collect();
+    let count_tiles = |board: &Board| board.bounds().map_or(0, |(min_col, max_col, min_row, max_row)| {
+        (min_row..=max_row).map(|row| (min_col..=max_col).filter(|&col| (board.get_val(row, col) as usize) < ENGLISH_ALPHABET_SIZE).count()).sum::<usize>()
+    });
+    let mut ids: Vec<i64> = Vec::new();
+    for ((id, width, height, num_tiles, tiles, hand), (letters, board, min_col, max_col, min_row, max_row)) in rows.into_iter().zip(boards.iter()) {
+        assert_eq!(hand, letters.to_vec());
+        assert_eq!((width, height), (max_col + 1 - min_col, max_row + 1 - min_row));
+        assert_eq!(tiles.len(), width * height);
+        assert_eq!(num_tiles, tiles.iter().filter(|&&tile| tile != 0).count());
+        assert_eq!(num_tiles, count_tiles(board));
+        // Each word is where its letters are in the tiles, counting from the top-left corner of the board
+        let mut words = connection.prepare("SELECT word, row, col, direction FROM words WHERE board_id = ?1").unwrap();
+        let words: Vec<(String, usize, usize, String)> = words.query_map([id], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))
+            .unwrap().map(Result::unwrap).collect();
+        assert!(!words.is_empty());
+        for (word, row, col, direction) in words.iter() {
+            for (i, letter) in word.bytes().enumerate() {
+                let (row, col) = if direction == "Horizontal" { (*row, col + i) } else { (row + i, *col) };
+                assert_eq!(board.get_val(min_row + row, min_col + col), letter.to_ascii_uppercase() - b'A');
+                assert_eq!(tiles[row * width + col] & 0x7f, letter.to_ascii_uppercase() - b'A' + 1);
+            }
+        }
+        ids.push(id);
+    }
+    // Boards with as many tiles as the first one and a word containing the first letter of its first word, by SQL and by the boards
+    let (_, first, ..) = &boards[0];
+    let first_letter: String = connection.query_row("SELECT substr(word, 1, 1) FROM words WHERE board_id = ?1 LIMIT 1", [ids[0]], |row| row.get(0)).unwrap();
+    let num_tiles = count_tiles(first);
+    let mut query = connection.prepare("SELECT id FROM boards WHERE num_tiles = ?1 AND id IN (SELECT board_id FROM words WHERE word LIKE ?2) ORDER BY id").unwrap();
+    let found: Vec<i64> = query.query_map(rusqlite::params![num_tiles as i64, format!("%{}%", first_letter)], |row| row.get(0)).unwrap().map(Result::unwrap).collect();
+    let letter = first_letter.as_bytes()[0].to_ascii_uppercase() - b'A';
+    let expected: Vec<i64> = ids.iter().zip(boards.iter()).filter(|(_, (_, board, min_col, max_col, min_row, max_row))| {
+        count_tiles(board) == num_tiles && (*min_row..=*max_row).any(|row| (*min_col..=*max_col).any(|col| board.get_val(row, col) == letter))
+    }).map(|(id, _)| *id).collect();
+    assert!(found.contains(&ids[0]));
+    assert_eq!(found, expected);
+    // Boards from several threads all go through the one connection
+    let threaded = dir.join("threaded");
+    run(&["--boards", "20", "--threads", "4", "--seed", "35", "--max-hand-size", "15", "--format", "sqlite", "--quiet", "--out", threaded.to_str().unwrap()]);
+    let connection = rusqlite::Connection::open(threaded.join("boards.sqlite")).unwrap();
+    let counts: (i64, i64) = connection.query_row("SELECT COUNT(*), COUNT(DISTINCT board_id) FROM boards JOIN words ON words.board_id = boards.id", [], |row| Ok((row.get(0)?, row.get(1)?))).unwrap();
+    assert!(counts.0 >= 20);
+    assert_eq!(counts.1, 20);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn resumed_runs_finish_cut_off_files_with_exactly_the_target() {
+    let dir = temp_dir("resume");
+    let out = dir.to_str().unwrap();
+    run(&["--boards", "8", "--threads", "2", "--seed", "36", "--max-hand-size", "15", "--plays", "--quiet", "--out", out]);
+    let original: Vec<Vec<u8>> = board_files(&dir, "bgb").into_iter().map(|(_, bytes)| bytes).collect();
+    assert_eq!(original.len(), 2);
+    // The first thread's last board is cut off partway through, and the second's last line of plays is
+    let board_file = dir.join("0_board5.bgb");
+    fs::write(&board_file, &original[0][..original[0].len() - 4]).unwrap();
+    let plays_file = dir.join("1_board5.plays.jsonl");
+    let plays = fs::read(&plays_file).unwrap();
+    fs::write(&plays_file, &plays[..plays.len() - 5]).unwrap();
+    let output = run(&["--boards", "12", "--threads", "2", "--seed", "36", "--max-hand-size", "15", "--plays", "--resume", "--quiet", "--out", out]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Resuming with 6 boards already generated"));
+    let mut total = 0;
+    for (thread, (_, bytes)) in board_files(&dir, "bgb").iter().enumerate() {
+        let boards = parse_bgb::<ENGLISH_ALPHABET_SIZE>(bytes).unwrap();
+        let original = parse_bgb::<ENGLISH_ALPHABET_SIZE>(&original[thread]).unwrap();
+        // The complete boards are kept, and the ones after them are from hands that weren't solved before
+        for (board, original_board) in boards.iter().zip(original.iter()).take(3) {
+            assert_eq!(board.0, original_board.0);
+            assert!(board.1.identical(&original_board.1));
+        }
+        assert!(boards[3..].iter().all(|board| original.iter().all(|original_board| board.0 != original_board.0)));
+        let plays = fs::read_to_string(dir.join(format!("{}_board5.plays.jsonl", thread))).unwrap();
+        assert_eq!(plays.lines().count(), boards.len());
+        total += boards.len();
+    }
+    assert_eq!(total, 12);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn hands_only_contain_letters_of_the_tile_set() {
+    let dir = temp_dir("tiles");
+    let tiles = dir.join("vowels.txt");
+    fs::write(&tiles, "A 20\nE 20\nI 12\nO 12\nU 6\nT 10\nS 10\nR 8\nN 8\nL 6\nD 4\n").unwrap();
+    let out = dir.join("out");
+    run(&["--boards", "6", "--threads", "1", "--seed", "42", "--max-hand-size", "15", "--tiles", tiles.to_str().unwrap(), "--quiet", "--out", out.to_str().unwrap()]);
+    let boards = parse_bgb::<ENGLISH_ALPHABET_SIZE>(&board_files(&out, "bgb").remove(0).1).unwrap();
+    assert_eq!(boards.len(), 6);
+    let pool: HashSet<usize> = "AEIOUTSRNLD".bytes().map(|letter| (letter - b'A') as usize).collect();
+    for (letters, ..) in boards.iter() {
+        assert!(letters.iter().enumerate().all(|(letter, &count)| count == 0 || pool.contains(&letter)), "{:?} isn't drawn from the tile set", letters);
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn blanks_are_played_on_every_board() {
+    let dir = temp_dir("blanks");
+    run(&["--boards", "4", "--threads", "1", "--seed", "44", "--max-hand-size", "15", "--blanks", "2", "--quiet", "--out", dir.to_str().unwrap()]);
+    let boards = parse_bgb::<ENGLISH_ALPHABET_SIZE>(&board_files(&dir, "bgb").remove(0).1).unwrap();
+    assert_eq!(boards.len(), 4);
+    for (letters, board, min_col, max_col, min_row, max_row) in boards.iter() {
+        // The hand counts only its letters, and the two blanks are marked on the board
+        let tiles = (*min_row..=*max_row).flat_map(|row| (*min_col..=*max_col).map(move |col| (row, col)))
+            .filter(|&(row, col)| (board.get_val(row, col) as usize) < ENGLISH_ALPHABET_SIZE).count();
+        assert_eq!(tiles, letters.iter().map(|&count| count as usize).sum::<usize>() + 2);
+        assert_eq!(board.blanks().len(), 2);
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn boards_only_have_words_of_the_minimum_length() {
+    let dir = temp_dir("min_word_length");
+    run(&["--boards", "4", "--threads", "1", "--seed", "45", "--max-hand-size", "15", "--min-word-length", "3", "--quiet", "--out", dir.to_str().unwrap()]);
+    let boards = parse_bgb::<ENGLISH_ALPHABET_SIZE>(&board_files(&dir, "bgb").remove(0).1).unwrap();
+    assert_eq!(boards.len(), 4);
+    let valid_words: HashSet<Word> = parse_dictionary(include_str!("../../new_short_dictionary.txt"), false).unwrap().into_iter().collect();
+    for (_, board, ..) in boards.iter() {
+        assert_eq!(validate_board(board, &valid_words, 3), Ok(()));
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn banned_words_are_kept_off_every_board() {
+    let dir = temp_dir("banned_words");
+    let unbanned = dir.join("unbanned");
+    run(&["--boards", "5", "--threads", "1", "--seed", "48", "--max-hand-size", "15", "--quiet", "--out", unbanned.to_str().unwrap()]);
+    let board_words = |bytes: &[u8]| -> Vec<Vec<String>> {
+        parse_bgb::<ENGLISH_ALPHABET_SIZE>(bytes).unwrap().iter()
+            .map(|(_, board, min_col, max_col, min_row, max_row)| extract_words(board, *min_col, *max_col, *min_row, *max_row).into_iter().map(|(word, ..)| word).collect()).collect()
+    };
+    // Every word of the first board of the same seed is banned, given in lowercase
+    let banned: Vec<String> = board_words(&board_files(&unbanned, "bgb").remove(0).1).remove(0);
+    let banned_file = dir.join("banned.txt");
+    fs::write(&banned_file, banned.iter().map(|word| word.to_lowercase() + "\n").collect::<String>()).unwrap();
+    let out = dir.join("banned");
+    run(&["--boards", "5", "--threads", "1", "--seed", "48", "--max-hand-size", "15", "--banned-words", banned_file.to_str().unwrap(), "--quiet", "--out", out.to_str().unwrap()]);
+    let boards = board_words(&board_files(&out, "bgb").remove(0).1);
+    assert_eq!(boards.len(), 5);
+    for words in boards.iter() {
+        assert!(words.iter().all(|word| !banned.contains(word)), "{:?} has a banned word of {:?}", words, banned);
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn partial_boards_list_the_letters_left_over() {
+    let dir = temp_dir("partial");
+    // So small a budget leaves some hands unsolved
+    run(&["--boards", "6", "--threads", "1", "--seed", "50", "--max-hand-size", "15", "--max-words-checked", "30", "--allow-partial", "--format", "jsonl", "--quiet", "--out", dir.to_str().unwrap()]);
+    let valid_words: HashSet<Word> = parse_dictionary(include_str!("../../new_short_dictionary.txt"), false).unwrap().into_iter().collect();
+    let files = board_files(&dir, "jsonl");
+    assert_eq!(files.len(), 2);
+    let mut partial_boards = 0;
+    for (name, bytes) in files.iter() {
+        let partial = name.ends_with(".partial.jsonl");
+        for line in String::from_utf8_lossy(bytes).lines() {
+            let record: serde_json::Value = serde_json::from_str(line).unwrap();
+            assert_eq!(record["partial"], partial);
+            let unused = record["unused"].as_str().unwrap();
+            assert_eq!(unused.is_empty(), !partial);
+            let rows: Vec<&str> = record["board"].as_array().unwrap().iter().map(|row| row.as_str().unwrap()).collect();
+            let board: Board = rows.join("\n").parse().unwrap();
+            assert_eq!(validate_board(&board, &valid_words, DEFAULT_MIN_WORD_LENGTH), Ok(()));
+            // The letters on the board and those left over make up the hand
+            let mut counts = [0u64; ENGLISH_ALPHABET_SIZE];
+            for letter in rows.concat().bytes().chain(unused.bytes()).filter(u8::is_ascii_alphabetic) {
+                counts[(letter.to_ascii_uppercase() - b'A') as usize] += 1;
+            }
+            let hand: Vec<u64> = record["hand"].as_array().unwrap().iter().map(|count| count.as_u64().unwrap()).collect();
+            assert_eq!(counts.to_vec(), hand);
+            partial_boards += usize::from(partial);
+        }
+    }
+    assert!(partial_boards > 0);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn hands_are_solved_into_several_distinct_boards() {
+    let dir = temp_dir("solutions_per_hand");
+    run(&["--boards", "6", "--threads", "1", "--seed", "52", "--max-hand-size", "15", "--solutions-per-hand", "3", "--quiet", "--out", dir.to_str().unwrap()]);
+    let boards = parse_bgb::<ENGLISH_ALPHABET_SIZE>(&board_files(&dir, "bgb").remove(0).1).unwrap();
+    assert_eq!(boards.len(), 6);
+    let hands: HashSet<_> = boards.iter().map(|(letters, ..)| *letters).collect();
+    assert!(hands.len() < boards.len());
+    // No two boards of a hand are the same up to symmetry
+    let forms: HashSet<_> = boards.iter().map(|(letters, board, min_col, max_col, min_row, max_row)| (*letters, canonical_form(board, *min_col, *max_col, *min_row, *max_row))).collect();
+    assert_eq!(forms.len(), boards.len());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn shuffled_word_orders_repeat_with_the_seed() {
+    let dir = temp_dir("word_order");
+    for word_order in ["shuffled-within-length", "random"] {
+        let outputs: Vec<Vec<(String, Vec<u8>)>> = ["a", "b"].iter().map(|name| {
+            let out = dir.join(format!("{}_{}", word_order, name));
+            run(&["--boards", "4", "--threads", "1", "--seed", "53", "--max-hand-size", "15", "--word-order", word_order, "--quiet", "--out", out.to_str().unwrap()]);
+            board_files(&out, "bgb")
+        }).collect();
+        assert_eq!(outputs[0], outputs[1]);
+        let valid_words: HashSet<Word> = parse_dictionary(include_str!("../../new_short_dictionary.txt"), false).unwrap().into_iter().collect();
+        let boards = parse_bgb::<ENGLISH_ALPHABET_SIZE>(&outputs[0][0].1).unwrap();
+        assert_eq!(boards.len(), 4);
+        assert!(boards.iter().all(|(_, board, ..)| validate_board(board, &valid_words, DEFAULT_MIN_WORD_LENGTH).is_ok()));
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn hands_are_played_onto_the_complete_from_board() {
+    let dir = temp_dir("complete_from");
+    let banana = dir.join("banana.txt");
+    fs::write(&banana, "BANANA\n").unwrap();
+    let out = dir.join("out");
+    run(&["--boards", "4", "--threads", "1", "--seed", "69", "--max-hand-size", "15", "--complete-from", banana.to_str().unwrap(), "--quiet", "--out", out.to_str().unwrap()]);
+    let boards = parse_bgb::<ENGLISH_ALPHABET_SIZE>(&board_files(&out, "bgb").remove(0).1).unwrap();
+    assert_eq!(boards.len(), 4);
+    let valid_words: HashSet<Word> = parse_dictionary(include_str!("../../new_short_dictionary.txt"), false).unwrap().into_iter().collect();
+    for (letters, board, min_col, max_col, min_row, max_row) in boards.iter() {
+        assert_eq!(validate_board(board, &valid_words, DEFAULT_MIN_WORD_LENGTH), Ok(()));
+        assert!(extract_words(board, *min_col, *max_col, *min_row, *max_row).iter().any(|(word, _, _, direction)| word == "BANANA" && *direction == Direction::Horizontal));
+        // The hand is only the letters played onto BANANA
+        let tiles = (*min_row..=*max_row).flat_map(|row| (*min_col..=*max_col).map(move |col| (row, col)))
+            .filter(|&(row, col)| (board.get_val(row, col) as usize) < ENGLISH_ALPHABET_SIZE).count();
+        assert_eq!(tiles, letters.iter().map(|&count| count as usize).sum::<usize>() + 6);
+    }
+    // A board that isn't legal is refused before any hand is drawn
+    fs::write(&banana, "BANANX\n").unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_training_data")).args(["--boards", "1", "--complete-from", banana.to_str().unwrap(), "--quiet", "--out", out.to_str().unwrap()]).output().unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("isn't legal"));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn every_board_contains_the_anchor_word() {
+    let dir = temp_dir("anchor_word");
+    run(&["--boards", "5", "--threads", "1", "--seed", "70", "--max-hand-size", "15", "--anchor-word", "TRAIN", "--quiet", "--out", dir.to_str().unwrap()]);
+    let boards = parse_bgb::<ENGLISH_ALPHABET_SIZE>(&board_files(&dir, "bgb").remove(0).1).unwrap();
+    assert_eq!(boards.len(), 5);
+    for (_, board, min_col, max_col, min_row, max_row) in boards.iter() {
+        let words = extract_words(board, *min_col, *max_col, *min_row, *max_row);
+        assert!(words.iter().any(|(word, _, _, direction)| word == "TRAIN" && *direction == Direction::Horizontal), "{:?}", words);
+    }
+    // A word longer than any hand could never be made, so it's refused rather than redrawing hands forever
+    let output = Command::new(env!("CARGO_BIN_EXE_training_data")).args(["--boards", "1", "--max-hand-size", "15", "--anchor-word", "UNSATISFACTORILY", "--quiet", "--out", dir.to_str().unwrap()]).output().unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("can't be made"));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn realistic_pools_deal_the_whole_tile_set_across_hands() {
+    let dir = temp_dir("realistic_pool");
+    // A tile set of 44 tiles, so that four hands of 11 drawn one after another from the pool use up every tile in it
+    let tiles = dir.join("small.txt");
+    let tile_counts = [('A', 6), ('E', 6), ('I', 4), ('O', 4), ('T', 5), ('S', 5), ('R', 4), ('N', 4), ('L', 3), ('D', 3)];
+    fs::write(&tiles, tile_counts.iter().map(|(letter, count)| format!("{} {}\n", letter, count)).collect::<String>()).unwrap();
+    let out = dir.join("out");
+    let output = run(&["--boards", "4", "--threads", "1", "--seed", "73", "--min-hand-size", "11", "--max-hand-size", "11", "--tiles", tiles.to_str().unwrap(), "--realistic-pool", "--out", out.to_str().unwrap()]);
+    // Every hand drawn was solved, so none came from a refilled pool
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Solved 4 of 4 hands"), "{}", String::from_utf8_lossy(&output.stdout));
+    let boards = parse_bgb::<ENGLISH_ALPHABET_SIZE>(&board_files(&out, "bgb").remove(0).1).unwrap();
+    assert_eq!(boards.len(), 4);
+    let mut dealt = [0usize; ENGLISH_ALPHABET_SIZE];
+    for (letters, ..) in boards.iter() {
+        dealt.iter_mut().zip(letters.iter()).for_each(|(total, &count)| *total += count as usize);
+    }
+    // Between them the hands hold exactly the tile set, where hands drawn independently would have too many of some letters
+    for (letter, count) in tile_counts {
+        assert_eq!(dealt[(letter as u8 - b'A') as usize], count, "{} {}s were dealt from a tile set of {}", dealt[(letter as u8 - b'A') as usize], letter, count);
+    }
+    assert_eq!(dealt.iter().sum::<usize>(), 44);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn four_threads_with_a_master_seed_write_identical_files_per_thread() {
+    let dir = temp_dir("master_seed");
+    // The hash of every file a run wrote (the boards and the plays of each thread), by name
+    let hashes = |name: &str, seed: &str| -> Vec<(String, u64)> {
+        let out = dir.join(name);
+        run(&["--boards", "8", "--threads", "4", "--seed", seed, "--max-hand-size", "15", "--plays", "--quiet", "--out", out.to_str().unwrap()]);
+        let mut files: Vec<(String, Vec<u8>)> = board_files(&out, "bgb");
+        files.extend(board_files(&out, "jsonl"));
+        files.into_iter().map(|(name, contents)| {
+            let mut hasher = DefaultHasher::new();
+            contents.hash(&mut hasher);
+            (name, hasher.finish())
+        }).collect()
+    };
+    let first = hashes("a", "7");
+    // A board file and a plays file for each thread
+    assert_eq!(first.len(), 8, "{:?}", first);
+    assert_eq!(first, hashes("b", "7"));
+    // Each thread's seed is derived from the master seed, so another master seed changes every thread's boards
+    let other = hashes("c", "8");
+    assert!(first.iter().zip(other.iter()).all(|((name, hash), (other_name, other_hash))| name == other_name && hash != other_hash));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn boards_are_printed_plainly_when_not_on_a_terminal() {
+    let dir = temp_dir("plain_boards");
+    let dictionary = dir.join("dictionary.txt");
+    fs::write(&dictionary, "CAT\nTOMB\n").unwrap();
+    let solved = String::from_utf8(run(&["solve", "--letters", "CATOMB", "--no-pool-check", "--dictionary", dictionary.to_str().unwrap()]).stdout).unwrap();
+    assert!(solved.starts_with("C   \nA   \nTOMB\n"), "{}", solved);
+    let boards = dir.join("boards.txt");
+    fs::write(&boards, "CAT\n  O\n  X\n  B\n").unwrap();
+    let validated = Command::new(env!("CARGO_BIN_EXE_training_data")).args(["validate", "--input", boards.to_str().unwrap(), "--dictionary", dictionary.to_str().unwrap()]).output().unwrap();
+    assert_eq!(validated.status.code(), Some(1));
+    let validated = String::from_utf8(validated.stdout).unwrap();
+    assert!(validated.contains("CAT\n  O\n  X\n  B"), "{}", validated);
+    // Neither the grid nor its colors, which are only for a terminal
+    for output in [solved, validated] {
+        assert!(!output.contains('│') && !output.contains('\x1b'), "{}", output);
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn onehot_boards_argmax_to_the_same_seeds_bgb_boards() {
+    let dir = temp_dir("onehot");
+    let (bgb_dir, npy_dir) = (dir.join("bgb"), dir.join("npy"));
+    let (height, width) = (16, 16);
+    run(&["--boards", "6", "--threads", "1", "--seed", "87", "--max-hand-size", "15", "--quiet", "--out", bgb_dir.to_str().unwrap()]);
+    run(&["--boards", "6", "--threads", "1", "--seed", "87", "--max-hand-size", "15", "--quiet", "--format", "npy-onehot", "--pad-to", "16x16", "--out", npy_dir.to_str().unwrap()]);
+    let boards = parse_bgb::<ENGLISH_ALPHABET_SIZE>(&board_files(&bgb_dir, "bgb")[0].1).unwrap();
+    let npy = board_files(&npy_dir, "npy");
+    assert_eq!(npy.len(), 1);
+    // After the 128-byte header, each board is (27, 16, 16)
+    let grids: Vec<&[u8]> = npy[0].1[128..].chunks(ONEHOT_CHANNELS * height * width).collect();
+    assert_eq!(grids.len(), boards.len());
+    for ((_, board, min_col, max_col, min_row, max_row), grid) in boards.iter().zip(grids) {
+        let (top, left) = ((height - (max_row + 1 - min_row)) / 2, (width - (max_col + 1 - min_col)) / 2);
+        for row in 0..height {
+            for col in 0..width {
+                let channel = (0..ONEHOT_CHANNELS).max_by_key(|&channel| grid[(channel * height + row) * width + col]).unwrap();
+                let (board_row, board_col) = ((row + min_row).wrapping_sub(top), (col + min_col).wrapping_sub(left));
+                let expected = if (*min_row..=*max_row).contains(&board_row) && (*min_col..=*max_col).contains(&board_col) && board.get_val(board_row, board_col) != EMPTY_VALUE {
+                    board.get_val(board_row, board_col) as usize + 1
+                }
+                else {
+                    0
+                };
+                assert_eq!(channel, expected, "Cell ({}, {})", row, col);
+            }
+        }
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn a_board_and_its_transpose_are_always_split_into_the_same_set() {
+    let dir = temp_dir("split");
+    let data = dir.join("data");
+    // --augment writes every rotation and reflection of each board, so each board's transpose is in the dataset too
+    run(&["--boards", "10", "--threads", "1", "--seed", "89", "--max-hand-size", "15", "--augment", "--quiet", "--out", data.to_str().unwrap()]);
+    let original = board_files(&data, "bgb");
+    let original_boards = parse_bgb::<ENGLISH_ALPHABET_SIZE>(&original[0].1).unwrap();
+    assert_eq!(original_boards.len(), 80);
+    for seed in ["1", "2", "3"] {
+        let out = dir.join(format!("split_{}", seed));
+        let output = run(&["split", data.to_str().unwrap(), "--out", out.to_str().unwrap(), "--ratios", "0.4,0.3,0.3", "--seed", seed]);
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        // Each board of each set, as its text
+        let sets: Vec<HashSet<String>> = ["train", "validation", "test"].iter().map(|name| {
+            let files = board_files(&out.join(name), "bgb");
+            assert_eq!(files.len(), 1, "{}", name);
+            // The format version is kept
+            assert_eq!(files[0].1[0], original[0].1[0]);
+            let boards = parse_bgb::<ENGLISH_ALPHABET_SIZE>(&files[0].1).unwrap();
+            assert!(stdout.contains(&format!("{}: {} boards", name, boards.len())), "{}", stdout);
+            boards.iter().map(|(_, board, ..)| board_to_string(board)).collect()
+        }).collect();
+        assert_eq!(sets.iter().map(HashSet::len).sum::<usize>(), original_boards.iter().map(|(_, board, ..)| board_to_string(board)).collect::<HashSet<_>>().len());
+        for (_, board, min_col, max_col, min_row, max_row) in original_boards.iter() {
+            let (transpose, ..) = transform_board(board, *min_col, *max_col, *min_row, *max_row, Symmetry::Transpose);
+            let set = sets.iter().position(|set| set.contains(&board_to_string(board))).unwrap();
+            assert!(sets[set].contains(&board_to_string(&transpose)), "With seed {}, the transpose of\n{}\nisn't in the same set", seed, board_to_string(board));
+        }
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn merging_drops_duplicated_and_transposed_boards() {
+    let dir = temp_dir("merge");
+    let path = |name: &str| dir.join(name).to_str().unwrap().to_string();
+    // Every rotation and reflection of five boards; the same five boards again; and five more with their metrics (format version 2)
+    run(&["--boards", "5", "--threads", "1", "--seed", "90", "--max-hand-size", "15", "--augment", "--quiet", "--out", &path("augmented")]);
+    run(&["--boards", "5", "--threads", "1", "--seed", "90", "--max-hand-size", "15", "--quiet", "--out", &path("repeated")]);
+    run(&["--boards", "5", "--threads", "1", "--seed", "91", "--max-hand-size", "15", "--bgb-metrics", "--quiet", "--out", &path("metrics")]);
+    // And a version 1 file written by hand, of a board, its transpose, and a copy of it
+    let board: Board = "CAT\n  O\n  M\n  B".parse().unwrap();
+    let (min_col, max_col, min_row, max_row) = board.bounds().unwrap();
+    let (transpose, ..) = transform_board(&board, min_col, max_col, min_row, max_row, Symmetry::Transpose);
+    let mut letters = [0u8; ENGLISH_ALPHABET_SIZE];
+    for letter in "CATOMB".bytes() {
+        letters[(letter - b'A') as usize] += 1;
+    }
+    let mut by_hand = vec![BGB_FORMAT_VERSION];
+    for board in [&board, &transpose, &board] {
+        by_hand.extend(board_to_bytes(&letters, board).unwrap());
+    }
+    fs::write(dir.join("by_hand.bgb"), by_hand).unwrap();
+    let inputs: Vec<String> = [("augmented", "bgb"), ("repeated", "bgb"), ("metrics", "bgb")].iter()
+        .map(|(name, extension)| dir.join(name).join(&board_files(&dir.join(name), extension)[0].0).to_str().unwrap().to_string())
+        .chain([path("by_hand.bgb")]).collect();
+    let canonical_forms = |file: &str| -> Vec<Vec<u8>> {
+        parse_bgb::<ENGLISH_ALPHABET_SIZE>(&fs::read(file).unwrap()).unwrap().iter().map(|(_, board, min_col, max_col, min_row, max_row)| canonical_form(board, *min_col, *max_col, *min_row, *max_row)).collect()
+    };
+    let distinct: Vec<HashSet<Vec<u8>>> = inputs.iter().map(|input| canonical_forms(input).into_iter().collect()).collect();
+    assert_eq!(distinct[0], distinct[1]);
+    for exact in [false, true] {
+        let merged = path(if exact { "exact.bgb" } else { "merged.bgb" });
+        let mut args = vec!["bgb", "merge"];
+        args.extend(inputs.iter().map(String::as_str));
+        args.extend(["--out", &merged]);
+        if exact {
+            args.push("--exact");
+        }
+        run(&args);
+        // The newest format version, with each board once
+        let bytes = fs::read(&merged).unwrap();
+        assert_eq!(bytes[0], 2);
+        let forms = canonical_forms(&merged);
+        assert_eq!(forms.iter().collect::<HashSet<_>>().len(), forms.len());
+        assert_eq!(forms.into_iter().collect::<HashSet<_>>(), distinct.iter().flatten().cloned().collect::<HashSet<_>>());
+        let manifest: serde_json::Value = serde_json::from_str(&fs::read_to_string(merged.replace(".bgb", ".manifest.json")).unwrap()).unwrap();
+        let duplicates: Vec<u64> = manifest["inputs"].as_array().unwrap().iter().map(|input| input["duplicates"].as_u64().unwrap()).collect();
+        assert_eq!(duplicates, vec![40 - distinct[0].len() as u64, 5, 0, 2]);
+        assert!(manifest["inputs"].as_array().unwrap().iter().all(|input| input["collisions"] == 0));
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn generating_stalls_while_the_writer_is_blocked() {
+    // Standard output isn't read at first, so the writer thread blocks once the pipe is full, and the threads generating boards should
+    // then block on the channel to it rather than queueing boards for as long as the run goes on
+    let threads = 2;
+    let mut child = Command::new(env!("CARGO_BIN_EXE_training_data"))
+        .args(["--stream", "--stream-format", "jsonl", "--threads", &threads.to_string(), "--seed", "5", "--min-hand-size", "3", "--max-hand-size", "4", "--quiet"])
+        .stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::piped()).spawn().expect("Failed to run training_data!");
+    // Hands this small are solved thousands of times a second, so without the bound far more boards than fit in the pipe and the channel
+    // would be generated by now
+    thread::sleep(Duration::from_secs(2));
+    // Closing standard input ends the run, once everything generated has been written; it's given time to notice before the pipe is
+    // drained, so that no more boards are generated once the writer thread is unblocked
+    drop(child.stdin.take());
+    thread::sleep(Duration::from_millis(500));
+    let mut stdout = String::new();
+    child.stdout.take().unwrap().read_to_string(&mut stdout).unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let lines: Vec<&str> = stdout.lines().collect();
+    // Each board written before the pipe was full, then a board for each place in the channel, one being written by the writer thread,
+    // and one being sent by each thread generating boards
+    let fit_in_pipe = lines.iter().scan(0, |start, line| {
+        let line_start = *start;
+        *start += line.len() + 1;
+        Some(line_start)
+    }).take_while(|&start| start < PIPE_CAPACITY).count();
+    assert!(lines.len() <= fit_in_pipe + CHANNEL_CAPACITY + 1 + threads, "{} boards were generated with only {} fitting in the pipe", lines.len(), fit_in_pipe);
+    // None were lost either: every board the threads generated was written
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains(&format!("Generated {} boards in total", lines.len())), "{} boards written, but {}", lines.len(), stderr);
+    for line in lines {
+        serde_json::from_str::<serde_json::Value>(line).unwrap_or_else(|e| panic!("Invalid record {:?}: {}", line, e));
+    }
+}
@@ -0,0 +1,138 @@
+//! Tests of the `serve` subcommand, running the server on a free port and sending it requests over TCP
+#![cfg(feature = "serve")]
+use std::{fs, io::{BufRead, BufReader, Read, Write}, net::{Shutdown, TcpStream}, process::{Child, Command, Stdio}};
+
+/// The server, stopped when dropped so that a failed test doesn't leave it running
+struct Server {
+    /// The server's process
+    process: Child,
+    /// Address it's listening on, like `127.0.0.1:40123`
+    address: String
+}
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
+
+/// Starts the server on a free port, with a dictionary of CAT and TOMB
+/// # Arguments
+/// * `name` - Name of the test, so that tests running at the same time don't share a dictionary file
+/// # Returns
+/// * `Server` - The running server
+fn start(name: &str) -> Server {
+    let dictionary = std::env::temp_dir().join(format!("training_data_serve_{}_{}.txt", name, std::process::id()));
+    fs::write(&dictionary, "CAT\nTOMB\n").unwrap();
+    let mut process = Command::new(env!("CARGO_BIN_EXE_training_data"))
+        .args(["serve", "--bind", "127.0.0.1:0", "--threads", "2", "--max-timeout", "5", "--dictionary", dictionary.to_str().unwrap()])
+        .stdout(Stdio::piped()).spawn().expect("Failed to run training_data!");
+    // It prints the address once it's listening, with the port it was given
+    let mut line = String::new();
+    BufReader::new(process.stdout.as_mut().unwrap()).read_line(&mut line).unwrap();
+    let address = line.trim().strip_prefix("Listening on http://").unwrap_or_else(|| panic!("Unexpected output {:?}", line)).to_string();
+    Server { process, address }
+}
+
+/// Sends the server raw bytes as a request
+/// # Arguments
+/// * `server` - The server
+/// * `request` - The whole request; the connection is closed for writing after it, so a short body ends early
+/// # Returns
+/// * `(u16, serde_json::Value)` - The status code and body of the response
+fn send(server: &Server, request: &[u8]) -> (u16, serde_json::Value) {
+    let mut stream = TcpStream::connect(&server.address).unwrap();
+    stream.write_all(request).unwrap();
+    stream.shutdown(Shutdown::Write).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let (head, body) = response.split_once("\r\n\r\n").unwrap_or_else(|| panic!("Invalid response {:?}", response));
+    assert!(head.contains("Content-Type: application/json"), "{}", head);
+    assert!(head.contains(&format!("Content-Length: {}", body.len())), "{}", head);
+    let status = head.split_whitespace().nth(1).unwrap().parse().unwrap();
+    (status, serde_json::from_str(body).unwrap_or_else(|e| panic!("Invalid JSON {:?}: {}", body, e)))
+}
+
+/// Sends the server a request with a body
+/// # Arguments
+/// * `server` - The server
+/// * `method` - Method of the request, like `POST`
+/// * `target` - Path and query of the request
+/// * `body` - The body
+/// # Returns
+/// * `(u16, serde_json::Value)` - The status code and body of the response
+fn request(server: &Server, method: &str, target: &str, body: &str) -> (u16, serde_json::Value) {
+    send(server, format!("{} {} HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{}", method, target, body.len(), body).as_bytes())
+}
+
+#[test]
+fn solve_validate_and_generate_answer_with_json() {
+    let server = start("endpoints");
+    let (status, solved) = request(&server, "POST", "/solve", r#"{"letters": "CATOMB", "timeout_ms": 1000}"#);
+    assert_eq!(status, 200);
+    assert_eq!(solved["solved"], true);
+    assert_eq!(solved["hand"], "ABCMOT");
+    assert_eq!(solved["board"], serde_json::json!(["C   ", "A   ", "TOMB"]));
+    assert_eq!(solved["words"].as_array().unwrap().len(), 2);
+    // A hand that can't be solved says why
+    let (status, unsolved) = request(&server, "POST", "/solve", r#"{"letters": "A:3,E:2,Q:1"}"#);
+    assert_eq!(status, 200);
+    assert_eq!(unsolved["solved"], false);
+    assert!(unsolved["reason"].is_string());
+    assert_eq!(unsolved["board"], serde_json::json!([]));
+    let (status, legal) = request(&server, "POST", "/validate", r#"{"board": ["CAT", "..O", "..M", "..B"]}"#);
+    assert_eq!(status, 200);
+    assert_eq!(legal["valid"], true);
+    assert_eq!(legal["errors"], serde_json::json!([]));
+    let (status, illegal) = request(&server, "POST", "/validate", r#"{"board": ["CAT.", "..O.", "..X.", "..B.", "....", "...A"]}"#);
+    assert_eq!(status, 200);
+    assert_eq!(illegal["valid"], false);
+    assert_eq!(illegal["invalid_words"].as_array().unwrap().len(), 1);
+    assert_eq!(illegal["invalid_words"][0]["word"], "TOXB");
+    assert_eq!(illegal["components"].as_array().unwrap().len(), 2);
+    let (status, generated) = request(&server, "GET", "/generate?hand_size=11&seed=3&timeout_ms=500", "");
+    assert_eq!(status, 200);
+    assert_eq!(generated["hand"].as_str().unwrap().len(), 11);
+    // The same seed draws the same hand
+    assert_eq!(request(&server, "GET", "/generate?hand_size=11&seed=3&timeout_ms=500", "").1["hand"], generated["hand"]);
+    assert_eq!(request(&server, "GET", "/generate?seed=3&timeout_ms=500", "").1["hand"].as_str().unwrap().len(), 21);
+}
+
+#[test]
+fn malformed_requests_are_answered_with_errors() {
+    let server = start("malformed");
+    let error = |(status, body): (u16, serde_json::Value)| (status, body["error"].as_str().unwrap_or_else(|| panic!("No error in {}", body)).to_string());
+    for (request, expected_status, expected_error) in [
+        (&b"nonsense\r\n\r\n"[..], 400, "Invalid request line"),
+        (b"", 400, "Invalid request line"),
+        (b"POST /solve HTTP/1.1\r\nContent-Length: many\r\n\r\n", 400, "Invalid Content-Length"),
+        (b"POST /solve HTTP/1.1\r\nContent-Length: 2000000\r\n\r\n", 413, "The body can be at most"),
+        // The body is shorter than its Content-Length
+        (b"POST /solve HTTP/1.1\r\nContent-Length: 100\r\n\r\n{\"letters\"", 400, "Failed to read the request")
+    ] {
+        let (status, message) = error(send(&server, request));
+        assert_eq!(status, expected_status, "{:?}", String::from_utf8_lossy(request));
+        assert!(message.starts_with(expected_error), "{:?} gave {:?}", String::from_utf8_lossy(request), message);
+    }
+    for (method, target, body, expected_status, expected_error) in [
+        ("POST", "/solve", "not json", 400, "Invalid body"),
+        ("POST", "/solve", r#"{"timeout_ms": 10}"#, 400, "Invalid body"),
+        ("POST", "/solve", r#"{"letters": "CAT1"}"#, 400, "Invalid letter '1' in hand"),
+        ("POST", "/solve", r#"{"letters": ""}"#, 400, "The hand has no letters"),
+        ("POST", "/validate", r#"{"board": "CAT"}"#, 400, "Invalid body"),
+        ("POST", "/validate", r#"{"board": ["C#T"]}"#, 400, "Invalid board"),
+        ("GET", "/generate?hand_size=0", "", 400, "hand_size must be between 1 and 144"),
+        ("GET", "/generate?hand_size=145", "", 400, "hand_size must be between 1 and 144"),
+        ("GET", "/generate?seed=first", "", 400, "Invalid seed"),
+        ("GET", "/solve", "", 405, "Use POST"),
+        ("PUT", "/validate", "", 405, "Use POST"),
+        ("POST", "/generate", "", 405, "Use GET"),
+        ("GET", "/boards", "", 404, "No endpoint /boards")
+    ] {
+        let (status, message) = error(request(&server, method, target, body));
+        assert_eq!(status, expected_status, "{} {} {}", method, target, body);
+        assert!(message.starts_with(expected_error), "{} {} {} gave {:?}", method, target, body, message);
+    }
+    // The server still answers after every malformed request
+    assert_eq!(request(&server, "POST", "/solve", r#"{"letters": "CATOMB"}"#).1["solved"], true);
+}